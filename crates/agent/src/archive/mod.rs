@@ -0,0 +1,158 @@
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine as _;
+use noodle_core::error::{NoodleError, Result};
+use ring::aead::{Aad, LessSafeKey, Nonce, UnboundKey, AES_256_GCM, NONCE_LEN};
+use ring::pbkdf2::{self, PBKDF2_HMAC_SHA256};
+use ring::rand::{SecureRandom, SystemRandom};
+use serde::{Deserialize, Serialize};
+use std::num::NonZeroU32;
+
+/// Starts every encrypted archive, followed by the format version.
+const MAGIC: &[u8] = b"NOODLEX";
+const FORMAT_VERSION: u8 = 1;
+const SALT_LEN: usize = 16;
+const KEY_LEN: usize = 32;
+/// PBKDF2-HMAC-SHA256 rounds for new archives; stored in the header so it
+/// can be raised without breaking older files.
+const PBKDF2_ITERATIONS: u32 = 600_000;
+/// The most rounds an archive may ask for. The count comes from the file, so
+/// without a limit a crafted one could keep a core busy for hours.
+const MAX_PBKDF2_ITERATIONS: u32 = 10 * PBKDF2_ITERATIONS;
+pub const MIN_PASSWORD_CHARS: usize = 8;
+/// Appended to the names of encrypted exports.
+pub const EXTENSION: &str = "noodlex";
+
+/// One file inside an archive.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArchiveEntry {
+    pub name: String,
+    pub content: String,
+}
+
+/// Encrypts `entries` with a key derived from `password`.
+///
+/// Layout: magic, version, iterations (u32, big endian), salt, nonce, then
+/// the AES-256-GCM ciphertext of the JSON-encoded entries with its tag. The
+/// header is authenticated along with the contents.
+pub async fn seal(entries: &[ArchiveEntry], password: &str) -> Result<Vec<u8>> {
+    if password.chars().count() < MIN_PASSWORD_CHARS {
+        return Err(NoodleError::Validation(format!(
+            "Password must be at least {} characters",
+            MIN_PASSWORD_CHARS
+        )));
+    }
+    let rng = SystemRandom::new();
+    let mut salt = [0u8; SALT_LEN];
+    let mut nonce = [0u8; NONCE_LEN];
+    rng.fill(&mut salt)
+        .and_then(|_| rng.fill(&mut nonce))
+        .map_err(|_| NoodleError::Internal("No secure random source".into()))?;
+
+    let mut out = Vec::from(MAGIC);
+    out.push(FORMAT_VERSION);
+    out.extend_from_slice(&PBKDF2_ITERATIONS.to_be_bytes());
+    out.extend_from_slice(&salt);
+    out.extend_from_slice(&nonce);
+
+    let mut contents =
+        serde_json::to_vec(entries).map_err(|e| NoodleError::Internal(e.to_string()))?;
+    key(password, &salt, PBKDF2_ITERATIONS)
+        .await?
+        .seal_in_place_append_tag(
+            Nonce::assume_unique_for_key(nonce),
+            Aad::from(&out),
+            &mut contents,
+        )
+        .map_err(|_| NoodleError::Internal("Encryption failed".into()))?;
+    out.extend_from_slice(&contents);
+    Ok(out)
+}
+
+/// Decrypts an archive made by [`seal`].
+pub async fn open(data: &[u8], password: &str) -> Result<Vec<ArchiveEntry>> {
+    let header_len = MAGIC.len() + 1 + 4 + SALT_LEN + NONCE_LEN;
+    if data.len() < header_len || !data.starts_with(MAGIC) {
+        return Err(NoodleError::Validation(
+            "Not an encrypted Noodle export".into(),
+        ));
+    }
+    let (header, ciphertext) = data.split_at(header_len);
+    let version = header[MAGIC.len()];
+    if version != FORMAT_VERSION {
+        return Err(NoodleError::Validation(format!(
+            "Encrypted export is from a newer version of Noodle (format {})",
+            version
+        )));
+    }
+    let rest = &header[MAGIC.len() + 1..];
+    let (iterations, rest) = rest.split_at(4);
+    let (salt, nonce) = rest.split_at(SALT_LEN);
+    let iterations = u32::from_be_bytes(iterations.try_into().unwrap_or_default());
+    if iterations > MAX_PBKDF2_ITERATIONS {
+        return Err(NoodleError::Validation("Damaged encrypted export".into()));
+    }
+    let nonce = Nonce::try_assume_unique_for_key(nonce)
+        .map_err(|_| NoodleError::Validation("Damaged encrypted export".into()))?;
+
+    let mut contents = ciphertext.to_vec();
+    let plaintext = key(password, salt, iterations)
+        .await?
+        .open_in_place(nonce, Aad::from(header), &mut contents)
+        .map_err(|_| NoodleError::Validation("Wrong password, or the export is damaged".into()))?;
+    serde_json::from_slice(plaintext).map_err(|e| NoodleError::Validation(e.to_string()))
+}
+
+/// [`seal`] for sending over IPC, base64 encoded.
+pub async fn seal_base64(entries: &[ArchiveEntry], password: &str) -> Result<String> {
+    seal(entries, password)
+        .await
+        .map(|data| BASE64.encode(data))
+}
+
+/// Encrypts one exported file, returning the archive's file name and its
+/// base64 encoded contents.
+pub async fn seal_file(
+    file_name: &str,
+    content: String,
+    password: &str,
+) -> Result<(String, String)> {
+    let entry = ArchiveEntry {
+        name: file_name.to_string(),
+        content,
+    };
+    let sealed = seal_base64(&[entry], password).await?;
+    Ok((format!("{}.{}", file_name, EXTENSION), sealed))
+}
+
+/// [`open`] for an archive received base64 encoded.
+pub async fn open_base64(data: &str, password: &str) -> Result<Vec<ArchiveEntry>> {
+    let data = BASE64
+        .decode(data.trim())
+        .map_err(|_| NoodleError::Validation("Not an encrypted Noodle export".into()))?;
+    open(&data, password).await
+}
+
+/// Derives the key on a blocking thread; the rounds take long enough to
+/// stall the async runtime.
+async fn key(password: &str, salt: &[u8], iterations: u32) -> Result<LessSafeKey> {
+    let iterations = NonZeroU32::new(iterations)
+        .ok_or_else(|| NoodleError::Validation("Damaged encrypted export".into()))?;
+    let password = password.to_string();
+    let salt = salt.to_vec();
+    let key = tokio::task::spawn_blocking(move || {
+        let mut key = [0u8; KEY_LEN];
+        pbkdf2::derive(
+            PBKDF2_HMAC_SHA256,
+            iterations,
+            &salt,
+            password.as_bytes(),
+            &mut key,
+        );
+        key
+    })
+    .await
+    .map_err(|e| NoodleError::Internal(e.to_string()))?;
+    UnboundKey::new(&AES_256_GCM, &key)
+        .map(LessSafeKey::new)
+        .map_err(|_| NoodleError::Internal("Invalid key".into()))
+}
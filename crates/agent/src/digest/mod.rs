@@ -0,0 +1,169 @@
+pub mod roundup;
+pub mod whats_new;
+
+use crate::engine::policy::ActivityPolicy;
+use chrono::{DateTime, Utc};
+use noodle_core::error::Result;
+use noodle_core::types::ProjectSettings;
+use serde::Serialize;
+use std::collections::HashSet;
+use std::sync::Arc;
+use storage::sqlite::SqliteStorage;
+
+/// In the order items are listed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DigestReason {
+    DueToday,
+    /// Arrived while the user was out of office and still needs a reply.
+    Missed,
+    UrgentUnanswered,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DigestItem {
+    pub email_id: i64,
+    pub subject: String,
+    pub sender: String,
+    pub project: Option<String>,
+    pub summary: Option<String>,
+    pub due_by: Option<DateTime<Utc>>,
+    pub reason: DigestReason,
+    /// Sent by a VIP sender.
+    pub vip: bool,
+    /// Its project's notifications are muted, so it isn't announced.
+    pub muted: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Digest {
+    pub generated_at: DateTime<Utc>,
+    /// Inbound high-urgency emails still waiting for a reply, leaving out
+    /// those of muted or excluded projects; drives the tray badge.
+    pub urgent_unhandled: usize,
+    /// Deadlines due today come first, then unanswered urgent mail, newest
+    /// first. Within each, VIP senders' mail leads. While the user is away in
+    /// delegation mode, what they missed replaces the urgent mail.
+    pub items: Vec<DigestItem>,
+    /// When the user went out of office, in delegation mode.
+    pub away_since: Option<DateTime<Utc>>,
+    /// Inbox emails received since `away_since`.
+    pub received_while_away: i64,
+}
+
+impl Digest {
+    /// Items to announce: all but those of projects with muted notifications.
+    pub fn announced(&self) -> impl Iterator<Item = &DigestItem> {
+        self.items.iter().filter(|item| !item.muted)
+    }
+
+    /// The first `n` [`announced`](Self::announced) items.
+    pub fn top(&self, n: usize) -> Vec<&DigestItem> {
+        self.announced().take(n).collect()
+    }
+}
+
+pub struct DigestService {
+    sqlite: Arc<SqliteStorage>,
+    policy: ActivityPolicy,
+}
+
+impl DigestService {
+    pub fn new(sqlite: Arc<SqliteStorage>) -> Self {
+        Self {
+            policy: ActivityPolicy::new(sqlite.clone()),
+            sqlite,
+        }
+    }
+
+    /// Builds the digest for the user's local day containing `now`, leaving out
+    /// projects whose settings exclude them from the digest, and reminders
+    /// about mail of projects with muted notifications.
+    pub async fn build(&self, now: DateTime<Utc>) -> Result<Digest> {
+        let settings = self.sqlite.list_project_settings().await?;
+        let projects = |keep: fn(&ProjectSettings) -> bool| -> HashSet<String> {
+            settings
+                .iter()
+                .filter(|p| keep(p))
+                .map(|p| p.name.to_lowercase())
+                .collect()
+        };
+        let excluded = projects(|p| !p.include_in_digest);
+        let muted = projects(|p| p.mute_notifications);
+
+        let tz = self.sqlite.get_user_timezone().await?;
+        let (start, end) = tz.day_bounds(now);
+        let due_today = self.sqlite.get_emails_due_between(start, end).await?;
+        let urgent = self.sqlite.get_unhandled_urgent().await?;
+        let urgent_unhandled = urgent
+            .iter()
+            .filter(|email| {
+                email["client_or_project"]["name"]
+                    .as_str()
+                    .map(str::to_lowercase)
+                    .is_none_or(|p| !excluded.contains(&p) && !muted.contains(&p))
+            })
+            .count();
+        // Reminders about unanswered mail wait until the user is back.
+        let away_since = self.policy.away_since().await?;
+        let (reminders, reminder_reason, received_while_away) = match away_since {
+            Some(since) => (
+                self.sqlite.get_missed_since(since).await?,
+                DigestReason::Missed,
+                self.sqlite.count_inbox_since(since).await?,
+            ),
+            None => (urgent, DigestReason::UrgentUnanswered, 0),
+        };
+
+        let vips: HashSet<String> = self
+            .sqlite
+            .list_vip_senders()
+            .await?
+            .into_iter()
+            .map(|s| s.to_lowercase())
+            .collect();
+
+        let mut seen = HashSet::new();
+        let mut items: Vec<DigestItem> = due_today
+            .iter()
+            .map(|email| (email, DigestReason::DueToday))
+            .chain(reminders.iter().map(|email| (email, reminder_reason)))
+            .filter_map(|(email, reason)| {
+                let mut item = digest_item(email, reason);
+                item.vip = vips.contains(&item.sender.trim().to_lowercase());
+                let project = item.project.as_ref().map(|p| p.to_lowercase());
+                item.muted = project.as_ref().is_some_and(|p| muted.contains(p));
+                let included = project.is_none_or(|p| !excluded.contains(&p))
+                    && (reason == DigestReason::DueToday || !item.muted);
+                (included && seen.insert(item.email_id)).then_some(item)
+            })
+            .collect();
+        // Stable, so the order within VIP and other mail is kept.
+        items.sort_by_key(|item| (item.reason, !item.vip));
+
+        Ok(Digest {
+            generated_at: now,
+            urgent_unhandled,
+            items,
+            away_since,
+            received_while_away,
+        })
+    }
+}
+
+fn digest_item(email: &serde_json::Value, reason: DigestReason) -> DigestItem {
+    let text = |key: &str| email[key].as_str().map(str::to_string);
+    DigestItem {
+        email_id: email["id"].as_i64().unwrap_or_default(),
+        subject: text("subject").unwrap_or_default(),
+        sender: text("sender").unwrap_or_default(),
+        project: email["client_or_project"]["name"]
+            .as_str()
+            .map(str::to_string),
+        summary: text("summary").filter(|s| !s.is_empty()),
+        due_by: serde_json::from_value(email["due_by"].clone()).ok(),
+        reason,
+        vip: false,
+        muted: false,
+    }
+}
@@ -0,0 +1,178 @@
+use crate::pipeline::pacing::PacingController;
+use crate::search::summarize::{citations, complete};
+use ai::injection;
+use ai::provider::AiProvider;
+use chrono::{DateTime, Duration, Utc};
+use noodle_core::error::{NoodleError, Result};
+use noodle_core::locale::UserLocale;
+use noodle_core::types::{DateRange, NewsletterRoundup, TopicSource};
+use std::sync::Arc;
+use storage::sqlite::{EmailRow, SqliteStorage};
+use tokio::sync::RwLock;
+use tokio::task::JoinSet;
+use tracing::warn;
+
+/// How often a roundup is due, and the range the first one covers.
+pub const ROUNDUP_PERIOD_DAYS: i64 = 7;
+/// Most newsletters in one roundup; the newest are kept.
+const MAX_NEWSLETTERS: i64 = 40;
+/// Body characters sent to the model per newsletter.
+const MAX_BODY_CHARS: usize = 6000;
+
+/// Summarizes the newsletters kept out of fact extraction, so they can be
+/// skimmed in one place.
+pub struct RoundupService {
+    sqlite: Arc<SqliteStorage>,
+    ai: Arc<RwLock<Arc<dyn AiProvider>>>,
+    pacing: Arc<PacingController>,
+}
+
+impl RoundupService {
+    pub fn new(
+        sqlite: Arc<SqliteStorage>,
+        ai: Arc<RwLock<Arc<dyn AiProvider>>>,
+        pacing: Arc<PacingController>,
+    ) -> Self {
+        Self { sqlite, ai, pacing }
+    }
+
+    /// When the next roundup is due: a period after the last one ended,
+    /// even if it failed. `None` before the first, which is due right away.
+    pub async fn next_due(&self) -> Result<Option<DateTime<Utc>>> {
+        Ok(self
+            .sqlite
+            .last_newsletter_roundup_end(false)
+            .await?
+            .map(|end| end + Duration::days(ROUNDUP_PERIOD_DAYS)))
+    }
+
+    /// Builds and stores the roundup of newsletters received since the last
+    /// completed one, or over the last period for the first one. Returns
+    /// `None` when no newsletters arrived.
+    pub async fn run(&self, now: DateTime<Utc>) -> Result<Option<NewsletterRoundup>> {
+        let start = self
+            .sqlite
+            .last_newsletter_roundup_end(true)
+            .await?
+            .unwrap_or(now - Duration::days(ROUNDUP_PERIOD_DAYS));
+        let newsletters = self
+            .sqlite
+            .list_newsletters_between(start, now, MAX_NEWSLETTERS)
+            .await?;
+        if newsletters.is_empty() {
+            return Ok(None);
+        }
+
+        let locale = self.sqlite.get_user_locale().await?;
+        let mut sources = self.map(newsletters).await?;
+        let mut roundup = NewsletterRoundup {
+            id: 0,
+            date_range: DateRange {
+                start: Some(start),
+                end: Some(now),
+            },
+            run_at: Utc::now(),
+            status: "completed".into(),
+            output_text: None,
+            sources: Vec::new(),
+            error_text: None,
+        };
+        let ai = self.ai.read().await.clone();
+        let prompt = roundup_prompt(&sources, locale);
+        match self
+            .pacing
+            .llm_call("newsletter_roundup", complete(ai.as_ref(), prompt))
+            .await
+        {
+            Ok(text) => {
+                let cited = citations(&text);
+                for source in &mut sources {
+                    source.cited = cited.contains(&source.email_id);
+                }
+                roundup.output_text = Some(text);
+            }
+            Err(e) => {
+                roundup.status = "failed".into();
+                roundup.error_text = Some(e.to_string());
+            }
+        }
+        roundup.sources = sources;
+        roundup.id = self.sqlite.save_newsletter_roundup(&roundup).await?;
+        Ok(Some(roundup))
+    }
+
+    /// Summarizes each newsletter in a sentence or two, the calls paced with
+    /// the pipeline's. When the model fails on one, its subject stands in.
+    async fn map(&self, newsletters: Vec<EmailRow>) -> Result<Vec<TopicSource>> {
+        let ai = self.ai.read().await.clone();
+        let mut tasks = JoinSet::new();
+        for newsletter in newsletters {
+            let ai = ai.clone();
+            let pacing = self.pacing.clone();
+            tasks.spawn(async move {
+                let call = complete(ai.as_ref(), map_prompt(&newsletter));
+                let summary = match pacing.llm_call("newsletter_roundup", call).await {
+                    Ok(summary) if !summary.is_empty() => summary,
+                    Ok(_) => newsletter.subject.clone(),
+                    Err(e) => {
+                        warn!("Roundup fell back for newsletter {}: {}", newsletter.id, e);
+                        newsletter.subject.clone()
+                    }
+                };
+                TopicSource {
+                    email_id: newsletter.id,
+                    subject: newsletter.subject,
+                    sender: newsletter.sender,
+                    received_at: newsletter.received_at,
+                    summary,
+                    cited: false,
+                }
+            });
+        }
+
+        let mut sources = Vec::new();
+        while let Some(joined) = tasks.join_next().await {
+            sources.push(joined.map_err(|e| NoodleError::Internal(e.to_string()))?);
+        }
+        sources.sort_by_key(|s| s.received_at);
+        Ok(sources)
+    }
+}
+
+fn map_prompt(newsletter: &EmailRow) -> String {
+    let body: String = newsletter.body_text.chars().take(MAX_BODY_CHARS).collect();
+    format!(
+        "{}\n\n\
+         In one or two sentences, state the news in this newsletter worth \
+         knowing about. Leave out promotions and boilerplate. {}",
+        injection::data_block(&format!(
+            "Subject: {}\nFrom: {}\n\n{}",
+            newsletter.subject, newsletter.sender, body
+        )),
+        injection::DATA_INSTRUCTION
+    )
+}
+
+fn roundup_prompt(sources: &[TopicSource], locale: UserLocale) -> String {
+    let notes: Vec<String> = sources
+        .iter()
+        .map(|s| {
+            format!(
+                "[#{}] {} from {}: {}",
+                s.email_id,
+                locale.format_date(&s.received_at),
+                s.sender,
+                s.summary
+            )
+        })
+        .collect();
+    format!(
+        "Notes on this week's newsletters, each tagged with its email id:\n{}\n\n\
+         Write a short roundup of these newsletters for a busy reader, grouping \
+         related news under a few headings and putting the most useful first. \
+         Cite the newsletters behind every point with their tags, e.g. [#12]. \
+         Do not cite ids that are not in the notes. {}",
+        notes.join("\n"),
+        locale.prompt_instruction()
+    )
+}
@@ -0,0 +1,51 @@
+use super::DigestService;
+use chrono::{DateTime, Utc};
+use noodle_core::error::Result;
+use serde::Serialize;
+
+/// What happened while the user was away from the app, for the "welcome
+/// back" panel. Each list holds emails in the shape of the search results.
+#[derive(Debug, Clone, Serialize)]
+pub struct WhatsNew {
+    pub since: DateTime<Utc>,
+    pub urgent: Vec<serde_json::Value>,
+    pub blockers: Vec<serde_json::Value>,
+    /// Emails still needing a reply whose deadline passed since `since`.
+    pub overdue: Vec<serde_json::Value>,
+    /// Answers to questions the user asked or threads waiting on others.
+    pub replies: Vec<serde_json::Value>,
+}
+
+impl WhatsNew {
+    pub fn is_empty(&self) -> bool {
+        self.urgent.is_empty()
+            && self.blockers.is_empty()
+            && self.overdue.is_empty()
+            && self.replies.is_empty()
+    }
+
+    /// Every email listed, to localize and redact them in place.
+    pub fn emails_mut(&mut self) -> impl Iterator<Item = &mut serde_json::Value> {
+        self.urgent
+            .iter_mut()
+            .chain(&mut self.blockers)
+            .chain(&mut self.overdue)
+            .chain(&mut self.replies)
+    }
+}
+
+impl DigestService {
+    /// What changed between `since` and `now`.
+    pub async fn whats_new(&self, since: DateTime<Utc>, now: DateTime<Utc>) -> Result<WhatsNew> {
+        Ok(WhatsNew {
+            since,
+            urgent: self.sqlite.get_urgent_since(since).await?,
+            blockers: self.sqlite.get_blockers_since(since).await?,
+            overdue: self.sqlite.get_overdue_between(since, now).await?,
+            replies: self
+                .sqlite
+                .get_replies_to_open_questions_since(since)
+                .await?,
+        })
+    }
+}
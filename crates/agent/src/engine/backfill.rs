@@ -0,0 +1,68 @@
+use chrono::{DateTime, Utc};
+use noodle_core::types::ScanCheckpoint;
+
+/// Where a folder's initial scan stands. The checkpoint a resume starts
+/// from never passes an email that failed, and the delta checkpoint the
+/// scan leaves behind lets delta scans retry what it missed.
+#[derive(Debug, Clone)]
+pub struct BackfillProgress {
+    pub checkpoint: ScanCheckpoint,
+    /// When the folder was listed.
+    pub fetched_at: DateTime<Utc>,
+    /// The oldest email that failed to be processed.
+    failed_at: Option<DateTime<Utc>>,
+    /// An email could not be fetched from Outlook, so neither it nor the
+    /// rest of its page made it into the scan.
+    fetch_failed: bool,
+}
+
+impl BackfillProgress {
+    pub fn new(checkpoint: ScanCheckpoint, fetched_at: DateTime<Utc>) -> Self {
+        Self {
+            checkpoint,
+            fetched_at,
+            failed_at: None,
+            fetch_failed: false,
+        }
+    }
+
+    /// Counts an email taken in scan order, moving the checkpoint to it
+    /// unless it or an earlier one failed.
+    pub fn processed(&mut self, received_at: DateTime<Utc>, ok: bool) {
+        self.processed_out_of_order(received_at, ok);
+        if self.failed_at.is_none() && !self.fetch_failed {
+            self.checkpoint.last_received_at = received_at;
+        }
+    }
+
+    /// Counts an email taken out of order, such as VIP mail, which doesn't
+    /// move the checkpoint.
+    pub fn processed_out_of_order(&mut self, received_at: DateTime<Utc>, ok: bool) {
+        if !ok {
+            self.failed_at = Some(self.failed_at.map_or(received_at, |at| at.min(received_at)));
+        }
+        self.checkpoint.processed += 1;
+    }
+
+    /// Counts an email Outlook failed to return as done, so the scan still
+    /// completes.
+    pub fn fetch_failed(&mut self) {
+        self.fetch_failed = true;
+        self.checkpoint.processed += 1;
+    }
+
+    /// Where the folder's delta checkpoint moves once the scan is done: the
+    /// listing time, or the oldest failed email so delta scans retry it.
+    /// `None` after a failed fetch, as what was missed is unknown; delta
+    /// scans then go on from the checkpoint they had.
+    pub fn delta_checkpoint(&self) -> Option<DateTime<Utc>> {
+        if self.fetch_failed {
+            return None;
+        }
+        Some(
+            self.failed_at
+                .unwrap_or(self.fetched_at)
+                .min(self.fetched_at),
+        )
+    }
+}
@@ -0,0 +1,24 @@
+use std::time::Duration;
+use windows::Win32::System::SystemInformation::GetTickCount;
+use windows::Win32::UI::Input::KeyboardAndMouse::{GetLastInputInfo, LASTINPUTINFO};
+
+/// `app_config` keys of idle-time extraction.
+pub const IDLE_EXTRACTION_KEY: &str = "idle_extraction";
+pub const IDLE_THRESHOLD_MINS_KEY: &str = "idle_threshold_mins";
+
+pub const DEFAULT_IDLE_THRESHOLD_MINS: u64 = 10;
+
+/// Time since the user last touched the keyboard or mouse, via
+/// `GetLastInputInfo`; `None` if it could not be read.
+pub fn user_idle_time() -> Option<Duration> {
+    let mut info = LASTINPUTINFO {
+        cbSize: std::mem::size_of::<LASTINPUTINFO>() as u32,
+        dwTime: 0,
+    };
+    if !unsafe { GetLastInputInfo(&mut info) }.as_bool() {
+        return None;
+    }
+    // Both tick counts wrap after ~49.7 days; the wrapping difference is still right.
+    let idle_ms = unsafe { GetTickCount() }.wrapping_sub(info.dwTime);
+    Some(Duration::from_millis(idle_ms.into()))
+}
@@ -0,0 +1,387 @@
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine as _;
+use chrono::{DateTime, Utc};
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use noodle_core::error::{NoodleError, Result};
+use noodle_core::types::{Email, HoldVerification};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use storage::sqlite::SqliteStorage;
+use tokio::sync::Mutex;
+use tracing::{info, warn};
+
+/// `app_config` key turning the archive on.
+pub const LEGAL_HOLD_KEY: &str = "legal_hold";
+/// `app_config` key holding the sequence number and hash of the newest
+/// record, so a truncated archive is noticed.
+const HEAD_KEY: &str = "legal_hold_head";
+/// Records per segment file; full segments are made read-only.
+const SEGMENT_RECORDS: u64 = 10_000;
+const SEGMENT_PREFIX: &str = "segment-";
+const SEGMENT_EXTENSION: &str = "jsonl";
+/// `prev` of the first record.
+const GENESIS: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+
+/// One archived email, written as a line of JSON.
+#[derive(Serialize, Deserialize)]
+struct Record {
+    seq: u64,
+    archived_at: DateTime<Utc>,
+    email_id: i64,
+    entry_id: String,
+    /// Hash of the previous record.
+    prev: String,
+    hash: String,
+    /// The email with its headers and body as gzipped JSON, in base64.
+    data: String,
+}
+
+impl Record {
+    fn compute_hash(&self) -> String {
+        let mut hasher = Sha256::new();
+        hasher.update(
+            format!(
+                "{}\n{}\n{}\n{}\n{}\n",
+                self.seq,
+                self.prev,
+                self.archived_at.to_rfc3339(),
+                self.email_id,
+                self.entry_id
+            )
+            .as_bytes(),
+        );
+        hasher.update(self.data.as_bytes());
+        format!("{:x}", hasher.finalize())
+    }
+}
+
+/// The newest record, which the next one chains to.
+#[derive(Clone)]
+struct Head {
+    seq: u64,
+    hash: String,
+}
+
+/// Tamper-evident copy of every ingested email, for users under a legal
+/// hold or retention duty. Records are appended to numbered segment files,
+/// each carrying the hash of the one before it, so editing, removing or
+/// reordering any record breaks the chain from there on. Nothing is ever
+/// rewritten or deleted, even when the email is purged from the database.
+pub struct LegalHold {
+    sqlite: Arc<SqliteStorage>,
+    dir: PathBuf,
+    /// Loaded from the last segment on the first append.
+    head: Mutex<Option<Head>>,
+}
+
+impl LegalHold {
+    pub fn new(sqlite: Arc<SqliteStorage>, dir: PathBuf) -> Self {
+        Self {
+            sqlite,
+            dir,
+            head: Mutex::new(None),
+        }
+    }
+
+    pub async fn enabled(&self) -> Result<bool> {
+        Ok(self
+            .sqlite
+            .get_config(LEGAL_HOLD_KEY)
+            .await?
+            .is_some_and(|v| v == "true"))
+    }
+
+    /// Appends `email` when the archive is turned on.
+    pub async fn archive(&self, email: &Email) -> Result<()> {
+        if !self.enabled().await? {
+            return Ok(());
+        }
+        let mut head = self.head.lock().await;
+        let previous = head.clone();
+        let json = serde_json::to_vec(email).map_err(|e| NoodleError::Internal(e.to_string()))?;
+        let (email_id, entry_id) = (email.id, email.entry_id.clone());
+        let dir = self.dir.clone();
+        // Compressing and syncing the file would otherwise stall the runtime.
+        let appended = tokio::task::spawn_blocking(move || {
+            let previous = match previous {
+                Some(head) => head,
+                None => load_head(&dir)?,
+            };
+            append(&dir, previous, email_id, entry_id, &json)
+        })
+        .await
+        .map_err(|e| NoodleError::Internal(e.to_string()))?;
+        let new_head = match appended {
+            Ok(new_head) => new_head,
+            Err(e) => {
+                // The write may have stopped partway; reload (and repair)
+                // the head from disk next time.
+                *head = None;
+                return Err(e);
+            }
+        };
+
+        self.sqlite
+            .set_config(HEAD_KEY, &format!("{}:{}", new_head.seq, new_head.hash))
+            .await?;
+        *head = Some(new_head);
+        Ok(())
+    }
+
+    /// Re-reads every record, checking that sequence numbers are gapless,
+    /// each hash matches its record and chains to the one before, every
+    /// email still decodes, and the newest record is the one last written.
+    /// Partly written lines left by a crash mid-append are counted and
+    /// skipped; the records around them must still chain.
+    pub async fn verify(&self) -> Result<HoldVerification> {
+        // Appends wait, so the head can't move while it is compared.
+        let _head = self.head.lock().await;
+        let anchor = self.sqlite.get_config(HEAD_KEY).await?.unwrap_or_default();
+        let dir = self.dir.clone();
+        let (mut result, prev) = tokio::task::spawn_blocking(move || check_chain(&dir))
+            .await
+            .map_err(|e| NoodleError::Internal(e.to_string()))??;
+        if !result.intact {
+            return Ok(result);
+        }
+
+        let expected = if result.records == 0 {
+            String::new()
+        } else {
+            format!("{}:{}", result.records, prev)
+        };
+        if anchor != expected {
+            let next = result.records + 1;
+            mark_broken(
+                &mut result,
+                next,
+                "Records are missing from the end of the archive".to_string(),
+            );
+        }
+        info!(
+            "Verified {} legal hold records in {} segments ({} torn): {}",
+            result.records,
+            result.segments,
+            result.torn,
+            if result.intact { "intact" } else { "broken" }
+        );
+        Ok(result)
+    }
+}
+
+/// Writes the record after `previous` and returns it as the new head.
+fn append(
+    dir: &Path,
+    previous: Head,
+    email_id: i64,
+    entry_id: String,
+    json: &[u8],
+) -> Result<Head> {
+    let mut record = Record {
+        seq: previous.seq + 1,
+        archived_at: Utc::now(),
+        email_id,
+        entry_id,
+        prev: previous.hash,
+        hash: String::new(),
+        data: BASE64.encode(gzip(json)?),
+    };
+    record.hash = record.compute_hash();
+
+    let segment = segment_number(record.seq);
+    if record.seq > 1 && segment_number(record.seq - 1) != segment {
+        seal_segment(&segment_path(dir, segment - 1));
+    }
+    std::fs::create_dir_all(dir).map_err(|e| NoodleError::Storage(e.to_string()))?;
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(segment_path(dir, segment))
+        .map_err(|e| NoodleError::Storage(e.to_string()))?;
+    let mut line =
+        serde_json::to_string(&record).map_err(|e| NoodleError::Internal(e.to_string()))?;
+    line.push('\n');
+    file.write_all(line.as_bytes())
+        .and_then(|_| file.sync_data())
+        .map_err(|e| NoodleError::Storage(format!("Failed to archive email: {}", e)))?;
+
+    Ok(Head {
+        seq: record.seq,
+        hash: record.hash,
+    })
+}
+
+/// Walks every segment in order. Returns the result and the hash of the
+/// last intact record.
+fn check_chain(dir: &Path) -> Result<(HoldVerification, String)> {
+    let segments = segments(dir)?;
+    let mut result = HoldVerification {
+        records: 0,
+        segments: segments.len(),
+        intact: true,
+        first_broken: None,
+        problem: None,
+        torn: 0,
+    };
+    let mut prev = GENESIS.to_string();
+
+    for path in &segments {
+        let file = File::open(path).map_err(|e| NoodleError::Storage(e.to_string()))?;
+        for line in BufReader::new(file).lines() {
+            let seq = result.records + 1;
+            let line = line.map_err(|e| NoodleError::Storage(e.to_string()))?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            // A removed or mangled record still breaks the chain at the next
+            // one, or at the anchor when it was the newest.
+            let Ok(record) = serde_json::from_str::<Record>(&line) else {
+                warn!(
+                    "Skipping a partly written legal hold record in {}",
+                    path.display()
+                );
+                result.torn += 1;
+                continue;
+            };
+            let problem = if record.seq != seq {
+                Some(format!("Expected record {}, found {}", seq, record.seq))
+            } else if record.prev != prev {
+                Some("Does not chain to the previous record".to_string())
+            } else if record.hash != record.compute_hash() {
+                Some("Contents do not match the record's hash".to_string())
+            } else {
+                decode(&record.data).err().map(|e| e.to_string())
+            };
+            if let Some(problem) = problem {
+                mark_broken(&mut result, seq, problem);
+                return Ok((result, prev));
+            }
+            prev = record.hash;
+            result.records = seq;
+        }
+    }
+    Ok((result, prev))
+}
+
+fn segment_path(dir: &Path, number: u64) -> PathBuf {
+    dir.join(format!(
+        "{}{:06}.{}",
+        SEGMENT_PREFIX, number, SEGMENT_EXTENSION
+    ))
+}
+
+/// Segment files in order.
+fn segments(dir: &Path) -> Result<Vec<PathBuf>> {
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(NoodleError::Storage(e.to_string())),
+    };
+    let mut segments: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.extension().and_then(|e| e.to_str()) == Some(SEGMENT_EXTENSION)
+                && path
+                    .file_name()
+                    .and_then(|n| n.to_str())
+                    .is_some_and(|n| n.starts_with(SEGMENT_PREFIX))
+        })
+        .collect();
+    segments.sort();
+    Ok(segments)
+}
+
+/// The newest intact record, or the genesis when there is none. A crash
+/// mid-append can leave a partly written last line; it is ended with a
+/// newline so the next record starts on a line of its own, and the chain
+/// continues from the record before it.
+fn load_head(dir: &Path) -> Result<Head> {
+    let segments = segments(dir)?;
+    if let Some(newest) = segments.last() {
+        end_torn_line(newest)?;
+    }
+    for path in segments.iter().rev() {
+        let file = File::open(path).map_err(|e| NoodleError::Storage(e.to_string()))?;
+        let record = BufReader::new(file)
+            .lines()
+            .map_while(|line| line.ok())
+            .filter_map(|line| serde_json::from_str::<Record>(&line).ok())
+            .last();
+        if let Some(record) = record {
+            return Ok(Head {
+                seq: record.seq,
+                hash: record.hash,
+            });
+        }
+    }
+    Ok(Head {
+        seq: 0,
+        hash: GENESIS.to_string(),
+    })
+}
+
+fn end_torn_line(path: &Path) -> Result<()> {
+    let contents = std::fs::read(path).map_err(|e| NoodleError::Storage(e.to_string()))?;
+    if contents.is_empty() || contents.ends_with(b"\n") {
+        return Ok(());
+    }
+    warn!(
+        "Legal hold segment {} ends in a partly written record; continuing after it",
+        path.display()
+    );
+    OpenOptions::new()
+        .append(true)
+        .open(path)
+        .and_then(|mut file| file.write_all(b"\n").and_then(|_| file.sync_data()))
+        .map_err(|e| NoodleError::Storage(format!("Failed to repair the archive: {}", e)))
+}
+
+fn mark_broken(result: &mut HoldVerification, seq: u64, problem: String) {
+    result.intact = false;
+    result.first_broken = Some(seq);
+    result.problem = Some(problem);
+}
+
+fn segment_number(seq: u64) -> u64 {
+    (seq - 1) / SEGMENT_RECORDS + 1
+}
+
+/// Makes a full segment read-only, as a guard against accidental edits.
+fn seal_segment(path: &Path) {
+    let result = std::fs::metadata(path).and_then(|metadata| {
+        let mut permissions = metadata.permissions();
+        permissions.set_readonly(true);
+        std::fs::set_permissions(path, permissions)
+    });
+    if let Err(e) = result {
+        warn!("Failed to make {} read-only: {}", path.display(), e);
+    }
+}
+
+fn gzip(data: &[u8]) -> Result<Vec<u8>> {
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder
+        .write_all(data)
+        .and_then(|_| encoder.finish())
+        .map_err(|e| NoodleError::Internal(e.to_string()))
+}
+
+/// The archived email of a record.
+fn decode(data: &str) -> Result<Email> {
+    let compressed = BASE64
+        .decode(data)
+        .map_err(|e| NoodleError::Validation(format!("Damaged email data: {}", e)))?;
+    let mut json = Vec::new();
+    GzDecoder::new(compressed.as_slice())
+        .read_to_end(&mut json)
+        .map_err(|e| NoodleError::Validation(format!("Damaged email data: {}", e)))?;
+    serde_json::from_slice(&json)
+        .map_err(|e| NoodleError::Validation(format!("Damaged email data: {}", e)))
+}
@@ -0,0 +1,127 @@
+use super::shutdown::ShutdownCoordinator;
+use super::snapshots::VectorSnapshots;
+use crate::graph::OrgInference;
+use crate::pipeline::attachments::AttachmentStore;
+use crate::pipeline::ExtractionPipeline;
+use chrono::{DateTime, Duration as ChronoDuration, Timelike, Utc};
+use noodle_core::error::Result;
+use std::sync::Arc;
+use std::time::Duration;
+use storage::sqlite::SqliteStorage;
+use tracing::{error, info};
+
+/// `app_config` keys read by [`MaintenanceScheduler`].
+pub const MAINTENANCE_HOUR_KEY: &str = "maintenance_hour";
+pub const MAINTENANCE_VACUUM_KEY: &str = "maintenance_vacuum";
+
+const DEFAULT_MAINTENANCE_HOUR: u32 = 3;
+const CHECK_INTERVAL: Duration = Duration::from_secs(15 * 60);
+/// VACUUM rewrites the whole file, so even when enabled it runs at most weekly.
+const VACUUM_INTERVAL_DAYS: i64 = 7;
+
+/// Runs SQLite maintenance once a day during the idle hour configured by
+/// `maintenance_hour` (local time, default 03:00): checkpoints the WAL and
+/// refreshes planner statistics, plus a weekly VACUUM when
+/// `maintenance_vacuum` is enabled. Also recomputes the relations the entity
+/// graph infers from mail patterns, purges emails that have been in the
+/// trash for the retention period or are older than their project's
+/// retention, snapshots the vector store when
+/// `vector_snapshots` is enabled, and removes attachment files no email lists
+/// any more.
+pub struct MaintenanceScheduler {
+    sqlite: Arc<SqliteStorage>,
+    snapshots: Arc<VectorSnapshots>,
+    pipeline: Arc<ExtractionPipeline>,
+    attachments: Arc<AttachmentStore>,
+    shutdown: Arc<ShutdownCoordinator>,
+}
+
+impl MaintenanceScheduler {
+    pub fn new(
+        sqlite: Arc<SqliteStorage>,
+        snapshots: Arc<VectorSnapshots>,
+        pipeline: Arc<ExtractionPipeline>,
+        attachments: Arc<AttachmentStore>,
+        shutdown: Arc<ShutdownCoordinator>,
+    ) -> Self {
+        Self {
+            sqlite,
+            snapshots,
+            pipeline,
+            attachments,
+            shutdown,
+        }
+    }
+
+    pub async fn run(self) {
+        loop {
+            if let Err(e) = self.tick(Utc::now()).await {
+                error!("Scheduled maintenance failed: {}", e);
+            }
+            tokio::select! {
+                _ = tokio::time::sleep(CHECK_INTERVAL) => {}
+                _ = self.shutdown.cancelled() => return,
+            }
+        }
+    }
+
+    async fn tick(&self, now: DateTime<Utc>) -> Result<()> {
+        let tz = self.sqlite.get_user_timezone().await?;
+        let local = tz.to_local(now);
+        let hour = self
+            .sqlite
+            .get_config(MAINTENANCE_HOUR_KEY)
+            .await?
+            .and_then(|s| s.parse::<u32>().ok())
+            .filter(|h| *h < 24)
+            .unwrap_or(DEFAULT_MAINTENANCE_HOUR);
+        if local.hour() != hour {
+            return Ok(());
+        }
+
+        let ran_today = self
+            .sqlite
+            .get_last_maintenance()
+            .await?
+            .is_some_and(|last| tz.local_date(last.started_at) == local.date_naive());
+        if ran_today {
+            return Ok(());
+        }
+
+        let Some(_work) = self.shutdown.begin_work() else {
+            return Ok(());
+        };
+        let vacuum = self.vacuum_due(now).await?;
+        info!("Running scheduled SQLite maintenance (vacuum: {})", vacuum);
+        self.sqlite.run_maintenance(vacuum).await?;
+        OrgInference::new(self.sqlite.clone()).run().await?;
+        // Before garbage collection, which then removes the files of
+        // attachments only purged emails listed.
+        self.pipeline.purge_expired_trash(now).await?;
+        self.pipeline.purge_expired_project_mail(now).await?;
+        self.attachments.collect_garbage().await?;
+        if self.sqlite.get_all_config().await?.vector_snapshots {
+            // Qdrant may be down while SQLite maintenance succeeded.
+            if let Err(e) = self.snapshots.create().await {
+                error!("Scheduled vector snapshot failed: {}", e);
+            }
+        }
+        Ok(())
+    }
+
+    async fn vacuum_due(&self, now: DateTime<Utc>) -> Result<bool> {
+        let enabled = self
+            .sqlite
+            .get_config(MAINTENANCE_VACUUM_KEY)
+            .await?
+            .is_some_and(|v| v == "true");
+        if !enabled {
+            return Ok(false);
+        }
+        Ok(self
+            .sqlite
+            .get_last_vacuum()
+            .await?
+            .is_none_or(|last| now - last >= ChronoDuration::days(VACUUM_INTERVAL_DAYS)))
+    }
+}
@@ -1,19 +1,68 @@
+pub mod backfill;
+pub mod idle;
+pub mod legal_hold;
+pub mod maintenance;
+pub mod payload_sync;
+pub mod policy;
+pub mod power;
+pub mod shutdown;
+pub mod snapshots;
+
+use crate::pipeline::folders::{self, FOLDER_MODES_KEY};
+use crate::pipeline::observer::EventObserver;
 use crate::pipeline::ExtractionPipeline;
+use backfill::BackfillProgress;
+use chrono::{DateTime, Utc};
 use noodle_core::error::Result;
-use outlook::client::OutlookClient;
-use std::sync::Arc;
+use noodle_core::events::{AppEvent, LogEntry, LogLevel, Notifier, ScanProgress};
+use noodle_core::types::{QueuePriority, ScanCheckpoint, SyncState, SyncStatus};
+use outlook::client::{EmailStream, MailFolder, OutlookClient};
+use policy::ActivityPolicy;
+use shutdown::ShutdownCoordinator;
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
 use storage::sqlite::SqliteStorage;
-use tokio::time::{interval, Duration};
+use tokio::sync::Notify;
+use tokio::time::{sleep, Duration, Instant};
+use tokio_util::sync::CancellationToken;
 use tracing::{error, info};
 
+/// How often a paused sync re-checks whether it may resume.
+const PAUSE_POLL_SECS: u64 = 60;
+/// `app_config` key holding the end of the last completed scan (RFC 3339).
+pub const LAST_SYNC_KEY: &str = "last_sync_at";
+/// Delta scans re-read this much before the folder checkpoint, for mail that
+/// reaches the store after its received time (e.g. cached mode catching up).
+const DELTA_OVERLAP_MINS: i64 = 10;
+/// How far back a delta scan looks when a folder has no checkpoint yet.
+const DELTA_FALLBACK_DAYS: i64 = 1;
+/// How far ahead the calendar is read for meetings to hold LLM calls in;
+/// it is read again every scan.
+const BUSY_LOOKAHEAD_HOURS: i64 = 12;
+/// Outlook default folders scanned, by `OlDefaultFolders` id. Others are
+/// scanned when `folder_modes` lists them.
+const FOLDERS: [(i32, &str); 2] = [(6, "Inbox"), (5, "Sent Items")];
+
 pub struct SyncManager {
     pipeline: Arc<ExtractionPipeline>,
     outlook: Arc<OutlookClient>,
-    #[allow(dead_code)]
     sqlite: Arc<SqliteStorage>,
-    app_handle: tauri::AppHandle,
+    policy: ActivityPolicy,
+    /// Cancelled by [`Self::stop`] or app shutdown; stops Outlook fetches
+    /// too.
+    cancel: CancellationToken,
+    notifier: Arc<dyn Notifier>,
     history_days: i64,
     sync_interval_mins: i64,
+    /// Cuts the current wait short; see [`Self::sync_now`].
+    wake: Notify,
+    /// Set by [`Self::sync_now`] for the initial scan, which polls it
+    /// between emails instead of waiting.
+    delta_requested: AtomicBool,
+    status: Mutex<SyncStatus>,
+    /// Cancelled once the sync loop has returned.
+    finished: CancellationToken,
 }
 
 impl SyncManager {
@@ -21,29 +70,94 @@ impl SyncManager {
         pipeline: Arc<ExtractionPipeline>,
         outlook: Arc<OutlookClient>,
         sqlite: Arc<SqliteStorage>,
-        app_handle: tauri::AppHandle,
+        shutdown: Arc<ShutdownCoordinator>,
+        notifier: Arc<dyn Notifier>,
         history_days: i64,
         sync_interval_mins: i64,
     ) -> Self {
+        pipeline.set_observer(Arc::new(EventObserver::new(notifier.clone())));
         Self {
             pipeline,
             outlook,
+            policy: ActivityPolicy::new(sqlite.clone()),
             sqlite,
-            app_handle,
+            cancel: shutdown.child_token(),
+            notifier,
             history_days,
             sync_interval_mins,
+            wake: Notify::new(),
+            delta_requested: AtomicBool::new(false),
+            finished: CancellationToken::new(),
+            status: Mutex::new(SyncStatus {
+                state: SyncState::Idle,
+                pause_reason: None,
+                last_sync_at: None,
+            }),
         }
     }
 
-    fn log_to_ui(&self, message: &str, level: &str) {
-        use tauri::Emitter;
-        let _ = self.app_handle.emit(
-            "noodle://log",
-            serde_json::json!({
-                "message": message,
-                "level": level
-            }),
-        );
+    /// Runs a delta scan now instead of waiting for the next interval, and
+    /// re-checks a pause right away.
+    pub fn sync_now(&self) {
+        self.delta_requested.store(true, Ordering::Relaxed);
+        self.wake.notify_one();
+    }
+
+    /// Stops syncing after the email in progress. Outlook work stops between
+    /// items; an interrupted initial scan resumes from its checkpoint when
+    /// sync is started again.
+    pub fn stop(&self) {
+        self.cancel.cancel();
+    }
+
+    /// Resolves once the sync loop has returned, e.g. after [`Self::stop`].
+    pub async fn finished(&self) {
+        self.finished.cancelled().await
+    }
+
+    fn stopped(&self) -> bool {
+        self.cancel.is_cancelled()
+    }
+
+    pub fn status(&self) -> SyncStatus {
+        self.status
+            .lock()
+            .map(|status| status.clone())
+            .unwrap_or_else(|e| e.into_inner().clone())
+    }
+
+    /// Updates the status and emits [`AppEvent::SyncState`] with it.
+    fn set_state(&self, state: SyncState, pause_reason: Option<String>) {
+        let status = {
+            let mut status = self.status.lock().unwrap_or_else(|e| e.into_inner());
+            status.state = state;
+            status.pause_reason = pause_reason;
+            status.clone()
+        };
+        self.notifier.notify(AppEvent::SyncState(status));
+    }
+
+    async fn record_sync(&self) {
+        let now = Utc::now();
+        if let Err(e) = self
+            .sqlite
+            .set_config(LAST_SYNC_KEY, &now.to_rfc3339())
+            .await
+        {
+            error!("Failed to record last sync time: {}", e);
+        }
+        self.status
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .last_sync_at = Some(now);
+        self.set_state(SyncState::Idle, None);
+    }
+
+    fn log_to_ui(&self, message: &str, level: LogLevel) {
+        self.notifier.notify(AppEvent::Log(LogEntry {
+            message: message.to_string(),
+            level,
+        }));
 
         // Also persist to DB
         let sqlite = self.sqlite.clone();
@@ -55,44 +169,245 @@ impl SyncManager {
     }
 
     pub async fn start_background_sync(self: Arc<Self>) {
+        self.run().await;
+        self.finished.cancel();
+    }
+
+    async fn run(&self) {
         info!("Starting background sync manager");
-        self.log_to_ui("Sync manager started", "info");
+        self.log_to_ui("Sync manager started", LogLevel::Info);
+        let last_sync_at = match self.sqlite.get_config(LAST_SYNC_KEY).await {
+            Ok(value) => value
+                .and_then(|s| DateTime::parse_from_rfc3339(&s).ok())
+                .map(|dt| dt.with_timezone(&Utc)),
+            Err(e) => {
+                error!("Failed to read last sync time: {}", e);
+                None
+            }
+        };
+        self.status
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .last_sync_at = last_sync_at;
 
         // 1. Initial Scan (Last 30 days)
-        if let Err(e) = self.run_initial_scan().await {
-            error!("Initial scan failed: {}", e);
+        if !self.wait_until_sync_allowed().await {
+            return;
+        }
+        self.check_out_of_office().await;
+        self.check_calendar().await;
+        self.set_state(SyncState::Syncing, None);
+        let result = self.run_initial_scan().await;
+        self.pipeline.queue().clear_pending();
+        if self.stopped() {
+            self.set_state(SyncState::Idle, None);
+            info!("Sync manager stopped during the initial scan");
+            return;
+        }
+        match result {
+            Ok(()) => self.record_sync().await,
+            Err(e) => {
+                error!("Initial scan failed: {}", e);
+                self.set_state(SyncState::Idle, None);
+            }
         }
 
         // 2. Periodic Delta Scan
-        let mut interval = interval(Duration::from_secs(self.sync_interval_mins as u64 * 60));
         loop {
-            interval.tick().await;
+            let wait = self.delta_interval().await;
+            if !self.sleep_unless_stopped(wait).await || !self.wait_until_sync_allowed().await {
+                break;
+            }
+            self.delta_requested.store(false, Ordering::Relaxed);
+            self.check_out_of_office().await;
+            self.check_calendar().await;
+            if let Err(e) = self.pipeline.replay_vector_outbox().await {
+                error!("Vector outbox replay failed: {}", e);
+            }
             info!("Running periodic delta scan...");
-            if let Err(e) = self.run_delta_scan().await {
-                error!("Delta scan failed: {}", e);
+            self.set_state(SyncState::Syncing, None);
+            let result = self.run_delta_scan().await;
+            self.pipeline.queue().clear_pending();
+            match result {
+                Ok(()) => self.record_sync().await,
+                Err(e) => {
+                    error!("Delta scan failed: {}", e);
+                    self.set_state(SyncState::Idle, None);
+                }
+            }
+            if let Err(e) = self.pipeline.run_deferred_extractions().await {
+                error!("Deferred extraction failed: {}", e);
             }
         }
+        self.set_state(SyncState::Idle, None);
+        info!("Sync manager stopped");
     }
 
+    /// Follows the user's out-of-office status in Outlook.
+    async fn check_out_of_office(&self) {
+        let result = match self.outlook.out_of_office().await {
+            Ok(out) => self
+                .policy
+                .set_out_of_office(out, Utc::now())
+                .await
+                .map(|changed| (out, changed)),
+            Err(e) => Err(e),
+        };
+        match result {
+            Ok((true, true)) => {
+                self.log_to_ui("Out of office turned on in Outlook", LogLevel::Info)
+            }
+            Ok((false, true)) => self.log_to_ui("Back in the office", LogLevel::Info),
+            Ok(_) => {}
+            Err(e) => error!("Failed to check out-of-office status: {}", e),
+        }
+    }
+
+    /// Holds LLM calls through the meetings ahead when `meeting_pause_ai`
+    /// is on, and lets them go when it's off.
+    async fn check_calendar(&self) {
+        let busy = match self.policy.meeting_pause_ai().await {
+            Ok(true) => {
+                let now = Utc::now();
+                let ahead = now + chrono::Duration::hours(BUSY_LOOKAHEAD_HOURS);
+                match self.outlook.busy_times(now, ahead).await {
+                    Ok(busy) => busy,
+                    Err(e) => {
+                        error!("Failed to read the calendar: {}", e);
+                        return;
+                    }
+                }
+            }
+            Ok(false) => Vec::new(),
+            Err(e) => {
+                error!("Failed to read the meeting pause setting: {}", e);
+                return;
+            }
+        };
+        self.pipeline.pacing().set_busy_times(busy);
+    }
+
+    /// Sleeps for `duration` or until [`Self::sync_now`]; returns `false` if
+    /// sync was stopped meanwhile.
+    async fn sleep_unless_stopped(&self, duration: Duration) -> bool {
+        tokio::select! {
+            _ = sleep(duration) => !self.stopped(),
+            _ = self.wake.notified() => !self.stopped(),
+            _ = self.cancel.cancelled() => false,
+        }
+    }
+
+    /// Blocks while the activity policy pauses sync (focus mode, quiet hours,
+    /// battery). Returns `false` if sync was stopped while waiting.
+    async fn wait_until_sync_allowed(&self) -> bool {
+        let mut announced = false;
+        loop {
+            let reason = match self.policy.sync_pause_reason(Utc::now()).await {
+                Ok(Some(reason)) => reason,
+                Ok(None) => break,
+                Err(e) => {
+                    error!("Failed to read sync policy, continuing: {}", e);
+                    break;
+                }
+            };
+            if !announced {
+                self.log_to_ui(&format!("Sync paused: {}", reason), LogLevel::Info);
+                self.set_state(SyncState::Paused, Some(reason.to_string()));
+                announced = true;
+            }
+            if !self
+                .sleep_unless_stopped(Duration::from_secs(PAUSE_POLL_SECS))
+                .await
+            {
+                return false;
+            }
+        }
+        if announced {
+            self.log_to_ui("Sync resumed", LogLevel::Info);
+            self.set_state(SyncState::Idle, None);
+        }
+        !self.stopped()
+    }
+
+    /// Lists each folder oldest-first and processes it as backfill,
+    /// checkpointing after every email so an interrupted scan resumes from
+    /// where it stopped on the next start. Emails are fetched from Outlook
+    /// as the queue needs them rather than all up front, so memory stays
+    /// bounded on large mailboxes. Delta scans run in between when due, so
+    /// new mail doesn't wait for the backlog.
     async fn run_initial_scan(&self) -> Result<()> {
         info!("Running initial 90-day sync for all folders...");
-        let folders = [(6, "Inbox"), (5, "Sent Items")];
+        let mut batches = OpenBatches {
+            vips: self.sqlite.list_vip_senders().await?,
+            ..Default::default()
+        };
 
-        for (folder_id, folder_name) in folders {
+        for (folder, folder_name) in self.scanned_folders().await? {
+            let folder_name = folder_name.as_str();
+            if self.stopped() {
+                return Ok(());
+            }
             info!("Processing folder: {}", folder_name);
-            self.log_to_ui(&format!("Fetching emails from {}...", folder_name), "info");
-            let emails = match self
+            self.log_to_ui(
+                &format!("Fetching emails from {}...", folder_name),
+                LogLevel::Info,
+            );
+            let fetched_at = Utc::now();
+            let indexed = self.sqlite.list_indexed_at(folder_name).await?;
+            let mut emails = match self
                 .outlook
-                .get_emails_last_n_days(self.history_days, folder_id, folder_name)
+                .list_emails_last_n_days(
+                    self.history_days,
+                    &folder,
+                    folder_name,
+                    indexed,
+                    &self.cancel,
+                )
                 .await
             {
                 Ok(e) => e,
                 Err(e) => {
                     error!("Failed to fetch emails from {}: {}", folder_name, e);
-                    self.log_to_ui(&format!("Error fetching {}: {}", folder_name, e), "error");
+                    self.log_to_ui(
+                        &format!("Error fetching {}: {}", folder_name, e),
+                        LogLevel::Error,
+                    );
                     continue;
                 }
             };
+            emails.sort_by_key(|e| e.received_at);
+
+            let total = emails.len() as i64;
+            let checkpoint = match self.sqlite.get_scan_checkpoint(folder_name).await? {
+                Some(previous) => {
+                    // Emails received at the checkpoint itself are re-run; saving is idempotent.
+                    emails.retain(|e| e.received_at >= previous.last_received_at);
+                    let processed = total - emails.len() as i64;
+                    info!(
+                        "Resuming {} scan: {} of {} already processed",
+                        folder_name, processed, total
+                    );
+                    self.log_to_ui(
+                        &format!(
+                            "Resuming {} from checkpoint ({} of {} done)",
+                            folder_name, processed, total
+                        ),
+                        LogLevel::Info,
+                    );
+                    ScanCheckpoint {
+                        processed,
+                        total,
+                        ..previous
+                    }
+                }
+                None => ScanCheckpoint {
+                    folder: folder_name.to_string(),
+                    last_received_at: DateTime::<Utc>::MIN_UTC,
+                    processed: 0,
+                    total,
+                },
+            };
+            self.emit_scan_progress(&checkpoint);
 
             info!("Found {} emails in {}", emails.len(), folder_name);
             self.log_to_ui(
@@ -101,33 +416,105 @@ impl SyncManager {
                     emails.len(),
                     folder_name
                 ),
-                "info",
+                LogLevel::Info,
+            );
+            // VIP mail is fetched first, to be extracted right away instead
+            // of waiting its turn.
+            let (vip, rest): (Vec<_>, Vec<_>) = emails.into_iter().partition(|e| {
+                batches
+                    .vips
+                    .iter()
+                    .any(|v| v.eq_ignore_ascii_case(e.sender.trim()))
+            });
+            let entry_ids = vip.into_iter().chain(rest).map(|e| e.entry_id).collect();
+            batches.backfill_order.push(folder_name.to_string());
+            batches.backfill.insert(
+                folder_name.to_string(),
+                Backfill {
+                    progress: BackfillProgress::new(checkpoint, fetched_at),
+                    stream: Some(
+                        self.outlook
+                            .stream_emails(folder_name, entry_ids, &self.cancel),
+                    ),
+                },
             );
-            for email in emails {
-                let subject = email.subject.clone();
-                if let Err(e) = self.pipeline.process_email(email).await {
-                    error!(
-                        "Failed to process email '{}' from {}: {}",
-                        subject, folder_name, e
-                    );
-                    self.log_to_ui(&format!("Skipped '{}': {}", subject, e), "warn");
-                }
-            }
         }
 
+        if !self.drain(&mut batches, true).await {
+            return Ok(());
+        }
+        self.sqlite.clear_scan_checkpoints().await?;
         info!("Initial sync completed");
-        self.log_to_ui("Initial sync cycle completed", "info");
+        self.log_to_ui("Initial sync cycle completed", LogLevel::Info);
         Ok(())
     }
 
+    fn emit_scan_progress(&self, checkpoint: &ScanCheckpoint) {
+        self.notifier
+            .notify(AppEvent::ScanProgress(ScanProgress::from(checkpoint)));
+    }
+
+    /// Reads when `folder` was last scanned completely, from `app_config`.
+    async fn delta_checkpoint(&self, folder: &str) -> Option<DateTime<Utc>> {
+        match self.sqlite.get_config(&delta_checkpoint_key(folder)).await {
+            Ok(value) => value
+                .and_then(|s| DateTime::parse_from_rfc3339(&s).ok())
+                .map(|dt| dt.with_timezone(&Utc)),
+            Err(e) => {
+                error!("Failed to read delta checkpoint for {}: {}", folder, e);
+                None
+            }
+        }
+    }
+
+    async fn save_delta_checkpoint(&self, folder: &str, at: DateTime<Utc>) {
+        if let Err(e) = self
+            .sqlite
+            .set_config(&delta_checkpoint_key(folder), &at.to_rfc3339())
+            .await
+        {
+            error!("Failed to save delta checkpoint for {}: {}", folder, e);
+        }
+    }
+
     async fn run_delta_scan(&self) -> Result<()> {
         info!("Running periodic delta scan for all folders...");
-        let folders = [(6, "Inbox"), (5, "Sent Items")];
+        let mut batches = OpenBatches::default();
+        self.fetch_delta(&mut batches).await?;
+        self.drain(&mut batches, false).await;
+        Ok(())
+    }
 
-        for (folder_id, folder_name) in folders {
+    /// Queues what each folder received since its checkpoint, ahead of any
+    /// backfill. Folders whose previous delta batch is still queued are
+    /// left for the next poll.
+    async fn fetch_delta(&self, batches: &mut OpenBatches) -> Result<()> {
+        for (folder, folder_name) in self.scanned_folders().await? {
+            let folder_name = folder_name.as_str();
+            if self.stopped() {
+                return Ok(());
+            }
+            if batches.delta.contains_key(folder_name) {
+                continue;
+            }
+            let fetched_at = Utc::now();
+            // During the initial scan the backfill covers everything up to
+            // its own fetch.
+            let checkpoint = match self.delta_checkpoint(folder_name).await {
+                Some(checkpoint) => Some(checkpoint),
+                None => batches
+                    .backfill
+                    .get(folder_name)
+                    .map(|b| b.progress.fetched_at),
+            };
+            let since = match checkpoint {
+                Some(checkpoint) => checkpoint - chrono::Duration::minutes(DELTA_OVERLAP_MINS),
+                None => fetched_at - chrono::Duration::days(DELTA_FALLBACK_DAYS),
+            };
+            let indexed = self.sqlite.list_indexed_at(folder_name).await?;
             let emails = match self
                 .outlook
-                .get_emails_last_n_days(1, folder_id, folder_name)
+                .get_emails_since(since, &folder, folder_name, indexed, &self.cancel)
                 .await
             {
                 Ok(e) => e,
@@ -136,17 +523,235 @@ impl SyncManager {
                     continue;
                 }
             };
+            if !emails.is_empty() {
+                info!("Queued {} new emails from {}", emails.len(), folder_name);
+            }
+            self.pipeline.queue().enqueue(emails, QueuePriority::Delta);
+            batches.delta.insert(folder_name.to_string(), fetched_at);
+        }
+        Ok(())
+    }
 
-            for email in emails {
-                let subject = email.subject.clone();
-                if let Err(e) = self.pipeline.process_email(email).await {
-                    error!(
-                        "Failed to process email in delta scan '{}' from {}: {}",
-                        subject, folder_name, e
-                    );
+    /// Processes queued emails until none are left, closing each batch once
+    /// its folder has nothing pending. With `poll_delta`, delta scans run
+    /// whenever one is due or requested, and their emails go first. Returns
+    /// `false` if sync was stopped.
+    async fn drain(&self, batches: &mut OpenBatches, poll_delta: bool) -> bool {
+        let mut next_delta = Instant::now() + self.delta_interval().await;
+        loop {
+            self.close_drained(batches).await;
+            if self.stopped() {
+                return false;
+            }
+            if poll_delta
+                && (self.delta_requested.swap(false, Ordering::Relaxed)
+                    || Instant::now() >= next_delta)
+            {
+                info!("Checking for new mail during the initial scan...");
+                self.check_calendar().await;
+                if let Err(e) = self.fetch_delta(batches).await {
+                    error!("Delta scan failed: {}", e);
+                }
+                next_delta = Instant::now() + self.delta_interval().await;
+            }
+            self.refill_backfill(batches).await;
+            let Some((email, priority)) = self.pipeline.queue().next() else {
+                return true;
+            };
+
+            let subject = email.subject.clone();
+            let folder = email.folder.clone();
+            let received_at = email.received_at;
+            let result = self.pipeline.process_email(email).await;
+            if let Err(e) = &result {
+                error!(
+                    "Failed to process {:?} email '{}' from {}: {}",
+                    priority, subject, folder, e
+                );
+                self.log_to_ui(&format!("Skipped '{}': {}", subject, e), LogLevel::Warn);
+            }
+            if self.stopped() {
+                // The email may have been refused mid-way; leave it for the resume.
+                return false;
+            }
+
+            match priority {
+                QueuePriority::Backfill => {
+                    if let Some(backfill) = batches.backfill.get_mut(&folder) {
+                        backfill.progress.processed(received_at, result.is_ok());
+                        let checkpoint = &backfill.progress.checkpoint;
+                        if let Err(e) = self.sqlite.save_scan_checkpoint(checkpoint).await {
+                            error!("Failed to save scan checkpoint for {}: {}", folder, e);
+                        }
+                        self.emit_scan_progress(checkpoint);
+                    }
+                }
+                QueuePriority::Vip => {
+                    // Taken out of order, so only the count moves.
+                    if let Some(backfill) = batches.backfill.get_mut(&folder) {
+                        backfill
+                            .progress
+                            .processed_out_of_order(received_at, result.is_ok());
+                        self.emit_scan_progress(&backfill.progress.checkpoint);
+                    }
+                }
+                QueuePriority::Delta => {
+                    // The next scan retries from the oldest failure.
+                    if let (Err(_), Some(checkpoint)) = (&result, batches.delta.get_mut(&folder)) {
+                        *checkpoint = (*checkpoint).min(received_at);
+                    }
                 }
             }
         }
-        Ok(())
     }
+
+    /// Fetches the next backfill email once none is queued, from the first
+    /// folder scanned with emails left in Outlook. Keeping one queued
+    /// lets it take its turn between delta emails.
+    async fn refill_backfill(&self, batches: &mut OpenBatches) {
+        let queue = self.pipeline.queue();
+        if batches
+            .backfill
+            .keys()
+            .any(|folder| queue.has_pending(QueuePriority::Backfill, folder))
+        {
+            return;
+        }
+        for folder_name in &batches.backfill_order {
+            let Some(backfill) = batches.backfill.get_mut(folder_name) else {
+                continue;
+            };
+            let Some(stream) = backfill.stream.as_mut() else {
+                continue;
+            };
+            match stream.next().await {
+                Some(Ok(email)) => {
+                    let vip = batches
+                        .vips
+                        .iter()
+                        .any(|v| v.eq_ignore_ascii_case(email.sender.trim()));
+                    let priority = if vip {
+                        QueuePriority::Vip
+                    } else {
+                        QueuePriority::Backfill
+                    };
+                    queue.enqueue(vec![email], priority);
+                    return;
+                }
+                Some(Err(e)) => {
+                    // Counted as done, so the scan still completes. The delta
+                    // checkpoint stays, so a delta scan or restart fetches it
+                    // again.
+                    error!("Failed to fetch an email from {}: {}", folder_name, e);
+                    backfill.progress.fetch_failed();
+                    self.emit_scan_progress(&backfill.progress.checkpoint);
+                    return;
+                }
+                None => backfill.stream = None,
+            }
+        }
+    }
+
+    /// Saves the delta checkpoint of every batch whose folder has nothing
+    /// left in the queue or in Outlook.
+    async fn close_drained(&self, batches: &mut OpenBatches) {
+        let queue = self.pipeline.queue();
+        let drained: Vec<String> = batches
+            .delta
+            .keys()
+            .filter(|folder| !queue.has_pending(QueuePriority::Delta, folder))
+            .cloned()
+            .collect();
+        for folder in drained {
+            if let Some(checkpoint) = batches.delta.remove(&folder) {
+                self.save_delta_checkpoint(&folder, checkpoint).await;
+                batches.delta_done.insert(folder);
+            }
+        }
+
+        let drained: Vec<String> = batches
+            .backfill
+            .iter()
+            .filter(|(folder, backfill)| {
+                backfill.stream.is_none() && !queue.has_pending(QueuePriority::Backfill, folder)
+            })
+            .map(|(folder, _)| folder.clone())
+            .collect();
+        for folder in drained {
+            if let Some(backfill) = batches.backfill.remove(&folder) {
+                // Delta scans pick up from here. The checkpoint of one that
+                // already ran only moves back, to retry what failed here.
+                let Some(at) = backfill.progress.delta_checkpoint() else {
+                    continue;
+                };
+                let current = if batches.delta_done.contains(&folder) {
+                    self.delta_checkpoint(&folder).await
+                } else {
+                    None
+                };
+                if current.is_none_or(|current| at < current) {
+                    self.save_delta_checkpoint(&folder, at).await;
+                }
+            }
+        }
+    }
+
+    /// The folders to scan, with the names their emails are filed under;
+    /// see [`folders::scanned_folders`].
+    async fn scanned_folders(&self) -> Result<Vec<(MailFolder, String)>> {
+        let spec = self
+            .sqlite
+            .get_config(FOLDER_MODES_KEY)
+            .await?
+            .unwrap_or_default();
+        let defaults = FOLDERS.map(|(_, name)| name);
+        Ok(folders::scanned_folders(&spec, &defaults)
+            .into_iter()
+            .map(|name| {
+                let folder = match FOLDERS.iter().find(|(_, default)| *default == name) {
+                    Some((id, _)) => MailFolder::Default(*id),
+                    None => MailFolder::Named(name.clone()),
+                };
+                (folder, name)
+            })
+            .collect())
+    }
+
+    /// How long to wait between delta scans under the activity policy.
+    async fn delta_interval(&self) -> Duration {
+        let base = Duration::from_secs(self.sync_interval_mins as u64 * 60);
+        match self.policy.sync_interval(base).await {
+            Ok(wait) => wait,
+            Err(e) => {
+                error!("Failed to read sync policy, using base interval: {}", e);
+                base
+            }
+        }
+    }
+}
+
+/// Scan batches with emails still in the queue, per folder.
+#[derive(Default)]
+struct OpenBatches {
+    backfill: HashMap<String, Backfill>,
+    /// Folders of `backfill` in the order they are fetched from.
+    backfill_order: Vec<String>,
+    /// Where each folder's delta checkpoint moves once its batch drains.
+    delta: HashMap<String, DateTime<Utc>>,
+    /// Folders a delta batch finished for, whose checkpoint the backfill
+    /// must not move back.
+    delta_done: HashSet<String>,
+    /// VIP senders as of the start of the initial scan.
+    vips: Vec<String>,
+}
+
+/// A folder's initial scan.
+struct Backfill {
+    progress: BackfillProgress,
+    /// Emails not taken from Outlook yet; `None` once all were.
+    stream: Option<EmailStream>,
+}
+
+fn delta_checkpoint_key(folder: &str) -> String {
+    format!("delta_checkpoint:{}", folder)
 }
@@ -0,0 +1,41 @@
+use super::shutdown::ShutdownCoordinator;
+use crate::pipeline::ExtractionPipeline;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::error;
+
+/// How often queued payloads are pushed when nothing asks sooner.
+const SYNC_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Keeps the project and tags on Qdrant email points in step with SQLite,
+/// so vector search can filter on them. Triggers queue an email in the
+/// payload outbox whenever either changes; this pushes the queue right
+/// after [`ExtractionPipeline::request_payload_sync`] and every minute
+/// otherwise, which also retries failures.
+pub struct PayloadSyncWorker {
+    pipeline: Arc<ExtractionPipeline>,
+    shutdown: Arc<ShutdownCoordinator>,
+}
+
+impl PayloadSyncWorker {
+    pub fn new(pipeline: Arc<ExtractionPipeline>, shutdown: Arc<ShutdownCoordinator>) -> Self {
+        Self { pipeline, shutdown }
+    }
+
+    pub async fn run(self) {
+        loop {
+            // Keep going while entries sync, e.g. through the backfill of
+            // older points; failures wait for the next pass.
+            match self.pipeline.sync_vector_payloads().await {
+                Ok(synced) if synced > 0 && !self.shutdown.is_shutting_down() => continue,
+                Ok(_) => {}
+                Err(e) => error!("Vector payload sync failed: {}", e),
+            }
+            tokio::select! {
+                _ = tokio::time::sleep(SYNC_INTERVAL) => {}
+                _ = self.pipeline.payload_sync_requested() => {}
+                _ = self.shutdown.cancelled() => return,
+            }
+        }
+    }
+}
@@ -0,0 +1,233 @@
+use super::power::{current_power_state, PowerState};
+use chrono::{DateTime, NaiveTime, Utc};
+use noodle_core::error::Result;
+use std::sync::Arc;
+use std::time::Duration;
+use storage::sqlite::SqliteStorage;
+
+/// `app_config` keys read by [`ActivityPolicy`].
+pub const QUIET_HOURS_START_KEY: &str = "quiet_hours_start";
+pub const QUIET_HOURS_END_KEY: &str = "quiet_hours_end";
+pub const QUIET_HOURS_PAUSE_SYNC_KEY: &str = "quiet_hours_pause_sync";
+pub const FOCUS_UNTIL_KEY: &str = "focus_until";
+/// Set from the tray; holds sync until the user resumes it.
+pub const SYNC_PAUSED_KEY: &str = "sync_paused";
+/// When Outlook started reporting the user out of office; empty once back.
+pub const OUT_OF_OFFICE_SINCE_KEY: &str = "out_of_office_since";
+pub const DELEGATION_MODE_KEY: &str = "delegation_mode";
+/// `throttle` (default), `pause`, or `ignore`.
+pub const BATTERY_SYNC_MODE_KEY: &str = "battery_sync_mode";
+pub const BATTERY_INTERVAL_MULTIPLIER_KEY: &str = "battery_interval_multiplier";
+/// Holds LLM calls while the calendar shows the user in a meeting.
+pub const MEETING_PAUSE_AI_KEY: &str = "meeting_pause_ai";
+
+const DEFAULT_BATTERY_INTERVAL_MULTIPLIER: u32 = 4;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, strum_macros::Display)]
+pub enum PauseReason {
+    #[strum(serialize = "paused by user")]
+    User,
+    #[strum(serialize = "focus mode")]
+    FocusMode,
+    #[strum(serialize = "quiet hours")]
+    QuietHours,
+    #[strum(serialize = "running on battery")]
+    OnBattery,
+    #[strum(serialize = "battery saver")]
+    PowerSaver,
+}
+
+/// Daily local-time window, e.g. 22:00–07:00. Windows may wrap past midnight.
+#[derive(Debug, Clone, Copy)]
+pub struct QuietHours {
+    pub start: NaiveTime,
+    pub end: NaiveTime,
+}
+
+impl QuietHours {
+    pub fn contains(&self, time: NaiveTime) -> bool {
+        if self.start <= self.end {
+            time >= self.start && time < self.end
+        } else {
+            time >= self.start || time < self.end
+        }
+    }
+}
+
+/// Decides whether background work may run right now, based on the configured
+/// quiet hours, any active focus mode and the power source. A manual pause or
+/// focus mode always pauses background sync; quiet hours silence notifications and pause sync
+/// only when `quiet_hours_pause_sync` is set. On battery, sync runs less often
+/// (or pauses with `battery_sync_mode = pause`); battery saver pauses it unless
+/// the mode is `ignore`.
+pub struct ActivityPolicy {
+    sqlite: Arc<SqliteStorage>,
+}
+
+impl ActivityPolicy {
+    pub fn new(sqlite: Arc<SqliteStorage>) -> Self {
+        Self { sqlite }
+    }
+
+    pub async fn notifications_allowed(&self, now: DateTime<Utc>) -> Result<bool> {
+        Ok(self.focus_until(now).await?.is_none() && !self.in_quiet_hours(now).await?)
+    }
+
+    /// VIP mail still notifies during quiet hours, but not in focus mode.
+    pub async fn vip_notifications_allowed(&self, now: DateTime<Utc>) -> Result<bool> {
+        Ok(self.focus_until(now).await?.is_none())
+    }
+
+    pub async fn sync_allowed(&self, now: DateTime<Utc>) -> Result<bool> {
+        Ok(self.sync_pause_reason(now).await?.is_none())
+    }
+
+    pub async fn sync_pause_reason(&self, now: DateTime<Utc>) -> Result<Option<PauseReason>> {
+        if self.sync_paused().await? {
+            return Ok(Some(PauseReason::User));
+        }
+        if self.focus_until(now).await?.is_some() {
+            return Ok(Some(PauseReason::FocusMode));
+        }
+        let pause_sync = self
+            .sqlite
+            .get_config(QUIET_HOURS_PAUSE_SYNC_KEY)
+            .await?
+            .is_some_and(|v| v == "true");
+        if pause_sync && self.in_quiet_hours(now).await? {
+            return Ok(Some(PauseReason::QuietHours));
+        }
+
+        let mode = self.battery_sync_mode().await?;
+        Ok(match current_power_state() {
+            PowerState::PowerSaver if mode != "ignore" => Some(PauseReason::PowerSaver),
+            PowerState::OnBattery if mode == "pause" => Some(PauseReason::OnBattery),
+            _ => None,
+        })
+    }
+
+    /// Delta-scan interval for the current power source: `base` on AC power,
+    /// stretched by `battery_interval_multiplier` on battery in `throttle` mode.
+    pub async fn sync_interval(&self, base: Duration) -> Result<Duration> {
+        if current_power_state() != PowerState::OnBattery
+            || self.battery_sync_mode().await? != "throttle"
+        {
+            return Ok(base);
+        }
+        let multiplier = self
+            .sqlite
+            .get_config(BATTERY_INTERVAL_MULTIPLIER_KEY)
+            .await?
+            .and_then(|s| s.parse::<u32>().ok())
+            .filter(|m| *m > 0)
+            .unwrap_or(DEFAULT_BATTERY_INTERVAL_MULTIPLIER);
+        Ok(base * multiplier)
+    }
+
+    async fn battery_sync_mode(&self) -> Result<String> {
+        Ok(self
+            .sqlite
+            .get_config(BATTERY_SYNC_MODE_KEY)
+            .await?
+            .filter(|m| matches!(m.as_str(), "throttle" | "pause" | "ignore"))
+            .unwrap_or_else(|| "throttle".to_string()))
+    }
+
+    /// End of the active focus session, or `None` when focus mode is off or expired.
+    pub async fn focus_until(&self, now: DateTime<Utc>) -> Result<Option<DateTime<Utc>>> {
+        let until = self
+            .sqlite
+            .get_config(FOCUS_UNTIL_KEY)
+            .await?
+            .and_then(|s| DateTime::parse_from_rfc3339(&s).ok())
+            .map(|dt| dt.with_timezone(&Utc));
+        Ok(until.filter(|until| *until > now))
+    }
+
+    /// Starts focus mode until `until`; `None` ends it immediately.
+    pub async fn set_focus_until(&self, until: Option<DateTime<Utc>>) -> Result<()> {
+        let value = until.map(|dt| dt.to_rfc3339()).unwrap_or_default();
+        self.sqlite.set_config(FOCUS_UNTIL_KEY, &value).await
+    }
+
+    /// Records whether Outlook reports the user out of office, keeping the
+    /// time they left. Returns `true` when that changed.
+    pub async fn set_out_of_office(&self, out: bool, now: DateTime<Utc>) -> Result<bool> {
+        let since = self.out_of_office_since().await?;
+        match (out, since) {
+            (true, None) => {
+                self.sqlite
+                    .set_config(OUT_OF_OFFICE_SINCE_KEY, &now.to_rfc3339())
+                    .await?
+            }
+            (false, Some(_)) => self.sqlite.set_config(OUT_OF_OFFICE_SINCE_KEY, "").await?,
+            _ => return Ok(false),
+        }
+        Ok(true)
+    }
+
+    async fn out_of_office_since(&self) -> Result<Option<DateTime<Utc>>> {
+        Ok(self
+            .sqlite
+            .get_config(OUT_OF_OFFICE_SINCE_KEY)
+            .await?
+            .and_then(|s| DateTime::parse_from_rfc3339(&s).ok())
+            .map(|dt| dt.with_timezone(&Utc)))
+    }
+
+    /// When the user went out of office, if they are and delegation mode is
+    /// on.
+    pub async fn away_since(&self) -> Result<Option<DateTime<Utc>>> {
+        let delegation = self
+            .sqlite
+            .get_config(DELEGATION_MODE_KEY)
+            .await?
+            .is_some_and(|v| v == "true");
+        if !delegation {
+            return Ok(None);
+        }
+        self.out_of_office_since().await
+    }
+
+    pub async fn sync_paused(&self) -> Result<bool> {
+        Ok(self
+            .sqlite
+            .get_config(SYNC_PAUSED_KEY)
+            .await?
+            .is_some_and(|v| v == "true"))
+    }
+
+    pub async fn meeting_pause_ai(&self) -> Result<bool> {
+        Ok(self
+            .sqlite
+            .get_config(MEETING_PAUSE_AI_KEY)
+            .await?
+            .is_some_and(|v| v == "true"))
+    }
+
+    pub async fn set_sync_paused(&self, paused: bool) -> Result<()> {
+        self.sqlite
+            .set_config(SYNC_PAUSED_KEY, &paused.to_string())
+            .await
+    }
+
+    pub async fn quiet_hours(&self) -> Result<Option<QuietHours>> {
+        let time = |value: Option<String>| {
+            value.and_then(|s| NaiveTime::parse_from_str(s.trim(), "%H:%M").ok())
+        };
+        let start = time(self.sqlite.get_config(QUIET_HOURS_START_KEY).await?);
+        let end = time(self.sqlite.get_config(QUIET_HOURS_END_KEY).await?);
+        Ok(start
+            .zip(end)
+            .filter(|(start, end)| start != end)
+            .map(|(start, end)| QuietHours { start, end }))
+    }
+
+    async fn in_quiet_hours(&self, now: DateTime<Utc>) -> Result<bool> {
+        let Some(quiet) = self.quiet_hours().await? else {
+            return Ok(false);
+        };
+        let tz = self.sqlite.get_user_timezone().await?;
+        Ok(quiet.contains(tz.to_local(now).time()))
+    }
+}
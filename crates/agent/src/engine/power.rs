@@ -0,0 +1,31 @@
+use windows::Win32::System::Power::{GetSystemPowerStatus, SYSTEM_POWER_STATUS};
+
+/// `ACLineStatus` value reported while running on battery.
+const AC_OFFLINE: u8 = 0;
+/// `SystemStatusFlag` value reported while battery saver is on.
+const BATTERY_SAVER_ON: u8 = 1;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PowerState {
+    /// Plugged in, or the power state could not be read.
+    AcPower,
+    OnBattery,
+    /// Battery saver is active; heavy background work should stop.
+    PowerSaver,
+}
+
+/// Reads the current power source via `GetSystemPowerStatus`.
+pub fn current_power_state() -> PowerState {
+    let mut status = SYSTEM_POWER_STATUS::default();
+    if unsafe { GetSystemPowerStatus(&mut status) }.is_err() {
+        return PowerState::AcPower;
+    }
+
+    if status.SystemStatusFlag == BATTERY_SAVER_ON {
+        PowerState::PowerSaver
+    } else if status.ACLineStatus == AC_OFFLINE {
+        PowerState::OnBattery
+    } else {
+        PowerState::AcPower
+    }
+}
@@ -0,0 +1,79 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use tokio::sync::Notify;
+use tokio::time::{timeout, Duration};
+use tokio_util::sync::CancellationToken;
+
+/// Coordinates app exit with background work: cancels sync loops, stops new
+/// emails from entering the pipeline, and waits for in-flight ones to finish
+/// so no email is left half-written between SQLite and Qdrant.
+#[derive(Default)]
+pub struct ShutdownCoordinator {
+    token: CancellationToken,
+    in_flight: AtomicUsize,
+    idle: Notify,
+}
+
+/// Held for the duration of one unit of work; dropping it marks the work done.
+pub struct WorkGuard {
+    coordinator: Arc<ShutdownCoordinator>,
+}
+
+impl Drop for WorkGuard {
+    fn drop(&mut self) {
+        if self.coordinator.in_flight.fetch_sub(1, Ordering::SeqCst) == 1 {
+            self.coordinator.idle.notify_waiters();
+        }
+    }
+}
+
+impl ShutdownCoordinator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Resolves once shutdown has started; background loops select on this.
+    pub async fn cancelled(&self) {
+        self.token.cancelled().await
+    }
+
+    /// A token cancelled with shutdown that can also be cancelled on its own,
+    /// for work the user can stop.
+    pub fn child_token(&self) -> CancellationToken {
+        self.token.child_token()
+    }
+
+    pub fn is_shutting_down(&self) -> bool {
+        self.token.is_cancelled()
+    }
+
+    /// Registers in-flight work, or returns `None` once shutdown has begun.
+    pub fn begin_work(self: &Arc<Self>) -> Option<WorkGuard> {
+        self.in_flight.fetch_add(1, Ordering::SeqCst);
+        let guard = WorkGuard {
+            coordinator: self.clone(),
+        };
+        // Checked after incrementing so `shutdown` can't miss work that
+        // started concurrently with it.
+        if self.is_shutting_down() {
+            return None;
+        }
+        Some(guard)
+    }
+
+    /// Cancels background work and waits up to `grace` for in-flight work to
+    /// drain. Returns `false` if work was still running when the grace ran out.
+    pub async fn shutdown(&self, grace: Duration) -> bool {
+        self.token.cancel();
+        let drained = async {
+            loop {
+                let idle = self.idle.notified();
+                if self.in_flight.load(Ordering::SeqCst) == 0 {
+                    return;
+                }
+                idle.await;
+            }
+        };
+        timeout(grace, drained).await.is_ok()
+    }
+}
@@ -0,0 +1,95 @@
+use chrono::{DateTime, Utc};
+use noodle_core::error::{NoodleError, Result};
+use noodle_core::types::VectorSnapshot;
+use std::path::PathBuf;
+use std::sync::Arc;
+use storage::qdrant::{QdrantStorage, COLLECTION_EMAILS};
+use storage::sqlite::SqliteStorage;
+use tracing::info;
+
+const SNAPSHOT_EXTENSION: &str = "snapshot";
+
+/// Snapshots of the email vector collection, kept in the app data directory
+/// so a reset Qdrant container can be restored without re-embedding every
+/// email. Only the newest `vector_snapshot_retention` are kept.
+pub struct VectorSnapshots {
+    qdrant: Arc<QdrantStorage>,
+    sqlite: Arc<SqliteStorage>,
+    dir: PathBuf,
+}
+
+impl VectorSnapshots {
+    pub fn new(qdrant: Arc<QdrantStorage>, sqlite: Arc<SqliteStorage>, dir: PathBuf) -> Self {
+        Self {
+            qdrant,
+            sqlite,
+            dir,
+        }
+    }
+
+    /// Takes a snapshot, then deletes the oldest ones beyond the retention.
+    pub async fn create(&self) -> Result<VectorSnapshot> {
+        std::fs::create_dir_all(&self.dir).map_err(|e| NoodleError::Storage(e.to_string()))?;
+        let path = self
+            .qdrant
+            .create_snapshot(COLLECTION_EMAILS, &self.dir)
+            .await?;
+        info!("Saved vector snapshot {}", path.display());
+
+        let retention = self
+            .sqlite
+            .get_all_config()
+            .await?
+            .vector_snapshot_retention as usize;
+        let snapshots = self.list()?;
+        for old in snapshots.iter().skip(retention.max(1)) {
+            if let Err(e) = std::fs::remove_file(self.dir.join(&old.name)) {
+                info!("Failed to delete old vector snapshot {}: {}", old.name, e);
+            }
+        }
+        snapshots
+            .into_iter()
+            .find(|s| path.ends_with(&s.name))
+            .ok_or_else(|| NoodleError::Storage("Snapshot was not saved".into()))
+    }
+
+    /// Saved snapshots, newest first.
+    pub fn list(&self) -> Result<Vec<VectorSnapshot>> {
+        let entries = match std::fs::read_dir(&self.dir) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(NoodleError::Storage(e.to_string())),
+        };
+        let mut snapshots: Vec<VectorSnapshot> = entries
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| {
+                entry.path().extension().and_then(|e| e.to_str()) == Some(SNAPSHOT_EXTENSION)
+            })
+            .filter_map(|entry| {
+                let metadata = entry.metadata().ok()?;
+                Some(VectorSnapshot {
+                    name: entry.file_name().to_string_lossy().into_owned(),
+                    created_at: DateTime::<Utc>::from(metadata.modified().ok()?),
+                    size_bytes: metadata.len(),
+                })
+            })
+            .collect();
+        snapshots.sort_by_key(|s| std::cmp::Reverse(s.created_at));
+        Ok(snapshots)
+    }
+
+    /// Replaces the email collection with the saved snapshot `name`. Emails
+    /// indexed after it was taken lose their vectors until repaired.
+    pub async fn restore(&self, name: &str) -> Result<()> {
+        let snapshot = self
+            .list()?
+            .into_iter()
+            .find(|s| s.name == name)
+            .ok_or_else(|| NoodleError::Validation(format!("Snapshot {} not found", name)))?;
+        self.qdrant
+            .restore_snapshot(COLLECTION_EMAILS, &self.dir.join(&snapshot.name))
+            .await?;
+        info!("Restored vector snapshot {}", snapshot.name);
+        Ok(())
+    }
+}
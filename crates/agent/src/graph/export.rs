@@ -0,0 +1,190 @@
+use crate::archive;
+use noodle_core::error::Result;
+use noodle_core::types::{Graph, GraphLink, RelationStatus};
+use serde::{Deserialize, Serialize};
+use std::fmt::Write as _;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum GraphFormat {
+    /// For Gephi, yEd and Cytoscape.
+    Graphml,
+    /// Graphviz.
+    Dot,
+    /// `{nodes, links}` as d3-force and react-force-graph expect.
+    Json,
+}
+
+impl GraphFormat {
+    pub fn extension(self) -> &'static str {
+        match self {
+            GraphFormat::Graphml => "graphml",
+            GraphFormat::Dot => "dot",
+            GraphFormat::Json => "json",
+        }
+    }
+
+    pub fn mime_type(self) -> &'static str {
+        match self {
+            GraphFormat::Graphml => "application/graphml+xml",
+            GraphFormat::Dot => "text/vnd.graphviz",
+            GraphFormat::Json => "application/json",
+        }
+    }
+}
+
+/// A serialized graph, ready to be saved under `file_name`.
+#[derive(Debug, Clone, Serialize)]
+pub struct GraphExport {
+    pub file_name: String,
+    pub mime_type: &'static str,
+    pub content: String,
+    /// `content` is a base64 encoded encrypted archive holding the graph.
+    pub encrypted: bool,
+    pub nodes: usize,
+    pub links: usize,
+}
+
+impl GraphExport {
+    /// Wraps the graph file in an archive encrypted with `password`.
+    pub async fn encrypt(self, password: &str) -> Result<Self> {
+        let (file_name, content) =
+            archive::seal_file(&self.file_name, self.content, password).await?;
+        Ok(Self {
+            file_name,
+            mime_type: "application/octet-stream",
+            content,
+            encrypted: true,
+            ..self
+        })
+    }
+}
+
+pub fn render(graph: &Graph, format: GraphFormat) -> GraphExport {
+    let content = match format {
+        GraphFormat::Graphml => graphml(graph),
+        GraphFormat::Dot => dot(graph),
+        GraphFormat::Json => json(graph),
+    };
+    GraphExport {
+        file_name: format!("noodle-graph.{}", format.extension()),
+        mime_type: format.mime_type(),
+        content,
+        encrypted: false,
+        nodes: graph.nodes.len(),
+        links: graph.links.len(),
+    }
+}
+
+fn graphml(graph: &Graph) -> String {
+    let mut out = String::from(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+         <graphml xmlns=\"http://graphml.graphdrawing.org/xmlns\">\n  \
+         <key id=\"name\" for=\"node\" attr.name=\"name\" attr.type=\"string\"/>\n  \
+         <key id=\"type\" for=\"node\" attr.name=\"type\" attr.type=\"string\"/>\n  \
+         <key id=\"kind\" for=\"edge\" attr.name=\"type\" attr.type=\"string\"/>\n  \
+         <key id=\"weight\" for=\"edge\" attr.name=\"weight\" attr.type=\"long\"/>\n  \
+         <key id=\"confidence\" for=\"edge\" attr.name=\"confidence\" attr.type=\"double\"/>\n  \
+         <key id=\"status\" for=\"edge\" attr.name=\"status\" attr.type=\"string\"/>\n  \
+         <graph id=\"noodle\" edgedefault=\"directed\">\n",
+    );
+    for node in &graph.nodes {
+        let _ = writeln!(
+            out,
+            "    <node id=\"n{}\"><data key=\"name\">{}</data><data key=\"type\">{}</data></node>",
+            node.id,
+            xml_escape(&node.name),
+            xml_escape(&node.kind)
+        );
+    }
+    for (i, link) in graph.links.iter().enumerate() {
+        let _ = write!(
+            out,
+            "    <edge id=\"e{}\" source=\"n{}\" target=\"n{}\"><data key=\"kind\">{}</data><data key=\"weight\">{}</data>",
+            i,
+            link.source,
+            link.target,
+            xml_escape(&link.kind),
+            link.weight
+        );
+        if let Some(confidence) = link.confidence {
+            let _ = write!(out, "<data key=\"confidence\">{}</data>", confidence);
+        }
+        if let Some(status) = link.status {
+            let _ = write!(out, "<data key=\"status\">{}</data>", status);
+        }
+        out.push_str("</edge>\n");
+    }
+    out.push_str("  </graph>\n</graphml>\n");
+    out
+}
+
+fn dot(graph: &Graph) -> String {
+    let mut out = String::from("digraph noodle {\n");
+    for node in &graph.nodes {
+        let _ = writeln!(
+            out,
+            "  n{} [label=\"{}\", type=\"{}\"];",
+            node.id,
+            dot_escape(&node.name),
+            dot_escape(&node.kind)
+        );
+    }
+    for link in &graph.links {
+        let _ = write!(
+            out,
+            "  n{} -> n{} [label=\"{}\", weight={}",
+            link.source,
+            link.target,
+            dot_escape(&link.kind),
+            link.weight
+        );
+        if let Some(confidence) = link.confidence {
+            let _ = write!(out, ", confidence={}", confidence);
+        }
+        if let Some(status) = link.status {
+            let _ = write!(out, ", status=\"{}\"", status);
+        }
+        // Unconfirmed inferences are drawn dashed.
+        if is_suggestion(link) {
+            out.push_str(", style=dashed");
+        }
+        out.push_str("];\n");
+    }
+    out.push_str("}\n");
+    out
+}
+
+fn json(graph: &Graph) -> String {
+    let value = serde_json::json!({
+        "nodes": graph.nodes.iter().map(|n| serde_json::json!({
+            "id": n.id.to_string(),
+            "name": n.name,
+            "type": n.kind,
+        })).collect::<Vec<_>>(),
+        "links": graph.links.iter().map(|l| serde_json::json!({
+            "source": l.source.to_string(),
+            "target": l.target.to_string(),
+            "type": l.kind,
+            "weight": l.weight,
+            "confidence": l.confidence,
+            "status": l.status,
+        })).collect::<Vec<_>>(),
+    });
+    serde_json::to_string_pretty(&value).unwrap_or_default()
+}
+
+fn is_suggestion(link: &GraphLink) -> bool {
+    link.status == Some(RelationStatus::Suggested)
+}
+
+fn xml_escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn dot_escape(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('"', "\\\"")
+}
@@ -0,0 +1,173 @@
+pub mod export;
+
+use noodle_core::error::Result;
+use noodle_core::types::{InferredRelation, RelationKind, RelationStatus};
+use std::collections::HashMap;
+use std::sync::Arc;
+use storage::sqlite::SqliteStorage;
+use tracing::info;
+
+/// Shared emails needed before two people count as frequent collaborators.
+const MIN_SHARED_EMAILS: u32 = 3;
+/// Weighted interactions at which collaborator confidence reaches ~63%.
+const COLLABORATION_SCALE: f32 = 5.0;
+/// Strongest relations kept per run, so large mailboxes don't flood the graph.
+const MAX_RELATIONS: usize = 500;
+/// Public mail providers; sharing one says nothing about an organization.
+const PUBLIC_DOMAINS: &[&str] = &[
+    "gmail.com",
+    "googlemail.com",
+    "outlook.com",
+    "hotmail.com",
+    "live.com",
+    "yahoo.com",
+    "icloud.com",
+    "me.com",
+    "aol.com",
+    "proton.me",
+    "protonmail.com",
+    "gmx.com",
+];
+
+/// Interactions between one pair of people, in both directions.
+#[derive(Default)]
+struct PairStats {
+    /// Sender to a To recipient.
+    direct: u32,
+    /// Sender to a CC recipient, or two people copied on the same email.
+    copied: u32,
+    /// Direct mail weighs most; being copied together weighs least.
+    score: f32,
+}
+
+/// Infers who works with whom from the senders and recipients of stored mail:
+/// people who exchange mail often become `frequent_collaborator`s, and people
+/// in contact who share a company domain become `same_org`.
+pub struct OrgInference {
+    sqlite: Arc<SqliteStorage>,
+}
+
+impl OrgInference {
+    pub fn new(sqlite: Arc<SqliteStorage>) -> Self {
+        Self { sqlite }
+    }
+
+    /// Recomputes the inferred relations and returns how many were found.
+    pub async fn run(&self) -> Result<usize> {
+        let emails = self.sqlite.list_email_participants().await?;
+        let mut pairs: HashMap<(String, String), PairStats> = HashMap::new();
+        // Keeps the first spelling seen for each participant.
+        let mut names: HashMap<String, String> = HashMap::new();
+
+        for email in &emails {
+            let Some(sender) = participant(&email.sender, &mut names) else {
+                continue;
+            };
+            let to: Vec<String> = split(&email.to)
+                .filter_map(|p| participant(p, &mut names))
+                .collect();
+            let cc: Vec<String> = split(email.cc.as_deref().unwrap_or_default())
+                .filter_map(|p| participant(p, &mut names))
+                .collect();
+
+            for recipient in &to {
+                record(&mut pairs, &sender, recipient, |s| {
+                    s.direct += 1;
+                    s.score += 1.0;
+                });
+            }
+            for recipient in &cc {
+                record(&mut pairs, &sender, recipient, |s| {
+                    s.copied += 1;
+                    s.score += 0.5;
+                });
+            }
+            let recipients: Vec<&String> = to.iter().chain(&cc).collect();
+            for (i, a) in recipients.iter().enumerate() {
+                for b in &recipients[i + 1..] {
+                    record(&mut pairs, a, b, |s| {
+                        s.copied += 1;
+                        s.score += 0.25;
+                    });
+                }
+            }
+        }
+
+        let mut relations = Vec::new();
+        for ((a, b), stats) in &pairs {
+            let shared = stats.direct + stats.copied;
+            let evidence = format!(
+                "{} shared emails ({} direct, {} copied)",
+                shared, stats.direct, stats.copied
+            );
+            let relation = |kind, confidence: f32| InferredRelation {
+                id: 0,
+                source: names[a].clone(),
+                target: names[b].clone(),
+                kind,
+                confidence,
+                evidence: evidence.clone(),
+                status: RelationStatus::Suggested,
+            };
+            if shared >= MIN_SHARED_EMAILS {
+                let confidence = 1.0 - (-stats.score / COLLABORATION_SCALE).exp();
+                relations.push(relation(RelationKind::FrequentCollaborator, confidence));
+            }
+            if let (Some(domain), Some(other)) = (domain(a), domain(b)) {
+                if domain == other && !PUBLIC_DOMAINS.contains(&domain) {
+                    let confidence = (0.7 + 0.05 * shared as f32).min(0.95);
+                    relations.push(relation(RelationKind::SameOrg, confidence));
+                }
+            }
+        }
+        relations.sort_by(|a, b| b.confidence.total_cmp(&a.confidence));
+        relations.truncate(MAX_RELATIONS);
+
+        self.sqlite.save_inferred_relations(&relations).await?;
+        info!(
+            "Inferred {} relations from {} emails",
+            relations.len(),
+            emails.len()
+        );
+        Ok(relations.len())
+    }
+}
+
+/// Outlook separates recipients with semicolons.
+fn split(list: &str) -> impl Iterator<Item = &str> {
+    list.split(';')
+}
+
+/// The lowercase key for a participant, remembering how it was spelled.
+fn participant(raw: &str, names: &mut HashMap<String, String>) -> Option<String> {
+    let name = raw.trim().trim_matches(|c| c == '"' || c == '\'').trim();
+    if name.is_empty() || name.eq_ignore_ascii_case("unknown") {
+        return None;
+    }
+    let key = name.to_lowercase();
+    names.entry(key.clone()).or_insert_with(|| name.to_string());
+    Some(key)
+}
+
+fn record(
+    pairs: &mut HashMap<(String, String), PairStats>,
+    a: &str,
+    b: &str,
+    update: impl FnOnce(&mut PairStats),
+) {
+    if a == b {
+        return;
+    }
+    let key = if a < b {
+        (a.to_string(), b.to_string())
+    } else {
+        (b.to_string(), a.to_string())
+    };
+    update(pairs.entry(key).or_default());
+}
+
+fn domain(key: &str) -> Option<&str> {
+    key.rsplit_once('@')
+        .map(|(_, domain)| domain)
+        .filter(|d| !d.is_empty())
+}
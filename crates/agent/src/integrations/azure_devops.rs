@@ -0,0 +1,92 @@
+use super::{send, RemoteTicket, TicketDraft};
+use noodle_core::config::Config;
+use noodle_core::error::{NoodleError, Result};
+use serde::Deserialize;
+use serde_json::json;
+
+const API_VERSION: &str = "7.0";
+
+/// Azure DevOps Boards, authenticated with a personal access token.
+pub struct AzureDevopsClient {
+    client: reqwest::Client,
+    base_url: String,
+    token: String,
+    work_item_type: String,
+}
+
+#[derive(Deserialize)]
+struct CreatedWorkItem {
+    id: i64,
+}
+
+impl AzureDevopsClient {
+    /// Fails when the organization or token is not set up.
+    pub fn from_config(config: &Config) -> Result<Self> {
+        let (Some(base_url), Some(token)) = (
+            config.azure_devops_url.as_deref(),
+            config.azure_devops_token.as_deref(),
+        ) else {
+            return Err(NoodleError::Validation(
+                "Azure DevOps is not set up: add the organization URL and access token in Settings"
+                    .to_string(),
+            ));
+        };
+        Ok(Self {
+            client: reqwest::Client::new(),
+            base_url: base_url.trim_end_matches('/').to_string(),
+            token: token.to_string(),
+            work_item_type: config.azure_devops_work_item_type.clone(),
+        })
+    }
+
+    pub async fn create_work_item(&self, draft: &TicketDraft) -> Result<RemoteTicket> {
+        let project = urlencode(&draft.project);
+        // Work items are created from a JSON Patch document of their fields.
+        let body = json!([
+            { "op": "add", "path": "/fields/System.Title", "value": draft.summary },
+            { "op": "add", "path": "/fields/System.Description", "value": html(&draft.description) },
+            { "op": "add", "path": "/fields/System.Tags", "value": "noodle" },
+        ]);
+        let request = self
+            .client
+            .post(format!(
+                "{}/{}/_apis/wit/workitems/${}?api-version={}",
+                self.base_url,
+                project,
+                urlencode(&self.work_item_type),
+                API_VERSION
+            ))
+            // The token is the password; the user name is ignored.
+            .basic_auth("", Some(&self.token))
+            .header(reqwest::header::CONTENT_TYPE, "application/json-patch+json")
+            .body(body.to_string());
+        let created: CreatedWorkItem = send(request, "Azure DevOps").await?;
+        Ok(RemoteTicket {
+            key: created.id.to_string(),
+            url: format!(
+                "{}/{}/_workitems/edit/{}",
+                self.base_url, project, created.id
+            ),
+        })
+    }
+}
+
+/// Descriptions are HTML; keeps the text's line breaks.
+fn html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('\n', "<br>")
+}
+
+fn urlencode(segment: &str) -> String {
+    segment
+        .bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                (b as char).to_string()
+            }
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
@@ -0,0 +1,65 @@
+use super::{send, RemoteTicket, TicketDraft};
+use noodle_core::config::Config;
+use noodle_core::error::{NoodleError, Result};
+use serde::Deserialize;
+use serde_json::json;
+
+/// Jira Cloud or Data Center, through the v2 REST API, which takes the
+/// description as plain text.
+pub struct JiraClient {
+    client: reqwest::Client,
+    base_url: String,
+    email: String,
+    token: String,
+    issue_type: String,
+}
+
+#[derive(Deserialize)]
+struct CreatedIssue {
+    key: String,
+}
+
+impl JiraClient {
+    /// Fails when the site, account or token is not set up.
+    pub fn from_config(config: &Config) -> Result<Self> {
+        let (Some(base_url), Some(email), Some(token)) = (
+            config.jira_url.as_deref(),
+            config.jira_email.as_deref(),
+            config.jira_api_token.as_deref(),
+        ) else {
+            return Err(NoodleError::Validation(
+                "Jira is not set up: add the site, account email and API token in Settings"
+                    .to_string(),
+            ));
+        };
+        Ok(Self {
+            client: reqwest::Client::new(),
+            base_url: base_url.trim_end_matches('/').to_string(),
+            email: email.to_string(),
+            token: token.to_string(),
+            issue_type: config.jira_issue_type.clone(),
+        })
+    }
+
+    pub async fn create_issue(&self, draft: &TicketDraft) -> Result<RemoteTicket> {
+        let body = json!({
+            "fields": {
+                "project": { "key": draft.project },
+                "issuetype": { "name": self.issue_type },
+                "summary": draft.summary,
+                "description": draft.description,
+                "labels": ["noodle"],
+            }
+        });
+        let request = self
+            .client
+            .post(format!("{}/rest/api/2/issue", self.base_url))
+            .basic_auth(&self.email, Some(&self.token))
+            .json(&body);
+        let created: CreatedIssue = send(request, "Jira").await?;
+        Ok(RemoteTicket {
+            url: format!("{}/browse/{}", self.base_url, created.key),
+            key: created.key,
+        })
+    }
+}
@@ -0,0 +1,234 @@
+pub mod azure_devops;
+pub mod jira;
+
+use azure_devops::AzureDevopsClient;
+use jira::JiraClient;
+use noodle_core::error::{NoodleError, Result};
+use noodle_core::types::{IssueKind, IssueTicket, Severity, TicketTracker};
+use serde::de::DeserializeOwned;
+use sha2::{Digest, Sha256};
+use std::fmt::Write as _;
+use std::sync::Arc;
+use std::time::Duration;
+use storage::sqlite::SqliteStorage;
+use tracing::info;
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+/// A reservation this old was left by a run that never finished filing.
+const RESERVATION_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// A ticket as sent to a tracker.
+pub struct TicketDraft {
+    /// Jira project key or Azure DevOps project name.
+    pub project: String,
+    pub summary: String,
+    pub description: String,
+}
+
+/// What a tracker returned for a new ticket.
+pub struct RemoteTicket {
+    pub key: String,
+    pub url: String,
+}
+
+/// Files extracted risks, issues and blockers as tickets in Jira or Azure
+/// DevOps. The description cites the email the problem came from, and the
+/// ticket is remembered by [`item_hash`], so the issue links to it and is
+/// not filed twice, even once re-extraction has moved it in the list.
+pub struct TicketService {
+    sqlite: Arc<SqliteStorage>,
+}
+
+impl TicketService {
+    pub fn new(sqlite: Arc<SqliteStorage>) -> Self {
+        Self { sqlite }
+    }
+
+    /// Files the `position`th risk, issue or blocker of an email in
+    /// `tracker`, under `project` or the configured default project.
+    pub async fn create_from_issue(
+        &self,
+        email_id: i64,
+        kind: IssueKind,
+        position: usize,
+        tracker: TicketTracker,
+        project: Option<&str>,
+    ) -> Result<IssueTicket> {
+        let facts = self
+            .sqlite
+            .get_issue_facts(email_id)
+            .await?
+            .ok_or_else(|| NoodleError::Validation("Email has no extracted facts".to_string()))?;
+        let found = match kind {
+            IssueKind::Risk => facts
+                .risks
+                .get(position)
+                .map(|r| (&r.title, &r.details, &r.owner, &r.severity)),
+            IssueKind::Issue => facts
+                .issues
+                .get(position)
+                .map(|i| (&i.title, &i.details, &i.owner, &i.severity)),
+            IssueKind::Blocker => facts
+                .blockers
+                .get(position)
+                .map(|b| (&b.title, &b.details, &b.owner, &b.severity)),
+        };
+        let (title, details, owner, severity) =
+            found.ok_or_else(|| NoodleError::Validation(format!("Email has no such {}", kind)))?;
+        let hash = item_hash(kind, title);
+        if let Some(ticket) = self
+            .sqlite
+            .get_issue_ticket(email_id, kind, position, &hash, tracker)
+            .await?
+        {
+            return Err(NoodleError::Validation(format!(
+                "Already filed as {}",
+                ticket.ticket_key
+            )));
+        }
+        let email = self
+            .sqlite
+            .get_email_detail(email_id)
+            .await?
+            .ok_or_else(|| NoodleError::Validation("Email not found".to_string()))?;
+
+        let config = self.sqlite.get_all_config().await?;
+        let default_project = match tracker {
+            TicketTracker::Jira => config.jira_project_key.as_deref(),
+            TicketTracker::AzureDevops => config.azure_devops_project.as_deref(),
+        };
+        let project = project
+            .map(str::trim)
+            .filter(|p| !p.is_empty())
+            .or(default_project)
+            .ok_or_else(|| NoodleError::Validation("No project to file the ticket in".to_string()))?
+            .to_string();
+
+        let tz = self.sqlite.get_user_timezone().await?;
+        let locale = self.sqlite.get_user_locale().await?;
+        let mut description = String::new();
+        if !details.trim().is_empty() {
+            let _ = writeln!(description, "{}\n", details.trim());
+        }
+        let _ = writeln!(
+            description,
+            "{}: {}",
+            kind_label(kind),
+            severity_label(severity)
+        );
+        if let Some(owner) = owner.as_deref().filter(|o| !o.trim().is_empty()) {
+            let _ = writeln!(description, "Owner: {}", owner);
+        }
+        if let Some(project) = facts.project.as_deref() {
+            let _ = writeln!(description, "Project: {}", project);
+        }
+        let _ = write!(
+            description,
+            "\nSource: \"{}\" from {}, received {} (noodle://email/{})",
+            email.subject,
+            email.sender,
+            locale.format_datetime(&tz.to_local(email.received_at)),
+            email.id
+        );
+        let draft = TicketDraft {
+            project,
+            summary: title.trim().to_string(),
+            description,
+        };
+
+        let reservation = self
+            .sqlite
+            .reserve_issue_ticket(
+                email_id,
+                kind,
+                position,
+                &hash,
+                tracker,
+                chrono::Duration::from_std(RESERVATION_TIMEOUT).unwrap_or_default(),
+            )
+            .await?
+            .ok_or_else(|| {
+                NoodleError::Validation(format!("This {} is already being filed", kind))
+            })?;
+        let remote = match tracker {
+            TicketTracker::Jira => match JiraClient::from_config(&config) {
+                Ok(client) => client.create_issue(&draft).await,
+                Err(e) => Err(e),
+            },
+            TicketTracker::AzureDevops => match AzureDevopsClient::from_config(&config) {
+                Ok(client) => client.create_work_item(&draft).await,
+                Err(e) => Err(e),
+            },
+        };
+        let remote = match remote {
+            Ok(remote) => remote,
+            Err(e) => {
+                self.sqlite.release_issue_ticket(reservation).await?;
+                return Err(e);
+            }
+        };
+        info!(
+            "Filed {} {} of email {} as {} {}",
+            kind, position, email_id, tracker, remote.key
+        );
+        self.sqlite
+            .complete_issue_ticket(reservation, &remote.key, &remote.url)
+            .await
+    }
+}
+
+/// Identifies an extracted item across re-extractions, which may reorder
+/// the list it is in: a hash of its kind and its title, ignoring case and
+/// spacing.
+pub fn item_hash(kind: IssueKind, title: &str) -> String {
+    let title = title
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase();
+    let mut hasher = Sha256::new();
+    hasher.update(kind.to_string());
+    hasher.update("\n");
+    hasher.update(title);
+    format!("{:x}", hasher.finalize())
+}
+
+fn kind_label(kind: IssueKind) -> &'static str {
+    match kind {
+        IssueKind::Risk => "Risk",
+        IssueKind::Issue => "Issue",
+        IssueKind::Blocker => "Blocker",
+    }
+}
+
+fn severity_label(severity: &Severity) -> &'static str {
+    match severity {
+        Severity::Low => "low severity",
+        Severity::Medium => "medium severity",
+        Severity::High => "high severity",
+    }
+}
+
+/// Sends a request to `tracker` and reads its JSON reply, turning error
+/// statuses into errors carrying the reply's text.
+async fn send<T: DeserializeOwned>(request: reqwest::RequestBuilder, tracker: &str) -> Result<T> {
+    let response = request
+        .timeout(REQUEST_TIMEOUT)
+        .send()
+        .await
+        .map_err(|e| NoodleError::Internal(format!("Failed to reach {}: {}", tracker, e)))?;
+    let status = response.status();
+    if !status.is_success() {
+        let text = response.text().await.unwrap_or_default();
+        return Err(NoodleError::Internal(format!(
+            "{} rejected the ticket ({}): {}",
+            tracker,
+            status,
+            text.trim()
+        )));
+    }
+    response
+        .json()
+        .await
+        .map_err(|e| NoodleError::Internal(format!("Unexpected reply from {}: {}", tracker, e)))
+}
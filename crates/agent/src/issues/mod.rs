@@ -0,0 +1,266 @@
+use crate::pipeline::pacing::PacingController;
+use ai::embedding::cosine;
+use ai::provider::AiProvider;
+use noodle_core::error::{NoodleError, Result};
+use noodle_core::types::{IssueKind, IssueMention, IssueStatus, Severity};
+use std::sync::Arc;
+use storage::sqlite::{IssueCentroid, IssueFacts, IssueUpdateFacts, SqliteStorage};
+use tokio::sync::{Mutex, RwLock};
+use tracing::info;
+
+/// Cosine similarity at which a mention joins an existing cluster.
+const SIMILARITY_THRESHOLD: f32 = 0.85;
+/// Cosine similarity at which a later email's summary or key point counts
+/// as reporting on a cluster; lower, as they are worded unlike the mentions.
+const UPDATE_THRESHOLD: f32 = 0.75;
+/// Emails read per query while grouping.
+const CLUSTER_BATCH: i64 = 100;
+
+/// Groups the risks, issues and blockers extracted from different emails
+/// into canonical issues: each mention is embedded from its title and
+/// details and joins the most similar cluster above
+/// [`SIMILARITY_THRESHOLD`], or starts a new one. Runs incrementally, so
+/// clusters keep their ids; emails are grouped again when re-extracted.
+///
+/// Each cluster's lifecycle is then followed through the emails after it:
+/// a later email that commits to something or resolves something is
+/// matched against the clusters raised before it, in its project, marking
+/// them mitigated or resolved until they are mentioned again.
+pub struct IssueClustering {
+    sqlite: Arc<SqliteStorage>,
+    ai: Arc<RwLock<Arc<dyn AiProvider>>>,
+    pacing: Arc<PacingController>,
+    /// Held by the run in progress; two runs would each start clusters for
+    /// the same mentions.
+    running: Mutex<()>,
+}
+
+impl IssueClustering {
+    pub fn new(
+        sqlite: Arc<SqliteStorage>,
+        ai: Arc<RwLock<Arc<dyn AiProvider>>>,
+        pacing: Arc<PacingController>,
+    ) -> Self {
+        Self {
+            sqlite,
+            ai,
+            pacing,
+            running: Mutex::new(()),
+        }
+    }
+
+    /// Groups every email not grouped yet, then brings the clusters'
+    /// statuses up to date. Returns how many mentions were added; fails
+    /// when a run is already in progress.
+    pub async fn run(&self) -> Result<usize> {
+        let Ok(_running) = self.running.try_lock() else {
+            return Err(NoodleError::Validation(
+                "Issues are already being grouped".into(),
+            ));
+        };
+        let ai = self.ai.read().await.clone();
+        let mut centroids = self.sqlite.list_issue_centroids().await?;
+        let mut added = 0;
+        loop {
+            let batch = self
+                .sqlite
+                .list_unclustered_issue_facts(CLUSTER_BATCH)
+                .await?;
+            if batch.is_empty() {
+                break;
+            }
+            for facts in batch {
+                self.clear(&mut centroids, facts.email_id).await?;
+                for mention in mentions(&facts) {
+                    let text = format!("{}\n{}", mention.title, mention.details);
+                    let embedding = self.embed(ai.as_ref(), text.trim()).await?;
+                    match closest(&centroids, &embedding, SIMILARITY_THRESHOLD) {
+                        Some(i) => {
+                            let cluster = &mut centroids[i];
+                            cluster.centroid = mean(cluster, &embedding);
+                            cluster.mentions += 1;
+                            self.sqlite
+                                .add_issue_mention(
+                                    cluster.id,
+                                    &mention,
+                                    &embedding,
+                                    &cluster.centroid,
+                                )
+                                .await?;
+                        }
+                        None => {
+                            let id = self
+                                .sqlite
+                                .create_issue_cluster(&mention, &embedding)
+                                .await?;
+                            centroids.push(IssueCentroid {
+                                id,
+                                centroid: embedding,
+                                mentions: 1,
+                            });
+                        }
+                    }
+                    added += 1;
+                }
+                self.sqlite.mark_issues_clustered(facts.email_id).await?;
+            }
+        }
+        let removed = self.sqlite.delete_empty_issue_clusters().await?;
+        info!(
+            "Grouped {} issue mentions, removed {} emptied clusters",
+            added, removed
+        );
+
+        loop {
+            let batch = self
+                .sqlite
+                .list_untracked_issue_updates(CLUSTER_BATCH)
+                .await?;
+            if batch.is_empty() {
+                break;
+            }
+            for update in batch {
+                self.track(ai.as_ref(), &update).await?;
+            }
+        }
+        let changed = self.sqlite.refresh_issue_statuses().await?;
+        info!("{} issue clusters changed status", changed);
+        Ok(added)
+    }
+
+    /// Takes an email's earlier mentions out of their clusters, in
+    /// `centroids` and in storage.
+    async fn clear(&self, centroids: &mut [IssueCentroid], email_id: i64) -> Result<()> {
+        let mut changed: Vec<IssueCentroid> = Vec::new();
+        for (cluster_id, embedding) in self.sqlite.list_issue_mention_embeddings(email_id).await? {
+            let Some(cluster) = centroids.iter_mut().find(|c| c.id == cluster_id) else {
+                continue;
+            };
+            if let Some(embedding) = embedding {
+                cluster.centroid = without(cluster, &embedding);
+            }
+            cluster.mentions = (cluster.mentions - 1).max(0);
+            changed.retain(|c| c.id != cluster_id);
+            changed.push(cluster.clone());
+        }
+        self.sqlite.clear_issue_mentions(email_id, &changed).await
+    }
+
+    async fn embed(&self, ai: &dyn AiProvider, text: &str) -> Result<Vec<f32>> {
+        let embedding = self
+            .pacing
+            .llm_call("embedding", ai.generate_embedding(text))
+            .await?;
+        Ok(normalized(embedding))
+    }
+
+    /// Matches an email's summary and each key point against the clusters
+    /// it could be reporting on.
+    async fn track(&self, ai: &dyn AiProvider, update: &IssueUpdateFacts) -> Result<()> {
+        let status = if update.intent == "resolve" {
+            IssueStatus::Resolved
+        } else {
+            IssueStatus::Mitigated
+        };
+        let candidates = self
+            .sqlite
+            .list_issue_centroids_before(update.received_at, update.project.as_deref())
+            .await?;
+        let mut matched = Vec::new();
+        if !candidates.is_empty() {
+            let texts = std::iter::once(&update.summary).chain(&update.key_points);
+            for text in texts.map(|t| t.trim()).filter(|t| !t.is_empty()) {
+                let embedding = self.embed(ai, text).await?;
+                if let Some(i) = closest(&candidates, &embedding, UPDATE_THRESHOLD) {
+                    if !matched.contains(&candidates[i].id) {
+                        matched.push(candidates[i].id);
+                    }
+                }
+            }
+        }
+        self.sqlite
+            .save_issue_updates(update.email_id, update.received_at, status, &matched)
+            .await
+    }
+}
+
+/// An email's risks, issues and blockers.
+fn mentions(facts: &IssueFacts) -> Vec<IssueMention> {
+    let mention = |kind, position, title: &str, details: &str, severity: &Severity| IssueMention {
+        email_id: facts.email_id,
+        kind,
+        position,
+        title: title.to_string(),
+        details: details.to_string(),
+        severity: severity.clone(),
+        project: facts.project.clone(),
+        received_at: facts.received_at,
+    };
+    let risks = facts
+        .risks
+        .iter()
+        .enumerate()
+        .map(|(i, r)| mention(IssueKind::Risk, i, &r.title, &r.details, &r.severity));
+    let issues = facts
+        .issues
+        .iter()
+        .enumerate()
+        .map(|(i, x)| mention(IssueKind::Issue, i, &x.title, &x.details, &x.severity));
+    let blockers = facts
+        .blockers
+        .iter()
+        .enumerate()
+        .map(|(i, b)| mention(IssueKind::Blocker, i, &b.title, &b.details, &b.severity));
+    risks
+        .chain(issues)
+        .chain(blockers)
+        .filter(|m| !m.title.trim().is_empty())
+        .collect()
+}
+
+/// The cluster most similar to `embedding`, if any reaches `threshold`.
+/// Centroids of another embedding model never match.
+pub fn closest(centroids: &[IssueCentroid], embedding: &[f32], threshold: f32) -> Option<usize> {
+    centroids
+        .iter()
+        .enumerate()
+        .filter(|(_, c)| c.centroid.len() == embedding.len())
+        .map(|(i, c)| (i, cosine(&c.centroid, embedding)))
+        .filter(|(_, similarity)| *similarity >= threshold)
+        .max_by(|a, b| a.1.total_cmp(&b.1))
+        .map(|(i, _)| i)
+}
+
+fn normalized(mut v: Vec<f32>) -> Vec<f32> {
+    let norm = v.iter().map(|x| x * x).sum::<f32>().sqrt();
+    if norm > 0.0 {
+        v.iter_mut().for_each(|x| *x /= norm);
+    }
+    v
+}
+
+/// The cluster's mean with `embedding` added.
+pub fn mean(cluster: &IssueCentroid, embedding: &[f32]) -> Vec<f32> {
+    let n = cluster.mentions as f32;
+    cluster
+        .centroid
+        .iter()
+        .zip(embedding)
+        .map(|(c, e)| (c * n + e) / (n + 1.0))
+        .collect()
+}
+
+/// The cluster's mean with `embedding` taken out again. A cluster losing
+/// its last mention keeps its mean; it is deleted once the run is over.
+pub fn without(cluster: &IssueCentroid, embedding: &[f32]) -> Vec<f32> {
+    let n = cluster.mentions as f32;
+    if n <= 1.0 || cluster.centroid.len() != embedding.len() {
+        return cluster.centroid.clone();
+    }
+    cluster
+        .centroid
+        .iter()
+        .zip(embedding)
+        .map(|(c, e)| (c * n - e) / (n - 1.0))
+        .collect()
+}
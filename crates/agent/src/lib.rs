@@ -1,2 +1,13 @@
+pub mod archive;
+pub mod digest;
 pub mod engine;
+pub mod graph;
+pub mod integrations;
+pub mod issues;
+pub mod meetings;
 pub mod pipeline;
+pub mod prompts;
+pub mod report;
+pub mod search;
+pub mod share;
+pub mod timeline;
@@ -0,0 +1,361 @@
+use crate::pipeline::dates;
+use crate::pipeline::pacing::PacingController;
+use ai::injection;
+use ai::provider::{AiProvider, ChatRequest, JsonSchemaFormat, Message, ResponseFormat};
+use chrono::{DateTime, Duration, Utc};
+use noodle_core::error::{NoodleError, Result};
+use noodle_core::text::SanitizedText;
+use noodle_core::types::{Meeting, MeetingTask, MeetingTaskKind};
+use outlook::client::OutlookClient;
+use serde_json::{json, Value};
+use std::sync::Arc;
+use storage::sqlite::{MeetingCandidate, SqliteStorage};
+use tokio::sync::RwLock;
+use tracing::{info, warn};
+
+/// How long after a meeting ends before its follow-up mail is looked for,
+/// so notes and recaps have time to arrive.
+const FOLLOW_UP_DELAY_HOURS: i64 = 2;
+/// How long after a meeting ends follow-up mail is looked for. Meetings
+/// without any by then are left without follow-ups.
+const FOLLOW_UP_WINDOW_DAYS: i64 = 3;
+/// Mail from this long before a meeting can be about it too: the invite
+/// thread, the agenda, pre-reads.
+const LEAD_IN_DAYS: i64 = 2;
+/// Emails around a meeting checked for being related.
+const MAX_CANDIDATES: i64 = 500;
+/// Related emails sent to the model; the newest are kept.
+const MAX_RELATED_EMAILS: usize = 10;
+/// Body characters sent to the model per email.
+const MAX_BODY_CHARS: usize = 3000;
+/// Shortest meeting subject matched against email subjects; shorter ones,
+/// like "1:1" or "Sync", say too little.
+const MIN_SUBJECT_CHARS: usize = 5;
+/// Prefixes mail clients put before a subject they reply to, forward or
+/// answer an invite with.
+const SUBJECT_PREFIXES: &[&str] = &[
+    "re:",
+    "fw:",
+    "fwd:",
+    "accepted:",
+    "declined:",
+    "tentative:",
+    "canceled:",
+    "cancelled:",
+    "updated:",
+];
+/// Invite responses; they carry nothing to follow up on.
+const RESPONSE_PREFIXES: &[&str] = &["accepted:", "declined:", "tentative:"];
+const FOLLOW_UPS_SCHEMA_NAME: &str = "meeting_follow_ups";
+
+/// Turns ended calendar meetings into follow-ups: the mail around each
+/// meeting, found by subject and by who it is between, is read for the
+/// decisions it records and the action items people promised, which are
+/// stored as tasks of the meeting.
+pub struct MeetingFollowUps {
+    sqlite: Arc<SqliteStorage>,
+    outlook: Arc<OutlookClient>,
+    ai: Arc<RwLock<Arc<dyn AiProvider>>>,
+    pacing: Arc<PacingController>,
+}
+
+impl MeetingFollowUps {
+    pub fn new(
+        sqlite: Arc<SqliteStorage>,
+        outlook: Arc<OutlookClient>,
+        ai: Arc<RwLock<Arc<dyn AiProvider>>>,
+        pacing: Arc<PacingController>,
+    ) -> Self {
+        Self {
+            sqlite,
+            outlook,
+            ai,
+            pacing,
+        }
+    }
+
+    /// Stores the meetings that ended within the follow-up window, then
+    /// extracts follow-ups for those past the delay whose related mail has
+    /// arrived. Returns how many meetings were followed up.
+    pub async fn run(&self, now: DateTime<Utc>) -> Result<usize> {
+        let window_start = now - Duration::days(FOLLOW_UP_WINDOW_DAYS);
+        for meeting in self.outlook.list_meetings(window_start, now).await? {
+            self.sqlite.upsert_meeting(&meeting).await?;
+        }
+
+        let due = self
+            .sqlite
+            .list_meetings_awaiting_follow_up(
+                window_start,
+                now - Duration::hours(FOLLOW_UP_DELAY_HOURS),
+            )
+            .await?;
+        let mut followed_up = 0;
+        for meeting in due {
+            match self.follow_up(&meeting, now).await {
+                Ok(true) => followed_up += 1,
+                Ok(false) => {}
+                Err(e) => warn!("Follow-up of meeting {} failed: {}", meeting.id, e),
+            }
+        }
+        Ok(followed_up)
+    }
+
+    /// Extracts the meeting's follow-ups from the related mail received by
+    /// `now`, replacing earlier ones. Returns false while no related mail
+    /// came after the meeting; the invite and agenda alone say nothing about
+    /// what it settled. A failed extraction is noted on the meeting.
+    pub async fn follow_up(&self, meeting: &Meeting, now: DateTime<Utc>) -> Result<bool> {
+        let related = self.related_emails(meeting, now).await?;
+        if !related
+            .iter()
+            .any(|email| email.received_at >= meeting.end_at)
+        {
+            return Ok(false);
+        }
+
+        let tasks = match self.extract(meeting, &related).await {
+            Ok(tasks) => tasks,
+            Err(e) => {
+                self.sqlite
+                    .record_meeting_follow_up_error(meeting.id, &e.to_string())
+                    .await?;
+                return Err(e);
+            }
+        };
+        let email_ids: Vec<i64> = related.iter().map(|e| e.id).collect();
+        self.sqlite
+            .save_meeting_follow_up(meeting.id, &email_ids, &tasks)
+            .await?;
+        info!(
+            "Followed up meeting {} from {} emails: {} tasks",
+            meeting.id,
+            email_ids.len(),
+            tasks.len()
+        );
+        Ok(true)
+    }
+
+    /// The emails about the meeting, oldest first.
+    async fn related_emails(
+        &self,
+        meeting: &Meeting,
+        now: DateTime<Utc>,
+    ) -> Result<Vec<MeetingCandidate>> {
+        let end = now.min(meeting.end_at + Duration::days(FOLLOW_UP_WINDOW_DAYS));
+        let candidates = self
+            .sqlite
+            .list_meeting_candidates(
+                meeting.start_at - Duration::days(LEAD_IN_DAYS),
+                end,
+                MAX_CANDIDATES,
+            )
+            .await?;
+        let mut related: Vec<MeetingCandidate> = candidates
+            .into_iter()
+            .filter(|email| is_related(meeting, email))
+            .take(MAX_RELATED_EMAILS)
+            .collect();
+        related.reverse();
+        Ok(related)
+    }
+
+    async fn extract(
+        &self,
+        meeting: &Meeting,
+        related: &[MeetingCandidate],
+    ) -> Result<Vec<MeetingTask>> {
+        let request = ChatRequest {
+            messages: vec![Message {
+                role: "user".into(),
+                content: follow_up_prompt(meeting, related),
+            }],
+            temperature: 0.0,
+            response_format: Some(ResponseFormat::JsonSchema {
+                json_schema: JsonSchemaFormat {
+                    name: FOLLOW_UPS_SCHEMA_NAME.into(),
+                    schema: follow_ups_schema(),
+                    strict: true,
+                },
+            }),
+            model: None,
+        };
+        let ai = self.ai.read().await.clone();
+        let content = self
+            .pacing
+            .llm_call("meeting_follow_ups", ai.chat_completion(request))
+            .await?
+            .content;
+        // Providers without structured output may wrap the object in prose
+        // or a code fence.
+        let object = content
+            .find('{')
+            .zip(content.rfind('}'))
+            .filter(|(start, end)| start < end)
+            .map(|(start, end)| &content[start..=end])
+            .unwrap_or(&content);
+        let output: Value = serde_json::from_str(object).map_err(|e| {
+            NoodleError::AI(format!("Meeting follow-ups were not valid JSON: {}", e))
+        })?;
+
+        let tz = self.sqlite.get_user_timezone().await?;
+        let anchor = tz.to_local(meeting.end_at);
+        let related_ids: Vec<i64> = related.iter().map(|e| e.id).collect();
+        let mut tasks = Vec::new();
+        for (key, kind) in [
+            ("decisions", MeetingTaskKind::Decision),
+            ("action_items", MeetingTaskKind::ActionItem),
+        ] {
+            for item in output[key].as_array().into_iter().flatten() {
+                let text = SanitizedText::new(item["text"].as_str().unwrap_or_default());
+                if text.is_empty() {
+                    continue;
+                }
+                let owner = item["owner"]
+                    .as_str()
+                    .map(SanitizedText::new)
+                    .filter(|o| !o.is_empty())
+                    .map(SanitizedText::into_string);
+                tasks.push(MeetingTask {
+                    id: 0,
+                    meeting_id: meeting.id,
+                    kind,
+                    text: text.into_string(),
+                    owner,
+                    due_by: item["due_by"]
+                        .as_str()
+                        .and_then(|s| dates::normalize_due_date(s, anchor)),
+                    email_id: item["email_id"]
+                        .as_i64()
+                        .filter(|id| related_ids.contains(id)),
+                    done: false,
+                    created_at: Utc::now(),
+                });
+            }
+        }
+        Ok(tasks)
+    }
+}
+
+/// An email is about a meeting when its subject carries the meeting's, or
+/// when it came after the meeting started and is between the same people:
+/// from or to at least two attendees, or the only one.
+pub fn is_related(meeting: &Meeting, email: &MeetingCandidate) -> bool {
+    let subject = email.subject.trim().to_lowercase();
+    if RESPONSE_PREFIXES.iter().any(|p| subject.starts_with(p)) {
+        return false;
+    }
+    let topic = normalize_subject(&meeting.subject);
+    if topic.chars().count() >= MIN_SUBJECT_CHARS && normalize_subject(&subject).contains(&topic) {
+        return true;
+    }
+    if email.received_at < meeting.start_at || meeting.attendees.is_empty() {
+        return false;
+    }
+
+    let recipients: Vec<String> = email
+        .to
+        .split(';')
+        .chain(email.cc.as_deref().unwrap_or_default().split(';'))
+        .map(|name| name.trim().to_lowercase())
+        .filter(|name| !name.is_empty())
+        .collect();
+    let shared = meeting
+        .attendees
+        .iter()
+        .filter(|attendee| {
+            attendee.address.eq_ignore_ascii_case(&email.sender)
+                || (!attendee.name.is_empty() && recipients.contains(&attendee.name.to_lowercase()))
+        })
+        .count();
+    shared >= meeting.attendees.len().min(2)
+}
+
+/// The subject without reply, forward and invite prefixes, lowercased,
+/// with whitespace collapsed.
+pub fn normalize_subject(subject: &str) -> String {
+    let mut subject = subject.trim().to_lowercase();
+    while let Some(prefix) = SUBJECT_PREFIXES.iter().find(|p| subject.starts_with(**p)) {
+        subject = subject[prefix.len()..].trim_start().to_string();
+    }
+    subject.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+fn follow_up_prompt(meeting: &Meeting, related: &[MeetingCandidate]) -> String {
+    let attendees: Vec<&str> = meeting
+        .attendees
+        .iter()
+        .map(|a| {
+            if a.name.is_empty() {
+                a.address.as_str()
+            } else {
+                a.name.as_str()
+            }
+        })
+        .collect();
+    let emails: Vec<String> = related
+        .iter()
+        .map(|email| {
+            let body: String = email.body_text.chars().take(MAX_BODY_CHARS).collect();
+            format!(
+                "Email id {}:\n{}",
+                email.id,
+                injection::data_block(&format!(
+                    "Subject: {}\nFrom: {}\nTo: {}\nReceived: {}\n\n{}",
+                    email.subject,
+                    email.sender,
+                    email.to,
+                    email.received_at.format("%Y-%m-%d %H:%M"),
+                    body
+                ))
+            )
+        })
+        .collect();
+    let details = injection::data_block(&format!(
+        "Subject: {}\nOrganizer: {}\nAttendees: {}",
+        meeting.subject,
+        meeting.organizer,
+        attendees.join(", ")
+    ));
+    format!(
+        "{}\n\n\
+         A meeting took place from {} to {} UTC:\n{}\n\n\
+         These are the emails around it, before and after:\n\n{}\n\n\
+         List what the meeting and its follow-up mail settled. Return a JSON object with \
+         \"decisions\": things agreed, each {{\"text\", \"owner\": null, \"due_by\": null, \
+         \"email_id\"}}, and \"action_items\": things someone promised to do, each \
+         {{\"text\", \"owner\": who does it or null, \"due_by\": the deadline as written or \
+         null, \"email_id\"}}. \"email_id\" is the id of the email it is stated in. Only \
+         include what the emails state; return empty lists when they settle nothing.",
+        injection::DATA_INSTRUCTION,
+        meeting.start_at.format("%Y-%m-%d %H:%M"),
+        meeting.end_at.format("%H:%M"),
+        details,
+        emails.join("\n\n")
+    )
+}
+
+/// JSON schema of the follow-up output, to the same strict rules as the
+/// extraction schema.
+fn follow_ups_schema() -> Value {
+    let item = json!({
+        "type": "object",
+        "properties": {
+            "text": { "type": "string" },
+            "owner": { "type": ["string", "null"] },
+            "due_by": { "type": ["string", "null"] },
+            "email_id": { "type": ["integer", "null"] },
+        },
+        "required": ["text", "owner", "due_by", "email_id"],
+        "additionalProperties": false,
+    });
+    json!({
+        "type": "object",
+        "properties": {
+            "decisions": { "type": "array", "items": item },
+            "action_items": { "type": "array", "items": item },
+        },
+        "required": ["decisions", "action_items"],
+        "additionalProperties": false,
+    })
+}
@@ -0,0 +1,200 @@
+use chrono::{Duration, Utc};
+use noodle_core::error::Result;
+use noodle_core::types::{Alert, AlertKind, Email, EmailFact, Sentiment, Urgency};
+use std::sync::Arc;
+use storage::sqlite::SqliteStorage;
+use tokio::sync::broadcast;
+use tracing::info;
+
+/// Hostile emails from one sender within the window that raise an alert.
+const HOSTILE_THRESHOLD: i64 = 3;
+const HOSTILE_WINDOW_HOURS: i64 = 48;
+/// High-urgency emails for one project within the window that raise an alert.
+const URGENT_THRESHOLD: i64 = 5;
+const URGENT_WINDOW_HOURS: i64 = 6;
+/// The newest extractions compared against the longer baseline.
+const CONFIDENCE_RECENT: usize = 20;
+const CONFIDENCE_BASELINE: i64 = 100;
+/// Share of the baseline confidence below which the recent average alerts.
+const CONFIDENCE_DROP_RATIO: f64 = 0.6;
+/// An alert is not repeated for the same kind and subject within this time.
+const COOLDOWN_HOURS: i64 = 24;
+/// VIP mail older than this, e.g. found by a backfill, is not announced.
+const VIP_EMAIL_HOURS: i64 = 24;
+/// Alerts kept for subscribers that fall behind.
+const CHANNEL_CAPACITY: usize = 32;
+/// Key of confidence alerts, which are about extraction as a whole.
+const CONFIDENCE_KEY: &str = "extraction";
+
+/// Watches newly extracted facts for patterns worth telling the user about:
+/// a sender turning hostile, a project flooded with urgent mail, or
+/// extraction confidence collapsing, and announces new mail from VIP
+/// senders. Raised alerts are stored and sent to subscribers. Mail of
+/// projects with muted notifications raises none, other than about
+/// extraction as a whole.
+pub struct AnomalyDetector {
+    sqlite: Arc<SqliteStorage>,
+    alerts: broadcast::Sender<Alert>,
+}
+
+impl AnomalyDetector {
+    pub fn new(sqlite: Arc<SqliteStorage>) -> Self {
+        let (alerts, _) = broadcast::channel(CHANNEL_CAPACITY);
+        Self { sqlite, alerts }
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<Alert> {
+        self.alerts.subscribe()
+    }
+
+    /// Checks the facts just saved for `email`. Sender and project spikes
+    /// are only looked for in recent mail, so a backfill of old mail does
+    /// not alert on spikes long past.
+    pub async fn check(&self, email: &Email, facts: &EmailFact) -> Result<()> {
+        let project = &facts.client_or_project.name;
+        if self.muted(Some(project)).await? {
+            return self.check_confidence(email.id).await;
+        }
+        let now = Utc::now();
+        if facts.sentiment == Sentiment::Hostile
+            && email.received_at >= now - Duration::hours(HOSTILE_WINDOW_HOURS)
+        {
+            let count = self
+                .sqlite
+                .count_sender_sentiment_since(
+                    &email.sender,
+                    &Sentiment::Hostile.to_string(),
+                    now - Duration::hours(HOSTILE_WINDOW_HOURS),
+                )
+                .await?;
+            if count >= HOSTILE_THRESHOLD {
+                let message = format!(
+                    "{} hostile emails from {} in the last {} hours",
+                    count, email.sender, HOSTILE_WINDOW_HOURS
+                );
+                self.raise(
+                    AlertKind::HostileSender,
+                    &email.sender,
+                    message,
+                    Some(email.id),
+                )
+                .await?;
+            }
+        }
+
+        if facts.urgency == Urgency::High
+            && !project.is_empty()
+            && email.received_at >= now - Duration::hours(URGENT_WINDOW_HOURS)
+        {
+            let count = self
+                .sqlite
+                .count_project_urgency_since(
+                    project,
+                    &Urgency::High.to_string(),
+                    now - Duration::hours(URGENT_WINDOW_HOURS),
+                )
+                .await?;
+            if count >= URGENT_THRESHOLD {
+                let message = format!(
+                    "{} high-urgency emails for {} in the last {} hours",
+                    count, project, URGENT_WINDOW_HOURS
+                );
+                self.raise(AlertKind::UrgentFlood, project, message, Some(email.id))
+                    .await?;
+            }
+        }
+
+        self.check_confidence(email.id).await
+    }
+
+    /// Announces new mail from a VIP sender.
+    pub async fn vip_email(&self, email: &Email) -> Result<()> {
+        if email.received_at < Utc::now() - Duration::hours(VIP_EMAIL_HOURS) {
+            return Ok(());
+        }
+        let project = self.sqlite.get_email_project(email.id).await?;
+        if self.muted(project.as_deref()).await? {
+            return Ok(());
+        }
+        let message = format!("{}: {}", email.sender, email.subject);
+        self.send(AlertKind::VipEmail, &email.sender, &message, Some(email.id))
+            .await
+    }
+
+    /// Compares the newest extractions' confidence with the longer baseline
+    /// they are part of.
+    async fn check_confidence(&self, email_id: i64) -> Result<()> {
+        let confidences = self
+            .sqlite
+            .list_recent_confidences(CONFIDENCE_BASELINE)
+            .await?;
+        if confidences.len() < CONFIDENCE_BASELINE as usize {
+            return Ok(());
+        }
+        let recent = average(&confidences[..CONFIDENCE_RECENT]);
+        let baseline = average(&confidences);
+        if recent < baseline * CONFIDENCE_DROP_RATIO {
+            let message = format!(
+                "Extraction confidence fell to {:.0}% over the last {} emails, from {:.0}% on average",
+                recent * 100.0,
+                CONFIDENCE_RECENT,
+                baseline * 100.0
+            );
+            self.raise(
+                AlertKind::ConfidenceDrop,
+                CONFIDENCE_KEY,
+                message,
+                Some(email_id),
+            )
+            .await?;
+        }
+        Ok(())
+    }
+
+    async fn muted(&self, project: Option<&str>) -> Result<bool> {
+        match project.filter(|p| !p.is_empty()) {
+            Some(project) => Ok(self
+                .sqlite
+                .get_project_settings(project)
+                .await?
+                .mute_notifications),
+            None => Ok(false),
+        }
+    }
+
+    /// Stores and sends an alert, unless the same one was raised within the
+    /// cooldown.
+    async fn raise(
+        &self,
+        kind: AlertKind,
+        key: &str,
+        message: String,
+        email_id: Option<i64>,
+    ) -> Result<()> {
+        if let Some(last) = self.sqlite.last_alert_at(kind, key).await? {
+            if last >= Utc::now() - Duration::hours(COOLDOWN_HOURS) {
+                return Ok(());
+            }
+        }
+        self.send(kind, key, &message, email_id).await
+    }
+
+    /// Stores and sends an alert.
+    async fn send(
+        &self,
+        kind: AlertKind,
+        key: &str,
+        message: &str,
+        email_id: Option<i64>,
+    ) -> Result<()> {
+        let alert = self.sqlite.save_alert(kind, key, message, email_id).await?;
+        info!("Alert ({}): {}", kind, message);
+        // Nobody listening is fine; the alert is stored either way.
+        let _ = self.alerts.send(alert);
+        Ok(())
+    }
+}
+
+fn average(values: &[f64]) -> f64 {
+    values.iter().sum::<f64>() / values.len().max(1) as f64
+}
@@ -0,0 +1,279 @@
+use noodle_core::error::{NoodleError, Result};
+use noodle_core::types::{Attachment, Email};
+use outlook::client::{OutlookClient, SavedAttachment};
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, HashSet};
+use std::fs::File;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+use storage::sqlite::SqliteStorage;
+use tracing::{info, warn};
+
+/// Where Outlook saves attachments before they are hashed and moved.
+const INCOMING_DIR: &str = "incoming";
+/// Characters of text kept from an attachment.
+const MAX_EXTRACTED_CHARS: usize = 50_000;
+/// MIME types besides `text/*` whose content is read as text.
+const TEXT_MIME_TYPES: &[&str] = &["application/json", "application/xml"];
+/// Words per embedded chunk of attachment text.
+const CHUNK_WORDS: usize = 200;
+/// Words each chunk repeats from the one before, so a passage split between
+/// two is still found.
+const CHUNK_OVERLAP_WORDS: usize = 40;
+/// Chunks embedded per attachment; the rest of a long text isn't searched.
+const MAX_CHUNKS: usize = 32;
+/// Files this recent are kept by garbage collection even when unlisted, as
+/// the email they belong to may still be being stored.
+const GC_GRACE: Duration = Duration::from_secs(60 * 60);
+/// Leftovers of interrupted saves older than this are removed.
+const INCOMING_MAX_AGE: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Keeps attachment content on disk rather than in SQLite, each file named
+/// by the SHA-256 of its content under a directory of the hash's first two
+/// characters, so an attachment sent many times is stored once. What
+/// `attachment_max_size_mb` and `attachment_mime_types` leave out is only
+/// listed. Files no email lists any more are removed by
+/// [`Self::collect_garbage`].
+pub struct AttachmentStore {
+    sqlite: Arc<SqliteStorage>,
+    outlook: Arc<OutlookClient>,
+    dir: PathBuf,
+}
+
+impl AttachmentStore {
+    pub fn new(sqlite: Arc<SqliteStorage>, outlook: Arc<OutlookClient>, dir: PathBuf) -> Self {
+        Self {
+            sqlite,
+            outlook,
+            dir,
+        }
+    }
+
+    /// Lists the email's attachments, storing and extracting the text of
+    /// those the policy allows. Those stored for the email before, by file
+    /// name and size, are kept rather than fetched again. Returns how many
+    /// are stored.
+    pub async fn store(&self, email: &Email) -> Result<usize> {
+        let policy = self.sqlite.get_all_config().await?.attachment_policy();
+        let incoming = self.dir.join(INCOMING_DIR);
+        tokio::fs::create_dir_all(&incoming)
+            .await
+            .map_err(|e| NoodleError::Storage(e.to_string()))?;
+        let mut previous = HashMap::new();
+        for attachment in self.sqlite.list_attachments(email.id).await? {
+            if attachment.hash.is_empty()
+                || !tokio::fs::try_exists(content_path(&self.dir, &attachment.hash))
+                    .await
+                    .unwrap_or(false)
+            {
+                continue;
+            }
+            previous.insert(
+                (attachment.filename.clone(), attachment.size_bytes),
+                attachment,
+            );
+        }
+        let saved = self
+            .outlook
+            .save_attachments(
+                &email.entry_id,
+                &email.store_id,
+                &policy,
+                &incoming,
+                previous.keys().cloned().collect(),
+            )
+            .await?;
+        if saved.is_empty() {
+            return Ok(0);
+        }
+
+        let mut attachments = Vec::with_capacity(saved.len());
+        let mut stored = 0;
+        for attachment in saved {
+            let (hash, extracted_text) = match &attachment.path {
+                Some(path) => {
+                    let path = path.clone();
+                    let dir = self.dir.clone();
+                    let mime = attachment.mime.clone();
+                    let result = tokio::task::spawn_blocking(move || keep(&dir, &path, &mime))
+                        .await
+                        .map_err(|e| NoodleError::Internal(e.to_string()))?;
+                    match result {
+                        Ok(kept) => {
+                            stored += 1;
+                            kept
+                        }
+                        Err(e) => {
+                            warn!(
+                                "Failed to store attachment {} of email {}: {}",
+                                attachment.filename, email.id, e
+                            );
+                            (String::new(), None)
+                        }
+                    }
+                }
+                None if attachment.skipped_reason.is_none() => {
+                    match previous.remove(&(attachment.filename.clone(), attachment.size_bytes)) {
+                        Some(kept) => {
+                            stored += 1;
+                            (kept.hash, kept.extracted_text)
+                        }
+                        None => (String::new(), None),
+                    }
+                }
+                None => (String::new(), None),
+            };
+            attachments.push(to_attachment(email.id, attachment, hash, extracted_text));
+        }
+        self.sqlite
+            .replace_attachments(email.id, &attachments)
+            .await?;
+        Ok(stored)
+    }
+
+    /// Removes the files no email lists, and leftovers of interrupted saves.
+    /// Returns how many files were removed.
+    pub async fn collect_garbage(&self) -> Result<usize> {
+        let listed = self.sqlite.list_attachment_hashes().await?;
+        let dir = self.dir.clone();
+        let removed = tokio::task::spawn_blocking(move || remove_unlisted(&dir, &listed))
+            .await
+            .map_err(|e| NoodleError::Internal(e.to_string()))?;
+        if removed > 0 {
+            info!("Removed {} unreferenced attachment files", removed);
+        }
+        Ok(removed)
+    }
+}
+
+/// Removes from `dir` the content files whose hash isn't in `listed`, and
+/// leftovers of interrupted saves. Files modified within [`GC_GRACE`] are
+/// kept either way. Returns how many files were removed.
+pub fn remove_unlisted(dir: &Path, listed: &HashSet<String>) -> usize {
+    let mut removed = 0;
+    for (path, age) in files_under(&dir.join(INCOMING_DIR)) {
+        if age > INCOMING_MAX_AGE && std::fs::remove_file(&path).is_ok() {
+            removed += 1;
+        }
+    }
+    let Ok(shards) = std::fs::read_dir(dir) else {
+        return removed;
+    };
+    for shard in shards.flatten() {
+        if shard.file_name() == INCOMING_DIR {
+            continue;
+        }
+        for (path, age) in files_under(&shard.path()) {
+            let hash = path
+                .file_name()
+                .and_then(|n| n.to_str())
+                .unwrap_or_default();
+            if age > GC_GRACE && !listed.contains(hash) && std::fs::remove_file(&path).is_ok() {
+                removed += 1;
+            }
+        }
+    }
+    removed
+}
+
+/// Where the content with this hash is kept under `dir`.
+pub fn content_path(dir: &Path, hash: &str) -> PathBuf {
+    dir.join(&hash[..2]).join(hash)
+}
+
+/// Hashes the saved file and moves it to its place in `dir`, or drops it
+/// when the same content is stored already. Returns the hash and, for text,
+/// the content.
+pub fn keep(dir: &Path, saved: &Path, mime: &str) -> Result<(String, Option<String>)> {
+    let storage = |e: std::io::Error| NoodleError::Storage(e.to_string());
+    let mut hasher = Sha256::new();
+    std::io::copy(&mut File::open(saved).map_err(storage)?, &mut hasher).map_err(storage)?;
+    let hash = format!("{:x}", hasher.finalize());
+
+    let target = content_path(dir, &hash);
+    if target.exists() {
+        std::fs::remove_file(saved).map_err(storage)?;
+        // Counts as new for garbage collection, which may be about to
+        // remove it as unlisted.
+        File::options()
+            .write(true)
+            .open(&target)
+            .and_then(|f| f.set_modified(SystemTime::now()))
+            .map_err(storage)?;
+    } else {
+        std::fs::create_dir_all(dir.join(&hash[..2])).map_err(storage)?;
+        std::fs::rename(saved, &target).map_err(storage)?;
+    }
+
+    let is_text = mime.starts_with("text/") || TEXT_MIME_TYPES.contains(&mime);
+    let extracted_text = if is_text {
+        let mut bytes = Vec::new();
+        File::open(&target)
+            .and_then(|f| {
+                f.take(MAX_EXTRACTED_CHARS as u64 * 4)
+                    .read_to_end(&mut bytes)
+            })
+            .map_err(storage)?;
+        Some(
+            String::from_utf8_lossy(&bytes)
+                .chars()
+                .take(MAX_EXTRACTED_CHARS)
+                .collect(),
+        )
+    } else {
+        None
+    };
+    Ok((hash, extracted_text))
+}
+
+/// Splits attachment text into overlapping chunks of [`CHUNK_WORDS`] words
+/// for embedding, at most [`MAX_CHUNKS`].
+pub fn chunk_text(text: &str) -> Vec<String> {
+    let words: Vec<&str> = text.split_whitespace().collect();
+    let mut chunks = Vec::new();
+    let mut start = 0;
+    while start < words.len() && chunks.len() < MAX_CHUNKS {
+        let end = (start + CHUNK_WORDS).min(words.len());
+        chunks.push(words[start..end].join(" "));
+        if end == words.len() {
+            break;
+        }
+        start = end - CHUNK_OVERLAP_WORDS;
+    }
+    chunks
+}
+
+/// The files directly in `dir` with how long ago each was modified.
+fn files_under(dir: &Path) -> Vec<(PathBuf, Duration)> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    entries
+        .flatten()
+        .filter_map(|entry| {
+            let metadata = entry.metadata().ok().filter(|m| m.is_file())?;
+            let age = metadata.modified().ok()?.elapsed().unwrap_or_default();
+            Some((entry.path(), age))
+        })
+        .collect()
+}
+
+fn to_attachment(
+    email_id: i64,
+    saved: SavedAttachment,
+    hash: String,
+    extracted_text: Option<String>,
+) -> Attachment {
+    Attachment {
+        id: 0,
+        email_id,
+        filename: saved.filename,
+        mime: saved.mime,
+        size_bytes: saved.size_bytes,
+        extracted_text,
+        hash,
+        skipped_reason: saved.skipped_reason,
+    }
+}
@@ -0,0 +1,217 @@
+use super::ExtractionPipeline;
+use chrono::{DateTime, Utc};
+use noodle_core::error::{NoodleError, Result};
+use noodle_core::events::{AppEvent, BulkProgress, Notifier};
+use noodle_core::types::{BulkOperation, BulkResult, TriageState};
+use std::collections::HashSet;
+use storage::qdrant::COLLECTION_EMAILS;
+use tracing::{info, warn};
+
+/// Most emails one bulk action takes, so a stray select-all can't tie up
+/// the pipeline for hours.
+const MAX_BULK_EMAILS: usize = 1000;
+
+impl ExtractionPipeline {
+    /// Sets the triage state of the selected emails in one transaction.
+    pub async fn bulk_set_triage(
+        &self,
+        ids: &[i64],
+        state: TriageState,
+        notifier: &dyn Notifier,
+    ) -> Result<BulkResult> {
+        let ids = selection(ids)?;
+        let mut progress = Progress::start(BulkOperation::SetTriage, ids.len(), notifier);
+        let updated = self.sqlite.set_triage_state(&ids, state).await? as usize;
+        progress.complete();
+        Ok(progress.finish(updated, Vec::new()))
+    }
+
+    /// Files the selected emails under `project` in one transaction.
+    pub async fn bulk_reassign_project(
+        &self,
+        ids: &[i64],
+        project: &str,
+        notifier: &dyn Notifier,
+    ) -> Result<BulkResult> {
+        let project = project.trim();
+        if project.is_empty() {
+            return Err(NoodleError::Validation("Project name is required".into()));
+        }
+        let ids = selection(ids)?;
+        let mut progress = Progress::start(BulkOperation::ReassignProject, ids.len(), notifier);
+        let updated = self.sqlite.reassign_project(&ids, project).await? as usize;
+        self.request_payload_sync();
+        progress.complete();
+        Ok(progress.finish(updated, Vec::new()))
+    }
+
+    /// Reruns extraction and embedding for the selected emails one after
+    /// another, reporting progress after each. An email failing doesn't
+    /// stop the rest; shutting down does.
+    pub async fn bulk_reprocess(&self, ids: &[i64], notifier: &dyn Notifier) -> Result<BulkResult> {
+        let ids = selection(ids)?;
+        let mut progress = Progress::start(BulkOperation::Reprocess, ids.len(), notifier);
+        let mut updated = 0;
+        let mut failed = Vec::new();
+        for &id in &ids {
+            if self.shutdown.is_shutting_down() {
+                break;
+            }
+            match self.reprocess_email(id).await {
+                Ok(()) => updated += 1,
+                Err(e) => {
+                    warn!("Bulk reprocessing of email {} failed: {}", id, e);
+                    failed.push(id);
+                }
+            }
+            progress.advance(failed.len());
+        }
+        Ok(progress.finish(updated, failed))
+    }
+
+    /// Moves the selected emails to the trash in one transaction. Outlook
+    /// is left alone.
+    pub async fn bulk_trash(&self, ids: &[i64], notifier: &dyn Notifier) -> Result<BulkResult> {
+        let ids = selection(ids)?;
+        let mut progress = Progress::start(BulkOperation::Trash, ids.len(), notifier);
+        let updated = self.sqlite.trash_emails(&ids).await? as usize;
+        progress.complete();
+        Ok(progress.finish(updated, Vec::new()))
+    }
+
+    /// Deletes the selected emails for good in one transaction, then
+    /// removes their vectors. Outlook is left alone, and scans skip the
+    /// purged emails from then on.
+    pub async fn bulk_purge(&self, ids: &[i64], notifier: &dyn Notifier) -> Result<BulkResult> {
+        let ids = selection(ids)?;
+        let mut progress = Progress::start(BulkOperation::Purge, ids.len(), notifier);
+        let purged = self.purge(&ids).await?;
+        progress.complete();
+        Ok(progress.finish(purged, Vec::new()))
+    }
+
+    /// Purges the emails that have been in the trash past the retention
+    /// period. Returns how many were purged.
+    pub async fn purge_expired_trash(&self, now: DateTime<Utc>) -> Result<usize> {
+        let expired = self.sqlite.list_expired_trash(now).await?;
+        let purged = self.purge(&expired).await?;
+        if purged > 0 {
+            info!("Purged {} emails from the trash", purged);
+        }
+        Ok(purged)
+    }
+
+    /// Purges the emails of projects with their own retention that are older
+    /// than it. Returns how many were purged.
+    pub async fn purge_expired_project_mail(&self, now: DateTime<Utc>) -> Result<usize> {
+        let expired = self.sqlite.list_expired_project_emails(now).await?;
+        let purged = self.purge(&expired).await?;
+        if purged > 0 {
+            info!("Purged {} emails past their project's retention", purged);
+        }
+        Ok(purged)
+    }
+
+    /// Purges `ids` from SQLite and their vectors from Qdrant. Returns how
+    /// many of the emails existed.
+    async fn purge(&self, ids: &[i64]) -> Result<usize> {
+        let purged = self.sqlite.purge_emails(ids).await?;
+
+        // SQLite is what search and the dashboards go by, so vectors left
+        // behind by a failure here are only orphans for vector repair.
+        let points: Vec<u64> = purged
+            .iter()
+            .map(|key| {
+                self.qdrant
+                    .calculate_stable_id(&key.store_id, &key.entry_id)
+            })
+            .collect();
+        if let Err(e) = self
+            .qdrant
+            .delete_point_ids(COLLECTION_EMAILS, &points)
+            .await
+        {
+            warn!("Failed to delete vectors of purged emails: {}", e);
+        }
+        for key in &purged {
+            if let Err(e) = self
+                .qdrant
+                .replace_attachment_vectors(key.id, Vec::new())
+                .await
+            {
+                warn!(
+                    "Failed to delete attachment vectors of purged email {}: {}",
+                    key.id, e
+                );
+            }
+        }
+        Ok(purged.len())
+    }
+}
+
+/// The selected ids without repeats, in the order given.
+fn selection(ids: &[i64]) -> Result<Vec<i64>> {
+    let mut seen = HashSet::new();
+    let ids: Vec<i64> = ids.iter().copied().filter(|id| seen.insert(*id)).collect();
+    if ids.is_empty() {
+        return Err(NoodleError::Validation("No emails selected".into()));
+    }
+    if ids.len() > MAX_BULK_EMAILS {
+        return Err(NoodleError::Validation(format!(
+            "Bulk actions are limited to {} emails at a time",
+            MAX_BULK_EMAILS
+        )));
+    }
+    Ok(ids)
+}
+
+/// Reports a bulk action as [`AppEvent::BulkProgress`] events: one when it
+/// starts, then one per email or, for actions run in one transaction, one
+/// once it is done.
+struct Progress<'a> {
+    operation: BulkOperation,
+    done: usize,
+    total: usize,
+    notifier: &'a dyn Notifier,
+}
+
+impl<'a> Progress<'a> {
+    fn start(operation: BulkOperation, total: usize, notifier: &'a dyn Notifier) -> Self {
+        let progress = Self {
+            operation,
+            done: 0,
+            total,
+            notifier,
+        };
+        progress.notify(0);
+        progress
+    }
+
+    fn advance(&mut self, failed: usize) {
+        self.done += 1;
+        self.notify(failed);
+    }
+
+    fn complete(&mut self) {
+        self.done = self.total;
+        self.notify(0);
+    }
+
+    fn finish(self, updated: usize, failed: Vec<i64>) -> BulkResult {
+        BulkResult {
+            operation: self.operation,
+            requested: self.total,
+            updated,
+            failed,
+        }
+    }
+
+    fn notify(&self, failed: usize) {
+        self.notifier.notify(AppEvent::BulkProgress(BulkProgress {
+            operation: self.operation,
+            done: self.done,
+            total: self.total,
+            failed,
+        }));
+    }
+}
@@ -0,0 +1,96 @@
+use noodle_core::error::Result;
+use noodle_core::types::{CategoryAction, CategoryMatch, CategoryRule, Email, EmailFact};
+use outlook::client::OutlookClient;
+use std::sync::Arc;
+use storage::sqlite::SqliteStorage;
+use tracing::{info, warn};
+
+/// Gives newly extracted emails the categories of the user's rules, and sets
+/// them on the Outlook item for rules that opt in. Everything it does is
+/// recorded in the category audit log.
+pub struct CategoryLabeler {
+    sqlite: Arc<SqliteStorage>,
+    outlook: Arc<OutlookClient>,
+}
+
+impl CategoryLabeler {
+    pub fn new(sqlite: Arc<SqliteStorage>, outlook: Arc<OutlookClient>) -> Self {
+        Self { sqlite, outlook }
+    }
+
+    /// Labels `email` from its saved `facts`. Categories no longer matching
+    /// after a re-extraction are dropped in Noodle but left in Outlook.
+    pub async fn label(&self, email: &Email, facts: &EmailFact) -> Result<()> {
+        let rules = self.sqlite.list_category_rules().await?;
+        let matched: Vec<&CategoryRule> = rules
+            .iter()
+            .filter(|rule| matches(rule, email, facts))
+            .collect();
+        let labels: Vec<(i64, String)> = matched
+            .iter()
+            .map(|rule| (rule.id, rule.category.clone()))
+            .collect();
+        let added = self
+            .sqlite
+            .replace_email_categories(email.id, &labels)
+            .await?;
+        if !added.is_empty() {
+            info!("Labelled email {} with {}", email.id, added.join(", "));
+        }
+
+        for rule in matched.into_iter().filter(|rule| rule.write_back) {
+            self.write_back(email, rule).await?;
+        }
+        Ok(())
+    }
+
+    /// Sets the rule's category on the Outlook item. A failed write is
+    /// audited rather than returned, so it doesn't fail the email.
+    async fn write_back(&self, email: &Email, rule: &CategoryRule) -> Result<()> {
+        let written = self
+            .outlook
+            .add_category(&email.entry_id, &email.store_id, &rule.category, rule.color)
+            .await;
+        let (action, detail) = match written {
+            Ok(true) => (CategoryAction::Written, None),
+            Ok(false) => (CategoryAction::AlreadySet, None),
+            Err(e) => {
+                warn!(
+                    "Failed to set category {} on email {} in Outlook: {}",
+                    rule.category, email.id, e
+                );
+                (CategoryAction::WriteFailed, Some(e.to_string()))
+            }
+        };
+        if action == CategoryAction::Written {
+            // Saving the item changed its modification time; it has nothing
+            // new for the scans.
+            self.sqlite.touch_last_indexed(email.id).await?;
+        }
+        self.sqlite
+            .record_category_audit(
+                action,
+                Some(rule.id),
+                Some(email.id),
+                &rule.category,
+                detail.as_deref(),
+            )
+            .await
+    }
+}
+
+fn matches(rule: &CategoryRule, email: &Email, facts: &EmailFact) -> bool {
+    let pattern = rule.pattern.trim().to_lowercase();
+    match rule.match_on {
+        CategoryMatch::Project => facts.client_or_project.name.to_lowercase() == pattern,
+        CategoryMatch::Sender => {
+            let sender = email.sender.to_lowercase();
+            if pattern.starts_with('@') {
+                sender.ends_with(&pattern)
+            } else {
+                sender == pattern
+            }
+        }
+        CategoryMatch::PrimaryType => facts.primary_type.to_string() == pattern,
+    }
+}
@@ -0,0 +1,189 @@
+use chrono::{
+    DateTime, Datelike, Duration, FixedOffset, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Utc,
+    Weekday,
+};
+
+/// Deadlines without an explicit time ("Friday", "EOD") resolve to close of business.
+const END_OF_DAY_HOUR: u32 = 17;
+/// Words after which an abbreviated weekday ("by Fri") counts. Elsewhere
+/// only full names do, as "sun", "sat", "wed" and "mon" are words too.
+const WEEKDAY_LEADS: &[&str] = &["by", "on", "next", "this"];
+
+/// Resolves a deadline as written by the model — either a timestamp or a phrase
+/// like "EOD Friday", "next Tuesday 3pm", "in 2 weeks" — to a UTC instant.
+/// Relative phrases are anchored to `anchor` (the email's sent time in the
+/// user's timezone). Returns `None` when the phrase carries no usable date.
+pub fn normalize_due_date(raw: &str, anchor: DateTime<FixedOffset>) -> Option<DateTime<Utc>> {
+    let raw = raw.trim();
+    if raw.is_empty() {
+        return None;
+    }
+
+    if let Ok(dt) = DateTime::parse_from_rfc3339(raw) {
+        return Some(dt.with_timezone(&Utc));
+    }
+
+    let tz = *anchor.offset();
+    for format in ["%Y-%m-%dT%H:%M:%S", "%Y-%m-%d %H:%M:%S", "%Y-%m-%dT%H:%M"] {
+        if let Ok(naive) = NaiveDateTime::parse_from_str(raw, format) {
+            return at_local(tz, naive.date(), naive.time());
+        }
+    }
+    if let Ok(date) = NaiveDate::parse_from_str(raw, "%Y-%m-%d") {
+        return at_local(tz, date, end_of_day());
+    }
+
+    let text = raw.to_lowercase();
+    let words: Vec<&str> = text
+        .split(|c: char| !c.is_alphanumeric() && c != ':')
+        .filter(|w| !w.is_empty())
+        .collect();
+
+    let date = relative_date(&words, anchor.date_naive())?;
+    let time = time_of_day(&words).unwrap_or_else(end_of_day);
+    at_local(tz, date, time)
+}
+
+fn relative_date(words: &[&str], today: NaiveDate) -> Option<NaiveDate> {
+    let has = |w: &str| words.contains(&w);
+
+    for (i, word) in words.iter().enumerate() {
+        let previous = i.checked_sub(1).map(|p| words[p]);
+        if let Some(weekday) = weekday(word, previous) {
+            return Some(weekday_on_or_after(
+                today,
+                weekday,
+                previous == Some("next"),
+            ));
+        }
+    }
+
+    if let Some(date) = in_n_units(words, today) {
+        return Some(date);
+    }
+    if has("tomorrow") {
+        return Some(today + Duration::days(1));
+    }
+    if has("next") && has("week") {
+        let monday = weekday_on_or_after(today, Weekday::Mon, false);
+        return Some(if monday == today {
+            today + Duration::days(7)
+        } else {
+            monday
+        });
+    }
+    if has("eow") || (has("week") && (has("end") || has("this"))) {
+        return Some(weekday_on_or_after(today, Weekday::Fri, false));
+    }
+    if has("eom") || (has("end") && has("month")) {
+        let first_of_next = if today.month() == 12 {
+            NaiveDate::from_ymd_opt(today.year() + 1, 1, 1)
+        } else {
+            NaiveDate::from_ymd_opt(today.year(), today.month() + 1, 1)
+        }?;
+        return Some(first_of_next - Duration::days(1));
+    }
+    if has("today")
+        || has("tonight")
+        || has("eod")
+        || has("cob")
+        || has("eob")
+        || (has("end") && has("day"))
+    {
+        return Some(today);
+    }
+
+    None
+}
+
+/// `word` as a weekday: a full name, or an abbreviation following one of
+/// [`WEEKDAY_LEADS`].
+fn weekday(word: &str, previous: Option<&str>) -> Option<Weekday> {
+    let weekday = word.parse::<Weekday>().ok()?;
+    let full = word.len() > 3;
+    (full || previous.is_some_and(|p| WEEKDAY_LEADS.contains(&p))).then_some(weekday)
+}
+
+/// Upcoming `target` weekday, counting today. With `next`, skips to the
+/// following week whenever the plain upcoming day still falls in this week.
+fn weekday_on_or_after(today: NaiveDate, target: Weekday, next: bool) -> NaiveDate {
+    let ahead = (target.num_days_from_monday() as i64
+        - today.weekday().num_days_from_monday() as i64)
+        .rem_euclid(7);
+    let date = today + Duration::days(ahead);
+
+    if next && (ahead == 0 || date.iso_week() == today.iso_week()) {
+        date + Duration::days(7)
+    } else {
+        date
+    }
+}
+
+/// "in 3 days", "within 2 weeks".
+fn in_n_units(words: &[&str], today: NaiveDate) -> Option<NaiveDate> {
+    words.windows(3).find_map(|w| {
+        if w[0] != "in" && w[0] != "within" {
+            return None;
+        }
+        let n: i64 = w[1].parse().ok()?;
+        match w[2] {
+            "day" | "days" => Some(today + Duration::days(n)),
+            "week" | "weeks" => Some(today + Duration::weeks(n)),
+            _ => None,
+        }
+    })
+}
+
+/// "noon", "5pm", "5 pm", "3:30pm", "17:00".
+fn time_of_day(words: &[&str]) -> Option<NaiveTime> {
+    for (i, word) in words.iter().enumerate() {
+        match *word {
+            "noon" => return NaiveTime::from_hms_opt(12, 0, 0),
+            "midnight" => return NaiveTime::from_hms_opt(23, 59, 0),
+            _ => {}
+        }
+
+        let (clock, meridiem) = if let Some(c) = word.strip_suffix("am") {
+            (c, Some(false))
+        } else if let Some(c) = word.strip_suffix("pm") {
+            (c, Some(true))
+        } else {
+            let following = words.get(i + 1).copied();
+            match following {
+                Some("am") => (*word, Some(false)),
+                Some("pm") => (*word, Some(true)),
+                _ if word.contains(':') => (*word, None),
+                _ => continue,
+            }
+        };
+
+        if let Some(time) = parse_clock(clock, meridiem) {
+            return Some(time);
+        }
+    }
+    None
+}
+
+/// `meridiem` is `Some(true)` for pm, `Some(false)` for am, `None` for 24h clock.
+fn parse_clock(clock: &str, meridiem: Option<bool>) -> Option<NaiveTime> {
+    let (hour, minute) = match clock.split_once(':') {
+        Some((h, m)) => (h.parse::<u32>().ok()?, m.parse::<u32>().ok()?),
+        None => (clock.parse::<u32>().ok()?, 0),
+    };
+    let hour = match meridiem {
+        Some(true) if hour < 12 => hour + 12,
+        Some(false) if hour == 12 => 0,
+        _ => hour,
+    };
+    NaiveTime::from_hms_opt(hour, minute, 0)
+}
+
+fn end_of_day() -> NaiveTime {
+    NaiveTime::from_hms_opt(END_OF_DAY_HOUR, 0, 0).unwrap_or_default()
+}
+
+fn at_local(tz: FixedOffset, date: NaiveDate, time: NaiveTime) -> Option<DateTime<Utc>> {
+    tz.from_local_datetime(&date.and_time(time))
+        .single()
+        .map(|dt| dt.with_timezone(&Utc))
+}
@@ -0,0 +1,143 @@
+use ai::injection;
+use ai::provider::{AiProvider, ChatRequest, Message};
+use noodle_core::error::{NoodleError, Result};
+use std::sync::Arc;
+use storage::sqlite::SqliteStorage;
+use tokio::sync::RwLock;
+
+/// Sent messages the average reply length is taken over.
+const PROFILE_MESSAGES: usize = 30;
+/// Sent messages shown to the model as examples of the user's writing.
+const PROFILE_EXAMPLES: usize = 4;
+/// Characters kept of each example.
+const EXAMPLE_CHARS: usize = 800;
+/// Longest transcript accepted, about ten minutes of speech.
+const MAX_TRANSCRIPT_CHARS: usize = 10_000;
+
+/// How the user writes, from their own text in Sent Items.
+pub struct WritingProfile {
+    /// Recent messages, newest first, cut to [`EXAMPLE_CHARS`].
+    pub examples: Vec<String>,
+    pub average_words: Option<usize>,
+}
+
+impl WritingProfile {
+    pub async fn load(sqlite: &SqliteStorage) -> Result<Self> {
+        let samples = sqlite.get_writing_samples(PROFILE_MESSAGES).await?;
+        let average_words = (!samples.is_empty()).then(|| {
+            samples
+                .iter()
+                .map(|s| s.split_whitespace().count())
+                .sum::<usize>()
+                / samples.len()
+        });
+        let examples = samples
+            .iter()
+            .take(PROFILE_EXAMPLES)
+            .map(|s| s.chars().take(EXAMPLE_CHARS).collect())
+            .collect();
+        Ok(Self {
+            examples,
+            average_words,
+        })
+    }
+
+    fn prompt_section(&self) -> String {
+        if self.examples.is_empty() {
+            return "There are no sent emails to learn the user's style from; write plainly \
+                    and briefly."
+                .into();
+        }
+        let examples: Vec<String> = self
+            .examples
+            .iter()
+            .map(|e| injection::data_block(e))
+            .collect();
+        let length = self
+            .average_words
+            .map(|words| format!(" Their emails average {} words.", words))
+            .unwrap_or_default();
+        format!(
+            "Emails the user wrote recently, to copy their tone, greeting, sign-off and \
+             length from, not their content:{}\n{}",
+            length,
+            examples.join("\n")
+        )
+    }
+}
+
+/// Turns a spoken reply, as transcribed by the OS, into a written one in
+/// the user's style, for replying hands-free.
+pub struct DictationDrafter {
+    sqlite: Arc<SqliteStorage>,
+    ai: Arc<RwLock<Arc<dyn AiProvider>>>,
+}
+
+impl DictationDrafter {
+    pub fn new(sqlite: Arc<SqliteStorage>, ai: Arc<RwLock<Arc<dyn AiProvider>>>) -> Self {
+        Self { sqlite, ai }
+    }
+
+    /// Writes the reply to `email_id` that `transcript` dictates. The
+    /// transcript decides what is said; filler, false starts and
+    /// corrections in it are cleaned up.
+    pub async fn draft(&self, email_id: i64, transcript: &str) -> Result<String> {
+        let transcript = transcript.trim();
+        if transcript.is_empty() {
+            return Err(NoodleError::Validation(
+                "Nothing was dictated; say or type what the reply should say".into(),
+            ));
+        }
+        if transcript.chars().count() > MAX_TRANSCRIPT_CHARS {
+            return Err(NoodleError::Validation(format!(
+                "Dictation is limited to {} characters",
+                MAX_TRANSCRIPT_CHARS
+            )));
+        }
+        let email = self
+            .sqlite
+            .get_email_detail(email_id)
+            .await?
+            .ok_or_else(|| NoodleError::Storage(format!("Email {} not found", email_id)))?;
+
+        let profile = WritingProfile::load(&self.sqlite).await?;
+        let locale = self.sqlite.get_user_locale().await?;
+        let prompt = format!(
+            "{}\n\n\
+             The user is replying to this email:\n{}\n\n\
+             {}\n\n\
+             The user dictated their reply; this is the speech-to-text transcript:\n\
+             \"\"\"\n{}\n\"\"\"\n\n\
+             Write the reply the user dictated as a polished email body. Say what the \
+             transcript says and nothing more: keep every point, commitment and date, drop \
+             filler words and false starts, and follow the user's own corrections (\"no, \
+             make that Friday\"). Fix words the transcription evidently misheard using the \
+             email for context. Return only the reply text. {}",
+            injection::DATA_INSTRUCTION,
+            injection::data_block(&format!(
+                "Subject: {}\nFrom: {}\n\n{}",
+                email.subject, email.sender, email.body_text
+            )),
+            profile.prompt_section(),
+            transcript,
+            locale.prompt_instruction()
+        );
+
+        let request = ChatRequest {
+            messages: vec![Message {
+                role: "user".into(),
+                content: prompt,
+            }],
+            temperature: 0.3,
+            response_format: None,
+            model: None,
+        };
+        let ai = self.ai.read().await.clone();
+        Ok(ai
+            .chat_completion(request)
+            .await?
+            .content
+            .trim()
+            .to_string())
+    }
+}
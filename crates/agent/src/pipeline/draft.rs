@@ -1,7 +1,8 @@
+use ai::injection;
 use ai::provider::{AiProvider, ChatRequest, Message};
 use noodle_core::error::Result;
 use std::sync::Arc;
-use storage::qdrant::QdrantStorage;
+use storage::qdrant::{QdrantStorage, VECTOR_NAME};
 use storage::sqlite::SqliteStorage;
 
 use tokio::sync::RwLock;
@@ -22,32 +23,30 @@ impl DraftAssistant {
     }
 
     pub async fn generate_draft(&self, email_id: i64) -> Result<String> {
-        use sqlx::Row;
         // 1. Fetch email from SQLite
-        let email = sqlx::query_as::<_, storage::sqlite::EmailRow>(
-            "SELECT id, subject, sender, received_at, body_text FROM emails WHERE id = ?",
-        )
-        .bind(email_id)
-        .fetch_one(self.sqlite.pool())
-        .await
-        .map_err(|e: sqlx::Error| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+        let email = self
+            .sqlite
+            .get_email_detail(email_id)
+            .await?
+            .ok_or_else(|| {
+                noodle_core::error::NoodleError::Storage(format!("Email {} not found", email_id))
+            })?;
 
         // 2. Fetch facts (optional)
-        let facts = sqlx::query("SELECT summary FROM extracted_email_facts WHERE email_id = ?")
-            .bind(email_id)
-            .fetch_optional(self.sqlite.pool())
-            .await
-            .map_err(|e: sqlx::Error| noodle_core::error::NoodleError::Storage(e.to_string()))?;
-
-        let summary = facts
-            .map(|r: sqlx::sqlite::SqliteRow| r.get::<String, _>("summary"))
+        let summary = self
+            .sqlite
+            .get_fact_summary(email_id)
+            .await?
             .unwrap_or_default();
 
         // 3. Fetch similar emails from Qdrant for style/context
         let ai = self.ai.read().await;
         let embedding = ai.generate_embedding(&email.body_text).await?;
         drop(ai); // Release lock before other async ops if needed, though not strictly necessary here as search_emails is on qdrant
-        let similar = self.qdrant.search_emails(embedding, None, 3).await?;
+        let similar = self
+            .qdrant
+            .search_emails(VECTOR_NAME, embedding, None, 3)
+            .await?;
 
         let mut context = String::new();
         for point in similar {
@@ -60,6 +59,7 @@ impl DraftAssistant {
         }
 
         // 4. Build grounded prompt
+        let locale = self.sqlite.get_user_locale().await?;
         let prompt = format!(
             "Analyze the following email and draft a professional reply.
             
@@ -73,8 +73,16 @@ impl DraftAssistant {
             Body to reply to:
             {}
             
-            Draft a reply that is concise, professional, and addresses all points in the summary.",
-            email.subject, email.sender, summary, context, email.body_text
+            Draft a reply that is concise, professional, and addresses all points in the summary.
+            {}
+            {}",
+            email.subject,
+            email.sender,
+            summary,
+            context,
+            injection::data_block(&email.body_text),
+            injection::DATA_INSTRUCTION,
+            locale.prompt_instruction()
         );
 
         let request = ChatRequest {
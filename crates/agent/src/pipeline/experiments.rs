@@ -0,0 +1,300 @@
+use super::{ExtractionPipeline, ExtractionTrial};
+use crate::prompts::PromptLibrary;
+use ai::provider::AiProvider;
+use noodle_core::error::{NoodleError, Result};
+use noodle_core::events::{AppEvent, ExperimentProgress, Notifier};
+use noodle_core::types::{
+    Email, EmailFact, ExperimentComparison, ExperimentEmail, ExperimentOutcome, ExperimentVariant,
+    ExtractionExperiment, VariantStats,
+};
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, HashSet};
+use std::sync::Arc;
+use std::time::Instant;
+use storage::sqlite::SqliteStorage;
+use tokio::sync::RwLock;
+use tracing::{info, warn};
+
+/// Emails an experiment runs over when none are picked.
+pub const DEFAULT_SAMPLE_SIZE: usize = 20;
+/// The most emails one experiment runs over.
+pub const MAX_SAMPLE_SIZE: usize = 200;
+
+/// The extracted fields the variants are compared on. Lists are compared by
+/// how many items they have, the project by name and the deadline by day.
+const FIELDS: &[&str] = &[
+    "primary_type",
+    "intent",
+    "urgency",
+    "sentiment",
+    "waiting_on",
+    "needs_response",
+    "project",
+    "due_by",
+    "risks",
+    "issues",
+    "blockers",
+    "open_questions",
+];
+
+/// Runs two extraction variants over the same emails and stores how their
+/// results compare. The emails' own facts are left as they are.
+pub struct ExperimentRunner {
+    sqlite: Arc<SqliteStorage>,
+    pipeline: Arc<ExtractionPipeline>,
+    prompts: PromptLibrary,
+}
+
+impl ExperimentRunner {
+    pub fn new(
+        sqlite: Arc<SqliteStorage>,
+        ai: Arc<RwLock<Arc<dyn AiProvider>>>,
+        pipeline: Arc<ExtractionPipeline>,
+    ) -> Self {
+        Self {
+            prompts: PromptLibrary::new(sqlite.clone(), ai),
+            sqlite,
+            pipeline,
+        }
+    }
+
+    /// Runs both variants over `email_ids`, or over `sample_size` extracted
+    /// emails picked at random, and stores the experiment. Progress is
+    /// reported after each email; shutting down stops the run without
+    /// storing it.
+    pub async fn run(
+        &self,
+        name: &str,
+        mut variant_a: ExperimentVariant,
+        mut variant_b: ExperimentVariant,
+        email_ids: Option<Vec<i64>>,
+        sample_size: Option<usize>,
+        notifier: &dyn Notifier,
+    ) -> Result<ExtractionExperiment> {
+        for (variant, label) in [(&mut variant_a, "A"), (&mut variant_b, "B")] {
+            if variant.label.trim().is_empty() {
+                variant.label = label.into();
+            }
+            variant.model = variant.model.take().filter(|m| !m.trim().is_empty());
+            for price in [&mut variant.prompt_price, &mut variant.completion_price] {
+                *price = price.filter(|p| p.is_finite() && *p > 0.0);
+            }
+        }
+        let name = match name.trim() {
+            "" => format!("{} vs {}", variant_a.label, variant_b.label),
+            name => name.to_string(),
+        };
+        let trial_a = self.trial(&variant_a).await?;
+        let trial_b = self.trial(&variant_b).await?;
+
+        let ids = match email_ids.filter(|ids| !ids.is_empty()) {
+            Some(ids) => {
+                let mut seen = HashSet::new();
+                ids.into_iter()
+                    .filter(|id| seen.insert(*id))
+                    .take(MAX_SAMPLE_SIZE)
+                    .collect()
+            }
+            None => {
+                let size = sample_size
+                    .unwrap_or(DEFAULT_SAMPLE_SIZE)
+                    .clamp(1, MAX_SAMPLE_SIZE);
+                self.sqlite.sample_extracted_email_ids(size as i64).await?
+            }
+        };
+        if ids.is_empty() {
+            return Err(NoodleError::Validation(
+                "There are no extracted emails to run an experiment over yet".into(),
+            ));
+        }
+
+        let total = ids.len();
+        let progress = |done| {
+            notifier.notify(AppEvent::ExperimentProgress(ExperimentProgress {
+                name: name.clone(),
+                done,
+                total,
+            }))
+        };
+        progress(0);
+        let mut emails = Vec::with_capacity(total);
+        for (done, id) in ids.into_iter().enumerate() {
+            if self.pipeline.shutdown.is_shutting_down() {
+                return Err(NoodleError::Internal(format!(
+                    "Experiment {} was stopped by shutdown after {} of {} emails",
+                    name, done, total
+                )));
+            }
+            let Some(email) = self.sqlite.get_email(id).await? else {
+                warn!("Email {} not found; leaving it out of the experiment", id);
+                progress(done + 1);
+                continue;
+            };
+            let a = self.outcome(&email, &trial_a).await;
+            let b = self.outcome(&email, &trial_b).await;
+            emails.push(ExperimentEmail {
+                email_id: email.id,
+                subject: email.subject,
+                disagreements: disagreements(&a, &b),
+                a,
+                b,
+            });
+            progress(done + 1);
+        }
+
+        let comparison = compare(&emails, &variant_a, &variant_b);
+        info!(
+            "Experiment {}: {} emails compared, {:.0}% agreement",
+            name,
+            comparison.compared,
+            comparison.agreement * 100.0
+        );
+        self.sqlite
+            .save_experiment(&name, &variant_a, &variant_b, &emails, &comparison)
+            .await
+    }
+
+    async fn trial(&self, variant: &ExperimentVariant) -> Result<ExtractionTrial> {
+        let template = match &variant.prompt_id {
+            Some(id) => Some(self.prompts.extraction_template(id).await?),
+            None => None,
+        };
+        Ok(ExtractionTrial {
+            template,
+            model: variant.model.clone(),
+        })
+    }
+
+    /// A failed extraction is part of the result rather than an error.
+    async fn outcome(&self, email: &Email, trial: &ExtractionTrial) -> ExperimentOutcome {
+        let started = Instant::now();
+        let result = self.pipeline.trial_extraction(email, trial).await;
+        let duration_ms = started.elapsed().as_millis() as u64;
+        match result {
+            Ok((facts, usage)) => ExperimentOutcome {
+                fields: compared_fields(&facts),
+                confidence: Some(facts.confidence),
+                prompt_tokens: usage.prompt_tokens,
+                completion_tokens: usage.completion_tokens,
+                duration_ms,
+                error: None,
+            },
+            Err(e) => ExperimentOutcome {
+                duration_ms,
+                error: Some(e.to_string()),
+                ..Default::default()
+            },
+        }
+    }
+}
+
+fn compared_fields(facts: &EmailFact) -> BTreeMap<String, Value> {
+    BTreeMap::from([
+        ("primary_type".into(), json!(facts.primary_type)),
+        ("intent".into(), json!(facts.intent)),
+        ("urgency".into(), json!(facts.urgency)),
+        ("sentiment".into(), json!(facts.sentiment)),
+        ("waiting_on".into(), json!(facts.waiting_on)),
+        ("needs_response".into(), json!(facts.needs_response)),
+        (
+            "project".into(),
+            json!(facts.client_or_project.name.trim().to_lowercase()),
+        ),
+        (
+            "due_by".into(),
+            json!(facts.due_by.map(|d| d.date_naive().to_string())),
+        ),
+        ("risks".into(), json!(facts.risks.len())),
+        ("issues".into(), json!(facts.issues.len())),
+        ("blockers".into(), json!(facts.blockers.len())),
+        ("open_questions".into(), json!(facts.open_questions.len())),
+    ])
+}
+
+/// The fields two outcomes differ on; none unless both succeeded.
+pub fn disagreements(a: &ExperimentOutcome, b: &ExperimentOutcome) -> Vec<String> {
+    if a.error.is_some() || b.error.is_some() {
+        return Vec::new();
+    }
+    FIELDS
+        .iter()
+        .filter(|field| a.fields.get(**field) != b.fields.get(**field))
+        .map(|field| field.to_string())
+        .collect()
+}
+
+/// Agreement over the emails both variants extracted, and each variant's
+/// stats over all of them.
+pub fn compare(
+    emails: &[ExperimentEmail],
+    variant_a: &ExperimentVariant,
+    variant_b: &ExperimentVariant,
+) -> ExperimentComparison {
+    let compared: Vec<&ExperimentEmail> = emails
+        .iter()
+        .filter(|e| e.a.error.is_none() && e.b.error.is_none())
+        .collect();
+    let mut field_agreement = BTreeMap::new();
+    let mut agreed = 0;
+    if !compared.is_empty() {
+        for field in FIELDS {
+            let agree = compared
+                .iter()
+                .filter(|e| !e.disagreements.iter().any(|d| d == field))
+                .count();
+            agreed += agree;
+            field_agreement.insert(field.to_string(), agree as f32 / compared.len() as f32);
+        }
+    }
+    let comparisons = compared.len() * FIELDS.len();
+    ExperimentComparison {
+        compared: compared.len() as u32,
+        field_agreement,
+        agreement: if comparisons == 0 {
+            0.0
+        } else {
+            agreed as f32 / comparisons as f32
+        },
+        a: stats(emails.iter().map(|e| &e.a), variant_a),
+        b: stats(emails.iter().map(|e| &e.b), variant_b),
+    }
+}
+
+/// Failed extractions count toward tokens, cost and time but not toward
+/// confidence.
+pub fn stats<'a>(
+    outcomes: impl Iterator<Item = &'a ExperimentOutcome>,
+    variant: &ExperimentVariant,
+) -> VariantStats {
+    let mut stats = VariantStats::default();
+    let mut confidence = 0.0;
+    let mut duration_ms = 0;
+    for outcome in outcomes {
+        match outcome.confidence {
+            Some(c) if outcome.error.is_none() => {
+                stats.succeeded += 1;
+                confidence += c;
+            }
+            _ => stats.failed += 1,
+        }
+        stats.prompt_tokens += outcome.prompt_tokens as u64;
+        stats.completion_tokens += outcome.completion_tokens as u64;
+        duration_ms += outcome.duration_ms;
+    }
+    if stats.succeeded > 0 {
+        stats.mean_confidence = confidence / stats.succeeded as f32;
+    }
+    if variant.prompt_price.is_some() || variant.completion_price.is_some() {
+        let per_million = |tokens: u64, price: Option<f64>| {
+            tokens as f64 * price.unwrap_or_default() / 1_000_000.0
+        };
+        stats.cost = Some(
+            per_million(stats.prompt_tokens, variant.prompt_price)
+                + per_million(stats.completion_tokens, variant.completion_price),
+        );
+    }
+    stats.mean_duration_ms = duration_ms
+        .checked_div((stats.succeeded + stats.failed) as u64)
+        .unwrap_or_default();
+    stats
+}
@@ -0,0 +1,58 @@
+/// `app_config` key holding per-folder modes, as `folder=mode` pairs
+/// separated by commas, e.g. `Newsletters=index_only, Junk=exclude`.
+pub const FOLDER_MODES_KEY: &str = "folder_modes";
+
+/// Which pipeline stages run for a folder's emails.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FolderMode {
+    /// Extraction and embedding; folders not listed get this.
+    #[default]
+    Full,
+    /// Saved and embedded for search, without fact extraction.
+    IndexOnly,
+    /// Not scanned at all.
+    Exclude,
+}
+
+impl FolderMode {
+    fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "full" => Some(Self::Full),
+            "index_only" => Some(Self::IndexOnly),
+            "exclude" => Some(Self::Exclude),
+            _ => None,
+        }
+    }
+}
+
+/// The mode `spec` gives `folder`, matching folder names case-insensitively.
+/// Malformed entries are ignored; settings are validated when saved.
+pub fn folder_mode(spec: &str, folder: &str) -> FolderMode {
+    spec.split(',')
+        .filter_map(|entry| entry.split_once('='))
+        .find(|(name, _)| name.trim().eq_ignore_ascii_case(folder.trim()))
+        .and_then(|(_, mode)| FolderMode::parse(mode))
+        .unwrap_or_default()
+}
+
+/// The folders a scan reads: the `defaults` that `spec` doesn't exclude,
+/// then every other folder it lists and doesn't exclude, in its order.
+pub fn scanned_folders(spec: &str, defaults: &[&str]) -> Vec<String> {
+    let mut folders: Vec<String> = defaults
+        .iter()
+        .filter(|folder| folder_mode(spec, folder) != FolderMode::Exclude)
+        .map(|folder| folder.to_string())
+        .collect();
+    for (name, _) in spec.split(',').filter_map(|entry| entry.split_once('=')) {
+        let name = name.trim();
+        let listed = defaults
+            .iter()
+            .map(|f| f.to_string())
+            .chain(folders.iter().cloned())
+            .any(|f| f.eq_ignore_ascii_case(name));
+        if !name.is_empty() && !listed && folder_mode(spec, name) != FolderMode::Exclude {
+            folders.push(name.to_string());
+        }
+    }
+    folders
+}
@@ -1,35 +1,250 @@
+pub mod anomaly;
+pub mod attachments;
+pub mod bulk;
+pub mod categories;
+pub mod dates;
+pub mod dictation;
 pub mod draft;
+pub mod experiments;
+pub mod folders;
+pub mod newsletter;
+pub mod observer;
+pub mod pacing;
+pub mod payloads;
+pub mod queue;
+pub mod sanitize;
+pub mod schema;
+pub mod truncate;
+pub mod vectors;
 
-use ai::provider::{AiProvider, ChatRequest, Message};
+use crate::engine::idle;
+use crate::engine::legal_hold::LegalHold;
+use crate::engine::shutdown::ShutdownCoordinator;
+use ai::injection;
+use ai::provider::{AiProvider, ChatRequest, JsonSchemaFormat, Message, ResponseFormat, Usage};
+use ai::schema::{repair_request, SchemaValidator};
+use anomaly::AnomalyDetector;
+use attachments::AttachmentStore;
+use categories::CategoryLabeler;
 use chrono::Utc;
+use folders::FolderMode;
 use noodle_core::error::Result;
+use noodle_core::metrics::{Stage, StageMetrics};
 use noodle_core::types::{
-    Email, EmailFact, Intent, PrimaryType, ProjectInfo, Provenance, Sentiment, Urgency, WaitingOn,
+    Alert, Email, EmailFact, PipelineStage, ProcessStage, ProjectInfo, Provenance, QueueStatus,
 };
-use std::sync::Arc;
-use storage::qdrant::QdrantStorage;
-use storage::sqlite::SqliteStorage;
-use tracing::info;
+use observer::ProcessObserver;
+use outlook::client::OutlookClient;
+use pacing::{PacingConfig, PacingController};
+use queue::{ActiveGuard, WorkQueue};
+use std::sync::{Arc, LazyLock};
+use std::time::Duration;
+use storage::qdrant::{email_filter_payload, QdrantStorage};
+use storage::sqlite::{SqliteStorage, VectorPayload};
+use tracing::{info, warn};
 use uuid::Uuid;
 
-use tokio::sync::RwLock;
+use tokio::sync::{Notify, RwLock};
+
+/// Outbox entries retried per replay pass.
+const OUTBOX_REPLAY_BATCH: i64 = 50;
+/// Deferred extractions run per idle pass.
+const DEFERRED_EXTRACTION_BATCH: i64 = 50;
+/// Pending emails and failed upserts listed by [`ExtractionPipeline::queue_status`].
+const QUEUE_STATUS_LIMIT: usize = 100;
+/// Where the user's replies are, to match them to the mail they answer.
+const SENT_ITEMS: &str = "Sent Items";
 
 pub struct ExtractionPipeline {
     sqlite: Arc<SqliteStorage>,
     qdrant: Arc<QdrantStorage>,
     ai: Arc<RwLock<Arc<dyn AiProvider>>>,
+    pacing: Arc<PacingController>,
+    queue: WorkQueue,
+    shutdown: Arc<ShutdownCoordinator>,
+    anomalies: AnomalyDetector,
+    categories: CategoryLabeler,
+    legal_hold: Arc<LegalHold>,
+    attachments: Arc<AttachmentStore>,
+    /// Set by the sync manager; see [`Self::set_observer`].
+    observer: std::sync::RwLock<Option<Arc<dyn ProcessObserver>>>,
+    metrics: Arc<StageMetrics>,
+    /// Notified when a project or tag changed; see
+    /// [`Self::request_payload_sync`].
+    payload_sync: Notify,
 }
 
 impl ExtractionPipeline {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         sqlite: Arc<SqliteStorage>,
         qdrant: Arc<QdrantStorage>,
         ai: Arc<RwLock<Arc<dyn AiProvider>>>,
+        shutdown: Arc<ShutdownCoordinator>,
+        legal_hold: Arc<LegalHold>,
+        attachments: Arc<AttachmentStore>,
+        outlook: Arc<OutlookClient>,
+        metrics: Arc<StageMetrics>,
     ) -> Self {
-        Self { sqlite, qdrant, ai }
+        Self {
+            metrics,
+            legal_hold,
+            attachments,
+            anomalies: AnomalyDetector::new(sqlite.clone()),
+            categories: CategoryLabeler::new(sqlite.clone(), outlook),
+            sqlite,
+            qdrant,
+            ai,
+            pacing: Arc::new(PacingController::default()),
+            queue: WorkQueue::default(),
+            shutdown,
+            observer: std::sync::RwLock::new(None),
+            payload_sync: Notify::new(),
+        }
+    }
+
+    /// The pacing the pipeline's LLM calls go through, for other features to
+    /// share, so their calls count against the same limits.
+    pub fn pacing(&self) -> Arc<PacingController> {
+        self.pacing.clone()
+    }
+
+    /// Reports every step finished from now on to `observer`, in place of
+    /// the previous one.
+    pub fn set_observer(&self, observer: Arc<dyn ProcessObserver>) {
+        *self.observer.write().unwrap_or_else(|e| e.into_inner()) = Some(observer);
+    }
+
+    fn completed(&self, email: &Email, stage: ProcessStage) {
+        let observer = self
+            .observer
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone();
+        if let Some(observer) = observer {
+            observer.stage_completed(email, stage);
+        }
     }
 
-    pub async fn process_email(&self, mut email: Email) -> Result<()> {
+    /// Alerts raised from now on by the anomaly checks run after extraction.
+    pub fn subscribe_alerts(&self) -> tokio::sync::broadcast::Receiver<Alert> {
+        self.anomalies.subscribe()
+    }
+
+    /// Re-reads the pacing settings so changes apply without a restart.
+    async fn refresh_pacing(&self) -> Result<()> {
+        let max_concurrency = self
+            .sqlite
+            .get_config(pacing::MAX_CONCURRENCY_KEY)
+            .await?
+            .and_then(|s| s.parse::<usize>().ok())
+            .unwrap_or(pacing::DEFAULT_MAX_CONCURRENCY);
+        let low_impact = self
+            .sqlite
+            .get_config(pacing::LOW_IMPACT_MODE_KEY)
+            .await?
+            .is_some_and(|v| v == "true");
+        let emails_per_minute = if low_impact {
+            let rate = self
+                .sqlite
+                .get_config(pacing::LOW_IMPACT_RATE_KEY)
+                .await?
+                .and_then(|s| s.parse::<u32>().ok())
+                .unwrap_or(pacing::DEFAULT_LOW_IMPACT_RATE);
+            Some(rate)
+        } else {
+            None
+        };
+
+        self.pacing.configure(PacingConfig {
+            max_concurrency: if low_impact { 1 } else { max_concurrency },
+            emails_per_minute,
+        });
+        Ok(())
+    }
+
+    pub fn queue(&self) -> &WorkQueue {
+        &self.queue
+    }
+
+    pub async fn queue_status(&self) -> Result<QueueStatus> {
+        let (pending, pending_total, in_progress) = self.queue.snapshot(QUEUE_STATUS_LIMIT);
+        let retries = self
+            .sqlite
+            .list_vector_retries(QUEUE_STATUS_LIMIT as i64)
+            .await?;
+        let (active_workers, max_workers) = self.pacing.workers();
+        Ok(QueueStatus {
+            pending,
+            pending_total,
+            in_progress,
+            retries,
+            active_workers,
+            max_workers,
+            latencies: self.metrics.snapshot(),
+        })
+    }
+
+    /// Runs one email through extraction and embedding. Fails with a
+    /// cancellation error if the user cancels it from the queue meanwhile;
+    /// an email cancelled after it was saved is embedded by the outbox replay.
+    pub async fn process_email(&self, email: Email) -> Result<()> {
+        let Some(_work) = self.shutdown.begin_work() else {
+            return Err(noodle_core::error::NoodleError::Internal(
+                "Shutting down".into(),
+            ));
+        };
+        let active = self.queue.start(&email);
+        tokio::select! {
+            result = self.run_email(email, &active) => result,
+            _ = active.cancelled() => Err(cancelled()),
+        }
+    }
+
+    /// Reruns extraction and embedding for a stored email, e.g. after changing
+    /// prompts or provider settings. Unlike a scan it skips low impact pacing,
+    /// since the user is waiting on the result.
+    pub async fn reprocess_email(&self, id: i64) -> Result<()> {
+        self.rerun_email(id, true).await
+    }
+
+    /// Regenerates the vector of a stored email without re-extracting facts.
+    pub async fn reembed_email(&self, id: i64) -> Result<()> {
+        self.rerun_email(id, false).await
+    }
+
+    async fn rerun_email(&self, id: i64, extract: bool) -> Result<()> {
+        let Some(_work) = self.shutdown.begin_work() else {
+            return Err(noodle_core::error::NoodleError::Internal(
+                "Shutting down".into(),
+            ));
+        };
+        let email = self.sqlite.get_email(id).await?.ok_or_else(|| {
+            noodle_core::error::NoodleError::Validation(format!("Email {} not found", id))
+        })?;
+        self.refresh_pacing().await?;
+        let active = self.queue.start(&email);
+        let run = async {
+            if extract {
+                active.set_stage(PipelineStage::Extracting);
+                self.extract_and_save(&email).await?;
+            }
+            // The outbox entry was settled when the email was first indexed.
+            self.sqlite.enqueue_vector_upsert(id).await?;
+            active.set_stage(PipelineStage::Embedding);
+            self.embed(&email).await?;
+            self.completed(&email, ProcessStage::Indexed);
+            Ok(())
+        };
+        tokio::select! {
+            result = run => result,
+            _ = active.cancelled() => Err(cancelled()),
+        }
+    }
+
+    async fn run_email(&self, mut email: Email, active: &ActiveGuard<'_>) -> Result<()> {
+        self.refresh_pacing().await?;
+        self.pacing.email_slot().await;
         info!("Processing email: {}", email.subject);
 
         // 0. Compute hash
@@ -40,33 +255,423 @@ impl ExtractionPipeline {
         hasher.update(&email.body_text);
         email.hash = format!("{:x}", hasher.finalize());
 
+        let mode = self.folder_mode(&email.folder).await?;
+        if mode == FolderMode::Exclude {
+            info!("Skipping email in excluded folder {}", email.folder);
+            return Ok(());
+        }
+        if self
+            .sqlite
+            .is_purged(&email.store_id, &email.entry_id)
+            .await?
+        {
+            info!("Skipping purged email {}", email.entry_id);
+            return Ok(());
+        }
+
         // 1. Persist to SQLite first to get internal ID
-        let id = self.sqlite.save_email(&email).await?;
+        let id = self
+            .metrics
+            .time(Stage::SqliteSave, self.sqlite.save_email(&email))
+            .await?;
         email.id = id;
+        if email.folder == SENT_ITEMS {
+            self.link_responses(&email).await;
+        }
+        if let Err(e) = self.legal_hold.archive(&email).await {
+            // Not indexed until archived, so the scans fetch it again.
+            self.sqlite.reset_last_indexed(id).await?;
+            return Err(e);
+        }
+        if email.excluded_reason.is_none() {
+            // The email is useful without its attachments, so a failure
+            // doesn't hold it back.
+            match self.attachments.store(&email).await {
+                Ok(0) => {
+                    // None left to search, as when an email is fetched again.
+                    let clear = self.qdrant.replace_attachment_vectors(id, Vec::new());
+                    if let Err(e) = clear.await {
+                        warn!("Failed to clear attachment vectors of email {}: {}", id, e);
+                    }
+                }
+                Ok(_) => {
+                    if let Err(e) = self.index_attachments(&email).await {
+                        warn!("Failed to index attachments of email {}: {}", id, e);
+                    }
+                }
+                Err(e) => warn!("Failed to store attachments of email {}: {}", id, e),
+            }
+        }
+        self.completed(&email, ProcessStage::Saved);
 
-        // 2. Extract facts using AI
-        let mut facts = self.extract_facts(&email).await?;
-        facts.email_id = id;
+        // VIP mail is never held back for idle time.
+        let vip = self.sqlite.is_vip_sender(&email.sender).await?;
+        let newsletter = !vip && newsletter::is_newsletter(&self.sqlite, &email).await?;
+        self.sqlite.set_newsletter(id, newsletter).await?;
+        if mode == FolderMode::Full && !vip && self.extraction_deferred().await? {
+            // Only ingestion runs now; the email is searchable right away and
+            // its facts follow once the user is idle.
+            self.sqlite.defer_extraction(id).await?;
+            active.set_stage(PipelineStage::Embedding);
+            self.embed(&email).await?;
+            self.completed(&email, ProcessStage::Indexed);
+            info!("Deferred extraction of email {} until the user is idle", id);
+            return Ok(());
+        }
 
-        // 3. Save facts to SQLite
-        self.sqlite.save_facts(&facts).await?;
+        // 2-5. Extract and save facts while embedding and persisting to Qdrant.
+        // Each side settles its own state, so one failing doesn't lose the
+        // other's work: facts that failed leave the email not indexed, so the
+        // scans fetch it again, and a failed vector stays in the outbox that
+        // save_email created, for the replay.
+        active.set_stage(PipelineStage::ExtractingAndEmbedding);
+        let (extracted, embedded) = tokio::join!(self.extract_and_save(&email), self.embed(&email));
+        if let Err(e) = extracted {
+            self.sqlite.reset_last_indexed(id).await?;
+            return Err(e);
+        }
+        embedded?;
+        self.completed(&email, ProcessStage::Indexed);
 
-        // 4. Generate embeddings
-        let ai = self.ai.read().await;
-        let embedding = ai.generate_embedding(&email.body_text).await?;
+        if vip {
+            if let Err(e) = self.anomalies.vip_email(&email).await {
+                warn!("Failed to announce VIP email {}: {}", email.id, e);
+            }
+        }
+        info!("Successfully processed email: {}", email.id);
+        Ok(())
+    }
+
+    /// Extracts facts using AI and saves them, unless the email is a
+    /// newsletter, its folder is index-only or its thread belongs to a
+    /// project with extraction turned off. Such emails are still embedded
+    /// for search.
+    async fn extract_and_save(&self, email: &Email) -> Result<()> {
+        // Newsletters are left to the weekly roundup, out of the digest and
+        // project dashboards that facts feed.
+        if self.folder_mode(&email.folder).await? != FolderMode::Full
+            || self.sqlite.is_newsletter(email.id).await?
+            || !self.extraction_enabled_for_thread(email).await?
+        {
+            return self.sqlite.complete_deferred_extraction(email.id).await;
+        }
+        let mut facts = self.extract_facts(email).await?;
+        facts.email_id = email.id;
 
-        // 5. Persist to Qdrant
-        let payload = qdrant_client::Payload::new(); // Add metadata
-        self.qdrant
-            .upsert_email_vector(&email.store_id, &email.entry_id, embedding, payload)
+        let settings = self
+            .sqlite
+            .get_project_settings(&facts.client_or_project.name)
             .await?;
+        if settings.extraction_enabled {
+            self.metrics
+                .time(Stage::SqliteSave, self.sqlite.save_facts(&facts))
+                .await?;
+            self.link_responses(email).await;
+            // The facts are saved; a failed check should not fail the email.
+            if let Err(e) = self.anomalies.check(email, &facts).await {
+                warn!("Anomaly check failed for email {}: {}", email.id, e);
+            }
+            if let Err(e) = self.categories.label(email, &facts).await {
+                warn!(
+                    "Failed to apply category rules to email {}: {}",
+                    email.id, e
+                );
+            }
+        } else {
+            info!(
+                "Discarding facts for email {}: extraction disabled for project {}",
+                email.id, settings.name
+            );
+        }
+        self.sqlite.complete_deferred_extraction(email.id).await?;
+        self.completed(email, ProcessStage::Extracted);
+        Ok(())
+    }
 
-        info!("Successfully processed email: {}", email.id);
+    /// Clears `needs_response` on mail of the email's conversation that Sent
+    /// Items already answers. A failure only leaves it flagged until the
+    /// next email of the thread.
+    async fn link_responses(&self, email: &Email) {
+        let Some(conversation_id) = email.conversation_id.as_deref() else {
+            return;
+        };
+        match self.sqlite.link_responses(conversation_id).await {
+            Ok(0) => {}
+            Ok(linked) => info!(
+                "Marked {} emails answered by the thread of email {}",
+                linked, email.id
+            ),
+            Err(e) => warn!("Failed to link replies for email {}: {}", email.id, e),
+        }
+    }
+
+    /// How `folder` is configured in `folder_modes`.
+    pub async fn folder_mode(&self, folder: &str) -> Result<FolderMode> {
+        let spec = self
+            .sqlite
+            .get_config(folders::FOLDER_MODES_KEY)
+            .await?
+            .unwrap_or_default();
+        Ok(folders::folder_mode(&spec, folder))
+    }
+
+    /// Whether extraction has to wait: idle-time extraction is on and the
+    /// user has used the machine within the idle threshold.
+    async fn extraction_deferred(&self) -> Result<bool> {
+        let enabled = self
+            .sqlite
+            .get_config(idle::IDLE_EXTRACTION_KEY)
+            .await?
+            .is_some_and(|v| v == "true");
+        if !enabled {
+            return Ok(false);
+        }
+        let threshold_mins = self
+            .sqlite
+            .get_config(idle::IDLE_THRESHOLD_MINS_KEY)
+            .await?
+            .and_then(|s| s.parse::<u64>().ok())
+            .filter(|m| *m > 0)
+            .unwrap_or(idle::DEFAULT_IDLE_THRESHOLD_MINS);
+        Ok(idle::user_idle_time()
+            .is_some_and(|idle| idle < Duration::from_secs(threshold_mins * 60)))
+    }
+
+    /// Runs extractions deferred while the user was active, oldest first,
+    /// until the user comes back or the batch is done. Returns how many ran.
+    pub async fn run_deferred_extractions(&self) -> Result<usize> {
+        let Some(_work) = self.shutdown.begin_work() else {
+            return Ok(0);
+        };
+        let pending = self
+            .sqlite
+            .list_deferred_extractions(DEFERRED_EXTRACTION_BATCH)
+            .await?;
+        let mut completed = 0;
+
+        for email_id in pending {
+            if self.shutdown.is_shutting_down() || self.extraction_deferred().await? {
+                break;
+            }
+            let Some(email) = self.sqlite.get_email(email_id).await? else {
+                self.sqlite.complete_deferred_extraction(email_id).await?;
+                continue;
+            };
+            self.refresh_pacing().await?;
+            self.pacing.email_slot().await;
+            let active = self.queue.start(&email);
+            active.set_stage(PipelineStage::Extracting);
+            let result = tokio::select! {
+                result = self.extract_and_save(&email) => result,
+                _ = active.cancelled() => Err(cancelled()),
+            };
+            match result {
+                Ok(()) => completed += 1,
+                Err(e) => warn!("Deferred extraction failed for email {}: {}", email_id, e),
+            }
+        }
+
+        if completed > 0 {
+            info!("Ran {} deferred extractions", completed);
+        }
+        Ok(completed)
+    }
+
+    /// Indexes the email's vector and settles its outbox entry; a failure is
+    /// recorded on the entry so the replay retries it.
+    async fn embed(&self, email: &Email) -> Result<()> {
+        if let Err(e) = self.index_vector(email).await {
+            self.sqlite
+                .fail_vector_upsert(email.id, &e.to_string())
+                .await?;
+            return Err(e);
+        }
+        self.sqlite.complete_vector_upsert(email.id).await?;
+        self.completed(email, ProcessStage::Embedded);
         Ok(())
     }
 
+    /// Retries vector upserts left in the outbox by failed or interrupted runs.
+    /// Returns how many were completed.
+    pub async fn replay_vector_outbox(&self) -> Result<usize> {
+        let Some(_work) = self.shutdown.begin_work() else {
+            return Ok(0);
+        };
+        let pending = self
+            .sqlite
+            .get_pending_vector_upserts(OUTBOX_REPLAY_BATCH)
+            .await?;
+        let mut completed = 0;
+
+        for email_id in pending {
+            if self.shutdown.is_shutting_down() {
+                break;
+            }
+            let Some(email) = self.sqlite.get_email(email_id).await? else {
+                self.sqlite.complete_vector_upsert(email_id).await?;
+                continue;
+            };
+            match self.index_vector(&email).await {
+                Ok(()) => {
+                    self.sqlite.complete_vector_upsert(email_id).await?;
+                    self.completed(&email, ProcessStage::Embedded);
+                    completed += 1;
+                }
+                Err(e) => {
+                    warn!("Vector upsert retry failed for email {}: {}", email_id, e);
+                    self.sqlite
+                        .fail_vector_upsert(email_id, &e.to_string())
+                        .await?;
+                }
+            }
+        }
+
+        if completed > 0 {
+            info!("Replayed {} pending vector upserts", completed);
+        }
+        Ok(completed)
+    }
+
+    async fn index_vector(&self, email: &Email) -> Result<()> {
+        let limit = self.sqlite.get_all_config().await?.max_embedding_body_chars;
+        let body = truncate::truncate_body(&email.body_text, limit as usize);
+        let ai = self.ai.read().await;
+        let embedding = self
+            .pacing
+            .llm_call(
+                "embedding",
+                self.metrics
+                    .time(Stage::Embedding, ai.generate_embedding(&body.text)),
+            )
+            .await?;
+        let subject_embedding = if email.subject.trim().is_empty() {
+            embedding.clone()
+        } else {
+            self.pacing
+                .llm_call(
+                    "embedding",
+                    self.metrics
+                        .time(Stage::Embedding, ai.generate_embedding(&email.subject)),
+                )
+                .await?
+        };
+        drop(ai);
+
+        // The payload outbox skips emails still waiting for their vector,
+        // so this upsert carries their current filterable fields.
+        let fields = self
+            .sqlite
+            .get_vector_payload(email.id)
+            .await?
+            .unwrap_or(VectorPayload {
+                email_id: email.id,
+                sender: email.sender.clone(),
+                conversation_id: email.conversation_id.clone(),
+                project: None,
+                tags: Vec::new(),
+            });
+        let mut payload = email_filter_payload(&fields);
+        payload.insert("email_id", email.id);
+        payload.insert("subject", email.subject.clone());
+        let upsert = self.qdrant.upsert_email_vector(
+            &email.store_id,
+            &email.entry_id,
+            subject_embedding,
+            embedding,
+            payload,
+        );
+        self.metrics.time(Stage::QdrantUpsert, upsert).await
+    }
+
+    /// Embeds the text of the email's stored attachments in chunks, so
+    /// search finds them, replacing their earlier vectors.
+    async fn index_attachments(&self, email: &Email) -> Result<()> {
+        let ai = self.ai.read().await.clone();
+        let mut chunks = Vec::new();
+        for attachment in self.sqlite.list_attachments(email.id).await? {
+            let Some(text) = attachment.extracted_text.as_deref() else {
+                continue;
+            };
+            for (index, chunk) in attachments::chunk_text(text).into_iter().enumerate() {
+                let embedding = self
+                    .pacing
+                    .llm_call(
+                        "embedding",
+                        self.metrics
+                            .time(Stage::Embedding, ai.generate_embedding(&chunk)),
+                    )
+                    .await?;
+                let mut payload = qdrant_client::Payload::new();
+                payload.insert("email_id", email.id);
+                payload.insert("attachment_id", attachment.id);
+                payload.insert("filename", attachment.filename.clone());
+                payload.insert("chunk_index", index as i64);
+                payload.insert("text", chunk);
+                chunks.push((embedding, payload));
+            }
+        }
+        let upsert = self.qdrant.replace_attachment_vectors(email.id, chunks);
+        self.metrics.time(Stage::QdrantUpsert, upsert).await
+    }
+
+    async fn extraction_enabled_for_thread(&self, email: &Email) -> Result<bool> {
+        let Some(conversation_id) = email.conversation_id.as_deref() else {
+            return Ok(true);
+        };
+        match self
+            .sqlite
+            .get_conversation_project(conversation_id)
+            .await?
+        {
+            Some(project) => Ok(self
+                .sqlite
+                .get_project_settings(&project)
+                .await?
+                .extraction_enabled),
+            None => Ok(true),
+        }
+    }
+
     async fn extract_facts(&self, email: &Email) -> Result<EmailFact> {
-        let prompt = format!(
+        self.run_extraction(email, None)
+            .await
+            .map(|(facts, _)| facts)
+    }
+
+    /// Extracts facts from `email` with the trial's prompt and model, without
+    /// storing them, and returns the tokens spent too. Trials aren't counted
+    /// in the extraction metrics.
+    pub async fn trial_extraction(
+        &self,
+        email: &Email,
+        trial: &ExtractionTrial,
+    ) -> Result<(EmailFact, Usage)> {
+        self.run_extraction(email, Some(trial)).await
+    }
+
+    async fn run_extraction(
+        &self,
+        email: &Email,
+        trial: Option<&ExtractionTrial>,
+    ) -> Result<(EmailFact, Usage)> {
+        let locale = self.sqlite.get_user_locale().await?;
+        let limit = self
+            .sqlite
+            .get_all_config()
+            .await?
+            .max_extraction_body_chars;
+        let body = truncate::truncate_body(&email.body_text, limit as usize);
+        let data = injection::data_block(&format!(
+            "Subject: {}\nFrom: {}\n\n{}",
+            email.subject, email.sender, body.text
+        ));
+        let prompt = match trial.and_then(|t| t.template.as_deref()) {
+            Some(template) => format!(
+                "{}\n\n{}",
+                template.replace("{{email}}", &data),
+                injection::DATA_INSTRUCTION
+            ),
+            None => format!(
             "Analyze the following email and extract structured project health signals.
 You must assign the email to exactly one client_or_project.
 Classify the primary_type, intent, urgency, and sentiment carefully based on the rules.
@@ -79,14 +684,15 @@ Rules:
 - sentiment: 'neutral', 'positive', 'concerned', 'hostile'.
 - waiting_on: 'me', 'them', 'third_party', 'none'.
 - severity: 'low', 'medium', 'high'.
-- due_by: ISO8601 string or null.
+- due_by: ISO8601 string if the exact date is clear, otherwise the deadline phrase exactly as written (e.g. \"EOD Friday\"), or null.
+- Write summary, key_points, titles, details and answer summaries in {}; keep the values listed above in English.
 
 Respond ONLY with valid JSON matching this schema:
 {{
   \"primary_type\": \"update|request|decision|fyi\",
   \"intent\": \"inform|ask|escalate|commit|clarify|resolve\",
   \"urgency\": \"low|medium|high\",
-  \"due_by\": \"YYYY-MM-DDTHH:MM:SSZ\" or null,
+  \"due_by\": \"YYYY-MM-DDTHH:MM:SSZ\" or \"deadline phrase\" or null,
   \"sentiment\": \"neutral|positive|concerned|hostile\",
   \"client_or_project\": {{ \"name\": \"string\", \"confidence\": 0.0-1.0 }},
   \"risks\": [
@@ -111,11 +717,24 @@ Respond ONLY with valid JSON matching this schema:
   \"confidence\": 0.0-1.0
 }}
 
-Subject: {}
-From: {}
-Body: {}",
-            email.subject, email.sender, email.body_text
-        );
+{}
+
+{}",
+            locale.language(),
+            injection::DATA_INSTRUCTION,
+            data
+        ),
+        };
+        // Emails steering the model still get facts, flagged for a human.
+        let scan = injection::scan(&format!("{}\n{}", email.subject, body.text));
+        let review_reason = scan.is_suspicious().then(|| {
+            warn!(
+                "Email {} looks like a prompt injection attempt: {}",
+                email.id,
+                scan.signals.join(", ")
+            );
+            format!("Possible prompt injection: {}", scan.signals.join(", "))
+        });
 
         let request = ChatRequest {
             messages: vec![Message {
@@ -123,74 +742,196 @@ Body: {}",
                 content: prompt,
             }],
             temperature: 0.0,
-            response_format: Some(ai::provider::ResponseFormat::Json),
-            model: None,
+            response_format: Some(ResponseFormat::JsonSchema {
+                json_schema: JsonSchemaFormat {
+                    name: schema::EMAIL_FACTS_SCHEMA_NAME.into(),
+                    schema: schema::email_facts_schema(),
+                    strict: true,
+                },
+            }),
+            model: trial.and_then(|t| t.model.clone()),
         };
 
         let ai = self.ai.read().await;
-        // Retry logic could be added here
-        let response = ai.chat_completion(request).await?;
+        let response = self
+            .llm_call("extraction", trial.is_some(), ai.chat_completion(request))
+            .await?;
+        let mut usage = response.usage;
+
+        // Providers that enforce the schema return it as-is; with the others,
+        // output that doesn't parse or match gets one repair pass.
+        let mut content = response.content;
+        let mut produced_by = (response.provider, response.model);
+        if !ai.supports_structured_output().await {
+            let problems = schema_problems(&content);
+            if !problems.is_empty() {
+                warn!(
+                    "Extraction for email {} did not match the schema; repairing",
+                    email.id
+                );
+                let mut repair = repair_request(&schema::email_facts_schema(), &content, &problems);
+                repair.model = trial.and_then(|t| t.model.clone());
+                let repaired = self
+                    .llm_call(
+                        "extraction_repair",
+                        trial.is_some(),
+                        ai.chat_completion(repair),
+                    )
+                    .await?;
+                usage.prompt_tokens += repaired.usage.prompt_tokens;
+                usage.completion_tokens += repaired.usage.completion_tokens;
+                content = repaired.content;
+                produced_by = (repaired.provider, repaired.model);
+            }
+        }
 
-        // Attempt to parse directly into EmailFact-compatible struct or generic Value then map
-        // We parse to Value first to handle defaults/errors gracefully
-        let fact_data: serde_json::Value =
-            serde_json::from_str(&response.content).map_err(|e: serde_json::Error| {
+        // Parsed to a Value first so missing or odd fields fall back to defaults
+        let mut fact_data: serde_json::Value =
+            serde_json::from_str(&content).map_err(|e: serde_json::Error| {
                 noodle_core::error::NoodleError::AI(format!(
                     "JSON Parse Error: {} Content: {}",
-                    e, response.content
+                    e, content
                 ))
             })?;
 
-        // Helper to parse enums defaults
-        let primary_type = serde_json::from_value(fact_data["primary_type"].clone())
-            .unwrap_or(noodle_core::types::PrimaryType::Fyi);
-        let intent = serde_json::from_value(fact_data["intent"].clone())
-            .unwrap_or(noodle_core::types::Intent::Inform);
-        let urgency = serde_json::from_value(fact_data["urgency"].clone())
-            .unwrap_or(noodle_core::types::Urgency::Low);
-        let sentiment = serde_json::from_value(fact_data["sentiment"].clone())
-            .unwrap_or(noodle_core::types::Sentiment::Neutral);
-        let waiting_on = serde_json::from_value(fact_data["waiting_on"].clone())
-            .unwrap_or(noodle_core::types::WaitingOn::None);
-
-        let due_by_str = fact_data["due_by"].as_str();
-        let due_by = due_by_str.and_then(|s| {
-            chrono::DateTime::parse_from_rfc3339(s)
-                .ok()
-                .map(|dt| dt.with_timezone(&Utc))
-        });
+        // Missing fields get defaults; values outside the schema fail the email.
+        let primary_type = sanitize::strict_field(
+            &fact_data,
+            "primary_type",
+            noodle_core::types::PrimaryType::Fyi,
+        )?;
+        let intent =
+            sanitize::strict_field(&fact_data, "intent", noodle_core::types::Intent::Inform)?;
+        let urgency =
+            sanitize::strict_field(&fact_data, "urgency", noodle_core::types::Urgency::Low)?;
+        let sentiment = sanitize::strict_field(
+            &fact_data,
+            "sentiment",
+            noodle_core::types::Sentiment::Neutral,
+        )?;
+        let waiting_on = sanitize::strict_field(
+            &fact_data,
+            "waiting_on",
+            noodle_core::types::WaitingOn::None,
+        )?;
+
+        // Relative deadlines ("EOD Friday") are resolved against when the email was sent,
+        // in the user's timezone.
+        let anchor = self
+            .sqlite
+            .get_user_timezone()
+            .await?
+            .to_local(email.sent_at);
+        let due_by_raw = fact_data["due_by"]
+            .as_str()
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+            .map(str::to_string);
+        let due_by = due_by_raw
+            .as_deref()
+            .and_then(|s| dates::normalize_due_date(s, anchor));
+
+        if let Some(questions) = fact_data["open_questions"].as_array_mut() {
+            for question in questions {
+                let raw = question["due_by"]
+                    .as_str()
+                    .map(str::trim)
+                    .filter(|s| !s.is_empty())
+                    .map(str::to_string);
+                let normalized = raw
+                    .as_deref()
+                    .and_then(|s| dates::normalize_due_date(s, anchor));
+                question["due_by"] = serde_json::json!(normalized);
+                question["due_by_raw"] = serde_json::json!(raw);
+            }
+        }
 
-        Ok(EmailFact {
+        let mut facts = EmailFact {
             email_id: email.id,
             primary_type,
             intent,
-            client_or_project: serde_json::from_value(fact_data["client_or_project"].clone())
-                .unwrap_or(ProjectInfo {
+            client_or_project: sanitize::strict_field(
+                &fact_data,
+                "client_or_project",
+                ProjectInfo {
                     name: "Unknown".into(),
                     confidence: 0.0,
-                }),
+                },
+            )?,
             sentiment,
             urgency,
             due_by,
+            due_by_raw,
             needs_response: fact_data["needs_response"].as_bool().unwrap_or(false),
             waiting_on,
             summary: fact_data["summary"].as_str().unwrap_or("").into(),
-            key_points: serde_json::from_value(fact_data["key_points"].clone()).unwrap_or_default(),
-            risks: serde_json::from_value(fact_data["risks"].clone()).unwrap_or_default(),
-            issues: serde_json::from_value(fact_data["issues"].clone()).unwrap_or_default(),
-            blockers: serde_json::from_value(fact_data["blockers"].clone()).unwrap_or_default(),
-            open_questions: serde_json::from_value(fact_data["open_questions"].clone())
-                .unwrap_or_default(),
-            answered_questions: serde_json::from_value(fact_data["answered_questions"].clone())
-                .unwrap_or_default(),
+            key_points: sanitize::strict_field(&fact_data, "key_points", Vec::new())?,
+            risks: sanitize::strict_field(&fact_data, "risks", Vec::new())?,
+            issues: sanitize::strict_field(&fact_data, "issues", Vec::new())?,
+            blockers: sanitize::strict_field(&fact_data, "blockers", Vec::new())?,
+            open_questions: sanitize::strict_field(&fact_data, "open_questions", Vec::new())?,
+            answered_questions: sanitize::strict_field(
+                &fact_data,
+                "answered_questions",
+                Vec::new(),
+            )?,
             confidence: fact_data["confidence"].as_f64().unwrap_or(0.0) as f32,
+            review_reason,
             provenance: Provenance {
-                model: "local".into(),
-                provider: "local".into(),
+                model: produced_by.1.unwrap_or_else(|| "unknown".into()),
+                provider: produced_by.0.unwrap_or_else(|| "unknown".into()),
                 prompt_id: Uuid::new_v4(),
                 created_at: Utc::now(),
+                truncated: body.truncated,
             },
             created_at: Utc::now(),
-        })
+        };
+        sanitize::scrub(&mut facts);
+        Ok((facts, usage))
+    }
+
+    /// Paces an extraction call as `kind`; live ones are also timed for the
+    /// metrics. Trials, which may use another model, are paced apart.
+    async fn llm_call<F: std::future::Future>(
+        &self,
+        kind: &'static str,
+        trial: bool,
+        call: F,
+    ) -> F::Output {
+        if trial {
+            self.pacing.llm_call("experiment", call).await
+        } else {
+            self.pacing
+                .llm_call(kind, self.metrics.time(Stage::Extraction, call))
+                .await
+        }
+    }
+}
+
+/// The prompt and model a trial extraction runs with; see
+/// [`ExtractionPipeline::trial_extraction`].
+#[derive(Debug, Clone, Default)]
+pub struct ExtractionTrial {
+    /// A prompt whose `{{email}}` is replaced by the email; the built-in
+    /// prompt when `None`.
+    pub template: Option<String>,
+    /// The configured model when `None`.
+    pub model: Option<String>,
+}
+
+/// [`schema::email_facts_schema`], compiled on first use.
+static FACTS_VALIDATOR: LazyLock<SchemaValidator> = LazyLock::new(|| {
+    SchemaValidator::new(&schema::email_facts_schema()).expect("the facts schema compiles")
+});
+
+/// Why `content` is not valid extraction output; empty when it is.
+fn schema_problems(content: &str) -> Vec<String> {
+    match serde_json::from_str(content) {
+        Ok(value) => FACTS_VALIDATOR.errors(&value),
+        Err(e) => vec![format!("not valid JSON: {}", e)],
     }
 }
+
+fn cancelled() -> noodle_core::error::NoodleError {
+    noodle_core::error::NoodleError::Internal("Cancelled".into())
+}
@@ -0,0 +1,57 @@
+use chrono::Duration;
+use noodle_core::error::Result;
+use noodle_core::types::Email;
+use storage::sqlite::SqliteStorage;
+
+/// First word of the local part of addresses bulk mail is sent from.
+const BULK_SENDER_PREFIXES: &[&str] = &[
+    "newsletter",
+    "newsletters",
+    "news",
+    "digest",
+    "bulletin",
+    "updates",
+    "marketing",
+    "mailer",
+    "noreply",
+    "no-reply",
+    "donotreply",
+    "do-not-reply",
+];
+/// Emails, this one included, a sender has to have sent within the window
+/// to count as a newsletter.
+const RECURRING_MIN_EMAILS: i64 = 3;
+const RECURRING_WINDOW_DAYS: i64 = 60;
+
+/// Whether `email` is a recurring newsletter: regular mail that is either
+/// mailing list mail, carrying a `List-Unsubscribe` header, or from an
+/// address that looks like a bulk sender. Senders the user has replied to
+/// are people, however their mail is sent, and never count.
+pub async fn is_newsletter(sqlite: &SqliteStorage, email: &Email) -> Result<bool> {
+    if email.list_unsubscribe.is_none() && !looks_like_bulk_sender(&email.sender) {
+        return Ok(false);
+    }
+    if sqlite.has_replied_to(&email.sender).await? {
+        return Ok(false);
+    }
+    let since = email.received_at - Duration::days(RECURRING_WINDOW_DAYS);
+    let recent = sqlite
+        .count_emails_from(&email.sender, since, email.received_at)
+        .await?;
+    Ok(recent >= RECURRING_MIN_EMAILS)
+}
+
+/// Whether the local part of `sender` starts with one of
+/// [`BULK_SENDER_PREFIXES`] as a whole word, as in `news@` or `news.eu@`
+/// but not `newsom@`.
+pub fn looks_like_bulk_sender(sender: &str) -> bool {
+    let sender = sender.to_lowercase();
+    let Some((local, _)) = sender.split_once('@') else {
+        return false;
+    };
+    BULK_SENDER_PREFIXES.iter().any(|prefix| {
+        local
+            .strip_prefix(prefix)
+            .is_some_and(|rest| !rest.starts_with(|c: char| c.is_alphabetic()))
+    })
+}
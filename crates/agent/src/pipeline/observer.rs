@@ -0,0 +1,29 @@
+use noodle_core::events::{AppEvent, EmailProgress, Notifier};
+use noodle_core::types::{Email, ProcessStage};
+use std::sync::Arc;
+
+/// Told about each step [`super::ExtractionPipeline`] finishes for an email.
+/// Called inline from the pipeline, so it must not block.
+pub trait ProcessObserver: Send + Sync {
+    fn stage_completed(&self, email: &Email, stage: ProcessStage);
+}
+
+/// Reports progress as [`AppEvent::EmailProgress`] events.
+pub struct EventObserver {
+    notifier: Arc<dyn Notifier>,
+}
+
+impl EventObserver {
+    pub fn new(notifier: Arc<dyn Notifier>) -> Self {
+        Self { notifier }
+    }
+}
+
+impl ProcessObserver for EventObserver {
+    fn stage_completed(&self, email: &Email, stage: ProcessStage) {
+        self.notifier.notify(AppEvent::EmailProgress(EmailProgress {
+            email_id: email.id,
+            stage,
+        }));
+    }
+}
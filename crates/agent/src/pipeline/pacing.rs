@@ -0,0 +1,182 @@
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::Mutex;
+use tokio::sync::Semaphore;
+use tokio::time::{sleep_until, Duration, Instant};
+
+/// `app_config` keys read when configuring the pacing controller.
+pub const MAX_CONCURRENCY_KEY: &str = "llm_max_concurrency";
+pub const LOW_IMPACT_MODE_KEY: &str = "low_impact_mode";
+pub const LOW_IMPACT_RATE_KEY: &str = "low_impact_emails_per_minute";
+
+pub const DEFAULT_MAX_CONCURRENCY: usize = 2;
+pub const DEFAULT_LOW_IMPACT_RATE: u32 = 6;
+
+/// Weight of the newest sample in the latency moving average.
+const LATENCY_SMOOTHING: f64 = 0.3;
+/// A call this many times slower than average means the machine is saturated.
+const SLOWDOWN_FACTOR: u32 = 2;
+/// How often a call held for a meeting checks whether it still is, so a
+/// cancelled meeting or a changed setting lets it go.
+const BUSY_RECHECK: Duration = Duration::from_secs(60);
+
+#[derive(Debug, Clone, Copy)]
+pub struct PacingConfig {
+    pub max_concurrency: usize,
+    /// Low impact mode: start at most this many emails per minute.
+    pub emails_per_minute: Option<u32>,
+}
+
+impl Default for PacingConfig {
+    fn default() -> Self {
+        Self {
+            max_concurrency: DEFAULT_MAX_CONCURRENCY,
+            emails_per_minute: None,
+        }
+    }
+}
+
+struct PacingState {
+    config: PacingConfig,
+    next_email_at: Instant,
+    cooldown_until: Instant,
+    /// Moving average per call kind; embeddings and chat completions differ by
+    /// orders of magnitude and must not be compared against each other.
+    avg_latency: HashMap<&'static str, Duration>,
+    /// Meetings and other busy time in the user's calendar, during which no
+    /// call starts.
+    busy: Vec<(DateTime<Utc>, DateTime<Utc>)>,
+}
+
+/// Keeps local inference from saturating the machine: caps concurrent LLM
+/// calls, backs off for one call's duration whenever a call runs much slower
+/// than the moving average, and in low impact mode spaces out emails. Calls
+/// wait out the busy times set by [`Self::set_busy_times`].
+pub struct PacingController {
+    permits: Semaphore,
+    state: Mutex<PacingState>,
+}
+
+impl Default for PacingController {
+    fn default() -> Self {
+        Self::new(PacingConfig::default())
+    }
+}
+
+impl PacingController {
+    pub fn new(config: PacingConfig) -> Self {
+        let now = Instant::now();
+        Self {
+            permits: Semaphore::new(config.max_concurrency.max(1)),
+            state: Mutex::new(PacingState {
+                config,
+                next_email_at: now,
+                cooldown_until: now,
+                avg_latency: HashMap::new(),
+                busy: Vec::new(),
+            }),
+        }
+    }
+
+    /// Applies new limits; concurrency changes take effect as permits free up.
+    pub fn configure(&self, config: PacingConfig) {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        let current = state.config.max_concurrency.max(1);
+        let wanted = config.max_concurrency.max(1);
+        if wanted > current {
+            self.permits.add_permits(wanted - current);
+        } else if wanted < current {
+            let forgotten = self.permits.forget_permits(current - wanted);
+            // Permits held by in-flight calls can't be forgotten; keep the
+            // bookkeeping in line with what the semaphore really has.
+            state.config.max_concurrency = current - forgotten;
+            state.config.emails_per_minute = config.emails_per_minute;
+            return;
+        }
+        state.config = config;
+    }
+
+    /// LLM calls running now, and the current concurrency cap.
+    pub fn workers(&self) -> (usize, usize) {
+        let max = self
+            .state
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .config
+            .max_concurrency
+            .max(1);
+        (max.saturating_sub(self.permits.available_permits()), max)
+    }
+
+    /// Waits for the next email slot. A no-op unless low impact mode is on.
+    pub async fn email_slot(&self) {
+        let slot = {
+            let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+            let Some(rate) = state.config.emails_per_minute.filter(|r| *r > 0) else {
+                return;
+            };
+            let slot = state.next_email_at.max(Instant::now());
+            state.next_email_at = slot + Duration::from_secs(60) / rate;
+            slot
+        };
+        sleep_until(slot).await;
+    }
+
+    /// Replaces the times the user is busy; empty lets calls run again.
+    pub fn set_busy_times(&self, busy: Vec<(DateTime<Utc>, DateTime<Utc>)>) {
+        self.state.lock().unwrap_or_else(|e| e.into_inner()).busy = busy;
+    }
+
+    /// When the busy time `now` falls in ends, following back-to-back
+    /// meetings through; `None` when the user is free.
+    pub fn busy_until(&self, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        let state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        let mut until = now;
+        while let Some(end) = state
+            .busy
+            .iter()
+            .filter(|(start, end)| *start <= until && *end > until)
+            .map(|(_, end)| *end)
+            .max()
+        {
+            until = end;
+        }
+        (until > now).then_some(until)
+    }
+
+    /// Runs one LLM call under the concurrency cap and records its latency
+    /// against other calls of the same `kind`. Waits while the user is busy.
+    pub async fn llm_call<F: Future>(&self, kind: &'static str, call: F) -> F::Output {
+        while let Some(until) = self.busy_until(Utc::now()) {
+            let wait = (until - Utc::now()).to_std().unwrap_or_default();
+            tokio::time::sleep(wait.min(BUSY_RECHECK)).await;
+        }
+        let _permit = self.permits.acquire().await;
+        let cooldown = self
+            .state
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .cooldown_until;
+        sleep_until(cooldown).await;
+
+        let started = Instant::now();
+        let output = call.await;
+        self.record_latency(kind, started.elapsed());
+        output
+    }
+
+    fn record_latency(&self, kind: &'static str, latency: Duration) {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        let avg = match state.avg_latency.get(kind) {
+            Some(&avg) => {
+                if latency > avg * SLOWDOWN_FACTOR {
+                    state.cooldown_until = Instant::now() + latency;
+                }
+                avg.mul_f64(1.0 - LATENCY_SMOOTHING) + latency.mul_f64(LATENCY_SMOOTHING)
+            }
+            None => latency,
+        };
+        state.avg_latency.insert(kind, avg);
+    }
+}
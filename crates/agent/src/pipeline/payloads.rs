@@ -0,0 +1,70 @@
+use super::ExtractionPipeline;
+use noodle_core::error::Result;
+use tracing::{info, warn};
+
+/// Payloads pushed per sync pass.
+const PAYLOAD_SYNC_BATCH: i64 = 200;
+
+impl ExtractionPipeline {
+    /// Wakes the payload sync worker, e.g. right after the user tagged an
+    /// email, so vector search sees the change without waiting for its
+    /// next pass.
+    pub fn request_payload_sync(&self) {
+        self.payload_sync.notify_one();
+    }
+
+    /// Resolves once [`Self::request_payload_sync`] has been called.
+    pub async fn payload_sync_requested(&self) {
+        self.payload_sync.notified().await
+    }
+
+    /// Pushes the project and tags of emails queued in the payload outbox
+    /// to their Qdrant points. Returns how many were synced; failures stay
+    /// queued for the next pass.
+    pub async fn sync_vector_payloads(&self) -> Result<usize> {
+        // Without a client nothing could be updated, and settling the
+        // entries would leave existing points stale once Qdrant is back.
+        if !self.qdrant.is_available() {
+            return Ok(0);
+        }
+        let Some(_work) = self.shutdown.begin_work() else {
+            return Ok(0);
+        };
+        let pending = self
+            .sqlite
+            .get_pending_payload_syncs(PAYLOAD_SYNC_BATCH)
+            .await?;
+        let mut synced = 0;
+
+        for (email_id, enqueued_at) in pending {
+            if self.shutdown.is_shutting_down() {
+                break;
+            }
+            let Some(fields) = self.sqlite.get_vector_payload(email_id).await? else {
+                self.sqlite
+                    .complete_payload_sync(email_id, &enqueued_at)
+                    .await?;
+                continue;
+            };
+            match self.qdrant.set_email_payload(&fields).await {
+                Ok(()) => {
+                    self.sqlite
+                        .complete_payload_sync(email_id, &enqueued_at)
+                        .await?;
+                    synced += 1;
+                }
+                Err(e) => {
+                    warn!("Payload sync failed for email {}: {}", email_id, e);
+                    self.sqlite
+                        .fail_payload_sync(email_id, &e.to_string())
+                        .await?;
+                }
+            }
+        }
+
+        if synced > 0 {
+            info!("Synced {} vector payloads", synced);
+        }
+        Ok(synced)
+    }
+}
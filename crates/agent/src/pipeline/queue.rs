@@ -0,0 +1,234 @@
+use chrono::{DateTime, Utc};
+use noodle_core::types::{ActiveEmail, Email, PipelineStage, QueuePriority, QueuedEmail};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::Mutex;
+use tokio_util::sync::CancellationToken;
+
+/// Delta emails processed in a row before one backfill email gets a turn,
+/// so a steady stream of new mail can't stall the backfill.
+pub const DELTA_BURST: usize = 5;
+
+struct Active {
+    /// Tells this run apart from a later one of the same email.
+    run: u64,
+    subject: String,
+    stage: PipelineStage,
+    started_at: DateTime<Utc>,
+    cancel: CancellationToken,
+}
+
+#[derive(Default)]
+struct QueueState {
+    vip: VecDeque<Email>,
+    delta: VecDeque<Email>,
+    backfill: VecDeque<Email>,
+    /// Delta emails taken since the last backfill one.
+    delta_streak: usize,
+    active: HashMap<String, Active>,
+    /// Runs started so far, numbering the next one.
+    runs: u64,
+}
+
+impl QueueState {
+    fn pending_mut(&mut self, priority: QueuePriority) -> &mut VecDeque<Email> {
+        match priority {
+            QueuePriority::Vip => &mut self.vip,
+            QueuePriority::Delta => &mut self.delta,
+            QueuePriority::Backfill => &mut self.backfill,
+        }
+    }
+
+    fn pending(&self) -> impl Iterator<Item = (&Email, QueuePriority)> {
+        let tagged = |priority| move |e| (e, priority);
+        self.vip
+            .iter()
+            .map(tagged(QueuePriority::Vip))
+            .chain(self.delta.iter().map(tagged(QueuePriority::Delta)))
+            .chain(self.backfill.iter().map(tagged(QueuePriority::Backfill)))
+    }
+}
+
+/// Tracks what the pipeline has to do: emails scans fetched but have not
+/// processed yet, and the ones being processed now. VIP emails are taken
+/// first, then delta emails before backfill ones, except that every
+/// [`DELTA_BURST`] delta emails the backfill gets one turn. Lets the user
+/// cancel pending and in-progress emails by Outlook entry id.
+#[derive(Default)]
+pub struct WorkQueue {
+    state: Mutex<QueueState>,
+}
+
+/// Marks one email as in progress until dropped. When the same email is
+/// started again meanwhile, only the newer run is tracked, and this guard
+/// leaves it alone.
+pub struct ActiveGuard<'a> {
+    queue: &'a WorkQueue,
+    entry_id: String,
+    run: u64,
+    cancel: CancellationToken,
+}
+
+impl ActiveGuard<'_> {
+    pub fn set_stage(&self, stage: PipelineStage) {
+        let mut state = self.queue.lock();
+        if let Some(active) = state
+            .active
+            .get_mut(&self.entry_id)
+            .filter(|a| a.run == self.run)
+        {
+            active.stage = stage;
+        }
+    }
+
+    /// Resolves when the user cancels this email.
+    pub async fn cancelled(&self) {
+        self.cancel.cancelled().await
+    }
+}
+
+impl Drop for ActiveGuard<'_> {
+    fn drop(&mut self) {
+        let mut state = self.queue.lock();
+        if state
+            .active
+            .get(&self.entry_id)
+            .is_some_and(|a| a.run == self.run)
+        {
+            state.active.remove(&self.entry_id);
+        }
+    }
+}
+
+impl WorkQueue {
+    fn lock(&self) -> std::sync::MutexGuard<'_, QueueState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Adds a scan's emails behind others of the same priority, in the order
+    /// they will be processed. Emails already waiting keep their place.
+    pub fn enqueue(&self, emails: Vec<Email>, priority: QueuePriority) {
+        let mut state = self.lock();
+        let waiting: HashSet<String> = state.pending().map(|(e, _)| e.entry_id.clone()).collect();
+        state.pending_mut(priority).extend(
+            emails
+                .into_iter()
+                .filter(|e| !waiting.contains(&e.entry_id)),
+        );
+    }
+
+    /// Takes the next email to process.
+    pub fn next(&self) -> Option<(Email, QueuePriority)> {
+        let mut state = self.lock();
+        if let Some(email) = state.vip.pop_front() {
+            return Some((email, QueuePriority::Vip));
+        }
+        let backfill_turn = state.delta_streak >= DELTA_BURST && !state.backfill.is_empty();
+        if !backfill_turn {
+            if let Some(email) = state.delta.pop_front() {
+                state.delta_streak += 1;
+                return Some((email, QueuePriority::Delta));
+            }
+        }
+        state.delta_streak = 0;
+        state
+            .backfill
+            .pop_front()
+            .map(|email| (email, QueuePriority::Backfill))
+    }
+
+    /// Whether emails of `folder` are still waiting at `priority`. VIP
+    /// emails count as backfill, which they were taken out of.
+    pub fn has_pending(&self, priority: QueuePriority, folder: &str) -> bool {
+        self.lock().pending().any(|(e, p)| {
+            e.folder == folder
+                && (p == priority
+                    || (p == QueuePriority::Vip && priority == QueuePriority::Backfill))
+        })
+    }
+
+    /// Forgets whatever the scans left pending.
+    pub fn clear_pending(&self) {
+        let mut state = self.lock();
+        state.vip.clear();
+        state.delta.clear();
+        state.backfill.clear();
+        state.delta_streak = 0;
+    }
+
+    pub fn start(&self, email: &Email) -> ActiveGuard<'_> {
+        let cancel = CancellationToken::new();
+        let mut state = self.lock();
+        state.runs += 1;
+        let run = state.runs;
+        state.active.insert(
+            email.entry_id.clone(),
+            Active {
+                run,
+                subject: email.subject.clone(),
+                stage: PipelineStage::Waiting,
+                started_at: Utc::now(),
+                cancel: cancel.clone(),
+            },
+        );
+        drop(state);
+        ActiveGuard {
+            queue: self,
+            entry_id: email.entry_id.clone(),
+            run,
+            cancel,
+        }
+    }
+
+    /// Cancels a pending or in-progress email. Returns `false` if it is neither.
+    pub fn cancel(&self, entry_id: &str) -> bool {
+        let mut state = self.lock();
+        if let Some(active) = state.active.get(entry_id) {
+            active.cancel.cancel();
+            return true;
+        }
+        for priority in [
+            QueuePriority::Vip,
+            QueuePriority::Delta,
+            QueuePriority::Backfill,
+        ] {
+            let pending = state.pending_mut(priority);
+            if let Some(pos) = pending.iter().position(|e| e.entry_id == entry_id) {
+                pending.remove(pos);
+                return true;
+            }
+        }
+        false
+    }
+
+    /// The first `limit` pending emails in the order they will be taken
+    /// (bursts aside), the pending total and everything in progress.
+    pub fn snapshot(&self, limit: usize) -> (Vec<QueuedEmail>, usize, Vec<ActiveEmail>) {
+        let state = self.lock();
+        let now = Utc::now();
+        let pending = state
+            .pending()
+            .take(limit)
+            .map(|(e, priority)| QueuedEmail {
+                entry_id: e.entry_id.clone(),
+                subject: e.subject.clone(),
+                folder: e.folder.clone(),
+                received_at: e.received_at,
+                priority,
+            })
+            .collect();
+        let mut active: Vec<ActiveEmail> = state
+            .active
+            .iter()
+            .map(|(entry_id, a)| ActiveEmail {
+                entry_id: entry_id.clone(),
+                subject: a.subject.clone(),
+                stage: a.stage,
+                started_at: a.started_at,
+                elapsed_ms: (now - a.started_at).num_milliseconds(),
+            })
+            .collect();
+        active.sort_by_key(|a| a.started_at);
+        let total = state.vip.len() + state.delta.len() + state.backfill.len();
+        (pending, total, active)
+    }
+}
@@ -0,0 +1,104 @@
+use noodle_core::error::{NoodleError, Result};
+use noodle_core::text::SanitizedText;
+use noodle_core::types::EmailFact;
+use serde::de::DeserializeOwned;
+
+/// Longest values kept per field, in characters. [`SanitizedText`] already
+/// caps everything at its own limit; these fit what the views show.
+const MAX_TITLE_CHARS: usize = 200;
+const MAX_DETAILS_CHARS: usize = 2000;
+const MAX_SUMMARY_CHARS: usize = 1000;
+const MAX_KEY_POINT_CHARS: usize = 500;
+const MAX_QUESTION_CHARS: usize = 500;
+const MAX_NAME_CHARS: usize = 100;
+/// Key points kept per email.
+const MAX_KEY_POINTS: usize = 20;
+
+/// Reads `field` of the model output as `T`. A missing or null field gets
+/// `default`; a value that isn't one `T` allows, such as an urgency the
+/// schema doesn't list, is an error rather than a silent default.
+pub fn strict_field<T: DeserializeOwned>(
+    data: &serde_json::Value,
+    field: &str,
+    default: T,
+) -> Result<T> {
+    match data.get(field) {
+        None | Some(serde_json::Value::Null) => Ok(default),
+        Some(value) => serde_json::from_value(value.clone()).map_err(|e| {
+            NoodleError::AI(format!(
+                "Invalid {} in model output: {} ({})",
+                field, value, e
+            ))
+        }),
+    }
+}
+
+/// Applies the per-field limits to `facts` and keeps confidences within
+/// 0–1, dropping list entries left without a title or question.
+pub fn scrub(facts: &mut EmailFact) {
+    let project = &mut facts.client_or_project;
+    project.name = limit(&project.name, MAX_NAME_CHARS).into_string();
+    if project.name.is_empty() {
+        project.name = "Unknown".into();
+    }
+    project.confidence = clamp_confidence(project.confidence);
+
+    facts.summary = limit(&facts.summary, MAX_SUMMARY_CHARS);
+    facts.key_points = facts
+        .key_points
+        .iter()
+        .map(|point| limit(point, MAX_KEY_POINT_CHARS))
+        .filter(|point| !point.is_empty())
+        .take(MAX_KEY_POINTS)
+        .collect();
+    facts.confidence = clamp_confidence(facts.confidence);
+
+    // Risks, issues and blockers share their shape.
+    macro_rules! scrub_concerns {
+        ($list:expr) => {
+            $list.retain_mut(|item| {
+                item.title = limit(&item.title, MAX_TITLE_CHARS);
+                item.details = limit(&item.details, MAX_DETAILS_CHARS);
+                item.owner = limit_name(item.owner.as_deref());
+                item.confidence = clamp_confidence(item.confidence);
+                !item.title.is_empty()
+            })
+        };
+    }
+    scrub_concerns!(facts.risks);
+    scrub_concerns!(facts.issues);
+    scrub_concerns!(facts.blockers);
+
+    facts.open_questions.retain_mut(|q| {
+        q.question = limit(&q.question, MAX_QUESTION_CHARS);
+        q.asked_by = limit_name(q.asked_by.as_deref());
+        q.owner = limit_name(q.owner.as_deref());
+        q.confidence = clamp_confidence(q.confidence);
+        !q.question.is_empty()
+    });
+    facts.answered_questions.retain_mut(|q| {
+        q.question = limit(&q.question, MAX_QUESTION_CHARS);
+        q.answer_summary = limit(&q.answer_summary, MAX_DETAILS_CHARS);
+        q.confidence = clamp_confidence(q.confidence);
+        !q.question.is_empty()
+    });
+}
+
+fn limit(text: &str, max_chars: usize) -> SanitizedText {
+    SanitizedText::with_limit(text, max_chars)
+}
+
+/// Names left empty by sanitizing count as not given.
+fn limit_name(name: Option<&str>) -> Option<SanitizedText> {
+    name.map(|n| limit(n, MAX_NAME_CHARS))
+        .filter(|n| !n.is_empty())
+}
+
+/// NaN, which models don't produce but broken output can, counts as 0.
+fn clamp_confidence(confidence: f32) -> f32 {
+    if confidence.is_nan() {
+        0.0
+    } else {
+        confidence.clamp(0.0, 1.0)
+    }
+}
@@ -0,0 +1,78 @@
+use serde_json::{json, Value};
+
+/// Name the extraction schema is sent under.
+pub const EMAIL_FACTS_SCHEMA_NAME: &str = "email_facts";
+
+/// JSON schema of the extraction output, mirroring the one spelled out in
+/// the prompt. Written to OpenAI's strict rules (every property required,
+/// no extra properties, optional values nullable) so servers can enforce it.
+pub fn email_facts_schema() -> Value {
+    let confidence = json!({ "type": "number", "minimum": 0, "maximum": 1 });
+    let severity = json!({ "type": "string", "enum": ["low", "medium", "high"] });
+    let nullable_string = json!({ "type": ["string", "null"] });
+    let signal = object(json!({
+        "title": { "type": "string" },
+        "details": { "type": "string" },
+        "owner": nullable_string,
+        "severity": severity,
+        "confidence": confidence,
+    }));
+
+    object(json!({
+        "primary_type": { "type": "string", "enum": ["update", "request", "decision", "fyi"] },
+        "intent": {
+            "type": "string",
+            "enum": ["inform", "ask", "escalate", "commit", "clarify", "resolve"]
+        },
+        "urgency": severity,
+        "due_by": nullable_string,
+        "sentiment": {
+            "type": "string",
+            "enum": ["neutral", "positive", "concerned", "hostile"]
+        },
+        "client_or_project": object(json!({
+            "name": { "type": "string" },
+            "confidence": confidence,
+        })),
+        "risks": { "type": "array", "items": signal },
+        "issues": { "type": "array", "items": signal },
+        "blockers": { "type": "array", "items": signal },
+        "open_questions": {
+            "type": "array",
+            "items": object(json!({
+                "question": { "type": "string" },
+                "asked_by": nullable_string,
+                "owner": nullable_string,
+                "due_by": nullable_string,
+                "confidence": confidence,
+            }))
+        },
+        "answered_questions": {
+            "type": "array",
+            "items": object(json!({
+                "question": { "type": "string" },
+                "answer_summary": { "type": "string" },
+                "confidence": confidence,
+            }))
+        },
+        "needs_response": { "type": "boolean" },
+        "waiting_on": { "type": "string", "enum": ["me", "them", "third_party", "none"] },
+        "summary": { "type": "string" },
+        "key_points": { "type": "array", "items": { "type": "string" } },
+        "confidence": confidence,
+    }))
+}
+
+/// An object schema requiring all of `properties` and nothing else.
+fn object(properties: Value) -> Value {
+    let required: Vec<&String> = properties
+        .as_object()
+        .map(|p| p.keys().collect())
+        .unwrap_or_default();
+    json!({
+        "type": "object",
+        "properties": properties,
+        "required": required,
+        "additionalProperties": false,
+    })
+}
@@ -0,0 +1,79 @@
+/// Share of the limit given to the start of the body; the rest keeps its end,
+/// where sign-offs and the actual ask often are.
+const HEAD_SHARE: f64 = 0.7;
+
+/// Lines that start the quoted history of a reply or forward.
+const HISTORY_MARKERS: &[&str] = &[
+    "-----original message-----",
+    "----- original message -----",
+    "-----forwarded message-----",
+    "---------- forwarded message ---------",
+    "________________________________",
+];
+
+/// A body cut down to fit a limit.
+pub struct Truncated {
+    pub text: String,
+    /// Whether anything was removed.
+    pub truncated: bool,
+}
+
+/// Fits `body` into `max_chars`. Bodies within the limit are kept whole.
+/// Longer ones first lose the quoted history of earlier messages, and if
+/// that is not enough, the middle, keeping the head and the tail.
+pub fn truncate_body(body: &str, max_chars: usize) -> Truncated {
+    if body.chars().count() <= max_chars {
+        return Truncated {
+            text: body.to_string(),
+            truncated: false,
+        };
+    }
+
+    // A forward with nothing above the history keeps the history.
+    let text = Some(strip_history(body))
+        .filter(|t| !t.is_empty())
+        .unwrap_or_else(|| body.to_string());
+    let len = text.chars().count();
+    if len <= max_chars {
+        return Truncated {
+            text,
+            truncated: true,
+        };
+    }
+
+    let head = (max_chars as f64 * HEAD_SHARE) as usize;
+    let tail = max_chars.saturating_sub(head);
+    let start: String = text.chars().take(head).collect();
+    let end: String = text.chars().skip(len - tail).collect();
+    Truncated {
+        text: format!(
+            "{}\n\n[… {} characters omitted …]\n\n{}",
+            start,
+            len - head - tail,
+            end
+        ),
+        truncated: true,
+    }
+}
+
+/// Drops everything from the first quoted-history marker on, and `>` quoted
+/// lines before it.
+fn strip_history(body: &str) -> String {
+    let mut kept = Vec::new();
+    for line in body.lines() {
+        let trimmed = line.trim();
+        if is_history_start(trimmed) {
+            break;
+        }
+        if !trimmed.starts_with('>') {
+            kept.push(line);
+        }
+    }
+    kept.join("\n").trim_end().to_string()
+}
+
+fn is_history_start(line: &str) -> bool {
+    let lower = line.to_lowercase();
+    HISTORY_MARKERS.iter().any(|m| lower.starts_with(m))
+        || (lower.starts_with("on ") && lower.ends_with(" wrote:"))
+}
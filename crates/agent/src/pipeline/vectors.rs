@@ -0,0 +1,150 @@
+use super::ExtractionPipeline;
+use chrono::Utc;
+use noodle_core::error::{NoodleError, Result};
+use noodle_core::types::{VectorRepairReport, VectorStats};
+use std::collections::{HashMap, HashSet};
+use storage::qdrant::COLLECTION_EMAILS;
+use tracing::{info, warn};
+
+/// Missing email ids listed in [`VectorStats`].
+const MISSING_SAMPLE: usize = 50;
+
+/// The comparison behind [`VectorStats`], with everything repair needs.
+struct VectorCheck {
+    stats: VectorStats,
+    orphans: Vec<u64>,
+    missing: Vec<i64>,
+}
+
+impl ExtractionPipeline {
+    /// Compares the Qdrant email collection with SQLite: point and email
+    /// counts, orphaned points, emails without a vector, and whether the
+    /// embedding model still produces vectors of the collection's size.
+    pub async fn vector_stats(&self) -> Result<VectorStats> {
+        Ok(self.check_vectors().await?.stats)
+    }
+
+    /// Deletes orphaned points and re-embeds emails that have no vector.
+    pub async fn repair_vectors(&self) -> Result<VectorRepairReport> {
+        let Some(_work) = self.shutdown.begin_work() else {
+            return Err(NoodleError::Internal("Shutting down".into()));
+        };
+        let started = Utc::now();
+        let check = self.check_vectors().await?;
+        if !check.stats.available {
+            return Err(NoodleError::Validation("Qdrant is not available".into()));
+        }
+        if !check.stats.dimensions_match() {
+            return Err(NoodleError::Validation(format!(
+                "The embedding model produces {}-dimensional vectors but the collection \
+                 expects {}; re-embedding would fail",
+                check.stats.embedding_dim.unwrap_or_default(),
+                check.stats.collection_dim.unwrap_or_default()
+            )));
+        }
+
+        self.qdrant
+            .delete_point_ids(COLLECTION_EMAILS, &check.orphans)
+            .await?;
+
+        let mut reembedded = 0;
+        let mut failed = 0;
+        for id in &check.missing {
+            if self.shutdown.is_shutting_down() {
+                break;
+            }
+            let Some(email) = self.sqlite.get_email(*id).await? else {
+                continue;
+            };
+            // embed settles the entry, or records the failure for the replay.
+            self.sqlite.enqueue_vector_upsert(*id).await?;
+            match self.embed(&email).await {
+                Ok(()) => reembedded += 1,
+                Err(e) => {
+                    warn!("Re-embedding email {} failed: {}", id, e);
+                    failed += 1;
+                }
+            }
+        }
+
+        info!(
+            "Vector repair deleted {} orphaned points, re-embedded {} emails ({} failed)",
+            check.orphans.len(),
+            reembedded,
+            failed
+        );
+        Ok(VectorRepairReport {
+            orphans_deleted: check.orphans.len(),
+            reembedded,
+            failed,
+            duration_ms: (Utc::now() - started).num_milliseconds(),
+        })
+    }
+
+    async fn check_vectors(&self) -> Result<VectorCheck> {
+        let emails = self.sqlite.list_email_keys().await?;
+        let mut stats = VectorStats {
+            available: self.qdrant.is_available(),
+            email_count: emails.len() as i64,
+            ..Default::default()
+        };
+        if !stats.available {
+            return Ok(VectorCheck {
+                stats,
+                orphans: Vec::new(),
+                missing: Vec::new(),
+            });
+        }
+
+        let points: HashSet<u64> = self
+            .qdrant
+            .list_point_ids(COLLECTION_EMAILS)
+            .await?
+            .into_iter()
+            .collect();
+        let expected: HashMap<u64, i64> = emails
+            .iter()
+            .map(|e| {
+                (
+                    self.qdrant.calculate_stable_id(&e.store_id, &e.entry_id),
+                    e.id,
+                )
+            })
+            .collect();
+        let orphans: Vec<u64> = points
+            .iter()
+            .filter(|id| !expected.contains_key(id))
+            .copied()
+            .collect();
+        let mut missing: Vec<i64> = expected
+            .iter()
+            .filter(|(point, _)| !points.contains(point))
+            .map(|(_, &email_id)| email_id)
+            .collect();
+        missing.sort_unstable();
+
+        stats.point_count = points.len();
+        stats.collection_dim = self.qdrant.collection_dim(COLLECTION_EMAILS).await?;
+        stats.embedding_dim = self.embedding_dim().await;
+        stats.orphaned_points = orphans.len();
+        stats.missing_vectors = missing.len();
+        stats.missing_email_ids = missing.iter().take(MISSING_SAMPLE).copied().collect();
+        Ok(VectorCheck {
+            stats,
+            orphans,
+            missing,
+        })
+    }
+
+    /// Size of the vectors the configured model produces, from a probe embedding.
+    async fn embedding_dim(&self) -> Option<u64> {
+        let ai = self.ai.read().await.clone();
+        match ai.generate_embedding("dimension check").await {
+            Ok(vector) => Some(vector.len() as u64),
+            Err(e) => {
+                warn!("Could not reach the embedding model: {}", e);
+                None
+            }
+        }
+    }
+}
@@ -0,0 +1,519 @@
+use ai::injection;
+use ai::provider::{AiProvider, ChatRequest, JsonSchemaFormat, Message, ResponseFormat};
+use ai::schema::SchemaValidator;
+use chrono::{Datelike, Duration, Utc};
+use noodle_core::error::{NoodleError, Result};
+use noodle_core::types::{
+    CustomPrompt, PackPrompt, PromptKind, PromptPack, PromptPackImport, PromptPreview,
+    PromptVariable, SearchFilter, PROMPT_PACK_VERSION,
+};
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::str::FromStr;
+use std::sync::Arc;
+use storage::sqlite::SqliteStorage;
+use tokio::sync::RwLock;
+
+/// Placeholders filled in without being declared: the mail a prompt runs
+/// on and dates in the user's timezone, as `YYYY-MM-DD`.
+pub const BUILTIN_VARIABLES: &[&str] = &["email", "emails", "today", "week_start", "month_start"];
+const MAX_PACK_PROMPTS: usize = 100;
+const MAX_TEMPLATE_CHARS: usize = 20_000;
+/// Body characters of the sample email in a preview.
+const SAMPLE_BODY_CHARS: usize = 4000;
+/// Recent emails listed for a periodic prompt's preview.
+const SAMPLE_EMAILS: i64 = 20;
+const OUTPUT_SCHEMA_NAME: &str = "prompt_output";
+
+/// Shares prompts as [`PromptPack`] files: exports them, imports packs
+/// after checking them, and fills a prompt's `{{name}}` placeholders to
+/// preview it against sample mail.
+pub struct PromptLibrary {
+    sqlite: Arc<SqliteStorage>,
+    ai: Arc<RwLock<Arc<dyn AiProvider>>>,
+}
+
+impl PromptLibrary {
+    pub fn new(sqlite: Arc<SqliteStorage>, ai: Arc<RwLock<Arc<dyn AiProvider>>>) -> Self {
+        Self { sqlite, ai }
+    }
+
+    /// The prompts `ids`, or all of them when empty, as a pack named `name`.
+    pub async fn export_pack(
+        &self,
+        ids: &[String],
+        name: &str,
+        description: Option<String>,
+    ) -> Result<PromptPack> {
+        let name = name.trim();
+        if name.is_empty() {
+            return Err(NoodleError::Validation("Name the prompt pack".into()));
+        }
+        let prompts: Vec<PackPrompt> = self
+            .sqlite
+            .list_prompts()
+            .await?
+            .into_iter()
+            .filter(|p| ids.is_empty() || ids.contains(&p.id))
+            .map(|p| PackPrompt {
+                // Prompts written here don't declare their placeholders,
+                // which importing requires.
+                variables: declared_variables(&p),
+                name: p.name,
+                kind: p.kind,
+                description: p.description,
+                template: p.content,
+                output_schema: p.output_schema,
+                schedule_cron: p.schedule_cron,
+            })
+            .collect();
+        if prompts.is_empty() {
+            return Err(NoodleError::Validation("No prompts to export".into()));
+        }
+        Ok(PromptPack {
+            version: PROMPT_PACK_VERSION,
+            name: name.to_string(),
+            description: description.filter(|d| !d.trim().is_empty()),
+            author: None,
+            exported_at: Some(Utc::now()),
+            prompts,
+        })
+    }
+
+    /// Checks `pack` and stores its prompts; nothing is stored if any of
+    /// them is rejected. Prompts imported from a pack of the same name
+    /// before are updated.
+    pub async fn import_pack(&self, pack: Value) -> Result<PromptPackImport> {
+        let pack = parse_pack(pack)?;
+        let mut result = PromptPackImport {
+            pack: pack.name.clone(),
+            created: 0,
+            updated: 0,
+        };
+        for (_, created) in self
+            .sqlite
+            .import_prompts(&pack.name, &pack.prompts)
+            .await?
+        {
+            if created {
+                result.created += 1;
+            } else {
+                result.updated += 1;
+            }
+        }
+        Ok(result)
+    }
+
+    /// The value of each of the prompt's variables: from `values`, or its
+    /// default with built-ins filled in. Placeholders of prompts written
+    /// here are variables without a default.
+    pub async fn resolve_variables(
+        &self,
+        id: &str,
+        values: &HashMap<String, String>,
+    ) -> Result<BTreeMap<String, String>> {
+        let prompt = self.prompt(id).await?;
+        let builtins = self.builtin_values().await?;
+        resolve(&prompt, values, &builtins)
+    }
+
+    /// The prompt with its variables at their defaults and `{{email}}` left
+    /// for the extraction to fill in, to run in place of the built-in
+    /// extraction prompt.
+    pub async fn extraction_template(&self, id: &str) -> Result<String> {
+        let prompt = self.prompt(id).await?;
+        if !placeholders(&prompt.content).contains("email") {
+            return Err(NoodleError::Validation(format!(
+                "{} doesn't use {{{{email}}}}",
+                prompt.name
+            )));
+        }
+        let mut values = self.builtin_values().await?;
+        let mut resolved = resolve(&prompt, &HashMap::new(), &values)?;
+        values.insert("email".into(), "{{email}}".into());
+        resolved.extend(values);
+        Ok(render(&prompt.content, &resolved))
+    }
+
+    /// Fills the prompt in and runs it. `{{email}}` is `email_id`'s email,
+    /// or the newest one; `{{emails}}` lists recent mail. `sample` text
+    /// stands in for either.
+    pub async fn preview(
+        &self,
+        id: &str,
+        values: &HashMap<String, String>,
+        email_id: Option<i64>,
+        sample: Option<String>,
+    ) -> Result<PromptPreview> {
+        let prompt = self.prompt(id).await?;
+        let mut builtins = self.builtin_values().await?;
+        let mut resolved = resolve(&prompt, values, &builtins)?;
+        let used = placeholders(&prompt.content);
+        let sample = sample.filter(|s| !s.trim().is_empty());
+        if used.contains("email") {
+            let text = match &sample {
+                Some(sample) => sample.clone(),
+                None => self.sample_email(email_id).await?,
+            };
+            builtins.insert("email".into(), injection::data_block(&text));
+        }
+        if used.contains("emails") {
+            let text = match &sample {
+                Some(sample) => sample.clone(),
+                None => self.sample_emails().await?,
+            };
+            builtins.insert("emails".into(), injection::data_block(&text));
+        }
+        resolved.extend(builtins);
+        let mut rendered = render(&prompt.content, &resolved);
+        if used.contains("email") || used.contains("emails") {
+            rendered.push_str("\n\n");
+            rendered.push_str(injection::DATA_INSTRUCTION);
+        }
+
+        let response_format =
+            prompt
+                .output_schema
+                .as_ref()
+                .map(|schema| ResponseFormat::JsonSchema {
+                    json_schema: JsonSchemaFormat {
+                        name: OUTPUT_SCHEMA_NAME.into(),
+                        schema: schema.clone(),
+                        strict: false,
+                    },
+                });
+        let ai = self.ai.read().await.clone();
+        let output = ai
+            .chat_completion(ChatRequest {
+                messages: vec![Message {
+                    role: "user".into(),
+                    content: rendered.clone(),
+                }],
+                temperature: 0.0,
+                response_format,
+                model: None,
+            })
+            .await?
+            .content;
+
+        let schema_errors = match &prompt.output_schema {
+            Some(schema) => {
+                // Providers without structured output may wrap the object in
+                // prose or a code fence.
+                let object = output
+                    .find('{')
+                    .zip(output.rfind('}'))
+                    .filter(|(start, end)| start < end)
+                    .map(|(start, end)| &output[start..=end])
+                    .unwrap_or(&output);
+                match serde_json::from_str::<Value>(object) {
+                    Ok(json) => SchemaValidator::new(schema)?.errors(&json),
+                    Err(e) => vec![format!("The output is not JSON: {}", e)],
+                }
+            }
+            None => Vec::new(),
+        };
+        Ok(PromptPreview {
+            rendered,
+            output,
+            schema_errors,
+        })
+    }
+
+    async fn prompt(&self, id: &str) -> Result<CustomPrompt> {
+        self.sqlite
+            .get_prompt(id)
+            .await?
+            .ok_or_else(|| NoodleError::Validation("Prompt not found".into()))
+    }
+
+    async fn builtin_values(&self) -> Result<BTreeMap<String, String>> {
+        let tz = self.sqlite.get_user_timezone().await?;
+        let today = tz.local_date(Utc::now());
+        let week_start = today - Duration::days(today.weekday().num_days_from_monday() as i64);
+        let month_start = today.with_day(1).unwrap_or(today);
+        Ok(BTreeMap::from([
+            ("today".into(), today.to_string()),
+            ("week_start".into(), week_start.to_string()),
+            ("month_start".into(), month_start.to_string()),
+        ]))
+    }
+
+    async fn sample_email(&self, email_id: Option<i64>) -> Result<String> {
+        let id = match email_id {
+            Some(id) => id,
+            None => self
+                .sqlite
+                .list_emails(&SearchFilter::default(), 1)
+                .await?
+                .first()
+                .and_then(|e| e["id"].as_i64())
+                .ok_or_else(|| {
+                    NoodleError::Validation("There is no email to preview with yet".into())
+                })?,
+        };
+        let email = self
+            .sqlite
+            .get_email(id)
+            .await?
+            .ok_or_else(|| NoodleError::Validation(format!("Email {} not found", id)))?;
+        let body: String = self
+            .sqlite
+            .get_email_body(id)
+            .await?
+            .unwrap_or_default()
+            .chars()
+            .take(SAMPLE_BODY_CHARS)
+            .collect();
+        Ok(format!(
+            "Subject: {}\nFrom: {}\nDate: {}\n\n{}",
+            email.subject,
+            email.sender,
+            email.received_at.to_rfc3339(),
+            body
+        ))
+    }
+
+    async fn sample_emails(&self) -> Result<String> {
+        let emails = self
+            .sqlite
+            .list_emails(&SearchFilter::default(), SAMPLE_EMAILS)
+            .await?;
+        Ok(emails
+            .iter()
+            .map(|e| {
+                format!(
+                    "[#{}] {} · {} · {}: {}",
+                    e["id"],
+                    e["received_at"].as_str().unwrap_or_default(),
+                    e["sender"].as_str().unwrap_or_default(),
+                    e["subject"].as_str().unwrap_or_default(),
+                    e["summary"].as_str().unwrap_or_default()
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n"))
+    }
+}
+
+/// Reads a pack and checks that each prompt can be used: a known format
+/// version, unique names, declared variables, a valid output schema and
+/// schedule.
+pub fn parse_pack(pack: Value) -> Result<PromptPack> {
+    let pack: PromptPack = serde_json::from_value(pack)
+        .map_err(|e| NoodleError::Validation(format!("Not a prompt pack: {}", e)))?;
+    if pack.version > PROMPT_PACK_VERSION {
+        return Err(NoodleError::Validation(format!(
+            "Prompt pack is from a newer version of Noodle (format {})",
+            pack.version
+        )));
+    }
+    if pack.name.trim().is_empty() {
+        return Err(NoodleError::Validation(
+            "The prompt pack has no name".into(),
+        ));
+    }
+    if pack.prompts.is_empty() || pack.prompts.len() > MAX_PACK_PROMPTS {
+        return Err(NoodleError::Validation(format!(
+            "A prompt pack holds 1 to {} prompts",
+            MAX_PACK_PROMPTS
+        )));
+    }
+    let mut names = HashSet::new();
+    for prompt in &pack.prompts {
+        check_prompt(prompt).map_err(|e| match e {
+            NoodleError::Validation(message) => {
+                NoodleError::Validation(format!("Prompt '{}': {}", prompt.name, message))
+            }
+            e => e,
+        })?;
+        if !names.insert(prompt.name.trim().to_lowercase()) {
+            return Err(NoodleError::Validation(format!(
+                "Prompt '{}' appears twice in the pack",
+                prompt.name
+            )));
+        }
+    }
+    Ok(pack)
+}
+
+fn check_prompt(prompt: &PackPrompt) -> Result<()> {
+    if prompt.name.trim().is_empty() {
+        return Err(NoodleError::Validation("it has no name".into()));
+    }
+    if prompt.template.trim().is_empty() {
+        return Err(NoodleError::Validation("the template is empty".into()));
+    }
+    if prompt.template.chars().count() > MAX_TEMPLATE_CHARS {
+        return Err(NoodleError::Validation(format!(
+            "templates are limited to {} characters",
+            MAX_TEMPLATE_CHARS
+        )));
+    }
+    if prompt.kind == PromptKind::Extraction && placeholders(&prompt.template).contains("emails") {
+        return Err(NoodleError::Validation(
+            "extraction prompts run on one email; use {{email}}".into(),
+        ));
+    }
+
+    let mut declared = HashSet::new();
+    for variable in &prompt.variables {
+        if !is_variable_name(&variable.name) {
+            return Err(NoodleError::Validation(format!(
+                "'{}' is not a variable name; use lowercase letters, digits and _",
+                variable.name
+            )));
+        }
+        if BUILTIN_VARIABLES.contains(&variable.name.as_str()) {
+            return Err(NoodleError::Validation(format!(
+                "{{{{{}}}}} is built in and can't be declared",
+                variable.name
+            )));
+        }
+        if !declared.insert(variable.name.as_str()) {
+            return Err(NoodleError::Validation(format!(
+                "variable {} is declared twice",
+                variable.name
+            )));
+        }
+        if let Some(default) = &variable.default {
+            if let Some(name) = placeholders(default)
+                .into_iter()
+                .find(|p| !BUILTIN_VARIABLES.contains(&p.as_str()))
+            {
+                return Err(NoodleError::Validation(format!(
+                    "the default of {} uses {{{{{}}}}}; defaults can only use built-ins",
+                    variable.name, name
+                )));
+            }
+        }
+    }
+    if let Some(name) = placeholders(&prompt.template)
+        .into_iter()
+        .find(|p| !declared.contains(p.as_str()) && !BUILTIN_VARIABLES.contains(&p.as_str()))
+    {
+        return Err(NoodleError::Validation(format!(
+            "the template uses {{{{{}}}}}, which isn't declared",
+            name
+        )));
+    }
+
+    if let Some(schema) = &prompt.output_schema {
+        if !schema.is_object() {
+            return Err(NoodleError::Validation(
+                "the output schema must be a JSON object".into(),
+            ));
+        }
+        SchemaValidator::new(schema)?;
+    }
+    if let Some(cron) = &prompt.schedule_cron {
+        if prompt.kind != PromptKind::Periodic {
+            return Err(NoodleError::Validation(
+                "only periodic prompts have a schedule".into(),
+            ));
+        }
+        cron::Schedule::from_str(cron).map_err(|e| {
+            NoodleError::Validation(format!("the schedule '{}' is not valid: {}", cron, e))
+        })?;
+    }
+    Ok(())
+}
+
+/// Each variable's value: given, else its default, else an error. Built-in
+/// placeholders are left to the caller.
+fn resolve(
+    prompt: &CustomPrompt,
+    values: &HashMap<String, String>,
+    builtins: &BTreeMap<String, String>,
+) -> Result<BTreeMap<String, String>> {
+    let mut resolved = BTreeMap::new();
+    for variable in declared_variables(prompt) {
+        let value = match values.get(&variable.name).filter(|v| !v.trim().is_empty()) {
+            Some(value) => value.clone(),
+            None => match &variable.default {
+                Some(default) => render(default, builtins),
+                None => {
+                    return Err(NoodleError::Validation(format!(
+                        "{} needs a value",
+                        variable.name
+                    )))
+                }
+            },
+        };
+        resolved.insert(variable.name, value);
+    }
+    Ok(resolved)
+}
+
+/// The prompt's variables, with placeholders it doesn't declare added
+/// without a default.
+fn declared_variables(prompt: &CustomPrompt) -> Vec<PromptVariable> {
+    let mut variables = prompt.variables.clone();
+    let mut undeclared: Vec<String> = placeholders(&prompt.content)
+        .into_iter()
+        .filter(|name| {
+            !BUILTIN_VARIABLES.contains(&name.as_str())
+                && !variables.iter().any(|v| &v.name == name)
+        })
+        .collect();
+    undeclared.sort();
+    variables.extend(undeclared.into_iter().map(|name| PromptVariable {
+        name,
+        description: None,
+        default: None,
+    }));
+    variables
+}
+
+/// The names of the `{{name}}` placeholders in `template`.
+fn placeholders(template: &str) -> HashSet<String> {
+    let mut names = HashSet::new();
+    let mut rest = template;
+    while let Some(start) = rest.find("{{") {
+        rest = &rest[start + 2..];
+        let Some(end) = rest.find("}}") else {
+            break;
+        };
+        let name = rest[..end].trim();
+        if is_variable_name(name) {
+            names.insert(name.to_string());
+        }
+        rest = &rest[end + 2..];
+    }
+    names
+}
+
+/// `template` with each known placeholder replaced; unknown ones are kept.
+fn render(template: &str, values: &BTreeMap<String, String>) -> String {
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find("{{") {
+        out.push_str(&rest[..start]);
+        let after = &rest[start + 2..];
+        match after.find("}}") {
+            Some(end) => match values.get(after[..end].trim()) {
+                Some(value) => {
+                    out.push_str(value);
+                    rest = &after[end + 2..];
+                }
+                None => {
+                    out.push_str("{{");
+                    rest = after;
+                }
+            },
+            None => {
+                out.push_str(&rest[start..]);
+                rest = "";
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+fn is_variable_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_')
+}
@@ -0,0 +1,306 @@
+use crate::archive;
+use chrono::Utc;
+use noodle_core::error::Result;
+use noodle_core::text::SanitizedText;
+use noodle_core::types::{Annotation, ExportProfile, Severity};
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::fmt::Write as _;
+use std::sync::Arc;
+use storage::sqlite::{ProjectEmailFacts, SqliteStorage};
+
+/// Replaces redacted terms and, for clients, email addresses.
+const REDACTED: &str = "[redacted]";
+
+/// A project health report, ready to be saved under `file_name`.
+#[derive(Debug, Clone, Serialize)]
+pub struct ProjectReport {
+    pub file_name: String,
+    pub mime_type: &'static str,
+    pub content: String,
+    /// `content` is a base64 encoded encrypted archive holding the report.
+    pub encrypted: bool,
+    pub emails: usize,
+}
+
+impl ProjectReport {
+    /// Wraps the report in an archive encrypted with `password`.
+    pub async fn encrypt(self, password: &str) -> Result<Self> {
+        let (file_name, content) =
+            archive::seal_file(&self.file_name, self.content, password).await?;
+        Ok(Self {
+            file_name,
+            mime_type: "application/octet-stream",
+            content,
+            encrypted: true,
+            ..self
+        })
+    }
+}
+
+/// Renders a project's health as Markdown for sharing outside the app:
+/// decisions, open risks and blockers, and a summary per email with the
+/// user's tags and notes. What is included depends on the
+/// [`ExportProfile`]; `redact` terms (names, companies, amounts) are masked
+/// everywhere regardless of profile.
+pub struct ProjectReporter {
+    sqlite: Arc<SqliteStorage>,
+}
+
+impl ProjectReporter {
+    pub fn new(sqlite: Arc<SqliteStorage>) -> Self {
+        Self { sqlite }
+    }
+
+    /// Without `with_notes`, annotations only contribute their tags, as
+    /// while the app is locked.
+    pub async fn build(
+        &self,
+        project: &str,
+        profile: ExportProfile,
+        redact: &[String],
+        with_notes: bool,
+    ) -> Result<ProjectReport> {
+        let emails = self.sqlite.get_project_email_facts(project, None).await?;
+        let tz = self.sqlite.get_user_timezone().await?;
+        let locale = self.sqlite.get_user_locale().await?;
+        let date = |email: &ProjectEmailFacts| locale.format_date(&tz.to_local(email.received_at));
+        let with_people = profile != ExportProfile::Client;
+
+        let mut out = String::new();
+        let _ = writeln!(out, "# {}: project health\n", project);
+        let _ = writeln!(
+            out,
+            "Exported {} · {} emails · {} report\n",
+            locale.format_date(&tz.to_local(Utc::now())),
+            emails.len(),
+            profile
+        );
+
+        if let Some(annotation) = self.sqlite.get_project_annotation(project).await? {
+            write_annotation(&mut out, &annotation, with_notes);
+            out.push('\n');
+        }
+        let annotations: HashMap<i64, Annotation> = self
+            .sqlite
+            .list_annotations()
+            .await?
+            .into_iter()
+            .filter_map(|a| a.email_id.map(|id| (id, a)))
+            .collect();
+
+        out.push_str("## Decisions\n\n");
+        let decisions: Vec<&ProjectEmailFacts> = emails
+            .iter()
+            .filter(|e| e.primary_type == "decision")
+            .collect();
+        if decisions.is_empty() {
+            out.push_str("None recorded.\n");
+        }
+        for email in decisions.iter().rev() {
+            let _ = writeln!(out, "- {}: {}", date(email), email.summary);
+        }
+
+        // Newest first, each risk and blocker listed once.
+        let risks = emails.iter().rev().flat_map(|e| {
+            e.risks
+                .iter()
+                .map(move |r| Signal::new(date(e), &r.title, &r.severity, &r.details, &r.owner))
+        });
+        write_signals(&mut out, "Risks", risks, with_people);
+        let blockers = emails.iter().rev().flat_map(|e| {
+            e.blockers
+                .iter()
+                .map(move |b| Signal::new(date(e), &b.title, &b.severity, &b.details, &b.owner))
+        });
+        write_signals(&mut out, "Blockers", blockers, with_people);
+
+        out.push_str("\n## Correspondence\n");
+        for email in emails.iter().rev() {
+            let _ = writeln!(out, "\n### {}: {}\n", date(email), email.subject);
+            if with_people {
+                let _ = writeln!(out, "From: {}\n", email.sender);
+            }
+            let _ = writeln!(out, "{}", email.summary);
+            if let Some(annotation) = annotations.get(&email.id) {
+                out.push('\n');
+                write_annotation(&mut out, annotation, with_notes);
+            }
+            if profile == ExportProfile::Internal {
+                if let Some(body) = self.sqlite.get_email_body(email.id).await? {
+                    let _ = writeln!(out, "\n```text\n{}\n```", body.trim().replace("```", "'''"));
+                }
+            }
+        }
+
+        let mut content = redact_terms(&out, redact);
+        if profile == ExportProfile::Client {
+            content = mask_email_addresses(&content);
+        }
+        Ok(ProjectReport {
+            file_name: format!(
+                "{}-{}.md",
+                file_stem(&redact_terms(project, redact)),
+                profile
+            ),
+            mime_type: "text/markdown",
+            content,
+            encrypted: false,
+            emails: emails.len(),
+        })
+    }
+}
+
+/// A risk or blocker as listed in the report.
+struct Signal<'a> {
+    date: String,
+    title: &'a str,
+    severity: &'a Severity,
+    details: &'a str,
+    owner: Option<&'a str>,
+}
+
+impl<'a> Signal<'a> {
+    fn new(
+        date: String,
+        title: &'a str,
+        severity: &'a Severity,
+        details: &'a str,
+        owner: &'a Option<SanitizedText>,
+    ) -> Self {
+        Self {
+            date,
+            title,
+            severity,
+            details,
+            owner: owner.as_ref().map(|o| o.as_str()),
+        }
+    }
+}
+
+/// Lists `signals` under `heading`, skipping titles already listed.
+fn write_signals<'a>(
+    out: &mut String,
+    heading: &str,
+    signals: impl Iterator<Item = Signal<'a>>,
+    with_owners: bool,
+) {
+    let _ = writeln!(out, "\n## {}\n", heading);
+    let mut seen = HashSet::new();
+    for signal in signals {
+        if !seen.insert(signal.title.to_lowercase()) {
+            continue;
+        }
+        let _ = write!(
+            out,
+            "- **{}** ({}, {}): {}",
+            signal.title, signal.severity, signal.date, signal.details
+        );
+        match signal.owner {
+            Some(owner) if with_owners => {
+                let _ = writeln!(out, " Owner: {}", owner);
+            }
+            _ => out.push('\n'),
+        }
+    }
+    if seen.is_empty() {
+        out.push_str("None recorded.\n");
+    }
+}
+
+/// The annotation's tags and, `with_note`, its note.
+fn write_annotation(out: &mut String, annotation: &Annotation, with_note: bool) {
+    if !annotation.tags.is_empty() {
+        let tags: Vec<String> = annotation.tags.iter().map(|t| format!("#{}", t)).collect();
+        let _ = writeln!(out, "Tags: {}", tags.join(" "));
+    }
+    if let Some(note) = annotation.note.as_ref().filter(|_| with_note) {
+        let _ = writeln!(out, "Note: {}", note);
+    }
+}
+
+/// Masks every occurrence of `terms` as a whole word, ignoring ASCII case,
+/// so "Al" leaves "Alpha" alone.
+pub fn redact_terms(text: &str, terms: &[String]) -> String {
+    let mut terms: Vec<&str> = terms
+        .iter()
+        .map(|t| t.trim())
+        .filter(|t| !t.is_empty())
+        .collect();
+    // Longer terms first, so "Acme Corp" wins over "Acme".
+    terms.sort_by_key(|t| std::cmp::Reverse(t.len()));
+
+    let word_char = |c: Option<char>| c.is_some_and(char::is_alphanumeric);
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    let mut previous = None;
+    'outer: while let Some(c) = rest.chars().next() {
+        if !word_char(previous) {
+            for term in &terms {
+                let whole_word = rest
+                    .get(..term.len())
+                    .is_some_and(|s| s.eq_ignore_ascii_case(term))
+                    && !word_char(rest[term.len()..].chars().next());
+                if whole_word {
+                    out.push_str(REDACTED);
+                    rest = &rest[term.len()..];
+                    previous = term.chars().last();
+                    continue 'outer;
+                }
+            }
+        }
+        out.push(c);
+        rest = &rest[c.len_utf8()..];
+        previous = Some(c);
+    }
+    out
+}
+
+/// Masks anything shaped like an email address.
+pub fn mask_email_addresses(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut word = String::new();
+    for c in text.chars() {
+        if c.is_alphanumeric() || "@._%+-".contains(c) {
+            word.push(c);
+        } else {
+            out.push_str(&mask_word(&word));
+            word.clear();
+            out.push(c);
+        }
+    }
+    out.push_str(&mask_word(&word));
+    out
+}
+
+fn mask_word(word: &str) -> String {
+    let address = word.trim_end_matches('.');
+    let is_address = address.split_once('@').is_some_and(|(local, domain)| {
+        !local.is_empty() && domain.contains('.') && !domain.starts_with('.')
+    });
+    if is_address {
+        format!("{}{}", REDACTED, &word[address.len()..])
+    } else {
+        word.to_string()
+    }
+}
+
+/// A file name safe on Windows for `project`.
+pub fn file_stem(project: &str) -> String {
+    let stem: String = project
+        .chars()
+        .map(|c| {
+            if c.is_alphanumeric() || c == '-' {
+                c
+            } else {
+                '-'
+            }
+        })
+        .collect();
+    let stem = stem.trim_matches('-');
+    if stem.is_empty() {
+        "project".into()
+    } else {
+        stem.to_lowercase()
+    }
+}
@@ -0,0 +1,504 @@
+use super::summarize::citations;
+use super::tools::{source_from_result, ChatTools, TOOL_DESCRIPTIONS};
+use super::verify::CitationVerifier;
+use super::SearchService;
+use ai::injection;
+use ai::provider::{AiProvider, ChatRequest, Message, ResponseFormat};
+use noodle_core::error::{NoodleError, Result};
+use noodle_core::events::{AppEvent, ChatToolProgress, Notifier};
+use noodle_core::locale::UserLocale;
+use noodle_core::types::{
+    ChatMessage, ChatToolCall, SearchFilter, TopicSource, UnsupportedCitation,
+};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use std::time::Instant;
+use storage::sqlite::SqliteStorage;
+use tokio::sync::RwLock;
+use tracing::warn;
+
+/// Emails retrieved for each answer.
+const MAX_SOURCES: u64 = 8;
+/// Body characters sent to the model per email.
+const MAX_BODY_CHARS: usize = 2000;
+/// Earlier messages sent along with a question, the newest ones.
+const HISTORY_MESSAGES: usize = 10;
+const MAX_QUESTION_CHARS: usize = 2000;
+/// Tools a research answer may call before it has to answer.
+const MAX_TOOL_STEPS: usize = 6;
+
+/// A question and its answer, as stored in the session.
+#[derive(Debug, Clone, Serialize)]
+pub struct ChatTurn {
+    pub session_id: i64,
+    pub question: ChatMessage,
+    pub answer: ChatMessage,
+}
+
+/// Answers questions about the user's mail in sessions, so follow-ups like
+/// "and what did they decide?" are read with what was said before. Each
+/// answer is grounded in retrieved emails, cited as `[#id]`; with
+/// `chat_rerun_retrieval` off, follow-ups reuse the previous answer's emails.
+///
+/// Research answers, for questions one search doesn't cover, let the model
+/// call [`ChatTools`] in turn instead: it replies with one JSON step at a
+/// time, a tool call or the answer, for up to [`MAX_TOOL_STEPS`] calls. The
+/// calls are kept with the answer and announced as they run.
+///
+/// With `chat_verify_citations` on, every answer's citations are checked
+/// by [`CitationVerifier`] and the unsupported ones kept with it.
+pub struct ChatAssistant {
+    sqlite: Arc<SqliteStorage>,
+    search: Arc<SearchService>,
+    ai: Arc<RwLock<Arc<dyn AiProvider>>>,
+}
+
+impl ChatAssistant {
+    pub fn new(
+        sqlite: Arc<SqliteStorage>,
+        search: Arc<SearchService>,
+        ai: Arc<RwLock<Arc<dyn AiProvider>>>,
+    ) -> Self {
+        Self { sqlite, search, ai }
+    }
+
+    /// Asks `question` in `session_id`, or in a new session titled after
+    /// it, researching it with tools when `research` is set. The question is
+    /// stored first, so it stays in the session even when answering fails.
+    pub async fn ask(
+        &self,
+        session_id: Option<i64>,
+        question: &str,
+        research: bool,
+        notifier: &dyn Notifier,
+    ) -> Result<ChatTurn> {
+        let question = question.trim();
+        if question.is_empty() {
+            return Err(NoodleError::Validation("Enter a question".into()));
+        }
+        if question.chars().count() > MAX_QUESTION_CHARS {
+            return Err(NoodleError::Validation(format!(
+                "Questions are limited to {} characters",
+                MAX_QUESTION_CHARS
+            )));
+        }
+        let (session_id, history) = match session_id {
+            Some(id) => (id, self.sqlite.get_chat_messages(id).await?),
+            None => (self.sqlite.create_chat_session(question).await?, Vec::new()),
+        };
+        let asked = self
+            .sqlite
+            .add_chat_message(session_id, "user", question, &[], &[], &[])
+            .await?;
+
+        let answer = if research {
+            self.research(session_id, &history, question, notifier)
+                .await?
+        } else {
+            self.answer(session_id, &history, question).await?
+        };
+        Ok(ChatTurn {
+            session_id,
+            question: asked,
+            answer,
+        })
+    }
+
+    async fn answer(
+        &self,
+        session_id: i64,
+        history: &[ChatMessage],
+        question: &str,
+    ) -> Result<ChatMessage> {
+        let previous = history
+            .iter()
+            .rev()
+            .find(|m| m.role == "assistant" && !m.sources.is_empty());
+        let mut sources = match previous {
+            Some(answer) if !self.sqlite.get_all_config().await?.chat_rerun_retrieval => {
+                answer.sources.clone()
+            }
+            _ => self.retrieve(history, question).await?,
+        };
+
+        let locale = self.sqlite.get_user_locale().await?;
+        let mut messages = history_messages(history);
+        messages.push(Message {
+            role: "user".into(),
+            content: self.answer_prompt(question, &sources, locale).await?,
+        });
+        let ai = self.ai.read().await.clone();
+        let answer = ai
+            .chat_completion(ChatRequest {
+                messages,
+                temperature: 0.2,
+                response_format: None,
+                model: None,
+            })
+            .await?
+            .content
+            .trim()
+            .to_string();
+
+        mark_cited(&answer, &mut sources);
+        let unsupported = self.check_citations(&answer, &sources).await?;
+        self.sqlite
+            .add_chat_message(
+                session_id,
+                "assistant",
+                &answer,
+                &sources,
+                &[],
+                &unsupported,
+            )
+            .await
+    }
+
+    /// Lets the model call tools until it answers. A plain prose reply, as
+    /// small models sometimes give, is taken as the answer; after
+    /// [`MAX_TOOL_STEPS`] calls the model is told to answer from what it
+    /// has. Any other reply that isn't an answer, such as a tool call once
+    /// the steps are used up, is sent back once before giving up, so raw JSON
+    /// is never saved as the answer. Tool errors go back to the model so it
+    /// can correct its call.
+    async fn research(
+        &self,
+        session_id: i64,
+        history: &[ChatMessage],
+        question: &str,
+        notifier: &dyn Notifier,
+    ) -> Result<ChatMessage> {
+        let locale = self.sqlite.get_user_locale().await?;
+        let tools = ChatTools::new(self.sqlite.clone(), self.search.clone());
+        let mut messages = history_messages(history);
+        messages.push(Message {
+            role: "user".into(),
+            content: research_prompt(question, locale),
+        });
+        let mut trace: Vec<ChatToolCall> = Vec::new();
+        let mut sources: Vec<TopicSource> = Vec::new();
+
+        let mut retried = false;
+        let answer = loop {
+            let out_of_steps = trace.len() >= MAX_TOOL_STEPS;
+            if out_of_steps && !retried {
+                messages.push(Message {
+                    role: "user".into(),
+                    content: "You have used all your tool calls. Reply now with \
+                              {\"answer\": \"...\"} from what the tools returned."
+                        .into(),
+                });
+            }
+            let ai = self.ai.read().await.clone();
+            let reply = ai
+                .chat_completion(ChatRequest {
+                    messages: messages.clone(),
+                    temperature: 0.0,
+                    response_format: Some(ResponseFormat::Json),
+                    model: None,
+                })
+                .await?
+                .content;
+            let (tool, arguments) = match next_move(&reply, out_of_steps, retried) {
+                ResearchMove::Answer(answer) => break answer,
+                ResearchMove::Call { tool, arguments } => (tool, arguments),
+                ResearchMove::Retry => {
+                    warn!("Chat reply was neither an answer nor a tool call; asking again");
+                    retried = true;
+                    messages.push(Message {
+                        role: "assistant".into(),
+                        content: reply,
+                    });
+                    messages.push(Message {
+                        role: "user".into(),
+                        content: "That was not an answer. Reply now with only \
+                                  {\"answer\": \"...\"} from what the tools returned."
+                            .into(),
+                    });
+                    continue;
+                }
+                ResearchMove::GiveUp => {
+                    return Err(NoodleError::AI("The model did not give an answer".into()))
+                }
+            };
+
+            let mut call = ChatToolCall {
+                step: trace.len() + 1,
+                tool,
+                arguments,
+                result: None,
+                error: None,
+                duration_ms: 0,
+            };
+            notifier.notify(AppEvent::ChatToolCall(ChatToolProgress {
+                session_id,
+                call: call.clone(),
+            }));
+            let started = Instant::now();
+            let result = tools.call(&call.tool, &call.arguments, locale).await;
+            call.duration_ms = started.elapsed().as_millis() as u64;
+            let content = match result {
+                Ok(output) => {
+                    for source in output.sources {
+                        if !sources.iter().any(|s| s.email_id == source.email_id) {
+                            sources.push(source);
+                        }
+                    }
+                    call.result = Some(output.summary);
+                    format!(
+                        "Result of {}:\n{}",
+                        call.tool,
+                        injection::data_block(&output.text)
+                    )
+                }
+                Err(e) => {
+                    warn!("Chat tool {} failed: {}", call.tool, e);
+                    call.error = Some(e.to_string());
+                    format!("{} failed: {}", call.tool, e)
+                }
+            };
+            notifier.notify(AppEvent::ChatToolCall(ChatToolProgress {
+                session_id,
+                call: call.clone(),
+            }));
+            messages.push(Message {
+                role: "assistant".into(),
+                content: reply,
+            });
+            messages.push(Message {
+                role: "user".into(),
+                content,
+            });
+            trace.push(call);
+        };
+
+        let answer = answer.trim();
+        if answer.is_empty() {
+            return Err(NoodleError::AI("The model gave an empty answer".into()));
+        }
+        mark_cited(answer, &mut sources);
+        let unsupported = self.check_citations(answer, &sources).await?;
+        self.sqlite
+            .add_chat_message(
+                session_id,
+                "assistant",
+                answer,
+                &sources,
+                &trace,
+                &unsupported,
+            )
+            .await
+    }
+
+    /// The citations of `answer` that don't hold up, when checking is on.
+    /// A failed check is logged rather than holding the answer back.
+    async fn check_citations(
+        &self,
+        answer: &str,
+        sources: &[TopicSource],
+    ) -> Result<Vec<UnsupportedCitation>> {
+        if !self.sqlite.get_all_config().await?.chat_verify_citations {
+            return Ok(Vec::new());
+        }
+        match CitationVerifier::new(self.sqlite.clone(), self.ai.clone())
+            .verify(answer, sources)
+            .await
+        {
+            Ok(unsupported) => Ok(unsupported),
+            Err(e) => {
+                warn!("Checking the answer's citations failed: {}", e);
+                Ok(Vec::new())
+            }
+        }
+    }
+
+    /// The emails most relevant to `question`. A follow-up is first
+    /// rewritten into a question that stands on its own, as retrieval
+    /// doesn't see the conversation.
+    async fn retrieve(&self, history: &[ChatMessage], question: &str) -> Result<Vec<TopicSource>> {
+        let query = if history.is_empty() {
+            question.to_string()
+        } else {
+            match self.standalone_question(history, question).await {
+                Ok(query) if !query.is_empty() => query,
+                Ok(_) => question.to_string(),
+                Err(e) => {
+                    warn!("Rewriting a follow-up for retrieval failed: {}", e);
+                    question.to_string()
+                }
+            }
+        };
+        let emails = self
+            .search
+            .hybrid_search(&query, &SearchFilter::default(), MAX_SOURCES)
+            .await?;
+        Ok(emails.iter().filter_map(source_from_result).collect())
+    }
+
+    async fn standalone_question(&self, history: &[ChatMessage], question: &str) -> Result<String> {
+        let conversation = history
+            .iter()
+            .skip(history.len().saturating_sub(HISTORY_MESSAGES))
+            .map(|m| format!("{}: {}", m.role, m.content))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let prompt = format!(
+            "Conversation so far:\n{}\n\nFollow-up question: {}\n\n\
+             Rewrite the follow-up as one search query that makes sense without \
+             the conversation, naming the people, projects and topics it refers \
+             to. Reply with only the query.",
+            conversation, question
+        );
+        let ai = self.ai.read().await.clone();
+        super::summarize::complete(ai.as_ref(), prompt).await
+    }
+
+    async fn answer_prompt(
+        &self,
+        question: &str,
+        sources: &[TopicSource],
+        locale: UserLocale,
+    ) -> Result<String> {
+        if sources.is_empty() {
+            return Ok(format!(
+                "{}\n\nNo emails matched this question. Say so briefly rather \
+                 than answering from general knowledge. {}",
+                question,
+                locale.prompt_instruction()
+            ));
+        }
+        let mut emails = Vec::new();
+        for source in sources {
+            let body: String = self
+                .sqlite
+                .get_email_body(source.email_id)
+                .await?
+                .unwrap_or_default()
+                .chars()
+                .take(MAX_BODY_CHARS)
+                .collect();
+            emails.push(format!(
+                "[#{}] Subject: {}\nFrom: {}\nDate: {}\nSummary: {}\n\n{}",
+                source.email_id,
+                source.subject,
+                source.sender,
+                locale.format_date(&source.received_at),
+                source.summary,
+                body
+            ));
+        }
+        Ok(format!(
+            "Emails that may answer the question, each tagged with its id:\n{}\n\n\
+             Question: {}\n\n\
+             Answer from these emails and the conversation so far. Cite the emails \
+             behind every statement with their tags, e.g. [#12] or [#12][#40], and \
+             do not cite ids that are not listed. If the emails don't answer the \
+             question, say so. {} {}",
+            injection::data_block(&emails.join("\n\n---\n\n")),
+            question,
+            injection::DATA_INSTRUCTION,
+            locale.prompt_instruction()
+        ))
+    }
+}
+
+/// One reply of a research answer: a tool call or the answer.
+#[derive(Debug, Deserialize)]
+pub struct ResearchStep {
+    pub tool: Option<String>,
+    #[serde(default)]
+    pub arguments: serde_json::Value,
+    pub answer: Option<String>,
+}
+
+/// Providers without JSON mode may wrap the step in prose or a code fence.
+pub fn parse_step(reply: &str) -> Option<ResearchStep> {
+    let start = reply.find('{')?;
+    let end = reply.rfind('}')?;
+    serde_json::from_str(reply.get(start..=end)?).ok()
+}
+
+/// What a research answer does with one reply of the model.
+#[derive(Debug, PartialEq)]
+pub enum ResearchMove {
+    Answer(String),
+    Call {
+        tool: String,
+        arguments: serde_json::Value,
+    },
+    /// Ask once more for the answer.
+    Retry,
+    GiveUp,
+}
+
+/// The move for `reply`. Prose that isn't a JSON step is the answer, even
+/// when it mentions a brace; a reply that opens like JSON but doesn't parse
+/// as a step is a broken step, not an answer.
+pub fn next_move(reply: &str, out_of_steps: bool, retried: bool) -> ResearchMove {
+    match parse_step(reply) {
+        Some(ResearchStep {
+            answer: Some(answer),
+            ..
+        }) => return ResearchMove::Answer(answer),
+        Some(ResearchStep {
+            tool: Some(tool),
+            arguments,
+            ..
+        }) if !out_of_steps => return ResearchMove::Call { tool, arguments },
+        None if !looks_like_step(reply) => return ResearchMove::Answer(reply.to_string()),
+        _ => {}
+    }
+    if retried {
+        ResearchMove::GiveUp
+    } else {
+        ResearchMove::Retry
+    }
+}
+
+/// Whether `reply` is an attempt at a step: it opens with a JSON object,
+/// bare or in a code fence, or names a step's keys.
+fn looks_like_step(reply: &str) -> bool {
+    let trimmed = reply.trim_start();
+    trimmed.starts_with('{')
+        || trimmed.starts_with("```")
+        || reply.contains("\"tool\"")
+        || reply.contains("\"answer\"")
+}
+
+fn research_prompt(question: &str, locale: UserLocale) -> String {
+    format!(
+        "Research this question about the user's email with the tools below, \
+         then answer it.\n\nQuestion: {}\n\nTools:\n{}\n\n\
+         Reply with exactly one JSON object and nothing else: \
+         {{\"tool\": \"<name>\", \"arguments\": {{...}}}} to call a tool, or \
+         {{\"answer\": \"...\"}} once you can answer. You may call up to {} tools, \
+         one at a time; their results come back to you. In the answer, cite the \
+         emails behind every statement with their tags, e.g. [#12] or [#12][#40], \
+         and only cite emails a tool returned. If the tools found nothing that \
+         answers the question, say so. {} {}",
+        question,
+        TOOL_DESCRIPTIONS,
+        MAX_TOOL_STEPS,
+        injection::DATA_INSTRUCTION,
+        locale.prompt_instruction()
+    )
+}
+
+/// The newest messages of `history`, as sent along with a question.
+fn history_messages(history: &[ChatMessage]) -> Vec<Message> {
+    history
+        .iter()
+        .skip(history.len().saturating_sub(HISTORY_MESSAGES))
+        .map(|m| Message {
+            role: m.role.clone(),
+            content: m.content.clone(),
+        })
+        .collect()
+}
+
+fn mark_cited(answer: &str, sources: &mut [TopicSource]) {
+    let cited = citations(answer);
+    for source in sources {
+        source.cited = cited.contains(&source.email_id);
+    }
+}
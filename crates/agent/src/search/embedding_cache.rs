@@ -0,0 +1,85 @@
+use ai::provider::AiProvider;
+use noodle_core::error::Result;
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex, Weak};
+
+#[derive(Default)]
+struct Inner {
+    /// Provider the entries were computed with. Held weakly so a replaced
+    /// provider can't be mistaken for its successor at the same address.
+    provider: Option<Weak<dyn AiProvider>>,
+    entries: HashMap<String, Vec<f32>>,
+    /// Least recently used first.
+    order: VecDeque<String>,
+}
+
+/// Query text → embedding, least recently used evicted first. Emptied when
+/// the AI provider is replaced, since another model embeds differently.
+pub struct EmbeddingCache {
+    capacity: usize,
+    inner: Mutex<Inner>,
+}
+
+impl EmbeddingCache {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            inner: Mutex::new(Inner::default()),
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Inner> {
+        self.inner.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// The embedding of `text` by `ai`, from the cache when possible.
+    pub async fn embed(&self, ai: &Arc<dyn AiProvider>, text: &str) -> Result<Vec<f32>> {
+        let key = text.trim().to_string();
+        {
+            let mut inner = self.lock();
+            let same_provider = inner
+                .provider
+                .as_ref()
+                .and_then(Weak::upgrade)
+                .is_some_and(|cached| Arc::ptr_eq(&cached, ai));
+            if !same_provider {
+                *inner = Inner {
+                    provider: Some(Arc::downgrade(ai)),
+                    ..Default::default()
+                };
+            }
+            if let Some(embedding) = inner.entries.get(&key).cloned() {
+                touch(&mut inner.order, &key);
+                return Ok(embedding);
+            }
+        }
+
+        let embedding = ai.generate_embedding(&key).await?;
+
+        let mut inner = self.lock();
+        // The provider may have changed while the embedding was computed.
+        let current = inner
+            .provider
+            .as_ref()
+            .and_then(Weak::upgrade)
+            .is_some_and(|cached| Arc::ptr_eq(&cached, ai));
+        if current && !inner.entries.contains_key(&key) {
+            if inner.entries.len() >= self.capacity {
+                if let Some(oldest) = inner.order.pop_front() {
+                    inner.entries.remove(&oldest);
+                }
+            }
+            inner.entries.insert(key.clone(), embedding.clone());
+            inner.order.push_back(key);
+        }
+        Ok(embedding)
+    }
+}
+
+fn touch(order: &mut VecDeque<String>, key: &str) {
+    if let Some(pos) = order.iter().position(|k| k == key) {
+        if let Some(key) = order.remove(pos) {
+            order.push_back(key);
+        }
+    }
+}
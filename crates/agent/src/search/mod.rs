@@ -0,0 +1,454 @@
+pub mod chat;
+pub mod embedding_cache;
+pub mod planner;
+pub mod rerank;
+pub mod summarize;
+pub mod tools;
+pub mod verify;
+
+use ai::provider::AiProvider;
+use embedding_cache::EmbeddingCache;
+use noodle_core::error::Result;
+use noodle_core::types::SearchFilter;
+use planner::{QueryPlan, QueryPlanner};
+use qdrant_client::qdrant::{Condition, Filter, ScoredPoint};
+use rerank::Reranker;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use storage::qdrant::{
+    QdrantStorage, CONVERSATION_KEY, PROJECT_KEY, SENDER_KEY, SUBJECT_VECTOR_NAME, TAGS_KEY,
+    VECTOR_NAME,
+};
+use storage::sqlite::SqliteStorage;
+use tokio::sync::RwLock;
+use tracing::warn;
+
+/// Reciprocal rank fusion constant; dampens the advantage of top-ranked hits.
+const RRF_K: f64 = 60.0;
+const CHUNK_WORDS: usize = 40;
+/// Vector hits are filtered in SQLite, so over-fetch when a filter will drop some.
+const FILTERED_OVERFETCH: u64 = 4;
+/// Queries of at most this many words also search the subject vectors.
+const SHORT_QUERY_WORDS: usize = 3;
+/// Share of a short query's similarity that comes from the subject.
+const SUBJECT_WEIGHT: f32 = 0.6;
+/// Attachment chunks fetched per search; several may belong to one email.
+const ATTACHMENT_OVERFETCH: u64 = 3;
+/// Query embeddings kept for repeated searches.
+const EMBEDDING_CACHE_SIZE: usize = 256;
+
+pub struct SearchService {
+    sqlite: Arc<SqliteStorage>,
+    qdrant: Arc<QdrantStorage>,
+    ai: Arc<RwLock<Arc<dyn AiProvider>>>,
+    planner: QueryPlanner,
+    reranker: Reranker,
+    embeddings: EmbeddingCache,
+}
+
+impl SearchService {
+    pub fn new(
+        sqlite: Arc<SqliteStorage>,
+        qdrant: Arc<QdrantStorage>,
+        ai: Arc<RwLock<Arc<dyn AiProvider>>>,
+    ) -> Self {
+        Self {
+            reranker: Reranker::new(sqlite.clone(), ai.clone()),
+            sqlite,
+            qdrant,
+            planner: QueryPlanner::new(ai.clone()),
+            embeddings: EmbeddingCache::new(EMBEDDING_CACHE_SIZE),
+            ai,
+        }
+    }
+
+    /// Plans a natural-language query into filters plus a semantic part, then
+    /// runs it. With `rerank_results` on, the LLM reorders the best results.
+    pub async fn search(&self, query: &str, limit: u64) -> Result<Vec<serde_json::Value>> {
+        let tz = self.sqlite.get_user_timezone().await?;
+        let mut plan = self.planner.plan(query, tz).await;
+        if plan.semantic_query.is_empty() && plan.filter.is_empty() {
+            plan = QueryPlan::semantic(query);
+        }
+
+        if plan.semantic_query.is_empty() {
+            return self.sqlite.list_emails(&plan.filter, limit as i64).await;
+        }
+
+        // Re-ranking picks from more candidates than are shown, so relevant
+        // results just past the limit can still make it in.
+        let rerank = self.sqlite.get_all_config().await?.rerank_results;
+        let fetch = if rerank {
+            limit.max(rerank::CANDIDATES as u64)
+        } else {
+            limit
+        };
+        let mut results = self
+            .hybrid_search(&plan.semantic_query, &plan.filter, fetch)
+            .await?;
+        if rerank {
+            results = self.reranker.rerank(query, results).await;
+            results.truncate(limit as usize);
+        }
+        let attachments = match self
+            .attachment_search(&plan.semantic_query, &plan.filter, limit)
+            .await
+        {
+            Ok(attachments) => attachments,
+            Err(e) => {
+                warn!("Attachment search unavailable: {}", e);
+                vec![]
+            }
+        };
+        Ok(interleave(results, attachments, limit as usize))
+    }
+
+    /// Searches the text of stored attachments. Each result is the email an
+    /// attachment came with, marked `"result_type": "attachment"`, with the
+    /// attachment and the passage that matched.
+    pub async fn search_attachments(
+        &self,
+        query: &str,
+        limit: u64,
+    ) -> Result<Vec<serde_json::Value>> {
+        let query = query.trim();
+        if query.is_empty() {
+            return Ok(vec![]);
+        }
+        self.attachment_search(query, &SearchFilter::default(), limit)
+            .await
+    }
+
+    async fn attachment_search(
+        &self,
+        query: &str,
+        filter: &SearchFilter,
+        limit: u64,
+    ) -> Result<Vec<serde_json::Value>> {
+        let ai = self.ai.read().await.clone();
+        let embedding = self.embeddings.embed(&ai, query).await?;
+        let fetch = if filter.is_empty() {
+            limit * ATTACHMENT_OVERFETCH
+        } else {
+            limit * ATTACHMENT_OVERFETCH * FILTERED_OVERFETCH
+        };
+        let points = self.qdrant.search_attachments(embedding, fetch).await?;
+
+        // The best chunk of each email's attachments stands for the email.
+        let mut hits = Vec::new();
+        let mut matches: HashMap<i64, serde_json::Value> = HashMap::new();
+        for point in points {
+            let payload = &point.payload;
+            let Some(email_id) = payload.get("email_id").and_then(|v| v.as_integer()) else {
+                continue;
+            };
+            if matches.contains_key(&email_id) {
+                continue;
+            }
+            let string = |key: &str| {
+                payload
+                    .get(key)
+                    .and_then(|v| v.as_str())
+                    .cloned()
+                    .unwrap_or_default()
+            };
+            matches.insert(
+                email_id,
+                serde_json::json!({
+                    "attachment": {
+                        "id": payload.get("attachment_id").and_then(|v| v.as_integer()),
+                        "filename": string("filename"),
+                    },
+                    "explanation": {
+                        "vector": { "chunk": string("text"), "similarity": point.score },
+                        "keyword": null
+                    },
+                }),
+            );
+            hits.push((email_id, point.score));
+        }
+
+        let mut emails = self.sqlite.get_emails_by_ids(hits, filter).await?;
+        emails.truncate(limit as usize);
+        for email in &mut emails {
+            let id = email["id"].as_i64().unwrap_or_default();
+            if let Some(found) = matches.remove(&id) {
+                email["result_type"] = "attachment".into();
+                email["attachment"] = found["attachment"].clone();
+                email["explanation"] = found["explanation"].clone();
+            }
+        }
+        Ok(emails)
+    }
+
+    /// Searches one conversation, for "search this thread" in the UI. An
+    /// empty query lists the whole thread.
+    pub async fn search_in_thread(
+        &self,
+        conversation_id: &str,
+        query: &str,
+        limit: u64,
+    ) -> Result<Vec<serde_json::Value>> {
+        let filter = SearchFilter {
+            conversation_id: Some(conversation_id.to_string()),
+            ..Default::default()
+        };
+        self.scoped_search(query, &filter, limit).await
+    }
+
+    /// Searches mail from one sender, for "search from sender" in the UI. An
+    /// empty query lists their latest emails.
+    pub async fn search_from_sender(
+        &self,
+        sender: &str,
+        query: &str,
+        limit: u64,
+    ) -> Result<Vec<serde_json::Value>> {
+        let filter = SearchFilter {
+            sender: Some(sender.to_string()),
+            ..Default::default()
+        };
+        self.scoped_search(query, &filter, limit).await
+    }
+
+    /// Runs `query` as is, without planning, since the scope comes from
+    /// what the user clicked rather than from the query.
+    async fn scoped_search(
+        &self,
+        query: &str,
+        filter: &SearchFilter,
+        limit: u64,
+    ) -> Result<Vec<serde_json::Value>> {
+        let query = query.trim();
+        if query.is_empty() {
+            return self.sqlite.list_emails(filter, limit as i64).await;
+        }
+        self.hybrid_search(query, filter, limit).await
+    }
+
+    /// Hybrid search: vector and keyword hits are fused by rank, and every result
+    /// carries an `explanation` describing why it was retrieved.
+    pub async fn hybrid_search(
+        &self,
+        query: &str,
+        filter: &SearchFilter,
+        limit: u64,
+    ) -> Result<Vec<serde_json::Value>> {
+        let vector_results = match self.vector_search(query, filter, limit).await {
+            Ok(results) => results,
+            Err(e) => {
+                warn!(
+                    "Vector search unavailable, using keyword results only: {}",
+                    e
+                );
+                vec![]
+            }
+        };
+        let keyword_results = self
+            .sqlite
+            .search_keyword(query, filter, limit as i64)
+            .await?;
+
+        let mut order: Vec<i64> = Vec::new();
+        let mut fused: HashMap<i64, (f64, serde_json::Value)> = HashMap::new();
+
+        for (rank, mut email) in vector_results.into_iter().enumerate() {
+            let id = email["id"].as_i64().unwrap_or_default();
+            let chunk = best_matching_chunk(email["body_text"].as_str().unwrap_or(""), query);
+            email["explanation"] = serde_json::json!({
+                "vector": { "chunk": chunk, "similarity": email["score"].clone() },
+                "keyword": null
+            });
+            order.push(id);
+            fused.insert(id, (1.0 / (RRF_K + rank as f64 + 1.0), email));
+        }
+
+        for (rank, mut email) in keyword_results.into_iter().enumerate() {
+            let id = email["id"].as_i64().unwrap_or_default();
+            let keyword = serde_json::json!({
+                "snippet": email["snippet"].take(),
+                "rank": email["rank"].take()
+            });
+            let contribution = 1.0 / (RRF_K + rank as f64 + 1.0);
+
+            match fused.get_mut(&id) {
+                Some((score, existing)) => {
+                    *score += contribution;
+                    existing["explanation"]["keyword"] = keyword;
+                }
+                None => {
+                    if let Some(obj) = email.as_object_mut() {
+                        obj.remove("snippet");
+                        obj.remove("rank");
+                    }
+                    email["explanation"] =
+                        serde_json::json!({ "vector": null, "keyword": keyword });
+                    order.push(id);
+                    fused.insert(id, (contribution, email));
+                }
+            }
+        }
+
+        let mut results: Vec<(f64, serde_json::Value)> = order
+            .into_iter()
+            .filter_map(|id| fused.remove(&id))
+            .collect();
+        results.sort_by(|a, b| b.0.total_cmp(&a.0));
+        results.truncate(limit as usize);
+
+        Ok(results.into_iter().map(|(_, email)| email).collect())
+    }
+
+    async fn vector_search(
+        &self,
+        query: &str,
+        filter: &SearchFilter,
+        limit: u64,
+    ) -> Result<Vec<serde_json::Value>> {
+        let ai = self.ai.read().await.clone();
+        let embedding = self.embeddings.embed(&ai, query).await?;
+
+        let fetch = if filter.is_empty() {
+            limit
+        } else {
+            limit * FILTERED_OVERFETCH
+        };
+        let payload = payload_filter(filter);
+        let body = self
+            .qdrant
+            .search_emails(VECTOR_NAME, embedding.clone(), payload.clone(), fetch)
+            .await?;
+        let hits = if query.split_whitespace().count() <= SHORT_QUERY_WORDS {
+            let subject = self
+                .qdrant
+                .search_emails(SUBJECT_VECTOR_NAME, embedding, payload, fetch)
+                .await?;
+            weighted_hits(subject, body)
+        } else {
+            scored_hits(body).collect()
+        };
+
+        let mut emails = self.sqlite.get_emails_by_ids(hits, filter).await?;
+        emails.truncate(limit as usize);
+        Ok(emails)
+    }
+}
+
+/// The part of `filter` Qdrant applies itself from the payload the sync
+/// worker maintains, so the nearest points fetched already match it. SQL
+/// still applies the whole filter to them.
+pub fn payload_filter(filter: &SearchFilter) -> Option<Filter> {
+    let mut conditions: Vec<Condition> = filter
+        .tags
+        .iter()
+        .map(|tag| Condition::matches(TAGS_KEY, tag.clone()))
+        .collect();
+    // Without a text index these are substring matches, like SQL's LIKE.
+    if let Some(sender) = &filter.sender {
+        conditions.push(Condition::matches_text(SENDER_KEY, sender.to_lowercase()));
+    }
+    if let Some(project) = &filter.project {
+        conditions.push(Condition::matches_text(PROJECT_KEY, project.to_lowercase()));
+    }
+    if let Some(conversation_id) = &filter.conversation_id {
+        conditions.push(Condition::matches(
+            CONVERSATION_KEY,
+            conversation_id.clone(),
+        ));
+    }
+    (!conditions.is_empty()).then(|| Filter::must(conditions))
+}
+
+/// Merges email and attachment results by rank, marking emails with
+/// `"result_type": "email"`, so both kinds are seen near the top. An email
+/// found both ways is listed once, where it ranks first.
+pub fn interleave(
+    emails: Vec<serde_json::Value>,
+    attachments: Vec<serde_json::Value>,
+    limit: usize,
+) -> Vec<serde_json::Value> {
+    let mut emails = emails.into_iter().map(|mut email| {
+        email["result_type"] = "email".into();
+        email
+    });
+    let mut attachments = attachments.into_iter();
+    let mut merged = Vec::with_capacity(limit);
+    let mut seen = HashSet::new();
+    while merged.len() < limit {
+        match (emails.next(), attachments.next()) {
+            (None, None) => break,
+            (email, attachment) => merged.extend(
+                email
+                    .into_iter()
+                    .chain(attachment)
+                    .filter(|result| result["id"].as_i64().is_none_or(|id| seen.insert(id))),
+            ),
+        }
+    }
+    merged.truncate(limit);
+    merged
+}
+
+fn scored_hits(points: Vec<ScoredPoint>) -> impl Iterator<Item = (i64, f32)> {
+    points.into_iter().filter_map(|p| {
+        p.payload
+            .get("email_id")
+            .and_then(|v| v.as_integer())
+            .map(|id| (id, p.score))
+    })
+}
+
+/// Blends subject and body similarity, weighting the subject by
+/// [`SUBJECT_WEIGHT`]. An email found by one search only scores zero on the
+/// other, so strong subject matches still surface. Best first.
+fn weighted_hits(subject: Vec<ScoredPoint>, body: Vec<ScoredPoint>) -> Vec<(i64, f32)> {
+    let mut scores: HashMap<i64, (f32, f32)> = HashMap::new();
+    for (id, score) in scored_hits(subject) {
+        scores.entry(id).or_default().0 = score;
+    }
+    for (id, score) in scored_hits(body) {
+        scores.entry(id).or_default().1 = score;
+    }
+    let mut hits: Vec<(i64, f32)> = scores
+        .into_iter()
+        .map(|(id, (subject, body))| (id, SUBJECT_WEIGHT * subject + (1.0 - SUBJECT_WEIGHT) * body))
+        .collect();
+    hits.sort_by(|a, b| b.1.total_cmp(&a.1));
+    hits
+}
+
+/// Picks the body window sharing the most terms with the query. Emails are
+/// embedded whole, so this approximates which passage drove the similarity.
+fn best_matching_chunk(body: &str, query: &str) -> String {
+    let terms: Vec<String> = query
+        .split_whitespace()
+        .map(|t| t.to_lowercase())
+        .filter(|t| t.len() > 2)
+        .collect();
+
+    let words: Vec<&str> = body.split_whitespace().collect();
+    if words.is_empty() {
+        return String::new();
+    }
+
+    words
+        .chunks(CHUNK_WORDS)
+        .map(|chunk| {
+            let hits = chunk
+                .iter()
+                .filter(|w| {
+                    let w = w.to_lowercase();
+                    terms.iter().any(|t| w.contains(t.as_str()))
+                })
+                .count();
+            (hits, chunk.join(" "))
+        })
+        .fold((0, String::new()), |best, (hits, text)| {
+            if best.1.is_empty() || hits > best.0 {
+                (hits, text)
+            } else {
+                best
+            }
+        })
+        .1
+}
@@ -0,0 +1,164 @@
+use ai::provider::{AiProvider, ChatRequest, Message, ResponseFormat};
+use chrono::{NaiveDate, Utc};
+use noodle_core::time::UserTimezone;
+use noodle_core::types::{Annotation, DateRange, SearchFilter};
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use tracing::warn;
+
+/// Queries this short are treated as plain keywords; planning them costs an LLM
+/// round trip for no gain.
+const MIN_PLANNED_WORDS: usize = 3;
+
+#[derive(Debug, Clone)]
+pub struct QueryPlan {
+    /// Free-text part of the query, used for vector and keyword retrieval.
+    /// Empty when the query consisted only of filters.
+    pub semantic_query: String,
+    pub filter: SearchFilter,
+}
+
+impl QueryPlan {
+    pub fn semantic(query: &str) -> Self {
+        Self {
+            semantic_query: query.to_string(),
+            filter: SearchFilter::default(),
+        }
+    }
+}
+
+pub struct QueryPlanner {
+    ai: Arc<RwLock<Arc<dyn AiProvider>>>,
+}
+
+impl QueryPlanner {
+    pub fn new(ai: Arc<RwLock<Arc<dyn AiProvider>>>) -> Self {
+        Self { ai }
+    }
+
+    /// Splits a natural-language query into structured filters plus the remaining
+    /// semantic query. Date filters cover whole days in `tz`, and `#words`
+    /// filter by the user's own tags. Never fails: any planner error degrades
+    /// to plain semantic search.
+    pub async fn plan(&self, query: &str, tz: UserTimezone) -> QueryPlan {
+        let (query, tags) = split_tags(query);
+        let mut plan = if query.split_whitespace().count() < MIN_PLANNED_WORDS {
+            QueryPlan::semantic(&query)
+        } else {
+            match self.run_planner(&query, tz).await {
+                Ok(plan) => plan,
+                Err(e) => {
+                    warn!(
+                        "Query planner failed, falling back to semantic search: {}",
+                        e
+                    );
+                    QueryPlan::semantic(&query)
+                }
+            }
+        };
+        plan.filter.tags = tags;
+        plan
+    }
+
+    async fn run_planner(
+        &self,
+        query: &str,
+        tz: UserTimezone,
+    ) -> noodle_core::error::Result<QueryPlan> {
+        let prompt = format!(
+            "Convert the email search query below into JSON filters.
+Today is {}. Resolve relative dates (\"last month\", \"this week\") to absolute dates.
+Only set a field when the query clearly states it; otherwise use null.
+Put the remaining topic words (without the filter words) in semantic_query.
+
+Respond ONLY with valid JSON matching this schema:
+{{
+  \"semantic_query\": \"string\",
+  \"sender\": \"string|null\",
+  \"project\": \"string|null\",
+  \"folder\": \"Inbox|Sent Items|null\",
+  \"urgency\": \"low|medium|high|null\",
+  \"sentiment\": \"neutral|positive|concerned|hostile|null\",
+  \"needs_response\": true|false|null,
+  \"date_from\": \"YYYY-MM-DD|null\",
+  \"date_to\": \"YYYY-MM-DD|null\"
+}}
+
+Query: {}",
+            tz.local_date(Utc::now()).format("%Y-%m-%d"),
+            query
+        );
+
+        let request = ChatRequest {
+            messages: vec![Message {
+                role: "user".into(),
+                content: prompt,
+            }],
+            temperature: 0.0,
+            response_format: Some(ResponseFormat::Json),
+            model: None,
+        };
+
+        let ai = self.ai.read().await;
+        let response = ai.chat_completion(request).await?;
+        let data: serde_json::Value = serde_json::from_str(&response.content)
+            .map_err(|e| noodle_core::error::NoodleError::AI(e.to_string()))?;
+
+        let text = |key: &str| {
+            data[key]
+                .as_str()
+                .map(str::trim)
+                .filter(|s| !s.is_empty())
+                .map(str::to_string)
+        };
+
+        let day =
+            |key: &str| text(key).and_then(|s| NaiveDate::parse_from_str(&s, "%Y-%m-%d").ok());
+        let date_from = day("date_from").map(|d| tz.start_of_day(d));
+        let date_to = day("date_to").map(|d| tz.start_of_day(d + chrono::Duration::days(1)));
+
+        let filter = SearchFilter {
+            sender: text("sender"),
+            project: text("project"),
+            folder: text("folder"),
+            urgency: serde_json::from_value(data["urgency"].clone()).ok(),
+            sentiment: serde_json::from_value(data["sentiment"].clone()).ok(),
+            needs_response: data["needs_response"].as_bool(),
+            date_range: (date_from.is_some() || date_to.is_some()).then_some(DateRange {
+                start: date_from,
+                end: date_to,
+            }),
+            conversation_id: None,
+            tags: Vec::new(),
+        };
+
+        Ok(QueryPlan {
+            semantic_query: text("semantic_query").unwrap_or_default(),
+            filter,
+        })
+    }
+}
+
+/// The query without its `#tag` words, and the tags. Tags are the user's
+/// own, so they are matched exactly rather than left to the planner.
+/// Numbers like `#123` stay in the query, as they are mostly ticket and
+/// invoice numbers.
+fn split_tags(query: &str) -> (String, Vec<String>) {
+    let mut words = Vec::new();
+    let mut tags = Vec::new();
+    for word in query.split_whitespace() {
+        let tag = word
+            .strip_prefix('#')
+            .and_then(Annotation::normalize_tag)
+            .filter(|tag| !tag.chars().all(|c| c.is_ascii_digit()));
+        match tag {
+            Some(tag) => {
+                if !tags.contains(&tag) {
+                    tags.push(tag);
+                }
+            }
+            None => words.push(word),
+        }
+    }
+    (words.join(" "), tags)
+}
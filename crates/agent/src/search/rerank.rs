@@ -0,0 +1,152 @@
+use ai::injection;
+use ai::provider::{AiProvider, ChatRequest, Message, ResponseFormat};
+use noodle_core::error::{NoodleError, Result};
+use sha2::{Digest, Sha256};
+use std::sync::Arc;
+use storage::sqlite::SqliteStorage;
+use tokio::sync::RwLock;
+use tracing::warn;
+
+/// Candidates shown to the model.
+pub const CANDIDATES: usize = 20;
+/// Results the model puts in order; the rest keep their retrieval order.
+const TOP: usize = 5;
+/// Body characters shown per candidate.
+const SNIPPET_CHARS: usize = 300;
+/// Cached rankings older than this are dropped.
+const CACHE_DAYS: i64 = 30;
+
+/// Reorders the best search results by asking the LLM which candidates
+/// answer the query. Rankings are cached by query and candidate set.
+pub struct Reranker {
+    sqlite: Arc<SqliteStorage>,
+    ai: Arc<RwLock<Arc<dyn AiProvider>>>,
+}
+
+impl Reranker {
+    pub fn new(sqlite: Arc<SqliteStorage>, ai: Arc<RwLock<Arc<dyn AiProvider>>>) -> Self {
+        Self { sqlite, ai }
+    }
+
+    /// Moves the [`TOP`] most relevant of the first [`CANDIDATES`] results to
+    /// the front, marking each with its `explanation.rerank` position. Never
+    /// fails: on any error the results come back unchanged.
+    pub async fn rerank(
+        &self,
+        query: &str,
+        mut results: Vec<serde_json::Value>,
+    ) -> Vec<serde_json::Value> {
+        let count = results.len().min(CANDIDATES);
+        if count < 2 {
+            return results;
+        }
+        let candidates = &results[..count];
+        let ids: Vec<i64> = candidates.iter().filter_map(|e| e["id"].as_i64()).collect();
+
+        let key = cache_key(query, &ids);
+        let ranked = match self.sqlite.get_rerank(&key).await {
+            Ok(Some(ranked)) => ranked,
+            _ => match self.ask(query, candidates, &ids).await {
+                Ok(ranked) => {
+                    if let Err(e) = self.sqlite.save_rerank(&key, &ranked, CACHE_DAYS).await {
+                        warn!("Failed to cache re-ranking: {}", e);
+                    }
+                    ranked
+                }
+                Err(e) => {
+                    warn!("Re-ranking failed, keeping retrieval order: {}", e);
+                    return results;
+                }
+            },
+        };
+
+        let mut front = Vec::new();
+        for (position, id) in ranked.iter().enumerate() {
+            if let Some(i) = results.iter().position(|e| e["id"].as_i64() == Some(*id)) {
+                let mut email = results.remove(i);
+                email["explanation"]["rerank"] = serde_json::json!(position + 1);
+                front.push(email);
+            }
+        }
+        front.extend(results);
+        front
+    }
+
+    async fn ask(
+        &self,
+        query: &str,
+        candidates: &[serde_json::Value],
+        ids: &[i64],
+    ) -> Result<Vec<i64>> {
+        let listing: Vec<String> = candidates
+            .iter()
+            .map(|e| {
+                let body: String = e["body_text"]
+                    .as_str()
+                    .unwrap_or_default()
+                    .split_whitespace()
+                    .collect::<Vec<_>>()
+                    .join(" ")
+                    .chars()
+                    .take(SNIPPET_CHARS)
+                    .collect();
+                format!(
+                    "[{}]\n{}",
+                    e["id"],
+                    injection::data_block(&format!(
+                        "Subject: {}\nFrom: {}\n{}",
+                        e["subject"].as_str().unwrap_or_default(),
+                        e["sender"].as_str().unwrap_or_default(),
+                        body
+                    ))
+                )
+            })
+            .collect();
+        let prompt = format!(
+            "Search query: {}\n\nCandidate emails, each with its id in brackets:\n\n{}\n\n\
+             Pick the {} emails most relevant to the query, most relevant first. {}\n\
+             Respond ONLY with JSON: {{\"ids\": [id, ...]}}",
+            query,
+            listing.join("\n\n"),
+            TOP,
+            injection::DATA_INSTRUCTION
+        );
+        let request = ChatRequest {
+            messages: vec![Message {
+                role: "user".into(),
+                content: prompt,
+            }],
+            temperature: 0.0,
+            response_format: Some(ResponseFormat::Json),
+            model: None,
+        };
+
+        let ai = self.ai.read().await.clone();
+        let response = ai.chat_completion(request).await?;
+        let data: serde_json::Value =
+            serde_json::from_str(&response.content).map_err(|e| NoodleError::AI(e.to_string()))?;
+        let mut ranked: Vec<i64> = Vec::new();
+        for id in data["ids"].as_array().into_iter().flatten() {
+            // Ids the model made up or repeated are ignored.
+            if let Some(id) = id.as_i64().filter(|id| ids.contains(id)) {
+                if !ranked.contains(&id) {
+                    ranked.push(id);
+                }
+            }
+        }
+        ranked.truncate(TOP);
+        if ranked.is_empty() {
+            return Err(NoodleError::AI("No candidate ids in the ranking".into()));
+        }
+        Ok(ranked)
+    }
+}
+
+fn cache_key(query: &str, ids: &[i64]) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(query.trim().to_lowercase());
+    for id in ids {
+        hasher.update(id.to_le_bytes());
+    }
+    format!("{:x}", hasher.finalize())
+}
@@ -0,0 +1,277 @@
+use super::SearchService;
+use crate::pipeline::pacing::PacingController;
+use ai::injection;
+use ai::provider::{AiProvider, ChatRequest, Message};
+use chrono::{DateTime, Utc};
+use noodle_core::error::{NoodleError, Result};
+use noodle_core::locale::UserLocale;
+use noodle_core::types::{DateRange, SearchFilter, TopicSource, TopicSummary};
+use std::collections::HashSet;
+use std::sync::Arc;
+use storage::sqlite::SqliteStorage;
+use tokio::sync::RwLock;
+use tokio::task::JoinSet;
+use tracing::warn;
+
+/// Most emails retrieved for one topic.
+const MAX_EMAILS: u64 = 40;
+/// Body characters sent to the model per email.
+const MAX_BODY_CHARS: usize = 6000;
+/// Notes synthesized in one call; longer lists are condensed in batches first.
+const REDUCE_BATCH: usize = 15;
+/// What the map step answers for an email that says nothing about the topic.
+const IRRELEVANT: &str = "NONE";
+
+pub struct TopicSummarizer {
+    sqlite: Arc<SqliteStorage>,
+    search: Arc<SearchService>,
+    ai: Arc<RwLock<Arc<dyn AiProvider>>>,
+    pacing: Arc<PacingController>,
+}
+
+impl TopicSummarizer {
+    pub fn new(
+        sqlite: Arc<SqliteStorage>,
+        search: Arc<SearchService>,
+        ai: Arc<RwLock<Arc<dyn AiProvider>>>,
+        pacing: Arc<PacingController>,
+    ) -> Self {
+        Self {
+            sqlite,
+            search,
+            ai,
+            pacing,
+        }
+    }
+
+    /// Retrieves the emails about `query` in `date_range`, summarizes each with
+    /// respect to the topic (map), then synthesizes those notes into one summary
+    /// citing email ids (reduce). The result is stored even when the synthesis
+    /// fails, with the error recorded on it.
+    pub async fn summarize(&self, query: &str, date_range: DateRange) -> Result<TopicSummary> {
+        let query = query.trim();
+        if query.is_empty() {
+            return Err(NoodleError::Validation("Enter a topic to summarize".into()));
+        }
+        let filter = SearchFilter {
+            date_range: Some(date_range.clone()),
+            ..Default::default()
+        };
+        let emails = self
+            .search
+            .hybrid_search(query, &filter, MAX_EMAILS)
+            .await?;
+        if emails.is_empty() {
+            return Err(NoodleError::Validation(
+                "No emails match this topic in the selected range".into(),
+            ));
+        }
+
+        let locale = self.sqlite.get_user_locale().await?;
+        let mut sources = self.map(query, emails, locale).await?;
+        let mut summary = TopicSummary {
+            id: 0,
+            query: query.to_string(),
+            date_range,
+            run_at: Utc::now(),
+            status: "completed".into(),
+            output_text: None,
+            sources: Vec::new(),
+            error_text: None,
+        };
+        if sources.is_empty() {
+            summary.output_text = Some("None of the matching emails discuss this topic.".into());
+        } else {
+            match self.reduce(query, &sources, locale).await {
+                Ok(text) => {
+                    let cited = citations(&text);
+                    for source in &mut sources {
+                        source.cited = cited.contains(&source.email_id);
+                    }
+                    summary.output_text = Some(text);
+                }
+                Err(e) => {
+                    summary.status = "failed".into();
+                    summary.error_text = Some(e.to_string());
+                }
+            }
+        }
+        summary.sources = sources;
+        summary.id = self.sqlite.save_topic_summary(&summary).await?;
+        Ok(summary)
+    }
+
+    /// Summarizes each email with respect to the topic, dropping the ones that
+    /// turn out to be unrelated. The calls share the pipeline's pacing, which
+    /// caps how many run at once. When the model fails on an email, its stored
+    /// extraction summary is used instead.
+    async fn map(
+        &self,
+        query: &str,
+        emails: Vec<serde_json::Value>,
+        locale: UserLocale,
+    ) -> Result<Vec<TopicSource>> {
+        let ai = self.ai.read().await.clone();
+        let mut tasks = JoinSet::new();
+        for email in emails {
+            let Some(email_id) = email["id"].as_i64() else {
+                continue;
+            };
+            let ai = ai.clone();
+            let pacing = self.pacing.clone();
+            let prompt = map_prompt(query, &email, locale);
+            tasks.spawn(async move {
+                let note = match pacing
+                    .llm_call("topic_summary", complete(ai.as_ref(), prompt))
+                    .await
+                {
+                    Ok(note) => Some(note),
+                    Err(e) => {
+                        warn!("Topic summary fell back for email {}: {}", email_id, e);
+                        email["summary"].as_str().map(str::to_string)
+                    }
+                };
+                (email_id, email, note)
+            });
+        }
+
+        let mut sources = Vec::new();
+        while let Some(joined) = tasks.join_next().await {
+            let (email_id, email, note) =
+                joined.map_err(|e| NoodleError::Internal(e.to_string()))?;
+            let Some(note) = note.filter(|n| !n.is_empty() && n != IRRELEVANT) else {
+                continue;
+            };
+            sources.push(TopicSource {
+                email_id,
+                subject: email["subject"].as_str().unwrap_or_default().to_string(),
+                sender: email["sender"].as_str().unwrap_or_default().to_string(),
+                received_at: serde_json::from_value::<DateTime<Utc>>(email["received_at"].clone())
+                    .unwrap_or_default(),
+                summary: note,
+                cited: false,
+            });
+        }
+        sources.sort_by_key(|s| s.received_at);
+        Ok(sources)
+    }
+
+    /// Synthesizes the per-email notes. Lists longer than [`REDUCE_BATCH`] are
+    /// condensed batch by batch until one call can take them all.
+    async fn reduce(
+        &self,
+        query: &str,
+        sources: &[TopicSource],
+        locale: UserLocale,
+    ) -> Result<String> {
+        let ai = self.ai.read().await.clone();
+        let mut notes: Vec<String> = sources
+            .iter()
+            .map(|s| {
+                format!(
+                    "[#{}] {} from {}: {}",
+                    s.email_id,
+                    locale.format_date(&s.received_at),
+                    s.sender,
+                    s.summary
+                )
+            })
+            .collect();
+        while notes.len() > REDUCE_BATCH {
+            let mut condensed = Vec::new();
+            for batch in notes.chunks(REDUCE_BATCH) {
+                let prompt = condense_prompt(query, batch, locale);
+                let note = self
+                    .pacing
+                    .llm_call("topic_summary", complete(ai.as_ref(), prompt))
+                    .await?;
+                condensed.push(note);
+            }
+            notes = condensed;
+        }
+        let prompt = synthesis_prompt(query, &notes, locale);
+        self.pacing
+            .llm_call("topic_summary", complete(ai.as_ref(), prompt))
+            .await
+    }
+}
+
+pub(crate) async fn complete(ai: &dyn AiProvider, prompt: String) -> Result<String> {
+    let request = ChatRequest {
+        messages: vec![Message {
+            role: "user".into(),
+            content: prompt,
+        }],
+        temperature: 0.2,
+        response_format: None,
+        model: None,
+    };
+    Ok(ai
+        .chat_completion(request)
+        .await?
+        .content
+        .trim()
+        .to_string())
+}
+
+fn map_prompt(query: &str, email: &serde_json::Value, locale: UserLocale) -> String {
+    let body: String = email["body_text"]
+        .as_str()
+        .unwrap_or_default()
+        .chars()
+        .take(MAX_BODY_CHARS)
+        .collect();
+    format!(
+        "Topic: {}\n\n{}\n\n\
+         In at most three sentences, state what this email says about the topic: \
+         facts, decisions, requests and open questions. Reply with only {} if it \
+         says nothing about the topic. {} {}",
+        query,
+        injection::data_block(&format!(
+            "Subject: {}\nFrom: {}\nDate: {}\n\n{}",
+            email["subject"].as_str().unwrap_or_default(),
+            email["sender"].as_str().unwrap_or_default(),
+            email["received_at"].as_str().unwrap_or_default(),
+            body
+        )),
+        IRRELEVANT,
+        injection::DATA_INSTRUCTION,
+        locale.prompt_instruction()
+    )
+}
+
+fn condense_prompt(query: &str, notes: &[String], locale: UserLocale) -> String {
+    format!(
+        "Topic: {}\n\nNotes from emails, each tagged with its email id:\n{}\n\n\
+         Condense these notes into a shorter list of points about the topic. \
+         Keep the [#id] tags of the emails each point comes from. {}",
+        query,
+        notes.join("\n"),
+        locale.prompt_instruction()
+    )
+}
+
+fn synthesis_prompt(query: &str, notes: &[String], locale: UserLocale) -> String {
+    format!(
+        "Topic: {}\n\nNotes from emails, each tagged with its email id:\n{}\n\n\
+         Write a summary of what these emails say about the topic: how it developed, \
+         decisions made, and what is still open. Cite the emails behind every \
+         statement with their tags, e.g. [#12] or [#12][#40]. Do not cite ids that \
+         are not in the notes. {}",
+        query,
+        notes.join("\n"),
+        locale.prompt_instruction()
+    )
+}
+
+/// Email ids cited as `[#id]` in the synthesis. A bare `#12`, such as an
+/// issue number the emails mention, isn't a citation.
+pub fn citations(text: &str) -> HashSet<i64> {
+    text.split("[#")
+        .skip(1)
+        .filter_map(|rest| {
+            let (digits, _) = rest.split_once(']')?;
+            digits.parse().ok()
+        })
+        .collect()
+}
@@ -0,0 +1,419 @@
+use super::SearchService;
+use chrono::{DateTime, Duration, Utc};
+use noodle_core::error::{NoodleError, Result};
+use noodle_core::locale::UserLocale;
+use noodle_core::types::{MeetingTaskKind, SearchFilter, TopicSource};
+use serde_json::Value;
+use std::fmt::Write;
+use std::sync::Arc;
+use storage::sqlite::{ProjectEmailFacts, SqliteStorage};
+
+/// Emails a `search` call returns.
+const SEARCH_LIMIT: u64 = 8;
+/// Emails of a thread or project listed in full; older ones are counted.
+const MAX_LISTED_EMAILS: usize = 15;
+/// Risks, blockers and decisions listed per project.
+const MAX_SIGNALS: usize = 8;
+/// How far back `list_tasks` looks for meeting action items.
+const TASK_LOOKBACK_DAYS: i64 = 60;
+/// How far ahead `list_tasks` looks for deadlines.
+const DEADLINE_DAYS: i64 = 14;
+/// Characters of one tool result sent to the model.
+const MAX_RESULT_CHARS: usize = 6000;
+
+/// The tools, as described to the model.
+pub const TOOL_DESCRIPTIONS: &str = "\
+- search {\"query\": string, \"project\"?: string, \"sender\"?: string}: emails matching the query, \
+optionally only a project's or a sender's.
+- get_thread {\"email_id\": number}: every email of the conversation an email belongs to, oldest first.
+- get_project_health {\"project\": string}: a project's decisions, open risks and blockers, and latest emails.
+- list_tasks {\"owner\"?: string}: open action items from meetings and emails with deadlines coming up.";
+
+/// What a tool returned: `text` for the model, a short `summary` for the
+/// trace, and the emails it showed, which the answer may cite.
+pub struct ToolOutput {
+    pub text: String,
+    pub summary: String,
+    pub sources: Vec<TopicSource>,
+}
+
+/// The internal tools the chat assistant researches with. Each is read-only
+/// and returns plain text, with emails tagged `[#id]` like in plain answers.
+pub struct ChatTools {
+    sqlite: Arc<SqliteStorage>,
+    search: Arc<SearchService>,
+}
+
+impl ChatTools {
+    pub fn new(sqlite: Arc<SqliteStorage>, search: Arc<SearchService>) -> Self {
+        Self { sqlite, search }
+    }
+
+    /// Runs `tool` with the model's `arguments`. Bad arguments are
+    /// validation errors, worded for the model to correct them.
+    pub async fn call(
+        &self,
+        tool: &str,
+        arguments: &Value,
+        locale: UserLocale,
+    ) -> Result<ToolOutput> {
+        let mut output = match tool {
+            "search" => self.search(arguments, locale).await?,
+            "get_thread" => self.get_thread(arguments, locale).await?,
+            "get_project_health" => self.get_project_health(arguments, locale).await?,
+            "list_tasks" => self.list_tasks(arguments, locale).await?,
+            _ => {
+                return Err(NoodleError::Validation(format!(
+                    "Unknown tool {}; use search, get_thread, get_project_health or list_tasks",
+                    tool
+                )))
+            }
+        };
+        if output.text.chars().count() > MAX_RESULT_CHARS {
+            output.text = output.text.chars().take(MAX_RESULT_CHARS).collect();
+            output.text.push_str("\n[truncated]");
+        }
+        Ok(output)
+    }
+
+    async fn search(&self, arguments: &Value, locale: UserLocale) -> Result<ToolOutput> {
+        let query = text_argument(arguments, "query")
+            .ok_or_else(|| NoodleError::Validation("search needs a query".into()))?;
+        let project = match text_argument(arguments, "project") {
+            Some(project) => Some(self.project_name(&project).await?),
+            None => None,
+        };
+        let filter = SearchFilter {
+            project,
+            sender: text_argument(arguments, "sender"),
+            ..Default::default()
+        };
+        let sources: Vec<TopicSource> = self
+            .search
+            .hybrid_search(&query, &filter, SEARCH_LIMIT)
+            .await?
+            .iter()
+            .filter_map(source_from_result)
+            .collect();
+        let text = if sources.is_empty() {
+            "No emails matched.".to_string()
+        } else {
+            sources
+                .iter()
+                .map(|s| {
+                    format!(
+                        "[#{}] {} · {} · {}\n{}",
+                        s.email_id,
+                        s.subject,
+                        s.sender,
+                        locale.format_date(&s.received_at),
+                        s.summary
+                    )
+                })
+                .collect::<Vec<_>>()
+                .join("\n\n")
+        };
+        Ok(ToolOutput {
+            text,
+            summary: count(sources.len(), "email"),
+            sources,
+        })
+    }
+
+    async fn get_thread(&self, arguments: &Value, locale: UserLocale) -> Result<ToolOutput> {
+        let email_id = arguments["email_id"]
+            .as_i64()
+            .or_else(|| arguments["email_id"].as_str()?.trim().parse().ok())
+            .ok_or_else(|| NoodleError::Validation("get_thread needs an email_id".into()))?;
+        let emails = self.sqlite.get_thread_email_facts(email_id).await?;
+        if emails.is_empty() {
+            return Err(NoodleError::Validation(format!(
+                "Email {} was not found or is not processed yet",
+                email_id
+            )));
+        }
+        let mut text = String::new();
+        let skipped = emails.len().saturating_sub(MAX_LISTED_EMAILS);
+        if skipped > 0 {
+            let _ = writeln!(text, "({} earlier emails left out)\n", skipped);
+        }
+        for email in &emails[skipped..] {
+            write_email(&mut text, email, locale);
+        }
+        Ok(ToolOutput {
+            text,
+            summary: count(emails.len(), "email"),
+            sources: emails[skipped..].iter().map(source_from_facts).collect(),
+        })
+    }
+
+    async fn get_project_health(
+        &self,
+        arguments: &Value,
+        locale: UserLocale,
+    ) -> Result<ToolOutput> {
+        let project = text_argument(arguments, "project")
+            .ok_or_else(|| NoodleError::Validation("get_project_health needs a project".into()))?;
+        let project = self.project_name(&project).await?;
+        let emails = self.sqlite.get_project_email_facts(&project, None).await?;
+
+        let mut text = String::new();
+        // Every email the text tags, so the answer can cite any of them.
+        let mut tagged: Vec<i64> = Vec::new();
+        let _ = writeln!(
+            text,
+            "Project {}: {}",
+            project,
+            count(emails.len(), "email")
+        );
+        if let Some(latest) = emails.last() {
+            let _ = writeln!(
+                text,
+                "Latest activity: {}",
+                locale.format_date(&latest.received_at)
+            );
+        }
+        if let Some(note) = self
+            .sqlite
+            .get_project_annotation(&project)
+            .await?
+            .and_then(|a| a.note)
+        {
+            let _ = writeln!(text, "The user's note: {}", note);
+        }
+
+        // Newest first, like the project health report.
+        text.push_str("\nDecisions:\n");
+        let decisions: Vec<&ProjectEmailFacts> = emails
+            .iter()
+            .rev()
+            .filter(|e| e.primary_type == "decision")
+            .take(MAX_SIGNALS)
+            .collect();
+        if decisions.is_empty() {
+            text.push_str("None recorded.\n");
+        }
+        for email in &decisions {
+            tagged.push(email.id);
+            let _ = writeln!(
+                text,
+                "- [#{}] {}: {}",
+                email.id,
+                locale.format_date(&email.received_at),
+                email.summary
+            );
+        }
+        for (heading, signals) in [
+            (
+                "Risks",
+                emails
+                    .iter()
+                    .rev()
+                    .flat_map(|e| e.risks.iter().map(move |r| (e.id, &r.title, &r.severity)))
+                    .take(MAX_SIGNALS)
+                    .collect::<Vec<_>>(),
+            ),
+            (
+                "Blockers",
+                emails
+                    .iter()
+                    .rev()
+                    .flat_map(|e| {
+                        e.blockers
+                            .iter()
+                            .map(move |b| (e.id, &b.title, &b.severity))
+                    })
+                    .take(MAX_SIGNALS)
+                    .collect::<Vec<_>>(),
+            ),
+        ] {
+            let _ = writeln!(text, "\n{}:", heading);
+            if signals.is_empty() {
+                text.push_str("None recorded.\n");
+            }
+            for (id, title, severity) in signals {
+                tagged.push(id);
+                let _ = writeln!(text, "- [#{}] {} ({})", id, title, severity);
+            }
+        }
+
+        text.push_str("\nLatest emails:\n");
+        let latest: Vec<&ProjectEmailFacts> = emails.iter().rev().take(MAX_LISTED_EMAILS).collect();
+        for email in &latest {
+            tagged.push(email.id);
+            write_email(&mut text, email, locale);
+        }
+        Ok(ToolOutput {
+            text,
+            summary: format!(
+                "{}, {} decisions",
+                count(emails.len(), "email"),
+                decisions.len()
+            ),
+            sources: emails
+                .iter()
+                .rev()
+                .filter(|e| tagged.contains(&e.id))
+                .map(source_from_facts)
+                .collect(),
+        })
+    }
+
+    async fn list_tasks(&self, arguments: &Value, locale: UserLocale) -> Result<ToolOutput> {
+        let owner = text_argument(arguments, "owner").map(|o| o.to_lowercase());
+        let now = Utc::now();
+        let tasks: Vec<_> = self
+            .sqlite
+            .list_meeting_follow_ups(now - Duration::days(TASK_LOOKBACK_DAYS), 100)
+            .await?
+            .into_iter()
+            .flat_map(|follow_up| {
+                let title = follow_up.meeting.subject.clone();
+                follow_up.tasks.into_iter().map(move |t| (title.clone(), t))
+            })
+            .filter(|(_, task)| task.kind == MeetingTaskKind::ActionItem && !task.done)
+            .filter(|(_, task)| match &owner {
+                Some(owner) => task
+                    .owner
+                    .as_deref()
+                    .is_some_and(|o| o.to_lowercase().contains(owner)),
+                None => true,
+            })
+            .collect();
+        let due = self
+            .sqlite
+            .get_emails_due_between(now, now + Duration::days(DEADLINE_DAYS))
+            .await?;
+        let task_email_ids: Vec<i64> = tasks.iter().filter_map(|(_, t)| t.email_id).collect();
+        let task_emails = self
+            .sqlite
+            .get_emails_by_ids(
+                task_email_ids.into_iter().map(|id| (id, 0.0)).collect(),
+                &SearchFilter::default(),
+            )
+            .await?;
+
+        let mut text = String::from("Open action items from meetings:\n");
+        if tasks.is_empty() {
+            text.push_str("None.\n");
+        }
+        for (meeting, task) in &tasks {
+            let _ = write!(text, "- {} (from \"{}\"", task.text, meeting);
+            if let Some(owner) = &task.owner {
+                let _ = write!(text, ", owner {}", owner);
+            }
+            if let Some(due_by) = &task.due_by {
+                let _ = write!(text, ", due {}", locale.format_date(due_by));
+            }
+            if let Some(email_id) = task.email_id {
+                let _ = write!(text, ", [#{}]", email_id);
+            }
+            text.push_str(")\n");
+        }
+        text.push_str("\nEmails with deadlines in the next two weeks:\n");
+        if due.is_empty() {
+            text.push_str("None.\n");
+        }
+        for email in &due {
+            let due_by = serde_json::from_value::<DateTime<Utc>>(email["due_by"].clone())
+                .map(|d| locale.format_date(&d))
+                .unwrap_or_default();
+            let _ = writeln!(
+                text,
+                "- [#{}] {} · due {} · {}",
+                email["id"],
+                email["subject"].as_str().unwrap_or_default(),
+                due_by,
+                email["summary"].as_str().unwrap_or_default()
+            );
+        }
+        let mut sources: Vec<TopicSource> = due.iter().filter_map(source_from_result).collect();
+        for source in task_emails.iter().filter_map(source_from_result) {
+            if !sources.iter().any(|s| s.email_id == source.email_id) {
+                sources.push(source);
+            }
+        }
+        Ok(ToolOutput {
+            text,
+            summary: format!(
+                "{}, {}",
+                count(tasks.len(), "action item"),
+                count(due.len(), "deadline")
+            ),
+            sources,
+        })
+    }
+
+    /// The stored name of `project`, matched without case as the model
+    /// may not keep it.
+    async fn project_name(&self, project: &str) -> Result<String> {
+        let names = self.sqlite.list_project_names().await?;
+        names
+            .iter()
+            .find(|name| name.eq_ignore_ascii_case(project))
+            .cloned()
+            .ok_or_else(|| {
+                NoodleError::Validation(format!(
+                    "Unknown project {}; known projects are: {}",
+                    project,
+                    names.join(", ")
+                ))
+            })
+    }
+}
+
+/// A search result as a source an answer can cite.
+pub(super) fn source_from_result(email: &Value) -> Option<TopicSource> {
+    Some(TopicSource {
+        email_id: email["id"].as_i64()?,
+        subject: email["subject"].as_str().unwrap_or_default().to_string(),
+        sender: email["sender"].as_str().unwrap_or_default().to_string(),
+        received_at: serde_json::from_value::<DateTime<Utc>>(email["received_at"].clone())
+            .unwrap_or_default(),
+        summary: email["summary"].as_str().unwrap_or_default().to_string(),
+        cited: false,
+    })
+}
+
+fn source_from_facts(email: &ProjectEmailFacts) -> TopicSource {
+    TopicSource {
+        email_id: email.id,
+        subject: email.subject.clone(),
+        sender: email.sender.clone(),
+        received_at: email.received_at,
+        summary: email.summary.clone(),
+        cited: false,
+    }
+}
+
+fn write_email(text: &mut String, email: &ProjectEmailFacts, locale: UserLocale) {
+    let _ = writeln!(
+        text,
+        "[#{}] {} · {} · {}\n{}",
+        email.id,
+        email.subject,
+        email.sender,
+        locale.format_date(&email.received_at),
+        email.summary
+    );
+    for point in &email.key_points {
+        let _ = writeln!(text, "- {}", point);
+    }
+    text.push('\n');
+}
+
+fn text_argument(arguments: &Value, name: &str) -> Option<String> {
+    arguments[name]
+        .as_str()
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(str::to_string)
+}
+
+fn count(n: usize, noun: &str) -> String {
+    if n == 1 {
+        format!("1 {}", noun)
+    } else {
+        format!("{} {}s", n, noun)
+    }
+}
@@ -0,0 +1,236 @@
+use ai::embedding::cosine;
+use ai::provider::AiProvider;
+use noodle_core::error::Result;
+use noodle_core::types::{TopicSource, UnsupportedCitation};
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use storage::sqlite::SqliteStorage;
+use tokio::sync::RwLock;
+use tracing::warn;
+
+/// Share of a statement's words an email must contain to support it.
+const WORD_SUPPORT: f32 = 0.5;
+/// Similarity between a statement and an email's passage that supports it
+/// when the words differ.
+const EMBEDDING_SUPPORT: f32 = 0.7;
+/// Characters per passage compared by embedding.
+const PASSAGE_CHARS: usize = 1200;
+/// Passages of one email compared by embedding.
+const MAX_PASSAGES: usize = 4;
+/// Words too common to show that an email says the same thing.
+const STOPWORDS: &[&str] = &[
+    "about", "after", "also", "and", "are", "been", "before", "but", "can", "could", "did", "does",
+    "for", "from", "had", "has", "have", "her", "his", "into", "its", "not", "now", "our", "over",
+    "said", "says", "she", "should", "that", "the", "their", "them", "then", "there", "they",
+    "this", "was", "were", "what", "when", "which", "who", "will", "with", "would", "you", "your",
+];
+
+/// Checks each `[#id]` citation of an answer against the email it names:
+/// the email must contain most of the statement's words or, failing that, a
+/// passage whose embedding is close to the statement's. Citations of emails
+/// that weren't among the sources fail outright.
+pub struct CitationVerifier {
+    sqlite: Arc<SqliteStorage>,
+    ai: Arc<RwLock<Arc<dyn AiProvider>>>,
+}
+
+impl CitationVerifier {
+    pub fn new(sqlite: Arc<SqliteStorage>, ai: Arc<RwLock<Arc<dyn AiProvider>>>) -> Self {
+        Self { sqlite, ai }
+    }
+
+    /// The citations in `answer` their email doesn't back up. When
+    /// embeddings are unavailable, only the word check is made.
+    pub async fn verify(
+        &self,
+        answer: &str,
+        sources: &[TopicSource],
+    ) -> Result<Vec<UnsupportedCitation>> {
+        let ai = self.ai.read().await.clone();
+        let mut texts: HashMap<i64, String> = HashMap::new();
+        let mut passages: HashMap<i64, Vec<Vec<f32>>> = HashMap::new();
+        let mut embeddings_failed = false;
+        let mut unsupported = Vec::new();
+
+        for (claim, ids) in cited_claims(answer) {
+            let words = content_words(&claim);
+            let mut claim_embedding: Option<Vec<f32>> = None;
+            for email_id in ids {
+                let Some(source) = sources.iter().find(|s| s.email_id == email_id) else {
+                    unsupported.push(UnsupportedCitation {
+                        claim: claim.clone(),
+                        email_id,
+                        reason: "Not one of the emails the answer was given".into(),
+                    });
+                    continue;
+                };
+                let text = match texts.entry(email_id) {
+                    Entry::Occupied(entry) => entry.into_mut(),
+                    Entry::Vacant(entry) => {
+                        let body = self
+                            .sqlite
+                            .get_email_body(email_id)
+                            .await?
+                            .unwrap_or_default();
+                        entry.insert(format!("{}\n{}\n{}", source.subject, source.summary, body))
+                    }
+                };
+                if word_support(&words, text) >= WORD_SUPPORT {
+                    continue;
+                }
+
+                if !embeddings_failed {
+                    match self
+                        .embedding_support(
+                            ai.as_ref(),
+                            &claim,
+                            &mut claim_embedding,
+                            passages.entry(email_id),
+                            text,
+                        )
+                        .await
+                    {
+                        Ok(similarity) if similarity >= EMBEDDING_SUPPORT => continue,
+                        Ok(_) => {}
+                        Err(e) => {
+                            warn!("Checking citations by embedding failed: {}", e);
+                            embeddings_failed = true;
+                        }
+                    }
+                }
+                unsupported.push(UnsupportedCitation {
+                    claim: claim.clone(),
+                    email_id,
+                    reason: "The email doesn't appear to say this".into(),
+                });
+            }
+        }
+        Ok(unsupported)
+    }
+
+    /// The best similarity between the claim and a passage of the email,
+    /// embedding each at most once per answer.
+    async fn embedding_support(
+        &self,
+        ai: &dyn AiProvider,
+        claim: &str,
+        claim_embedding: &mut Option<Vec<f32>>,
+        passages: Entry<'_, i64, Vec<Vec<f32>>>,
+        text: &str,
+    ) -> Result<f32> {
+        if claim_embedding.is_none() {
+            *claim_embedding = Some(ai.generate_embedding(claim).await?);
+        }
+        let claim_embedding = claim_embedding.as_deref().unwrap_or_default();
+        let passages = match passages {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => {
+                let mut embedded = Vec::new();
+                for passage in split_passages(text) {
+                    embedded.push(ai.generate_embedding(&passage).await?);
+                }
+                entry.insert(embedded)
+            }
+        };
+        Ok(passages
+            .iter()
+            .map(|p| cosine(claim_embedding, p))
+            .fold(0.0, f32::max))
+    }
+}
+
+/// The statements of `text` that cite emails, without their tags, each
+/// with the ids it cites. A statement is a sentence or line; tags right
+/// after its full stop still belong to it.
+pub fn cited_claims(text: &str) -> Vec<(String, Vec<i64>)> {
+    let mut claims: Vec<(String, Vec<i64>)> = Vec::new();
+    for line in text.lines() {
+        let mut claim = String::new();
+        let mut ids: Vec<i64> = Vec::new();
+        let mut rest = line;
+        while !rest.is_empty() {
+            if let Some((id, after)) = parse_tag(rest) {
+                if !ids.contains(&id) {
+                    ids.push(id);
+                }
+                rest = after;
+                if claim.trim_end().ends_with(['.', '!', '?'])
+                    && parse_tag(rest.trim_start()).is_none()
+                {
+                    push_claim(&mut claims, &mut claim, &mut ids);
+                }
+                continue;
+            }
+            let c = rest.chars().next().unwrap_or_default();
+            rest = &rest[c.len_utf8()..];
+            // A sentence ends at a full stop followed by a space, unless
+            // citation tags follow, which are taken first.
+            if matches!(c, '.' | '!' | '?')
+                && rest.starts_with(' ')
+                && parse_tag(rest.trim_start()).is_none()
+            {
+                claim.push(c);
+                push_claim(&mut claims, &mut claim, &mut ids);
+                continue;
+            }
+            claim.push(c);
+        }
+        push_claim(&mut claims, &mut claim, &mut ids);
+    }
+    claims
+}
+
+fn push_claim(claims: &mut Vec<(String, Vec<i64>)>, claim: &mut String, ids: &mut Vec<i64>) {
+    // Removed tags leave a space before the punctuation they preceded.
+    let text = claim
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .replace(" .", ".")
+        .replace(" ,", ",");
+    if !ids.is_empty() && !text.is_empty() {
+        claims.push((text, std::mem::take(ids)));
+    }
+    claim.clear();
+    ids.clear();
+}
+
+/// `[#12]` at the start of `text`, with what follows it.
+pub fn parse_tag(text: &str) -> Option<(i64, &str)> {
+    let rest = text.strip_prefix("[#")?;
+    let end = rest.find(']')?;
+    let id = rest[..end].trim().parse().ok()?;
+    Some((id, &rest[end + 1..]))
+}
+
+pub fn content_words(text: &str) -> HashSet<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .map(str::to_lowercase)
+        .filter(|w| w.chars().count() >= 3 && !STOPWORDS.contains(&w.as_str()))
+        .collect()
+}
+
+/// The share of `words` found in `text`, matching on the first five
+/// characters so "deadline" matches "deadlines".
+pub fn word_support(words: &HashSet<String>, text: &str) -> f32 {
+    if words.is_empty() {
+        return 1.0;
+    }
+    let stems: HashSet<String> = content_words(text).iter().map(|w| stem(w)).collect();
+    let found = words.iter().filter(|w| stems.contains(&stem(w))).count();
+    found as f32 / words.len() as f32
+}
+
+fn stem(word: &str) -> String {
+    word.chars().take(5).collect()
+}
+
+fn split_passages(text: &str) -> Vec<String> {
+    let chars: Vec<char> = text.chars().collect();
+    chars
+        .chunks(PASSAGE_CHARS)
+        .take(MAX_PASSAGES)
+        .map(|chunk| chunk.iter().collect())
+        .collect()
+}
@@ -0,0 +1,392 @@
+use crate::digest::{Digest, DigestReason};
+use noodle_core::error::{NoodleError, Result};
+use noodle_core::types::ChatFormat;
+use serde_json::{json, Value};
+use std::fmt::Write as _;
+use std::sync::Arc;
+use storage::sqlite::{ProjectEmailFacts, SqliteStorage};
+
+/// Emails of a thread listed; older ones are only counted.
+const MAX_THREAD_EMAILS: usize = 15;
+/// Slack rejects longer header and section texts.
+const SLACK_HEADER_CHARS: usize = 150;
+const SLACK_SECTION_CHARS: usize = 3000;
+
+/// A summary ready to be rendered for a chat tool.
+pub struct SharedSummary {
+    pub title: String,
+    /// Who, when and which project, in one line.
+    pub subtitle: Option<String>,
+    pub sections: Vec<Section>,
+}
+
+pub struct Section {
+    pub heading: Option<String>,
+    pub text: Option<String>,
+    pub items: Vec<Item>,
+}
+
+/// A bullet: a bold lead and what follows it.
+pub struct Item {
+    pub title: String,
+    pub detail: Option<String>,
+}
+
+impl Item {
+    fn new(title: impl Into<String>, detail: Option<String>) -> Self {
+        Self {
+            title: title.into(),
+            detail: detail.filter(|d| !d.trim().is_empty()),
+        }
+    }
+}
+
+impl SharedSummary {
+    pub fn render(&self, format: ChatFormat) -> String {
+        match format {
+            ChatFormat::Markdown => self.to_markdown(),
+            ChatFormat::Slack => pretty(&self.to_slack_blocks()),
+            ChatFormat::Teams => pretty(&self.to_teams_message()),
+        }
+    }
+
+    fn to_markdown(&self) -> String {
+        let mut out = format!("**{}**\n", self.title);
+        if let Some(subtitle) = &self.subtitle {
+            let _ = writeln!(out, "_{}_", subtitle);
+        }
+        for section in &self.sections {
+            out.push('\n');
+            if let Some(heading) = &section.heading {
+                let _ = writeln!(out, "### {}", heading);
+            }
+            if let Some(text) = &section.text {
+                let _ = writeln!(out, "{}", text);
+            }
+            for item in &section.items {
+                let _ = writeln!(out, "- {}", item_line(item, "**"));
+            }
+        }
+        out
+    }
+
+    fn to_slack_blocks(&self) -> Value {
+        let mut blocks = vec![json!({
+            "type": "header",
+            "text": {
+                "type": "plain_text",
+                "text": truncate(&self.title, SLACK_HEADER_CHARS),
+            },
+        })];
+        if let Some(subtitle) = &self.subtitle {
+            blocks.push(json!({
+                "type": "context",
+                "elements": [{ "type": "mrkdwn", "text": slack_escape(subtitle) }],
+            }));
+        }
+        for section in &self.sections {
+            let mut lines = Vec::new();
+            if let Some(heading) = &section.heading {
+                lines.push(format!("*{}*", slack_escape(heading)));
+            }
+            if let Some(text) = &section.text {
+                lines.push(slack_escape(text));
+            }
+            for item in &section.items {
+                let item = Item::new(
+                    slack_escape(&item.title),
+                    item.detail.as_deref().map(slack_escape),
+                );
+                lines.push(format!("• {}", item_line(&item, "*")));
+            }
+            blocks.push(json!({
+                "type": "section",
+                "text": {
+                    "type": "mrkdwn",
+                    "text": truncate(&lines.join("\n"), SLACK_SECTION_CHARS),
+                },
+            }));
+        }
+        // `text` is what notifications show.
+        json!({ "text": self.title, "blocks": blocks })
+    }
+
+    fn to_teams_message(&self) -> Value {
+        let mut body = vec![json!({
+            "type": "TextBlock",
+            "text": self.title,
+            "size": "Large",
+            "weight": "Bolder",
+            "wrap": true,
+        })];
+        if let Some(subtitle) = &self.subtitle {
+            body.push(json!({
+                "type": "TextBlock",
+                "text": subtitle,
+                "isSubtle": true,
+                "spacing": "None",
+                "wrap": true,
+            }));
+        }
+        for section in &self.sections {
+            if let Some(heading) = &section.heading {
+                body.push(json!({
+                    "type": "TextBlock",
+                    "text": heading,
+                    "weight": "Bolder",
+                    "separator": true,
+                    "wrap": true,
+                }));
+            }
+            if let Some(text) = &section.text {
+                body.push(json!({ "type": "TextBlock", "text": text, "wrap": true }));
+            }
+            if !section.items.is_empty() {
+                // Adaptive Cards render markdown lists separated by `\r`.
+                let list: Vec<String> = section
+                    .items
+                    .iter()
+                    .map(|item| format!("- {}", item_line(item, "**")))
+                    .collect();
+                body.push(json!({ "type": "TextBlock", "text": list.join("\r"), "wrap": true }));
+            }
+        }
+        json!({
+            "type": "message",
+            "attachments": [{
+                "contentType": "application/vnd.microsoft.card.adaptive",
+                "content": {
+                    "$schema": "http://adaptivecards.io/schemas/adaptive-card.json",
+                    "type": "AdaptiveCard",
+                    "version": "1.4",
+                    "body": body,
+                },
+            }],
+        })
+    }
+}
+
+/// Turns email summaries, threads and digests into [`SharedSummary`]s, with
+/// dates in the user's timezone and locale.
+pub struct ChatFormatter {
+    sqlite: Arc<SqliteStorage>,
+}
+
+impl ChatFormatter {
+    pub fn new(sqlite: Arc<SqliteStorage>) -> Self {
+        Self { sqlite }
+    }
+
+    pub async fn email(&self, id: i64) -> Result<SharedSummary> {
+        let emails = self.sqlite.get_thread_email_facts(id).await?;
+        let email = emails
+            .iter()
+            .find(|e| e.id == id)
+            .ok_or_else(|| NoodleError::Validation("Email has no extracted summary".to_string()))?;
+        let date = self.dates().await?;
+
+        let mut subtitle = vec![email.sender.clone(), date(email.received_at)];
+        subtitle.extend(email.project.clone());
+        let mut sections = vec![Section {
+            heading: None,
+            text: Some(email.summary.clone()),
+            items: Vec::new(),
+        }];
+        if !email.key_points.is_empty() {
+            sections.push(Section {
+                heading: Some("Key points".to_string()),
+                text: None,
+                items: email
+                    .key_points
+                    .iter()
+                    .map(|p| Item::new(p.clone(), None))
+                    .collect(),
+            });
+        }
+        let concerns = concerns(email);
+        if !concerns.is_empty() {
+            sections.push(Section {
+                heading: Some("Risks and blockers".to_string()),
+                text: None,
+                items: concerns,
+            });
+        }
+        if let Some(due_by) = email.due_by {
+            sections.push(Section {
+                heading: None,
+                text: Some(format!("Due {}", date(due_by))),
+                items: Vec::new(),
+            });
+        }
+        Ok(SharedSummary {
+            title: email.subject.clone(),
+            subtitle: Some(subtitle.join(" · ")),
+            sections,
+        })
+    }
+
+    /// The conversation `email_id` belongs to, one entry per email, newest
+    /// last, with the latest email's open risks and blockers.
+    pub async fn thread(&self, email_id: i64) -> Result<SharedSummary> {
+        let emails = self.sqlite.get_thread_email_facts(email_id).await?;
+        let (Some(first), Some(last)) = (emails.first(), emails.last()) else {
+            return Err(NoodleError::Validation(
+                "Thread has no extracted summaries".to_string(),
+            ));
+        };
+        let date = self.dates().await?;
+
+        let mut subtitle = vec![
+            format!("{} emails", emails.len()),
+            format!("{} – {}", date(first.received_at), date(last.received_at)),
+        ];
+        subtitle.extend(last.project.clone());
+        let skipped = emails.len().saturating_sub(MAX_THREAD_EMAILS);
+        let mut sections = Vec::new();
+        if skipped > 0 {
+            sections.push(Section {
+                heading: None,
+                text: Some(format!("{} earlier emails not shown.", skipped)),
+                items: Vec::new(),
+            });
+        }
+        sections.extend(emails[skipped..].iter().map(|e| Section {
+            heading: Some(format!("{} · {}", date(e.received_at), e.sender)),
+            text: Some(e.summary.clone()),
+            items: Vec::new(),
+        }));
+        let concerns = concerns(last);
+        if !concerns.is_empty() {
+            sections.push(Section {
+                heading: Some("Open risks and blockers".to_string()),
+                text: None,
+                items: concerns,
+            });
+        }
+        Ok(SharedSummary {
+            title: first.subject.clone(),
+            subtitle: Some(subtitle.join(" · ")),
+            sections,
+        })
+    }
+
+    pub async fn digest(&self, digest: &Digest) -> Result<SharedSummary> {
+        let date = self.dates().await?;
+        let mut subtitle = format!(
+            "{} urgent emails waiting for a reply",
+            digest.urgent_unhandled
+        );
+        if let Some(away_since) = digest.away_since {
+            let _ = write!(
+                subtitle,
+                " · away since {}, {} emails received",
+                date(away_since),
+                digest.received_while_away
+            );
+        }
+
+        let mut sections: Vec<Section> = Vec::new();
+        let mut current = None;
+        for item in &digest.items {
+            if current != Some(item.reason) {
+                current = Some(item.reason);
+                sections.push(Section {
+                    heading: Some(reason_heading(item.reason).to_string()),
+                    text: None,
+                    items: Vec::new(),
+                });
+            }
+            let mut detail = item.sender.clone();
+            if let Some(project) = &item.project {
+                let _ = write!(detail, " ({})", project);
+            }
+            if let Some(due_by) = item.due_by {
+                let _ = write!(detail, ", due {}", date(due_by));
+            }
+            if let Some(summary) = &item.summary {
+                let _ = write!(detail, ": {}", summary);
+            }
+            if let Some(section) = sections.last_mut() {
+                section
+                    .items
+                    .push(Item::new(item.subject.clone(), Some(detail)));
+            }
+        }
+        if sections.is_empty() {
+            sections.push(Section {
+                heading: None,
+                text: Some("Nothing due today and no urgent mail waiting.".to_string()),
+                items: Vec::new(),
+            });
+        }
+        Ok(SharedSummary {
+            title: format!("Digest for {}", date(digest.generated_at)),
+            subtitle: Some(subtitle),
+            sections,
+        })
+    }
+
+    /// Formats instants as dates in the user's timezone and locale.
+    async fn dates(&self) -> Result<impl Fn(chrono::DateTime<chrono::Utc>) -> String> {
+        let tz = self.sqlite.get_user_timezone().await?;
+        let locale = self.sqlite.get_user_locale().await?;
+        Ok(move |instant| locale.format_date(&tz.to_local(instant)))
+    }
+}
+
+fn reason_heading(reason: DigestReason) -> &'static str {
+    match reason {
+        DigestReason::DueToday => "Due today",
+        DigestReason::Missed => "Missed while away",
+        DigestReason::UrgentUnanswered => "Urgent and unanswered",
+    }
+}
+
+/// An email's risks and blockers, as bullets.
+fn concerns(email: &ProjectEmailFacts) -> Vec<Item> {
+    let risks = email
+        .risks
+        .iter()
+        .map(|r| (&r.title, &r.severity, &r.details));
+    let blockers = email
+        .blockers
+        .iter()
+        .map(|b| (&b.title, &b.severity, &b.details));
+    risks
+        .chain(blockers)
+        .map(|(title, severity, details)| {
+            Item::new(
+                format!("{} ({})", title, severity),
+                Some(details.to_string()),
+            )
+        })
+        .collect()
+}
+
+/// `**title** — detail`, with `bold` as the emphasis marker.
+fn item_line(item: &Item, bold: &str) -> String {
+    match &item.detail {
+        Some(detail) => format!("{}{}{} — {}", bold, item.title, bold, detail),
+        None => item.title.clone(),
+    }
+}
+
+/// Slack's mrkdwn treats these three as control characters.
+fn slack_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+fn truncate(text: &str, max_chars: usize) -> String {
+    if text.chars().count() <= max_chars {
+        return text.to_string();
+    }
+    let mut out: String = text.chars().take(max_chars - 1).collect();
+    out.push('…');
+    out
+}
+
+fn pretty(value: &Value) -> String {
+    serde_json::to_string_pretty(value).unwrap_or_default()
+}
@@ -0,0 +1,135 @@
+use noodle_core::error::Result;
+use noodle_core::types::{Blocker, DateRange, TimelineEvent, TimelineEventKind};
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use storage::sqlite::{ProjectEmailFacts, SqliteStorage};
+
+#[derive(Default)]
+struct Thread {
+    /// Blockers raised in the thread and not resolved yet, by normalized title.
+    open_blockers: HashMap<String, Blocker>,
+    due_by: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+pub struct TimelineService {
+    sqlite: Arc<SqliteStorage>,
+}
+
+impl TimelineService {
+    pub fn new(sqlite: Arc<SqliteStorage>) -> Self {
+        Self { sqlite }
+    }
+
+    /// A project's history in `range`, oldest first; see [`timeline`].
+    pub async fn build(&self, project: &str, range: &DateRange) -> Result<Vec<TimelineEvent>> {
+        let emails = self
+            .sqlite
+            .get_project_email_facts(project, range.end)
+            .await?;
+        Ok(timeline(&emails, range))
+    }
+}
+
+/// The history in `range` of a project's emails, given oldest first: every
+/// email with its summary, decisions, risks raised for the first time,
+/// blockers resolved and deadlines moved. Emails before the range count
+/// too, so a risk or deadline already known is not reported as new.
+pub fn timeline(emails: &[ProjectEmailFacts], range: &DateRange) -> Vec<TimelineEvent> {
+    let mut seen_risks: HashSet<String> = HashSet::new();
+    let mut threads: HashMap<String, Thread> = HashMap::new();
+    let mut events = Vec::new();
+    for email in emails {
+        let mut found = Vec::new();
+        let event = |kind: TimelineEventKind, title: String| TimelineEvent {
+            at: email.received_at,
+            kind,
+            email_id: email.id,
+            title,
+            detail: None,
+            severity: None,
+            due_by: None,
+            previous_due_by: None,
+        };
+
+        found.push(TimelineEvent {
+            detail: Some(email.summary.clone()),
+            ..event(TimelineEventKind::Email, email.subject.clone())
+        });
+        if email.primary_type == "decision" {
+            found.push(TimelineEvent {
+                detail: Some(email.summary.clone()),
+                ..event(TimelineEventKind::Decision, email.subject.clone())
+            });
+        }
+        for risk in &email.risks {
+            if seen_risks.insert(normalize(&risk.title)) {
+                found.push(TimelineEvent {
+                    detail: Some(risk.details.to_string()),
+                    severity: Some(risk.severity.clone()),
+                    ..event(TimelineEventKind::RiskRaised, risk.title.to_string())
+                });
+            }
+        }
+
+        // Blockers and deadlines are followed per thread; an email outside
+        // any conversation starts its own.
+        let key = email
+            .conversation_id
+            .clone()
+            .unwrap_or_else(|| format!("email:{}", email.id));
+        let thread = threads.entry(key).or_default();
+        if email.intent == "resolve" {
+            for blocker in resolved_blockers(thread, &email.blockers) {
+                found.push(TimelineEvent {
+                    detail: Some(blocker.details.into_string()),
+                    severity: Some(blocker.severity),
+                    ..event(
+                        TimelineEventKind::BlockerResolved,
+                        blocker.title.into_string(),
+                    )
+                });
+            }
+        } else {
+            for blocker in &email.blockers {
+                thread
+                    .open_blockers
+                    .entry(normalize(&blocker.title))
+                    .or_insert_with(|| blocker.clone());
+            }
+        }
+        if let Some(due_by) = email.due_by {
+            if thread.due_by != Some(due_by) {
+                found.push(TimelineEvent {
+                    due_by: Some(due_by),
+                    previous_due_by: thread.due_by,
+                    ..event(TimelineEventKind::DeadlineChanged, email.subject.clone())
+                });
+                thread.due_by = Some(due_by);
+            }
+        }
+
+        if range.start.is_none_or(|start| email.received_at >= start)
+            && range.end.is_none_or(|end| email.received_at < end)
+        {
+            events.extend(found);
+        }
+    }
+    events
+}
+
+/// Takes the open blockers of the thread a resolving email is about: those
+/// it names, or the only one open when it names none it could match.
+fn resolved_blockers(thread: &mut Thread, named: &[Blocker]) -> Vec<Blocker> {
+    let mut resolved: Vec<Blocker> = named
+        .iter()
+        .filter_map(|b| thread.open_blockers.remove(&normalize(&b.title)))
+        .collect();
+    if resolved.is_empty() && thread.open_blockers.len() == 1 {
+        resolved.extend(thread.open_blockers.drain().map(|(_, b)| b));
+    }
+    resolved
+}
+
+fn normalize(title: &str) -> String {
+    title.trim().to_lowercase()
+}
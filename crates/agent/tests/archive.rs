@@ -0,0 +1,67 @@
+use agent::archive::{self, ArchiveEntry};
+
+const PASSWORD: &str = "correct horse battery";
+/// Where the header's round count starts: after the magic and version.
+const ITERATIONS_AT: usize = 8;
+
+fn entries() -> Vec<ArchiveEntry> {
+    vec![ArchiveEntry {
+        name: "report.md".into(),
+        content: "# Apollo\n\nShipping Friday.".into(),
+    }]
+}
+
+#[tokio::test]
+async fn sealed_archive_opens_with_its_password() {
+    let sealed = archive::seal(&entries(), PASSWORD).await.unwrap();
+    let opened = archive::open(&sealed, PASSWORD).await.unwrap();
+    assert_eq!(opened.len(), 1);
+    assert_eq!(opened[0].name, "report.md");
+    assert_eq!(opened[0].content, "# Apollo\n\nShipping Friday.");
+
+    let (file_name, encoded) = archive::seal_file("graph.json", "{}".into(), PASSWORD)
+        .await
+        .unwrap();
+    assert_eq!(file_name, "graph.json.noodlex");
+    let opened = archive::open_base64(&encoded, PASSWORD).await.unwrap();
+    assert_eq!(opened[0].content, "{}");
+
+    assert!(archive::seal(&entries(), "short").await.is_err());
+}
+
+#[tokio::test]
+async fn wrong_password_does_not_open_an_archive() {
+    let sealed = archive::seal(&entries(), PASSWORD).await.unwrap();
+    assert!(archive::open(&sealed, "correct horse battery!")
+        .await
+        .is_err());
+}
+
+#[tokio::test]
+async fn tampered_archives_are_rejected() {
+    let sealed = archive::seal(&entries(), PASSWORD).await.unwrap();
+
+    // The header is authenticated along with the contents.
+    let mut header = sealed.clone();
+    header[ITERATIONS_AT + 4] ^= 1;
+    assert!(archive::open(&header, PASSWORD).await.is_err());
+
+    let mut ciphertext = sealed.clone();
+    let last = ciphertext.len() - 1;
+    ciphertext[last] ^= 1;
+    assert!(archive::open(&ciphertext, PASSWORD).await.is_err());
+
+    assert!(archive::open(&sealed[..20], PASSWORD).await.is_err());
+    assert!(archive::open(b"not an archive at all", PASSWORD)
+        .await
+        .is_err());
+}
+
+#[tokio::test]
+async fn oversized_round_counts_are_refused_before_deriving() {
+    let mut sealed = archive::seal(&entries(), PASSWORD).await.unwrap();
+    sealed[ITERATIONS_AT..ITERATIONS_AT + 4].copy_from_slice(&u32::MAX.to_be_bytes());
+    let started = std::time::Instant::now();
+    assert!(archive::open(&sealed, PASSWORD).await.is_err());
+    assert!(started.elapsed() < std::time::Duration::from_secs(5));
+}
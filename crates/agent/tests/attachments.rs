@@ -0,0 +1,122 @@
+use agent::pipeline::attachments::{chunk_text, content_path, keep, remove_unlisted};
+use std::collections::HashSet;
+use std::fs::File;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+fn words(range: std::ops::Range<usize>) -> String {
+    range
+        .map(|i| format!("w{}", i))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[test]
+fn short_text_is_one_chunk_with_whitespace_collapsed() {
+    assert_eq!(chunk_text("alpha\n\n  beta\tgamma"), ["alpha beta gamma"]);
+    assert!(chunk_text("  \n ").is_empty());
+}
+
+#[test]
+fn chunks_repeat_the_end_of_the_one_before() {
+    let chunks = chunk_text(&words(0..360));
+    assert_eq!(chunks, [words(0..200), words(160..360)]);
+
+    // Exactly one chunk's worth isn't followed by an overlap-only chunk.
+    assert_eq!(chunk_text(&words(0..200)), [words(0..200)]);
+}
+
+#[test]
+fn long_text_is_cut_off_after_the_last_chunk() {
+    let chunks = chunk_text(&words(0..100_000));
+    assert_eq!(chunks.len(), 32);
+    assert_eq!(chunks[31], words(31 * 160..31 * 160 + 200));
+}
+
+fn saved(dir: &Path, name: &str, content: &str) -> PathBuf {
+    let path = dir.join(name);
+    std::fs::write(&path, content).unwrap();
+    path
+}
+
+fn age(path: &Path, by: Duration) {
+    File::options()
+        .write(true)
+        .open(path)
+        .unwrap()
+        .set_modified(SystemTime::now() - by)
+        .unwrap();
+}
+
+#[test]
+fn content_is_kept_once_under_its_hash() {
+    let dir = tempfile::tempdir().unwrap();
+    let (hash, text) = keep(
+        dir.path(),
+        &saved(dir.path(), "a.txt", "Ship Friday"),
+        "text/plain",
+    )
+    .unwrap();
+    assert_eq!(text.as_deref(), Some("Ship Friday"));
+    let path = content_path(dir.path(), &hash);
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), "Ship Friday");
+    assert!(!dir.path().join("a.txt").exists());
+
+    // The same content again is dropped and counts as new.
+    age(&path, Duration::from_secs(3600 * 2));
+    let copy = saved(dir.path(), "b.txt", "Ship Friday");
+    let (again, _) = keep(dir.path(), &copy, "text/plain").unwrap();
+    assert_eq!(again, hash);
+    assert!(!copy.exists());
+    let modified = std::fs::metadata(&path).unwrap().modified().unwrap();
+    assert!(modified.elapsed().unwrap() < Duration::from_secs(60));
+}
+
+#[test]
+fn only_text_types_are_extracted() {
+    let dir = tempfile::tempdir().unwrap();
+    let json = saved(dir.path(), "a.json", "{}");
+    assert_eq!(
+        keep(dir.path(), &json, "application/json")
+            .unwrap()
+            .1
+            .as_deref(),
+        Some("{}")
+    );
+    let pdf = saved(dir.path(), "a.pdf", "%PDF");
+    assert_eq!(keep(dir.path(), &pdf, "application/pdf").unwrap().1, None);
+}
+
+#[test]
+fn unlisted_content_is_removed_after_the_grace_period() {
+    let dir = tempfile::tempdir().unwrap();
+    let mut hashes = Vec::new();
+    for content in ["listed", "unlisted", "recent"] {
+        let file = saved(dir.path(), content, content);
+        hashes.push(keep(dir.path(), &file, "text/plain").unwrap().0);
+    }
+    let [listed, unlisted, recent] = [0, 1, 2].map(|i| content_path(dir.path(), &hashes[i]));
+    age(&listed, Duration::from_secs(3600 * 2));
+    age(&unlisted, Duration::from_secs(3600 * 2));
+
+    let removed = remove_unlisted(dir.path(), &HashSet::from([hashes[0].clone()]));
+    assert_eq!(removed, 1);
+    assert!(listed.exists());
+    assert!(!unlisted.exists());
+    assert!(recent.exists());
+}
+
+#[test]
+fn leftovers_of_interrupted_saves_are_removed_after_a_day() {
+    let dir = tempfile::tempdir().unwrap();
+    let incoming = dir.path().join("incoming");
+    std::fs::create_dir(&incoming).unwrap();
+    let old = saved(&incoming, "old.pdf", "%PDF");
+    let new = saved(&incoming, "new.pdf", "%PDF");
+    age(&old, Duration::from_secs(3600 * 25));
+    age(&new, Duration::from_secs(3600 * 2));
+
+    assert_eq!(remove_unlisted(dir.path(), &HashSet::new()), 1);
+    assert!(!old.exists());
+    assert!(new.exists());
+}
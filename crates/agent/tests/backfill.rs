@@ -0,0 +1,82 @@
+use agent::engine::backfill::BackfillProgress;
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use noodle_core::types::ScanCheckpoint;
+
+fn at(hour: u32) -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(2025, 5, 20, hour, 0, 0).unwrap()
+}
+
+fn progress() -> BackfillProgress {
+    BackfillProgress::new(
+        ScanCheckpoint {
+            folder: "Inbox".into(),
+            last_received_at: DateTime::<Utc>::MIN_UTC,
+            processed: 0,
+            total: 5,
+        },
+        at(12),
+    )
+}
+
+#[test]
+fn the_checkpoint_follows_emails_in_scan_order() {
+    let mut progress = progress();
+    progress.processed(at(1), true);
+    progress.processed(at(2), true);
+
+    assert_eq!(progress.checkpoint.last_received_at, at(2));
+    assert_eq!(progress.checkpoint.processed, 2);
+    assert_eq!(progress.delta_checkpoint(), Some(at(12)));
+}
+
+#[test]
+fn the_checkpoint_stays_before_the_first_failure() {
+    let mut progress = progress();
+    progress.processed(at(1), true);
+    progress.processed(at(2), false);
+    progress.processed(at(3), true);
+
+    assert_eq!(progress.checkpoint.last_received_at, at(1));
+    assert_eq!(progress.checkpoint.processed, 3);
+    // Delta scans retry from the failed email.
+    assert_eq!(progress.delta_checkpoint(), Some(at(2)));
+}
+
+#[test]
+fn out_of_order_emails_only_count() {
+    let mut progress = progress();
+    progress.processed_out_of_order(at(5), true);
+    assert_eq!(
+        progress.checkpoint.last_received_at,
+        DateTime::<Utc>::MIN_UTC
+    );
+    assert_eq!(progress.checkpoint.processed, 1);
+
+    progress.processed_out_of_order(at(4), false);
+    progress.processed(at(1), true);
+    assert_eq!(
+        progress.checkpoint.last_received_at,
+        DateTime::<Utc>::MIN_UTC
+    );
+    assert_eq!(progress.delta_checkpoint(), Some(at(4)));
+}
+
+#[test]
+fn a_failed_fetch_leaves_the_delta_checkpoint_alone() {
+    let mut progress = progress();
+    progress.processed(at(1), true);
+    progress.fetch_failed();
+    progress.processed(at(3), true);
+
+    assert_eq!(progress.checkpoint.last_received_at, at(1));
+    assert_eq!(progress.checkpoint.processed, 3);
+    assert_eq!(progress.delta_checkpoint(), None);
+}
+
+#[test]
+fn the_delta_checkpoint_never_passes_the_listing() {
+    let mut progress = progress();
+    // Received after the folder was listed, e.g. by a clock skew.
+    progress.processed(at(12) + Duration::hours(1), false);
+    assert_eq!(progress.delta_checkpoint(), Some(at(12)));
+}
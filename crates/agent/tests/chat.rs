@@ -0,0 +1,84 @@
+use agent::search::chat::{next_move, parse_step, ResearchMove};
+use serde_json::json;
+
+#[test]
+fn steps_are_found_in_prose_and_code_fences() {
+    let step =
+        parse_step(r#"{"tool": "search_emails", "arguments": {"query": "budget"}}"#).unwrap();
+    assert_eq!(step.tool.as_deref(), Some("search_emails"));
+    assert_eq!(step.arguments, json!({"query": "budget"}));
+
+    let step = parse_step("Sure:\n```json\n{\"answer\": \"Approved [#4].\"}\n```").unwrap();
+    assert_eq!(step.answer.as_deref(), Some("Approved [#4]."));
+    assert!(step.tool.is_none());
+}
+
+#[test]
+fn replies_without_a_json_object_are_not_steps() {
+    assert!(parse_step("The budget was approved [#4].").is_none());
+    assert!(parse_step("{\"tool\": ").is_none());
+    assert!(parse_step("} backwards {").is_none());
+}
+
+#[test]
+fn an_answer_ends_the_research() {
+    assert_eq!(
+        next_move(r#"{"answer": "Approved [#4]."}"#, false, false),
+        ResearchMove::Answer("Approved [#4].".into())
+    );
+    // Even after the tool calls are used up or a retry.
+    assert_eq!(
+        next_move(r#"{"answer": "Approved."}"#, true, true),
+        ResearchMove::Answer("Approved.".into())
+    );
+}
+
+#[test]
+fn tool_calls_run_until_the_steps_are_used_up() {
+    let reply = r#"{"tool": "list_tasks", "arguments": {}}"#;
+    assert_eq!(
+        next_move(reply, false, false),
+        ResearchMove::Call {
+            tool: "list_tasks".into(),
+            arguments: json!({}),
+        }
+    );
+    assert_eq!(next_move(reply, true, false), ResearchMove::Retry);
+    assert_eq!(next_move(reply, true, true), ResearchMove::GiveUp);
+}
+
+#[test]
+fn prose_is_the_answer_even_with_braces() {
+    assert_eq!(
+        next_move("Nothing was decided yet.", false, false),
+        ResearchMove::Answer("Nothing was decided yet.".into())
+    );
+    let reply = "The template uses {name} and {date} placeholders [#7].";
+    assert_eq!(
+        next_move(reply, false, false),
+        ResearchMove::Answer(reply.into())
+    );
+}
+
+#[test]
+fn broken_steps_are_asked_again_once() {
+    for reply in [
+        "{\"answer\": \"unterminated",
+        "```json\n{\"tool\": search}\n```",
+        "Calling {\"tool\": \"search_emails\", oops}",
+        "{}",
+    ] {
+        assert_eq!(
+            next_move(reply, false, false),
+            ResearchMove::Retry,
+            "{}",
+            reply
+        );
+        assert_eq!(
+            next_move(reply, false, true),
+            ResearchMove::GiveUp,
+            "{}",
+            reply
+        );
+    }
+}
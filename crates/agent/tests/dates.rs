@@ -0,0 +1,81 @@
+use agent::pipeline::dates::normalize_due_date;
+use chrono::{DateTime, FixedOffset, TimeZone, Utc};
+
+/// Wednesday 11 June 2025, 10:00 at UTC+2.
+fn anchor() -> DateTime<FixedOffset> {
+    FixedOffset::east_opt(2 * 3600)
+        .unwrap()
+        .with_ymd_and_hms(2025, 6, 11, 10, 0, 0)
+        .unwrap()
+}
+
+/// `day` of June 2025 at `hour` local time, in UTC.
+fn june(day: u32, hour: u32) -> Option<DateTime<Utc>> {
+    Some(Utc.with_ymd_and_hms(2025, 6, day, hour - 2, 0, 0).unwrap())
+}
+
+fn due(raw: &str) -> Option<DateTime<Utc>> {
+    normalize_due_date(raw, anchor())
+}
+
+#[test]
+fn end_of_day_phrases_resolve_to_close_of_business_today() {
+    assert_eq!(due("EOD"), june(11, 17));
+    assert_eq!(due("by end of day"), june(11, 17));
+    assert_eq!(due("today at noon"), june(11, 12));
+    assert_eq!(
+        due("tomorrow 3:30pm").unwrap().to_rfc3339(),
+        "2025-06-12T13:30:00+00:00"
+    );
+}
+
+#[test]
+fn next_week_starts_on_the_following_monday() {
+    assert_eq!(due("next week"), june(16, 17));
+    assert_eq!(due("end of this week"), june(13, 17));
+    assert_eq!(due("in 2 weeks"), june(25, 17));
+}
+
+#[test]
+fn weekdays_roll_over_to_the_coming_one() {
+    assert_eq!(due("Wednesday"), june(11, 17));
+    assert_eq!(due("Monday"), june(16, 17));
+    assert_eq!(due("EOD Friday"), june(13, 17));
+    assert_eq!(due("by Fri 3pm"), june(13, 15));
+    // Friday is still this week, so "next" skips a week; Monday is not.
+    assert_eq!(due("next Friday"), june(20, 17));
+    assert_eq!(due("next Mon"), june(16, 17));
+
+    let new_year = FixedOffset::east_opt(0)
+        .unwrap()
+        .with_ymd_and_hms(2025, 12, 31, 9, 0, 0)
+        .unwrap();
+    assert_eq!(
+        normalize_due_date("Friday", new_year),
+        Some(Utc.with_ymd_and_hms(2026, 1, 2, 17, 0, 0).unwrap())
+    );
+}
+
+#[test]
+fn timestamps_pass_through() {
+    assert_eq!(
+        due("2025-06-20T09:00:00Z"),
+        Some(Utc.with_ymd_and_hms(2025, 6, 20, 9, 0, 0).unwrap())
+    );
+    assert_eq!(due("2025-06-20"), june(20, 17));
+}
+
+#[test]
+fn words_that_only_look_like_deadlines_do_not_match() {
+    for raw in [
+        "",
+        "   ",
+        "whenever you can",
+        "as soon as the sun comes out",
+        "sat on it for a while",
+        "mon ami",
+        "wed",
+    ] {
+        assert_eq!(due(raw), None, "{:?}", raw);
+    }
+}
@@ -0,0 +1,127 @@
+use agent::pipeline::experiments::{compare, disagreements, stats};
+use noodle_core::types::{ExperimentEmail, ExperimentOutcome, ExperimentVariant};
+use serde_json::json;
+use std::collections::BTreeMap;
+
+fn outcome(urgency: &str, confidence: f32) -> ExperimentOutcome {
+    ExperimentOutcome {
+        fields: BTreeMap::from([
+            ("urgency".to_string(), json!(urgency)),
+            ("project".to_string(), json!("apollo")),
+        ]),
+        confidence: Some(confidence),
+        prompt_tokens: 1_000,
+        completion_tokens: 200,
+        duration_ms: 400,
+        error: None,
+    }
+}
+
+fn failed() -> ExperimentOutcome {
+    ExperimentOutcome {
+        prompt_tokens: 1_000,
+        duration_ms: 100,
+        error: Some("invalid JSON".into()),
+        ..Default::default()
+    }
+}
+
+fn email(email_id: i64, a: ExperimentOutcome, b: ExperimentOutcome) -> ExperimentEmail {
+    ExperimentEmail {
+        email_id,
+        subject: format!("Email {}", email_id),
+        disagreements: disagreements(&a, &b),
+        a,
+        b,
+    }
+}
+
+#[test]
+fn outcomes_disagree_on_the_fields_that_differ() {
+    assert_eq!(
+        disagreements(&outcome("high", 0.9), &outcome("low", 0.9)),
+        vec!["urgency"]
+    );
+    assert!(disagreements(&outcome("high", 0.9), &outcome("high", 0.5)).is_empty());
+    // A failed extraction has nothing to disagree on.
+    assert!(disagreements(&outcome("high", 0.9), &failed()).is_empty());
+}
+
+#[test]
+fn agreement_is_measured_over_emails_both_variants_extracted() {
+    let emails = [
+        email(1, outcome("high", 0.9), outcome("high", 0.7)),
+        email(2, outcome("high", 0.9), outcome("low", 0.7)),
+        email(3, failed(), outcome("low", 0.7)),
+    ];
+    let comparison = compare(
+        &emails,
+        &ExperimentVariant::default(),
+        &ExperimentVariant::default(),
+    );
+
+    assert_eq!(comparison.compared, 2);
+    assert_eq!(comparison.field_agreement["urgency"], 0.5);
+    assert_eq!(comparison.field_agreement["project"], 1.0);
+    // One urgency disagreement out of every field of both emails.
+    let fields = comparison.field_agreement.len() as f32;
+    assert!((comparison.agreement - (2.0 * fields - 1.0) / (2.0 * fields)).abs() < 1e-6);
+
+    assert_eq!((comparison.a.succeeded, comparison.a.failed), (2, 1));
+    assert_eq!((comparison.b.succeeded, comparison.b.failed), (3, 0));
+}
+
+#[test]
+fn nothing_compared_is_no_agreement() {
+    let emails = [email(1, failed(), outcome("high", 0.9))];
+    let comparison = compare(
+        &emails,
+        &ExperimentVariant::default(),
+        &ExperimentVariant::default(),
+    );
+    assert_eq!(comparison.compared, 0);
+    assert_eq!(comparison.agreement, 0.0);
+    assert!(comparison.field_agreement.is_empty());
+
+    let comparison = compare(
+        &[],
+        &ExperimentVariant::default(),
+        &ExperimentVariant::default(),
+    );
+    assert_eq!(comparison.a.mean_duration_ms, 0);
+    assert_eq!(comparison.a.mean_confidence, 0.0);
+}
+
+#[test]
+fn failures_count_toward_tokens_and_time_but_not_confidence() {
+    let outcomes = [outcome("high", 0.9), outcome("low", 0.5), failed()];
+    let stats = stats(outcomes.iter(), &ExperimentVariant::default());
+
+    assert_eq!((stats.succeeded, stats.failed), (2, 1));
+    assert!((stats.mean_confidence - 0.7).abs() < 1e-6);
+    assert_eq!(stats.prompt_tokens, 3_000);
+    assert_eq!(stats.completion_tokens, 400);
+    assert_eq!(stats.mean_duration_ms, 300);
+    // No prices, no cost.
+    assert_eq!(stats.cost, None);
+}
+
+#[test]
+fn cost_is_priced_per_million_tokens() {
+    let outcomes = [outcome("high", 0.9), failed()];
+    let priced = ExperimentVariant {
+        prompt_price: Some(3.0),
+        completion_price: Some(15.0),
+        ..Default::default()
+    };
+    let cost = stats(outcomes.iter(), &priced).cost.unwrap();
+    // 2,000 prompt tokens at $3/M and 200 completion tokens at $15/M.
+    assert!((cost - 0.009).abs() < 1e-9);
+
+    let prompt_only = ExperimentVariant {
+        prompt_price: Some(3.0),
+        ..Default::default()
+    };
+    let cost = stats(outcomes.iter(), &prompt_only).cost.unwrap();
+    assert!((cost - 0.006).abs() < 1e-9);
+}
@@ -0,0 +1,39 @@
+use agent::pipeline::folders::{folder_mode, scanned_folders, FolderMode};
+
+const DEFAULTS: [&str; 2] = ["Inbox", "Sent Items"];
+
+#[test]
+fn folders_not_listed_are_full() {
+    let spec = "Newsletters=index_only, Junk = EXCLUDE";
+    assert_eq!(folder_mode(spec, "newsletters"), FolderMode::IndexOnly);
+    assert_eq!(folder_mode(spec, " junk "), FolderMode::Exclude);
+    assert_eq!(folder_mode(spec, "Inbox"), FolderMode::Full);
+    assert_eq!(folder_mode("", "Inbox"), FolderMode::Full);
+    // Malformed entries are ignored.
+    assert_eq!(
+        folder_mode("Inbox, Archive=sometimes", "Archive"),
+        FolderMode::Full
+    );
+}
+
+#[test]
+fn default_folders_are_scanned_unless_excluded() {
+    assert_eq!(scanned_folders("", &DEFAULTS), DEFAULTS);
+    assert_eq!(
+        scanned_folders("sent items=exclude, inbox=index_only", &DEFAULTS),
+        ["Inbox"]
+    );
+}
+
+#[test]
+fn listed_folders_are_scanned_in_any_mode_but_exclude() {
+    let spec = "Newsletters=index_only, Junk=exclude, Inbox/Projects=full, newsletters=full";
+    assert_eq!(
+        scanned_folders(spec, &DEFAULTS),
+        ["Inbox", "Sent Items", "Newsletters", "Inbox/Projects"]
+    );
+    assert_eq!(
+        scanned_folders("Inbox=exclude, Archive=full, =full", &DEFAULTS),
+        ["Sent Items", "Archive"]
+    );
+}
@@ -0,0 +1,23 @@
+use agent::integrations::item_hash;
+use noodle_core::types::IssueKind;
+
+#[test]
+fn item_hash_ignores_case_and_spacing() {
+    assert_eq!(
+        item_hash(IssueKind::Risk, "Vendor may  slip the\nlaunch"),
+        item_hash(IssueKind::Risk, " vendor may slip the launch ")
+    );
+}
+
+#[test]
+fn item_hash_tells_items_and_kinds_apart() {
+    let risk = item_hash(IssueKind::Risk, "Vendor may slip the launch");
+    assert_ne!(
+        risk,
+        item_hash(IssueKind::Blocker, "Vendor may slip the launch")
+    );
+    assert_ne!(
+        risk,
+        item_hash(IssueKind::Risk, "Vendor slipped the launch")
+    );
+}
@@ -0,0 +1,64 @@
+use agent::issues::{closest, mean, without};
+use ai::embedding::cosine;
+use storage::sqlite::IssueCentroid;
+
+fn cluster(id: i64, centroid: Vec<f32>, mentions: i64) -> IssueCentroid {
+    IssueCentroid {
+        id,
+        centroid,
+        mentions,
+    }
+}
+
+#[test]
+fn cosine_ignores_length_and_handles_zero_vectors() {
+    assert!((cosine(&[1.0, 0.0], &[3.0, 0.0]) - 1.0).abs() < 1e-6);
+    assert!(cosine(&[1.0, 0.0], &[0.0, 2.0]).abs() < 1e-6);
+    assert!((cosine(&[1.0, 0.0], &[-1.0, 0.0]) + 1.0).abs() < 1e-6);
+    assert_eq!(cosine(&[0.0, 0.0], &[1.0, 0.0]), 0.0);
+}
+
+#[test]
+fn closest_picks_the_most_similar_cluster_above_the_threshold() {
+    let centroids = vec![
+        cluster(1, vec![1.0, 0.0], 1),
+        cluster(2, vec![0.8, 0.6], 1),
+        cluster(3, vec![0.0, 1.0], 1),
+    ];
+
+    assert_eq!(closest(&centroids, &[0.9, 0.44], 0.5), Some(1));
+    assert_eq!(closest(&centroids, &[1.0, 0.0], 0.5), Some(0));
+    // Nothing is similar enough.
+    assert_eq!(closest(&centroids, &[0.7, -0.7], 0.85), None);
+    assert_eq!(closest(&[], &[1.0, 0.0], 0.0), None);
+}
+
+#[test]
+fn closest_skips_centroids_of_another_model() {
+    let centroids = vec![
+        cluster(1, vec![1.0, 0.0, 0.0], 1),
+        cluster(2, vec![0.6, 0.8], 1),
+    ];
+    assert_eq!(closest(&centroids, &[1.0, 0.0], 0.5), Some(1));
+}
+
+#[test]
+fn mean_weights_the_centroid_by_its_mentions() {
+    let three = cluster(1, vec![1.0, 0.0], 3);
+    assert_eq!(mean(&three, &[0.0, 1.0]), vec![0.75, 0.25]);
+
+    let empty = cluster(2, vec![0.3, 0.3], 0);
+    assert_eq!(mean(&empty, &[0.0, 1.0]), vec![0.0, 1.0]);
+}
+
+#[test]
+fn without_undoes_mean() {
+    let two = cluster(1, vec![1.0, 0.0], 2);
+    let added = mean(&two, &[0.0, 1.0]);
+    let removed = without(&cluster(1, added, 3), &[0.0, 1.0]);
+    assert!((removed[0] - 1.0).abs() < 1e-6 && removed[1].abs() < 1e-6);
+
+    // A last mention leaves the mean for the empty cluster to be deleted.
+    let one = cluster(2, vec![0.6, 0.8], 1);
+    assert_eq!(without(&one, &[0.6, 0.8]), vec![0.6, 0.8]);
+}
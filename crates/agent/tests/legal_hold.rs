@@ -0,0 +1,126 @@
+use agent::engine::legal_hold::{LegalHold, LEGAL_HOLD_KEY};
+use noodle_core::fixtures;
+use noodle_core::types::Email;
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::Arc;
+use storage::sqlite::SqliteStorage;
+use tempfile::TempDir;
+
+const SEGMENT: &str = "segment-000001.jsonl";
+
+async fn open() -> (TempDir, Arc<SqliteStorage>, PathBuf) {
+    let dir = tempfile::tempdir().unwrap();
+    let storage = SqliteStorage::new(dir.path().join("noodle.db"))
+        .await
+        .unwrap();
+    storage.set_config(LEGAL_HOLD_KEY, "true").await.unwrap();
+    let archive = dir.path().join("legal_hold");
+    (dir, Arc::new(storage), archive)
+}
+
+fn email(id: i64) -> Email {
+    Email {
+        id,
+        subject: format!("Status {}", id),
+        body_text: "Shipping Friday.".into(),
+        hash: id.to_string(),
+        ..fixtures::email(&format!("entry-{}", id))
+    }
+}
+
+async fn archive_all(hold: &LegalHold, ids: std::ops::RangeInclusive<i64>) {
+    for id in ids {
+        hold.archive(&email(id)).await.unwrap();
+    }
+}
+
+fn rewrite(path: &PathBuf, edit: impl FnOnce(Vec<String>) -> Vec<String>) {
+    let lines = std::fs::read_to_string(path)
+        .unwrap()
+        .lines()
+        .map(String::from)
+        .collect();
+    std::fs::write(path, edit(lines).join("\n") + "\n").unwrap();
+}
+
+#[tokio::test]
+async fn appended_records_form_an_intact_chain() {
+    let (_dir, sqlite, archive) = open().await;
+    let hold = LegalHold::new(sqlite.clone(), archive.clone());
+    archive_all(&hold, 1..=3).await;
+
+    // A fresh instance picks the chain up from disk.
+    let hold = LegalHold::new(sqlite, archive);
+    archive_all(&hold, 4..=4).await;
+    let result = hold.verify().await.unwrap();
+    assert!(result.intact, "{:?}", result.problem);
+    assert_eq!(result.records, 4);
+    assert_eq!(result.segments, 1);
+    assert_eq!(result.torn, 0);
+}
+
+#[tokio::test]
+async fn nothing_is_archived_while_the_hold_is_off() {
+    let (_dir, sqlite, archive) = open().await;
+    sqlite.set_config(LEGAL_HOLD_KEY, "false").await.unwrap();
+    let hold = LegalHold::new(sqlite, archive.clone());
+    archive_all(&hold, 1..=2).await;
+    assert!(!archive.exists());
+    assert_eq!(hold.verify().await.unwrap().records, 0);
+}
+
+#[tokio::test]
+async fn tampering_breaks_the_chain() {
+    let (_dir, sqlite, archive) = open().await;
+    let hold = LegalHold::new(sqlite, archive.clone());
+    archive_all(&hold, 1..=3).await;
+    let segment = archive.join(SEGMENT);
+    let original = std::fs::read_to_string(&segment).unwrap();
+
+    rewrite(&segment, |mut lines| {
+        lines[1] = lines[1].replace("entry-2", "entry-9");
+        lines
+    });
+    let result = hold.verify().await.unwrap();
+    assert!(!result.intact);
+    assert_eq!(result.first_broken, Some(2));
+
+    std::fs::write(&segment, &original).unwrap();
+    rewrite(&segment, |mut lines| {
+        lines.remove(1);
+        lines
+    });
+    assert_eq!(hold.verify().await.unwrap().first_broken, Some(2));
+
+    std::fs::write(&segment, &original).unwrap();
+    rewrite(&segment, |mut lines| {
+        lines.pop();
+        lines
+    });
+    let result = hold.verify().await.unwrap();
+    assert!(!result.intact);
+    assert_eq!(result.first_broken, Some(3));
+
+    std::fs::write(&segment, &original).unwrap();
+    assert!(hold.verify().await.unwrap().intact);
+}
+
+#[tokio::test]
+async fn a_torn_last_record_is_reported_and_the_chain_continues() {
+    let (_dir, sqlite, archive) = open().await;
+    let hold = LegalHold::new(sqlite.clone(), archive.clone());
+    archive_all(&hold, 1..=2).await;
+    let mut segment = std::fs::OpenOptions::new()
+        .append(true)
+        .open(archive.join(SEGMENT))
+        .unwrap();
+    segment.write_all(br#"{"seq":3,"archived_at":"20"#).unwrap();
+
+    let hold = LegalHold::new(sqlite, archive);
+    archive_all(&hold, 3..=4).await;
+    let result = hold.verify().await.unwrap();
+    assert!(result.intact, "{:?}", result.problem);
+    assert_eq!(result.records, 4);
+    assert_eq!(result.torn, 1);
+}
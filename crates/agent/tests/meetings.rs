@@ -0,0 +1,95 @@
+use agent::meetings::{is_related, normalize_subject};
+use chrono::{Duration, TimeZone, Utc};
+use noodle_core::types::{Meeting, MeetingAttendee};
+use storage::sqlite::MeetingCandidate;
+
+fn meeting(subject: &str, attendees: &[(&str, &str)]) -> Meeting {
+    let start_at = Utc.with_ymd_and_hms(2025, 5, 6, 14, 0, 0).unwrap();
+    Meeting {
+        id: 1,
+        global_id: "global".into(),
+        subject: subject.into(),
+        organizer: "Ann".into(),
+        attendees: attendees
+            .iter()
+            .map(|(name, address)| MeetingAttendee {
+                name: name.to_string(),
+                address: address.to_string(),
+            })
+            .collect(),
+        start_at,
+        end_at: start_at + Duration::hours(1),
+        followed_up_at: None,
+        follow_up_error: None,
+    }
+}
+
+fn candidate(subject: &str, sender: &str, to: &str, hours_after_start: i64) -> MeetingCandidate {
+    MeetingCandidate {
+        id: 1,
+        subject: subject.into(),
+        sender: sender.into(),
+        to: to.into(),
+        cc: None,
+        received_at: Utc.with_ymd_and_hms(2025, 5, 6, 14, 0, 0).unwrap()
+            + Duration::hours(hours_after_start),
+        body_text: String::new(),
+    }
+}
+
+#[test]
+fn normalize_subject_strips_reply_and_invite_prefixes() {
+    assert_eq!(
+        normalize_subject("RE: Fw:  Launch   review"),
+        "launch review"
+    );
+    assert_eq!(normalize_subject("Updated: Launch review"), "launch review");
+    assert_eq!(normalize_subject("  Launch review "), "launch review");
+    // Only leading prefixes go.
+    assert_eq!(normalize_subject("Launch re: review"), "launch re: review");
+}
+
+#[test]
+fn mail_carrying_the_meeting_subject_is_related() {
+    let meeting = meeting("Launch review", &[]);
+    let before = candidate("Re: Launch Review", "bob@example.com", "Me", -24);
+    assert!(is_related(&meeting, &before));
+    let after = candidate("Notes: launch review", "bob@example.com", "Me", 2);
+    assert!(is_related(&meeting, &after));
+}
+
+#[test]
+fn invite_responses_and_short_subjects_are_not_related() {
+    let meeting = meeting("Launch review", &[]);
+    let accepted = candidate("Accepted: Launch review", "bob@example.com", "Ann", 0);
+    assert!(!is_related(&meeting, &accepted));
+
+    let meeting = self::meeting("1:1", &[]);
+    let unrelated = candidate("Re: 1:1 notes", "bob@example.com", "Me", 2);
+    assert!(!is_related(&meeting, &unrelated));
+}
+
+#[test]
+fn mail_between_the_attendees_after_the_start_is_related() {
+    let meeting = meeting(
+        "Sync",
+        &[("Ann", "ann@example.com"), ("Bob", "bob@example.com")],
+    );
+    let recap = candidate("Notes", "ann@example.com", "Bob; Carol", 2);
+    assert!(is_related(&meeting, &recap));
+
+    // Before the meeting, or with only one attendee in it, it isn't.
+    let earlier = candidate("Notes", "ann@example.com", "Bob", -2);
+    assert!(!is_related(&meeting, &earlier));
+    let elsewhere = candidate("Notes", "ann@example.com", "Carol", 2);
+    assert!(!is_related(&meeting, &elsewhere));
+}
+
+#[test]
+fn a_single_attendee_is_enough_for_a_one_on_one() {
+    let meeting = meeting("Chat", &[("Bob", "bob@example.com")]);
+    assert!(is_related(
+        &meeting,
+        &candidate("Thanks", "bob@example.com", "Me", 1)
+    ));
+}
@@ -0,0 +1,102 @@
+use agent::pipeline::newsletter::{is_newsletter, looks_like_bulk_sender};
+use chrono::{Duration, Utc};
+use noodle_core::fixtures;
+use noodle_core::types::Email;
+use storage::sqlite::SqliteStorage;
+use tempfile::TempDir;
+
+async fn open() -> (TempDir, SqliteStorage) {
+    let dir = tempfile::tempdir().unwrap();
+    let storage = SqliteStorage::new(dir.path().join("noodle.db"))
+        .await
+        .unwrap();
+    (dir, storage)
+}
+
+fn email(id: i64, sender: &str, days_ago: i64) -> Email {
+    let at = Utc::now() - Duration::days(days_ago);
+    Email {
+        id,
+        conversation_id: Some(format!("conversation-{}", id)),
+        subject: format!("Issue {}", id),
+        sender: sender.into(),
+        sent_at: at,
+        received_at: at,
+        body_text: "This week's news.".into(),
+        last_indexed_at: at,
+        hash: id.to_string(),
+        ..fixtures::email(&format!("entry-{}", id))
+    }
+}
+
+#[test]
+fn bulk_senders_are_matched_on_the_first_word() {
+    for sender in [
+        "news@example.com",
+        "News.EU@example.com",
+        "newsletter-weekly@example.com",
+        "no-reply@example.com",
+        "noreply+bounce@example.com",
+        "updates2@example.com",
+    ] {
+        assert!(looks_like_bulk_sender(sender), "{}", sender);
+    }
+    for sender in [
+        "newsom@example.com",
+        "newman@example.com",
+        "digestive@example.com",
+        "alice@news.example.com",
+        "not an address",
+    ] {
+        assert!(!looks_like_bulk_sender(sender), "{}", sender);
+    }
+}
+
+#[tokio::test]
+async fn recurring_bulk_mail_is_a_newsletter() {
+    let (_dir, sqlite) = open().await;
+    for id in 1..=2 {
+        sqlite
+            .save_email(&email(id, "news@example.com", 10 * id))
+            .await
+            .unwrap();
+    }
+    let latest = email(3, "news@example.com", 0);
+    sqlite.save_email(&latest).await.unwrap();
+    assert!(is_newsletter(&sqlite, &latest).await.unwrap());
+
+    // Two emails in the window aren't recurring yet.
+    let second = email(2, "news@example.com", 20);
+    assert!(!is_newsletter(&sqlite, &second).await.unwrap());
+}
+
+#[tokio::test]
+async fn a_list_unsubscribe_header_marks_bulk_mail() {
+    let (_dir, sqlite) = open().await;
+    for id in 1..=3 {
+        let mut email = email(id, "alice@example.com", 10 - id);
+        sqlite.save_email(&email).await.unwrap();
+        assert!(!is_newsletter(&sqlite, &email).await.unwrap());
+
+        email.list_unsubscribe = Some("<mailto:leave@example.com>".into());
+        assert_eq!(is_newsletter(&sqlite, &email).await.unwrap(), id == 3);
+    }
+}
+
+#[tokio::test]
+async fn senders_the_user_replied_to_are_not_newsletters() {
+    let (_dir, sqlite) = open().await;
+    for id in 1..=3 {
+        sqlite
+            .save_email(&email(id, "news@example.com", id))
+            .await
+            .unwrap();
+    }
+    let mut reply = email(4, "me@example.com", 0);
+    reply.folder = "Sent Items".into();
+    reply.conversation_id = Some("conversation-2".into());
+    sqlite.save_email(&reply).await.unwrap();
+
+    let latest = email(5, "news@example.com", 0);
+    assert!(!is_newsletter(&sqlite, &latest).await.unwrap());
+}
@@ -0,0 +1,44 @@
+use agent::pipeline::pacing::PacingController;
+use chrono::{Duration, TimeZone, Utc};
+use std::time::Instant;
+
+#[test]
+fn calls_are_held_until_the_meeting_ends() {
+    let pacing = PacingController::default();
+    let at = |hour, min| Utc.with_ymd_and_hms(2025, 5, 20, hour, min, 0).unwrap();
+    pacing.set_busy_times(vec![(at(10, 0), at(10, 30)), (at(14, 0), at(15, 0))]);
+
+    assert_eq!(pacing.busy_until(at(9, 59)), None);
+    assert_eq!(pacing.busy_until(at(10, 0)), Some(at(10, 30)));
+    assert_eq!(pacing.busy_until(at(10, 15)), Some(at(10, 30)));
+    assert_eq!(pacing.busy_until(at(10, 30)), None);
+    assert_eq!(pacing.busy_until(at(14, 45)), Some(at(15, 0)));
+
+    pacing.set_busy_times(Vec::new());
+    assert_eq!(pacing.busy_until(at(10, 15)), None);
+}
+
+#[test]
+fn back_to_back_and_overlapping_meetings_hold_through() {
+    let pacing = PacingController::default();
+    let at = |hour, min| Utc.with_ymd_and_hms(2025, 5, 20, hour, min, 0).unwrap();
+    pacing.set_busy_times(vec![
+        (at(11, 0), at(12, 0)),
+        (at(9, 0), at(10, 0)),
+        (at(10, 0), at(10, 30)),
+        (at(10, 15), at(11, 0)),
+    ]);
+
+    assert_eq!(pacing.busy_until(at(9, 30)), Some(at(12, 0)));
+}
+
+#[tokio::test]
+async fn calls_run_right_away_when_the_user_is_free() {
+    let pacing = PacingController::default();
+    let now = Utc::now();
+    pacing.set_busy_times(vec![(now - Duration::hours(2), now - Duration::hours(1))]);
+
+    let started = Instant::now();
+    assert_eq!(pacing.llm_call("test", async { 7 }).await, 7);
+    assert!(started.elapsed() < std::time::Duration::from_secs(1));
+}
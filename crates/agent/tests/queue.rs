@@ -0,0 +1,94 @@
+use agent::pipeline::queue::{WorkQueue, DELTA_BURST};
+use noodle_core::fixtures::email;
+use noodle_core::types::{Email, PipelineStage, QueuePriority};
+
+fn emails(prefix: &str, count: usize) -> Vec<Email> {
+    (0..count)
+        .map(|i| email(&format!("{}{}", prefix, i)))
+        .collect()
+}
+
+/// Takes what is left, as entry ids.
+fn drain(queue: &WorkQueue) -> Vec<String> {
+    std::iter::from_fn(|| queue.next())
+        .map(|(email, _)| email.entry_id)
+        .collect()
+}
+
+#[test]
+fn vip_mail_comes_first_then_delta_then_backfill() {
+    let queue = WorkQueue::default();
+    queue.enqueue(emails("b", 2), QueuePriority::Backfill);
+    queue.enqueue(emails("d", 2), QueuePriority::Delta);
+    queue.enqueue(emails("v", 1), QueuePriority::Vip);
+
+    let (first, priority) = queue.next().unwrap();
+    assert_eq!(
+        (first.entry_id.as_str(), priority),
+        ("v0", QueuePriority::Vip)
+    );
+    assert_eq!(drain(&queue), ["d0", "d1", "b0", "b1"]);
+    assert!(queue.next().is_none());
+}
+
+#[test]
+fn backfill_gets_a_turn_after_each_delta_burst() {
+    let queue = WorkQueue::default();
+    queue.enqueue(emails("d", 2 * DELTA_BURST + 1), QueuePriority::Delta);
+    queue.enqueue(emails("b", 3), QueuePriority::Backfill);
+
+    let order = drain(&queue);
+    assert_eq!(order[DELTA_BURST], "b0");
+    assert_eq!(order[2 * DELTA_BURST + 1], "b1");
+    assert_eq!(order[2 * DELTA_BURST + 2], "d10");
+    assert_eq!(order.last().unwrap(), "b2");
+    assert!(order[..DELTA_BURST].iter().all(|id| id.starts_with('d')));
+}
+
+#[test]
+fn delta_mail_runs_on_while_there_is_no_backfill() {
+    let queue = WorkQueue::default();
+    queue.enqueue(emails("d", DELTA_BURST + 2), QueuePriority::Delta);
+    assert_eq!(drain(&queue).len(), DELTA_BURST + 2);
+
+    // An empty queue ends the streak, so backfill enqueued later still
+    // waits its turn.
+    queue.enqueue(emails("b", 1), QueuePriority::Backfill);
+    queue.enqueue(emails("e", 1), QueuePriority::Delta);
+    assert_eq!(drain(&queue), ["e0", "b0"]);
+}
+
+#[test]
+fn emails_already_waiting_keep_their_place() {
+    let queue = WorkQueue::default();
+    queue.enqueue(emails("b", 2), QueuePriority::Backfill);
+    queue.enqueue(vec![email("b1"), email("d0")], QueuePriority::Delta);
+    assert_eq!(drain(&queue), ["d0", "b0", "b1"]);
+}
+
+#[test]
+fn vip_mail_still_counts_as_pending_backfill() {
+    let queue = WorkQueue::default();
+    queue.enqueue(emails("v", 1), QueuePriority::Vip);
+    assert!(queue.has_pending(QueuePriority::Backfill, "Inbox"));
+    assert!(!queue.has_pending(QueuePriority::Delta, "Inbox"));
+    assert!(!queue.has_pending(QueuePriority::Backfill, "Sent Items"));
+}
+
+#[test]
+fn a_finished_run_leaves_a_newer_run_of_the_email_tracked() {
+    let queue = WorkQueue::default();
+    let first = queue.start(&email("e1"));
+    let second = queue.start(&email("e1"));
+
+    first.set_stage(PipelineStage::Extracting);
+    drop(first);
+    let (_, _, active) = queue.snapshot(10);
+    assert_eq!(active.len(), 1);
+    assert_eq!(active[0].stage, PipelineStage::Waiting);
+    assert!(queue.cancel("e1"));
+
+    drop(second);
+    assert!(queue.snapshot(10).2.is_empty());
+    assert!(!queue.cancel("e1"));
+}
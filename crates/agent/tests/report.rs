@@ -0,0 +1,52 @@
+use agent::report::{file_stem, mask_email_addresses, redact_terms};
+
+fn terms(list: &[&str]) -> Vec<String> {
+    list.iter().map(|t| t.to_string()).collect()
+}
+
+#[test]
+fn terms_are_redacted_as_whole_words() {
+    assert_eq!(
+        redact_terms("Al met Alpha and AL's team.", &terms(&["al"])),
+        "[redacted] met Alpha and [redacted]'s team."
+    );
+    assert_eq!(
+        redact_terms(
+            "Acme Corp and Acme, not Acmeville.",
+            &terms(&["Acme", "acme corp"])
+        ),
+        "[redacted] and [redacted], not Acmeville."
+    );
+    assert_eq!(
+        redact_terms("Paid $5,000 on time.", &terms(&["$5,000", " "])),
+        "Paid [redacted] on time."
+    );
+}
+
+#[test]
+fn terms_next_to_each_other_are_each_redacted() {
+    assert_eq!(
+        redact_terms("Dana/Acme", &terms(&["dana", "acme"])),
+        "[redacted]/[redacted]"
+    );
+    assert_eq!(
+        redact_terms("DanaAcme", &terms(&["dana", "acme"])),
+        "DanaAcme"
+    );
+}
+
+#[test]
+fn only_address_shaped_words_are_masked() {
+    assert_eq!(
+        mask_email_addresses("Ask dana@acme.com. Or @team and v1.2."),
+        "Ask [redacted]. Or @team and v1.2."
+    );
+}
+
+#[test]
+fn file_names_keep_redacted_project_names_out() {
+    let project = redact_terms("Acme Rollout", &terms(&["acme"]));
+    assert_eq!(file_stem(&project), "redacted--rollout");
+    assert_eq!(file_stem("Acme: Phase 2/3"), "acme--phase-2-3");
+    assert_eq!(file_stem("???"), "project");
+}
@@ -0,0 +1,69 @@
+use agent::pipeline::sanitize::{scrub, strict_field};
+use noodle_core::fixtures::facts;
+use noodle_core::types::{Intent, OpenQuestion, ProjectInfo, Risk, Sentiment, Severity, Urgency};
+use serde_json::json;
+
+fn risk(title: &str, owner: Option<&str>, confidence: f32) -> Risk {
+    Risk {
+        title: title.into(),
+        details: "x".repeat(3000).as_str().into(),
+        owner: owner.map(Into::into),
+        severity: Severity::High,
+        confidence,
+    }
+}
+
+#[test]
+fn strict_fields_default_only_when_missing() {
+    let data = json!({"urgency": "high", "risks": null, "intent": "shout"});
+    assert_eq!(
+        strict_field(&data, "urgency", Urgency::Low).unwrap(),
+        Urgency::High
+    );
+    assert_eq!(
+        strict_field(&data, "sentiment", Sentiment::Neutral).unwrap(),
+        Sentiment::Neutral
+    );
+    assert!(strict_field(&data, "risks", Vec::<Risk>::new())
+        .unwrap()
+        .is_empty());
+    assert!(strict_field(&data, "intent", Intent::Inform).is_err());
+}
+
+#[test]
+fn scrub_limits_fields_and_drops_empty_entries() {
+    let mut facts = facts(1);
+    facts.client_or_project = ProjectInfo {
+        name: "<b></b>".into(),
+        confidence: 7.0,
+    };
+    facts.summary = "s".repeat(1500).as_str().into();
+    facts.key_points = (0..30).map(|i| format!("Point {}", i).into()).collect();
+    facts.key_points.insert(0, "<p></p>".into());
+    facts.risks = vec![
+        risk("Launch slips", Some("<i></i>"), f32::NAN),
+        risk("<span></span>", None, 0.5),
+    ];
+    facts.open_questions = vec![OpenQuestion {
+        question: "".into(),
+        asked_by: None,
+        owner: None,
+        due_by: None,
+        due_by_raw: None,
+        confidence: 0.5,
+    }];
+    facts.confidence = -1.0;
+
+    scrub(&mut facts);
+    assert_eq!(facts.client_or_project.name, "Unknown");
+    assert_eq!(facts.client_or_project.confidence, 1.0);
+    assert_eq!(facts.summary.chars().count(), 1000);
+    assert_eq!(facts.key_points.len(), 20);
+    assert_eq!(facts.key_points[0].as_str(), "Point 0");
+    assert_eq!(facts.risks.len(), 1);
+    assert_eq!(facts.risks[0].details.chars().count(), 2000);
+    assert_eq!(facts.risks[0].owner, None);
+    assert_eq!(facts.risks[0].confidence, 0.0);
+    assert!(facts.open_questions.is_empty());
+    assert_eq!(facts.confidence, 0.0);
+}
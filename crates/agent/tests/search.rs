@@ -0,0 +1,112 @@
+use agent::search::{interleave, payload_filter};
+use noodle_core::types::{SearchFilter, Urgency};
+use qdrant_client::qdrant::{Condition, Filter};
+use serde_json::{json, Value};
+use storage::qdrant::{CONVERSATION_KEY, PROJECT_KEY, SENDER_KEY, TAGS_KEY};
+
+#[test]
+fn filters_without_payload_fields_leave_qdrant_unfiltered() {
+    assert_eq!(payload_filter(&SearchFilter::default()), None);
+
+    let filter = SearchFilter {
+        folder: Some("Inbox".into()),
+        urgency: Some(Urgency::High),
+        ..Default::default()
+    };
+    assert_eq!(payload_filter(&filter), None);
+}
+
+#[test]
+fn tags_and_project_are_matched_in_the_payload() {
+    let filter = SearchFilter {
+        project: Some("Acme Rollout".into()),
+        tags: vec!["legal".into(), "q3".into()],
+        ..Default::default()
+    };
+
+    assert_eq!(
+        payload_filter(&filter),
+        Some(Filter::must([
+            Condition::matches(TAGS_KEY, "legal".to_string()),
+            Condition::matches(TAGS_KEY, "q3".to_string()),
+            // Stored lowercased, see `email_filter_payload`.
+            Condition::matches_text(PROJECT_KEY, "acme rollout"),
+        ]))
+    );
+}
+
+#[test]
+fn thread_and_sender_scopes_are_matched_in_the_payload() {
+    let thread = SearchFilter {
+        conversation_id: Some("AAQkADk3".into()),
+        ..Default::default()
+    };
+    assert_eq!(
+        payload_filter(&thread),
+        Some(Filter::must([Condition::matches(
+            CONVERSATION_KEY,
+            "AAQkADk3".to_string()
+        )]))
+    );
+
+    let sender = SearchFilter {
+        sender: Some("Alice@Example.com".into()),
+        ..Default::default()
+    };
+    assert_eq!(
+        payload_filter(&sender),
+        Some(Filter::must([Condition::matches_text(
+            SENDER_KEY,
+            "alice@example.com"
+        )]))
+    );
+}
+
+fn email(id: i64) -> Value {
+    json!({ "id": id })
+}
+
+fn attachment(id: i64) -> Value {
+    json!({ "id": id, "result_type": "attachment" })
+}
+
+fn ids_and_types(results: &[Value]) -> Vec<(i64, &str)> {
+    results
+        .iter()
+        .map(|r| {
+            (
+                r["id"].as_i64().unwrap(),
+                r["result_type"].as_str().unwrap(),
+            )
+        })
+        .collect()
+}
+
+#[test]
+fn emails_and_attachments_alternate_by_rank() {
+    let merged = interleave(vec![email(1), email(2), email(3)], vec![attachment(10)], 10);
+    assert_eq!(
+        ids_and_types(&merged),
+        [(1, "email"), (10, "attachment"), (2, "email"), (3, "email")]
+    );
+
+    let merged = interleave(vec![email(1), email(2)], vec![attachment(10)], 2);
+    assert_eq!(ids_and_types(&merged), [(1, "email"), (10, "attachment")]);
+}
+
+#[test]
+fn an_email_found_both_ways_is_listed_once_where_it_ranks_first() {
+    let merged = interleave(
+        vec![email(1), email(2)],
+        vec![attachment(2), attachment(3)],
+        10,
+    );
+    assert_eq!(
+        ids_and_types(&merged),
+        [(1, "email"), (2, "attachment"), (3, "attachment")]
+    );
+
+    // Duplicates don't take up the limit.
+    let merged = interleave(vec![email(1), email(2)], vec![attachment(1)], 2);
+    assert_eq!(ids_and_types(&merged), [(1, "email"), (2, "email")]);
+}
@@ -0,0 +1,22 @@
+use agent::search::summarize::citations;
+use std::collections::HashSet;
+
+fn ids(list: &[i64]) -> HashSet<i64> {
+    list.iter().copied().collect()
+}
+
+#[test]
+fn tagged_ids_are_citations() {
+    assert_eq!(
+        citations("Budget approved [#12][#40]. Launch moved [#7]."),
+        ids(&[12, 40, 7])
+    );
+    assert_eq!(citations("Repeated [#3] and [#3]."), ids(&[3]));
+}
+
+#[test]
+fn bare_numbers_and_broken_tags_are_not_citations() {
+    assert!(citations("Ticket #123 is still open, see issue #9.").is_empty());
+    assert!(citations("Unclosed [#12 and empty [#] and [# 4] tags.").is_empty());
+    assert!(citations("").is_empty());
+}
@@ -0,0 +1,199 @@
+use agent::timeline::timeline;
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use noodle_core::types::{Blocker, DateRange, Risk, Severity, TimelineEventKind};
+use storage::sqlite::ProjectEmailFacts;
+
+const ALL: DateRange = DateRange {
+    start: None,
+    end: None,
+};
+
+fn day(n: i64) -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(2025, 3, 1, 9, 0, 0).unwrap() + Duration::days(n)
+}
+
+fn email(id: i64, thread: &str, intent: &str) -> ProjectEmailFacts {
+    ProjectEmailFacts {
+        id,
+        conversation_id: Some(thread.to_string()),
+        subject: format!("Email {}", id),
+        sender: "dana@example.com".into(),
+        received_at: day(id),
+        project: Some("Apollo".into()),
+        primary_type: "status".into(),
+        intent: intent.into(),
+        summary: format!("Summary {}", id),
+        key_points: Vec::new(),
+        due_by: None,
+        risks: Vec::new(),
+        blockers: Vec::new(),
+    }
+}
+
+fn blocker(title: &str) -> Blocker {
+    Blocker {
+        title: title.into(),
+        details: format!("{} details", title).into(),
+        owner: None,
+        severity: Severity::High,
+        confidence: 0.9,
+    }
+}
+
+fn risk(title: &str) -> Risk {
+    Risk {
+        title: title.into(),
+        details: "".into(),
+        owner: None,
+        severity: Severity::Medium,
+        confidence: 0.9,
+    }
+}
+
+/// The kind and title of each event other than the emails themselves.
+fn facts(emails: &[ProjectEmailFacts], range: &DateRange) -> Vec<(TimelineEventKind, String)> {
+    timeline(emails, range)
+        .into_iter()
+        .filter(|e| e.kind != TimelineEventKind::Email)
+        .map(|e| (e.kind, e.title))
+        .collect()
+}
+
+#[test]
+fn every_email_is_listed_with_its_summary() {
+    let decision = ProjectEmailFacts {
+        primary_type: "decision".into(),
+        ..email(2, "t", "inform")
+    };
+    let events = timeline(&[email(1, "t", "inform"), decision], &ALL);
+    let kinds: Vec<_> = events.iter().map(|e| (e.kind, e.email_id)).collect();
+    assert_eq!(
+        kinds,
+        [
+            (TimelineEventKind::Email, 1),
+            (TimelineEventKind::Email, 2),
+            (TimelineEventKind::Decision, 2),
+        ]
+    );
+    assert_eq!(events[0].detail.as_deref(), Some("Summary 1"));
+}
+
+#[test]
+fn risks_are_reported_once() {
+    let emails = [
+        ProjectEmailFacts {
+            risks: vec![risk("Vendor delay")],
+            ..email(1, "a", "inform")
+        },
+        ProjectEmailFacts {
+            risks: vec![risk(" vendor DELAY "), risk("Budget cut")],
+            ..email(2, "b", "inform")
+        },
+    ];
+    assert_eq!(
+        facts(&emails, &ALL),
+        [
+            (TimelineEventKind::RiskRaised, "Vendor delay".into()),
+            (TimelineEventKind::RiskRaised, "Budget cut".into()),
+        ]
+    );
+}
+
+#[test]
+fn resolving_clears_only_the_named_blocker() {
+    let emails = [
+        ProjectEmailFacts {
+            blockers: vec![blocker("Legal review"), blocker("Missing API key")],
+            ..email(1, "t", "inform")
+        },
+        ProjectEmailFacts {
+            blockers: vec![blocker("missing api key")],
+            ..email(2, "t", "resolve")
+        },
+        // Names nothing, so the one left open is the one resolved.
+        email(3, "t", "resolve"),
+        // Nothing is open any more.
+        email(4, "t", "resolve"),
+    ];
+    let events = timeline(&emails, &ALL);
+    let resolved: Vec<_> = events
+        .iter()
+        .filter(|e| e.kind == TimelineEventKind::BlockerResolved)
+        .map(|e| (e.email_id, e.title.as_str()))
+        .collect();
+    assert_eq!(resolved, [(2, "Missing API key"), (3, "Legal review")]);
+}
+
+#[test]
+fn resolving_without_naming_one_of_several_clears_none() {
+    let emails = [
+        ProjectEmailFacts {
+            blockers: vec![blocker("Legal review"), blocker("Missing API key")],
+            ..email(1, "t", "inform")
+        },
+        ProjectEmailFacts {
+            blockers: vec![blocker("Something else")],
+            ..email(2, "t", "resolve")
+        },
+        // Blockers are followed per thread.
+        ProjectEmailFacts {
+            blockers: vec![blocker("Legal review")],
+            ..email(3, "other", "resolve")
+        },
+    ];
+    assert!(facts(&emails, &ALL).is_empty());
+}
+
+#[test]
+fn deadlines_are_reported_when_they_move() {
+    let emails = [
+        ProjectEmailFacts {
+            due_by: Some(day(10)),
+            ..email(1, "t", "inform")
+        },
+        ProjectEmailFacts {
+            due_by: Some(day(10)),
+            ..email(2, "t", "inform")
+        },
+        ProjectEmailFacts {
+            due_by: Some(day(14)),
+            ..email(3, "t", "inform")
+        },
+    ];
+    let changes: Vec<_> = timeline(&emails, &ALL)
+        .into_iter()
+        .filter(|e| e.kind == TimelineEventKind::DeadlineChanged)
+        .map(|e| (e.email_id, e.previous_due_by, e.due_by))
+        .collect();
+    assert_eq!(
+        changes,
+        [(1, None, Some(day(10))), (3, Some(day(10)), Some(day(14)))]
+    );
+}
+
+#[test]
+fn emails_before_the_range_are_known_but_not_listed() {
+    let emails = [
+        ProjectEmailFacts {
+            risks: vec![risk("Vendor delay")],
+            due_by: Some(day(10)),
+            ..email(1, "t", "inform")
+        },
+        ProjectEmailFacts {
+            risks: vec![risk("Vendor delay")],
+            due_by: Some(day(10)),
+            ..email(2, "t", "inform")
+        },
+        email(3, "t", "inform"),
+    ];
+    let range = DateRange {
+        start: Some(day(2)),
+        end: Some(day(3)),
+    };
+    let events = timeline(&emails, &range);
+    assert_eq!(events.len(), 1);
+    assert_eq!(
+        (events[0].kind, events[0].email_id),
+        (TimelineEventKind::Email, 2)
+    );
+}
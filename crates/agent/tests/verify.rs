@@ -0,0 +1,65 @@
+use agent::search::verify::{cited_claims, content_words, parse_tag, word_support};
+
+fn claim(text: &str, ids: &[i64]) -> (String, Vec<i64>) {
+    (text.to_string(), ids.to_vec())
+}
+
+#[test]
+fn tags_are_read_from_the_start_of_the_text() {
+    assert_eq!(parse_tag("[#12] and more"), Some((12, " and more")));
+    assert_eq!(parse_tag("[#7]"), Some((7, "")));
+    for text in ["#12", "[#12", "[#]", "[#x]", " [#12]", "see [#12]"] {
+        assert_eq!(parse_tag(text), None, "{}", text);
+    }
+}
+
+#[test]
+fn adjacent_tags_cite_the_same_statement() {
+    assert_eq!(
+        cited_claims("Budget approved [#12][#40]. Launch moved [#7]."),
+        vec![
+            claim("Budget approved.", &[12, 40]),
+            claim("Launch moved.", &[7]),
+        ]
+    );
+    assert_eq!(
+        cited_claims("Repeated [#3] [#3]."),
+        vec![claim("Repeated.", &[3])]
+    );
+}
+
+#[test]
+fn tags_after_a_full_stop_belong_to_the_sentence_before() {
+    assert_eq!(
+        cited_claims("Budget approved. [#12] Launch moved! [#7][#8] Nothing else."),
+        vec![
+            claim("Budget approved.", &[12]),
+            claim("Launch moved!", &[7, 8]),
+        ]
+    );
+}
+
+#[test]
+fn each_line_of_an_answer_is_its_own_statement() {
+    assert_eq!(
+        cited_claims("- Budget approved [#12]\n- Nothing cited here\n- Launch moved [#7]"),
+        vec![
+            claim("- Budget approved", &[12]),
+            claim("- Launch moved", &[7]),
+        ]
+    );
+    assert!(cited_claims("No citations at all.\nNone here either.").is_empty());
+}
+
+#[test]
+fn word_support_matches_on_word_stems() {
+    let words = content_words("The deadline moved to Friday");
+    let support = word_support(&words, "Deadlines were moved again");
+    assert!((support - 2.0 / 3.0).abs() < 1e-6);
+    assert_eq!(word_support(&words, "Nothing related"), 0.0);
+    // A statement of stopwords only can't be checked by its words.
+    assert_eq!(
+        word_support(&content_words("It was there"), "Anything"),
+        1.0
+    );
+}
@@ -0,0 +1,132 @@
+use crate::provider::Message;
+use std::collections::HashMap;
+use tracing::warn;
+
+/// Tokens assumed for each message's role and framing.
+const MESSAGE_OVERHEAD_TOKENS: usize = 4;
+/// Tokens kept free for the reply, at most a quarter of the window.
+const RESERVED_OUTPUT_TOKENS: usize = 1024;
+/// Share of a trimmed message's budget kept from its start, where the
+/// instructions are; the rest keeps its end.
+const HEAD_SHARE: f64 = 0.7;
+const TRIM_MARKER: &str = "\n\n[… trimmed to fit the model's context window …]\n\n";
+
+/// Rough token count for `text`: about four characters per token for
+/// English, with every word counting at least once so short-word and
+/// non-Latin text is not underestimated.
+pub fn estimate_tokens(text: &str) -> usize {
+    let chars = text.chars().count();
+    let words = text.split_whitespace().count();
+    chars.div_ceil(4).max(words)
+}
+
+fn message_tokens(message: &Message) -> usize {
+    estimate_tokens(&message.content) + MESSAGE_OVERHEAD_TOKENS
+}
+
+/// Context window sizes per model, with a default for models not listed.
+#[derive(Debug, Clone)]
+pub struct ContextWindows {
+    default: usize,
+    models: HashMap<String, usize>,
+}
+
+impl ContextWindows {
+    pub fn new(default: usize) -> Self {
+        Self {
+            default,
+            models: HashMap::new(),
+        }
+    }
+
+    /// Parses overrides written as `model=tokens` pairs separated by commas,
+    /// e.g. `llama3=8192, qwen2.5=32768`.
+    pub fn parse(default: usize, spec: &str) -> Result<Self, String> {
+        let mut windows = Self::new(default);
+        for entry in spec.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+            let (model, tokens) = entry
+                .split_once('=')
+                .ok_or_else(|| format!("expected model=tokens, got \"{}\"", entry))?;
+            let tokens = tokens
+                .trim()
+                .parse()
+                .map_err(|_| format!("\"{}\" is not a token count", tokens.trim()))?;
+            windows.models.insert(model.trim().to_lowercase(), tokens);
+        }
+        Ok(windows)
+    }
+
+    /// The window of `model`. A listed name also covers its tags, so
+    /// `llama3` applies to `llama3:8b`.
+    pub fn for_model(&self, model: &str) -> usize {
+        let model = model.to_lowercase();
+        let base = model.split(':').next().unwrap_or_default();
+        self.models
+            .get(&model)
+            .or_else(|| self.models.get(base))
+            .copied()
+            .unwrap_or(self.default)
+    }
+}
+
+impl Default for ContextWindows {
+    fn default() -> Self {
+        Self::new(8192)
+    }
+}
+
+/// Fits `messages` into a context window of `window` tokens, leaving room
+/// for the reply. System messages (the instructions and schema) are kept
+/// whole; the others are kept newest first, and the oldest one that does
+/// not fit is trimmed in the middle. Returns whether anything was cut.
+pub fn fit_messages(messages: &mut Vec<Message>, window: usize) -> bool {
+    let reserve = RESERVED_OUTPUT_TOKENS.min(window / 4);
+    let budget = window.saturating_sub(reserve);
+    let total: usize = messages.iter().map(message_tokens).sum();
+    if total <= budget {
+        return false;
+    }
+
+    let system: usize = messages
+        .iter()
+        .filter(|m| m.role == "system")
+        .map(message_tokens)
+        .sum();
+    let mut remaining = budget.saturating_sub(system);
+    let mut keep = vec![true; messages.len()];
+    for (i, message) in messages.iter_mut().enumerate().rev() {
+        if message.role == "system" {
+            continue;
+        }
+        let tokens = message_tokens(message);
+        if tokens <= remaining {
+            remaining -= tokens;
+        } else if remaining > MESSAGE_OVERHEAD_TOKENS + estimate_tokens(TRIM_MARKER) {
+            let allowed = remaining - MESSAGE_OVERHEAD_TOKENS - estimate_tokens(TRIM_MARKER);
+            message.content = trim_middle(&message.content, allowed);
+            remaining = 0;
+        } else {
+            keep[i] = false;
+        }
+    }
+    let mut keep = keep.into_iter();
+    messages.retain(|_| keep.next().unwrap_or(true));
+    warn!(
+        "Prompt of ~{} tokens trimmed to fit a {} token context window",
+        total, window
+    );
+    true
+}
+
+/// Keeps the head and tail of `text` within about `tokens` tokens.
+fn trim_middle(text: &str, tokens: usize) -> String {
+    let chars: Vec<char> = text.chars().collect();
+    // Scaled from the estimate, so dense text loses proportionally more.
+    let ratio = tokens as f64 / estimate_tokens(text).max(1) as f64;
+    let allowed = ((chars.len() as f64) * ratio) as usize;
+    let head = (allowed as f64 * HEAD_SHARE) as usize;
+    let tail = allowed - head;
+    let start: String = chars[..head].iter().collect();
+    let end: String = chars[chars.len() - tail..].iter().collect();
+    format!("{}{}{}", start, TRIM_MARKER, end)
+}
@@ -1,2 +1,5 @@
+pub mod budget;
+pub mod embedding;
+pub mod injection;
 pub mod provider;
 pub mod schema;
@@ -1,55 +1,148 @@
+use chrono::{DateTime, Utc};
 use noodle_core::error::{NoodleError, Result};
-use std::ptr;
-use windows::core::{BSTR, PWSTR};
+use serde::Serialize;
+use windows::core::{HSTRING, PWSTR};
+use windows::Win32::Foundation::{ERROR_NOT_FOUND, FILETIME};
 use windows::Win32::Security::Credentials::{
-    CredReadW, CredWriteW, CREDENTIALW, CRED_PERSIST_LOCAL_MACHINE, CRED_TYPE_GENERIC,
+    CredDeleteW, CredEnumerateW, CredFree, CredReadW, CredWriteW, CREDENTIALW,
+    CRED_ENUMERATE_FLAGS, CRED_PERSIST_LOCAL_MACHINE, CRED_TYPE_GENERIC,
 };
 
+/// Prefix of the Credential Manager targets Noodle writes, e.g. `Noodle/openai`.
+const TARGET_PREFIX: &str = "Noodle/";
+
+/// An API key saved in Windows Credential Manager, without the key itself.
+#[derive(Debug, Clone, Serialize)]
+pub struct SavedCredential {
+    pub provider: String,
+    pub last_written: Option<DateTime<Utc>>,
+}
+
+/// Memory returned by `CredReadW` and `CredEnumerateW`, released with
+/// `CredFree` when dropped.
+struct CredBuffer<T>(*mut T);
+
+impl<T> Drop for CredBuffer<T> {
+    fn drop(&mut self) {
+        if !self.0.is_null() {
+            unsafe { CredFree(self.0 as *const _) };
+        }
+    }
+}
+
 pub struct CredentialStore;
 
 impl CredentialStore {
     pub fn save_api_key(provider: &str, key: &str) -> Result<()> {
-        let target = format!("Noodle/{}", provider);
-        let target_bstr = BSTR::from(target.as_str());
+        let target = target(provider);
+        let mut target_wide: Vec<u16> = target.encode_utf16().chain(Some(0)).collect();
         let key_bytes = key.as_bytes();
-
-        unsafe {
-            let cred = CREDENTIALW {
-                Type: CRED_TYPE_GENERIC,
-                TargetName: PWSTR(target_bstr.as_ptr() as *mut _),
-                CredentialBlob: key_bytes.as_ptr() as *mut _,
-                CredentialBlobSize: key_bytes.len() as u32,
-                Persist: CRED_PERSIST_LOCAL_MACHINE,
-                ..Default::default()
-            };
-
-            CredWriteW(&cred, 0)
-                .map_err(|e| NoodleError::Internal(format!("Failed to write credential: {}", e)))?;
-        }
-
-        Ok(())
+        let cred = CREDENTIALW {
+            Type: CRED_TYPE_GENERIC,
+            TargetName: PWSTR(target_wide.as_mut_ptr()),
+            CredentialBlob: key_bytes.as_ptr() as *mut _,
+            CredentialBlobSize: key_bytes.len() as u32,
+            Persist: CRED_PERSIST_LOCAL_MACHINE,
+            ..Default::default()
+        };
+        unsafe { CredWriteW(&cred, 0) }
+            .map_err(|e| NoodleError::Internal(format!("Failed to write credential: {}", e)))
     }
 
     pub fn get_api_key(provider: &str) -> Result<Option<String>> {
-        let target = format!("Noodle/{}", provider);
-        let target_bstr = BSTR::from(target.as_str());
-
-        unsafe {
-            let mut cred_ptr: *mut CREDENTIALW = ptr::null_mut();
-            let res = CredReadW(&target_bstr, CRED_TYPE_GENERIC, 0, &mut cred_ptr);
-
-            if res.is_err() {
-                return Ok(None);
+        let mut cred = CredBuffer(std::ptr::null_mut::<CREDENTIALW>());
+        let read = unsafe {
+            CredReadW(
+                &HSTRING::from(target(provider)),
+                CRED_TYPE_GENERIC,
+                0,
+                &mut cred.0,
+            )
+        };
+        match read {
+            Ok(()) => {}
+            Err(e) if e.code() == ERROR_NOT_FOUND.to_hresult() => return Ok(None),
+            Err(e) => {
+                return Err(NoodleError::Internal(format!(
+                    "Failed to read credential: {}",
+                    e
+                )))
             }
+        }
+        let cred = unsafe { &*cred.0 };
+        let blob = if cred.CredentialBlob.is_null() {
+            &[][..]
+        } else {
+            unsafe {
+                std::slice::from_raw_parts(cred.CredentialBlob, cred.CredentialBlobSize as usize)
+            }
+        };
+        Ok(Some(String::from_utf8_lossy(blob).into_owned()))
+    }
 
-            let cred = &*cred_ptr;
-            let blob =
-                std::slice::from_raw_parts(cred.CredentialBlob, cred.CredentialBlobSize as usize);
-            let key = String::from_utf8_lossy(blob).to_string();
-
-            // Note: In real usage, you must call CredFree(cred_ptr)
+    /// Removes the saved key; returns false when there was none.
+    pub fn delete_api_key(provider: &str) -> Result<bool> {
+        match unsafe { CredDeleteW(&HSTRING::from(target(provider)), CRED_TYPE_GENERIC, 0) } {
+            Ok(()) => Ok(true),
+            Err(e) if e.code() == ERROR_NOT_FOUND.to_hresult() => Ok(false),
+            Err(e) => Err(NoodleError::Internal(format!(
+                "Failed to delete credential: {}",
+                e
+            ))),
+        }
+    }
 
-            Ok(Some(key))
+    /// Every key Noodle has saved, by provider name.
+    pub fn list_saved_credentials() -> Result<Vec<SavedCredential>> {
+        let mut count = 0;
+        let mut creds = CredBuffer(std::ptr::null_mut::<*mut CREDENTIALW>());
+        let enumerated = unsafe {
+            CredEnumerateW(
+                &HSTRING::from(format!("{}*", TARGET_PREFIX)),
+                CRED_ENUMERATE_FLAGS(0),
+                &mut count,
+                &mut creds.0,
+            )
+        };
+        match enumerated {
+            Ok(()) => {}
+            Err(e) if e.code() == ERROR_NOT_FOUND.to_hresult() => return Ok(Vec::new()),
+            Err(e) => {
+                return Err(NoodleError::Internal(format!(
+                    "Failed to list credentials: {}",
+                    e
+                )))
+            }
         }
+        let creds = unsafe { std::slice::from_raw_parts(creds.0, count as usize) };
+        let mut saved: Vec<SavedCredential> = creds
+            .iter()
+            .map(|cred| unsafe { &**cred })
+            .filter(|cred| cred.Type == CRED_TYPE_GENERIC)
+            .filter_map(|cred| {
+                let target = unsafe { cred.TargetName.to_string() }.ok()?;
+                Some(SavedCredential {
+                    provider: target.strip_prefix(TARGET_PREFIX)?.to_string(),
+                    last_written: filetime_to_utc(cred.LastWritten),
+                })
+            })
+            .collect();
+        saved.sort_by(|a, b| a.provider.cmp(&b.provider));
+        Ok(saved)
     }
 }
+
+fn target(provider: &str) -> String {
+    format!("{}{}", TARGET_PREFIX, provider)
+}
+
+/// FILETIME counts 100ns intervals since 1601-01-01.
+fn filetime_to_utc(time: FILETIME) -> Option<DateTime<Utc>> {
+    const UNIX_EPOCH_OFFSET_SECS: i64 = 11_644_473_600;
+    let ticks = (u64::from(time.dwHighDateTime) << 32) | u64::from(time.dwLowDateTime);
+    let ticks = i64::try_from(ticks).ok()?;
+    DateTime::from_timestamp(
+        ticks / 10_000_000 - UNIX_EPOCH_OFFSET_SECS,
+        (ticks % 10_000_000 * 100) as u32,
+    )
+}
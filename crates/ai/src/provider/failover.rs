@@ -0,0 +1,160 @@
+use super::{AiProvider, ChatRequest, ChatResponse};
+use async_trait::async_trait;
+use noodle_core::error::{NoodleError, Result};
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tracing::{info, warn};
+
+/// Consecutive failures that take a provider out of rotation.
+const FAILURE_THRESHOLD: u32 = 3;
+/// How long a tripped provider is skipped before it gets another try.
+const COOLDOWN: Duration = Duration::from_secs(60);
+
+/// Stops sending calls to a provider that keeps failing, for [`COOLDOWN`].
+/// The first call after the cooldown decides whether it is back.
+#[derive(Default)]
+struct CircuitBreaker {
+    failures: AtomicU32,
+    open_until: Mutex<Option<Instant>>,
+}
+
+impl CircuitBreaker {
+    fn is_open(&self) -> bool {
+        let open_until = self.open_until.lock().unwrap_or_else(|e| e.into_inner());
+        open_until.is_some_and(|until| Instant::now() < until)
+    }
+
+    fn record_success(&self) {
+        self.failures.store(0, Ordering::Relaxed);
+        *self.open_until.lock().unwrap_or_else(|e| e.into_inner()) = None;
+    }
+
+    /// Returns true when this failure tripped the breaker.
+    fn record_failure(&self) -> bool {
+        let failures = self.failures.fetch_add(1, Ordering::Relaxed) + 1;
+        if failures < FAILURE_THRESHOLD {
+            return false;
+        }
+        *self.open_until.lock().unwrap_or_else(|e| e.into_inner()) =
+            Some(Instant::now() + COOLDOWN);
+        true
+    }
+}
+
+struct Member {
+    name: String,
+    provider: Arc<dyn AiProvider>,
+    breaker: CircuitBreaker,
+}
+
+/// A primary provider with fallbacks, e.g. a local Ollama backed by a cloud
+/// endpoint. Chat calls go to the first provider whose circuit is closed and
+/// move down the list on errors and, when there is somewhere to go, timeouts;
+/// responses name the provider that produced them. Embeddings and model lists always come from the
+/// primary, since vectors from different models can't be compared.
+pub struct FailoverProvider {
+    members: Vec<Member>,
+    timeout: Duration,
+}
+
+impl FailoverProvider {
+    pub fn new(name: impl Into<String>, primary: Arc<dyn AiProvider>, timeout: Duration) -> Self {
+        Self {
+            members: Vec::new(),
+            timeout,
+        }
+        .with_fallback(name, primary)
+    }
+
+    /// Adds a provider tried after the ones already added.
+    pub fn with_fallback(mut self, name: impl Into<String>, provider: Arc<dyn AiProvider>) -> Self {
+        self.members.push(Member {
+            name: name.into(),
+            provider,
+            breaker: CircuitBreaker::default(),
+        });
+        self
+    }
+
+    fn primary(&self) -> &Member {
+        &self.members[0]
+    }
+}
+
+#[async_trait]
+impl AiProvider for FailoverProvider {
+    async fn chat_completion(&self, request: ChatRequest) -> Result<ChatResponse> {
+        // With every circuit open, trying them all beats failing outright.
+        let mut candidates: Vec<&Member> = self
+            .members
+            .iter()
+            .filter(|m| !m.breaker.is_open())
+            .collect();
+        if candidates.is_empty() {
+            candidates = self.members.iter().collect();
+        }
+
+        let mut last_error = None;
+        for member in candidates {
+            let call = member.provider.chat_completion(request.clone());
+            // A lone provider is left to its own timeouts; cutting a slow local
+            // model short only helps when another provider can take over.
+            let result = if self.members.len() == 1 {
+                call.await
+            } else {
+                tokio::time::timeout(self.timeout, call)
+                    .await
+                    .unwrap_or_else(|_| {
+                        Err(NoodleError::AI(format!(
+                            "No response within {}s",
+                            self.timeout.as_secs()
+                        )))
+                    })
+            };
+            match result {
+                Ok(mut response) => {
+                    member.breaker.record_success();
+                    if member.name != self.primary().name {
+                        info!(
+                            "Chat completion served by fallback provider {}",
+                            member.name
+                        );
+                    }
+                    response.provider = Some(member.name.clone());
+                    return Ok(response);
+                }
+                Err(e) => {
+                    warn!("AI provider {} failed: {}", member.name, e);
+                    if member.breaker.record_failure() {
+                        warn!(
+                            "AI provider {} tripped its circuit breaker; skipping it for {}s",
+                            member.name,
+                            COOLDOWN.as_secs()
+                        );
+                    }
+                    last_error = Some(e);
+                }
+            }
+        }
+        Err(last_error.unwrap_or_else(|| NoodleError::AI("No AI provider configured".into())))
+    }
+
+    async fn generate_embedding(&self, text: &str) -> Result<Vec<f32>> {
+        self.primary().provider.generate_embedding(text).await
+    }
+
+    async fn list_models(&self) -> Result<Vec<String>> {
+        self.primary().provider.list_models().await
+    }
+
+    /// Only when every provider enforces schemas, since any of them may answer.
+    async fn supports_structured_output(&self) -> bool {
+        for member in &self.members {
+            if !member.provider.supports_structured_output().await {
+                return false;
+            }
+        }
+        true
+    }
+}
@@ -1,14 +1,26 @@
 pub mod creds;
+pub mod failover;
 
+use crate::budget::{fit_messages, ContextWindows};
 use async_trait::async_trait;
 use noodle_core::error::Result;
 use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicU8, Ordering};
+use tokio::sync::OnceCell;
+use tracing::{info, warn};
 
 #[async_trait]
 pub trait AiProvider: Send + Sync {
     async fn chat_completion(&self, request: ChatRequest) -> Result<ChatResponse>;
     async fn generate_embedding(&self, text: &str) -> Result<Vec<f32>>;
     async fn list_models(&self) -> Result<Vec<String>>;
+
+    /// Whether [`ResponseFormat::JsonSchema`] is enforced by the server.
+    /// Providers that can't enforce it fall back to plain JSON mode, so the
+    /// caller has to validate and repair the output itself.
+    async fn supports_structured_output(&self) -> bool {
+        false
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -31,14 +43,30 @@ pub struct Message {
 pub enum ResponseFormat {
     #[serde(rename = "json_object")]
     Json,
+    /// JSON constrained to a schema, in OpenAI's `json_schema` shape.
+    #[serde(rename = "json_schema")]
+    JsonSchema { json_schema: JsonSchemaFormat },
     #[serde(rename = "text")]
     Text,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JsonSchemaFormat {
+    pub name: String,
+    pub schema: serde_json::Value,
+    pub strict: bool,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ChatResponse {
     pub content: String,
     pub usage: Usage,
+    /// Model that produced the response, when the server says.
+    #[serde(default)]
+    pub model: Option<String>,
+    /// Set by [`failover::FailoverProvider`] to the provider that answered.
+    #[serde(default)]
+    pub provider: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -52,10 +80,16 @@ pub enum ProviderType {
     OpenAICompatible, // Lemonade, Foundry, etc.
 }
 
+/// Ollama accepts a JSON schema as `format` from this version on.
+const OLLAMA_STRUCTURED_OUTPUT_VERSION: (u32, u32) = (0, 5);
+
 pub struct OllamaProvider {
     client: reqwest::Client,
     base_url: String,
     model_name: Option<String>,
+    context: ContextWindows,
+    /// Read from `/api/version` on first use.
+    structured_output: OnceCell<bool>,
 }
 
 impl OllamaProvider {
@@ -64,12 +98,43 @@ impl OllamaProvider {
             client: reqwest::Client::new(),
             base_url,
             model_name,
+            context: ContextWindows::default(),
+            structured_output: OnceCell::new(),
         }
     }
+
+    pub fn with_context_windows(mut self, context: ContextWindows) -> Self {
+        self.context = context;
+        self
+    }
+
+    async fn server_version(&self) -> Option<(u32, u32)> {
+        let url = format!("{}/api/version", self.base_url);
+        let body: serde_json::Value = self.client.get(&url).send().await.ok()?.json().await.ok()?;
+        let mut parts = body["version"].as_str()?.split('.');
+        let major = parts.next()?.parse().ok()?;
+        let minor = parts.next()?.parse().ok()?;
+        Some((major, minor))
+    }
 }
 
 #[async_trait]
 impl AiProvider for OllamaProvider {
+    async fn supports_structured_output(&self) -> bool {
+        *self
+            .structured_output
+            .get_or_init(|| async {
+                let version = self.server_version().await;
+                let supported = version.is_some_and(|v| v >= OLLAMA_STRUCTURED_OUTPUT_VERSION);
+                info!(
+                    "Ollama {:?} structured output support: {}",
+                    version, supported
+                );
+                supported
+            })
+            .await
+    }
+
     async fn list_models(&self) -> Result<Vec<String>> {
         let url = format!("{}/api/tags", self.base_url);
         let response = self
@@ -91,7 +156,7 @@ impl AiProvider for OllamaProvider {
                     .filter_map(|m| m["name"].as_str().map(|s| s.to_string()))
                     .collect()
             })
-            .unwrap_or_else(Vec::new);
+            .unwrap_or_default();
 
         Ok(models)
     }
@@ -103,16 +168,27 @@ impl AiProvider for OllamaProvider {
             .model
             .or(self.model_name.clone())
             .unwrap_or_else(|| "llama3".to_string());
+        let window = self.context.for_model(&model);
+        let mut messages = request.messages;
+        fit_messages(&mut messages, window);
+        let format = match request.response_format {
+            Some(ResponseFormat::JsonSchema { json_schema })
+                if self.supports_structured_output().await =>
+            {
+                json_schema.schema
+            }
+            Some(ResponseFormat::Json | ResponseFormat::JsonSchema { .. }) => "json".into(),
+            _ => "".into(),
+        };
 
-        // Ollama specific request format
+        // Ollama specific request format. Without num_ctx Ollama uses its own
+        // small default and silently drops the start of longer prompts.
         let ollama_req = serde_json::json!({
             "model": model,
-            "messages": request.messages,
+            "messages": messages,
             "stream": false,
-            "format": match request.response_format {
-                Some(ResponseFormat::Json) => "json",
-                _ => "",
-            }
+            "format": format,
+            "options": { "num_ctx": window }
         });
 
         let response = self
@@ -139,7 +215,12 @@ impl AiProvider for OllamaProvider {
             completion_tokens: body["eval_count"].as_u64().unwrap_or(0) as u32,
         };
 
-        Ok(ChatResponse { content, usage })
+        Ok(ChatResponse {
+            content,
+            usage,
+            model: Some(model),
+            provider: None,
+        })
     }
 
     async fn generate_embedding(&self, text: &str) -> Result<Vec<f32>> {
@@ -169,11 +250,27 @@ impl AiProvider for OllamaProvider {
     }
 }
 
+/// What an OpenAI-compatible server did with `json_schema` so far.
+const SCHEMA_SUPPORT_UNKNOWN: u8 = 0;
+const SCHEMA_SUPPORTED: u8 = 1;
+const SCHEMA_UNSUPPORTED: u8 = 2;
+
+/// Whether an error body blames the `json_schema` response format, rather
+/// than something else about the request.
+fn rejects_schema(error: &str) -> bool {
+    let error = error.to_ascii_lowercase();
+    error.contains("response_format") || error.contains("schema")
+}
+
 pub struct OpenAICompatibleProvider {
     client: reqwest::Client,
     base_url: String,
     api_key: Option<String>,
     model_name: Option<String>,
+    context: ContextWindows,
+    /// Learned from the first schema request: servers without support
+    /// reject the request, which is then retried in plain JSON mode.
+    schema_support: AtomicU8,
 }
 
 impl OpenAICompatibleProvider {
@@ -183,12 +280,48 @@ impl OpenAICompatibleProvider {
             base_url,
             api_key,
             model_name,
+            context: ContextWindows::default(),
+            schema_support: AtomicU8::new(SCHEMA_SUPPORT_UNKNOWN),
+        }
+    }
+
+    pub fn with_context_windows(mut self, context: ContextWindows) -> Self {
+        self.context = context;
+        self
+    }
+
+    async fn post_chat(&self, request: &ChatRequest) -> Result<reqwest::Response> {
+        let url = format!("{}/chat/completions", self.base_url);
+        let mut builder = self.client.post(&url);
+
+        if let Some(key) = &self.api_key {
+            builder = builder.bearer_auth(key);
+        }
+
+        let mut req_json = serde_json::to_value(request).unwrap();
+        if let Some(obj) = req_json.as_object_mut() {
+            // Inject model if missing and configured
+            if !obj.contains_key("model") {
+                if let Some(m) = &self.model_name {
+                    obj.insert("model".to_string(), serde_json::Value::String(m.clone()));
+                }
+            }
         }
+
+        builder
+            .json(&req_json)
+            .send()
+            .await
+            .map_err(|e| noodle_core::error::NoodleError::AI(e.to_string()))
     }
 }
 
 #[async_trait]
 impl AiProvider for OpenAICompatibleProvider {
+    async fn supports_structured_output(&self) -> bool {
+        self.schema_support.load(Ordering::Relaxed) == SCHEMA_SUPPORTED
+    }
+
     async fn list_models(&self) -> Result<Vec<String>> {
         let url = format!("{}/models", self.base_url); // usually /v1/models but base_url might include v1
         let mut builder = self.client.get(&url);
@@ -213,35 +346,62 @@ impl AiProvider for OpenAICompatibleProvider {
                     .filter_map(|m| m["id"].as_str().map(|s| s.to_string()))
                     .collect()
             })
-            .unwrap_or_else(Vec::new);
+            .unwrap_or_default();
 
         Ok(models)
     }
 
-    async fn chat_completion(&self, request: ChatRequest) -> Result<ChatResponse> {
-        let url = format!("{}/chat/completions", self.base_url);
-        let mut builder = self.client.post(&url);
-
-        if let Some(key) = &self.api_key {
-            builder = builder.bearer_auth(key);
+    async fn chat_completion(&self, mut request: ChatRequest) -> Result<ChatResponse> {
+        let model = request
+            .model
+            .as_deref()
+            .or(self.model_name.as_deref())
+            .unwrap_or_default();
+        fit_messages(&mut request.messages, self.context.for_model(model));
+
+        let wants_schema = matches!(
+            request.response_format,
+            Some(ResponseFormat::JsonSchema { .. })
+        );
+        if wants_schema && self.schema_support.load(Ordering::Relaxed) == SCHEMA_UNSUPPORTED {
+            request.response_format = Some(ResponseFormat::Json);
         }
-
-        let mut req_json = serde_json::to_value(&request).unwrap();
-        if let Some(obj) = req_json.as_object_mut() {
-            // Inject model if missing and configured
-            if !obj.contains_key("model") {
-                if let Some(m) = &self.model_name {
-                    obj.insert("model".to_string(), serde_json::Value::String(m.clone()));
+        let mut response = self.post_chat(&request).await?;
+        if matches!(
+            request.response_format,
+            Some(ResponseFormat::JsonSchema { .. })
+        ) {
+            let status = response.status();
+            if matches!(
+                status,
+                reqwest::StatusCode::BAD_REQUEST | reqwest::StatusCode::UNPROCESSABLE_ENTITY
+            ) {
+                let error = response
+                    .text()
+                    .await
+                    .map_err(|e| noodle_core::error::NoodleError::AI(e.to_string()))?;
+                // Auth, quota and other request errors say nothing about
+                // schema support, and stay errors.
+                if !rejects_schema(&error) {
+                    return Err(noodle_core::error::NoodleError::AI(format!(
+                        "AI server returned {}: {}",
+                        status, error
+                    )));
                 }
+                warn!(
+                    "Server rejected json_schema output ({}); using JSON mode",
+                    status
+                );
+                self.schema_support
+                    .store(SCHEMA_UNSUPPORTED, Ordering::Relaxed);
+                request.response_format = Some(ResponseFormat::Json);
+                response = self.post_chat(&request).await?;
+            } else if status.is_success() {
+                self.schema_support
+                    .store(SCHEMA_SUPPORTED, Ordering::Relaxed);
             }
         }
 
-        let response = builder
-            .json(&req_json)
-            .send()
-            .await
-            .map_err(|e| noodle_core::error::NoodleError::AI(e.to_string()))?;
-
         let body: serde_json::Value = response
             .json()
             .await
@@ -258,8 +418,18 @@ impl AiProvider for OpenAICompatibleProvider {
             prompt_tokens: body["usage"]["prompt_tokens"].as_u64().unwrap_or(0) as u32,
             completion_tokens: body["usage"]["completion_tokens"].as_u64().unwrap_or(0) as u32,
         };
-
-        Ok(ChatResponse { content, usage })
+        let model = body["model"]
+            .as_str()
+            .map(str::to_string)
+            .or_else(|| request.model.clone())
+            .or_else(|| self.model_name.clone());
+
+        Ok(ChatResponse {
+            content,
+            usage,
+            model,
+            provider: None,
+        })
     }
 
     async fn generate_embedding(&self, text: &str) -> Result<Vec<f32>> {
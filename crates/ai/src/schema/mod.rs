@@ -31,6 +31,12 @@ impl ExtractionValidator {
     }
 }
 
+impl Default for ExtractionValidator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 pub struct ExtractionPipeline {
     ai: Arc<dyn AiProvider>,
     validator: ExtractionValidator,
@@ -103,3 +109,54 @@ impl ExtractionPipeline {
         .await
     }
 }
+
+/// Checks model output against an arbitrary JSON schema, for providers that
+/// only promise syntactically valid JSON.
+pub struct SchemaValidator {
+    schema: JSONSchema,
+}
+
+impl SchemaValidator {
+    pub fn new(schema: &Value) -> Result<Self> {
+        let schema = JSONSchema::compile(schema)
+            .map_err(|e| NoodleError::Validation(format!("Invalid schema: {}", e)))?;
+        Ok(Self { schema })
+    }
+
+    /// What is wrong with `json`; empty when it matches.
+    pub fn errors(&self, json: &Value) -> Vec<String> {
+        match self.schema.validate(json) {
+            Ok(()) => Vec::new(),
+            Err(errors) => errors
+                .map(|e| format!("{}: {}", e.instance_path, e))
+                .collect(),
+        }
+    }
+}
+
+/// A request asking the model to turn `output` into JSON matching `schema`,
+/// given what was wrong with it.
+pub fn repair_request(schema: &Value, output: &str, problems: &[String]) -> ChatRequest {
+    ChatRequest {
+        messages: vec![
+            Message {
+                role: "system".into(),
+                content: format!(
+                    "You are a JSON repair specialist. Output corrected JSON only, matching this schema:\n{}",
+                    schema
+                ),
+            },
+            Message {
+                role: "user".into(),
+                content: format!(
+                    "Problems:\n- {}\n\nJSON to fix:\n{}",
+                    problems.join("\n- "),
+                    output
+                ),
+            },
+        ],
+        temperature: 0.0,
+        response_format: Some(ResponseFormat::Json),
+        model: None,
+    }
+}
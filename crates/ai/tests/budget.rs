@@ -0,0 +1,97 @@
+use ai::budget::{estimate_tokens, fit_messages, ContextWindows};
+use ai::provider::Message;
+
+fn message(role: &str, content: &str) -> Message {
+    Message {
+        role: role.into(),
+        content: content.into(),
+    }
+}
+
+/// A message of `tokens` estimated tokens, plus the framing of every message.
+fn sized(role: &str, fill: char, tokens: usize) -> Message {
+    message(role, &fill.to_string().repeat(tokens * 4))
+}
+
+fn total(messages: &[Message]) -> usize {
+    messages
+        .iter()
+        .map(|m| estimate_tokens(&m.content) + 4)
+        .sum()
+}
+
+#[test]
+fn tokens_are_four_characters_or_one_per_word() {
+    assert_eq!(estimate_tokens(""), 0);
+    assert_eq!(estimate_tokens("abcd"), 1);
+    assert_eq!(estimate_tokens("hello world"), 3);
+    assert_eq!(estimate_tokens("a b c d e"), 5);
+    assert_eq!(estimate_tokens("日本語のテキスト"), 2);
+}
+
+#[test]
+fn listed_models_cover_their_tags() {
+    let windows = ContextWindows::parse(4096, "llama3=8192, Qwen2.5 = 32768,").unwrap();
+    assert_eq!(windows.for_model("llama3"), 8192);
+    assert_eq!(windows.for_model("llama3:8b"), 8192);
+    assert_eq!(windows.for_model("QWEN2.5:14b"), 32768);
+    assert_eq!(windows.for_model("mistral"), 4096);
+    assert_eq!(ContextWindows::default().for_model("llama3"), 8192);
+
+    assert!(ContextWindows::parse(4096, "llama3").is_err());
+    assert!(ContextWindows::parse(4096, "llama3=big").is_err());
+}
+
+#[test]
+fn prompts_that_fit_are_left_alone() {
+    let mut messages = vec![sized("system", 's', 100), sized("user", 'u', 100)];
+    let before = messages.clone();
+    assert!(!fit_messages(&mut messages, 8192));
+    assert_eq!(messages.len(), 2);
+    assert_eq!(messages[1].content, before[1].content);
+}
+
+#[test]
+fn the_oldest_message_that_does_not_fit_is_trimmed_in_the_middle() {
+    // A 400 token window keeps 100 for the reply.
+    let mut messages = vec![
+        sized("system", 's', 100),
+        message("user", &format!("{}{}", "a".repeat(200), "z".repeat(200))),
+        sized("user", 'b', 100),
+    ];
+    assert!(fit_messages(&mut messages, 400));
+
+    assert_eq!(messages.len(), 3);
+    assert_eq!(messages[0].content, "s".repeat(400));
+    assert_eq!(messages[2].content, "b".repeat(400));
+    let trimmed = &messages[1].content;
+    assert!(trimmed.starts_with('a') && trimmed.ends_with('z'));
+    assert!(trimmed.contains("trimmed to fit"));
+    assert!(trimmed.len() < 400);
+    assert!(total(&messages) <= 300, "{}", total(&messages));
+}
+
+#[test]
+fn messages_before_the_trimmed_one_are_dropped() {
+    let mut messages = vec![
+        sized("system", 's', 100),
+        sized("user", 'o', 100),
+        sized("assistant", 'a', 100),
+        sized("user", 'n', 100),
+    ];
+    assert!(fit_messages(&mut messages, 400));
+
+    let roles: Vec<&str> = messages.iter().map(|m| m.role.as_str()).collect();
+    assert_eq!(roles, ["system", "assistant", "user"]);
+    assert!(messages[1].content.starts_with('a'));
+    assert_eq!(messages[2].content, "n".repeat(400));
+    assert!(total(&messages) <= 300);
+}
+
+#[test]
+fn instructions_are_kept_whole_even_when_nothing_else_fits() {
+    let mut messages = vec![sized("system", 's', 500), sized("user", 'u', 10)];
+    assert!(fit_messages(&mut messages, 400));
+    assert_eq!(messages.len(), 1);
+    assert_eq!(messages[0].content, "s".repeat(2000));
+}
@@ -0,0 +1,215 @@
+use ai::provider::failover::FailoverProvider;
+use ai::provider::{AiProvider, ChatRequest, ChatResponse, Usage};
+use async_trait::async_trait;
+use noodle_core::error::{NoodleError, Result};
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Answers with its name after `delay`, or fails while `failing` is set.
+struct Stub {
+    name: &'static str,
+    failing: bool,
+    delay: Duration,
+    calls: AtomicU32,
+}
+
+impl Stub {
+    fn ok(name: &'static str) -> Arc<Self> {
+        Self::new(name, false, Duration::ZERO)
+    }
+
+    fn failing(name: &'static str) -> Arc<Self> {
+        Self::new(name, true, Duration::ZERO)
+    }
+
+    fn slow(name: &'static str, delay: Duration) -> Arc<Self> {
+        Self::new(name, false, delay)
+    }
+
+    fn new(name: &'static str, failing: bool, delay: Duration) -> Arc<Self> {
+        Arc::new(Self {
+            name,
+            failing,
+            delay,
+            calls: AtomicU32::new(0),
+        })
+    }
+
+    fn calls(&self) -> u32 {
+        self.calls.load(Ordering::Relaxed)
+    }
+}
+
+#[async_trait]
+impl AiProvider for Stub {
+    async fn chat_completion(&self, _request: ChatRequest) -> Result<ChatResponse> {
+        self.calls.fetch_add(1, Ordering::Relaxed);
+        tokio::time::sleep(self.delay).await;
+        if self.failing {
+            return Err(NoodleError::AI(format!("{} is down", self.name)));
+        }
+        Ok(ChatResponse {
+            content: self.name.to_string(),
+            usage: Usage {
+                prompt_tokens: 1,
+                completion_tokens: 1,
+            },
+            model: None,
+            provider: None,
+        })
+    }
+
+    async fn generate_embedding(&self, _text: &str) -> Result<Vec<f32>> {
+        Ok(vec![self.calls() as f32])
+    }
+
+    async fn list_models(&self) -> Result<Vec<String>> {
+        Ok(vec![self.name.to_string()])
+    }
+}
+
+fn request() -> ChatRequest {
+    ChatRequest {
+        messages: Vec::new(),
+        temperature: 0.0,
+        response_format: None,
+        model: None,
+    }
+}
+
+const TIMEOUT: Duration = Duration::from_millis(100);
+
+#[tokio::test]
+async fn the_primary_answers_while_it_works() {
+    let (primary, fallback) = (Stub::ok("ollama"), Stub::ok("openai"));
+    let chain = FailoverProvider::new("ollama", primary.clone(), TIMEOUT)
+        .with_fallback("openai", fallback.clone());
+
+    let response = chain.chat_completion(request()).await.unwrap();
+    assert_eq!(response.content, "ollama");
+    assert_eq!(response.provider.as_deref(), Some("ollama"));
+    assert_eq!((primary.calls(), fallback.calls()), (1, 0));
+}
+
+#[tokio::test]
+async fn fallbacks_are_tried_in_the_order_added() {
+    let (primary, second, third) = (
+        Stub::failing("ollama"),
+        Stub::failing("lemonade"),
+        Stub::ok("openai"),
+    );
+    let chain = FailoverProvider::new("ollama", primary.clone(), TIMEOUT)
+        .with_fallback("lemonade", second.clone())
+        .with_fallback("openai", third.clone());
+
+    let response = chain.chat_completion(request()).await.unwrap();
+    assert_eq!(response.provider.as_deref(), Some("openai"));
+    assert_eq!((primary.calls(), second.calls(), third.calls()), (1, 1, 1));
+}
+
+#[tokio::test]
+async fn the_last_error_is_returned_when_every_provider_fails() {
+    let chain = FailoverProvider::new("ollama", Stub::failing("ollama"), TIMEOUT)
+        .with_fallback("openai", Stub::failing("openai"));
+
+    let error = chain.chat_completion(request()).await.unwrap_err();
+    assert!(error.to_string().contains("openai is down"), "{}", error);
+}
+
+#[tokio::test]
+async fn a_slow_primary_times_out_to_a_fallback() {
+    let (primary, fallback) = (
+        Stub::slow("ollama", Duration::from_secs(5)),
+        Stub::ok("openai"),
+    );
+    let chain = FailoverProvider::new("ollama", primary.clone(), TIMEOUT)
+        .with_fallback("openai", fallback.clone());
+
+    let response = chain.chat_completion(request()).await.unwrap();
+    assert_eq!(response.provider.as_deref(), Some("openai"));
+}
+
+#[tokio::test]
+async fn a_lone_provider_is_not_timed_out() {
+    let chain = FailoverProvider::new("ollama", Stub::slow("ollama", TIMEOUT * 3), TIMEOUT);
+
+    let response = chain.chat_completion(request()).await.unwrap();
+    assert_eq!(response.provider.as_deref(), Some("ollama"));
+}
+
+#[tokio::test]
+async fn a_provider_that_keeps_failing_is_skipped() {
+    let (primary, fallback) = (Stub::failing("ollama"), Stub::ok("openai"));
+    let chain = FailoverProvider::new("ollama", primary.clone(), TIMEOUT)
+        .with_fallback("openai", fallback.clone());
+
+    for _ in 0..3 {
+        chain.chat_completion(request()).await.unwrap();
+    }
+    assert_eq!(primary.calls(), 3);
+
+    // The third failure opened the primary's circuit.
+    let response = chain.chat_completion(request()).await.unwrap();
+    assert_eq!(response.provider.as_deref(), Some("openai"));
+    assert_eq!((primary.calls(), fallback.calls()), (3, 4));
+}
+
+#[tokio::test]
+async fn a_success_resets_the_failure_count() {
+    let primary = Arc::new(Flaky::default());
+    let chain = FailoverProvider::new("ollama", primary.clone(), TIMEOUT)
+        .with_fallback("openai", Stub::ok("openai"));
+
+    // fail, fail, succeed, fail, fail: never three failures in a row.
+    for _ in 0..5 {
+        chain.chat_completion(request()).await.unwrap();
+    }
+    chain.chat_completion(request()).await.unwrap();
+    assert_eq!(primary.calls.load(Ordering::Relaxed), 6);
+}
+
+#[tokio::test]
+async fn every_provider_is_tried_when_all_circuits_are_open() {
+    let (primary, fallback) = (Stub::failing("ollama"), Stub::failing("openai"));
+    let chain = FailoverProvider::new("ollama", primary.clone(), TIMEOUT)
+        .with_fallback("openai", fallback.clone());
+
+    for _ in 0..4 {
+        chain.chat_completion(request()).await.unwrap_err();
+    }
+    assert_eq!((primary.calls(), fallback.calls()), (4, 4));
+}
+
+#[tokio::test]
+async fn embeddings_and_models_come_from_the_primary() {
+    let chain = FailoverProvider::new("ollama", Stub::failing("ollama"), TIMEOUT)
+        .with_fallback("openai", Stub::ok("openai"));
+
+    assert_eq!(chain.list_models().await.unwrap(), vec!["ollama"]);
+    assert!(chain.generate_embedding("text").await.is_ok());
+}
+
+/// Fails every call but the third.
+#[derive(Default)]
+struct Flaky {
+    calls: AtomicU32,
+}
+
+#[async_trait]
+impl AiProvider for Flaky {
+    async fn chat_completion(&self, request: ChatRequest) -> Result<ChatResponse> {
+        if self.calls.fetch_add(1, Ordering::Relaxed) + 1 == 3 {
+            return Stub::ok("ollama").chat_completion(request).await;
+        }
+        Err(NoodleError::AI("flaky".into()))
+    }
+
+    async fn generate_embedding(&self, _text: &str) -> Result<Vec<f32>> {
+        Ok(Vec::new())
+    }
+
+    async fn list_models(&self) -> Result<Vec<String>> {
+        Ok(Vec::new())
+    }
+}
@@ -0,0 +1,599 @@
+use crate::error::{NoodleError, Result};
+use chrono::{DateTime, NaiveTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use validator::{Validate, ValidationError};
+
+/// User settings stored in `app_config`, one string value per field name.
+///
+/// The defaults here are the single source of truth for unset keys, and they
+/// decide how each stored string is parsed: a key whose default is a number or
+/// a boolean must hold one. Keys the app keeps for its own bookkeeping (e.g.
+/// `focus_until`, `digest_last_sent`) are not settings and are not listed.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Validate)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    #[validate(custom(function = "validate_provider"))]
+    pub provider_type: String,
+    #[validate(url)]
+    pub ollama_url: String,
+    #[validate(url)]
+    pub lemonade_url: String,
+    #[validate(url)]
+    pub foundry_url: String,
+    #[validate(url)]
+    pub openai_url: String,
+    pub model_name: Option<String>,
+    pub api_key: Option<String>,
+    /// Context window in tokens of models not listed in `model_context_windows`.
+    #[validate(range(min = 512, max = 2000000))]
+    pub context_window: u32,
+    /// Per-model context windows, as `model=tokens` pairs separated by commas.
+    #[validate(custom(function = "validate_context_windows"))]
+    pub model_context_windows: String,
+    /// Providers tried in order when `provider_type` fails or times out,
+    /// separated by commas, each optionally with its model as `provider=model`.
+    #[validate(custom(function = "validate_fallback_providers"))]
+    pub fallback_providers: String,
+    /// Seconds a chat call may take before the next provider is tried.
+    #[validate(range(min = 5, max = 600))]
+    pub provider_timeout_secs: u32,
+
+    /// Minutes between delta scans.
+    #[validate(range(min = 1, max = 1440))]
+    pub sync_interval: u64,
+    #[validate(range(min = 1, max = 3650))]
+    pub history_days: i64,
+    /// Per-folder `full`, `index_only` or `exclude`, as `folder=mode` pairs
+    /// separated by commas; folders not listed are `full`. Besides Inbox and
+    /// Sent Items, the folders listed here are the ones scanned.
+    #[validate(custom(function = "validate_folder_modes"))]
+    pub folder_modes: String,
+    pub confirm_exit: bool,
+    /// When launched at login, stay in the tray and start syncing right away.
+    pub start_minimized: bool,
+    /// Global shortcut that opens the quick search palette; empty disables it.
+    pub quick_search_shortcut: String,
+    #[validate(custom(function = "validate_timezone"))]
+    pub timezone: Option<String>,
+    /// Language of generated summaries and drafts, and how dates are written.
+    #[validate(custom(function = "validate_locale"))]
+    pub locale: String,
+    /// Check the release feed in the background and notify about new versions.
+    pub update_check: bool,
+    /// `stable` or `beta`; beta also offers pre-releases.
+    #[validate(custom(function = "validate_update_channel"))]
+    pub update_channel: String,
+    /// Keep a tamper-evident, append-only copy of every ingested email.
+    pub legal_hold: bool,
+    /// Analyze Sent Items for tone, commitments and unanswered questions.
+    pub self_insights: bool,
+    /// Let the LLM reorder the best search results by relevance.
+    pub rerank_results: bool,
+    /// Search again for every follow-up in an `ask_noodle` chat, rather than
+    /// answering from the emails the previous answer used.
+    pub chat_rerun_retrieval: bool,
+    /// Check that the emails an `ask_noodle` answer cites say what the
+    /// answer attributes to them, and flag the citations that don't.
+    pub chat_verify_citations: bool,
+
+    /// Jira site tickets are filed in, e.g. `https://example.atlassian.net`.
+    #[validate(url)]
+    pub jira_url: Option<String>,
+    /// Account the Jira API token belongs to.
+    pub jira_email: Option<String>,
+    pub jira_api_token: Option<String>,
+    /// Project tickets go to unless another key is given.
+    pub jira_project_key: Option<String>,
+    pub jira_issue_type: String,
+    /// Azure DevOps organization, e.g. `https://dev.azure.com/example`.
+    #[validate(url)]
+    pub azure_devops_url: Option<String>,
+    pub azure_devops_token: Option<String>,
+    /// Project work items go to unless another is given.
+    pub azure_devops_project: Option<String>,
+    pub azure_devops_work_item_type: String,
+
+    /// Serve the companion API on `127.0.0.1:companion_api_port`, for the
+    /// browser extension. Read at startup.
+    pub companion_api: bool,
+    #[validate(range(min = 1024, max = 65535))]
+    pub companion_api_port: u32,
+    /// Bearer token every companion API request has to carry.
+    pub companion_api_token: Option<String>,
+
+    /// Ask for Windows Hello, or the Windows password, at startup and after
+    /// `app_lock_idle_mins` without input; email bodies stay hidden until then.
+    pub app_lock: bool,
+    #[validate(range(min = 1, max = 240))]
+    pub app_lock_idle_mins: u32,
+
+    pub digest_notifications: bool,
+    #[validate(custom(function = "validate_time"))]
+    pub digest_time: String,
+    /// While Outlook reports the user out of office, digests list what they
+    /// missed and reminders about mail needing a reply wait for their return.
+    pub delegation_mode: bool,
+    /// Summarize the week's newsletters, which skip fact extraction, into
+    /// one roundup.
+    pub newsletter_roundup: bool,
+    /// Extract decisions and action items from the mail around calendar
+    /// meetings once they end.
+    pub meeting_follow_ups: bool,
+    #[validate(custom(function = "validate_time"))]
+    pub quiet_hours_start: Option<String>,
+    #[validate(custom(function = "validate_time"))]
+    pub quiet_hours_end: Option<String>,
+    pub quiet_hours_pause_sync: bool,
+    /// Hold LLM calls while the calendar shows the user in a meeting.
+    pub meeting_pause_ai: bool,
+    #[validate(custom(function = "validate_battery_mode"))]
+    pub battery_sync_mode: String,
+    #[validate(range(min = 1, max = 24))]
+    pub battery_interval_multiplier: u32,
+
+    #[validate(range(min = 1, max = 16))]
+    pub llm_max_concurrency: u32,
+    /// Body characters sent for extraction; longer bodies are truncated.
+    #[validate(range(min = 1000, max = 200000))]
+    pub max_extraction_body_chars: u32,
+    /// Body characters embedded for search; longer bodies are truncated.
+    #[validate(range(min = 500, max = 100000))]
+    pub max_embedding_body_chars: u32,
+    /// Attachments larger than this are listed but not stored; 0 stores none.
+    #[validate(range(max = 500))]
+    pub attachment_max_size_mb: u32,
+    /// MIME types of the attachments stored, comma separated. `type/*`
+    /// matches every subtype, and a pattern ending in `.*` every type
+    /// starting with it. Empty stores none.
+    #[validate(custom(function = "validate_mime_types"))]
+    pub attachment_mime_types: String,
+    pub low_impact_mode: bool,
+    #[validate(range(min = 1, max = 600))]
+    pub low_impact_emails_per_minute: u32,
+    /// Save and embed new mail right away, but leave fact extraction for
+    /// when the user has been idle for `idle_threshold_mins`.
+    pub idle_extraction: bool,
+    #[validate(range(min = 1, max = 240))]
+    pub idle_threshold_mins: u32,
+
+    #[validate(range(min = 0, max = 23))]
+    pub maintenance_hour: u32,
+    pub maintenance_vacuum: bool,
+    /// Snapshot the vector store during the daily maintenance window.
+    pub vector_snapshots: bool,
+    /// Vector store snapshots kept; older ones are deleted.
+    #[validate(range(min = 1, max = 30))]
+    pub vector_snapshot_retention: u32,
+
+    #[validate(range(min = 1, max = 64))]
+    pub sqlite_max_connections: u32,
+    #[validate(range(min = 1, max = 64))]
+    pub sqlite_read_connections: u32,
+    pub sqlite_busy_timeout_ms: u64,
+    #[validate(custom(function = "validate_synchronous"))]
+    pub sqlite_synchronous: String,
+}
+
+/// Settings that stay on this machine: never exported or saved in profiles,
+/// encrypted at rest, and only ever shown to the UI as [`SECRET_MASK`]. New
+/// credentials, tokens or secret URLs belong here.
+pub const SECRET_KEYS: &[&str] = &[
+    "api_key",
+    "jira_api_token",
+    "azure_devops_token",
+    "companion_api_token",
+];
+
+/// Shown in place of a secret that is set. Saving it back leaves the secret as is.
+pub const SECRET_MASK: &str = "••••••••";
+
+/// Bumped when a bundle's layout changes incompatibly.
+pub const SETTINGS_BUNDLE_VERSION: u32 = 1;
+
+/// Portable settings file produced by `export_settings`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SettingsBundle {
+    pub version: u32,
+    pub exported_at: DateTime<Utc>,
+    /// Stored string form of each setting, secrets excluded.
+    pub settings: BTreeMap<String, String>,
+}
+
+/// A named set of settings the user can switch to.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SettingsProfile {
+    pub name: String,
+    pub settings: BTreeMap<String, String>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Which attachments are stored and have their text extracted. Others are
+/// only listed, with the reason they were left out.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AttachmentPolicy {
+    pub max_bytes: i64,
+    /// Lowercase patterns, as in [`Config::attachment_mime_types`].
+    pub mime_types: Vec<String>,
+}
+
+impl AttachmentPolicy {
+    /// Why an attachment of this type and size isn't stored, or `None` when
+    /// it is.
+    pub fn skip_reason(&self, mime: &str, size_bytes: i64) -> Option<&'static str> {
+        let mime = mime.to_ascii_lowercase();
+        let allowed = self
+            .mime_types
+            .iter()
+            .any(|pattern| match pattern.strip_suffix('*') {
+                Some(prefix) => mime.starts_with(prefix),
+                None => mime == *pattern,
+            });
+        if !allowed {
+            Some("type_not_allowed")
+        } else if size_bytes > self.max_bytes {
+            Some("too_large")
+        } else {
+            None
+        }
+    }
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            provider_type: "ollama".into(),
+            ollama_url: "http://localhost:11434".into(),
+            lemonade_url: "http://localhost:8000/v1".into(),
+            foundry_url: "http://localhost:5000/v1".into(),
+            openai_url: "https://api.openai.com/v1".into(),
+            model_name: None,
+            api_key: None,
+            context_window: 8192,
+            model_context_windows: String::new(),
+            fallback_providers: String::new(),
+            provider_timeout_secs: 120,
+            sync_interval: 2,
+            history_days: 90,
+            folder_modes: String::new(),
+            confirm_exit: true,
+            start_minimized: false,
+            quick_search_shortcut: "CommandOrControl+Shift+N".into(),
+            timezone: None,
+            locale: "en-US".into(),
+            update_check: true,
+            update_channel: "stable".into(),
+            legal_hold: false,
+            self_insights: false,
+            rerank_results: false,
+            chat_rerun_retrieval: true,
+            chat_verify_citations: true,
+            jira_url: None,
+            jira_email: None,
+            jira_api_token: None,
+            jira_project_key: None,
+            jira_issue_type: "Task".into(),
+            azure_devops_url: None,
+            azure_devops_token: None,
+            azure_devops_project: None,
+            azure_devops_work_item_type: "Task".into(),
+            companion_api: false,
+            companion_api_port: 4870,
+            companion_api_token: None,
+            app_lock: false,
+            app_lock_idle_mins: 5,
+            digest_notifications: false,
+            digest_time: "08:00".into(),
+            delegation_mode: false,
+            newsletter_roundup: true,
+            meeting_follow_ups: true,
+            quiet_hours_start: None,
+            quiet_hours_end: None,
+            quiet_hours_pause_sync: false,
+            meeting_pause_ai: false,
+            battery_sync_mode: "throttle".into(),
+            battery_interval_multiplier: 4,
+            llm_max_concurrency: 2,
+            max_extraction_body_chars: 12000,
+            max_embedding_body_chars: 8000,
+            attachment_max_size_mb: 25,
+            attachment_mime_types: "application/pdf, text/*, application/msword, \
+                application/vnd.ms-excel, application/vnd.ms-powerpoint, \
+                application/vnd.openxmlformats-officedocument.*"
+                .into(),
+            low_impact_mode: false,
+            low_impact_emails_per_minute: 6,
+            idle_extraction: false,
+            idle_threshold_mins: 10,
+            maintenance_hour: 3,
+            maintenance_vacuum: false,
+            vector_snapshots: false,
+            vector_snapshot_retention: 3,
+            sqlite_max_connections: 8,
+            sqlite_read_connections: 4,
+            sqlite_busy_timeout_ms: 5000,
+            sqlite_synchronous: "normal".into(),
+        }
+    }
+}
+
+impl Config {
+    /// Builds a config from stored `app_config` rows. Unknown keys and values
+    /// that don't parse are skipped, leaving the default in place.
+    pub fn from_entries<'a>(entries: impl IntoIterator<Item = (&'a str, &'a str)>) -> Self {
+        let mut config = Self::default();
+        for (key, value) in entries {
+            let _ = config.apply_entry(key, value);
+        }
+        config
+    }
+
+    /// Sets one field from its stored string form. Fails on unknown keys and
+    /// on values of the wrong type; range checks are left to [`Validate`].
+    pub fn apply_entry(&mut self, key: &str, value: &str) -> Result<()> {
+        let mut fields = match serde_json::to_value(&*self) {
+            Ok(Value::Object(fields)) => fields,
+            _ => return Err(NoodleError::Internal("config is not an object".into())),
+        };
+        let value = value.trim();
+        let parsed = match Self::default_value(key)? {
+            Value::Bool(_) => match value {
+                "true" => Value::Bool(true),
+                "false" => Value::Bool(false),
+                _ => return Err(invalid(key, "expected true or false")),
+            },
+            Value::Number(_) => value
+                .parse::<serde_json::Number>()
+                .map(Value::Number)
+                .map_err(|_| invalid(key, "expected a number"))?,
+            // Optional text settings are unset when empty.
+            Value::Null if value.is_empty() => Value::Null,
+            _ => Value::String(value.to_string()),
+        };
+        fields.insert(key.to_string(), parsed);
+        *self = serde_json::from_value(Value::Object(fields))
+            .map_err(|e| invalid(key, &e.to_string()))?;
+        Ok(())
+    }
+
+    /// Every setting in its stored string form; unset optional values are empty.
+    pub fn to_entries(&self) -> Vec<(String, String)> {
+        let Ok(Value::Object(fields)) = serde_json::to_value(self) else {
+            return Vec::new();
+        };
+        fields
+            .into_iter()
+            .map(|(key, value)| {
+                let value = match value {
+                    Value::Null => String::new(),
+                    Value::String(s) => s,
+                    other => other.to_string(),
+                };
+                (key, value)
+            })
+            .collect()
+    }
+
+    /// [`Self::to_entries`] without [`SECRET_KEYS`], for bundles and profiles.
+    pub fn to_portable_entries(&self) -> BTreeMap<String, String> {
+        self.to_entries()
+            .into_iter()
+            .filter(|(key, _)| !SECRET_KEYS.contains(&key.as_str()))
+            .collect()
+    }
+
+    /// [`Self::fallback_providers`] as provider types with their models.
+    pub fn fallback_provider_list(&self) -> Vec<(String, Option<String>)> {
+        parse_fallback_providers(&self.fallback_providers)
+            .map(|(provider, model)| (provider.to_string(), model.map(str::to_string)))
+            .collect()
+    }
+
+    /// Which attachments are stored, from [`Self::attachment_max_size_mb`]
+    /// and [`Self::attachment_mime_types`].
+    pub fn attachment_policy(&self) -> AttachmentPolicy {
+        AttachmentPolicy {
+            max_bytes: i64::from(self.attachment_max_size_mb) * 1024 * 1024,
+            mime_types: self
+                .attachment_mime_types
+                .split(',')
+                .map(|t| t.trim().to_ascii_lowercase())
+                .filter(|t| !t.is_empty())
+                .collect(),
+        }
+    }
+
+    pub fn is_secret(key: &str) -> bool {
+        SECRET_KEYS.contains(&key)
+    }
+
+    /// A copy with every secret that is set replaced by [`SECRET_MASK`], for
+    /// showing to the UI.
+    pub fn masked(&self) -> Self {
+        let mut masked = self.clone();
+        for (key, value) in self.to_entries() {
+            if Self::is_secret(&key) && !value.is_empty() {
+                let _ = masked.apply_entry(&key, SECRET_MASK);
+            }
+        }
+        masked
+    }
+
+    pub fn is_setting(key: &str) -> bool {
+        Self::default_value(key).is_ok()
+    }
+
+    /// Runs the field validators and flattens failures into one message.
+    pub fn check(&self) -> Result<()> {
+        self.validate().map_err(|errors| {
+            let mut messages: Vec<String> = errors
+                .field_errors()
+                .iter()
+                .map(|(field, errors)| {
+                    let reason = errors
+                        .first()
+                        .and_then(|e| e.message.as_ref().map(|m| m.to_string()))
+                        .unwrap_or_else(|| match errors.first() {
+                            Some(e) if e.code == "range" => "out of range".to_string(),
+                            Some(e) if e.code == "url" => "not a valid URL".to_string(),
+                            _ => "invalid value".to_string(),
+                        });
+                    format!("{}: {}", field, reason)
+                })
+                .collect();
+            messages.sort();
+            NoodleError::Validation(messages.join("; "))
+        })
+    }
+
+    fn default_value(key: &str) -> Result<Value> {
+        match serde_json::to_value(Self::default()) {
+            Ok(Value::Object(mut fields)) => fields
+                .remove(key)
+                .ok_or_else(|| NoodleError::Validation(format!("Unknown setting: {}", key))),
+            _ => Err(NoodleError::Internal("config is not an object".into())),
+        }
+    }
+}
+
+fn invalid(key: &str, reason: &str) -> NoodleError {
+    NoodleError::Validation(format!("{}: {}", key, reason))
+}
+
+fn error(code: &'static str, message: &'static str) -> ValidationError {
+    ValidationError::new(code).with_message(message.into())
+}
+
+fn validate_provider(value: &str) -> std::result::Result<(), ValidationError> {
+    match value {
+        "ollama" | "lemonade" | "foundry" | "openai" => Ok(()),
+        _ => Err(error(
+            "provider",
+            "must be ollama, lemonade, foundry or openai",
+        )),
+    }
+}
+
+fn parse_fallback_providers(value: &str) -> impl Iterator<Item = (&str, Option<&str>)> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|e| !e.is_empty())
+        .map(|entry| match entry.split_once('=') {
+            Some((provider, model)) => (provider.trim(), Some(model.trim())),
+            None => (entry, None),
+        })
+}
+
+fn validate_fallback_providers(value: &str) -> std::result::Result<(), ValidationError> {
+    let valid = parse_fallback_providers(value).all(|(provider, model)| {
+        validate_provider(provider).is_ok() && model.is_none_or(|m| !m.is_empty())
+    });
+    if valid {
+        Ok(())
+    } else {
+        Err(error(
+            "fallback_providers",
+            "expected ollama, lemonade, foundry or openai, each optionally as provider=model",
+        ))
+    }
+}
+
+fn validate_context_windows(value: &str) -> std::result::Result<(), ValidationError> {
+    let valid = value
+        .split(',')
+        .map(str::trim)
+        .filter(|e| !e.is_empty())
+        .all(|entry| {
+            entry.split_once('=').is_some_and(|(model, tokens)| {
+                !model.trim().is_empty() && tokens.trim().parse::<u32>().is_ok()
+            })
+        });
+    if valid {
+        Ok(())
+    } else {
+        Err(error("context_windows", "expected model=tokens pairs"))
+    }
+}
+
+fn validate_mime_types(value: &str) -> std::result::Result<(), ValidationError> {
+    let valid = value
+        .split(',')
+        .map(str::trim)
+        .filter(|e| !e.is_empty())
+        .all(|pattern| {
+            pattern
+                .split_once('/')
+                .is_some_and(|(kind, subtype)| !kind.is_empty() && !subtype.is_empty())
+        });
+    if valid {
+        Ok(())
+    } else {
+        Err(error(
+            "attachment_mime_types",
+            "expected MIME types such as application/pdf or text/*",
+        ))
+    }
+}
+
+fn validate_folder_modes(value: &str) -> std::result::Result<(), ValidationError> {
+    let valid = value
+        .split(',')
+        .map(str::trim)
+        .filter(|e| !e.is_empty())
+        .all(|entry| {
+            entry.split_once('=').is_some_and(|(folder, mode)| {
+                !folder.trim().is_empty()
+                    && matches!(mode.trim(), "full" | "index_only" | "exclude")
+            })
+        });
+    if valid {
+        Ok(())
+    } else {
+        Err(error(
+            "folder_modes",
+            "expected folder=full|index_only|exclude pairs",
+        ))
+    }
+}
+
+fn validate_timezone(value: &str) -> std::result::Result<(), ValidationError> {
+    value
+        .parse::<chrono_tz::Tz>()
+        .map(|_| ())
+        .map_err(|_| error("timezone", "unknown timezone"))
+}
+
+fn validate_locale(value: &str) -> std::result::Result<(), ValidationError> {
+    crate::locale::UserLocale::find(value)
+        .map(|_| ())
+        .ok_or_else(|| error("locale", "unsupported locale"))
+}
+
+fn validate_update_channel(value: &str) -> std::result::Result<(), ValidationError> {
+    match value {
+        "stable" | "beta" => Ok(()),
+        _ => Err(error("update_channel", "must be stable or beta")),
+    }
+}
+
+fn validate_time(value: &str) -> std::result::Result<(), ValidationError> {
+    NaiveTime::parse_from_str(value, "%H:%M")
+        .map(|_| ())
+        .map_err(|_| error("time", "expected HH:MM"))
+}
+
+fn validate_battery_mode(value: &str) -> std::result::Result<(), ValidationError> {
+    match value {
+        "throttle" | "pause" | "ignore" => Ok(()),
+        _ => Err(error("battery_mode", "must be throttle, pause or ignore")),
+    }
+}
+
+fn validate_synchronous(value: &str) -> std::result::Result<(), ValidationError> {
+    match value.to_ascii_lowercase().as_str() {
+        "off" | "normal" | "full" | "extra" => Ok(()),
+        _ => Err(error("synchronous", "must be off, normal, full or extra")),
+    }
+}
@@ -0,0 +1,116 @@
+use crate::types::{BulkOperation, ChatToolCall, ProcessStage, ScanCheckpoint, SyncStatus};
+use serde::Serialize;
+
+/// Names the frontend listens for, one per [`AppEvent`] variant.
+pub const LOG: &str = "noodle://log";
+pub const SYNC_STATE: &str = "noodle://sync-state";
+pub const SCAN_PROGRESS: &str = "noodle://scan-progress";
+pub const EMAIL_PROGRESS: &str = "noodle://email-progress";
+pub const BULK_PROGRESS: &str = "noodle://bulk-progress";
+pub const CHAT_TOOL_CALL: &str = "noodle://chat-tool-call";
+pub const EXPERIMENT_PROGRESS: &str = "noodle://experiment-progress";
+
+/// Events raised by the backend crates for the UI. They don't know about
+/// windows or Tauri; a [`Notifier`] delivers them. The payload is the
+/// variant's content, serialized as is.
+#[derive(Debug, Clone, Serialize)]
+#[serde(untagged)]
+pub enum AppEvent {
+    /// A line for the log panel.
+    Log(LogEntry),
+    SyncState(SyncStatus),
+    /// How far the initial scan of a folder has come.
+    ScanProgress(ScanProgress),
+    /// A pipeline step finished for one email.
+    EmailProgress(EmailProgress),
+    /// How far a bulk action over selected emails has come.
+    BulkProgress(BulkProgress),
+    /// A research answer started or finished calling a tool.
+    ChatToolCall(ChatToolProgress),
+    /// How many emails an extraction experiment has run over.
+    ExperimentProgress(ExperimentProgress),
+}
+
+impl AppEvent {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Log(_) => LOG,
+            Self::SyncState(_) => SYNC_STATE,
+            Self::ScanProgress(_) => SCAN_PROGRESS,
+            Self::EmailProgress(_) => EMAIL_PROGRESS,
+            Self::BulkProgress(_) => BULK_PROGRESS,
+            Self::ChatToolCall(_) => CHAT_TOOL_CALL,
+            Self::ExperimentProgress(_) => EXPERIMENT_PROGRESS,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, strum_macros::Display)]
+#[serde(rename_all = "lowercase")]
+#[strum(serialize_all = "lowercase")]
+pub enum LogLevel {
+    Info,
+    Warn,
+    Error,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LogEntry {
+    pub message: String,
+    pub level: LogLevel,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ScanProgress {
+    pub folder: String,
+    pub processed: i64,
+    pub total: i64,
+    /// Whether a stopped scan can pick up from here.
+    pub resumable: bool,
+}
+
+impl From<&ScanCheckpoint> for ScanProgress {
+    fn from(checkpoint: &ScanCheckpoint) -> Self {
+        Self {
+            folder: checkpoint.folder.clone(),
+            processed: checkpoint.processed,
+            total: checkpoint.total,
+            resumable: checkpoint.processed < checkpoint.total,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct EmailProgress {
+    pub email_id: i64,
+    pub stage: ProcessStage,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BulkProgress {
+    pub operation: BulkOperation,
+    pub done: usize,
+    pub total: usize,
+    pub failed: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ChatToolProgress {
+    pub session_id: i64,
+    pub call: ChatToolCall,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ExperimentProgress {
+    pub name: String,
+    pub done: usize,
+    pub total: usize,
+}
+
+/// Where backend crates send [`AppEvent`]s, so they build without a UI
+/// framework. The app's is the Tauri event bus in the `ui` crate; other
+/// front ends bring their own. Delivery is best effort, so notifying never
+/// fails.
+pub trait Notifier: Send + Sync {
+    fn notify(&self, event: AppEvent);
+}
@@ -0,0 +1,76 @@
+//! Emails and facts for the other crates' tests, behind the `test-util`
+//! feature. Tests override the fields they care about with struct update
+//! syntax.
+
+use crate::text::SanitizedText;
+use crate::types::{
+    Email, EmailFact, Intent, PrimaryType, ProjectInfo, Provenance, Sentiment, Urgency, WaitingOn,
+};
+use chrono::Utc;
+use uuid::Uuid;
+
+/// An unsaved Inbox email from alice@example.com, received now.
+pub fn email(entry_id: &str) -> Email {
+    let now = Utc::now();
+    Email {
+        id: 0,
+        store_id: "store".into(),
+        entry_id: entry_id.into(),
+        conversation_id: None,
+        folder: "Inbox".into(),
+        subject: "Status".into(),
+        sender: "alice@example.com".into(),
+        to: "me@example.com".into(),
+        cc: None,
+        bcc: None,
+        sent_at: now,
+        received_at: now,
+        body_text: String::new(),
+        body_html: None,
+        importance: 1,
+        categories: None,
+        flags: None,
+        internet_message_id: None,
+        list_unsubscribe: None,
+        last_indexed_at: now,
+        hash: entry_id.into(),
+        excluded_reason: None,
+    }
+}
+
+/// Low-urgency facts filing the email under Apollo, needing no response.
+pub fn facts(email_id: i64) -> EmailFact {
+    let now = Utc::now();
+    EmailFact {
+        email_id,
+        primary_type: PrimaryType::Update,
+        intent: Intent::Inform,
+        client_or_project: ProjectInfo {
+            name: "Apollo".into(),
+            confidence: 0.9,
+        },
+        sentiment: Sentiment::Neutral,
+        urgency: Urgency::Low,
+        due_by: None,
+        due_by_raw: None,
+        needs_response: false,
+        waiting_on: WaitingOn::None,
+        summary: SanitizedText::new("Summary"),
+        key_points: vec![],
+        risks: vec![],
+        issues: vec![],
+        blockers: vec![],
+        open_questions: vec![],
+        answered_questions: vec![],
+        confidence: 0.9,
+        review_reason: None,
+        provenance: Provenance {
+            model: "test".into(),
+            provider: "test".into(),
+            prompt_id: Uuid::nil(),
+            created_at: now,
+            truncated: false,
+        },
+        created_at: now,
+    }
+}
@@ -1,2 +1,10 @@
+pub mod config;
 pub mod error;
+pub mod events;
+#[cfg(feature = "test-util")]
+pub mod fixtures;
+pub mod locale;
+pub mod metrics;
+pub mod text;
+pub mod time;
 pub mod types;
@@ -0,0 +1,133 @@
+use chrono::{DateTime, TimeZone};
+
+/// `app_config` key holding the user's locale tag (e.g. "de-DE").
+pub const LOCALE_CONFIG_KEY: &str = "locale";
+
+/// How one supported locale writes dates, and the language generated text
+/// is written in.
+#[derive(Debug)]
+pub struct LocaleInfo {
+    pub tag: &'static str,
+    /// English name of the language, as given to the model in prompts.
+    pub language: &'static str,
+    /// `strftime` pattern of a short numeric date.
+    pub date_format: &'static str,
+    /// `strftime` pattern of a short numeric date with the time of day.
+    pub datetime_format: &'static str,
+}
+
+/// Locales the app can be switched to; the first is the default.
+pub const SUPPORTED_LOCALES: &[LocaleInfo] = &[
+    LocaleInfo {
+        tag: "en-US",
+        language: "English",
+        date_format: "%m/%d/%Y",
+        datetime_format: "%m/%d/%Y %I:%M %p",
+    },
+    LocaleInfo {
+        tag: "en-GB",
+        language: "English",
+        date_format: "%d/%m/%Y",
+        datetime_format: "%d/%m/%Y %H:%M",
+    },
+    LocaleInfo {
+        tag: "de-DE",
+        language: "German",
+        date_format: "%d.%m.%Y",
+        datetime_format: "%d.%m.%Y %H:%M",
+    },
+    LocaleInfo {
+        tag: "fr-FR",
+        language: "French",
+        date_format: "%d/%m/%Y",
+        datetime_format: "%d/%m/%Y %H:%M",
+    },
+    LocaleInfo {
+        tag: "es-ES",
+        language: "Spanish",
+        date_format: "%d/%m/%Y",
+        datetime_format: "%d/%m/%Y %H:%M",
+    },
+    LocaleInfo {
+        tag: "it-IT",
+        language: "Italian",
+        date_format: "%d/%m/%Y",
+        datetime_format: "%d/%m/%Y %H:%M",
+    },
+    LocaleInfo {
+        tag: "nl-NL",
+        language: "Dutch",
+        date_format: "%d-%m-%Y",
+        datetime_format: "%d-%m-%Y %H:%M",
+    },
+    LocaleInfo {
+        tag: "pt-BR",
+        language: "Portuguese",
+        date_format: "%d/%m/%Y",
+        datetime_format: "%d/%m/%Y %H:%M",
+    },
+    LocaleInfo {
+        tag: "ja-JP",
+        language: "Japanese",
+        date_format: "%Y/%m/%d",
+        datetime_format: "%Y/%m/%d %H:%M",
+    },
+];
+
+/// The locale generated content and dates are presented in. Dates are still
+/// stored and exchanged as RFC 3339; this only affects what people read.
+#[derive(Debug, Clone, Copy)]
+pub struct UserLocale(&'static LocaleInfo);
+
+impl Default for UserLocale {
+    fn default() -> Self {
+        Self(&SUPPORTED_LOCALES[0])
+    }
+}
+
+impl UserLocale {
+    /// Resolves a configured tag, ignoring case and `_` vs `-`; unset, empty
+    /// or unsupported tags fall back to the default.
+    pub fn from_config(value: Option<&str>) -> Self {
+        value.and_then(Self::find).unwrap_or_default()
+    }
+
+    pub fn find(tag: &str) -> Option<Self> {
+        let tag = tag.trim().replace('_', "-");
+        SUPPORTED_LOCALES
+            .iter()
+            .find(|l| l.tag.eq_ignore_ascii_case(&tag))
+            .map(Self)
+    }
+
+    pub fn tag(&self) -> &'static str {
+        self.0.tag
+    }
+
+    pub fn language(&self) -> &'static str {
+        self.0.language
+    }
+
+    pub fn format_date<Tz: TimeZone>(&self, dt: &DateTime<Tz>) -> String
+    where
+        Tz::Offset: std::fmt::Display,
+    {
+        dt.format(self.0.date_format).to_string()
+    }
+
+    pub fn format_datetime<Tz: TimeZone>(&self, dt: &DateTime<Tz>) -> String
+    where
+        Tz::Offset: std::fmt::Display,
+    {
+        dt.format(self.0.datetime_format).to_string()
+    }
+
+    /// Appended to prompts whose output the user reads, so summaries and
+    /// drafts come back in their language.
+    pub fn prompt_instruction(&self) -> String {
+        format!(
+            "Write all text meant for the reader in {}.",
+            self.0.language
+        )
+    }
+}
@@ -0,0 +1,120 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::future::Future;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tracing::Instrument;
+
+/// Timings kept per stage for the percentiles; older ones only count
+/// towards the totals.
+const RECENT_SAMPLES: usize = 500;
+
+/// A step of getting an email from Outlook into search, timed separately so
+/// the slow one stands out.
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, strum_macros::Display,
+)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum Stage {
+    /// Opening one item through Outlook COM.
+    OutlookFetch,
+    SqliteSave,
+    /// One extraction request to the LLM; a repair pass counts as another.
+    Extraction,
+    /// One embedding request.
+    Embedding,
+    QdrantUpsert,
+}
+
+/// How long a stage took since the app started.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StageLatency {
+    pub stage: Stage,
+    pub count: u64,
+    pub mean_ms: f64,
+    /// Over the last [`RECENT_SAMPLES`] runs.
+    pub p50_ms: f64,
+    pub p95_ms: f64,
+    pub max_ms: f64,
+}
+
+#[derive(Default)]
+struct Samples {
+    count: u64,
+    total: Duration,
+    max: Duration,
+    recent: VecDeque<Duration>,
+}
+
+/// Collects stage timings in memory. Shared by the Outlook client and the
+/// pipeline; cheap enough to record on every call.
+#[derive(Default)]
+pub struct StageMetrics {
+    samples: Mutex<HashMap<Stage, Samples>>,
+}
+
+impl StageMetrics {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn record(&self, stage: Stage, elapsed: Duration) {
+        let mut samples = self.samples.lock().unwrap_or_else(|e| e.into_inner());
+        let samples = samples.entry(stage).or_default();
+        samples.count += 1;
+        samples.total += elapsed;
+        samples.max = samples.max.max(elapsed);
+        if samples.recent.len() == RECENT_SAMPLES {
+            samples.recent.pop_front();
+        }
+        samples.recent.push_back(elapsed);
+    }
+
+    /// Runs `work` in a span named after `stage` and records how long it
+    /// took, whether it failed or not.
+    pub async fn time<F: Future>(&self, stage: Stage, work: F) -> F::Output {
+        let started = Instant::now();
+        let output = work.instrument(tracing::debug_span!("stage", %stage)).await;
+        let elapsed = started.elapsed();
+        tracing::debug!("{} took {:?}", stage, elapsed);
+        self.record(stage, elapsed);
+        output
+    }
+
+    /// Every stage recorded so far, in pipeline order.
+    pub fn snapshot(&self) -> Vec<StageLatency> {
+        let samples = self.samples.lock().unwrap_or_else(|e| e.into_inner());
+        let mut latencies: Vec<StageLatency> = samples
+            .iter()
+            .map(|(stage, samples)| {
+                let mut recent: Vec<Duration> = samples.recent.iter().copied().collect();
+                recent.sort();
+                StageLatency {
+                    stage: *stage,
+                    count: samples.count,
+                    mean_ms: millis(samples.total) / samples.count as f64,
+                    p50_ms: percentile(&recent, 0.5),
+                    p95_ms: percentile(&recent, 0.95),
+                    max_ms: millis(samples.max),
+                }
+            })
+            .collect();
+        latencies.sort_by_key(|latency| latency.stage as u8);
+        latencies
+    }
+}
+
+fn millis(duration: Duration) -> f64 {
+    duration.as_secs_f64() * 1000.0
+}
+
+/// Nearest-rank percentile of sorted `samples`, in milliseconds; 0 when
+/// there are none.
+pub fn percentile(samples: &[Duration], p: f64) -> f64 {
+    if samples.is_empty() {
+        return 0.0;
+    }
+    let rank = ((samples.len() as f64 * p).ceil() as usize).clamp(1, samples.len());
+    millis(samples[rank - 1])
+}
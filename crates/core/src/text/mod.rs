@@ -0,0 +1,269 @@
+use serde::{Deserialize, Deserializer, Serialize};
+use std::fmt;
+use std::ops::Deref;
+
+/// Longest text kept when the caller sets no tighter limit.
+pub const MAX_TEXT_CHARS: usize = 4000;
+
+/// Elements dropped together with their content.
+const HIDDEN_ELEMENTS: &[&str] = &["script", "style", "iframe", "object"];
+/// Tag names stripped besides [`HIDDEN_ELEMENTS`]: the HTML elements found in
+/// mail and in what models echo back of it.
+const HTML_ELEMENTS: &[&str] = &[
+    "a",
+    "abbr",
+    "address",
+    "area",
+    "article",
+    "aside",
+    "audio",
+    "b",
+    "base",
+    "bdi",
+    "bdo",
+    "blockquote",
+    "body",
+    "br",
+    "button",
+    "canvas",
+    "caption",
+    "center",
+    "cite",
+    "code",
+    "col",
+    "colgroup",
+    "data",
+    "dd",
+    "del",
+    "details",
+    "dfn",
+    "dialog",
+    "div",
+    "dl",
+    "dt",
+    "em",
+    "embed",
+    "fieldset",
+    "figcaption",
+    "figure",
+    "font",
+    "footer",
+    "form",
+    "frame",
+    "frameset",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "head",
+    "header",
+    "hr",
+    "html",
+    "i",
+    "img",
+    "input",
+    "ins",
+    "kbd",
+    "label",
+    "legend",
+    "li",
+    "link",
+    "main",
+    "map",
+    "mark",
+    "marquee",
+    "meta",
+    "meter",
+    "nav",
+    "noscript",
+    "ol",
+    "optgroup",
+    "option",
+    "output",
+    "p",
+    "param",
+    "picture",
+    "pre",
+    "progress",
+    "q",
+    "s",
+    "samp",
+    "section",
+    "select",
+    "small",
+    "source",
+    "span",
+    "strike",
+    "strong",
+    "sub",
+    "summary",
+    "sup",
+    "svg",
+    "table",
+    "tbody",
+    "td",
+    "template",
+    "textarea",
+    "tfoot",
+    "th",
+    "thead",
+    "time",
+    "title",
+    "tr",
+    "track",
+    "tt",
+    "u",
+    "ul",
+    "var",
+    "video",
+    "wbr",
+];
+
+/// Model-written text that is safe to store and show: HTML tags, script and
+/// style blocks, control characters and bidirectional overrides are removed,
+/// and the length is capped. It is plain text, never markup, so views must
+/// still render it as text rather than HTML. Deserializing sanitizes as well,
+/// so values read back from storage keep the guarantee.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, Serialize)]
+#[serde(transparent)]
+pub struct SanitizedText(String);
+
+impl SanitizedText {
+    pub fn new(text: &str) -> Self {
+        Self::with_limit(text, MAX_TEXT_CHARS)
+    }
+
+    /// Sanitizes `text` and cuts it to `max_chars`, marking the cut with `…`.
+    pub fn with_limit(text: &str, max_chars: usize) -> Self {
+        let text = strip_markup(text);
+        let text: String = text
+            .chars()
+            .filter(|c| !is_unsafe_char(*c))
+            .collect::<String>()
+            .trim()
+            .to_string();
+        if text.chars().count() <= max_chars {
+            return Self(text);
+        }
+        let mut cut: String = text.chars().take(max_chars.saturating_sub(1)).collect();
+        cut.truncate(cut.trim_end().len());
+        cut.push('…');
+        Self(cut)
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    pub fn into_string(self) -> String {
+        self.0
+    }
+}
+
+impl Deref for SanitizedText {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for SanitizedText {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl From<&str> for SanitizedText {
+    fn from(text: &str) -> Self {
+        Self::new(text)
+    }
+}
+
+impl From<String> for SanitizedText {
+    fn from(text: String) -> Self {
+        Self::new(&text)
+    }
+}
+
+impl<'de> Deserialize<'de> for SanitizedText {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer).map(|text| Self::new(&text))
+    }
+}
+
+/// Control characters other than line breaks and tabs, and the bidi
+/// controls that can make text display differently from how it reads.
+fn is_unsafe_char(c: char) -> bool {
+    (c.is_control() && c != '\n' && c != '\t')
+        || matches!(c, '\u{202A}'..='\u{202E}' | '\u{2066}'..='\u{2069}')
+}
+
+/// Drops [`HIDDEN_ELEMENTS`] with their content and the tags of other
+/// known elements, keeping the text between tags. Office's namespaced tags
+/// (`<o:p>`), comments, doctypes and processing instructions go too. Any
+/// other `<`, as in `a < b`, `x<y and z>w` or `<jo@example.com>`, is text.
+fn strip_markup(text: &str) -> String {
+    // ASCII lowercasing keeps byte offsets, so indexes carry over to `text`.
+    let lowered = text.to_ascii_lowercase();
+    let mut out = String::with_capacity(text.len());
+    let mut pos = 0;
+    while let Some(found) = lowered[pos..].find('<') {
+        let start = pos + found;
+        out.push_str(&text[pos..start]);
+        let len = lowered[start..].find('>');
+        let Some(len) = len.filter(|len| is_tag(&lowered[start + 1..start + len])) else {
+            out.push('<');
+            pos = start + 1;
+            continue;
+        };
+        let tag = &lowered[start + 1..start + len];
+        pos = start + len + 1;
+        if let Some(element) = HIDDEN_ELEMENTS
+            .iter()
+            .find(|e| tag_name(tag) == Some(**e) && !tag.starts_with('/'))
+        {
+            let close = format!("</{}", element);
+            pos = match lowered[pos..].find(&close) {
+                Some(end) => {
+                    let after = pos + end;
+                    lowered[after..]
+                        .find('>')
+                        .map_or(text.len(), |gt| after + gt + 1)
+                }
+                None => text.len(),
+            };
+        }
+    }
+    out.push_str(&text[pos..]);
+    out
+}
+
+/// Whether `tag`, the text between `<` and `>`, is markup.
+fn is_tag(tag: &str) -> bool {
+    // Comments, Outlook's conditional comments, doctypes and XML declarations.
+    if ["!--", "![", "!doctype", "?xml"]
+        .iter()
+        .any(|prefix| tag.starts_with(prefix))
+    {
+        return true;
+    }
+    tag_name(tag).is_some_and(|name| {
+        name.contains(':') || HIDDEN_ELEMENTS.contains(&name) || HTML_ELEMENTS.contains(&name)
+    })
+}
+
+/// The element name of an opening or closing tag, when it is followed by
+/// nothing, whitespace or `/`, as in a well-formed tag.
+fn tag_name(tag: &str) -> Option<&str> {
+    let tag = tag.strip_prefix('/').unwrap_or(tag);
+    let end = tag
+        .find(|c: char| !(c.is_ascii_alphanumeric() || matches!(c, ':' | '-')))
+        .unwrap_or(tag.len());
+    let (name, rest) = tag.split_at(end);
+    let well_formed = name.starts_with(|c: char| c.is_ascii_alphabetic())
+        && (rest.is_empty() || rest.starts_with(|c: char| c.is_whitespace() || c == '/'));
+    well_formed.then_some(name)
+}
@@ -0,0 +1,97 @@
+use chrono::{DateTime, Duration, FixedOffset, Local, NaiveDate, Offset, TimeZone, Utc};
+use chrono_tz::Tz;
+
+/// `app_config` key holding the user's IANA timezone name (e.g. "Europe/Berlin").
+pub const TIMEZONE_CONFIG_KEY: &str = "timezone";
+
+/// Timezone used for displaying timestamps and for calendar-day logic
+/// ("due today", daily digests). Timestamps are stored in UTC; this only
+/// decides how they are presented and where a day begins and ends.
+#[derive(Debug, Clone, Copy, Default)]
+pub enum UserTimezone {
+    /// Follow the operating system's timezone.
+    #[default]
+    System,
+    Named(Tz),
+}
+
+impl UserTimezone {
+    /// Resolves the configured IANA name; unset, empty or unknown names fall
+    /// back to the system timezone.
+    pub fn from_config(value: Option<&str>) -> Self {
+        value
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+            .and_then(|s| s.parse::<Tz>().ok())
+            .map(Self::Named)
+            .unwrap_or_default()
+    }
+
+    pub fn name(&self) -> String {
+        match self {
+            Self::System => "system".to_string(),
+            Self::Named(tz) => tz.name().to_string(),
+        }
+    }
+
+    /// Converts a UTC instant to the user's wall-clock time.
+    pub fn to_local(&self, utc: DateTime<Utc>) -> DateTime<FixedOffset> {
+        match self {
+            Self::System => utc.with_timezone(&Local).fixed_offset(),
+            Self::Named(tz) => utc.with_timezone(tz).fixed_offset(),
+        }
+    }
+
+    /// The user's calendar date at `utc`.
+    pub fn local_date(&self, utc: DateTime<Utc>) -> NaiveDate {
+        self.to_local(utc).date_naive()
+    }
+
+    /// UTC bounds `[start, end)` of the local calendar day containing `utc`.
+    /// Days are not always 24h long across DST transitions, so both ends are
+    /// resolved separately.
+    pub fn day_bounds(&self, utc: DateTime<Utc>) -> (DateTime<Utc>, DateTime<Utc>) {
+        let date = self.local_date(utc);
+        let next = date + Duration::days(1);
+        (self.start_of_day(date), self.start_of_day(next))
+    }
+
+    /// UTC instant of local midnight on `date`. When midnight is skipped by a
+    /// DST change, the first valid instant after it is used.
+    pub fn start_of_day(&self, date: NaiveDate) -> DateTime<Utc> {
+        let midnight = date.and_hms_opt(0, 0, 0).unwrap_or_default();
+        let offset = match self {
+            Self::System => resolve_offset(&Local, midnight),
+            Self::Named(tz) => resolve_offset(tz, midnight),
+        };
+        (midnight - Duration::seconds(offset.local_minus_utc() as i64)).and_utc()
+    }
+}
+
+fn resolve_offset<T: TimeZone>(tz: &T, local: chrono::NaiveDateTime) -> FixedOffset {
+    tz.offset_from_local_datetime(&local)
+        .earliest()
+        .or_else(|| {
+            tz.offset_from_local_datetime(&(local + Duration::hours(1)))
+                .earliest()
+        })
+        .map(|o| o.fix())
+        .unwrap_or_else(|| Utc.fix())
+}
+
+/// Rewrites the UTC RFC 3339 timestamp stored under each of `keys` to the
+/// user's local offset. Values that are missing or not timestamps are left as-is.
+pub fn localize_fields(value: &mut serde_json::Value, keys: &[&str], tz: UserTimezone) {
+    for key in keys {
+        let Some(field) = value.get_mut(*key) else {
+            continue;
+        };
+        let local = field
+            .as_str()
+            .and_then(|s| DateTime::parse_from_rfc3339(s).ok())
+            .map(|dt| tz.to_local(dt.with_timezone(&Utc)).to_rfc3339());
+        if let Some(local) = local {
+            *field = serde_json::Value::String(local);
+        }
+    }
+}
@@ -1,3 +1,5 @@
+use crate::metrics::StageLatency;
+use crate::text::SanitizedText;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
@@ -22,6 +24,8 @@ pub struct Email {
     pub categories: Option<String>,
     pub flags: Option<i32>,
     pub internet_message_id: Option<String>,
+    /// The `List-Unsubscribe` header of mailing list mail.
+    pub list_unsubscribe: Option<String>,
     pub last_indexed_at: DateTime<Utc>,
     pub hash: String,
     pub excluded_reason: Option<String>,
@@ -35,7 +39,11 @@ pub struct Attachment {
     pub mime: String,
     pub size_bytes: i64,
     pub extracted_text: Option<String>,
+    /// SHA-256 of the content, which names its file; empty when not stored.
     pub hash: String,
+    /// Why the attachment policy left it out: `too_large` or
+    /// `type_not_allowed`.
+    pub skipped_reason: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -47,16 +55,21 @@ pub struct EmailFact {
     pub sentiment: Sentiment,
     pub urgency: Urgency,
     pub due_by: Option<DateTime<Utc>>,
+    pub due_by_raw: Option<String>,
     pub needs_response: bool,
     pub waiting_on: WaitingOn,
-    pub summary: String,
-    pub key_points: Vec<String>,
+    pub summary: SanitizedText,
+    pub key_points: Vec<SanitizedText>,
     pub risks: Vec<Risk>,
     pub issues: Vec<Issue>,
     pub blockers: Vec<Blocker>,
     pub open_questions: Vec<OpenQuestion>,
     pub answered_questions: Vec<AnsweredQuestion>,
     pub confidence: f32,
+    /// Set when the facts need checking by hand, e.g. because the email
+    /// looks like it tries to steer the model.
+    #[serde(default)]
+    pub review_reason: Option<String>,
     pub provenance: Provenance,
     pub created_at: DateTime<Utc>,
 }
@@ -118,7 +131,18 @@ pub enum WaitingOn {
     None,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, strum_macros::Display)]
+#[derive(
+    Debug,
+    Clone,
+    Serialize,
+    Deserialize,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    strum_macros::Display,
+    strum_macros::EnumString,
+)]
 #[serde(rename_all = "snake_case")]
 #[strum(serialize_all = "snake_case")]
 pub enum Severity {
@@ -129,44 +153,169 @@ pub enum Severity {
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Risk {
-    pub title: String,
-    pub details: String,
-    pub owner: Option<String>,
+    pub title: SanitizedText,
+    pub details: SanitizedText,
+    pub owner: Option<SanitizedText>,
     pub severity: Severity,
     pub confidence: f32,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Issue {
-    pub title: String,
-    pub details: String,
-    pub owner: Option<String>,
+    pub title: SanitizedText,
+    pub details: SanitizedText,
+    pub owner: Option<SanitizedText>,
     pub severity: Severity,
     pub confidence: f32,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Blocker {
+    pub title: SanitizedText,
+    pub details: SanitizedText,
+    pub owner: Option<SanitizedText>,
+    pub severity: Severity,
+    pub confidence: f32,
+}
+
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    Serialize,
+    Deserialize,
+    PartialEq,
+    Eq,
+    strum_macros::Display,
+    strum_macros::EnumString,
+)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum IssueKind {
+    Risk,
+    Issue,
+    Blocker,
+}
+
+/// Where a canonical issue stands: raised, being handled (someone committed
+/// to a fix, or it was last reported at a lower severity), or reported
+/// handled after its last mention.
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    Serialize,
+    Deserialize,
+    PartialEq,
+    Eq,
+    strum_macros::Display,
+    strum_macros::EnumString,
+)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum IssueStatus {
+    Open,
+    Mitigated,
+    Resolved,
+}
+
+/// One email's risk, issue or blocker, as grouped into an [`IssueCluster`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IssueMention {
+    pub email_id: i64,
+    pub kind: IssueKind,
+    /// Index in the email's risks, issues or blockers.
+    pub position: usize,
     pub title: String,
     pub details: String,
-    pub owner: Option<String>,
     pub severity: Severity,
-    pub confidence: f32,
+    pub project: Option<String>,
+    pub received_at: DateTime<Utc>,
+}
+
+/// Risks, issues and blockers from different emails that describe the same
+/// underlying problem. Named after its first mention.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IssueCluster {
+    pub id: i64,
+    pub kind: IssueKind,
+    pub title: String,
+    pub details: String,
+    /// The highest severity any mention gave it.
+    pub severity: Severity,
+    pub status: IssueStatus,
+    pub projects: Vec<String>,
+    pub first_seen: DateTime<Utc>,
+    pub last_seen: DateTime<Utc>,
+    pub mitigated_at: Option<DateTime<Utc>>,
+    pub resolved_at: Option<DateTime<Utc>>,
+    /// Oldest first.
+    pub mentions: Vec<IssueMention>,
+}
+
+/// How long a project's canonical issues stay open. An issue raised in
+/// several projects counts for each of them.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProjectIssueMetrics {
+    pub project: String,
+    pub open: u32,
+    pub mitigated: u32,
+    pub resolved: u32,
+    /// Mean days from first mention to resolution, over resolved issues.
+    pub mean_days_to_resolve: Option<f64>,
+    /// Days the oldest unresolved issue has been open.
+    pub oldest_open_days: Option<f64>,
+}
+
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    Serialize,
+    Deserialize,
+    PartialEq,
+    Eq,
+    strum_macros::Display,
+    strum_macros::EnumString,
+)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum TicketTracker {
+    Jira,
+    AzureDevops,
+}
+
+/// A ticket filed from an extracted risk, issue or blocker.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IssueTicket {
+    pub id: i64,
+    pub email_id: i64,
+    pub kind: IssueKind,
+    /// Index in the email's risks, issues or blockers.
+    pub position: usize,
+    pub tracker: TicketTracker,
+    /// The remote key, e.g. `OPS-42`, or the work item id.
+    pub ticket_key: String,
+    pub url: String,
+    pub created_at: DateTime<Utc>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct OpenQuestion {
-    pub question: String,
-    pub asked_by: Option<String>,
-    pub owner: Option<String>,
+    pub question: SanitizedText,
+    pub asked_by: Option<SanitizedText>,
+    pub owner: Option<SanitizedText>,
     pub due_by: Option<DateTime<Utc>>,
+    /// The deadline as written, kept when it doesn't resolve to a date.
+    #[serde(default)]
+    pub due_by_raw: Option<String>,
     pub confidence: f32,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AnsweredQuestion {
-    pub question: String,
-    pub answer_summary: String,
+    pub question: SanitizedText,
+    pub answer_summary: SanitizedText,
     pub confidence: f32,
 }
 
@@ -176,6 +325,9 @@ pub struct Provenance {
     pub provider: String,
     pub prompt_id: Uuid,
     pub created_at: DateTime<Utc>,
+    /// The body was cut down to fit the extraction limit.
+    #[serde(default)]
+    pub truncated: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -235,11 +387,195 @@ pub struct Prompt {
     pub updated_at: DateTime<Utc>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+/// A user-authored prompt as shown in the prompt library.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CustomPrompt {
+    pub id: String,
+    pub name: String,
+    pub content: String,
+    pub kind: PromptKind,
+    pub description: Option<String>,
+    /// Values the template's `{{name}}` placeholders take.
+    pub variables: Vec<PromptVariable>,
+    /// JSON schema the model's answer is expected to match.
+    pub output_schema: Option<serde_json::Value>,
+    pub schedule_cron: Option<String>,
+    /// The pack it was imported from; `None` for prompts written here.
+    pub pack: Option<String>,
+}
+
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    Serialize,
+    Deserialize,
+    PartialEq,
+    Eq,
+    strum_macros::Display,
+    strum_macros::EnumString,
+)]
 #[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
 pub enum PromptKind {
+    /// Run on one email at a time.
     Extraction,
+    /// Run on a schedule over recent mail.
     Periodic,
+    /// Written in the prompt library for the user's own use.
+    Custom,
+}
+
+/// Bumped when a prompt pack's layout changes incompatibly.
+pub const PROMPT_PACK_VERSION: u32 = 1;
+
+/// Prompts bundled for sharing, as written by `export_prompt_pack` and read
+/// by `import_prompt_pack`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PromptPack {
+    pub version: u32,
+    /// Prompts are re-imported by pack and prompt name, so importing a newer
+    /// pack of the same name updates them.
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub author: Option<String>,
+    #[serde(default)]
+    pub exported_at: Option<DateTime<Utc>>,
+    pub prompts: Vec<PackPrompt>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PackPrompt {
+    pub name: String,
+    pub kind: PromptKind,
+    #[serde(default)]
+    pub description: Option<String>,
+    /// The prompt, with `{{name}}` placeholders for its variables and the
+    /// built-in ones such as `{{email}}` and `{{today}}`.
+    pub template: String,
+    #[serde(default)]
+    pub variables: Vec<PromptVariable>,
+    #[serde(default)]
+    pub output_schema: Option<serde_json::Value>,
+    /// When a periodic prompt runs, as a cron expression.
+    #[serde(default)]
+    pub schedule_cron: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PromptVariable {
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    /// Used when no value is given; may itself use built-in placeholders,
+    /// e.g. `{{week_start}}`. Without one a value is required.
+    #[serde(default)]
+    pub default: Option<String>,
+}
+
+/// What importing a [`PromptPack`] did.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PromptPackImport {
+    pub pack: String,
+    pub created: usize,
+    pub updated: usize,
+}
+
+/// A prompt filled in with sample data and the model's answer to it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PromptPreview {
+    /// The prompt as sent.
+    pub rendered: String,
+    pub output: String,
+    /// What the output gets wrong against the prompt's schema; empty when it
+    /// matches or there is no schema.
+    pub schema_errors: Vec<String>,
+}
+
+/// One side of an extraction experiment: a library prompt in place of the
+/// built-in extraction prompt, a model in place of the configured one, or
+/// both.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ExperimentVariant {
+    pub label: String,
+    /// A prompt that uses `{{email}}`; the built-in prompt when `None`.
+    #[serde(default)]
+    pub prompt_id: Option<String>,
+    #[serde(default)]
+    pub model: Option<String>,
+    /// US dollars per million prompt tokens, for the variant's cost. Left
+    /// unset for models that cost nothing to run, like local ones.
+    #[serde(default)]
+    pub prompt_price: Option<f64>,
+    /// US dollars per million completion tokens.
+    #[serde(default)]
+    pub completion_price: Option<f64>,
+}
+
+/// What one variant extracted from one email of an experiment.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ExperimentOutcome {
+    /// The compared fields by name; empty when the extraction failed.
+    pub fields: std::collections::BTreeMap<String, serde_json::Value>,
+    pub confidence: Option<f32>,
+    pub prompt_tokens: u32,
+    pub completion_tokens: u32,
+    pub duration_ms: u64,
+    pub error: Option<String>,
+}
+
+/// Both variants' results for one email of an experiment.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExperimentEmail {
+    pub email_id: i64,
+    pub subject: String,
+    pub a: ExperimentOutcome,
+    pub b: ExperimentOutcome,
+    /// The fields the variants disagree on.
+    pub disagreements: Vec<String>,
+}
+
+/// How one variant did over all of an experiment's emails.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct VariantStats {
+    pub succeeded: u32,
+    pub failed: u32,
+    /// Over the emails it extracted.
+    pub mean_confidence: f32,
+    pub prompt_tokens: u64,
+    pub completion_tokens: u64,
+    /// The tokens' cost in US dollars; `None` unless the variant has a price.
+    #[serde(default)]
+    pub cost: Option<f64>,
+    pub mean_duration_ms: u64,
+}
+
+/// The side-by-side result of an experiment.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ExperimentComparison {
+    /// Emails both variants extracted, which agreement is measured over.
+    pub compared: u32,
+    /// The share of compared emails on which the variants agree, by field.
+    pub field_agreement: std::collections::BTreeMap<String, f32>,
+    /// The share of all field comparisons that agree.
+    pub agreement: f32,
+    pub a: VariantStats,
+    pub b: VariantStats,
+}
+
+/// Two extraction variants run over the same emails, stored so prompt and
+/// model changes can be judged on their results.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExtractionExperiment {
+    pub id: i64,
+    pub name: String,
+    pub variant_a: ExperimentVariant,
+    pub variant_b: ExperimentVariant,
+    pub emails: Vec<ExperimentEmail>,
+    pub comparison: ExperimentComparison,
+    pub created_at: DateTime<Utc>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -252,12 +588,546 @@ pub struct PromptScope {
     pub participants: Option<Vec<String>>,
 }
 
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    Serialize,
+    Deserialize,
+    PartialEq,
+    Eq,
+    strum_macros::Display,
+    strum_macros::EnumString,
+)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum AlertKind {
+    /// New mail from a VIP sender.
+    VipEmail,
+    /// Several hostile emails from one sender in a short time.
+    HostileSender,
+    /// Many high-urgency emails for one project in a short time.
+    UrgentFlood,
+    /// Extraction confidence fell well below its usual level, e.g. after a
+    /// model change.
+    ConfidenceDrop,
+}
+
+/// Something unusual noticed in extracted facts.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Alert {
+    pub id: i64,
+    pub kind: AlertKind,
+    /// The sender, project or other subject of the alert.
+    pub key: String,
+    pub message: String,
+    /// The email that triggered the alert.
+    pub email_id: Option<i64>,
+    pub created_at: DateTime<Utc>,
+    pub acknowledged: bool,
+}
+
+/// The part of an email's facts a [`CategoryRule`] looks at.
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    Serialize,
+    Deserialize,
+    PartialEq,
+    Eq,
+    strum_macros::Display,
+    strum_macros::EnumString,
+)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum CategoryMatch {
+    Project,
+    Sender,
+    PrimaryType,
+}
+
+/// Labels extracted emails with an Outlook category.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CategoryRule {
+    pub id: i64,
+    pub match_on: CategoryMatch,
+    /// Compared without case. A sender pattern starting with `@` matches
+    /// everyone at that domain.
+    pub pattern: String,
+    pub category: String,
+    /// An Outlook `OlCategoryColor`, used when the category is added to the
+    /// mailbox's category list.
+    pub color: i32,
+    /// Whether the category is also set on the message in Outlook, rather
+    /// than only shown in Noodle.
+    pub write_back: bool,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    Serialize,
+    Deserialize,
+    PartialEq,
+    Eq,
+    strum_macros::Display,
+    strum_macros::EnumString,
+)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum CategoryAction {
+    RuleAdded,
+    RuleUpdated,
+    RuleRemoved,
+    /// An email got the category in Noodle.
+    Labelled,
+    /// The category was set on the Outlook item.
+    Written,
+    /// The Outlook item already had the category.
+    AlreadySet,
+    WriteFailed,
+}
+
+/// One entry of the category audit log: a rule change, or a rule applied to
+/// an email.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CategoryAuditEntry {
+    pub id: i64,
+    pub action: CategoryAction,
+    /// `None` once the rule was removed.
+    pub rule_id: Option<i64>,
+    pub email_id: Option<i64>,
+    pub category: String,
+    /// The rule for rule changes, the error for failed writes.
+    pub detail: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Who a project report is prepared for, deciding what it leaves out.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, strum_macros::Display)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum ExportProfile {
+    /// Everything, including the email bodies.
+    Internal,
+    /// Summaries, facts and decisions with who sent what; no bodies.
+    Manager,
+    /// As `Manager`, without senders or owners, and email addresses masked.
+    Client,
+}
+
+/// What a summary is formatted for when shared in a chat tool.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, strum_macros::Display)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum ChatFormat {
+    /// Slack Block Kit JSON.
+    Slack,
+    /// A Teams message with an Adaptive Card, as accepted by webhooks.
+    Teams,
+    Markdown,
+}
+
+/// The summary to share.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ChatSource {
+    Email {
+        id: i64,
+    },
+    /// The conversation the email belongs to.
+    Thread {
+        email_id: i64,
+    },
+    Digest,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DateRange {
     pub start: Option<DateTime<Utc>>,
     pub end: Option<DateTime<Utc>>,
 }
 
+/// Structured constraints applied to a search on top of its free-text query.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SearchFilter {
+    pub sender: Option<String>,
+    pub project: Option<String>,
+    pub folder: Option<String>,
+    pub urgency: Option<Urgency>,
+    pub sentiment: Option<Sentiment>,
+    pub needs_response: Option<bool>,
+    pub date_range: Option<DateRange>,
+    /// Outlook `ConversationID`; only set by thread-scoped searches.
+    pub conversation_id: Option<String>,
+    /// Tags the user gave the email; all of them must be there.
+    #[serde(default)]
+    pub tags: Vec<String>,
+}
+
+impl SearchFilter {
+    pub fn is_empty(&self) -> bool {
+        self.sender.is_none()
+            && self.project.is_none()
+            && self.folder.is_none()
+            && self.urgency.is_none()
+            && self.sentiment.is_none()
+            && self.needs_response.is_none()
+            && self.date_range.is_none()
+            && self.conversation_id.is_none()
+            && self.tags.is_empty()
+    }
+}
+
+/// User preferences for a single client/project, keyed by the project name the
+/// extractor assigns. Projects without a stored row use `ProjectSettings::new`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProjectSettings {
+    pub name: String,
+    pub mute_notifications: bool,
+    pub extraction_enabled: bool,
+    pub include_in_digest: bool,
+    /// Emails of the project older than this are purged by the nightly
+    /// maintenance; `None` keeps them.
+    pub retention_days: Option<i64>,
+}
+
+impl ProjectSettings {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            mute_notifications: false,
+            extraction_enabled: true,
+            include_in_digest: true,
+            retention_days: None,
+        }
+    }
+}
+
+/// Progress of the initial scan through one folder.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScanCheckpoint {
+    pub folder: String,
+    /// Emails are scanned oldest-first; everything received before this is done.
+    pub last_received_at: DateTime<Utc>,
+    pub processed: i64,
+    pub total: i64,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum SyncState {
+    /// Waiting for the next delta scan.
+    Idle,
+    Syncing,
+    /// Held back by the activity policy or the user.
+    Paused,
+}
+
+/// What the background sync loop is doing, as shown in the tray.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SyncStatus {
+    pub state: SyncState,
+    pub pause_reason: Option<String>,
+    /// End of the last scan that completed.
+    pub last_sync_at: Option<DateTime<Utc>>,
+}
+
+/// An email fetched by a scan and waiting for the pipeline.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QueuedEmail {
+    pub entry_id: String,
+    pub subject: String,
+    pub folder: String,
+    pub received_at: DateTime<Utc>,
+    pub priority: QueuePriority,
+}
+
+/// Which scan queued an email. New mail from delta scans goes ahead of the
+/// initial scan's backfill.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum QueuePriority {
+    Delta,
+    Backfill,
+    /// Backfill mail from a VIP sender, taken before everything else.
+    Vip,
+}
+
+/// A sender the user often replies to, offered as a VIP.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VipSuggestion {
+    pub sender: String,
+    /// Inbox emails from the sender the user replied to.
+    pub replied: i64,
+    pub received: i64,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum PipelineStage {
+    /// Held back by low impact pacing.
+    Waiting,
+    Extracting,
+    Embedding,
+    /// Extraction and embedding running side by side.
+    ExtractingAndEmbedding,
+}
+
+/// A step the pipeline finished for an email. Extraction and embedding run
+/// side by side, so `Extracted` and `Embedded` arrive in either order.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ProcessStage {
+    /// Stored in SQLite and archived.
+    Saved,
+    /// Facts extracted, whether or not the project keeps them.
+    Extracted,
+    /// Vector upserted to Qdrant.
+    Embedded,
+    /// Done; the email is searchable and scans skip it. With extraction
+    /// deferred to idle time, `Extracted` follows later.
+    Indexed,
+}
+
+/// An email the pipeline is working on.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ActiveEmail {
+    pub entry_id: String,
+    pub subject: String,
+    pub stage: PipelineStage,
+    pub started_at: DateTime<Utc>,
+    pub elapsed_ms: i64,
+}
+
+/// What changed when a sync found an indexed email with new content.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EmailChange {
+    pub id: i64,
+    pub email_id: i64,
+    pub detected_at: DateTime<Utc>,
+    /// Both set only when the subject changed.
+    pub previous_subject: Option<String>,
+    pub subject: Option<String>,
+    /// Unified diff of the plain-text body; `None` when it did not change.
+    pub body_diff: Option<String>,
+    pub lines_added: i64,
+    pub lines_removed: i64,
+}
+
+#[derive(
+    Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash, strum_macros::Display,
+)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum RelationKind {
+    FrequentCollaborator,
+    SameOrg,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, strum_macros::Display)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum RelationStatus {
+    Suggested,
+    Confirmed,
+    Dismissed,
+}
+
+/// A relation between two people inferred from mail patterns, for the user
+/// to confirm or dismiss.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InferredRelation {
+    pub id: i64,
+    /// Participants as they appear in the mail, e.g. an address or display name.
+    pub source: String,
+    pub target: String,
+    pub kind: RelationKind,
+    pub confidence: f32,
+    /// Why it was inferred, e.g. "14 shared emails (9 direct, 5 cc)".
+    pub evidence: String,
+    pub status: RelationStatus,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SearchHistoryEntry {
+    pub id: i64,
+    pub query: String,
+    pub result_count: i64,
+    pub searched_at: DateTime<Utc>,
+}
+
+/// A past query offered while typing, with how often it was run.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SearchSuggestion {
+    pub query: String,
+    pub count: i64,
+    pub last_searched_at: DateTime<Utc>,
+}
+
+/// Limits a graph export to the entities of one project and/or one type.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct GraphFilter {
+    pub project: Option<String>,
+    pub entity_type: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GraphNode {
+    pub id: i64,
+    pub name: String,
+    pub kind: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GraphLink {
+    pub source: i64,
+    pub target: i64,
+    pub kind: String,
+    /// Emails an extracted edge was seen in; 1 for inferred relations.
+    pub weight: i64,
+    /// Set for inferred relations only.
+    pub confidence: Option<f32>,
+    pub status: Option<RelationStatus>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Graph {
+    pub nodes: Vec<GraphNode>,
+    pub links: Vec<GraphLink>,
+}
+
+/// A vector upsert that failed and is retried before the next delta scan.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VectorRetry {
+    pub email_id: i64,
+    pub subject: String,
+    pub attempts: i64,
+    pub last_error: Option<String>,
+    pub enqueued_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QueueStatus {
+    /// The next pending emails, in processing order.
+    pub pending: Vec<QueuedEmail>,
+    pub pending_total: usize,
+    pub in_progress: Vec<ActiveEmail>,
+    pub retries: Vec<VectorRetry>,
+    /// LLM calls running now, and the concurrency cap.
+    pub active_workers: usize,
+    pub max_workers: usize,
+    /// Time spent per stage since the app started.
+    pub latencies: Vec<StageLatency>,
+}
+
+/// Outcome of one SQLite maintenance run.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MaintenanceReport {
+    pub started_at: DateTime<Utc>,
+    pub duration_ms: i64,
+    /// Frames moved from the WAL into the database file.
+    pub wal_pages_checkpointed: i64,
+    pub vacuumed: bool,
+    pub size_before_bytes: i64,
+    pub size_after_bytes: i64,
+}
+
+/// One migration, as known to this build and/or recorded in the database.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SchemaMigration {
+    pub version: i64,
+    pub description: String,
+    /// `None` for migrations this build ships but has not applied yet.
+    pub applied_at: Option<DateTime<Utc>>,
+    pub success: bool,
+    /// `false` for migrations applied by a newer build than this one.
+    pub known: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SchemaInfo {
+    pub current_version: Option<i64>,
+    pub migrations: Vec<SchemaMigration>,
+    /// Copy of the database taken before the last upgrade, if one exists.
+    pub backup_path: Option<String>,
+    pub backup_taken_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RepairReport {
+    /// `integrity_check` findings before repair; empty when the check passed.
+    pub problems_before: Vec<String>,
+    pub problems_after: Vec<String>,
+    pub duration_ms: i64,
+}
+
+/// How the Qdrant email collection compares with the emails in SQLite.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct VectorStats {
+    /// `false` when Qdrant is unreachable; only `email_count` is filled then.
+    pub available: bool,
+    pub email_count: i64,
+    pub point_count: usize,
+    /// Vector size the collection was created with.
+    pub collection_dim: Option<u64>,
+    /// Vector size the current embedding model produces; `None` if the
+    /// provider could not be reached.
+    pub embedding_dim: Option<u64>,
+    /// Points whose email is no longer in SQLite.
+    pub orphaned_points: usize,
+    /// Emails without a point; the first of them are in `missing_email_ids`.
+    pub missing_vectors: usize,
+    pub missing_email_ids: Vec<i64>,
+}
+
+impl VectorStats {
+    /// `false` when the embedding model and the collection disagree on size,
+    /// so no new vector can be stored.
+    pub fn dimensions_match(&self) -> bool {
+        match (self.collection_dim, self.embedding_dim) {
+            (Some(collection), Some(embedding)) => collection == embedding,
+            _ => true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VectorRepairReport {
+    pub orphans_deleted: usize,
+    pub reembedded: usize,
+    /// Emails whose embedding failed; they stay queued for retry.
+    pub failed: usize,
+    pub duration_ms: i64,
+}
+
+/// A saved snapshot of the email vector collection.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VectorSnapshot {
+    pub name: String,
+    pub created_at: DateTime<Utc>,
+    pub size_bytes: u64,
+}
+
+/// Result of checking the legal hold archive's hash chain.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HoldVerification {
+    /// Records read before the first problem, or all of them.
+    pub records: u64,
+    pub segments: usize,
+    pub intact: bool,
+    /// Sequence number of the first record that failed the check.
+    pub first_broken: Option<u64>,
+    pub problem: Option<String>,
+    /// Partly written lines left by a crash mid-append. They were never
+    /// anchored, so skipping them loses no record.
+    #[serde(default)]
+    pub torn: u64,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ModelPreference {
     pub provider: String,
@@ -275,3 +1145,299 @@ pub struct PeriodicRun {
     pub output_text: Option<String>,
     pub error_text: Option<String>,
 }
+
+/// A map-reduce summary of what the mail in a date range says about a topic.
+/// Stored like a [`PeriodicRun`], so it can be reopened without re-running it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TopicSummary {
+    pub id: i64,
+    pub query: String,
+    pub date_range: DateRange,
+    pub run_at: DateTime<Utc>,
+    /// `completed` or `failed`.
+    pub status: String,
+    /// The synthesis, citing emails as `[#id]`.
+    pub output_text: Option<String>,
+    /// Every email the summary was built from, in date order.
+    pub sources: Vec<TopicSource>,
+    pub error_text: Option<String>,
+}
+
+/// One email's contribution to a [`TopicSummary`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TopicSource {
+    pub email_id: i64,
+    pub subject: String,
+    pub sender: String,
+    pub received_at: DateTime<Utc>,
+    /// What the email says about the topic.
+    pub summary: String,
+    /// The synthesis cites this email.
+    pub cited: bool,
+}
+
+/// A conversation with `ask_noodle`. Its messages are fetched separately.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChatSession {
+    pub id: i64,
+    /// The question that started it.
+    pub title: String,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    pub message_count: i64,
+}
+
+/// One turn of a [`ChatSession`], from the user or the assistant.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChatMessage {
+    pub id: i64,
+    pub session_id: i64,
+    /// `user` or `assistant`.
+    pub role: String,
+    /// Assistant answers cite emails as `[#id]`.
+    pub content: String,
+    /// The emails retrieved for an answer; empty for questions.
+    pub sources: Vec<TopicSource>,
+    /// The tools a research answer called on its way, in order; empty for
+    /// questions and plain answers.
+    #[serde(default)]
+    pub trace: Vec<ChatToolCall>,
+    /// Citations in the answer that their email doesn't appear to back up.
+    #[serde(default)]
+    pub unsupported: Vec<UnsupportedCitation>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// A statement in an answer citing an email that doesn't support it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UnsupportedCitation {
+    /// The statement, without its citation tags.
+    pub claim: String,
+    pub email_id: i64,
+    pub reason: String,
+}
+
+/// One tool the assistant called while researching an answer, kept with
+/// the answer so the user can see how it got there.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChatToolCall {
+    /// 1 for the first call of an answer.
+    pub step: usize,
+    pub tool: String,
+    pub arguments: serde_json::Value,
+    /// A line on what came back, e.g. "5 emails"; `None` while running.
+    pub result: Option<String>,
+    pub error: Option<String>,
+    pub duration_ms: u64,
+}
+
+/// A summary of the newsletters received in a date range, stored like a
+/// [`TopicSummary`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NewsletterRoundup {
+    pub id: i64,
+    pub date_range: DateRange,
+    pub run_at: DateTime<Utc>,
+    /// `completed` or `failed`.
+    pub status: String,
+    /// The roundup, citing newsletters as `[#id]`.
+    pub output_text: Option<String>,
+    /// Every newsletter summarized, in date order.
+    pub sources: Vec<TopicSource>,
+    pub error_text: Option<String>,
+}
+
+/// A sender of newsletters and how to stop them.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NewsletterSender {
+    pub sender: String,
+    pub issues: i64,
+    pub last_received_at: DateTime<Utc>,
+    /// From the newest issue's `List-Unsubscribe` header: `mailto:` and
+    /// `https:` URIs in angle brackets.
+    pub list_unsubscribe: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum TimelineEventKind {
+    Email,
+    Decision,
+    RiskRaised,
+    BlockerResolved,
+    DeadlineChanged,
+}
+
+/// One entry in a project's history, tied to the email it came from.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TimelineEvent {
+    pub at: DateTime<Utc>,
+    pub kind: TimelineEventKind,
+    pub email_id: i64,
+    pub title: String,
+    pub detail: Option<String>,
+    /// Set for risks and resolved blockers.
+    pub severity: Option<Severity>,
+    /// Set for deadline changes; `previous_due_by` is empty the first time a
+    /// thread gets a deadline.
+    pub due_by: Option<DateTime<Utc>>,
+    pub previous_due_by: Option<DateTime<Utc>>,
+}
+
+/// Someone invited to a meeting, as the Outlook item lists them.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct MeetingAttendee {
+    pub name: String,
+    /// In the form emails' `sender` is stored in, so the two compare.
+    pub address: String,
+}
+
+/// A meeting from the Outlook calendar. Each occurrence of a recurring
+/// meeting is a meeting of its own.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Meeting {
+    pub id: i64,
+    /// Outlook's `GlobalAppointmentID`, shared by a series' occurrences.
+    pub global_id: String,
+    pub subject: String,
+    pub organizer: String,
+    /// Everyone invited but the user; rooms and other resources left out.
+    pub attendees: Vec<MeetingAttendee>,
+    pub start_at: DateTime<Utc>,
+    pub end_at: DateTime<Utc>,
+    /// When follow-ups were extracted from the mail around the meeting.
+    pub followed_up_at: Option<DateTime<Utc>>,
+    /// Why the last extraction failed; it is retried on the next run.
+    pub follow_up_error: Option<String>,
+}
+
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    Serialize,
+    Deserialize,
+    PartialEq,
+    Eq,
+    strum_macros::Display,
+    strum_macros::EnumString,
+)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum MeetingTaskKind {
+    Decision,
+    ActionItem,
+}
+
+/// A decision or action item from a meeting's follow-up mail.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MeetingTask {
+    pub id: i64,
+    pub meeting_id: i64,
+    pub kind: MeetingTaskKind,
+    pub text: String,
+    pub owner: Option<String>,
+    pub due_by: Option<DateTime<Utc>>,
+    /// The email it was found in.
+    pub email_id: Option<i64>,
+    pub done: bool,
+    pub created_at: DateTime<Utc>,
+}
+
+/// A meeting with the emails found around it and what they settled.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MeetingFollowUp {
+    pub meeting: Meeting,
+    pub email_ids: Vec<i64>,
+    pub tasks: Vec<MeetingTask>,
+}
+
+/// Where the user has put an email in their triage, set by hand over what
+/// extraction found.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum TriageState {
+    /// The user owes a reply.
+    NeedsResponse,
+    /// The ball is in the other side's court.
+    Waiting,
+    /// Nothing left to do.
+    Done,
+}
+
+impl TriageState {
+    /// The `needs_response` and `waiting_on` facts the state stands for.
+    pub fn facts(self) -> (bool, WaitingOn) {
+        match self {
+            Self::NeedsResponse => (true, WaitingOn::Me),
+            Self::Waiting => (false, WaitingOn::Them),
+            Self::Done => (false, WaitingOn::None),
+        }
+    }
+}
+
+/// An action applied to many emails at once.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum BulkOperation {
+    SetTriage,
+    ReassignProject,
+    Reprocess,
+    /// Moves to Noodle's trash, from where the email can be restored.
+    Trash,
+    Purge,
+}
+
+/// The outcome of a bulk action.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BulkResult {
+    pub operation: BulkOperation,
+    pub requested: usize,
+    /// Emails the action changed. Emails without facts have no triage or
+    /// project to set, and ids that don't exist are skipped.
+    pub updated: usize,
+    /// Emails the action failed for; only reprocessing fails per email.
+    pub failed: Vec<i64>,
+}
+
+/// What the user layered on top of the extracted facts of an email or a
+/// project: a note, tags of their own, and a pin or star.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Annotation {
+    pub id: i64,
+    /// Set for an email's annotation.
+    pub email_id: Option<i64>,
+    /// Set for a project's annotation, by the name extraction gives it.
+    pub project: Option<String>,
+    pub note: Option<String>,
+    /// As [`Annotation::normalize_tag`] leaves them.
+    pub tags: Vec<String>,
+    pub pinned: bool,
+    pub starred: bool,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl Annotation {
+    /// A tag as stored and searched: lowercase, without a leading `#`,
+    /// with dashes for spaces. `None` when nothing is left.
+    pub fn normalize_tag(tag: &str) -> Option<String> {
+        let tag = tag.trim().trim_start_matches('#').to_lowercase();
+        let tag = tag.split_whitespace().collect::<Vec<_>>().join("-");
+        (!tag.is_empty()).then_some(tag)
+    }
+}
+
+/// An annotation as the user edits it, for exactly one of an email or a
+/// project. Saving replaces what the target had.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AnnotationInput {
+    pub email_id: Option<i64>,
+    pub project: Option<String>,
+    pub note: Option<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub pinned: bool,
+    #[serde(default)]
+    pub starred: bool,
+}
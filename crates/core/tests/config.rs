@@ -0,0 +1,72 @@
+use noodle_core::config::{AttachmentPolicy, Config};
+
+fn policy(mime_types: &[&str], max_bytes: i64) -> AttachmentPolicy {
+    AttachmentPolicy {
+        max_bytes,
+        mime_types: mime_types.iter().map(|t| t.to_string()).collect(),
+    }
+}
+
+fn with_mime_types(value: &str) -> Config {
+    Config {
+        attachment_mime_types: value.to_string(),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn attachments_of_listed_types_are_stored() {
+    let policy = policy(&["application/pdf", "text/*"], 1_000);
+    assert_eq!(policy.skip_reason("application/pdf", 1_000), None);
+    assert_eq!(policy.skip_reason("Text/Plain", 10), None);
+    assert_eq!(policy.skip_reason("text/csv", 10), None);
+    assert_eq!(
+        policy.skip_reason("application/pdf+xml", 10),
+        Some("type_not_allowed")
+    );
+    assert_eq!(
+        policy.skip_reason("image/png", 10),
+        Some("type_not_allowed")
+    );
+}
+
+#[test]
+fn type_is_checked_before_size() {
+    let pdf = policy(&["application/pdf"], 1_000);
+    assert_eq!(pdf.skip_reason("application/pdf", 1_001), Some("too_large"));
+    assert_eq!(
+        pdf.skip_reason("image/png", 1_001),
+        Some("type_not_allowed")
+    );
+    assert_eq!(
+        policy(&[], 1_000).skip_reason("application/pdf", 10),
+        Some("type_not_allowed")
+    );
+}
+
+#[test]
+fn policy_comes_from_the_config() {
+    let config = Config {
+        attachment_max_size_mb: 2,
+        ..with_mime_types(" Application/PDF, ,text/* ")
+    };
+    let policy = config.attachment_policy();
+    assert_eq!(policy.max_bytes, 2 * 1024 * 1024);
+    assert_eq!(policy.mime_types, ["application/pdf", "text/*"]);
+}
+
+#[test]
+fn mime_types_must_have_a_type_and_subtype() {
+    for value in ["application/pdf, text/*", " text/plain ,", ""] {
+        assert!(with_mime_types(value).check().is_ok(), "{}", value);
+    }
+    for value in ["pdf", "application/", "/pdf", "text/*, images"] {
+        let error = with_mime_types(value).check().unwrap_err().to_string();
+        assert!(
+            error.contains("attachment_mime_types"),
+            "{}: {}",
+            value,
+            error
+        );
+    }
+}
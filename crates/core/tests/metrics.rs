@@ -0,0 +1,74 @@
+use noodle_core::metrics::{percentile, Stage, StageMetrics};
+use std::time::Duration;
+
+fn ms(values: &[u64]) -> Vec<Duration> {
+    values.iter().copied().map(Duration::from_millis).collect()
+}
+
+#[test]
+fn percentiles_are_nearest_rank() {
+    let samples = ms(&[10, 20, 30, 40, 50, 60, 70, 80, 90, 100]);
+    assert_eq!(percentile(&samples, 0.5), 50.0);
+    assert_eq!(percentile(&samples, 0.95), 100.0);
+    assert_eq!(percentile(&samples, 0.0), 10.0);
+    assert_eq!(percentile(&samples, 1.0), 100.0);
+}
+
+#[test]
+fn percentiles_of_one_sample_or_none() {
+    assert_eq!(percentile(&ms(&[42]), 0.5), 42.0);
+    assert_eq!(percentile(&ms(&[42]), 0.95), 42.0);
+    assert_eq!(percentile(&[], 0.5), 0.0);
+}
+
+#[test]
+fn nothing_recorded_is_an_empty_snapshot() {
+    assert!(StageMetrics::new().snapshot().is_empty());
+}
+
+#[test]
+fn snapshot_lists_recorded_stages_in_pipeline_order() {
+    let metrics = StageMetrics::new();
+    metrics.record(Stage::Embedding, Duration::from_millis(30));
+    metrics.record(Stage::OutlookFetch, Duration::from_millis(10));
+    metrics.record(Stage::OutlookFetch, Duration::from_millis(30));
+    metrics.record(Stage::OutlookFetch, Duration::from_millis(20));
+
+    let snapshot = metrics.snapshot();
+    let stages: Vec<Stage> = snapshot.iter().map(|l| l.stage).collect();
+    assert_eq!(stages, [Stage::OutlookFetch, Stage::Embedding]);
+
+    let fetch = &snapshot[0];
+    assert_eq!(fetch.count, 3);
+    assert_eq!(fetch.mean_ms, 20.0);
+    assert_eq!(fetch.p50_ms, 20.0);
+    assert_eq!(fetch.p95_ms, 30.0);
+    assert_eq!(fetch.max_ms, 30.0);
+
+    let embedding = &snapshot[1];
+    assert_eq!(embedding.count, 1);
+    assert_eq!(
+        (
+            embedding.mean_ms,
+            embedding.p50_ms,
+            embedding.p95_ms,
+            embedding.max_ms
+        ),
+        (30.0, 30.0, 30.0, 30.0)
+    );
+}
+
+#[test]
+fn percentiles_cover_recent_samples_and_totals_all() {
+    let metrics = StageMetrics::new();
+    metrics.record(Stage::SqliteSave, Duration::from_millis(1_000));
+    for _ in 0..500 {
+        metrics.record(Stage::SqliteSave, Duration::from_millis(1));
+    }
+
+    let save = &metrics.snapshot()[0];
+    assert_eq!(save.count, 501);
+    assert_eq!(save.max_ms, 1_000.0);
+    assert_eq!(save.p95_ms, 1.0);
+    assert!((save.mean_ms - 1_500.0 / 501.0).abs() < 1e-9);
+}
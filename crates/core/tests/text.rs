@@ -0,0 +1,62 @@
+use noodle_core::text::SanitizedText;
+
+fn clean(text: &str) -> String {
+    SanitizedText::new(text).into_string()
+}
+
+#[test]
+fn markup_is_stripped_and_text_kept() {
+    assert_eq!(clean("<p>Ship <b>Friday</b></p>"), "Ship Friday");
+    assert_eq!(
+        clean(r#"<a href="https://example.com">the plan</a><br/>"#),
+        "the plan"
+    );
+    assert_eq!(clean("<o:p>Budget</o:p>"), "Budget");
+    assert_eq!(
+        clean("<!--[if mso]>x<![endif]-->Hi <![if !supportLists]>there"),
+        "xHi there"
+    );
+    assert_eq!(clean("<!DOCTYPE html><html><body>Hi</body></html>"), "Hi");
+}
+
+#[test]
+fn hidden_elements_go_with_their_content() {
+    assert_eq!(clean("Hi<script>alert(1)</script> there"), "Hi there");
+    assert_eq!(clean("<STYLE type=\"text/css\">p {}</STYLE>Body"), "Body");
+    assert_eq!(clean("Before<iframe src=x>"), "Before");
+}
+
+#[test]
+fn angle_brackets_in_prose_survive() {
+    for text in [
+        "x<y and z>w",
+        "a < b and c > d",
+        "Reply to <jo@example.com>",
+        "Use Vec<String> here",
+        "<3 the new design",
+        "Unclosed <b tag",
+        "<bold> claims",
+    ] {
+        assert_eq!(clean(text), text);
+    }
+}
+
+#[test]
+fn unsafe_characters_are_removed() {
+    assert_eq!(clean("a\u{0007}b\u{202E}c\u{2066}d"), "abcd");
+    assert_eq!(clean("  line one\n\tline two  "), "line one\n\tline two");
+}
+
+#[test]
+fn long_text_is_cut_with_a_marker() {
+    assert_eq!(SanitizedText::with_limit("abcdef", 6).as_str(), "abcdef");
+    assert_eq!(SanitizedText::with_limit("abc defgh", 5).as_str(), "abc…");
+    assert_eq!(SanitizedText::new(&"x".repeat(5000)).chars().count(), 4000);
+}
+
+#[test]
+fn deserializing_sanitizes() {
+    let text: SanitizedText = serde_json::from_str(r#""<b>Hi</b>\u0007""#).unwrap();
+    assert_eq!(text.as_str(), "Hi");
+    assert_eq!(serde_json::to_string(&text).unwrap(), r#""Hi""#);
+}
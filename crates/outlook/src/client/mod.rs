@@ -1,18 +1,148 @@
 use crate::com::ComDispatch;
-use chrono::{DateTime, Duration, Utc};
+use chrono::{DateTime, Duration, Local, NaiveDate, TimeZone, Utc};
+use noodle_core::config::AttachmentPolicy;
 use noodle_core::error::{NoodleError, Result};
-use noodle_core::types::Email;
+use noodle_core::metrics::{Stage, StageMetrics};
+use noodle_core::types::{Email, Meeting, MeetingAttendee};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 use std::thread;
+use std::time::Instant;
 use tokio::sync::{mpsc, oneshot};
+use tokio_util::sync::CancellationToken;
 use windows::core::{BSTR, VARIANT};
 use windows::Win32::System::Com::{CoInitializeEx, IDispatch, COINIT_APARTMENTTHREADED};
 
+/// Stored as every email's `store_id`; it names no real Outlook store, so
+/// items are looked up in the default one.
+const DEFAULT_STORE_ID: &str = "outlook";
+/// `OlTableContents.olUserItems`: the folder's items, hidden ones excluded.
+const OL_USER_ITEMS: i32 = 0;
+/// `OlDefaultFolders.olFolderCalendar`.
+const OL_FOLDER_CALENDAR: i32 = 9;
+/// `OlMeetingStatus` values of appointments that are no meeting to follow
+/// up on: plain appointments, and meetings cancelled by either side.
+const NOT_MEETINGS: [i32; 3] = [0, 5, 7];
+/// `OlBusyStatus` values of time the user is taken up: busy and out of
+/// office.
+const BUSY: [i32; 2] = [2, 3];
+/// `OlMeetingRecipientType.olResource`: a room or equipment.
+const OL_RESOURCE: i32 = 3;
+/// `OlAttachmentType.olByValue`: a file. Links and embedded items are left
+/// out.
+const OL_BY_VALUE: i32 = 1;
+/// `PR_ATTACH_MIME_TAG`; Outlook only sets it on some attachments.
+const PR_ATTACH_MIME_TAG: &str = "http://schemas.microsoft.com/mapi/proptag/0x370E001F";
+/// `PR_ATTACHMENT_HIDDEN`: inline images such as signature logos.
+const PR_ATTACHMENT_HIDDEN: &str = "http://schemas.microsoft.com/mapi/proptag/0x7FFE000B";
+/// `PR_OOF_STATE` of the default store: whether automatic replies are on.
+const PR_OOF_STATE: &str = "http://schemas.microsoft.com/mapi/proptag/0x661D000B";
+/// `PR_INTERNET_MESSAGE_ID` of an item: its `Message-ID` header.
+const PR_INTERNET_MESSAGE_ID: &str = "http://schemas.microsoft.com/mapi/proptag/0x1035001F";
+/// The message's internet headers, as received.
+const PR_TRANSPORT_MESSAGE_HEADERS: &str = "http://schemas.microsoft.com/mapi/proptag/0x007D001F";
+/// Items opened per request to the Outlook thread, so other requests get a
+/// turn between pages of a long fetch.
+const FETCH_PAGE_SIZE: usize = 8;
+/// Fetched emails an [`EmailStream`] holds until they are taken. Fetching
+/// waits while it is full, which bounds memory on large folders.
+const STREAM_BUFFER: usize = 16;
+
+/// A mail folder to scan.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MailFolder {
+    /// One of Outlook's default folders, by `OlDefaultFolders` id.
+    Default(i32),
+    /// A folder of the default store by its path of names, such as
+    /// `Inbox/Newsletters`, ignoring case. A bare name not found at the top
+    /// is looked for in the folders below.
+    Named(String),
+}
+
+/// An item found by a listing, before its body is fetched.
+#[derive(Debug, Clone)]
+pub struct EmailRef {
+    pub entry_id: String,
+    pub received_at: DateTime<Utc>,
+    pub sender: String,
+}
+
+/// An attachment of an item, saved to a file when the policy allows it.
+#[derive(Debug, Clone)]
+pub struct SavedAttachment {
+    pub filename: String,
+    pub mime: String,
+    pub size_bytes: i64,
+    /// Where it was saved; the caller moves or removes the file.
+    pub path: Option<PathBuf>,
+    /// Why the policy left it out, when it did.
+    pub skipped_reason: Option<String>,
+}
+
+/// A stretch of calendar time, from its start to its end.
+pub type TimeSpan = (DateTime<Utc>, DateTime<Utc>);
+
+/// Emails fetched from Outlook in the background, in the order they were
+/// asked for. Items that fail to load come through as errors.
+pub struct EmailStream {
+    rx: mpsc::Receiver<Result<Email>>,
+}
+
+impl EmailStream {
+    /// The next email, or `None` once all were fetched.
+    pub async fn next(&mut self) -> Option<Result<Email>> {
+        self.rx.recv().await
+    }
+}
+
 enum OutlookRequest {
-    GetEmailsLastNDays {
-        days: i64,
-        folder_id: i32,
+    ListEmailsSince {
+        since: DateTime<Utc>,
+        folder: MailFolder,
+        folder_name: String,
+        indexed: HashMap<String, DateTime<Utc>>,
+        cancel: CancellationToken,
+        reply: oneshot::Sender<Result<Vec<EmailRef>>>,
+    },
+    FetchEmails {
+        entry_ids: Vec<String>,
         folder_name: String,
-        reply: oneshot::Sender<Result<Vec<Email>>>,
+        cancel: CancellationToken,
+        reply: oneshot::Sender<Vec<Result<Email>>>,
+    },
+    Display {
+        entry_id: String,
+        store_id: String,
+        reply: oneshot::Sender<Result<()>>,
+    },
+    OutOfOffice {
+        reply: oneshot::Sender<Result<bool>>,
+    },
+    AddCategory {
+        entry_id: String,
+        store_id: String,
+        category: String,
+        color: i32,
+        reply: oneshot::Sender<Result<bool>>,
+    },
+    ListMeetings {
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+        reply: oneshot::Sender<Result<Vec<Meeting>>>,
+    },
+    BusyTimes {
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+        reply: oneshot::Sender<Result<Vec<TimeSpan>>>,
+    },
+    SaveAttachments {
+        entry_id: String,
+        store_id: String,
+        policy: AttachmentPolicy,
+        dir: PathBuf,
+        stored: HashSet<(String, i64)>,
+        reply: oneshot::Sender<Result<Vec<SavedAttachment>>>,
     },
 }
 
@@ -22,7 +152,8 @@ pub struct OutlookClient {
 }
 
 impl OutlookClient {
-    pub fn new() -> Result<Self> {
+    /// Starts the Outlook thread. Item fetches are timed into `metrics`.
+    pub fn new(metrics: Arc<StageMetrics>) -> Result<Self> {
         let (tx, mut rx) = mpsc::channel(32);
 
         thread::spawn(move || {
@@ -47,15 +178,84 @@ impl OutlookClient {
             // Process requests
             while let Some(msg) = rx.blocking_recv() {
                 match msg {
-                    OutlookRequest::GetEmailsLastNDays {
-                        days,
-                        folder_id,
+                    OutlookRequest::ListEmailsSince {
+                        since,
+                        folder,
                         folder_name,
+                        indexed,
+                        cancel,
                         reply,
                     } => {
-                        let result = inner.get_emails_last_n_days(days, folder_id, &folder_name);
+                        let result = inner.list_emails_since(
+                            since,
+                            &folder,
+                            &folder_name,
+                            &indexed,
+                            &cancel,
+                        );
                         let _ = reply.send(result);
                     }
+                    OutlookRequest::FetchEmails {
+                        entry_ids,
+                        folder_name,
+                        cancel,
+                        reply,
+                    } => {
+                        // A cancelled page comes back short.
+                        let emails = entry_ids
+                            .iter()
+                            .take_while(|_| !cancel.is_cancelled())
+                            .map(|entry_id| {
+                                let started = Instant::now();
+                                let fetched = inner.fetch_item(entry_id);
+                                metrics.record(Stage::OutlookFetch, started.elapsed());
+                                let mut email = fetched?;
+                                email.folder = folder_name.clone();
+                                Ok(email)
+                            })
+                            .collect();
+                        let _ = reply.send(emails);
+                    }
+                    OutlookRequest::Display {
+                        entry_id,
+                        store_id,
+                        reply,
+                    } => {
+                        let _ = reply.send(inner.display(&entry_id, &store_id));
+                    }
+                    OutlookRequest::OutOfOffice { reply } => {
+                        let _ = reply.send(inner.out_of_office());
+                    }
+                    OutlookRequest::AddCategory {
+                        entry_id,
+                        store_id,
+                        category,
+                        color,
+                        reply,
+                    } => {
+                        let _ =
+                            reply.send(inner.add_category(&entry_id, &store_id, &category, color));
+                    }
+                    OutlookRequest::ListMeetings { start, end, reply } => {
+                        let _ = reply.send(inner.list_meetings(start, end));
+                    }
+                    OutlookRequest::BusyTimes { start, end, reply } => {
+                        let _ = reply.send(inner.busy_times(start, end));
+                    }
+                    OutlookRequest::SaveAttachments {
+                        entry_id,
+                        store_id,
+                        policy,
+                        dir,
+                        stored,
+                        reply,
+                    } => {
+                        let _ =
+                            reply
+                                .send(inner.save_attachments(
+                                    &entry_id, &store_id, &policy, &dir, &stored,
+                                ));
+                    }
                 }
             }
         });
@@ -63,18 +263,241 @@ impl OutlookClient {
         Ok(Self { tx })
     }
 
-    pub async fn get_emails_last_n_days(
+    pub async fn list_emails_last_n_days(
         &self,
         days: i64,
-        folder_id: i32,
+        folder: &MailFolder,
         folder_name: &str,
-    ) -> Result<Vec<Email>> {
+        indexed: HashMap<String, DateTime<Utc>>,
+        cancel: &CancellationToken,
+    ) -> Result<Vec<EmailRef>> {
+        let since = Utc::now() - Duration::days(days);
+        self.list_emails_since(since, folder, folder_name, indexed, cancel)
+            .await
+    }
+
+    /// Items in the folder received at or after `since`, without opening
+    /// them. Items listed in `indexed` (entry id to when it was last indexed)
+    /// are only included when Outlook modified them after that. Fails with
+    /// [`cancelled`] once `cancel` fires, without waiting for the listing.
+    pub async fn list_emails_since(
+        &self,
+        since: DateTime<Utc>,
+        folder: &MailFolder,
+        folder_name: &str,
+        indexed: HashMap<String, DateTime<Utc>>,
+        cancel: &CancellationToken,
+    ) -> Result<Vec<EmailRef>> {
+        let (reply_tx, reply_rx) = oneshot::channel();
+        self.tx
+            .send(OutlookRequest::ListEmailsSince {
+                since,
+                folder: folder.clone(),
+                folder_name: folder_name.to_string(),
+                indexed,
+                cancel: cancel.clone(),
+                reply: reply_tx,
+            })
+            .await
+            .map_err(|e| NoodleError::Outlook(format!("Failed to send request: {}", e)))?;
+
+        tokio::select! {
+            reply = reply_rx => reply
+                .map_err(|e| NoodleError::Outlook(format!("Failed to receive response: {}", e)))?,
+            _ = cancel.cancelled() => Err(cancelled()),
+        }
+    }
+
+    /// Starts fetching the listed items of `folder_name`, a page at a time,
+    /// while the caller works through what already arrived. Stops early when
+    /// the stream is dropped or `cancel` fires; the Outlook thread checks
+    /// `cancel` between items.
+    pub fn stream_emails(
+        &self,
+        folder_name: &str,
+        entry_ids: Vec<String>,
+        cancel: &CancellationToken,
+    ) -> EmailStream {
+        let (tx, rx) = mpsc::channel(STREAM_BUFFER);
+        let client = self.clone();
+        let folder_name = folder_name.to_string();
+        let cancel = cancel.clone();
+        tokio::spawn(async move {
+            for page in entry_ids.chunks(FETCH_PAGE_SIZE) {
+                if cancel.is_cancelled() {
+                    return;
+                }
+                let fetched = client.fetch_emails(&folder_name, page.to_vec(), cancel.clone());
+                let emails = match fetched.await {
+                    Ok(emails) => emails,
+                    Err(e) => {
+                        let _ = tx.send(Err(e)).await;
+                        return;
+                    }
+                };
+                for email in emails {
+                    if tx.send(email).await.is_err() {
+                        return;
+                    }
+                }
+            }
+        });
+        EmailStream { rx }
+    }
+
+    async fn fetch_emails(
+        &self,
+        folder_name: &str,
+        entry_ids: Vec<String>,
+        cancel: CancellationToken,
+    ) -> Result<Vec<Result<Email>>> {
         let (reply_tx, reply_rx) = oneshot::channel();
         self.tx
-            .send(OutlookRequest::GetEmailsLastNDays {
-                days,
-                folder_id,
+            .send(OutlookRequest::FetchEmails {
+                entry_ids,
                 folder_name: folder_name.to_string(),
+                cancel,
+                reply: reply_tx,
+            })
+            .await
+            .map_err(|e| NoodleError::Outlook(format!("Failed to send request: {}", e)))?;
+
+        reply_rx
+            .await
+            .map_err(|e| NoodleError::Outlook(format!("Failed to receive response: {}", e)))
+    }
+
+    /// Emails in the folder received at or after `since`, as
+    /// [`Self::list_emails_since`] lists them. For short windows such as
+    /// delta scans; longer ones should use [`Self::stream_emails`].
+    pub async fn get_emails_since(
+        &self,
+        since: DateTime<Utc>,
+        folder: &MailFolder,
+        folder_name: &str,
+        indexed: HashMap<String, DateTime<Utc>>,
+        cancel: &CancellationToken,
+    ) -> Result<Vec<Email>> {
+        let listed = self
+            .list_emails_since(since, folder, folder_name, indexed, cancel)
+            .await?;
+        let entry_ids = listed.into_iter().map(|e| e.entry_id).collect();
+        let mut stream = self.stream_emails(folder_name, entry_ids, cancel);
+        let mut emails = Vec::new();
+        while let Some(email) = stream.next().await {
+            match email {
+                Ok(email) => emails.push(email),
+                Err(e) => tracing::warn!(
+                    "Failed to map Outlook item to Email struct in {}: {}",
+                    folder_name,
+                    e
+                ),
+            }
+        }
+        if cancel.is_cancelled() {
+            return Err(cancelled());
+        }
+        Ok(emails)
+    }
+
+    /// Opens the original message in an Outlook inspector window.
+    pub async fn display(&self, entry_id: &str, store_id: &str) -> Result<()> {
+        let (reply_tx, reply_rx) = oneshot::channel();
+        self.tx
+            .send(OutlookRequest::Display {
+                entry_id: entry_id.to_string(),
+                store_id: store_id.to_string(),
+                reply: reply_tx,
+            })
+            .await
+            .map_err(|e| NoodleError::Outlook(format!("Failed to send request: {}", e)))?;
+
+        reply_rx
+            .await
+            .map_err(|e| NoodleError::Outlook(format!("Failed to receive response: {}", e)))?
+    }
+
+    /// Whether the user has automatic replies (out of office) turned on.
+    pub async fn out_of_office(&self) -> Result<bool> {
+        let (reply_tx, reply_rx) = oneshot::channel();
+        self.tx
+            .send(OutlookRequest::OutOfOffice { reply: reply_tx })
+            .await
+            .map_err(|e| NoodleError::Outlook(format!("Failed to send request: {}", e)))?;
+
+        reply_rx
+            .await
+            .map_err(|e| NoodleError::Outlook(format!("Failed to receive response: {}", e)))?
+    }
+
+    /// Adds `category` to the message's categories and saves it. A category
+    /// missing from the mailbox's list is added with `color`; existing ones
+    /// keep theirs. Returns false when the message already had it.
+    pub async fn add_category(
+        &self,
+        entry_id: &str,
+        store_id: &str,
+        category: &str,
+        color: i32,
+    ) -> Result<bool> {
+        let (reply_tx, reply_rx) = oneshot::channel();
+        self.tx
+            .send(OutlookRequest::AddCategory {
+                entry_id: entry_id.to_string(),
+                store_id: store_id.to_string(),
+                category: category.to_string(),
+                color,
+                reply: reply_tx,
+            })
+            .await
+            .map_err(|e| NoodleError::Outlook(format!("Failed to send request: {}", e)))?;
+
+        reply_rx
+            .await
+            .map_err(|e| NoodleError::Outlook(format!("Failed to receive response: {}", e)))?
+    }
+
+    /// Lists the message's file attachments and saves those `policy` allows
+    /// into `dir`, under names of their own. Those in `stored`, by file name
+    /// and size, are listed without being saved again.
+    pub async fn save_attachments(
+        &self,
+        entry_id: &str,
+        store_id: &str,
+        policy: &AttachmentPolicy,
+        dir: &Path,
+        stored: HashSet<(String, i64)>,
+    ) -> Result<Vec<SavedAttachment>> {
+        let (reply_tx, reply_rx) = oneshot::channel();
+        self.tx
+            .send(OutlookRequest::SaveAttachments {
+                entry_id: entry_id.to_string(),
+                store_id: store_id.to_string(),
+                policy: policy.clone(),
+                dir: dir.to_path_buf(),
+                stored,
+                reply: reply_tx,
+            })
+            .await
+            .map_err(|e| NoodleError::Outlook(format!("Failed to send request: {}", e)))?;
+
+        reply_rx
+            .await
+            .map_err(|e| NoodleError::Outlook(format!("Failed to receive response: {}", e)))?
+    }
+
+    /// Meetings in the default calendar that end within the range, with
+    /// each occurrence of a recurring meeting listed on its own.
+    pub async fn list_meetings(
+        &self,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<Vec<Meeting>> {
+        let (reply_tx, reply_rx) = oneshot::channel();
+        self.tx
+            .send(OutlookRequest::ListMeetings {
+                start,
+                end,
                 reply: reply_tx,
             })
             .await
@@ -84,6 +507,179 @@ impl OutlookClient {
             .await
             .map_err(|e| NoodleError::Outlook(format!("Failed to receive response: {}", e)))?
     }
+
+    /// When the user's calendar shows them busy or out of office within the
+    /// range, as start and end times. All-day events are left out.
+    pub async fn busy_times(
+        &self,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<Vec<TimeSpan>> {
+        let (reply_tx, reply_rx) = oneshot::channel();
+        self.tx
+            .send(OutlookRequest::BusyTimes {
+                start,
+                end,
+                reply: reply_tx,
+            })
+            .await
+            .map_err(|e| NoodleError::Outlook(format!("Failed to send request: {}", e)))?;
+
+        reply_rx
+            .await
+            .map_err(|e| NoodleError::Outlook(format!("Failed to receive response: {}", e)))?
+    }
+}
+
+/// A Restrict filter for items received at or after `since`. Jet filters
+/// (`[ReceivedTime] >= '...'`) parse dates with the regional settings of the
+/// machine, so they break on non-English installs; a DASL query compares in
+/// UTC and takes an ISO-style date whatever the locale.
+fn received_since_filter(since: DateTime<Utc>) -> String {
+    format!(
+        "@SQL=\"urn:schemas:httpmail:datereceived\" >= '{}'",
+        since.format("%Y-%m-%d %H:%M")
+    )
+}
+
+/// A Restrict filter for appointments overlapping the range, in DASL for
+/// the same reason as [`received_since_filter`].
+fn overlapping_filter(start: DateTime<Utc>, end: DateTime<Utc>) -> String {
+    format!(
+        "@SQL=\"urn:schemas:calendar:dtend\" > '{}' AND \"urn:schemas:calendar:dtstart\" < '{}'",
+        start.format("%Y-%m-%d %H:%M"),
+        end.format("%Y-%m-%d %H:%M")
+    )
+}
+
+/// A Restrict filter for appointments ending within the range, in DASL for
+/// the same reason as [`received_since_filter`].
+fn ending_between_filter(start: DateTime<Utc>, end: DateTime<Utc>) -> String {
+    format!(
+        "@SQL=\"urn:schemas:calendar:dtend\" >= '{}' AND \"urn:schemas:calendar:dtend\" <= '{}'",
+        start.format("%Y-%m-%d %H:%M"),
+        end.format("%Y-%m-%d %H:%M")
+    )
+}
+
+fn string_property(item: &ComDispatch, name: &str) -> String {
+    item.get_property(name)
+        .ok()
+        .and_then(|v| BSTR::try_from(&v).ok())
+        .map(|s| s.to_string())
+        .unwrap_or_default()
+}
+
+/// The MIME type of a file going by its extension, for attachments Outlook
+/// has no type for.
+fn mime_from_filename(filename: &str) -> &'static str {
+    let extension = filename
+        .rsplit_once('.')
+        .map(|(_, ext)| ext.to_ascii_lowercase())
+        .unwrap_or_default();
+    match extension.as_str() {
+        "pdf" => "application/pdf",
+        "txt" | "log" => "text/plain",
+        "csv" => "text/csv",
+        "md" => "text/markdown",
+        "htm" | "html" => "text/html",
+        "ics" => "text/calendar",
+        "json" => "application/json",
+        "xml" => "application/xml",
+        "doc" => "application/msword",
+        "xls" => "application/vnd.ms-excel",
+        "ppt" => "application/vnd.ms-powerpoint",
+        "docx" => "application/vnd.openxmlformats-officedocument.wordprocessingml.document",
+        "xlsx" => "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet",
+        "pptx" => "application/vnd.openxmlformats-officedocument.presentationml.presentation",
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "zip" => "application/zip",
+        _ => "application/octet-stream",
+    }
+}
+
+/// Returned by work stopped through its cancellation token.
+pub fn cancelled() -> NoodleError {
+    NoodleError::Outlook("Cancelled".into())
+}
+
+/// The folder of `parent` called `name`, ignoring case. With `nested`, the
+/// folders below are searched too, after those directly in `parent`.
+fn subfolder(parent: &ComDispatch, name: &str, nested: bool) -> Result<Option<ComDispatch>> {
+    let folders = dispatch(parent.get_property("Folders")?, "folders")?;
+    let count = i32::try_from(&folders.get_property("Count")?).unwrap_or(0);
+    let mut children = Vec::new();
+    for index in 1..=count {
+        let child = dispatch(
+            folders.call_method("Item", &mut [VARIANT::from(index)])?,
+            "folder",
+        )?;
+        if string_property(&child, "Name").eq_ignore_ascii_case(name) {
+            return Ok(Some(child));
+        }
+        children.push(child);
+    }
+    if nested {
+        for child in &children {
+            if let Some(found) = subfolder(child, name, true)? {
+                return Ok(Some(found));
+            }
+        }
+    }
+    Ok(None)
+}
+
+fn dispatch(var: VARIANT, what: &str) -> Result<ComDispatch> {
+    IDispatch::try_from(&var)
+        .map(ComDispatch)
+        .map_err(|e| NoodleError::Outlook(format!("Failed to get dispatch for {}: {}", what, e)))
+}
+
+/// The value of header `name` in a raw header block, unfolding continuation
+/// lines.
+pub fn header_value(headers: &str, name: &str) -> Option<String> {
+    let mut lines = headers.lines();
+    while let Some(line) = lines.next() {
+        let Some((key, value)) = line.split_once(':') else {
+            continue;
+        };
+        if !key.trim().eq_ignore_ascii_case(name) {
+            continue;
+        }
+        let mut value = value.trim().to_string();
+        for next in lines.by_ref() {
+            if !next.starts_with([' ', '\t']) {
+                break;
+            }
+            value.push(' ');
+            value.push_str(next.trim());
+        }
+        return Some(value).filter(|v| !v.is_empty());
+    }
+    None
+}
+
+/// Converts an OLE automation date in local time, as Outlook tables report
+/// built-in date columns, to UTC.
+fn local_ole_date_to_utc(value: f64) -> Option<DateTime<Utc>> {
+    let epoch = NaiveDate::from_ymd_opt(1899, 12, 30)?.and_hms_opt(0, 0, 0)?;
+    let local = epoch + Duration::milliseconds((value * 86_400_000.0) as i64);
+    Local
+        .from_local_datetime(&local)
+        .earliest()
+        .map(|dt| dt.with_timezone(&Utc))
+}
+
+/// Reads an OLE automation date as if it were UTC, which is how emails get
+/// their `received_at`. Listings use it too, so scan checkpoints compare
+/// the same values.
+fn ole_date_as_utc(value: f64) -> DateTime<Utc> {
+    let unix_epoch_offset_days = 25569.0;
+    let seconds_in_day = 86400.0;
+    let unix_timestamp = (value - unix_epoch_offset_days) * seconds_in_day;
+    DateTime::from_timestamp(unix_timestamp as i64, 0).unwrap_or_else(Utc::now)
 }
 
 struct InnerClient {
@@ -114,89 +710,409 @@ impl InnerClient {
         }
     }
 
-    fn get_emails_last_n_days(
-        &self,
-        days: i64,
-        folder_id: i32,
-        folder_name: &str,
-    ) -> Result<Vec<Email>> {
-        tracing::info!(
-            "Starting Outlook sync for folder: {} (ID: {})",
-            folder_name,
-            folder_id
-        );
+    fn display(&self, entry_id: &str, store_id: &str) -> Result<()> {
+        self.get_item(entry_id, store_id)?
+            .call_method("Display", &mut [])?;
+        Ok(())
+    }
 
-        let folder_var = self
+    fn get_item(&self, entry_id: &str, store_id: &str) -> Result<ComDispatch> {
+        let mut args = vec![VARIANT::from(entry_id)];
+        if store_id != DEFAULT_STORE_ID {
+            args.push(VARIANT::from(store_id));
+        }
+        let item_var = self
             .namespace
-            .call_method("GetDefaultFolder", &mut [VARIANT::from(folder_id)])?;
+            .call_method("GetItemFromID", &mut args)
+            .map_err(|e| {
+                NoodleError::Outlook(format!(
+                    "The message is no longer in Outlook; it may have been moved or deleted ({})",
+                    e
+                ))
+            })?;
+        Ok(ComDispatch(IDispatch::try_from(&item_var).map_err(
+            |e| NoodleError::Outlook(format!("Failed to get item {}: {}", entry_id, e)),
+        )?))
+    }
 
-        let folder = ComDispatch(IDispatch::try_from(&folder_var).map_err(|e| {
-            NoodleError::Outlook(format!("Failed to get folder {}: {}", folder_name, e))
-        })?);
+    fn add_category(
+        &self,
+        entry_id: &str,
+        store_id: &str,
+        category: &str,
+        color: i32,
+    ) -> Result<bool> {
+        let categories = dispatch(self.namespace.get_property("Categories")?, "categories")?;
+        // Item() returns Nothing, or fails on some versions, for a name
+        // that isn't in the list.
+        let known = categories
+            .call_method("Item", &mut [VARIANT::from(category)])
+            .is_ok_and(|c| IDispatch::try_from(&c).is_ok());
+        if !known {
+            categories.call_method("Add", &mut [VARIANT::from(category), VARIANT::from(color)])?;
+        }
 
-        let items_var = folder.get_property("Items")?;
-        let items = ComDispatch(IDispatch::try_from(&items_var).map_err(|e| {
-            NoodleError::Outlook(format!("Failed to get Items for {}: {}", folder_name, e))
-        })?);
+        let item = self.get_item(entry_id, store_id)?;
+        // Outlook separates an item's categories with the list separator of
+        // the user's locale.
+        let current = BSTR::try_from(&item.get_property("Categories")?)
+            .map(|s| s.to_string())
+            .unwrap_or_default();
+        if current
+            .split([',', ';'])
+            .any(|c| c.trim().eq_ignore_ascii_case(category))
+        {
+            return Ok(false);
+        }
+        let updated = if current.trim().is_empty() {
+            category.to_string()
+        } else {
+            format!("{}, {}", current, category)
+        };
+        item.set_property("Categories", VARIANT::from(updated.as_str()))?;
+        item.call_method("Save", &mut [])?;
+        Ok(true)
+    }
 
-        let filter_date = Utc::now() - Duration::days(days);
-        let filter = format!(
-            "[ReceivedTime] >= '{}'",
-            filter_date.format("%d %b %Y %H:%M %p")
+    fn save_attachments(
+        &self,
+        entry_id: &str,
+        store_id: &str,
+        policy: &AttachmentPolicy,
+        dir: &Path,
+        stored: &HashSet<(String, i64)>,
+    ) -> Result<Vec<SavedAttachment>> {
+        let item = self.get_item(entry_id, store_id)?;
+        let attachments = dispatch(item.get_property("Attachments")?, "attachments")?;
+        let count = i32::try_from(&attachments.get_property("Count")?).unwrap_or(0);
+        let mut saved = Vec::new();
+        for index in 1..=count {
+            let attachment = dispatch(
+                attachments.call_method("Item", &mut [VARIANT::from(index)])?,
+                "attachment",
+            )?;
+            if i32::try_from(&attachment.get_property("Type")?).unwrap_or(0) != OL_BY_VALUE {
+                continue;
+            }
+            let accessor = dispatch(
+                attachment.get_property("PropertyAccessor")?,
+                "attachment properties",
+            )?;
+            let hidden = accessor
+                .call_method("GetProperty", &mut [VARIANT::from(PR_ATTACHMENT_HIDDEN)])
+                .ok()
+                .and_then(|v| bool::try_from(&v).ok())
+                .unwrap_or(false);
+            if hidden {
+                continue;
+            }
+
+            let filename = string_property(&attachment, "FileName");
+            let mime = accessor
+                .call_method("GetProperty", &mut [VARIANT::from(PR_ATTACH_MIME_TAG)])
+                .ok()
+                .and_then(|v| BSTR::try_from(&v).ok())
+                .map(|s| s.to_string().to_ascii_lowercase())
+                .filter(|s| !s.is_empty())
+                .unwrap_or_else(|| mime_from_filename(&filename).to_string());
+            // Slightly more than the content, as it includes the attachment's
+            // properties.
+            let size_bytes = i32::try_from(&attachment.get_property("Size")?).unwrap_or(0) as i64;
+            let skipped_reason = policy.skip_reason(&mime, size_bytes);
+            let path = match skipped_reason {
+                Some(_) => None,
+                None if stored.contains(&(filename.clone(), size_bytes)) => None,
+                None => {
+                    let path = dir.join(format!(
+                        "{}-{}.part",
+                        Utc::now().timestamp_nanos_opt().unwrap_or_default(),
+                        index
+                    ));
+                    let target = path.to_string_lossy().to_string();
+                    attachment.call_method("SaveAsFile", &mut [VARIANT::from(target.as_str())])?;
+                    Some(path)
+                }
+            };
+            saved.push(SavedAttachment {
+                filename,
+                mime,
+                size_bytes,
+                path,
+                skipped_reason: skipped_reason.map(str::to_string),
+            });
+        }
+        Ok(saved)
+    }
+
+    fn list_meetings(&self, start: DateTime<Utc>, end: DateTime<Utc>) -> Result<Vec<Meeting>> {
+        let folder = dispatch(
+            self.namespace
+                .call_method("GetDefaultFolder", &mut [VARIANT::from(OL_FOLDER_CALENDAR)])?,
+            "calendar",
+        )?;
+        // Occurrences of recurring meetings are only listed from items
+        // sorted by start with recurrences included, before restricting.
+        let items = dispatch(folder.get_property("Items")?, "calendar items")?;
+        items.call_method("Sort", &mut [VARIANT::from("[Start]")])?;
+        items.set_property("IncludeRecurrences", VARIANT::from(true))?;
+        let filter = ending_between_filter(start, end);
+        let restricted = dispatch(
+            items.call_method("Restrict", &mut [VARIANT::from(filter.as_str())])?,
+            "calendar items",
+        )?;
+
+        let user = dispatch(self.namespace.get_property("CurrentUser")?, "current user")?;
+        let user_address = string_property(&user, "Address");
+
+        // A recurring series has no item count, so the items are walked
+        // until GetNext returns nothing.
+        let mut meetings = Vec::new();
+        let mut next = restricted.call_method("GetFirst", &mut [])?;
+        while let Ok(item) = IDispatch::try_from(&next).map(ComDispatch) {
+            match self.map_meeting(&item, &user_address) {
+                Ok(Some(meeting)) => meetings.push(meeting),
+                Ok(None) => {}
+                Err(e) => tracing::warn!("Failed to read calendar item: {}", e),
+            }
+            next = restricted.call_method("GetNext", &mut [])?;
+        }
+        tracing::info!(
+            "Outlook calendar has {} meetings ending between {} and {}",
+            meetings.len(),
+            start,
+            end
         );
+        Ok(meetings)
+    }
 
-        tracing::info!("Applying Outlook filter for {}: {}", folder_name, filter);
+    fn busy_times(&self, start: DateTime<Utc>, end: DateTime<Utc>) -> Result<Vec<TimeSpan>> {
+        let folder = dispatch(
+            self.namespace
+                .call_method("GetDefaultFolder", &mut [VARIANT::from(OL_FOLDER_CALENDAR)])?,
+            "calendar",
+        )?;
+        // See `list_meetings` on listing recurring meetings.
+        let items = dispatch(folder.get_property("Items")?, "calendar items")?;
+        items.call_method("Sort", &mut [VARIANT::from("[Start]")])?;
+        items.set_property("IncludeRecurrences", VARIANT::from(true))?;
+        let filter = overlapping_filter(start, end);
+        let restricted = dispatch(
+            items.call_method("Restrict", &mut [VARIANT::from(filter.as_str())])?,
+            "calendar items",
+        )?;
 
-        let filtered_items_var =
-            match items.call_method("Restrict", &mut [VARIANT::from(filter.as_str())]) {
-                Ok(v) => v,
-                Err(e) => {
-                    tracing::error!("Outlook Restrict method failed for {}: {}", folder_name, e);
-                    return Err(e);
+        let mut busy = Vec::new();
+        let mut next = restricted.call_method("GetFirst", &mut [])?;
+        while let Ok(item) = IDispatch::try_from(&next).map(ComDispatch) {
+            let status = item
+                .get_property("BusyStatus")
+                .ok()
+                .and_then(|v| i32::try_from(&v).ok())
+                .unwrap_or(0);
+            let all_day = item
+                .get_property("AllDayEvent")
+                .ok()
+                .and_then(|v| bool::try_from(&v).ok())
+                .unwrap_or(false);
+            if BUSY.contains(&status) && !all_day {
+                let time = |name: &str| {
+                    item.get_property(name)
+                        .ok()
+                        .and_then(|v| f64::try_from(&v).ok())
+                        .map(ole_date_as_utc)
+                };
+                if let (Some(from), Some(to)) = (time("StartUTC"), time("EndUTC")) {
+                    busy.push((from, to));
                 }
-            };
+            }
+            next = restricted.call_method("GetNext", &mut [])?;
+        }
+        Ok(busy)
+    }
 
-        let filtered_items =
-            ComDispatch(IDispatch::try_from(&filtered_items_var).map_err(|e| {
-                NoodleError::Outlook(format!(
-                    "Failed to restrict items in {}: {}",
-                    folder_name, e
-                ))
-            })?);
+    /// The appointment as a [`Meeting`], or `None` when it isn't one, e.g.
+    /// time the user blocked for themselves.
+    fn map_meeting(&self, item: &ComDispatch, user_address: &str) -> Result<Option<Meeting>> {
+        let status = i32::try_from(&item.get_property("MeetingStatus")?).unwrap_or(0);
+        if NOT_MEETINGS.contains(&status) {
+            return Ok(None);
+        }
 
-        let emails = self.parse_items(filtered_items, folder_name)?;
+        let recipients = dispatch(item.get_property("Recipients")?, "meeting recipients")?;
+        let count = i32::try_from(&recipients.get_property("Count")?).unwrap_or(0);
+        let mut attendees = Vec::new();
+        for index in 1..=count {
+            let recipient = dispatch(
+                recipients.call_method("Item", &mut [VARIANT::from(index)])?,
+                "meeting recipient",
+            )?;
+            let kind = i32::try_from(&recipient.get_property("Type")?).unwrap_or(0);
+            let address = string_property(&recipient, "Address");
+            if kind == OL_RESOURCE || address.eq_ignore_ascii_case(user_address) {
+                continue;
+            }
+            attendees.push(MeetingAttendee {
+                name: string_property(&recipient, "Name"),
+                address,
+            });
+        }
+        if attendees.is_empty() {
+            return Ok(None);
+        }
+
+        let global_id = Some(string_property(item, "GlobalAppointmentID"))
+            .filter(|id| !id.is_empty())
+            .unwrap_or_else(|| string_property(item, "EntryID"));
+        // The UTC variants, unlike Start and End, don't depend on the
+        // machine's time zone.
+        let start_at =
+            ole_date_as_utc(f64::try_from(&item.get_property("StartUTC")?).unwrap_or(0.0));
+        let end_at = ole_date_as_utc(f64::try_from(&item.get_property("EndUTC")?).unwrap_or(0.0));
+        Ok(Some(Meeting {
+            id: 0,
+            global_id,
+            subject: string_property(item, "Subject"),
+            organizer: string_property(item, "Organizer"),
+            attendees,
+            start_at,
+            end_at,
+            followed_up_at: None,
+            follow_up_error: None,
+        }))
+    }
+
+    fn out_of_office(&self) -> Result<bool> {
+        let store = dispatch(
+            self.namespace.get_property("DefaultStore")?,
+            "default store",
+        )?;
+        let accessor = dispatch(store.get_property("PropertyAccessor")?, "store properties")?;
+        // Stores without the property (e.g. PST-only profiles) have no OOF;
+        // GetProperty fails for them rather than returning nothing.
+        Ok(accessor
+            .call_method("GetProperty", &mut [VARIANT::from(PR_OOF_STATE)])
+            .ok()
+            .and_then(|state| bool::try_from(&state).ok())
+            .unwrap_or(false))
+    }
+
+    fn list_emails_since(
+        &self,
+        since: DateTime<Utc>,
+        folder: &MailFolder,
+        folder_name: &str,
+        indexed: &HashMap<String, DateTime<Utc>>,
+        cancel: &CancellationToken,
+    ) -> Result<Vec<EmailRef>> {
         tracing::info!(
-            "Outlook search in {} returned {} emails",
+            "Starting Outlook sync for folder: {} ({:?})",
             folder_name,
-            emails.len()
+            folder
         );
-        Ok(emails)
-    }
 
-    fn parse_items(&self, items: ComDispatch, folder_name: &str) -> Result<Vec<Email>> {
-        let count_var = items.get_property("Count")?;
-        let count = i32::try_from(&count_var).unwrap_or(0);
-        let mut emails = Vec::new();
+        let folder = self.folder(folder, folder_name)?;
 
-        for i in 1..=count {
-            let item_var = items.call_method("Item", &mut [VARIANT::from(i)])?;
-            let item_dispatch = IDispatch::try_from(&item_var);
-            if let Ok(dispatch) = item_dispatch {
-                let item = ComDispatch(dispatch);
-                if let Ok(mut email) = self.map_item_to_email(&item) {
-                    email.folder = folder_name.to_string();
-                    emails.push(email);
-                } else {
-                    tracing::warn!(
-                        "Failed to map Outlook item to Email struct in {} at index {}",
-                        folder_name,
-                        i
-                    );
+        let filter = received_since_filter(since);
+        tracing::info!("Applying Outlook filter for {}: {}", folder_name, filter);
+
+        let rows = self.list_rows(&folder, &filter, folder_name, cancel)?;
+        let total = rows.len();
+        let listed: Vec<EmailRef> = rows
+            .into_iter()
+            .filter(|(email, modified_at)| {
+                indexed
+                    .get(&email.entry_id)
+                    .is_none_or(|indexed_at| indexed_at < modified_at)
+            })
+            .map(|(email, _)| email)
+            .collect();
+        tracing::info!(
+            "Outlook search in {} matched {} items, {} new or modified",
+            folder_name,
+            total,
+            listed.len()
+        );
+        Ok(listed)
+    }
+
+    fn folder(&self, folder: &MailFolder, folder_name: &str) -> Result<ComDispatch> {
+        let not_found = || NoodleError::Outlook(format!("Folder {} not found", folder_name));
+        match folder {
+            MailFolder::Default(id) => dispatch(
+                self.namespace
+                    .call_method("GetDefaultFolder", &mut [VARIANT::from(*id)])?,
+                folder_name,
+            ),
+            MailFolder::Named(path) => {
+                let store = dispatch(self.namespace.get_property("DefaultStore")?, "store")?;
+                let mut folder = dispatch(store.call_method("GetRootFolder", &mut [])?, "store")?;
+                let names = path.split('/').map(str::trim).filter(|n| !n.is_empty());
+                for (depth, name) in names.enumerate() {
+                    folder = subfolder(&folder, name, depth == 0)?.ok_or_else(not_found)?;
                 }
+                Ok(folder)
             }
         }
+    }
 
-        Ok(emails)
+    /// Every item matching `filter` with its last modification time, read
+    /// from a table of a few columns so no item has to be opened.
+    fn list_rows(
+        &self,
+        folder: &ComDispatch,
+        filter: &str,
+        folder_name: &str,
+        cancel: &CancellationToken,
+    ) -> Result<Vec<(EmailRef, DateTime<Utc>)>> {
+        let table = dispatch(
+            folder.call_method(
+                "GetTable",
+                &mut [VARIANT::from(filter), VARIANT::from(OL_USER_ITEMS)],
+            )?,
+            folder_name,
+        )?;
+        let columns = dispatch(table.get_property("Columns")?, folder_name)?;
+        columns.call_method("RemoveAll", &mut [])?;
+        columns.call_method("Add", &mut [VARIANT::from("EntryID")])?;
+        columns.call_method("Add", &mut [VARIANT::from("LastModificationTime")])?;
+        columns.call_method("Add", &mut [VARIANT::from("ReceivedTime")])?;
+        columns.call_method("Add", &mut [VARIANT::from("SenderEmailAddress")])?;
+
+        let mut rows = Vec::new();
+        while !bool::try_from(&table.get_property("EndOfTable")?).unwrap_or(true) {
+            if cancel.is_cancelled() {
+                return Err(cancelled());
+            }
+            let row = dispatch(table.call_method("GetNextRow", &mut [])?, folder_name)?;
+            let entry_id = row.call_method("Item", &mut [VARIANT::from(1)])?;
+            let Ok(entry_id) = BSTR::try_from(&entry_id) else {
+                continue;
+            };
+            let modified = row.call_method("Item", &mut [VARIANT::from(2)])?;
+            // Unreadable times count as modified, so the item is fetched.
+            let modified_at = f64::try_from(&modified)
+                .ok()
+                .and_then(local_ole_date_to_utc)
+                .unwrap_or(DateTime::<Utc>::MAX_UTC);
+            let received = row.call_method("Item", &mut [VARIANT::from(3)])?;
+            let sender = row.call_method("Item", &mut [VARIANT::from(4)])?;
+            let email = EmailRef {
+                entry_id: entry_id.to_string(),
+                received_at: ole_date_as_utc(f64::try_from(&received).unwrap_or(0.0)),
+                sender: BSTR::try_from(&sender)
+                    .map(|s| s.to_string())
+                    .unwrap_or_default(),
+            };
+            rows.push((email, modified_at));
+        }
+        Ok(rows)
+    }
+
+    fn fetch_item(&self, entry_id: &str) -> Result<Email> {
+        let item_var = self
+            .namespace
+            .call_method("GetItemFromID", &mut [VARIANT::from(entry_id)])?;
+        self.map_item_to_email(&dispatch(item_var, entry_id)?)
     }
 
     fn map_item_to_email(&self, item: &ComDispatch) -> Result<Email> {
@@ -220,25 +1136,58 @@ impl InnerClient {
             .map(|s| s.to_string())
             .unwrap_or_else(|_| "Unknown".into());
 
-        let received_at_var = item.get_property("ReceivedTime")?;
-        let received_at_double = f64::try_from(&received_at_var).unwrap_or(0.0);
+        // Display names separated by semicolons, as Outlook shows them.
+        let to = item
+            .get_property("To")
+            .ok()
+            .and_then(|v| BSTR::try_from(&v).ok())
+            .map(|s| s.to_string())
+            .unwrap_or_default();
+        let cc = item
+            .get_property("CC")
+            .ok()
+            .and_then(|v| BSTR::try_from(&v).ok())
+            .map(|s| s.to_string())
+            .filter(|s| !s.is_empty());
 
-        let unix_epoch_offset_days = 25569.0;
-        let seconds_in_day = 86400.0;
-        let unix_timestamp = (received_at_double - unix_epoch_offset_days) * seconds_in_day;
-        let received_at =
-            DateTime::from_timestamp(unix_timestamp as i64, 0).unwrap_or_else(|| Utc::now());
+        let conversation_id = item
+            .get_property("ConversationID")
+            .ok()
+            .and_then(|v| BSTR::try_from(&v).ok())
+            .map(|s| s.to_string())
+            .filter(|s| !s.is_empty());
+
+        // Drafts and items created locally have neither.
+        let accessor = item
+            .get_property("PropertyAccessor")
+            .ok()
+            .and_then(|v| dispatch(v, "item properties").ok());
+        let string_property = |tag: &str| {
+            accessor
+                .as_ref()?
+                .call_method("GetProperty", &mut [VARIANT::from(tag)])
+                .ok()
+                .and_then(|v| BSTR::try_from(&v).ok())
+                .map(|s| s.to_string())
+                .filter(|s| !s.is_empty())
+        };
+        let internet_message_id = string_property(PR_INTERNET_MESSAGE_ID);
+        let list_unsubscribe = string_property(PR_TRANSPORT_MESSAGE_HEADERS)
+            .and_then(|headers| header_value(&headers, "List-Unsubscribe"));
+
+        let received_at_var = item.get_property("ReceivedTime")?;
+        let received_at = ole_date_as_utc(f64::try_from(&received_at_var).unwrap_or(0.0));
 
         Ok(Email {
             id: 0,
-            store_id: "outlook".into(),
+            store_id: DEFAULT_STORE_ID.into(),
             entry_id,
-            conversation_id: None,
+            conversation_id,
             folder: "Inbox".into(),
             subject,
             sender,
-            to: "".into(),
-            cc: None,
+            to,
+            cc,
             bcc: None,
             sent_at: received_at,
             received_at,
@@ -247,7 +1196,8 @@ impl InnerClient {
             importance: 1,
             categories: None,
             flags: None,
-            internet_message_id: None,
+            internet_message_id,
+            list_unsubscribe,
             last_indexed_at: Utc::now(),
             hash: "".into(),
             excluded_reason: None,
@@ -1,10 +1,13 @@
 use noodle_core::error::{NoodleError, Result};
 use windows::core::{BSTR, PCWSTR, VARIANT};
 use windows::Win32::System::Com::{
-    IDispatch, DISPATCH_FLAGS, DISPATCH_METHOD, DISPATCH_PROPERTYGET, DISPPARAMS, EXCEPINFO,
+    IDispatch, DISPATCH_FLAGS, DISPATCH_METHOD, DISPATCH_PROPERTYGET, DISPATCH_PROPERTYPUT,
+    DISPPARAMS, EXCEPINFO,
 };
 
 const LOCALE_USER_DEFAULT: u32 = 0x0400;
+/// Names the value argument of a property put.
+const DISPID_PROPERTYPUT: i32 = -3;
 
 /// A wrapper around IDispatch to make dynamic calls easier.
 pub struct ComDispatch(pub IDispatch);
@@ -17,6 +20,11 @@ impl ComDispatch {
         self.invoke(name, DISPATCH_PROPERTYGET.0 as u32, &mut [])
     }
 
+    pub fn set_property(&self, name: &str, value: VARIANT) -> Result<()> {
+        self.invoke(name, DISPATCH_PROPERTYPUT.0 as u32, &mut [value])?;
+        Ok(())
+    }
+
     pub fn call_method(&self, name: &str, args: &mut [VARIANT]) -> Result<VARIANT> {
         self.invoke(name, DISPATCH_METHOD.0 as u32, args)
     }
@@ -43,7 +51,12 @@ impl ComDispatch {
             if !args.is_empty() {
                 args.reverse(); // COM args are passed in reverse order
                 params.cArgs = args.len() as u32;
-                params.rgvarg = args.as_mut_ptr() as *mut VARIANT;
+                params.rgvarg = args.as_mut_ptr();
+            }
+            let mut put_id = DISPID_PROPERTYPUT;
+            if flags == DISPATCH_PROPERTYPUT.0 as u32 {
+                params.cNamedArgs = 1;
+                params.rgdispidNamedArgs = &mut put_id;
             }
 
             let mut result = VARIANT::default();
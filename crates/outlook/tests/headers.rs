@@ -0,0 +1,32 @@
+use outlook::client::header_value;
+
+const HEADERS: &str = "Received: from mail.example.com\r\n\
+From: News <news@example.com>\r\n\
+list-unsubscribe: <mailto:leave@example.com>,\r\n \
+<https://example.com/leave>\r\n\
+Subject: Weekly\r\n\
+X-Empty:\r\n";
+
+#[test]
+fn headers_are_found_without_case() {
+    assert_eq!(header_value(HEADERS, "Subject").as_deref(), Some("Weekly"));
+    assert_eq!(
+        header_value(HEADERS, "FROM").as_deref(),
+        Some("News <news@example.com>")
+    );
+}
+
+#[test]
+fn continuation_lines_are_unfolded() {
+    assert_eq!(
+        header_value(HEADERS, "List-Unsubscribe").as_deref(),
+        Some("<mailto:leave@example.com>, <https://example.com/leave>")
+    );
+}
+
+#[test]
+fn missing_and_empty_headers_have_no_value() {
+    assert_eq!(header_value(HEADERS, "List-Id"), None);
+    assert_eq!(header_value(HEADERS, "X-Empty"), None);
+    assert_eq!(header_value("", "Subject"), None);
+}
@@ -0,0 +1,27 @@
+-- Rebuild the FTS index over columns that exist on emails so snippet()/highlight() work
+DROP TRIGGER IF EXISTS emails_ai;
+DROP TRIGGER IF EXISTS emails_ad;
+DROP TRIGGER IF EXISTS emails_au;
+DROP TABLE IF EXISTS emails_fts;
+
+CREATE VIRTUAL TABLE emails_fts USING fts5(
+    subject,
+    body_text,
+    content='emails',
+    content_rowid='id'
+);
+
+CREATE TRIGGER emails_ai AFTER INSERT ON emails BEGIN
+  INSERT INTO emails_fts(rowid, subject, body_text) VALUES (new.id, new.subject, new.body_text);
+END;
+
+CREATE TRIGGER emails_ad AFTER DELETE ON emails BEGIN
+  INSERT INTO emails_fts(emails_fts, rowid, subject, body_text) VALUES('delete', old.id, old.subject, old.body_text);
+END;
+
+CREATE TRIGGER emails_au AFTER UPDATE ON emails BEGIN
+  INSERT INTO emails_fts(emails_fts, rowid, subject, body_text) VALUES('delete', old.id, old.subject, old.body_text);
+  INSERT INTO emails_fts(rowid, subject, body_text) VALUES (new.id, new.subject, new.body_text);
+END;
+
+INSERT INTO emails_fts(emails_fts) VALUES('rebuild');
@@ -0,0 +1,2 @@
+-- Keep the deadline phrase as written ("EOD Friday") next to the normalized due_by
+ALTER TABLE extracted_email_facts ADD COLUMN due_by_raw TEXT;
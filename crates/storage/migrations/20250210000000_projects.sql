@@ -0,0 +1,10 @@
+-- Per-project preferences. Rows exist only for projects the user has configured;
+-- everything else uses the defaults below.
+CREATE TABLE IF NOT EXISTS projects (
+    name TEXT PRIMARY KEY COLLATE NOCASE, -- matches client_or_project_json.name
+    mute_notifications BOOLEAN NOT NULL DEFAULT 0,
+    extraction_enabled BOOLEAN NOT NULL DEFAULT 1,
+    include_in_digest BOOLEAN NOT NULL DEFAULT 1,
+    retention_days INTEGER, -- NULL = global retention
+    updated_at DATETIME NOT NULL
+);
@@ -0,0 +1,9 @@
+-- Initial-scan progress per folder, so an interrupted scan resumes instead of
+-- starting over. Rows are cleared once the scan completes.
+CREATE TABLE IF NOT EXISTS scan_checkpoints (
+    folder TEXT PRIMARY KEY,
+    last_received_at DATETIME NOT NULL, -- newest email processed; scan runs oldest-first
+    processed INTEGER NOT NULL,
+    total INTEGER NOT NULL,
+    updated_at DATETIME NOT NULL
+);
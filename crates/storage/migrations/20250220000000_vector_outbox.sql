@@ -0,0 +1,10 @@
+-- Write-ahead outbox for Qdrant: a row is added in the same transaction that
+-- saves the email and removed once its vector is upserted. Anything left here
+-- is replayed at startup and before each delta scan.
+CREATE TABLE IF NOT EXISTS vector_outbox (
+    email_id INTEGER PRIMARY KEY,
+    enqueued_at DATETIME NOT NULL,
+    attempts INTEGER NOT NULL DEFAULT 0,
+    last_error TEXT,
+    FOREIGN KEY(email_id) REFERENCES emails(id) ON DELETE CASCADE
+);
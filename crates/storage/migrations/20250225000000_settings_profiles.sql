@@ -0,0 +1,7 @@
+-- Named snapshots of the non-secret settings, e.g. "work laptop" or "demo".
+CREATE TABLE IF NOT EXISTS settings_profiles (
+    name TEXT PRIMARY KEY COLLATE NOCASE,
+    settings_json TEXT NOT NULL, -- {"key": "value", ...} as stored in app_config
+    created_at DATETIME NOT NULL,
+    updated_at DATETIME NOT NULL
+);
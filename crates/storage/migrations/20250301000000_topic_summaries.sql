@@ -0,0 +1,15 @@
+-- Topic summaries, stored like periodic_runs but keyed by the query and date
+-- range they were built for instead of a prompt.
+CREATE TABLE IF NOT EXISTS topic_summaries (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    query TEXT NOT NULL,
+    range_start DATETIME,
+    range_end DATETIME,
+    run_at DATETIME NOT NULL,
+    status TEXT NOT NULL,
+    output_json TEXT, -- the per-email sources
+    output_text TEXT,
+    error_text TEXT
+);
+
+CREATE INDEX IF NOT EXISTS idx_topic_summaries_run_at ON topic_summaries(run_at);
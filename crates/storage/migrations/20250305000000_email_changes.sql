@@ -0,0 +1,19 @@
+-- Facts extracted before the email's content changed; cleared when they are
+-- extracted again.
+ALTER TABLE extracted_email_facts ADD COLUMN stale BOOLEAN NOT NULL DEFAULT 0;
+
+-- One row each time a sync finds an indexed email with a different hash, e.g.
+-- a message re-sent or edited in place.
+CREATE TABLE IF NOT EXISTS email_changes (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    email_id INTEGER NOT NULL,
+    detected_at DATETIME NOT NULL,
+    previous_subject TEXT, -- NULL when the subject did not change
+    subject TEXT,
+    body_diff TEXT, -- unified diff of body_text; NULL when the body did not change
+    lines_added INTEGER NOT NULL DEFAULT 0,
+    lines_removed INTEGER NOT NULL DEFAULT 0,
+    FOREIGN KEY(email_id) REFERENCES emails(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_email_changes_email ON email_changes(email_id, detected_at);
@@ -0,0 +1,16 @@
+-- Relations between people inferred from who mails whom. Unlike `edges` they
+-- are not tied to one email and are recomputed; the user's confirm or dismiss
+-- decision survives recomputation.
+CREATE TABLE IF NOT EXISTS inferred_relations (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    src_entity_id INTEGER NOT NULL,
+    dst_entity_id INTEGER NOT NULL,
+    edge_type TEXT NOT NULL, -- frequent_collaborator|same_org
+    confidence REAL NOT NULL,
+    evidence TEXT NOT NULL,
+    status TEXT NOT NULL DEFAULT 'suggested', -- suggested|confirmed|dismissed
+    updated_at DATETIME NOT NULL,
+    UNIQUE(src_entity_id, dst_entity_id, edge_type),
+    FOREIGN KEY(src_entity_id) REFERENCES entities(id) ON DELETE CASCADE,
+    FOREIGN KEY(dst_entity_id) REFERENCES entities(id) ON DELETE CASCADE
+);
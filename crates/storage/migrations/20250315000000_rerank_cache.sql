@@ -0,0 +1,9 @@
+-- LLM re-ranking results, keyed by a hash of the query and its candidates so a
+-- repeated search skips the model call until new mail changes the candidates.
+CREATE TABLE IF NOT EXISTS rerank_cache (
+    cache_key TEXT PRIMARY KEY,
+    ranked_ids TEXT NOT NULL, -- JSON array of email ids, most relevant first
+    created_at DATETIME NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_rerank_cache_created_at ON rerank_cache(created_at);
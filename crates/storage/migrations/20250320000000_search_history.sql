@@ -0,0 +1,9 @@
+-- Searches the user ran, for history and query suggestions.
+CREATE TABLE IF NOT EXISTS search_history (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    query TEXT NOT NULL,
+    result_count INTEGER NOT NULL,
+    searched_at DATETIME NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_search_history_searched_at ON search_history(searched_at);
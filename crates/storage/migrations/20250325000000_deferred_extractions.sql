@@ -0,0 +1,7 @@
+-- Emails saved and embedded whose fact extraction waits for the user to go
+-- idle. Rows are removed once extraction has run.
+CREATE TABLE IF NOT EXISTS deferred_extractions (
+    email_id INTEGER PRIMARY KEY,
+    deferred_at DATETIME NOT NULL,
+    FOREIGN KEY(email_id) REFERENCES emails(id) ON DELETE CASCADE
+);
@@ -0,0 +1,15 @@
+-- Anomalies noticed in extracted facts: hostile senders, urgent floods,
+-- extraction confidence dropping. `alert_key` is the sender, project or
+-- other subject of the alert, used to hold back repeats.
+CREATE TABLE IF NOT EXISTS alerts (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    kind TEXT NOT NULL,
+    alert_key TEXT NOT NULL,
+    message TEXT NOT NULL,
+    email_id INTEGER,
+    created_at DATETIME NOT NULL,
+    acknowledged BOOLEAN NOT NULL DEFAULT 0,
+    FOREIGN KEY(email_id) REFERENCES emails(id) ON DELETE SET NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_alerts_kind_key ON alerts(kind, alert_key, created_at);
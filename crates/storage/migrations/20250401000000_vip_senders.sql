@@ -0,0 +1,6 @@
+-- Senders whose mail matters most to the user: ranked first in the digest,
+-- notified about during quiet hours and extracted ahead of the backfill.
+CREATE TABLE IF NOT EXISTS vip_senders (
+    sender TEXT PRIMARY KEY COLLATE NOCASE,
+    added_at DATETIME NOT NULL
+);
@@ -0,0 +1,33 @@
+-- Risks, issues and blockers from different emails grouped into canonical
+-- issues by embedding similarity.
+CREATE TABLE IF NOT EXISTS issue_clusters (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    kind TEXT NOT NULL, -- risk|issue|blocker, of the first mention
+    title TEXT NOT NULL,
+    details TEXT NOT NULL,
+    severity TEXT NOT NULL, -- highest among the mentions
+    centroid_json TEXT NOT NULL, -- mean embedding of the mentions
+    mention_count INTEGER NOT NULL DEFAULT 0,
+    first_seen DATETIME NOT NULL,
+    last_seen DATETIME NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS issue_mentions (
+    email_id INTEGER NOT NULL,
+    kind TEXT NOT NULL,
+    position INTEGER NOT NULL, -- index in the email's risks/issues/blockers
+    cluster_id INTEGER NOT NULL,
+    title TEXT NOT NULL,
+    details TEXT NOT NULL,
+    severity TEXT NOT NULL,
+    project TEXT,
+    received_at DATETIME NOT NULL,
+    PRIMARY KEY(email_id, kind, position),
+    FOREIGN KEY(email_id) REFERENCES emails(id) ON DELETE CASCADE,
+    FOREIGN KEY(cluster_id) REFERENCES issue_clusters(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_issue_mentions_cluster ON issue_mentions(cluster_id);
+
+-- Cleared whenever facts are saved, so re-extracted emails are grouped again.
+ALTER TABLE extracted_email_facts ADD COLUMN issues_clustered BOOLEAN NOT NULL DEFAULT 0;
@@ -0,0 +1,22 @@
+-- Where each canonical issue stands, worked out from the emails after its
+-- last mention.
+ALTER TABLE issue_clusters ADD COLUMN status TEXT NOT NULL DEFAULT 'open'; -- open|mitigated|resolved
+ALTER TABLE issue_clusters ADD COLUMN mitigated_at DATETIME;
+ALTER TABLE issue_clusters ADD COLUMN resolved_at DATETIME;
+
+-- Later emails reporting an issue being handled (mitigated) or handled
+-- (resolved).
+CREATE TABLE IF NOT EXISTS issue_updates (
+    email_id INTEGER NOT NULL,
+    cluster_id INTEGER NOT NULL,
+    status TEXT NOT NULL, -- mitigated|resolved
+    received_at DATETIME NOT NULL,
+    PRIMARY KEY(email_id, cluster_id),
+    FOREIGN KEY(email_id) REFERENCES emails(id) ON DELETE CASCADE,
+    FOREIGN KEY(cluster_id) REFERENCES issue_clusters(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_issue_updates_cluster ON issue_updates(cluster_id);
+
+-- Cleared whenever facts are saved, so re-extracted emails are checked again.
+ALTER TABLE extracted_email_facts ADD COLUMN issues_tracked BOOLEAN NOT NULL DEFAULT 0;
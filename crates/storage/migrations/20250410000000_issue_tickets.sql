@@ -0,0 +1,14 @@
+-- Tickets filed in Jira or Azure DevOps from extracted risks, issues and
+-- blockers, kept to link back to them.
+CREATE TABLE IF NOT EXISTS issue_tickets (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    email_id INTEGER NOT NULL,
+    kind TEXT NOT NULL, -- risk|issue|blocker
+    position INTEGER NOT NULL, -- index in the email's risks/issues/blockers
+    tracker TEXT NOT NULL, -- jira|azure_devops
+    ticket_key TEXT NOT NULL,
+    url TEXT NOT NULL,
+    created_at DATETIME NOT NULL,
+    UNIQUE(email_id, kind, position, tracker),
+    FOREIGN KEY(email_id) REFERENCES emails(id) ON DELETE CASCADE
+);
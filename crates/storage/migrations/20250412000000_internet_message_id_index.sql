@@ -0,0 +1,2 @@
+-- Companion clients look emails up by their Message-ID header.
+CREATE INDEX IF NOT EXISTS idx_emails_internet_message_id ON emails(internet_message_id);
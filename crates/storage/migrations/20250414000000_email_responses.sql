@@ -0,0 +1,12 @@
+-- Inbound mail that needed a response, linked to the first Sent Items
+-- message after it in the same conversation.
+CREATE TABLE IF NOT EXISTS email_responses (
+    email_id INTEGER PRIMARY KEY,
+    response_email_id INTEGER NOT NULL,
+    responded_at DATETIME NOT NULL,
+    latency_secs INTEGER NOT NULL,
+    FOREIGN KEY(email_id) REFERENCES emails(id) ON DELETE CASCADE,
+    FOREIGN KEY(response_email_id) REFERENCES emails(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_emails_conversation ON emails(conversation_id);
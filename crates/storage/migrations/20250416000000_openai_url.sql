@@ -0,0 +1,6 @@
+-- OpenAI-compatible cloud endpoints used to be stored as ollama_url; they
+-- get their own key so a local Ollama can sit in front of them.
+INSERT OR IGNORE INTO app_config (key, value, updated_at)
+SELECT 'openai_url', value, updated_at FROM app_config
+WHERE key = 'ollama_url'
+  AND EXISTS (SELECT 1 FROM app_config WHERE key = 'provider_type' AND value = 'openai');
@@ -0,0 +1,3 @@
+-- Why an email's facts should be checked by hand before they are trusted,
+-- e.g. signs of a prompt injection attempt. NULL once reviewed.
+ALTER TABLE extracted_email_facts ADD COLUMN review_reason TEXT;
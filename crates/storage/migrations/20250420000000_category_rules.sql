@@ -0,0 +1,38 @@
+-- User rules that label extracted emails with an Outlook category, and
+-- optionally set it on the message in Outlook.
+CREATE TABLE IF NOT EXISTS category_rules (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    match_on TEXT NOT NULL, -- project, sender or primary_type
+    pattern TEXT NOT NULL,
+    category TEXT NOT NULL,
+    color INTEGER NOT NULL DEFAULT 0, -- OlCategoryColor
+    write_back BOOLEAN NOT NULL DEFAULT 0,
+    created_at DATETIME NOT NULL
+);
+
+-- The categories rules gave each email, as of its latest extraction.
+CREATE TABLE IF NOT EXISTS email_categories (
+    email_id INTEGER NOT NULL,
+    category TEXT NOT NULL COLLATE NOCASE,
+    rule_id INTEGER,
+    labelled_at DATETIME NOT NULL,
+    PRIMARY KEY (email_id, category),
+    FOREIGN KEY(email_id) REFERENCES emails(id) ON DELETE CASCADE,
+    FOREIGN KEY(rule_id) REFERENCES category_rules(id) ON DELETE SET NULL
+);
+
+-- Every rule change and every category given to an email or written to
+-- Outlook. Kept when the rule or email goes away.
+CREATE TABLE IF NOT EXISTS category_audit (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    action TEXT NOT NULL,
+    rule_id INTEGER,
+    email_id INTEGER,
+    category TEXT NOT NULL,
+    detail TEXT,
+    created_at DATETIME NOT NULL,
+    FOREIGN KEY(rule_id) REFERENCES category_rules(id) ON DELETE SET NULL,
+    FOREIGN KEY(email_id) REFERENCES emails(id) ON DELETE SET NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_category_audit_created ON category_audit(created_at);
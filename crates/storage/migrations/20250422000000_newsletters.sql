@@ -0,0 +1,20 @@
+-- Mailing list mail: kept out of fact extraction, and so out of the digest
+-- and project dashboards, and summarized in a weekly roundup instead.
+ALTER TABLE emails ADD COLUMN list_unsubscribe TEXT;
+ALTER TABLE emails ADD COLUMN newsletter BOOLEAN NOT NULL DEFAULT 0;
+
+CREATE INDEX IF NOT EXISTS idx_emails_newsletter ON emails(newsletter, received_at);
+
+-- Stored like topic_summaries, for the date range each roundup covers.
+CREATE TABLE IF NOT EXISTS newsletter_roundups (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    range_start DATETIME,
+    range_end DATETIME,
+    run_at DATETIME NOT NULL,
+    status TEXT NOT NULL,
+    output_json TEXT, -- the per-newsletter sources
+    output_text TEXT,
+    error_text TEXT
+);
+
+CREATE INDEX IF NOT EXISTS idx_newsletter_roundups_run_at ON newsletter_roundups(run_at);
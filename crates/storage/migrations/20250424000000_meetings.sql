@@ -0,0 +1,42 @@
+-- Meetings read from the Outlook calendar once they have ended, one row per
+-- occurrence of a recurring meeting.
+CREATE TABLE IF NOT EXISTS meetings (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    global_id TEXT NOT NULL,
+    subject TEXT NOT NULL,
+    organizer TEXT NOT NULL,
+    attendees_json TEXT NOT NULL,
+    start_at DATETIME NOT NULL,
+    end_at DATETIME NOT NULL,
+    followed_up_at DATETIME,
+    follow_up_error TEXT,
+    UNIQUE (global_id, start_at)
+);
+
+CREATE INDEX IF NOT EXISTS idx_meetings_end_at ON meetings(end_at);
+
+-- The emails a meeting's follow-ups were extracted from.
+CREATE TABLE IF NOT EXISTS meeting_emails (
+    meeting_id INTEGER NOT NULL,
+    email_id INTEGER NOT NULL,
+    PRIMARY KEY (meeting_id, email_id),
+    FOREIGN KEY(meeting_id) REFERENCES meetings(id) ON DELETE CASCADE,
+    FOREIGN KEY(email_id) REFERENCES emails(id) ON DELETE CASCADE
+);
+
+-- Decisions and action items from a meeting's follow-up mail.
+CREATE TABLE IF NOT EXISTS meeting_tasks (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    meeting_id INTEGER NOT NULL,
+    kind TEXT NOT NULL, -- decision or action_item
+    text TEXT NOT NULL,
+    owner TEXT,
+    due_by DATETIME,
+    email_id INTEGER,
+    done BOOLEAN NOT NULL DEFAULT 0,
+    created_at DATETIME NOT NULL,
+    FOREIGN KEY(meeting_id) REFERENCES meetings(id) ON DELETE CASCADE,
+    FOREIGN KEY(email_id) REFERENCES emails(id) ON DELETE SET NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_meeting_tasks_meeting ON meeting_tasks(meeting_id);
@@ -0,0 +1,6 @@
+-- Attachment content lives in files named by its hash, under the app data
+-- directory. Attachments the policy leaves out are listed with the reason.
+ALTER TABLE attachments ADD COLUMN skipped_reason TEXT;
+
+CREATE INDEX IF NOT EXISTS idx_attachments_email ON attachments(email_id);
+CREATE INDEX IF NOT EXISTS idx_attachments_hash ON attachments(hash);
@@ -0,0 +1,4 @@
+-- Sender profiles look mail up by sender, whatever its case, and check
+-- for the user's replies in each conversation.
+CREATE INDEX IF NOT EXISTS idx_emails_sender ON emails(LOWER(sender), received_at);
+CREATE INDEX IF NOT EXISTS idx_emails_conversation_folder ON emails(conversation_id, folder, sent_at);
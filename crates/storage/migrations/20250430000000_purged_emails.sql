@@ -0,0 +1,8 @@
+-- Emails the user purged, kept by their Outlook identity so scans don't
+-- bring them back while the message is still in the mailbox.
+CREATE TABLE IF NOT EXISTS purged_emails (
+    store_id TEXT NOT NULL,
+    entry_id TEXT NOT NULL,
+    purged_at DATETIME NOT NULL,
+    PRIMARY KEY (store_id, entry_id)
+);
@@ -0,0 +1,5 @@
+-- Emails the user deleted in Noodle, which leaves Outlook alone. They are
+-- left out of search and the dashboards and purged for good once they have
+-- been in the trash for the retention period.
+ALTER TABLE emails ADD COLUMN deleted_at DATETIME;
+CREATE INDEX IF NOT EXISTS idx_emails_deleted_at ON emails(deleted_at) WHERE deleted_at IS NOT NULL;
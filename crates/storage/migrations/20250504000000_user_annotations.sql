@@ -0,0 +1,19 @@
+-- What the user adds on top of the extracted facts, for one email or one
+-- project (by the name extraction gives it): a note, their own tags, and a
+-- pin or star. Each email and project has at most one.
+CREATE TABLE IF NOT EXISTS user_annotations (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    email_id INTEGER,
+    project TEXT COLLATE NOCASE,
+    note TEXT,
+    tags_json TEXT NOT NULL DEFAULT '[]', -- string[], lowercase
+    pinned BOOLEAN NOT NULL DEFAULT 0,
+    starred BOOLEAN NOT NULL DEFAULT 0,
+    updated_at DATETIME NOT NULL,
+    CHECK ((email_id IS NULL) != (project IS NULL)),
+    FOREIGN KEY(email_id) REFERENCES emails(id) ON DELETE CASCADE
+);
+CREATE UNIQUE INDEX IF NOT EXISTS idx_user_annotations_email
+    ON user_annotations(email_id) WHERE email_id IS NOT NULL;
+CREATE UNIQUE INDEX IF NOT EXISTS idx_user_annotations_project
+    ON user_annotations(project) WHERE project IS NOT NULL;
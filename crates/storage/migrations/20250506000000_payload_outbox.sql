@@ -0,0 +1,45 @@
+-- Emails whose Qdrant payload (project and tags) is behind SQLite. The
+-- triggers queue an email whenever either changes; the payload sync worker
+-- pushes the current values and removes the row.
+CREATE TABLE IF NOT EXISTS payload_outbox (
+    email_id INTEGER PRIMARY KEY,
+    enqueued_at DATETIME NOT NULL,
+    attempts INTEGER NOT NULL DEFAULT 0,
+    last_error TEXT,
+    FOREIGN KEY(email_id) REFERENCES emails(id) ON DELETE CASCADE
+);
+
+CREATE TRIGGER payload_outbox_facts_ai AFTER INSERT ON extracted_email_facts BEGIN
+    INSERT OR REPLACE INTO payload_outbox (email_id, enqueued_at)
+    VALUES (new.email_id, CURRENT_TIMESTAMP);
+END;
+
+CREATE TRIGGER payload_outbox_facts_au AFTER UPDATE OF client_or_project_json ON extracted_email_facts
+WHEN old.client_or_project_json IS NOT new.client_or_project_json BEGIN
+    INSERT OR REPLACE INTO payload_outbox (email_id, enqueued_at)
+    VALUES (new.email_id, CURRENT_TIMESTAMP);
+END;
+
+CREATE TRIGGER payload_outbox_annotations_ai AFTER INSERT ON user_annotations
+WHEN new.email_id IS NOT NULL BEGIN
+    INSERT OR REPLACE INTO payload_outbox (email_id, enqueued_at)
+    VALUES (new.email_id, CURRENT_TIMESTAMP);
+END;
+
+CREATE TRIGGER payload_outbox_annotations_au AFTER UPDATE OF tags_json ON user_annotations
+WHEN new.email_id IS NOT NULL AND old.tags_json IS NOT new.tags_json BEGIN
+    INSERT OR REPLACE INTO payload_outbox (email_id, enqueued_at)
+    VALUES (new.email_id, CURRENT_TIMESTAMP);
+END;
+
+-- Not fired when the email itself is deleted: the cascade has removed it
+-- by then, and its point goes with it.
+CREATE TRIGGER payload_outbox_annotations_ad AFTER DELETE ON user_annotations
+WHEN old.email_id IS NOT NULL AND EXISTS (SELECT 1 FROM emails WHERE id = old.email_id) BEGIN
+    INSERT OR REPLACE INTO payload_outbox (email_id, enqueued_at)
+    VALUES (old.email_id, CURRENT_TIMESTAMP);
+END;
+
+-- Points written before payloads carried a project and tags.
+INSERT INTO payload_outbox (email_id, enqueued_at)
+SELECT id, CURRENT_TIMESTAMP FROM emails;
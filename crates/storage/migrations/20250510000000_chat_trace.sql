@@ -0,0 +1,2 @@
+-- The tools a research answer called, kept for the user to look through.
+ALTER TABLE chat_messages ADD COLUMN trace_json TEXT NOT NULL DEFAULT '[]'; -- ChatToolCall[]
@@ -0,0 +1,2 @@
+-- Citations in an answer that their email doesn't appear to back up.
+ALTER TABLE chat_messages ADD COLUMN unsupported_json TEXT NOT NULL DEFAULT '[]'; -- UnsupportedCitation[]
@@ -0,0 +1,7 @@
+-- Prompt packs: prompts shared as JSON with their variables. Imported
+-- prompts remember their pack so a newer version of it updates them.
+ALTER TABLE prompts ADD COLUMN description TEXT;
+ALTER TABLE prompts ADD COLUMN variables_json TEXT NOT NULL DEFAULT '[]'; -- PromptVariable[]
+ALTER TABLE prompts ADD COLUMN pack TEXT;
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_prompts_pack_name ON prompts(pack, name) WHERE pack IS NOT NULL;
@@ -0,0 +1,13 @@
+-- Extraction A/B runs: two prompt/model variants over the same emails, with
+-- what each extracted and how the two compare.
+CREATE TABLE IF NOT EXISTS extraction_experiments (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    name TEXT NOT NULL,
+    variant_a_json TEXT NOT NULL, -- ExperimentVariant
+    variant_b_json TEXT NOT NULL, -- ExperimentVariant
+    emails_json TEXT NOT NULL DEFAULT '[]', -- ExperimentEmail[]
+    comparison_json TEXT NOT NULL, -- ExperimentComparison
+    created_at DATETIME NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_extraction_experiments_created_at ON extraction_experiments(created_at);
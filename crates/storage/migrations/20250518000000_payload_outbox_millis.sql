@@ -0,0 +1,37 @@
+-- The sync worker only removes an outbox row whose enqueued_at is the one it
+-- read, so a change queued while its push was in flight is pushed again.
+-- Whole seconds can't tell those two apart; milliseconds can.
+DROP TRIGGER payload_outbox_facts_ai;
+DROP TRIGGER payload_outbox_facts_au;
+DROP TRIGGER payload_outbox_annotations_ai;
+DROP TRIGGER payload_outbox_annotations_au;
+DROP TRIGGER payload_outbox_annotations_ad;
+
+CREATE TRIGGER payload_outbox_facts_ai AFTER INSERT ON extracted_email_facts BEGIN
+    INSERT OR REPLACE INTO payload_outbox (email_id, enqueued_at)
+    VALUES (new.email_id, strftime('%Y-%m-%d %H:%M:%f', 'now'));
+END;
+
+CREATE TRIGGER payload_outbox_facts_au AFTER UPDATE OF client_or_project_json ON extracted_email_facts
+WHEN old.client_or_project_json IS NOT new.client_or_project_json BEGIN
+    INSERT OR REPLACE INTO payload_outbox (email_id, enqueued_at)
+    VALUES (new.email_id, strftime('%Y-%m-%d %H:%M:%f', 'now'));
+END;
+
+CREATE TRIGGER payload_outbox_annotations_ai AFTER INSERT ON user_annotations
+WHEN new.email_id IS NOT NULL BEGIN
+    INSERT OR REPLACE INTO payload_outbox (email_id, enqueued_at)
+    VALUES (new.email_id, strftime('%Y-%m-%d %H:%M:%f', 'now'));
+END;
+
+CREATE TRIGGER payload_outbox_annotations_au AFTER UPDATE OF tags_json ON user_annotations
+WHEN new.email_id IS NOT NULL AND old.tags_json IS NOT new.tags_json BEGIN
+    INSERT OR REPLACE INTO payload_outbox (email_id, enqueued_at)
+    VALUES (new.email_id, strftime('%Y-%m-%d %H:%M:%f', 'now'));
+END;
+
+CREATE TRIGGER payload_outbox_annotations_ad AFTER DELETE ON user_annotations
+WHEN old.email_id IS NOT NULL AND EXISTS (SELECT 1 FROM emails WHERE id = old.email_id) BEGIN
+    INSERT OR REPLACE INTO payload_outbox (email_id, enqueued_at)
+    VALUES (old.email_id, strftime('%Y-%m-%d %H:%M:%f', 'now'));
+END;
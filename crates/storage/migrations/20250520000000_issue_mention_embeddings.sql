@@ -0,0 +1,3 @@
+-- Each mention's own embedding, so its cluster's mean can drop it again
+-- when the email is grouped anew. Mentions grouped before this have none.
+ALTER TABLE issue_mentions ADD COLUMN embedding_json TEXT;
@@ -0,0 +1,25 @@
+-- Tickets are keyed by a hash of the item they were filed for rather than
+-- its position, which shifts when the email is extracted again. A row
+-- without a ticket key is a reservation taken while the tracker is called.
+-- Tickets filed before keep `kind:position` as their hash.
+CREATE TABLE issue_tickets_keyed (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    email_id INTEGER NOT NULL,
+    kind TEXT NOT NULL, -- risk|issue|blocker
+    position INTEGER NOT NULL, -- index in the email's risks/issues/blockers when filed
+    item_hash TEXT NOT NULL,
+    tracker TEXT NOT NULL, -- jira|azure_devops
+    ticket_key TEXT, -- NULL while reserved
+    url TEXT,
+    created_at DATETIME NOT NULL,
+    UNIQUE(email_id, item_hash, tracker),
+    FOREIGN KEY(email_id) REFERENCES emails(id) ON DELETE CASCADE
+);
+
+INSERT INTO issue_tickets_keyed
+    (id, email_id, kind, position, item_hash, tracker, ticket_key, url, created_at)
+SELECT id, email_id, kind, position, kind || ':' || position, tracker, ticket_key, url, created_at
+FROM issue_tickets;
+
+DROP TABLE issue_tickets;
+ALTER TABLE issue_tickets_keyed RENAME TO issue_tickets;
@@ -0,0 +1,4 @@
+-- Email points now carry their sender and conversation for thread and sender
+-- searches to filter on; queue every email so existing points get them.
+INSERT OR REPLACE INTO payload_outbox (email_id, enqueued_at)
+SELECT id, strftime('%Y-%m-%d %H:%M:%f', 'now') FROM emails WHERE deleted_at IS NULL;
@@ -1,34 +1,60 @@
+use crate::sqlite::VectorPayload;
 use noodle_core::error::Result;
+use qdrant_client::qdrant::SnapshotDownload;
 use qdrant_client::qdrant::{
-    vectors_config::Config, CreateCollection, DeletePoints, Distance, Filter, PointStruct,
-    ScoredPoint, SearchPoints, UpsertPoints, VectorParams, VectorsConfig,
+    point_id::PointIdOptions, vectors_config::Config, Condition, CreateCollection,
+    CreateSnapshotRequest, DeletePoints, DeleteSnapshotRequest, Distance, Filter,
+    GetCollectionInfoRequest, PointId, PointStruct, ScoredPoint, ScrollPoints, SearchPoints,
+    SetPayloadPoints, UpsertPoints, VectorParams, VectorParamsMap, VectorsConfig,
 };
 use qdrant_client::{Payload, Qdrant};
 use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use tracing::info;
 
 pub const COLLECTION_EMAILS: &str = "emails";
 pub const COLLECTION_ATTACHMENTS: &str = "attachments";
 pub const VECTOR_NAME: &str = "body_embedding";
+/// Email points also carry an embedding of the subject alone, which short
+/// queries such as project codenames match far better than a whole body.
+pub const SUBJECT_VECTOR_NAME: &str = "subject_embedding";
 pub const DEFAULT_DIM: u64 = 1536;
+/// Email payload keys that can change after embedding and that vector
+/// search filters on, kept in step with SQLite by the payload sync.
+pub const PROJECT_KEY: &str = "project";
+pub const TAGS_KEY: &str = "tags";
+pub const SENDER_KEY: &str = "sender";
+pub const CONVERSATION_KEY: &str = "conversation_id";
+/// Point ids fetched per scroll request.
+const SCROLL_PAGE: u32 = 1000;
 
 pub struct QdrantStorage {
     client: Option<Arc<Qdrant>>,
+    /// Snapshot transfers go through the REST API, which gRPC does not cover.
+    rest_url: String,
+    /// Set when startup replaced a single-vector email collection; every
+    /// email then has to be embedded again.
+    layout_upgraded: bool,
 }
 
 impl QdrantStorage {
     pub async fn new(url: &str) -> Result<Self> {
         let client_result = Qdrant::from_url(url).build();
+        let rest_url = rest_url_for(url);
 
         match client_result {
             Ok(client) => {
-                let storage = Self {
+                let mut storage = Self {
                     client: Some(Arc::new(client)),
+                    rest_url,
+                    layout_upgraded: false,
                 };
                 // Try to ensure collections, but don't fail hard if it fails now
-                if let Err(e) = storage.ensure_collections().await {
-                    info!("Qdrant available but failed to ensure collections: {}", e);
+                match storage.ensure_collections().await {
+                    Ok(upgraded) => storage.layout_upgraded = upgraded,
+                    Err(e) => info!("Qdrant available but failed to ensure collections: {}", e),
                 }
                 Ok(storage)
             }
@@ -37,17 +63,82 @@ impl QdrantStorage {
                     "Qdrant connection failed (Vector Search will be disabled): {}",
                     e
                 );
-                Ok(Self { client: None })
+                Ok(Self {
+                    client: None,
+                    rest_url,
+                    layout_upgraded: false,
+                })
             }
         }
     }
 
-    async fn ensure_collections(&self) -> Result<()> {
+    pub fn is_available(&self) -> bool {
+        self.client.is_some()
+    }
+
+    pub fn layout_upgraded(&self) -> bool {
+        self.layout_upgraded
+    }
+
+    /// Returns `true` when the email collection had to be recreated.
+    async fn ensure_collections(&self) -> Result<bool> {
+        let mut upgraded = false;
         if self.client.is_some() {
-            self.ensure_collection(COLLECTION_EMAILS, 1536).await?;
-            self.ensure_collection(COLLECTION_ATTACHMENTS, 1536).await?;
+            upgraded = self.ensure_email_collection(DEFAULT_DIM).await?;
+            self.ensure_collection(COLLECTION_ATTACHMENTS, DEFAULT_DIM)
+                .await?;
         }
-        Ok(())
+        Ok(upgraded)
+    }
+
+    /// Creates the email collection with named subject and body vectors,
+    /// replacing one created with a single unnamed vector.
+    async fn ensure_email_collection(&self, dim: u64) -> Result<bool> {
+        let Some(client) = &self.client else {
+            return Ok(false);
+        };
+        let mut upgraded = false;
+        if client
+            .collection_exists(COLLECTION_EMAILS)
+            .await
+            .unwrap_or(false)
+        {
+            if !matches!(
+                self.vectors_config(COLLECTION_EMAILS).await?,
+                Some(Config::Params(_))
+            ) {
+                return Ok(false);
+            }
+            info!("Replacing single-vector collection: {}", COLLECTION_EMAILS);
+            client
+                .delete_collection(COLLECTION_EMAILS)
+                .await
+                .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+            upgraded = true;
+        }
+
+        info!("Creating collection: {}", COLLECTION_EMAILS);
+        let params = VectorParams {
+            size: dim,
+            distance: Distance::Cosine.into(),
+            ..Default::default()
+        };
+        client
+            .create_collection(CreateCollection {
+                collection_name: COLLECTION_EMAILS.into(),
+                vectors_config: Some(VectorsConfig {
+                    config: Some(Config::ParamsMap(VectorParamsMap {
+                        map: HashMap::from([
+                            (SUBJECT_VECTOR_NAME.to_string(), params),
+                            (VECTOR_NAME.to_string(), params),
+                        ]),
+                    })),
+                }),
+                ..Default::default()
+            })
+            .await
+            .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+        Ok(upgraded)
     }
 
     async fn ensure_collection(&self, name: &str, dim: u64) -> Result<()> {
@@ -77,16 +168,24 @@ impl QdrantStorage {
         &self,
         store_id: &str,
         entry_id: &str,
-        vector: Vec<f32>,
+        subject_vector: Vec<f32>,
+        body_vector: Vec<f32>,
         payload: Payload,
     ) -> Result<()> {
         if let Some(client) = &self.client {
             let stable_id = self.calculate_stable_id(store_id, entry_id);
-            let point = PointStruct::new(stable_id, vector, payload);
+            let vectors = HashMap::from([
+                (SUBJECT_VECTOR_NAME.to_string(), subject_vector),
+                (VECTOR_NAME.to_string(), body_vector),
+            ]);
+            let point = PointStruct::new(stable_id, vectors, payload);
             client
                 .upsert_points(UpsertPoints {
                     collection_name: COLLECTION_EMAILS.into(),
                     points: vec![point],
+                    // Acknowledge only once applied, so a finished process_email
+                    // never leaves a point in Qdrant's queue at shutdown.
+                    wait: Some(true),
                     ..Default::default()
                 })
                 .await
@@ -95,7 +194,8 @@ impl QdrantStorage {
         Ok(())
     }
 
-    fn calculate_stable_id(&self, store_id: &str, entry_id: &str) -> u64 {
+    /// The point id an email's vector is stored under.
+    pub fn calculate_stable_id(&self, store_id: &str, entry_id: &str) -> u64 {
         let mut hasher = Sha256::new();
         hasher.update(store_id);
         hasher.update(entry_id);
@@ -105,8 +205,11 @@ impl QdrantStorage {
         u64::from_le_bytes(bytes)
     }
 
+    /// Nearest emails by one of the named vectors, [`VECTOR_NAME`] or
+    /// [`SUBJECT_VECTOR_NAME`].
     pub async fn search_emails(
         &self,
+        vector_name: &str,
         vector: Vec<f32>,
         filter: Option<Filter>,
         limit: u64,
@@ -115,7 +218,8 @@ impl QdrantStorage {
             let result = client
                 .search_points(SearchPoints {
                     collection_name: COLLECTION_EMAILS.into(),
-                    vector: vector.into(),
+                    vector,
+                    vector_name: Some(vector_name.into()),
                     filter,
                     limit,
                     with_payload: Some(true.into()),
@@ -130,6 +234,94 @@ impl QdrantStorage {
         }
     }
 
+    /// Replaces the vectors of an email's attachment text with `chunks`,
+    /// each an embedding with its payload.
+    pub async fn replace_attachment_vectors(
+        &self,
+        email_id: i64,
+        chunks: Vec<(Vec<f32>, Payload)>,
+    ) -> Result<()> {
+        let Some(client) = &self.client else {
+            return Ok(());
+        };
+        client
+            .delete_points(DeletePoints {
+                collection_name: COLLECTION_ATTACHMENTS.into(),
+                points: Some(Filter::must([Condition::matches("email_id", email_id)]).into()),
+                wait: Some(true),
+                ..Default::default()
+            })
+            .await
+            .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+        if chunks.is_empty() {
+            return Ok(());
+        }
+        let points = chunks
+            .into_iter()
+            .enumerate()
+            .map(|(index, (vector, payload))| {
+                let mut hasher = Sha256::new();
+                hasher.update(format!("{}:{}", email_id, index));
+                let result = hasher.finalize();
+                let mut bytes = [0u8; 8];
+                bytes.copy_from_slice(&result[..8]);
+                PointStruct::new(u64::from_le_bytes(bytes), vector, payload)
+            })
+            .collect();
+        client
+            .upsert_points(UpsertPoints {
+                collection_name: COLLECTION_ATTACHMENTS.into(),
+                points,
+                wait: Some(true),
+                ..Default::default()
+            })
+            .await
+            .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+        Ok(())
+    }
+
+    /// Nearest chunks of attachment text.
+    pub async fn search_attachments(
+        &self,
+        vector: Vec<f32>,
+        limit: u64,
+    ) -> Result<Vec<ScoredPoint>> {
+        let Some(client) = &self.client else {
+            return Ok(vec![]);
+        };
+        let result = client
+            .search_points(SearchPoints {
+                collection_name: COLLECTION_ATTACHMENTS.into(),
+                vector,
+                limit,
+                with_payload: Some(true.into()),
+                ..Default::default()
+            })
+            .await
+            .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+        Ok(result.result)
+    }
+
+    /// Overwrites the filterable fields on an email's point. Does nothing
+    /// when the email has no point yet.
+    pub async fn set_email_payload(&self, fields: &VectorPayload) -> Result<()> {
+        if let Some(client) = &self.client {
+            client
+                .set_payload(SetPayloadPoints {
+                    collection_name: COLLECTION_EMAILS.into(),
+                    wait: Some(true),
+                    payload: email_filter_payload(fields).into(),
+                    points_selector: Some(
+                        Filter::must([Condition::matches("email_id", fields.email_id)]).into(),
+                    ),
+                    ..Default::default()
+                })
+                .await
+                .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+        }
+        Ok(())
+    }
+
     pub async fn delete_points(&self, collection: &str, filter: Filter) -> Result<()> {
         if let Some(client) = &self.client {
             client
@@ -143,4 +335,206 @@ impl QdrantStorage {
         }
         Ok(())
     }
+
+    /// Vector size `collection` was created with; for named vectors, the
+    /// size of the body vector.
+    pub async fn collection_dim(&self, collection: &str) -> Result<Option<u64>> {
+        Ok(match self.vectors_config(collection).await? {
+            Some(Config::Params(params)) => Some(params.size),
+            Some(Config::ParamsMap(named)) => named.map.get(VECTOR_NAME).map(|p| p.size),
+            None => None,
+        })
+    }
+
+    async fn vectors_config(&self, collection: &str) -> Result<Option<Config>> {
+        let Some(client) = &self.client else {
+            return Ok(None);
+        };
+        let info = client
+            .collection_info(GetCollectionInfoRequest {
+                collection_name: collection.into(),
+            })
+            .await
+            .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+        Ok(info
+            .result
+            .and_then(|i| i.config)
+            .and_then(|c| c.params)
+            .and_then(|p| p.vectors_config)
+            .and_then(|v| v.config))
+    }
+
+    /// Ids of every point in `collection`. Noodle only writes numeric ids.
+    pub async fn list_point_ids(&self, collection: &str) -> Result<Vec<u64>> {
+        let Some(client) = &self.client else {
+            return Ok(vec![]);
+        };
+        let mut ids = Vec::new();
+        let mut offset = None;
+        loop {
+            let page = client
+                .scroll(ScrollPoints {
+                    collection_name: collection.into(),
+                    offset,
+                    limit: Some(SCROLL_PAGE),
+                    with_payload: Some(false.into()),
+                    with_vectors: Some(false.into()),
+                    ..Default::default()
+                })
+                .await
+                .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+            ids.extend(page.result.into_iter().filter_map(|p| {
+                match p.id.and_then(|id| id.point_id_options) {
+                    Some(PointIdOptions::Num(id)) => Some(id),
+                    _ => None,
+                }
+            }));
+            match page.next_page_offset {
+                Some(next) => offset = Some(next),
+                None => break,
+            }
+        }
+        Ok(ids)
+    }
+
+    pub async fn delete_point_ids(&self, collection: &str, ids: &[u64]) -> Result<()> {
+        if let Some(client) = &self.client {
+            if ids.is_empty() {
+                return Ok(());
+            }
+            client
+                .delete_points(DeletePoints {
+                    collection_name: collection.into(),
+                    points: Some(
+                        ids.iter()
+                            .map(|&id| PointId::from(id))
+                            .collect::<Vec<_>>()
+                            .into(),
+                    ),
+                    wait: Some(true),
+                    ..Default::default()
+                })
+                .await
+                .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+        }
+        Ok(())
+    }
+
+    /// Snapshots `collection` and downloads the snapshot into `dir`, so it
+    /// outlives the Qdrant container. The server-side copy is deleted.
+    pub async fn create_snapshot(&self, collection: &str, dir: &Path) -> Result<PathBuf> {
+        let Some(client) = &self.client else {
+            return Err(unavailable());
+        };
+        let created = client
+            .create_snapshot(CreateSnapshotRequest {
+                collection_name: collection.into(),
+            })
+            .await
+            .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+        let name = created
+            .snapshot_description
+            .map(|d| d.name)
+            .ok_or_else(|| {
+                noodle_core::error::NoodleError::Storage("Qdrant returned no snapshot".into())
+            })?;
+
+        let path = dir.join(&name);
+        let downloaded = client
+            .download_snapshot(SnapshotDownload {
+                out_path: path.clone(),
+                collection_name: collection.into(),
+                snapshot_name: Some(name.clone()),
+                rest_api_uri: Some(self.rest_url.clone()),
+            })
+            .await
+            .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()));
+        if let Err(e) = client
+            .delete_snapshot(DeleteSnapshotRequest {
+                collection_name: collection.into(),
+                snapshot_name: name,
+            })
+            .await
+        {
+            info!("Failed to delete server-side snapshot: {}", e);
+        }
+        downloaded?;
+        Ok(path)
+    }
+
+    /// Replaces `collection` with the snapshot at `path` by uploading it.
+    /// The file is streamed, as snapshots can be larger than memory allows.
+    pub async fn restore_snapshot(&self, collection: &str, path: &Path) -> Result<()> {
+        if self.client.is_none() {
+            return Err(unavailable());
+        }
+        let file_name = path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or("snapshot");
+        let file = tokio::fs::File::open(path)
+            .await
+            .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+        let length = file
+            .metadata()
+            .await
+            .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?
+            .len();
+        let part = reqwest::multipart::Part::stream_with_length(file, length)
+            .file_name(file_name.to_string())
+            .mime_str("application/octet-stream")
+            .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+        let form = reqwest::multipart::Form::new().part("snapshot", part);
+
+        let url = format!(
+            "{}/collections/{}/snapshots/upload?priority=snapshot&wait=true",
+            self.rest_url, collection
+        );
+        let response = reqwest::Client::new()
+            .post(url)
+            .multipart(form)
+            .send()
+            .await
+            .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+        if !response.status().is_success() {
+            let status = response.status();
+            let text = response.text().await.unwrap_or_default();
+            return Err(noodle_core::error::NoodleError::Storage(format!(
+                "Snapshot restore failed ({}): {}",
+                status, text
+            )));
+        }
+        Ok(())
+    }
+}
+
+fn unavailable() -> noodle_core::error::NoodleError {
+    noodle_core::error::NoodleError::Storage("Qdrant is not available".into())
+}
+
+/// The filterable part of an email point's payload. Projects and senders
+/// are stored lowercased, as SQL matches them ignoring case.
+pub fn email_filter_payload(fields: &VectorPayload) -> Payload {
+    let mut map = serde_json::Map::new();
+    map.insert(SENDER_KEY.into(), fields.sender.to_lowercase().into());
+    map.insert(
+        CONVERSATION_KEY.into(),
+        fields.conversation_id.clone().into(),
+    );
+    map.insert(
+        PROJECT_KEY.into(),
+        fields.project.as_deref().map(str::to_lowercase).into(),
+    );
+    map.insert(TAGS_KEY.into(), fields.tags.clone().into());
+    Payload::from(map)
+}
+
+/// The REST endpoint next to a gRPC one: Qdrant serves REST on 6333 and gRPC
+/// on 6334 by default.
+fn rest_url_for(grpc_url: &str) -> String {
+    let url = grpc_url.trim_end_matches('/');
+    match url.strip_suffix(":6334") {
+        Some(host) => format!("{}:6333", host),
+        None => url.to_string(),
+    }
 }
@@ -1,11 +1,76 @@
 use chrono::{DateTime, Utc};
+use noodle_core::config::{Config, SettingsProfile};
 use noodle_core::error::Result;
+use noodle_core::locale::{UserLocale, LOCALE_CONFIG_KEY};
+use noodle_core::time::{UserTimezone, TIMEZONE_CONFIG_KEY};
+use noodle_core::types::{
+    Alert, AlertKind, Annotation, AnnotationInput, Attachment, Blocker, CategoryAction,
+    CategoryAuditEntry, CategoryMatch, CategoryRule, ChatMessage, ChatSession, ChatToolCall,
+    CustomPrompt, DateRange, EmailChange, ExperimentComparison, ExperimentEmail, ExperimentVariant,
+    ExtractionExperiment, Graph, GraphFilter, GraphLink, GraphNode, InferredRelation, Issue,
+    IssueCluster, IssueKind, IssueMention, IssueStatus, IssueTicket, MaintenanceReport, Meeting,
+    MeetingFollowUp, MeetingTask, NewsletterRoundup, NewsletterSender, PackPrompt,
+    ProjectIssueMetrics, ProjectSettings, PromptKind, RelationKind, RelationStatus, RepairReport,
+    Risk, ScanCheckpoint, SchemaInfo, SchemaMigration, SearchFilter, SearchHistoryEntry,
+    SearchSuggestion, TicketTracker, TopicSource, TopicSummary, TriageState, UnsupportedCitation,
+    VectorRetry, VipSuggestion,
+};
 use serde_json;
-use sqlx::{sqlite::SqlitePoolOptions, Row, SqlitePool};
-use std::path::Path;
-use tracing::info;
+use similar::{ChangeTag, TextDiff};
+use sqlx::{
+    migrate::Migrator,
+    sqlite::{SqlitePoolOptions, SqliteRow},
+    QueryBuilder, Row, Sqlite, SqlitePool,
+};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::LazyLock;
+use tracing::{info, warn};
 
-#[derive(sqlx::FromRow)]
+pub mod pool;
+mod secrets;
+
+use pool::PoolSettings;
+
+/// The migrations shipped with this build. Ones the database recorded that
+/// the build doesn't know, left by a newer version, don't stop it starting.
+static MIGRATOR: LazyLock<Migrator> = LazyLock::new(|| {
+    let mut migrator = sqlx::migrate!("./migrations");
+    migrator.set_ignore_missing(true);
+    migrator
+});
+
+const MAINTENANCE_LAST_RUN_KEY: &str = "maintenance_last_run";
+const MAINTENANCE_LAST_VACUUM_KEY: &str = "maintenance_last_vacuum";
+/// Days a deleted email stays in the trash before it is purged.
+const TRASH_RETENTION_DAYS: i64 = 30;
+const MAX_ANNOTATION_NOTE_CHARS: usize = 10_000;
+const MAX_ANNOTATION_TAGS: usize = 20;
+const MAX_CHAT_TITLE_CHARS: usize = 120;
+
+/// `priority` ranks mail for attention: urgency counts 0 to 2, needing a
+/// response 1, and a VIP sender adds 2, so a VIP's medium-urgency question
+/// comes before anyone else's urgent one.
+const EMAIL_WITH_FACTS_COLUMNS: &str = r#"
+    e.id, e.subject, e.sender, e.received_at, e.body_text, e.conversation_id,
+    f.primary_type, f.intent, f.urgency, f.sentiment, f.client_or_project_json,
+    f.needs_response, f.waiting_on, f.due_by, f.due_by_raw, f.risks_json, f.issues_json,
+    f.blockers_json, f.summary, f.stale, f.review_reason, e.newsletter, e.deleted_at,
+    (SELECT COUNT(*) FROM email_changes c WHERE c.email_id = e.id) AS change_count,
+    (SELECT r.responded_at FROM email_responses r WHERE r.email_id = e.id) AS responded_at,
+    (SELECT r.latency_secs FROM email_responses r WHERE r.email_id = e.id) AS response_latency_secs,
+    (SELECT group_concat(c.category, ', ') FROM email_categories c WHERE c.email_id = e.id) AS labels,
+    (SELECT json_object('id', a.id, 'note', a.note, 'tags', json(a.tags_json),
+                        'pinned', json(CASE WHEN a.pinned THEN 'true' ELSE 'false' END),
+                        'starred', json(CASE WHEN a.starred THEN 'true' ELSE 'false' END))
+     FROM user_annotations a WHERE a.email_id = e.id) AS annotation_json,
+    EXISTS(SELECT 1 FROM vip_senders v WHERE v.sender = LOWER(TRIM(e.sender))) AS vip,
+    (CASE f.urgency WHEN 'high' THEN 2 WHEN 'medium' THEN 1 ELSE 0 END
+     + COALESCE(f.needs_response, 0)
+     + 2 * EXISTS(SELECT 1 FROM vip_senders v WHERE v.sender = LOWER(TRIM(e.sender)))) AS priority
+"#;
+
+#[derive(sqlx::FromRow, serde::Serialize)]
 pub struct EmailRow {
     pub id: i64,
     pub subject: String,
@@ -14,8 +79,108 @@ pub struct EmailRow {
     pub body_text: String,
 }
 
+/// An email looked at when finding the mail around a meeting.
+#[derive(sqlx::FromRow)]
+pub struct MeetingCandidate {
+    pub id: i64,
+    pub subject: String,
+    pub sender: String,
+    /// Display names separated by semicolons.
+    pub to: String,
+    pub cc: Option<String>,
+    pub received_at: DateTime<Utc>,
+    pub body_text: String,
+}
+
+/// What identifies an email's vector in Qdrant.
+pub struct EmailKey {
+    pub id: i64,
+    pub store_id: String,
+    pub entry_id: String,
+}
+
+/// The fields of an email's Qdrant payload vector search filters on like
+/// SQL does. The project and tags can change after it is embedded.
+pub struct VectorPayload {
+    pub email_id: i64,
+    pub sender: String,
+    pub conversation_id: Option<String>,
+    pub project: Option<String>,
+    pub tags: Vec<String>,
+}
+
+/// Who an email was from and to, as stored from Outlook.
+pub struct EmailParticipants {
+    pub sender: String,
+    pub to: String,
+    pub cc: Option<String>,
+}
+
+/// Columns read into a [`CustomPrompt`] by `prompt_from_row`.
+const PROMPT_COLUMNS: &str = "id, name, kind, description, prompt_template, variables_json,
+    json_schema, schedule_cron, pack";
+
+const PROJECT_EMAIL_FACTS_COLUMNS: &str = r#"
+    e.id, e.conversation_id, e.subject, e.sender, e.received_at,
+    json_extract(f.client_or_project_json, '$.name') AS project,
+    f.primary_type, f.intent, f.summary, f.key_points_json, f.due_by, f.risks_json,
+    f.blockers_json
+"#;
+
+/// An extracted email with the facts timelines, reports and shared
+/// summaries are built from.
+pub struct ProjectEmailFacts {
+    pub id: i64,
+    pub conversation_id: Option<String>,
+    pub subject: String,
+    pub sender: String,
+    pub received_at: DateTime<Utc>,
+    pub project: Option<String>,
+    pub primary_type: String,
+    pub intent: String,
+    pub summary: String,
+    pub key_points: Vec<String>,
+    pub due_by: Option<DateTime<Utc>>,
+    pub risks: Vec<Risk>,
+    pub blockers: Vec<Blocker>,
+}
+
+/// The risks, issues and blockers of an extracted email.
+pub struct IssueFacts {
+    pub email_id: i64,
+    pub received_at: DateTime<Utc>,
+    pub project: Option<String>,
+    pub risks: Vec<Risk>,
+    pub issues: Vec<Issue>,
+    pub blockers: Vec<Blocker>,
+}
+
+/// The mean embedding of an issue cluster's mentions.
+#[derive(Clone)]
+pub struct IssueCentroid {
+    pub id: i64,
+    pub centroid: Vec<f32>,
+    pub mentions: i64,
+}
+
+/// An extracted email that may report earlier issues as handled: one that
+/// commits to something or resolves something.
+pub struct IssueUpdateFacts {
+    pub email_id: i64,
+    pub received_at: DateTime<Utc>,
+    pub project: Option<String>,
+    /// The email's extracted intent, "commit" or "resolve".
+    pub intent: String,
+    pub summary: String,
+    pub key_points: Vec<String>,
+}
+
 pub struct SqliteStorage {
     pool: SqlitePool,
+    /// Read-only connections for queries, so UI reads never queue behind
+    /// sync's write transactions for a pooled connection.
+    read_pool: SqlitePool,
+    path: PathBuf,
 }
 
 impl SqliteStorage {
@@ -28,55 +193,356 @@ impl SqliteStorage {
             })?
             .to_string();
 
+        let path = path.as_ref().to_path_buf();
+        let settings = PoolSettings::load(&path).await;
         let options = sqlx::sqlite::SqliteConnectOptions::new()
-            .filename(path)
+            .filename(&path)
             .create_if_missing(true)
-            .journal_mode(sqlx::sqlite::SqliteJournalMode::Wal);
+            .journal_mode(sqlx::sqlite::SqliteJournalMode::Wal)
+            .busy_timeout(settings.busy_timeout)
+            .synchronous(settings.synchronous);
 
         let pool = SqlitePoolOptions::new()
-            .max_connections(5)
-            .connect_with(options)
+            .max_connections(settings.max_connections)
+            .connect_with(options.clone())
             .await
             .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+        // Connections are opened lazily, so the file and its schema exist by the
+        // time the first read runs.
+        let read_pool = SqlitePoolOptions::new()
+            .max_connections(settings.read_connections)
+            .connect_lazy_with(options.read_only(true));
 
-        info!("Connected to SQLite at {}", path_str);
+        info!(
+            "Connected to SQLite at {} ({} write / {} read connections, busy timeout {:?}, synchronous {:?})",
+            path_str,
+            settings.max_connections,
+            settings.read_connections,
+            settings.busy_timeout,
+            settings.synchronous
+        );
 
-        let storage = Self { pool };
+        let storage = Self {
+            pool,
+            read_pool,
+            path,
+        };
         storage.migrate().await?;
+        // A failure leaves the plaintext readable; the next start tries again.
+        if let Err(e) = storage.seal_plaintext_secrets().await {
+            warn!("Failed to encrypt stored secrets: {}", e);
+        }
 
         Ok(storage)
     }
 
+    /// Applies pending migrations. An existing database is first copied to
+    /// `noodle.db.pre-migration.bak` so a failed upgrade can be rolled back by
+    /// hand. Migrations recorded by a newer build are tolerated, so an older
+    /// build can still open the database after a downgrade.
     pub async fn migrate(&self) -> Result<()> {
-        sqlx::migrate!("./migrations")
-            .run(&self.pool)
+        let applied = self.applied_migrations().await?;
+        let pending: Vec<i64> = MIGRATOR
+            .iter()
+            .map(|m| m.version)
+            .filter(|v| !applied.iter().any(|m| m.version == *v))
+            .collect();
+        let known: HashSet<i64> = MIGRATOR.iter().map(|m| m.version).collect();
+        for unknown in applied.iter().filter(|m| !known.contains(&m.version)) {
+            warn!(
+                "Database has migration {} ({}) from a newer version of Noodle",
+                unknown.version, unknown.description
+            );
+        }
+
+        if !pending.is_empty() && !applied.is_empty() {
+            let backup = self.backup_path();
+            self.backup_to(&backup).await?;
+            info!(
+                "Backed up database to {} before applying {} migration(s)",
+                backup.display(),
+                pending.len()
+            );
+        }
+
+        MIGRATOR.run(&self.pool).await.map_err(|e| {
+            noodle_core::error::NoodleError::Storage(format!(
+                "Migration failed: {}. A copy of the previous database is at {}",
+                e,
+                self.backup_path().display()
+            ))
+        })?;
+
+        info!("SQLite migrations completed");
+        Ok(())
+    }
+
+    /// Migrations shipped with this build merged with those recorded in the
+    /// database, plus the pre-migration backup if one exists.
+    pub async fn get_schema_info(&self) -> Result<SchemaInfo> {
+        let mut applied = self.applied_migrations().await?;
+        let mut migrations: Vec<SchemaMigration> = MIGRATOR
+            .iter()
+            .map(
+                |m| match applied.iter().position(|a| a.version == m.version) {
+                    Some(i) => applied.remove(i),
+                    None => SchemaMigration {
+                        version: m.version,
+                        description: m.description.to_string(),
+                        applied_at: None,
+                        success: false,
+                        known: true,
+                    },
+                },
+            )
+            .collect();
+        migrations.extend(applied);
+        migrations.sort_by_key(|m| m.version);
+
+        let current_version = migrations
+            .iter()
+            .filter(|m| m.applied_at.is_some() && m.success)
+            .map(|m| m.version)
+            .max();
+        let backup = self.backup_path();
+        let backup_taken_at = std::fs::metadata(&backup)
+            .and_then(|meta| meta.modified())
+            .ok()
+            .map(DateTime::<Utc>::from);
+
+        Ok(SchemaInfo {
+            current_version,
+            migrations,
+            backup_path: backup_taken_at.map(|_| backup.display().to_string()),
+            backup_taken_at,
+        })
+    }
+
+    /// Runs `integrity_check`, rebuilds every index and the full-text index,
+    /// then checks again so the caller can tell whether the repair helped.
+    pub async fn repair_database(&self) -> Result<RepairReport> {
+        let started = Utc::now();
+        let problems_before = self.integrity_problems().await?;
+
+        sqlx::query("REINDEX")
+            .execute(&self.pool)
+            .await
+            .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+        sqlx::query("INSERT INTO emails_fts(emails_fts) VALUES('rebuild')")
+            .execute(&self.pool)
             .await
             .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
 
-        info!("SQLite migrations completed");
+        let problems_after = self.integrity_problems().await?;
+        if problems_after.is_empty() {
+            info!("Database repair finished; integrity check passed");
+        } else {
+            warn!(
+                "Database repair finished with {} remaining problem(s)",
+                problems_after.len()
+            );
+        }
+
+        Ok(RepairReport {
+            problems_before,
+            problems_after,
+            duration_ms: (Utc::now() - started).num_milliseconds(),
+        })
+    }
+
+    async fn integrity_problems(&self) -> Result<Vec<String>> {
+        let rows = sqlx::query("PRAGMA integrity_check")
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+        Ok(rows
+            .iter()
+            .map(|row| row.get::<String, _>(0))
+            .filter(|msg| msg != "ok")
+            .collect())
+    }
+
+    async fn applied_migrations(&self) -> Result<Vec<SchemaMigration>> {
+        let exists: bool = sqlx::query_scalar(
+            "SELECT EXISTS(SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = '_sqlx_migrations')",
+        )
+        .fetch_one(&self.pool)
+        .await
+        .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+        if !exists {
+            return Ok(Vec::new());
+        }
+
+        let known: HashSet<i64> = MIGRATOR.iter().map(|m| m.version).collect();
+        let rows = sqlx::query(
+            "SELECT version, description, installed_on, success FROM _sqlx_migrations ORDER BY version",
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+        Ok(rows
+            .iter()
+            .map(|row| {
+                let version: i64 = row.get("version");
+                SchemaMigration {
+                    version,
+                    description: row.get("description"),
+                    applied_at: Some(row.get("installed_on")),
+                    success: row.get("success"),
+                    known: known.contains(&version),
+                }
+            })
+            .collect())
+    }
+
+    fn backup_path(&self) -> PathBuf {
+        let mut name = self.path.file_name().unwrap_or_default().to_os_string();
+        name.push(".pre-migration.bak");
+        self.path.with_file_name(name)
+    }
+
+    /// Writes a consistent copy of the database to `dest`, replacing any
+    /// previous copy.
+    async fn backup_to(&self, dest: &Path) -> Result<()> {
+        if dest.exists() {
+            std::fs::remove_file(dest)
+                .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+        }
+        let dest = dest.to_str().ok_or_else(|| {
+            noodle_core::error::NoodleError::Storage("Invalid backup path".to_string())
+        })?;
+        sqlx::query("VACUUM INTO ?")
+            .bind(dest)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
         Ok(())
     }
 
+    /// Checkpoints and truncates the WAL, refreshes query planner statistics and,
+    /// when `vacuum` is set, rebuilds the file to reclaim free pages. The report
+    /// is kept in `app_config` for `get_last_maintenance`.
+    pub async fn run_maintenance(&self, vacuum: bool) -> Result<MaintenanceReport> {
+        let started_at = Utc::now();
+        let size_before_bytes = self.database_size().await?;
+
+        let checkpoint = sqlx::query("PRAGMA wal_checkpoint(TRUNCATE)")
+            .fetch_one(&self.pool)
+            .await
+            .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+        let wal_pages_checkpointed: i64 = checkpoint.get(2);
+
+        sqlx::query("ANALYZE")
+            .execute(&self.pool)
+            .await
+            .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+
+        if vacuum {
+            sqlx::query("VACUUM")
+                .execute(&self.pool)
+                .await
+                .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+        }
+
+        let report = MaintenanceReport {
+            started_at,
+            duration_ms: (Utc::now() - started_at).num_milliseconds(),
+            wal_pages_checkpointed,
+            vacuumed: vacuum,
+            size_before_bytes,
+            size_after_bytes: self.database_size().await?,
+        };
+        let json = serde_json::to_string(&report)
+            .map_err(|e| noodle_core::error::NoodleError::Internal(e.to_string()))?;
+        self.set_config(MAINTENANCE_LAST_RUN_KEY, &json).await?;
+        if vacuum {
+            self.set_config(MAINTENANCE_LAST_VACUUM_KEY, &started_at.to_rfc3339())
+                .await?;
+        }
+
+        info!(
+            "SQLite maintenance finished in {}ms (vacuum: {})",
+            report.duration_ms, vacuum
+        );
+        Ok(report)
+    }
+
+    pub async fn get_last_maintenance(&self) -> Result<Option<MaintenanceReport>> {
+        Ok(self
+            .get_config(MAINTENANCE_LAST_RUN_KEY)
+            .await?
+            .and_then(|s| serde_json::from_str(&s).ok()))
+    }
+
+    pub async fn get_last_vacuum(&self) -> Result<Option<DateTime<Utc>>> {
+        Ok(self
+            .get_config(MAINTENANCE_LAST_VACUUM_KEY)
+            .await?
+            .and_then(|s| DateTime::parse_from_rfc3339(&s).ok())
+            .map(|dt| dt.with_timezone(&Utc)))
+    }
+
+    async fn database_size(&self) -> Result<i64> {
+        let row = sqlx::query(
+            "SELECT page_count * page_size AS size FROM pragma_page_count(), pragma_page_size()",
+        )
+        .fetch_one(&self.pool)
+        .await
+        .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+        Ok(row.get("size"))
+    }
+
+    /// Closes both pools, waiting for open connections and checkpointing the WAL.
+    pub async fn close(&self) {
+        self.read_pool.close().await;
+        self.pool.close().await;
+    }
+
     pub fn pool(&self) -> &SqlitePool {
         &self.pool
     }
 
+    /// Read-only pool for queries that don't write.
+    pub fn read_pool(&self) -> &SqlitePool {
+        &self.read_pool
+    }
+
     pub async fn save_email(&self, email: &noodle_core::types::Email) -> Result<i64> {
         let importance = email.importance as i64;
         let flags = email.flags.map(|f| f as i64);
 
+        let mut tx = self
+            .pool
+            .begin()
+            .await
+            .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+
+        let previous = sqlx::query(
+            "SELECT hash, subject, body_text FROM emails WHERE store_id = ? AND entry_id = ?",
+        )
+        .bind(&email.store_id)
+        .bind(&email.entry_id)
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+
         let row = sqlx::query(
             r#"
             INSERT INTO emails (
                 store_id, entry_id, conversation_id, folder, subject, sender, "to", cc, bcc, 
                 sent_at, received_at, body_text, body_html, importance, categories, flags, 
-                internet_message_id, last_indexed_at, hash
-            ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+                internet_message_id, list_unsubscribe, last_indexed_at, hash
+            ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
             ON CONFLICT(store_id, entry_id) DO UPDATE SET
                 folder = excluded.folder,
                 subject = excluded.subject,
+                conversation_id = COALESCE(excluded.conversation_id, conversation_id),
+                "to" = excluded."to",
+                cc = excluded.cc,
                 received_at = excluded.received_at,
                 body_text = excluded.body_text,
+                internet_message_id = COALESCE(excluded.internet_message_id, internet_message_id),
+                list_unsubscribe = COALESCE(excluded.list_unsubscribe, list_unsubscribe),
                 last_indexed_at = excluded.last_indexed_at,
                 hash = excluded.hash
             RETURNING id
@@ -99,289 +565,4923 @@ impl SqliteStorage {
         .bind(email.categories.as_ref())
         .bind(flags)
         .bind(email.internet_message_id.as_ref())
+        .bind(email.list_unsubscribe.as_ref())
         .bind(email.last_indexed_at)
         .bind(&email.hash)
-        .fetch_one(&self.pool)
+        .fetch_one(&mut *tx)
         .await
         .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+        let id: i64 = row.get("id");
 
-        Ok(row.get("id"))
-    }
-
-    pub async fn save_facts(&self, facts: &noodle_core::types::EmailFact) -> Result<()> {
-        let primary_type = facts.primary_type.to_string();
-        let intent = facts.intent.to_string();
-        let sentiment = facts.sentiment.to_string();
-        let urgency = facts.urgency.to_string();
-        let waiting_on = facts.waiting_on.to_string();
-
-        let client_project = serde_json::to_string(&facts.client_or_project).unwrap();
-        let key_points = serde_json::to_string(&facts.key_points).unwrap();
-        let risks = serde_json::to_string(&facts.risks).unwrap();
-        let issues = serde_json::to_string(&facts.issues).unwrap();
-        let blockers = serde_json::to_string(&facts.blockers).unwrap();
-        let open_questions = serde_json::to_string(&facts.open_questions).unwrap();
-        let answered_questions = serde_json::to_string(&facts.answered_questions).unwrap();
-
-        let provenance = serde_json::to_string(&facts.provenance).unwrap();
-
-        // Previous schema had 'deadlines_json', 'action_items_json', 'decisions_json', 'suggested_labels_json'.
-        // These are removed or re-mapped. We do NOT insert them.
+        // Re-sent or edited in place: log what changed and mark the facts as
+        // extracted from the old content until they are extracted again.
+        if let Some(previous) = previous.filter(|p| p.get::<String, _>("hash") != email.hash) {
+            let change = email_change(
+                id,
+                &previous.get::<String, _>("subject"),
+                &previous.get::<String, _>("body_text"),
+                email,
+            );
+            sqlx::query(
+                "INSERT INTO email_changes
+                    (email_id, detected_at, previous_subject, subject, body_diff, lines_added, lines_removed)
+                 VALUES (?, ?, ?, ?, ?, ?, ?)",
+            )
+            .bind(id)
+            .bind(Utc::now())
+            .bind(&change.previous_subject)
+            .bind(&change.subject)
+            .bind(&change.body_diff)
+            .bind(change.lines_added)
+            .bind(change.lines_removed)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+            sqlx::query("UPDATE extracted_email_facts SET stale = 1 WHERE email_id = ?")
+                .bind(id)
+                .execute(&mut *tx)
+                .await
+                .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+            info!(
+                "Email {} changed since it was indexed (+{} -{} lines)",
+                id, change.lines_added, change.lines_removed
+            );
+        }
 
+        // The vector upsert happens later and can fail; recording it in the same
+        // transaction guarantees every saved email eventually reaches Qdrant.
         sqlx::query(
-            r#"
-            INSERT INTO extracted_email_facts (
-                email_id, primary_type, intent, urgency, sentiment, client_or_project_json,
-                due_by, needs_response, waiting_on, summary, key_points_json,
-                risks_json, issues_json, blockers_json, open_questions_json, answered_questions_json,
-                confidence, provenance_json, created_at
-            ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
-            ON CONFLICT(email_id) DO UPDATE SET
-                primary_type = excluded.primary_type,
-                intent = excluded.intent,
-                urgency = excluded.urgency,
-                sentiment = excluded.sentiment,
-                client_or_project_json = excluded.client_or_project_json,
-                due_by = excluded.due_by,
-                needs_response = excluded.needs_response,
-                waiting_on = excluded.waiting_on,
-                summary = excluded.summary,
-                key_points_json = excluded.key_points_json,
-                risks_json = excluded.risks_json,
-                issues_json = excluded.issues_json,
-                blockers_json = excluded.blockers_json,
-                open_questions_json = excluded.open_questions_json,
-                answered_questions_json = excluded.answered_questions_json,
-                confidence = excluded.confidence,
-                provenance_json = excluded.provenance_json
-            "#,
+            "INSERT INTO vector_outbox (email_id, enqueued_at) VALUES (?, ?)
+             ON CONFLICT(email_id) DO NOTHING",
         )
-        .bind(facts.email_id)
-        .bind(primary_type)
-        .bind(intent)
-        .bind(urgency)
-        .bind(sentiment)
-        .bind(client_project)
-        .bind(facts.due_by)
-        .bind(facts.needs_response)
-        .bind(waiting_on)
-        .bind(&facts.summary)
-        .bind(key_points)
-        .bind(risks)
-        .bind(issues)
-        .bind(blockers)
-        .bind(open_questions)
-        .bind(answered_questions)
-        .bind(facts.confidence)
-        .bind(provenance)
-        .bind(facts.created_at)
-        .execute(&self.pool)
+        .bind(id)
+        .bind(Utc::now())
+        .execute(&mut *tx)
         .await
         .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
 
-        Ok(())
+        tx.commit()
+            .await
+            .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+        Ok(id)
     }
 
-    pub async fn get_dashboard_stats(&self) -> Result<serde_json::Value> {
-        let total_emails = sqlx::query("SELECT COUNT(*) as count FROM emails")
-            .fetch_one(&self.pool)
+    pub async fn get_email(&self, id: i64) -> Result<Option<noodle_core::types::Email>> {
+        let row = sqlx::query("SELECT * FROM emails WHERE id = ?")
+            .bind(id)
+            .fetch_optional(&self.read_pool)
             .await
-            .map(|r| r.get::<i64, _>("count"))
-            .unwrap_or(0);
-
-        let sentiment_data = sqlx::query(
-            "SELECT sentiment, COUNT(*) as count FROM extracted_email_facts GROUP BY sentiment",
-        )
-        .fetch_all(&self.pool)
-        .await
-        .unwrap_or_else(|_| vec![]);
-
-        let sentiments = sentiment_data
-            .into_iter()
-            .map(|r| serde_json::json!({ "sentiment": r.get::<String, _>("sentiment"), "count": r.get::<i64, _>("count") }))
-            .collect::<Vec<_>>();
+            .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
 
-        Ok(serde_json::json!({
-            "total_emails": total_emails,
-            "sentiments": sentiments
+        Ok(row.map(|r| noodle_core::types::Email {
+            id: r.get("id"),
+            store_id: r.get("store_id"),
+            entry_id: r.get("entry_id"),
+            conversation_id: r.get("conversation_id"),
+            folder: r.get("folder"),
+            subject: r.get("subject"),
+            sender: r.get("sender"),
+            to: r.get("to"),
+            cc: r.get("cc"),
+            bcc: r.get("bcc"),
+            sent_at: r.get("sent_at"),
+            received_at: r.get("received_at"),
+            body_text: r.get("body_text"),
+            body_html: r.get("body_html"),
+            importance: r.get::<i64, _>("importance") as i32,
+            categories: r.get("categories"),
+            flags: r.get::<Option<i64>, _>("flags").map(|f| f as i32),
+            internet_message_id: r.get("internet_message_id"),
+            list_unsubscribe: r.get("list_unsubscribe"),
+            last_indexed_at: r.get("last_indexed_at"),
+            hash: r.get("hash"),
+            excluded_reason: r.get("excluded_reason"),
         }))
     }
-    pub async fn get_emails_by_ids(&self, ids: Vec<i64>) -> Result<Vec<serde_json::Value>> {
-        let mut results = Vec::new();
-        for id in ids {
-            let email = sqlx::query(
-                r#"
-                SELECT 
-                    e.id, e.subject, e.sender, e.received_at, e.body_text,
-                    f.primary_type, f.intent, f.urgency, f.sentiment, f.client_or_project_json,
-                    f.needs_response, f.waiting_on, f.due_by, f.risks_json, f.issues_json, f.blockers_json,
-                    f.summary
-                FROM emails e
-                LEFT JOIN extracted_email_facts f ON e.id = f.email_id
-                WHERE e.id = ?
-                "#,
-            )
-            .bind(id)
-            .fetch_optional(&self.pool)
-            .await
-            .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
-
-            if let Some(row) = email {
-                let client_project: Option<serde_json::Value> = row
-                    .get::<Option<String>, _>("client_or_project_json")
-                    .and_then(|s| serde_json::from_str(&s).ok());
-
-                let risks: Option<serde_json::Value> = row
-                    .get::<Option<String>, _>("risks_json")
-                    .and_then(|s| serde_json::from_str(&s).ok());
-
-                results.push(serde_json::json!({
-                    "id": row.get::<i64, _>("id"),
-                    "subject": row.get::<String, _>("subject"),
-                    "sender": row.get::<String, _>("sender"),
-                    "received_at": row.get::<chrono::DateTime<chrono::Utc>, _>("received_at"),
-                    "body_text": row.get::<String, _>("body_text"),
-                    "primary_type": row.get::<Option<String>, _>("primary_type"),
-                    "intent": row.get::<Option<String>, _>("intent"),
-                    "urgency": row.get::<Option<String>, _>("urgency"),
-                    "sentiment": row.get::<Option<String>, _>("sentiment"),
-                    "needs_response": row.get::<Option<bool>, _>("needs_response"),
-                    "waiting_on": row.get::<Option<String>, _>("waiting_on"),
-                    "due_by": row.get::<Option<chrono::DateTime<chrono::Utc>>, _>("due_by"),
-                    "summary": row.get::<Option<String>, _>("summary"),
-                    "client_or_project": client_project,
-                    "risks": risks
-                }));
-            }
-        }
-        Ok(results)
-    }
 
-    pub async fn get_recent_emails(&self, limit: i64) -> Result<Vec<serde_json::Value>> {
-        let rows = sqlx::query(
-            r#"
-            SELECT 
-                e.id, e.subject, e.sender, e.received_at, e.body_text,
-                f.primary_type, f.intent, f.urgency, f.sentiment, f.client_or_project_json,
-                f.needs_response, f.waiting_on, f.due_by, f.risks_json, f.issues_json, f.blockers_json,
-                f.summary
-            FROM emails e
-            LEFT JOIN extracted_email_facts f ON e.id = f.email_id
-            ORDER BY e.received_at DESC 
-            LIMIT ?
-            "#,
+    /// The email with this `Message-ID` header, for clients that only see
+    /// the message in another mail client.
+    pub async fn find_email_by_internet_message_id(
+        &self,
+        internet_message_id: &str,
+    ) -> Result<Option<i64>> {
+        sqlx::query_scalar(
+            "SELECT id FROM emails WHERE internet_message_id = ? ORDER BY received_at DESC LIMIT 1",
         )
-        .bind(limit)
-        .fetch_all(&self.pool)
+        .bind(internet_message_id)
+        .fetch_optional(&self.read_pool)
         .await
-        .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
-
-        Ok(rows
-            .into_iter()
-            .map(|row| {
-                let client_project: Option<serde_json::Value> = row
-                    .get::<Option<String>, _>("client_or_project_json")
-                    .and_then(|s| serde_json::from_str(&s).ok());
-
-                let risks: Option<serde_json::Value> = row
-                    .get::<Option<String>, _>("risks_json")
-                    .and_then(|s| serde_json::from_str(&s).ok());
-
-                serde_json::json!({
-                    "id": row.get::<i64, _>("id"),
-                    "subject": row.get::<String, _>("subject"),
-                    "sender": row.get::<String, _>("sender"),
-                    "received_at": row.get::<chrono::DateTime<chrono::Utc>, _>("received_at"),
-                    "body_text": row.get::<String, _>("body_text"),
-                    "primary_type": row.get::<Option<String>, _>("primary_type"),
-                    "intent": row.get::<Option<String>, _>("intent"),
-                    "urgency": row.get::<Option<String>, _>("urgency"),
-                    "sentiment": row.get::<Option<String>, _>("sentiment"),
-                    "needs_response": row.get::<Option<bool>, _>("needs_response"),
-                    "waiting_on": row.get::<Option<String>, _>("waiting_on"),
-                    "due_by": row.get::<Option<chrono::DateTime<chrono::Utc>>, _>("due_by"),
-                    "summary": row.get::<Option<String>, _>("summary"),
-                    "client_or_project": client_project,
-                    "risks": risks
-                })
-            })
-            .collect())
+        .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))
     }
 
-    pub async fn get_entities(&self) -> Result<serde_json::Value> {
-        let nodes_rows = sqlx::query(
-            "SELECT id, canonical_name as name, entity_type as kind FROM entities LIMIT 100",
+    /// Marks an email's facts as checked by hand. Returns false when it has
+    /// none waiting for review.
+    pub async fn clear_fact_review(&self, email_id: i64) -> Result<bool> {
+        let result = sqlx::query(
+            "UPDATE extracted_email_facts SET review_reason = NULL
+             WHERE email_id = ? AND review_reason IS NOT NULL",
         )
-        .fetch_all(&self.pool)
+        .bind(email_id)
+        .execute(&self.pool)
         .await
-        .unwrap_or_else(|_| vec![]);
+        .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+        Ok(result.rows_affected() > 0)
+    }
 
-        let links_rows = sqlx::query("SELECT src_entity_id as source, dst_entity_id as target, edge_type as kind FROM edges LIMIT 200")
+    /// Sets the triage state of the listed emails' facts. Emails without
+    /// facts are left out. Returns how many were updated.
+    pub async fn set_triage_state(&self, email_ids: &[i64], state: TriageState) -> Result<u64> {
+        if email_ids.is_empty() {
+            return Ok(0);
+        }
+        let (needs_response, waiting_on) = state.facts();
+        let mut builder =
+            QueryBuilder::<Sqlite>::new("UPDATE extracted_email_facts SET needs_response = ");
+        builder.push_bind(needs_response);
+        builder.push(", waiting_on = ");
+        builder.push_bind(waiting_on.to_string());
+        builder.push(" WHERE email_id IN (");
+        let mut ids = builder.separated(", ");
+        for id in email_ids {
+            ids.push_bind(*id);
+        }
+        builder.push(")");
+        let result = builder
+            .build()
+            .execute(&self.pool)
+            .await
+            .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+        Ok(result.rows_affected())
+    }
+
+    /// Files the listed emails under `project`, with full confidence as the
+    /// user chose it. Emails without facts are left out. Returns how many
+    /// were updated.
+    pub async fn reassign_project(&self, email_ids: &[i64], project: &str) -> Result<u64> {
+        if email_ids.is_empty() {
+            return Ok(0);
+        }
+        let mut builder = QueryBuilder::<Sqlite>::new(
+            "UPDATE extracted_email_facts SET client_or_project_json = json_object('name', ",
+        );
+        builder.push_bind(project);
+        builder.push(", 'confidence', 1.0) WHERE email_id IN (");
+        let mut ids = builder.separated(", ");
+        for id in email_ids {
+            ids.push_bind(*id);
+        }
+        builder.push(")");
+        let result = builder
+            .build()
+            .execute(&self.pool)
+            .await
+            .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+        Ok(result.rows_affected())
+    }
+
+    /// Deletes the listed emails with everything derived from them, and
+    /// remembers them so scans don't store them again. Attachment files
+    /// are left to garbage collection. Returns the keys of the emails
+    /// deleted, to remove their vectors by.
+    pub async fn purge_emails(&self, email_ids: &[i64]) -> Result<Vec<EmailKey>> {
+        if email_ids.is_empty() {
+            return Ok(vec![]);
+        }
+        let mut tx = self
+            .pool
+            .begin()
+            .await
+            .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+
+        let mut builder =
+            QueryBuilder::<Sqlite>::new("SELECT id, store_id, entry_id FROM emails WHERE id IN (");
+        let mut ids = builder.separated(", ");
+        for id in email_ids {
+            ids.push_bind(*id);
+        }
+        builder.push(")");
+        let keys: Vec<EmailKey> = builder
+            .build()
+            .fetch_all(&mut *tx)
+            .await
+            .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?
+            .iter()
+            .map(|r| EmailKey {
+                id: r.get("id"),
+                store_id: r.get("store_id"),
+                entry_id: r.get("entry_id"),
+            })
+            .collect();
+
+        let now = Utc::now();
+        for key in &keys {
+            sqlx::query(
+                "INSERT INTO purged_emails (store_id, entry_id, purged_at) VALUES (?, ?, ?)
+                 ON CONFLICT(store_id, entry_id) DO UPDATE SET purged_at = excluded.purged_at",
+            )
+            .bind(&key.store_id)
+            .bind(&key.entry_id)
+            .bind(now)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+            sqlx::query("DELETE FROM emails WHERE id = ?")
+                .bind(key.id)
+                .execute(&mut *tx)
+                .await
+                .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+        }
+
+        tx.commit()
+            .await
+            .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+        Ok(keys)
+    }
+
+    /// Moves the listed emails to the trash. Emails already there keep
+    /// their deletion time. Returns how many were moved.
+    pub async fn trash_emails(&self, email_ids: &[i64]) -> Result<u64> {
+        if email_ids.is_empty() {
+            return Ok(0);
+        }
+        let mut builder = QueryBuilder::<Sqlite>::new("UPDATE emails SET deleted_at = ");
+        builder.push_bind(Utc::now());
+        builder.push(" WHERE deleted_at IS NULL AND id IN (");
+        let mut ids = builder.separated(", ");
+        for id in email_ids {
+            ids.push_bind(*id);
+        }
+        builder.push(")");
+        let result = builder
+            .build()
+            .execute(&self.pool)
+            .await
+            .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+        Ok(result.rows_affected())
+    }
+
+    /// Takes an email out of the trash. Returns false when it isn't there.
+    pub async fn restore_email(&self, email_id: i64) -> Result<bool> {
+        let result = sqlx::query(
+            "UPDATE emails SET deleted_at = NULL WHERE id = ? AND deleted_at IS NOT NULL",
+        )
+        .bind(email_id)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Emails in the trash, most recently deleted first, each with the
+    /// `purge_at` time it is purged at.
+    pub async fn list_trash(&self) -> Result<Vec<serde_json::Value>> {
+        let rows = sqlx::query(&format!(
+            "SELECT {} FROM emails e LEFT JOIN extracted_email_facts f ON e.id = f.email_id
+             WHERE e.deleted_at IS NOT NULL
+             ORDER BY e.deleted_at DESC, e.id DESC",
+            EMAIL_WITH_FACTS_COLUMNS
+        ))
+        .fetch_all(&self.read_pool)
+        .await
+        .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+
+        Ok(rows
+            .iter()
+            .map(|row| {
+                let mut email = email_with_facts_json(row);
+                let deleted_at: DateTime<Utc> = row.get("deleted_at");
+                email["purge_at"] =
+                    serde_json::json!(deleted_at + chrono::Duration::days(TRASH_RETENTION_DAYS));
+                email
+            })
+            .collect())
+    }
+
+    /// Emails that have been in the trash longer than the retention period
+    /// at `now`.
+    pub async fn list_expired_trash(&self, now: DateTime<Utc>) -> Result<Vec<i64>> {
+        sqlx::query_scalar("SELECT id FROM emails WHERE deleted_at < ? ORDER BY deleted_at")
+            .bind(now - chrono::Duration::days(TRASH_RETENTION_DAYS))
+            .fetch_all(&self.read_pool)
+            .await
+            .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))
+    }
+
+    /// Emails of projects with their own retention that are older than it,
+    /// oldest first.
+    pub async fn list_expired_project_emails(&self, now: DateTime<Utc>) -> Result<Vec<i64>> {
+        sqlx::query_scalar(
+            "SELECT e.id FROM emails e
+             JOIN extracted_email_facts f ON e.id = f.email_id
+             JOIN projects p ON p.name = json_extract(f.client_or_project_json, '$.name')
+             WHERE p.retention_days IS NOT NULL
+               AND julianday(e.received_at) < julianday(?) - p.retention_days
+             ORDER BY e.received_at",
+        )
+        .bind(now)
+        .fetch_all(&self.read_pool)
+        .await
+        .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))
+    }
+
+    /// Whether the user purged the email with this Outlook identity.
+    pub async fn is_purged(&self, store_id: &str, entry_id: &str) -> Result<bool> {
+        let row = sqlx::query("SELECT 1 FROM purged_emails WHERE store_id = ? AND entry_id = ?")
+            .bind(store_id)
+            .bind(entry_id)
+            .fetch_optional(&self.read_pool)
+            .await
+            .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+        Ok(row.is_some())
+    }
+
+    /// The fields shown in the email detail view.
+    pub async fn get_email_detail(&self, id: i64) -> Result<Option<EmailRow>> {
+        sqlx::query_as::<_, EmailRow>(
+            "SELECT id, subject, sender, received_at, body_text FROM emails WHERE id = ?",
+        )
+        .bind(id)
+        .fetch_optional(&self.read_pool)
+        .await
+        .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))
+    }
+
+    /// Content changes detected for an email, newest first.
+    pub async fn list_email_changes(&self, email_id: i64) -> Result<Vec<EmailChange>> {
+        let rows = sqlx::query(
+            "SELECT id, email_id, detected_at, previous_subject, subject, body_diff, lines_added, lines_removed
+             FROM email_changes WHERE email_id = ? ORDER BY detected_at DESC, id DESC",
+        )
+        .bind(email_id)
+        .fetch_all(&self.read_pool)
+        .await
+        .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+
+        Ok(rows
+            .iter()
+            .map(|r| EmailChange {
+                id: r.get("id"),
+                email_id: r.get("email_id"),
+                detected_at: r.get("detected_at"),
+                previous_subject: r.get("previous_subject"),
+                subject: r.get("subject"),
+                body_diff: r.get("body_diff"),
+                lines_added: r.get("lines_added"),
+                lines_removed: r.get("lines_removed"),
+            })
+            .collect())
+    }
+
+    pub async fn get_email_body(&self, id: i64) -> Result<Option<String>> {
+        sqlx::query_scalar("SELECT body_text FROM emails WHERE id = ?")
+            .bind(id)
+            .fetch_optional(&self.read_pool)
+            .await
+            .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))
+    }
+
+    /// The project an email's facts assign it to.
+    pub async fn get_email_project(&self, email_id: i64) -> Result<Option<String>> {
+        let row = sqlx::query(
+            "SELECT json_extract(client_or_project_json, '$.name') AS project
+             FROM extracted_email_facts WHERE email_id = ?",
+        )
+        .bind(email_id)
+        .fetch_optional(&self.read_pool)
+        .await
+        .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+        Ok(row.and_then(|r| r.get::<Option<String>, _>("project")))
+    }
+
+    /// The extracted summary for an email, if it has been processed.
+    pub async fn get_fact_summary(&self, email_id: i64) -> Result<Option<String>> {
+        sqlx::query_scalar("SELECT summary FROM extracted_email_facts WHERE email_id = ?")
+            .bind(email_id)
+            .fetch_optional(&self.read_pool)
+            .await
+            .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))
+    }
+
+    /// Email ids whose vector upsert has not completed yet, oldest first.
+    pub async fn get_pending_vector_upserts(&self, limit: i64) -> Result<Vec<i64>> {
+        let rows = sqlx::query(
+            "SELECT email_id FROM vector_outbox ORDER BY attempts, enqueued_at LIMIT ?",
+        )
+        .bind(limit)
+        .fetch_all(&self.read_pool)
+        .await
+        .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+
+        Ok(rows.iter().map(|r| r.get("email_id")).collect())
+    }
+
+    /// Queues a vector upsert for an email that is already saved.
+    pub async fn enqueue_vector_upsert(&self, email_id: i64) -> Result<()> {
+        sqlx::query(
+            "INSERT INTO vector_outbox (email_id, enqueued_at) VALUES (?, ?)
+             ON CONFLICT(email_id) DO NOTHING",
+        )
+        .bind(email_id)
+        .bind(Utc::now())
+        .execute(&self.pool)
+        .await
+        .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+        Ok(())
+    }
+
+    /// Queues every stored email for embedding, e.g. after the vector
+    /// collection was recreated. Returns how many were queued.
+    pub async fn enqueue_all_vector_upserts(&self) -> Result<u64> {
+        let result = sqlx::query(
+            "INSERT INTO vector_outbox (email_id, enqueued_at)
+             SELECT id, ? FROM emails WHERE true
+             ON CONFLICT(email_id) DO NOTHING",
+        )
+        .bind(Utc::now())
+        .execute(&self.pool)
+        .await
+        .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+        Ok(result.rows_affected())
+    }
+
+    pub async fn complete_vector_upsert(&self, email_id: i64) -> Result<()> {
+        sqlx::query("DELETE FROM vector_outbox WHERE email_id = ?")
+            .bind(email_id)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+        Ok(())
+    }
+
+    pub async fn list_email_keys(&self) -> Result<Vec<EmailKey>> {
+        let rows = sqlx::query("SELECT id, store_id, entry_id FROM emails ORDER BY id")
+            .fetch_all(&self.read_pool)
+            .await
+            .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+        Ok(rows
+            .iter()
+            .map(|r| EmailKey {
+                id: r.get("id"),
+                store_id: r.get("store_id"),
+                entry_id: r.get("entry_id"),
+            })
+            .collect())
+    }
+
+    /// When each email stored from `folder` was last indexed, by entry id.
+    pub async fn list_indexed_at(&self, folder: &str) -> Result<HashMap<String, DateTime<Utc>>> {
+        let rows = sqlx::query("SELECT entry_id, last_indexed_at FROM emails WHERE folder = ?")
+            .bind(folder)
+            .fetch_all(&self.read_pool)
+            .await
+            .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+        Ok(rows
+            .iter()
+            .map(|r| (r.get("entry_id"), r.get("last_indexed_at")))
+            .collect())
+    }
+
+    /// Marks an email as never indexed, so the next scan fetches and
+    /// processes it again even if Outlook did not modify it.
+    pub async fn reset_last_indexed(&self, id: i64) -> Result<()> {
+        sqlx::query("UPDATE emails SET last_indexed_at = ? WHERE id = ?")
+            .bind(DateTime::<Utc>::UNIX_EPOCH)
+            .bind(id)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+        Ok(())
+    }
+
+    /// Marks an email as indexed now, e.g. after Noodle itself modified the
+    /// Outlook item, so the scans don't fetch it again for that.
+    pub async fn touch_last_indexed(&self, id: i64) -> Result<()> {
+        sqlx::query("UPDATE emails SET last_indexed_at = ? WHERE id = ?")
+            .bind(Utc::now())
+            .bind(id)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+        Ok(())
+    }
+
+    /// Leaves an email's fact extraction for when the user is idle.
+    pub async fn defer_extraction(&self, email_id: i64) -> Result<()> {
+        sqlx::query(
+            "INSERT INTO deferred_extractions (email_id, deferred_at) VALUES (?, ?)
+             ON CONFLICT(email_id) DO NOTHING",
+        )
+        .bind(email_id)
+        .bind(Utc::now())
+        .execute(&self.pool)
+        .await
+        .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+        Ok(())
+    }
+
+    /// Email ids whose extraction was deferred, oldest first.
+    pub async fn list_deferred_extractions(&self, limit: i64) -> Result<Vec<i64>> {
+        sqlx::query_scalar("SELECT email_id FROM deferred_extractions ORDER BY deferred_at LIMIT ?")
+            .bind(limit)
+            .fetch_all(&self.read_pool)
+            .await
+            .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))
+    }
+
+    pub async fn complete_deferred_extraction(&self, email_id: i64) -> Result<()> {
+        sqlx::query("DELETE FROM deferred_extractions WHERE email_id = ?")
+            .bind(email_id)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+        Ok(())
+    }
+
+    /// Outbox entries that have failed at least once, oldest first.
+    pub async fn list_vector_retries(&self, limit: i64) -> Result<Vec<VectorRetry>> {
+        let rows = sqlx::query(
+            "SELECT o.email_id, e.subject, o.attempts, o.last_error, o.enqueued_at
+             FROM vector_outbox o JOIN emails e ON e.id = o.email_id
+             WHERE o.attempts > 0
+             ORDER BY o.enqueued_at
+             LIMIT ?",
+        )
+        .bind(limit)
+        .fetch_all(&self.read_pool)
+        .await
+        .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+
+        Ok(rows
+            .iter()
+            .map(|r| VectorRetry {
+                email_id: r.get("email_id"),
+                subject: r.get("subject"),
+                attempts: r.get("attempts"),
+                last_error: r.get("last_error"),
+                enqueued_at: r.get("enqueued_at"),
+            })
+            .collect())
+    }
+
+    /// Emails whose payload has to be pushed to Qdrant, oldest first, with
+    /// when they were queued. Those still waiting for their vector are left
+    /// out, as the upsert carries the current payload.
+    pub async fn get_pending_payload_syncs(&self, limit: i64) -> Result<Vec<(i64, String)>> {
+        let rows = sqlx::query(
+            "SELECT p.email_id, CAST(p.enqueued_at AS TEXT) AS enqueued_at FROM payload_outbox p
+             WHERE NOT EXISTS (SELECT 1 FROM vector_outbox v WHERE v.email_id = p.email_id)
+             ORDER BY p.attempts, p.enqueued_at LIMIT ?",
+        )
+        .bind(limit)
+        .fetch_all(&self.read_pool)
+        .await
+        .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+
+        Ok(rows
+            .iter()
+            .map(|r| (r.get("email_id"), r.get("enqueued_at")))
+            .collect())
+    }
+
+    /// The filterable fields an email's vector should carry.
+    pub async fn get_vector_payload(&self, email_id: i64) -> Result<Option<VectorPayload>> {
+        let row = sqlx::query(
+            "SELECT e.id, e.sender, e.conversation_id,
+                    json_extract(f.client_or_project_json, '$.name') AS project, a.tags_json
+             FROM emails e
+             LEFT JOIN extracted_email_facts f ON f.email_id = e.id
+             LEFT JOIN user_annotations a ON a.email_id = e.id
+             WHERE e.id = ?",
+        )
+        .bind(email_id)
+        .fetch_optional(&self.read_pool)
+        .await
+        .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+
+        Ok(row.map(|r| VectorPayload {
+            email_id: r.get("id"),
+            sender: r.get("sender"),
+            conversation_id: r.get("conversation_id"),
+            project: r.get("project"),
+            tags: r
+                .get::<Option<String>, _>("tags_json")
+                .and_then(|s| serde_json::from_str(&s).ok())
+                .unwrap_or_default(),
+        }))
+    }
+
+    /// Settles an email queued at `enqueued_at`, as returned by
+    /// [`Self::get_pending_payload_syncs`]. If it was queued again since,
+    /// the newer change stays for the next pass.
+    pub async fn complete_payload_sync(&self, email_id: i64, enqueued_at: &str) -> Result<()> {
+        sqlx::query("DELETE FROM payload_outbox WHERE email_id = ? AND enqueued_at = ?")
+            .bind(email_id)
+            .bind(enqueued_at)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+        Ok(())
+    }
+
+    pub async fn fail_payload_sync(&self, email_id: i64, error: &str) -> Result<()> {
+        sqlx::query(
+            "UPDATE payload_outbox SET attempts = attempts + 1, last_error = ? WHERE email_id = ?",
+        )
+        .bind(error)
+        .bind(email_id)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+        Ok(())
+    }
+
+    pub async fn fail_vector_upsert(&self, email_id: i64, error: &str) -> Result<()> {
+        sqlx::query(
+            "UPDATE vector_outbox SET attempts = attempts + 1, last_error = ? WHERE email_id = ?",
+        )
+        .bind(error)
+        .bind(email_id)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+        Ok(())
+    }
+
+    pub async fn save_facts(&self, facts: &noodle_core::types::EmailFact) -> Result<()> {
+        let primary_type = facts.primary_type.to_string();
+        let intent = facts.intent.to_string();
+        let sentiment = facts.sentiment.to_string();
+        let urgency = facts.urgency.to_string();
+        let waiting_on = facts.waiting_on.to_string();
+
+        let client_project = serde_json::to_string(&facts.client_or_project).unwrap();
+        let key_points = serde_json::to_string(&facts.key_points).unwrap();
+        let risks = serde_json::to_string(&facts.risks).unwrap();
+        let issues = serde_json::to_string(&facts.issues).unwrap();
+        let blockers = serde_json::to_string(&facts.blockers).unwrap();
+        let open_questions = serde_json::to_string(&facts.open_questions).unwrap();
+        let answered_questions = serde_json::to_string(&facts.answered_questions).unwrap();
+
+        let provenance = serde_json::to_string(&facts.provenance).unwrap();
+
+        // Previous schema had 'deadlines_json', 'action_items_json', 'decisions_json', 'suggested_labels_json'.
+        // These are removed or re-mapped. We do NOT insert them.
+
+        sqlx::query(
+            r#"
+            INSERT INTO extracted_email_facts (
+                email_id, primary_type, intent, urgency, sentiment, client_or_project_json,
+                due_by, due_by_raw, needs_response, waiting_on, summary, key_points_json,
+                risks_json, issues_json, blockers_json, open_questions_json, answered_questions_json,
+                confidence, review_reason, provenance_json, created_at
+            ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+            ON CONFLICT(email_id) DO UPDATE SET
+                primary_type = excluded.primary_type,
+                intent = excluded.intent,
+                urgency = excluded.urgency,
+                sentiment = excluded.sentiment,
+                client_or_project_json = excluded.client_or_project_json,
+                due_by = excluded.due_by,
+                due_by_raw = excluded.due_by_raw,
+                needs_response = excluded.needs_response AND NOT EXISTS (
+                    SELECT 1 FROM email_responses r WHERE r.email_id = excluded.email_id
+                ),
+                waiting_on = excluded.waiting_on,
+                summary = excluded.summary,
+                key_points_json = excluded.key_points_json,
+                risks_json = excluded.risks_json,
+                issues_json = excluded.issues_json,
+                blockers_json = excluded.blockers_json,
+                open_questions_json = excluded.open_questions_json,
+                answered_questions_json = excluded.answered_questions_json,
+                confidence = excluded.confidence,
+                review_reason = excluded.review_reason,
+                provenance_json = excluded.provenance_json,
+                stale = 0,
+                issues_clustered = 0,
+                issues_tracked = 0
+            "#,
+        )
+        .bind(facts.email_id)
+        .bind(primary_type)
+        .bind(intent)
+        .bind(urgency)
+        .bind(sentiment)
+        .bind(client_project)
+        .bind(facts.due_by)
+        .bind(facts.due_by_raw.as_ref())
+        .bind(facts.needs_response)
+        .bind(waiting_on)
+        .bind(facts.summary.as_str())
+        .bind(key_points)
+        .bind(risks)
+        .bind(issues)
+        .bind(blockers)
+        .bind(open_questions)
+        .bind(answered_questions)
+        .bind(facts.confidence)
+        .bind(facts.review_reason.as_ref())
+        .bind(provenance)
+        .bind(facts.created_at)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+
+        Ok(())
+    }
+
+    pub async fn get_dashboard_stats(&self) -> Result<serde_json::Value> {
+        let total_emails =
+            sqlx::query("SELECT COUNT(*) as count FROM emails WHERE deleted_at IS NULL")
+                .fetch_one(&self.read_pool)
+                .await
+                .map(|r| r.get::<i64, _>("count"))
+                .unwrap_or(0);
+
+        let sentiment_data = sqlx::query(
+            "SELECT f.sentiment, COUNT(*) as count
+             FROM extracted_email_facts f JOIN emails e ON e.id = f.email_id
+             WHERE e.deleted_at IS NULL GROUP BY f.sentiment",
+        )
+        .fetch_all(&self.read_pool)
+        .await
+        .unwrap_or_else(|_| vec![]);
+
+        let sentiments = sentiment_data
+            .into_iter()
+            .map(|r| serde_json::json!({ "sentiment": r.get::<String, _>("sentiment"), "count": r.get::<i64, _>("count") }))
+            .collect::<Vec<_>>();
+
+        Ok(serde_json::json!({
+            "total_emails": total_emails,
+            "sentiments": sentiments
+        }))
+    }
+
+    /// Links Inbox emails of `conversation_id` that need a response to the
+    /// first Sent Items message sent after them, recording how long the reply
+    /// took, and clears their `needs_response`. Returns how many were linked.
+    pub async fn link_responses(&self, conversation_id: &str) -> Result<u64> {
+        let mut tx = self
+            .pool
+            .begin()
+            .await
+            .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+        let linked = sqlx::query(
+            "INSERT OR IGNORE INTO email_responses (email_id, response_email_id, responded_at, latency_secs)
+             SELECT e.id, s.id, s.sent_at,
+                    CAST(ROUND((julianday(s.sent_at) - julianday(e.received_at)) * 86400) AS INTEGER)
+             FROM emails e
+             JOIN extracted_email_facts f ON f.email_id = e.id
+             JOIN emails s ON s.id = (
+                 SELECT r.id FROM emails r
+                 WHERE r.folder = 'Sent Items'
+                   AND r.conversation_id = e.conversation_id
+                   AND julianday(r.sent_at) > julianday(e.received_at)
+                 ORDER BY julianday(r.sent_at)
+                 LIMIT 1
+             )
+             WHERE e.conversation_id = ?
+               AND e.folder = 'Inbox'
+               AND f.needs_response = 1",
+        )
+        .bind(conversation_id)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?
+        .rows_affected();
+        // Also catches facts re-extracted after their email was linked.
+        sqlx::query(
+            "UPDATE extracted_email_facts SET needs_response = 0
+             WHERE needs_response = 1
+               AND email_id IN (
+                   SELECT r.email_id FROM email_responses r
+                   JOIN emails e ON e.id = r.email_id
+                   WHERE e.conversation_id = ?
+               )",
+        )
+        .bind(conversation_id)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+        tx.commit()
+            .await
+            .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+        Ok(linked)
+    }
+
+    /// Reply latency for inbound mail since `since`: each Inbox email is matched to
+    /// the first Sent Items message in the same conversation sent after it arrived.
+    /// Aggregated per sender and per project, slowest first; `unanswered` counts
+    /// inbound emails that still have no reply.
+    pub async fn get_response_times(&self, since: DateTime<Utc>) -> Result<serde_json::Value> {
+        let by_sender = self.response_times_grouped("sender", since).await?;
+        let by_project = self.response_times_grouped("project", since).await?;
+
+        Ok(serde_json::json!({
+            "by_sender": by_sender,
+            "by_project": by_project
+        }))
+    }
+
+    /// What the user wrote in their `limit` most recent Sent Items messages,
+    /// newest first, without the quoted history; messages with nothing of
+    /// their own, like bare forwards, are skipped.
+    pub async fn get_writing_samples(&self, limit: usize) -> Result<Vec<String>> {
+        let bodies: Vec<String> = sqlx::query_scalar(
+            "SELECT body_text FROM emails
+             WHERE folder = 'Sent Items' AND excluded_reason IS NULL
+             ORDER BY sent_at DESC LIMIT ?",
+        )
+        // Some are skipped, so read more than asked for.
+        .bind((limit * 2) as i64)
+        .fetch_all(&self.read_pool)
+        .await
+        .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+        Ok(bodies
+            .iter()
+            .map(|body| own_text(body).trim())
+            .filter(|text| !text.is_empty())
+            .take(limit)
+            .map(str::to_string)
+            .collect())
+    }
+
+    /// How the user writes, from Sent Items since `since`: tone distribution,
+    /// average length of their own text (quoted history left out), commitments
+    /// made, and questions they asked that nobody has answered yet.
+    pub async fn get_self_insights(&self, since: DateTime<Utc>) -> Result<serde_json::Value> {
+        let tone = sqlx::query(
+            "SELECT f.sentiment, COUNT(*) AS count
+             FROM emails s JOIN extracted_email_facts f ON f.email_id = s.id
+             WHERE s.folder = 'Sent Items' AND s.sent_at >= ? AND s.deleted_at IS NULL
+             GROUP BY f.sentiment ORDER BY count DESC",
+        )
+        .bind(since)
+        .fetch_all(&self.read_pool)
+        .await
+        .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+
+        // A reply is a sent message in a conversation with earlier inbound mail.
+        let bodies = sqlx::query(
+            "SELECT s.body_text,
+                    EXISTS (SELECT 1 FROM emails i
+                            WHERE i.folder = 'Inbox'
+                              AND i.conversation_id = s.conversation_id
+                              AND julianday(i.received_at) < julianday(s.sent_at)) AS is_reply
+             FROM emails s
+             WHERE s.folder = 'Sent Items' AND s.sent_at >= ? AND s.deleted_at IS NULL",
+        )
+        .bind(since)
+        .fetch_all(&self.read_pool)
+        .await
+        .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+        let (mut words, mut reply_words, mut replies) = (0usize, 0usize, 0usize);
+        for row in &bodies {
+            let count = own_text(&row.get::<String, _>("body_text"))
+                .split_whitespace()
+                .count();
+            words += count;
+            if row.get::<bool, _>("is_reply") {
+                reply_words += count;
+                replies += 1;
+            }
+        }
+        let average = |total: usize, n: usize| (n > 0).then(|| total as f64 / n as f64);
+
+        let commitments = sqlx::query(
+            "SELECT s.id, s.subject, s.sent_at, f.summary, f.due_by
+             FROM emails s JOIN extracted_email_facts f ON f.email_id = s.id
+             WHERE s.folder = 'Sent Items' AND s.sent_at >= ? AND s.deleted_at IS NULL AND f.intent = 'commit'
+             ORDER BY s.sent_at DESC",
+        )
+        .bind(since)
+        .fetch_all(&self.read_pool)
+        .await
+        .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+
+        let questions = sqlx::query(
+            "SELECT s.id, s.subject, s.sent_at,
+                    json_extract(q.value, '$.question') AS question,
+                    json_extract(q.value, '$.owner') AS owner
+             FROM emails s
+             JOIN extracted_email_facts f ON f.email_id = s.id,
+                  json_each(f.open_questions_json) q
+             WHERE s.folder = 'Sent Items' AND s.sent_at >= ? AND s.deleted_at IS NULL
+               AND NOT EXISTS (SELECT 1 FROM emails r
+                               WHERE r.folder = 'Inbox'
+                                 AND r.conversation_id = s.conversation_id
+                                 AND julianday(r.received_at) > julianday(s.sent_at))
+             ORDER BY s.sent_at DESC",
+        )
+        .bind(since)
+        .fetch_all(&self.read_pool)
+        .await
+        .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+
+        Ok(serde_json::json!({
+            "sent_count": bodies.len(),
+            "tone": tone.iter().map(|r| serde_json::json!({
+                "sentiment": r.get::<String, _>("sentiment"),
+                "count": r.get::<i64, _>("count")
+            })).collect::<Vec<_>>(),
+            "avg_words": average(words, bodies.len()),
+            "avg_reply_words": average(reply_words, replies),
+            "commitments": commitments.iter().map(|r| serde_json::json!({
+                "email_id": r.get::<i64, _>("id"),
+                "subject": r.get::<String, _>("subject"),
+                "sent_at": r.get::<DateTime<Utc>, _>("sent_at"),
+                "summary": r.get::<String, _>("summary"),
+                "due_by": r.get::<Option<DateTime<Utc>>, _>("due_by")
+            })).collect::<Vec<_>>(),
+            "unanswered_questions": questions.iter().filter_map(|r| {
+                let question = r.get::<Option<String>, _>("question")?;
+                Some(serde_json::json!({
+                    "email_id": r.get::<i64, _>("id"),
+                    "subject": r.get::<String, _>("subject"),
+                    "sent_at": r.get::<DateTime<Utc>, _>("sent_at"),
+                    "question": question,
+                    "owner": r.get::<Option<String>, _>("owner")
+                }))
+            }).collect::<Vec<_>>()
+        }))
+    }
+
+    /// How the user and one sender interact: their mail per month (at
+    /// `utc_offset_secs` from UTC), its tone, the questions they asked that
+    /// the user hasn't answered, how fast the user replies to them, and the
+    /// projects their mail is about.
+    pub async fn get_sender_profile(
+        &self,
+        address: &str,
+        utc_offset_secs: i32,
+    ) -> Result<serde_json::Value> {
+        let address = address.trim().to_lowercase();
+        let shift = format!("{:+} seconds", utc_offset_secs);
+
+        let totals = sqlx::query(
+            "SELECT COUNT(*) AS received,
+                    MIN(received_at) AS first_received_at,
+                    MAX(received_at) AS last_received_at
+             FROM emails WHERE LOWER(sender) = ? AND deleted_at IS NULL",
+        )
+        .bind(&address)
+        .fetch_one(&self.read_pool)
+        .await
+        .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+
+        let volume = sqlx::query(
+            "SELECT strftime('%Y-%m', received_at, ?) AS month, COUNT(*) AS count
+             FROM emails WHERE LOWER(sender) = ? AND deleted_at IS NULL
+             GROUP BY month ORDER BY month",
+        )
+        .bind(&shift)
+        .bind(&address)
+        .fetch_all(&self.read_pool)
+        .await
+        .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+
+        let tone = sqlx::query(
+            "SELECT f.sentiment, COUNT(*) AS count
+             FROM emails e JOIN extracted_email_facts f ON f.email_id = e.id
+             WHERE LOWER(e.sender) = ? AND e.deleted_at IS NULL
+             GROUP BY f.sentiment ORDER BY count DESC",
+        )
+        .bind(&address)
+        .fetch_all(&self.read_pool)
+        .await
+        .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+        let (mut score, mut rated) = (0.0, 0);
+        for row in &tone {
+            let weight = match row.get::<String, _>("sentiment").as_str() {
+                "positive" => 1.0,
+                "neutral" => 0.0,
+                "concerned" => -0.5,
+                "hostile" => -1.0,
+                _ => continue,
+            };
+            let count = row.get::<i64, _>("count");
+            score += weight * count as f64;
+            rated += count;
+        }
+
+        let latency = sqlx::query(
+            "SELECT COUNT(*) AS replied, AVG(r.latency_secs) AS average_secs
+             FROM emails e JOIN email_responses r ON r.email_id = e.id
+             WHERE LOWER(e.sender) = ? AND e.deleted_at IS NULL",
+        )
+        .bind(&address)
+        .fetch_one(&self.read_pool)
+        .await
+        .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+
+        let questions = sqlx::query(
+            "SELECT e.id, e.subject, e.received_at,
+                    json_extract(q.value, '$.question') AS question,
+                    json_extract(q.value, '$.due_by') AS due_by
+             FROM emails e
+             JOIN extracted_email_facts f ON f.email_id = e.id,
+                  json_each(f.open_questions_json) q
+             WHERE LOWER(e.sender) = ? AND e.deleted_at IS NULL
+               AND NOT EXISTS (SELECT 1 FROM emails s
+                               WHERE s.conversation_id = e.conversation_id
+                                 AND s.folder = 'Sent Items'
+                                 AND julianday(s.sent_at) > julianday(e.received_at))
+             ORDER BY e.received_at DESC
+             LIMIT 20",
+        )
+        .bind(&address)
+        .fetch_all(&self.read_pool)
+        .await
+        .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+
+        let projects = sqlx::query(
+            "SELECT json_extract(f.client_or_project_json, '$.name') AS project,
+                    COUNT(*) AS count,
+                    MAX(e.received_at) AS last_received_at
+             FROM emails e JOIN extracted_email_facts f ON f.email_id = e.id
+             WHERE LOWER(e.sender) = ? AND e.deleted_at IS NULL
+               AND COALESCE(json_extract(f.client_or_project_json, '$.name'), '') NOT IN ('', 'Unknown')
+             GROUP BY project ORDER BY count DESC
+             LIMIT 20",
+        )
+        .bind(&address)
+        .fetch_all(&self.read_pool)
+        .await
+        .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+
+        Ok(serde_json::json!({
+            "address": address,
+            "received": totals.get::<i64, _>("received"),
+            "first_received_at": totals.get::<Option<DateTime<Utc>>, _>("first_received_at"),
+            "last_received_at": totals.get::<Option<DateTime<Utc>>, _>("last_received_at"),
+            "volume": volume.iter().map(|r| serde_json::json!({
+                "month": r.get::<String, _>("month"),
+                "count": r.get::<i64, _>("count")
+            })).collect::<Vec<_>>(),
+            "tone": tone.iter().map(|r| serde_json::json!({
+                "sentiment": r.get::<String, _>("sentiment"),
+                "count": r.get::<i64, _>("count")
+            })).collect::<Vec<_>>(),
+            // From -1 (hostile) to 1 (positive).
+            "average_sentiment": (rated > 0).then(|| score / rated as f64),
+            "replied": latency.get::<i64, _>("replied"),
+            "average_response_secs": latency.get::<Option<f64>, _>("average_secs"),
+            "open_questions": questions.iter().filter_map(|r| {
+                let question = r.get::<Option<String>, _>("question")?;
+                Some(serde_json::json!({
+                    "email_id": r.get::<i64, _>("id"),
+                    "subject": r.get::<String, _>("subject"),
+                    "received_at": r.get::<DateTime<Utc>, _>("received_at"),
+                    "question": question,
+                    "due_by": r.get::<Option<String>, _>("due_by")
+                }))
+            }).collect::<Vec<_>>(),
+            "projects": projects.iter().map(|r| serde_json::json!({
+                "name": r.get::<String, _>("project"),
+                "count": r.get::<i64, _>("count"),
+                "last_received_at": r.get::<DateTime<Utc>, _>("last_received_at")
+            })).collect::<Vec<_>>()
+        }))
+    }
+
+    async fn response_times_grouped(
+        &self,
+        group_column: &str,
+        since: DateTime<Utc>,
+    ) -> Result<Vec<serde_json::Value>> {
+        let sql = format!(
+            "WITH inbound AS (
+                SELECT i.sender,
+                       COALESCE(json_extract(f.client_or_project_json, '$.name'), 'Unknown') AS project,
+                       (julianday((
+                           SELECT MIN(s.sent_at) FROM emails s
+                           WHERE s.folder = 'Sent Items'
+                             AND s.conversation_id = i.conversation_id
+                             AND julianday(s.sent_at) > julianday(i.received_at)
+                       )) - julianday(i.received_at)) * 24.0 AS hours
+                FROM emails i
+                LEFT JOIN extracted_email_facts f ON f.email_id = i.id
+                WHERE i.folder = 'Inbox'
+                  AND i.conversation_id IS NOT NULL
+                  AND i.received_at >= ?
+                  AND i.deleted_at IS NULL
+            )
+            SELECT {0} AS grp,
+                   COUNT(hours) AS replied,
+                   COUNT(*) - COUNT(hours) AS unanswered,
+                   AVG(hours) AS avg_hours,
+                   MAX(hours) AS max_hours
+            FROM inbound
+            GROUP BY {0}
+            ORDER BY avg_hours IS NULL, avg_hours DESC",
+            group_column
+        );
+
+        let rows = sqlx::query(&sql)
+            .bind(since)
+            .fetch_all(&self.read_pool)
+            .await
+            .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+
+        Ok(rows
+            .iter()
+            .map(|r| {
+                serde_json::json!({
+                    "name": r.get::<String, _>("grp"),
+                    "replied": r.get::<i64, _>("replied"),
+                    "unanswered": r.get::<i64, _>("unanswered"),
+                    "avg_hours": r.get::<Option<f64>, _>("avg_hours"),
+                    "max_hours": r.get::<Option<f64>, _>("max_hours"),
+                })
+            })
+            .collect())
+    }
+
+    /// Email counts in `[start, end)` bucketed by weekday (0 = Sunday) × hour and by
+    /// sender domain. `utc_offset_secs` shifts timestamps into the user's local time
+    /// before bucketing.
+    pub async fn get_volume_heatmap(
+        &self,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+        utc_offset_secs: i32,
+    ) -> Result<serde_json::Value> {
+        let shift = format!("{:+} seconds", utc_offset_secs);
+
+        let cells = sqlx::query(
+            "SELECT CAST(strftime('%w', received_at, ?) AS INTEGER) AS weekday,
+                    CAST(strftime('%H', received_at, ?) AS INTEGER) AS hour,
+                    COUNT(*) AS count
+             FROM emails
+             WHERE received_at >= ? AND received_at < ? AND deleted_at IS NULL
+             GROUP BY weekday, hour
+             ORDER BY weekday, hour",
+        )
+        .bind(&shift)
+        .bind(&shift)
+        .bind(start)
+        .bind(end)
+        .fetch_all(&self.read_pool)
+        .await
+        .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+
+        // Exchange-internal senders have no SMTP address; they share one bucket.
+        let domains = sqlx::query(
+            "SELECT CASE WHEN INSTR(sender, '@') > 0
+                         THEN LOWER(RTRIM(SUBSTR(sender, INSTR(sender, '@') + 1), '> '))
+                         ELSE '(internal)' END AS domain,
+                    COUNT(*) AS count
+             FROM emails
+             WHERE received_at >= ? AND received_at < ? AND deleted_at IS NULL
+             GROUP BY domain
+             ORDER BY count DESC
+             LIMIT 50",
+        )
+        .bind(start)
+        .bind(end)
+        .fetch_all(&self.read_pool)
+        .await
+        .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+
+        let cells = cells
+            .iter()
+            .map(|r| {
+                serde_json::json!({
+                    "weekday": r.get::<i64, _>("weekday"),
+                    "hour": r.get::<i64, _>("hour"),
+                    "count": r.get::<i64, _>("count"),
+                })
+            })
+            .collect::<Vec<_>>();
+        let domains = domains
+            .iter()
+            .map(|r| serde_json::json!({ "domain": r.get::<String, _>("domain"), "count": r.get::<i64, _>("count") }))
+            .collect::<Vec<_>>();
+
+        Ok(serde_json::json!({
+            "by_weekday_hour": cells,
+            "by_domain": domains
+        }))
+    }
+
+    /// High-urgency inbound emails that need a response and have no later reply in
+    /// their conversation, VIP mail first, then newest first. Projects excluded from
+    /// the digest are skipped.
+    pub async fn get_unhandled_urgent(&self) -> Result<Vec<serde_json::Value>> {
+        let rows = sqlx::query(&format!(
+            "SELECT {} FROM emails e
+             JOIN extracted_email_facts f ON e.id = f.email_id
+             LEFT JOIN projects p ON p.name = json_extract(f.client_or_project_json, '$.name')
+             WHERE e.folder = 'Inbox'
+               AND e.deleted_at IS NULL
+               AND f.urgency = 'high'
+               AND f.needs_response = 1
+               AND COALESCE(p.include_in_digest, 1) = 1
+               AND NOT EXISTS (
+                   SELECT 1 FROM emails s
+                   WHERE s.folder = 'Sent Items'
+                     AND s.conversation_id = e.conversation_id
+                     AND julianday(s.sent_at) > julianday(e.received_at)
+               )
+             ORDER BY priority DESC, e.received_at DESC",
+            EMAIL_WITH_FACTS_COLUMNS
+        ))
+        .fetch_all(&self.read_pool)
+        .await
+        .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+
+        Ok(rows.iter().map(email_with_facts_json).collect())
+    }
+
+    /// Open threads grouped into `me`, `them` and `third_party` lanes. Only the
+    /// latest extracted email of each conversation counts, so a thread sits in
+    /// exactly one lane (or none once its latest email waits on nobody).
+    pub async fn get_waiting_board(&self) -> Result<serde_json::Value> {
+        let rows = sqlx::query(&format!(
+            "WITH latest AS (
+                SELECT e.id,
+                       COUNT(*) OVER thread AS thread_size,
+                       ROW_NUMBER() OVER (thread ORDER BY e.received_at DESC, e.id DESC) AS rn
+                FROM emails e JOIN extracted_email_facts f ON e.id = f.email_id
+                WHERE e.deleted_at IS NULL
+                WINDOW thread AS (PARTITION BY COALESCE(e.conversation_id, 'email:' || e.id))
+            )
+            SELECT {}, l.thread_size
+            FROM latest l
+            JOIN emails e ON e.id = l.id
+            JOIN extracted_email_facts f ON e.id = f.email_id
+            WHERE l.rn = 1 AND f.waiting_on != 'none'
+            ORDER BY e.received_at DESC",
+            EMAIL_WITH_FACTS_COLUMNS
+        ))
+        .fetch_all(&self.read_pool)
+        .await
+        .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+
+        let mut lanes: HashMap<String, Vec<serde_json::Value>> = ["me", "them", "third_party"]
+            .into_iter()
+            .map(|lane| (lane.to_string(), vec![]))
+            .collect();
+        for row in &rows {
+            let mut email = email_with_facts_json(row);
+            email["thread_size"] = serde_json::json!(row.get::<i64, _>("thread_size"));
+            let lane = row.get::<String, _>("waiting_on");
+            if let Some(items) = lanes.get_mut(&lane) {
+                items.push(email);
+            }
+        }
+
+        Ok(serde_json::json!(lanes))
+    }
+
+    /// Fetches emails for a ranked list of vector-search hits in one query.
+    /// Results follow the order of `hits`, carry the similarity score, and drop
+    /// any email that does not satisfy `filter`.
+    pub async fn get_emails_by_ids(
+        &self,
+        hits: Vec<(i64, f32)>,
+        filter: &SearchFilter,
+    ) -> Result<Vec<serde_json::Value>> {
+        if hits.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let mut builder = QueryBuilder::<Sqlite>::new(format!(
+            "SELECT {} FROM emails e LEFT JOIN extracted_email_facts f ON e.id = f.email_id WHERE e.id IN (",
+            EMAIL_WITH_FACTS_COLUMNS
+        ));
+        let mut ids = builder.separated(", ");
+        for (id, _) in &hits {
+            ids.push_bind(*id);
+        }
+        builder.push(")");
+        push_search_filter(&mut builder, filter);
+
+        let rows = builder
+            .build()
+            .fetch_all(&self.read_pool)
+            .await
+            .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+
+        let mut by_id: HashMap<i64, SqliteRow> = rows
+            .into_iter()
+            .map(|row| (row.get::<i64, _>("id"), row))
+            .collect();
+
+        Ok(hits
+            .into_iter()
+            .filter_map(|(id, score)| {
+                by_id.remove(&id).map(|row| {
+                    let mut email = email_with_facts_json(&row);
+                    email["score"] = serde_json::json!(score);
+                    email
+                })
+            })
+            .collect())
+    }
+
+    /// One email in the same shape as the list and search results.
+    pub async fn get_email_with_facts(&self, id: i64) -> Result<Option<serde_json::Value>> {
+        let row = sqlx::query(&format!(
+            "SELECT {} FROM emails e LEFT JOIN extracted_email_facts f ON e.id = f.email_id
+             WHERE e.id = ?",
+            EMAIL_WITH_FACTS_COLUMNS
+        ))
+        .bind(id)
+        .fetch_optional(&self.read_pool)
+        .await
+        .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+
+        Ok(row.as_ref().map(email_with_facts_json))
+    }
+
+    pub async fn get_recent_emails(&self, limit: i64) -> Result<Vec<serde_json::Value>> {
+        self.list_emails(&SearchFilter::default(), limit).await
+    }
+
+    /// Most recent emails matching `filter`, for queries with no free-text part.
+    pub async fn list_emails(
+        &self,
+        filter: &SearchFilter,
+        limit: i64,
+    ) -> Result<Vec<serde_json::Value>> {
+        let mut builder = QueryBuilder::<Sqlite>::new(format!(
+            "SELECT {} FROM emails e LEFT JOIN extracted_email_facts f ON e.id = f.email_id WHERE 1 = 1",
+            EMAIL_WITH_FACTS_COLUMNS
+        ));
+        push_search_filter(&mut builder, filter);
+        builder.push(" ORDER BY e.received_at DESC LIMIT ");
+        builder.push_bind(limit);
+
+        let rows = builder
+            .build()
+            .fetch_all(&self.read_pool)
+            .await
+            .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+
+        Ok(rows.iter().map(email_with_facts_json).collect())
+    }
+
+    /// Inbox emails received since `since` that need a reply or are urgent
+    /// and have none yet, highest priority first, then newest. Projects left
+    /// out of the digest are skipped.
+    pub async fn get_missed_since(&self, since: DateTime<Utc>) -> Result<Vec<serde_json::Value>> {
+        let rows = sqlx::query(&format!(
+            "SELECT {} FROM emails e
+             JOIN extracted_email_facts f ON e.id = f.email_id
+             LEFT JOIN projects p ON p.name = json_extract(f.client_or_project_json, '$.name')
+             WHERE e.folder = 'Inbox'
+               AND e.deleted_at IS NULL
+               AND e.received_at >= ?
+               AND (f.needs_response = 1 OR f.urgency = 'high')
+               AND COALESCE(p.include_in_digest, 1) = 1
+               AND NOT EXISTS (
+                   SELECT 1 FROM emails s
+                   WHERE s.folder = 'Sent Items'
+                     AND s.conversation_id = e.conversation_id
+                     AND julianday(s.sent_at) > julianday(e.received_at)
+               )
+             ORDER BY priority DESC, e.received_at DESC",
+            EMAIL_WITH_FACTS_COLUMNS
+        ))
+        .bind(since)
+        .fetch_all(&self.read_pool)
+        .await
+        .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+
+        Ok(rows.iter().map(email_with_facts_json).collect())
+    }
+
+    /// High-urgency inbox emails received since `since`, newest first.
+    pub async fn get_urgent_since(&self, since: DateTime<Utc>) -> Result<Vec<serde_json::Value>> {
+        let rows = sqlx::query(&format!(
+            "SELECT {} FROM emails e
+             JOIN extracted_email_facts f ON e.id = f.email_id
+             WHERE e.folder = 'Inbox'
+               AND e.deleted_at IS NULL
+               AND e.newsletter = 0
+               AND e.received_at >= ?
+               AND f.urgency = 'high'
+             ORDER BY e.received_at DESC",
+            EMAIL_WITH_FACTS_COLUMNS
+        ))
+        .bind(since)
+        .fetch_all(&self.read_pool)
+        .await
+        .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+
+        Ok(rows.iter().map(email_with_facts_json).collect())
+    }
+
+    /// Emails whose facts, extracted since `since`, name blockers; newest
+    /// first. Extraction can lag behind receipt, so this goes by when the
+    /// facts were extracted.
+    pub async fn get_blockers_since(&self, since: DateTime<Utc>) -> Result<Vec<serde_json::Value>> {
+        let rows = sqlx::query(&format!(
+            "SELECT {} FROM emails e
+             JOIN extracted_email_facts f ON e.id = f.email_id
+             WHERE f.created_at >= ?
+               AND e.deleted_at IS NULL
+               AND json_array_length(f.blockers_json) > 0
+             ORDER BY e.received_at DESC",
+            EMAIL_WITH_FACTS_COLUMNS
+        ))
+        .bind(since)
+        .fetch_all(&self.read_pool)
+        .await
+        .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+
+        Ok(rows.iter().map(email_with_facts_json).collect())
+    }
+
+    /// Emails still needing a reply whose deadline passed in `[start, end)`,
+    /// earliest first.
+    pub async fn get_overdue_between(
+        &self,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<Vec<serde_json::Value>> {
+        let rows = sqlx::query(&format!(
+            "SELECT {} FROM emails e
+             JOIN extracted_email_facts f ON e.id = f.email_id
+             WHERE f.due_by >= ? AND f.due_by < ?
+               AND e.deleted_at IS NULL
+               AND f.needs_response = 1
+               AND NOT EXISTS (
+                   SELECT 1 FROM emails s
+                   WHERE s.folder = 'Sent Items'
+                     AND s.conversation_id = e.conversation_id
+                     AND julianday(s.sent_at) > julianday(e.received_at)
+               )
+             ORDER BY f.due_by ASC",
+            EMAIL_WITH_FACTS_COLUMNS
+        ))
+        .bind(start)
+        .bind(end)
+        .fetch_all(&self.read_pool)
+        .await
+        .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+
+        Ok(rows.iter().map(email_with_facts_json).collect())
+    }
+
+    /// Inbox emails received since `since` that answer the user: in a
+    /// thread where, before them, the user sent questions that were left
+    /// open or an email was waiting on the other side. Newest first.
+    pub async fn get_replies_to_open_questions_since(
+        &self,
+        since: DateTime<Utc>,
+    ) -> Result<Vec<serde_json::Value>> {
+        let rows = sqlx::query(&format!(
+            "SELECT {} FROM emails e
+             LEFT JOIN extracted_email_facts f ON e.id = f.email_id
+             WHERE e.folder = 'Inbox'
+               AND e.deleted_at IS NULL
+               AND e.received_at >= ?
+               AND EXISTS (
+                   SELECT 1 FROM emails q
+                   JOIN extracted_email_facts qf ON q.id = qf.email_id
+                   WHERE q.conversation_id = e.conversation_id
+                     AND julianday(q.received_at) < julianday(e.received_at)
+                     AND (qf.waiting_on = 'them'
+                          OR (q.folder = 'Sent Items'
+                              AND json_array_length(qf.open_questions_json) > 0))
+               )
+             ORDER BY e.received_at DESC",
+            EMAIL_WITH_FACTS_COLUMNS
+        ))
+        .bind(since)
+        .fetch_all(&self.read_pool)
+        .await
+        .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+
+        Ok(rows.iter().map(email_with_facts_json).collect())
+    }
+
+    pub async fn count_inbox_since(&self, since: DateTime<Utc>) -> Result<i64> {
+        sqlx::query_scalar(
+            "SELECT COUNT(*) FROM emails
+             WHERE folder = 'Inbox' AND newsletter = 0 AND received_at >= ? AND deleted_at IS NULL",
+        )
+        .bind(since)
+        .fetch_one(&self.read_pool)
+        .await
+        .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))
+    }
+
+    /// Emails whose extracted deadline falls in `[start, end)`, earliest first.
+    pub async fn get_emails_due_between(
+        &self,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<Vec<serde_json::Value>> {
+        let rows = sqlx::query(&format!(
+            "SELECT {} FROM emails e JOIN extracted_email_facts f ON e.id = f.email_id
+             WHERE f.due_by >= ? AND f.due_by < ? AND e.deleted_at IS NULL ORDER BY f.due_by ASC",
+            EMAIL_WITH_FACTS_COLUMNS
+        ))
+        .bind(start)
+        .bind(end)
+        .fetch_all(&self.read_pool)
+        .await
+        .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+
+        Ok(rows.iter().map(email_with_facts_json).collect())
+    }
+
+    /// Full-text search over subject and body. Each hit carries an FTS5 `snippet`
+    /// with matched terms wrapped in `**` and its bm25 `rank` (lower is better).
+    pub async fn search_keyword(
+        &self,
+        query: &str,
+        filter: &SearchFilter,
+        limit: i64,
+    ) -> Result<Vec<serde_json::Value>> {
+        let match_expr = fts_match_expression(query);
+        if match_expr.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let mut builder = QueryBuilder::<Sqlite>::new(format!(
+            r#"
+            SELECT {},
+                snippet(emails_fts, -1, '**', '**', '…', 16) AS snippet,
+                bm25(emails_fts) AS bm25_rank
+            FROM emails_fts
+            JOIN emails e ON e.id = emails_fts.rowid
+            LEFT JOIN extracted_email_facts f ON e.id = f.email_id
+            WHERE emails_fts MATCH "#,
+            EMAIL_WITH_FACTS_COLUMNS
+        ));
+        builder.push_bind(match_expr);
+        push_search_filter(&mut builder, filter);
+        builder.push(" ORDER BY bm25_rank LIMIT ");
+        builder.push_bind(limit);
+
+        let rows = builder
+            .build()
+            .fetch_all(&self.read_pool)
+            .await
+            .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+
+        Ok(rows
+            .iter()
+            .map(|row| {
+                let mut email = email_with_facts_json(row);
+                email["snippet"] = serde_json::json!(row.get::<String, _>("snippet"));
+                email["rank"] = serde_json::json!(row.get::<f64, _>("bm25_rank"));
+                email
+            })
+            .collect())
+    }
+
+    pub async fn get_entities(&self) -> Result<serde_json::Value> {
+        let nodes_rows = sqlx::query(
+            "SELECT id, canonical_name as name, entity_type as kind FROM entities LIMIT 100",
+        )
+        .fetch_all(&self.read_pool)
+        .await
+        .unwrap_or_else(|_| vec![]);
+
+        let links_rows = sqlx::query("SELECT src_entity_id as source, dst_entity_id as target, edge_type as kind FROM edges LIMIT 200")
+            .fetch_all(&self.read_pool)
+            .await
+            .unwrap_or_else(|_| vec![]);
+
+        // Inferred relations only between nodes in the graph, strongest first.
+        let inferred_rows = sqlx::query(
+            "SELECT src_entity_id as source, dst_entity_id as target, edge_type as kind, confidence, status
+             FROM inferred_relations
+             WHERE status != 'dismissed'
+               AND src_entity_id IN (SELECT id FROM entities LIMIT 100)
+               AND dst_entity_id IN (SELECT id FROM entities LIMIT 100)
+             ORDER BY confidence DESC LIMIT 200",
+        )
+        .fetch_all(&self.read_pool)
+        .await
+        .unwrap_or_else(|_| vec![]);
+
+        let mut links: Vec<serde_json::Value> = links_rows.into_iter().map(|l| serde_json::json!({ "source": l.get::<i64, _>("source").to_string(), "target": l.get::<i64, _>("target").to_string(), "type": l.get::<String, _>("kind") })).collect();
+        links.extend(inferred_rows.into_iter().map(|l| {
+            serde_json::json!({
+                "source": l.get::<i64, _>("source").to_string(),
+                "target": l.get::<i64, _>("target").to_string(),
+                "type": l.get::<String, _>("kind"),
+                "confidence": l.get::<f64, _>("confidence"),
+                "status": l.get::<String, _>("status")
+            })
+        }));
+
+        Ok(serde_json::json!({
+            "nodes": nodes_rows.into_iter().map(|n| serde_json::json!({ "id": n.get::<i64, _>("id").to_string(), "name": n.get::<String, _>("name"), "type": n.get::<String, _>("kind") })).collect::<Vec<_>>(),
+            "links": links
+        }))
+    }
+
+    /// The whole entity graph, or the part matching `filter`: extracted edges
+    /// merged per type, plus inferred relations the user has not dismissed.
+    /// Links are kept only when both ends are in the graph.
+    pub async fn get_graph(&self, filter: &GraphFilter) -> Result<Graph> {
+        // A project's entities are those mentioned in its emails, plus the
+        // people who sent them.
+        let nodes = sqlx::query(
+            "SELECT en.id, en.canonical_name, en.entity_type FROM entities en
+             WHERE (?1 IS NULL OR en.entity_type = ?1)
+               AND (?2 IS NULL
+                    OR en.id IN (
+                        SELECT m.entity_id FROM entity_mentions m
+                        JOIN extracted_email_facts f ON f.email_id = m.email_id
+                        WHERE json_extract(f.client_or_project_json, '$.name') = ?2 COLLATE NOCASE)
+                    OR en.normalized_key IN (
+                        SELECT 'person:' || lower(e.sender) FROM emails e
+                        JOIN extracted_email_facts f ON f.email_id = e.id
+                        WHERE json_extract(f.client_or_project_json, '$.name') = ?2 COLLATE NOCASE))
+             ORDER BY en.id",
+        )
+        .bind(filter.entity_type.as_deref())
+        .bind(filter.project.as_deref())
+        .fetch_all(&self.read_pool)
+        .await
+        .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+        let nodes: Vec<GraphNode> = nodes
+            .iter()
+            .map(|r| GraphNode {
+                id: r.get("id"),
+                name: r.get("canonical_name"),
+                kind: r.get("entity_type"),
+            })
+            .collect();
+        let ids: HashSet<i64> = nodes.iter().map(|n| n.id).collect();
+
+        let edges = sqlx::query(
+            "SELECT src_entity_id, dst_entity_id, edge_type, COUNT(*) AS weight
+             FROM edges GROUP BY src_entity_id, dst_entity_id, edge_type",
+        )
+        .fetch_all(&self.read_pool)
+        .await
+        .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+        let inferred = sqlx::query(
+            "SELECT src_entity_id, dst_entity_id, edge_type, confidence, status
+             FROM inferred_relations WHERE status != 'dismissed'",
+        )
+        .fetch_all(&self.read_pool)
+        .await
+        .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+
+        let links = edges
+            .iter()
+            .map(|r| GraphLink {
+                source: r.get("src_entity_id"),
+                target: r.get("dst_entity_id"),
+                kind: r.get("edge_type"),
+                weight: r.get("weight"),
+                confidence: None,
+                status: None,
+            })
+            .chain(inferred.iter().map(|r| GraphLink {
+                source: r.get("src_entity_id"),
+                target: r.get("dst_entity_id"),
+                kind: r.get("edge_type"),
+                weight: 1,
+                confidence: Some(r.get::<f64, _>("confidence") as f32),
+                status: Some(match r.get::<String, _>("status").as_str() {
+                    "confirmed" => RelationStatus::Confirmed,
+                    _ => RelationStatus::Suggested,
+                }),
+            }))
+            .filter(|l| ids.contains(&l.source) && ids.contains(&l.target))
+            .collect();
+
+        Ok(Graph { nodes, links })
+    }
+
+    /// Sender and recipients of every stored email.
+    pub async fn list_email_participants(&self) -> Result<Vec<EmailParticipants>> {
+        let rows = sqlx::query(r#"SELECT sender, "to", cc FROM emails"#)
+            .fetch_all(&self.read_pool)
+            .await
+            .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+        Ok(rows
+            .iter()
+            .map(|r| EmailParticipants {
+                sender: r.get("sender"),
+                to: r.get("to"),
+                cc: r.get("cc"),
+            })
+            .collect())
+    }
+
+    /// Replaces the inferred relations with `relations`, creating person
+    /// entities as needed. Relations the user confirmed or dismissed keep that
+    /// status; suggestions no longer inferred are dropped.
+    pub async fn save_inferred_relations(&self, relations: &[InferredRelation]) -> Result<()> {
+        let started = Utc::now();
+        let mut tx = self
+            .pool
+            .begin()
+            .await
+            .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+
+        for relation in relations {
+            let src = upsert_person(&mut tx, &relation.source, started).await?;
+            let dst = upsert_person(&mut tx, &relation.target, started).await?;
+            sqlx::query(
+                "INSERT INTO inferred_relations
+                    (src_entity_id, dst_entity_id, edge_type, confidence, evidence, updated_at)
+                 VALUES (?, ?, ?, ?, ?, ?)
+                 ON CONFLICT(src_entity_id, dst_entity_id, edge_type) DO UPDATE SET
+                    confidence = excluded.confidence,
+                    evidence = excluded.evidence,
+                    updated_at = excluded.updated_at",
+            )
+            .bind(src)
+            .bind(dst)
+            .bind(relation.kind.to_string())
+            .bind(relation.confidence)
+            .bind(&relation.evidence)
+            .bind(started)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+        }
+
+        sqlx::query("DELETE FROM inferred_relations WHERE status = 'suggested' AND updated_at < ?")
+            .bind(started)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+
+        tx.commit()
+            .await
+            .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+        Ok(())
+    }
+
+    /// Inferred relations, strongest first.
+    pub async fn list_inferred_relations(&self) -> Result<Vec<InferredRelation>> {
+        let rows = sqlx::query(
+            "SELECT r.id, s.canonical_name AS source, d.canonical_name AS target,
+                    r.edge_type, r.confidence, r.evidence, r.status
+             FROM inferred_relations r
+             JOIN entities s ON s.id = r.src_entity_id
+             JOIN entities d ON d.id = r.dst_entity_id
+             ORDER BY r.confidence DESC, r.id",
+        )
+        .fetch_all(&self.read_pool)
+        .await
+        .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+
+        Ok(rows
+            .iter()
+            .map(|r| InferredRelation {
+                id: r.get("id"),
+                source: r.get("source"),
+                target: r.get("target"),
+                kind: match r.get::<String, _>("edge_type").as_str() {
+                    "same_org" => RelationKind::SameOrg,
+                    _ => RelationKind::FrequentCollaborator,
+                },
+                confidence: r.get::<f64, _>("confidence") as f32,
+                evidence: r.get("evidence"),
+                status: match r.get::<String, _>("status").as_str() {
+                    "confirmed" => RelationStatus::Confirmed,
+                    "dismissed" => RelationStatus::Dismissed,
+                    _ => RelationStatus::Suggested,
+                },
+            })
+            .collect())
+    }
+
+    /// Returns `false` if no relation has this id.
+    pub async fn set_relation_status(&self, id: i64, status: RelationStatus) -> Result<bool> {
+        let result = sqlx::query("UPDATE inferred_relations SET status = ? WHERE id = ?")
+            .bind(status.to_string())
+            .bind(id)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    pub async fn save_log(
+        &self,
+        level: &str,
+        source: &str,
+        message: &str,
+        metadata: Option<serde_json::Value>,
+    ) -> Result<()> {
+        let metadata_str = metadata.map(|m| serde_json::to_string(&m).unwrap());
+        sqlx::query("INSERT INTO logs (timestamp, level, source, message, metadata_json) VALUES (?, ?, ?, ?, ?)")
+            .bind(Utc::now())
+            .bind(level)
+            .bind(source)
+            .bind(message)
+            .bind(metadata_str)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+        Ok(())
+    }
+
+    pub async fn get_logs(&self, limit: i64) -> Result<Vec<serde_json::Value>> {
+        let rows = sqlx::query("SELECT * FROM logs ORDER BY timestamp DESC LIMIT ?")
+            .bind(limit)
+            .fetch_all(&self.read_pool)
+            .await
+            .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+
+        Ok(rows.into_iter().map(|r| serde_json::json!({
+            "id": r.get::<i64, _>("id"),
+            "timestamp": r.get::<DateTime<Utc>, _>("timestamp"),
+            "level": r.get::<String, _>("level"),
+            "source": r.get::<String, _>("source"),
+            "message": r.get::<String, _>("message"),
+            "metadata": r.get::<Option<String>, _>("metadata_json").and_then(|s| serde_json::from_str::<serde_json::Value>(&s).ok())
+        })).collect())
+    }
+
+    pub async fn list_prompts(&self) -> Result<Vec<CustomPrompt>> {
+        let rows = sqlx::query(&format!(
+            "SELECT {} FROM prompts ORDER BY created_at",
+            PROMPT_COLUMNS
+        ))
+        .fetch_all(&self.read_pool)
+        .await
+        .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+
+        Ok(rows.iter().map(prompt_from_row).collect())
+    }
+
+    pub async fn get_prompt(&self, id: &str) -> Result<Option<CustomPrompt>> {
+        let row = sqlx::query(&format!(
+            "SELECT {} FROM prompts WHERE id = ?",
+            PROMPT_COLUMNS
+        ))
+        .bind(id)
+        .fetch_optional(&self.read_pool)
+        .await
+        .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+        Ok(row.as_ref().map(prompt_from_row))
+    }
+
+    /// Stores the prompts of the pack named `pack` in one transaction,
+    /// replacing those of the same name imported from it before. Returns
+    /// each prompt's id and whether it is new; on error nothing is stored.
+    pub async fn import_prompts(
+        &self,
+        pack: &str,
+        prompts: &[PackPrompt],
+    ) -> Result<Vec<(String, bool)>> {
+        let mut tx = self
+            .pool
+            .begin()
+            .await
+            .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+        let mut imported = Vec::with_capacity(prompts.len());
+        for prompt in prompts {
+            let variables_json = serde_json::to_string(&prompt.variables)
+                .map_err(|e| noodle_core::error::NoodleError::Internal(e.to_string()))?;
+            let json_schema = prompt.output_schema.as_ref().map(|s| s.to_string());
+            let now = Utc::now();
+            let existing: Option<String> =
+                sqlx::query_scalar("SELECT id FROM prompts WHERE pack = ? AND name = ?")
+                    .bind(pack)
+                    .bind(&prompt.name)
+                    .fetch_optional(&mut *tx)
+                    .await
+                    .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+
+            if let Some(id) = existing {
+                sqlx::query(
+                    "UPDATE prompts SET kind = ?, description = ?, prompt_template = ?,
+                            variables_json = ?, json_schema = ?, schedule_cron = ?, updated_at = ?
+                     WHERE id = ?",
+                )
+                .bind(prompt.kind.to_string())
+                .bind(prompt.description.as_ref())
+                .bind(&prompt.template)
+                .bind(variables_json)
+                .bind(json_schema)
+                .bind(prompt.schedule_cron.as_ref())
+                .bind(now)
+                .bind(&id)
+                .execute(&mut *tx)
+                .await
+                .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+                imported.push((id, false));
+                continue;
+            }
+
+            let id = uuid::Uuid::new_v4().to_string();
+            sqlx::query(
+                "INSERT INTO prompts
+                    (id, name, kind, description, scope_json, model_pref_json, prompt_template,
+                     variables_json, json_schema, schedule_cron, pack, created_at, updated_at)
+                 VALUES (?, ?, ?, ?, '{}', '{}', ?, ?, ?, ?, ?, ?, ?)",
+            )
+            .bind(&id)
+            .bind(&prompt.name)
+            .bind(prompt.kind.to_string())
+            .bind(prompt.description.as_ref())
+            .bind(&prompt.template)
+            .bind(variables_json)
+            .bind(json_schema)
+            .bind(prompt.schedule_cron.as_ref())
+            .bind(pack)
+            .bind(now)
+            .bind(now)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+            imported.push((id, true));
+        }
+        tx.commit()
+            .await
+            .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+        Ok(imported)
+    }
+
+    /// Stores a new custom prompt and returns its id.
+    pub async fn create_prompt(&self, name: &str, content: &str) -> Result<String> {
+        let id = uuid::Uuid::new_v4().to_string();
+        let now = Utc::now();
+        sqlx::query(
+            "INSERT INTO prompts (id, name, kind, scope_json, model_pref_json, prompt_template, created_at, updated_at)
+             VALUES (?, ?, 'custom', '{}', '{}', ?, ?, ?)",
+        )
+        .bind(&id)
+        .bind(name)
+        .bind(content)
+        .bind(now)
+        .bind(now)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+        Ok(id)
+    }
+
+    /// Returns `false` if no prompt has this id.
+    pub async fn update_prompt(&self, id: &str, name: &str, content: &str) -> Result<bool> {
+        let result = sqlx::query(
+            "UPDATE prompts SET name = ?, prompt_template = ?, updated_at = ? WHERE id = ?",
+        )
+        .bind(name)
+        .bind(content)
+        .bind(Utc::now())
+        .bind(id)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    pub async fn delete_prompt(&self, id: &str) -> Result<bool> {
+        let result = sqlx::query("DELETE FROM prompts WHERE id = ?")
+            .bind(id)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Stores a topic summary and returns its id.
+    pub async fn save_topic_summary(&self, summary: &TopicSummary) -> Result<i64> {
+        let sources = serde_json::to_string(&summary.sources)
+            .map_err(|e| noodle_core::error::NoodleError::Internal(e.to_string()))?;
+        let result = sqlx::query(
+            "INSERT INTO topic_summaries
+                (query, range_start, range_end, run_at, status, output_json, output_text, error_text)
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?)",
+        )
+        .bind(&summary.query)
+        .bind(summary.date_range.start)
+        .bind(summary.date_range.end)
+        .bind(summary.run_at)
+        .bind(&summary.status)
+        .bind(sources)
+        .bind(&summary.output_text)
+        .bind(&summary.error_text)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+        Ok(result.last_insert_rowid())
+    }
+
+    /// The newest topic summaries first.
+    pub async fn list_topic_summaries(&self, limit: i64) -> Result<Vec<TopicSummary>> {
+        let rows = sqlx::query(
+            "SELECT id, query, range_start, range_end, run_at, status, output_json, output_text, error_text
+             FROM topic_summaries ORDER BY run_at DESC LIMIT ?",
+        )
+        .bind(limit)
+        .fetch_all(&self.read_pool)
+        .await
+        .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+        Ok(rows.iter().map(topic_summary_from_row).collect())
+    }
+
+    /// Emails from `sender` received in `[since, until]`.
+    pub async fn count_emails_from(
+        &self,
+        sender: &str,
+        since: DateTime<Utc>,
+        until: DateTime<Utc>,
+    ) -> Result<i64> {
+        sqlx::query_scalar(
+            "SELECT COUNT(*) FROM emails
+             WHERE sender = ? COLLATE NOCASE AND received_at BETWEEN ? AND ?",
+        )
+        .bind(sender)
+        .bind(since)
+        .bind(until)
+        .fetch_one(&self.read_pool)
+        .await
+        .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))
+    }
+
+    /// Whether the user has replied to mail from `sender`: Sent Items has a
+    /// later message in the conversation of one of their emails.
+    pub async fn has_replied_to(&self, sender: &str) -> Result<bool> {
+        sqlx::query_scalar(
+            "SELECT EXISTS(
+                 SELECT 1 FROM emails i
+                 JOIN emails s ON s.conversation_id = i.conversation_id
+                 WHERE i.sender = ? COLLATE NOCASE
+                   AND s.folder = 'Sent Items'
+                   AND julianday(s.sent_at) > julianday(i.received_at)
+             )",
+        )
+        .bind(sender)
+        .fetch_one(&self.read_pool)
+        .await
+        .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))
+    }
+
+    pub async fn set_newsletter(&self, email_id: i64, newsletter: bool) -> Result<()> {
+        sqlx::query("UPDATE emails SET newsletter = ? WHERE id = ?")
+            .bind(newsletter)
+            .bind(email_id)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+        Ok(())
+    }
+
+    pub async fn is_newsletter(&self, email_id: i64) -> Result<bool> {
+        sqlx::query_scalar("SELECT EXISTS(SELECT 1 FROM emails WHERE id = ? AND newsletter = 1)")
+            .bind(email_id)
+            .fetch_one(&self.read_pool)
+            .await
+            .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))
+    }
+
+    /// Newsletters received in `[start, end)`, oldest first, at most `limit`
+    /// of the newest.
+    pub async fn list_newsletters_between(
+        &self,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+        limit: i64,
+    ) -> Result<Vec<EmailRow>> {
+        sqlx::query_as(
+            "SELECT * FROM (
+                SELECT id, subject, sender, received_at, body_text FROM emails
+                WHERE newsletter = 1 AND received_at >= ? AND received_at < ? AND deleted_at IS NULL
+                ORDER BY received_at DESC LIMIT ?
+             ) ORDER BY received_at",
+        )
+        .bind(start)
+        .bind(end)
+        .bind(limit)
+        .fetch_all(&self.read_pool)
+        .await
+        .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))
+    }
+
+    /// Everyone who sent newsletters, most recent first, with the newest
+    /// unsubscribe header each sent.
+    pub async fn list_newsletter_senders(&self) -> Result<Vec<NewsletterSender>> {
+        let rows = sqlx::query(
+            "SELECT sender, COUNT(*) AS issues, MAX(received_at) AS last_received_at,
+                (SELECT n.list_unsubscribe FROM emails n
+                 WHERE n.newsletter = 1 AND n.sender = e.sender AND n.list_unsubscribe IS NOT NULL
+                 ORDER BY n.received_at DESC LIMIT 1) AS list_unsubscribe
+             FROM emails e WHERE newsletter = 1
+             GROUP BY sender ORDER BY last_received_at DESC",
+        )
+        .fetch_all(&self.read_pool)
+        .await
+        .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+
+        Ok(rows
+            .iter()
+            .map(|r| NewsletterSender {
+                sender: r.get("sender"),
+                issues: r.get("issues"),
+                last_received_at: r.get("last_received_at"),
+                list_unsubscribe: r.get("list_unsubscribe"),
+            })
+            .collect())
+    }
+
+    /// Stores a newsletter roundup and returns its id.
+    pub async fn save_newsletter_roundup(&self, roundup: &NewsletterRoundup) -> Result<i64> {
+        let sources = serde_json::to_string(&roundup.sources)
+            .map_err(|e| noodle_core::error::NoodleError::Internal(e.to_string()))?;
+        let result = sqlx::query(
+            "INSERT INTO newsletter_roundups
+                (range_start, range_end, run_at, status, output_json, output_text, error_text)
+             VALUES (?, ?, ?, ?, ?, ?, ?)",
+        )
+        .bind(roundup.date_range.start)
+        .bind(roundup.date_range.end)
+        .bind(roundup.run_at)
+        .bind(&roundup.status)
+        .bind(sources)
+        .bind(&roundup.output_text)
+        .bind(&roundup.error_text)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+        Ok(result.last_insert_rowid())
+    }
+
+    /// Where the newest roundup, or the newest that completed, ended.
+    pub async fn last_newsletter_roundup_end(
+        &self,
+        completed_only: bool,
+    ) -> Result<Option<DateTime<Utc>>> {
+        sqlx::query_scalar(
+            "SELECT MAX(range_end) FROM newsletter_roundups WHERE ? = 0 OR status = 'completed'",
+        )
+        .bind(completed_only)
+        .fetch_one(&self.read_pool)
+        .await
+        .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))
+    }
+
+    /// The newest roundups first.
+    pub async fn list_newsletter_roundups(&self, limit: i64) -> Result<Vec<NewsletterRoundup>> {
+        let rows = sqlx::query(
+            "SELECT id, range_start, range_end, run_at, status, output_json, output_text, error_text
+             FROM newsletter_roundups ORDER BY run_at DESC LIMIT ?",
+        )
+        .bind(limit)
+        .fetch_all(&self.read_pool)
+        .await
+        .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+        Ok(rows
+            .iter()
+            .map(|row| NewsletterRoundup {
+                id: row.get("id"),
+                date_range: DateRange {
+                    start: row.get("range_start"),
+                    end: row.get("range_end"),
+                },
+                run_at: row.get("run_at"),
+                status: row.get("status"),
+                output_text: row.get("output_text"),
+                sources: row
+                    .get::<Option<String>, _>("output_json")
+                    .and_then(|s| serde_json::from_str(&s).ok())
+                    .unwrap_or_default(),
+                error_text: row.get("error_text"),
+            })
+            .collect())
+    }
+
+    pub async fn delete_topic_summary(&self, id: i64) -> Result<bool> {
+        let result = sqlx::query("DELETE FROM topic_summaries WHERE id = ?")
+            .bind(id)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Starts an `ask_noodle` conversation titled `title` and returns its id.
+    pub async fn create_chat_session(&self, title: &str) -> Result<i64> {
+        let title: String = title.trim().chars().take(MAX_CHAT_TITLE_CHARS).collect();
+        let now = Utc::now();
+        let result = sqlx::query(
+            "INSERT INTO chat_sessions (title, created_at, updated_at) VALUES (?, ?, ?)",
+        )
+        .bind(title)
+        .bind(now)
+        .bind(now)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+        Ok(result.last_insert_rowid())
+    }
+
+    /// Appends a message to a session and marks the session as updated.
+    pub async fn add_chat_message(
+        &self,
+        session_id: i64,
+        role: &str,
+        content: &str,
+        sources: &[TopicSource],
+        trace: &[ChatToolCall],
+        unsupported: &[UnsupportedCitation],
+    ) -> Result<ChatMessage> {
+        let sources_json = serde_json::to_string(sources)
+            .map_err(|e| noodle_core::error::NoodleError::Internal(e.to_string()))?;
+        let trace_json = serde_json::to_string(trace)
+            .map_err(|e| noodle_core::error::NoodleError::Internal(e.to_string()))?;
+        let unsupported_json = serde_json::to_string(unsupported)
+            .map_err(|e| noodle_core::error::NoodleError::Internal(e.to_string()))?;
+        let now = Utc::now();
+        let mut tx = self
+            .pool
+            .begin()
+            .await
+            .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+        let updated = sqlx::query("UPDATE chat_sessions SET updated_at = ? WHERE id = ?")
+            .bind(now)
+            .bind(session_id)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+        if updated.rows_affected() == 0 {
+            return Err(noodle_core::error::NoodleError::Validation(
+                "Chat session not found".into(),
+            ));
+        }
+        let result = sqlx::query(
+            "INSERT INTO chat_messages
+                 (session_id, role, content, sources_json, trace_json, unsupported_json, created_at)
+             VALUES (?, ?, ?, ?, ?, ?, ?)",
+        )
+        .bind(session_id)
+        .bind(role)
+        .bind(content)
+        .bind(sources_json)
+        .bind(trace_json)
+        .bind(unsupported_json)
+        .bind(now)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+        tx.commit()
+            .await
+            .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+        Ok(ChatMessage {
+            id: result.last_insert_rowid(),
+            session_id,
+            role: role.to_string(),
+            content: content.to_string(),
+            sources: sources.to_vec(),
+            trace: trace.to_vec(),
+            unsupported: unsupported.to_vec(),
+            created_at: now,
+        })
+    }
+
+    /// The most recently active sessions first.
+    pub async fn list_chat_sessions(&self, limit: i64) -> Result<Vec<ChatSession>> {
+        let rows = sqlx::query(
+            "SELECT s.id, s.title, s.created_at, s.updated_at,
+                    (SELECT COUNT(*) FROM chat_messages m WHERE m.session_id = s.id) AS message_count
+             FROM chat_sessions s ORDER BY s.updated_at DESC, s.id DESC LIMIT ?",
+        )
+        .bind(limit)
+        .fetch_all(&self.read_pool)
+        .await
+        .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+        Ok(rows
+            .iter()
+            .map(|r| ChatSession {
+                id: r.get("id"),
+                title: r.get("title"),
+                created_at: r.get("created_at"),
+                updated_at: r.get("updated_at"),
+                message_count: r.get("message_count"),
+            })
+            .collect())
+    }
+
+    /// A session's messages, oldest first.
+    pub async fn get_chat_messages(&self, session_id: i64) -> Result<Vec<ChatMessage>> {
+        let rows = sqlx::query(
+            "SELECT id, session_id, role, content, sources_json, trace_json, unsupported_json,
+                    created_at
+             FROM chat_messages WHERE session_id = ? ORDER BY id",
+        )
+        .bind(session_id)
+        .fetch_all(&self.read_pool)
+        .await
+        .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+        Ok(rows
+            .iter()
+            .map(|r| ChatMessage {
+                id: r.get("id"),
+                session_id: r.get("session_id"),
+                role: r.get("role"),
+                content: r.get("content"),
+                sources: serde_json::from_str(&r.get::<String, _>("sources_json"))
+                    .unwrap_or_default(),
+                trace: serde_json::from_str(&r.get::<String, _>("trace_json")).unwrap_or_default(),
+                unsupported: serde_json::from_str(&r.get::<String, _>("unsupported_json"))
+                    .unwrap_or_default(),
+                created_at: r.get("created_at"),
+            })
+            .collect())
+    }
+
+    pub async fn delete_chat_session(&self, id: i64) -> Result<bool> {
+        let result = sqlx::query("DELETE FROM chat_sessions WHERE id = ?")
+            .bind(id)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Up to `limit` extracted emails picked at random, for an extraction
+    /// experiment to run over.
+    pub async fn sample_extracted_email_ids(&self, limit: i64) -> Result<Vec<i64>> {
+        sqlx::query_scalar(
+            "SELECT e.id FROM emails e JOIN extracted_email_facts f ON f.email_id = e.id
+             WHERE e.deleted_at IS NULL AND e.excluded_reason IS NULL
+             ORDER BY RANDOM() LIMIT ?",
+        )
+        .bind(limit)
+        .fetch_all(&self.read_pool)
+        .await
+        .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))
+    }
+
+    /// Stores a finished extraction experiment.
+    pub async fn save_experiment(
+        &self,
+        name: &str,
+        variant_a: &ExperimentVariant,
+        variant_b: &ExperimentVariant,
+        emails: &[ExperimentEmail],
+        comparison: &ExperimentComparison,
+    ) -> Result<ExtractionExperiment> {
+        let to_json = |value: serde_json::Result<String>| {
+            value.map_err(|e| noodle_core::error::NoodleError::Internal(e.to_string()))
+        };
+        let now = Utc::now();
+        let result = sqlx::query(
+            "INSERT INTO extraction_experiments
+                 (name, variant_a_json, variant_b_json, emails_json, comparison_json, created_at)
+             VALUES (?, ?, ?, ?, ?, ?)",
+        )
+        .bind(name)
+        .bind(to_json(serde_json::to_string(variant_a))?)
+        .bind(to_json(serde_json::to_string(variant_b))?)
+        .bind(to_json(serde_json::to_string(emails))?)
+        .bind(to_json(serde_json::to_string(comparison))?)
+        .bind(now)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+        Ok(ExtractionExperiment {
+            id: result.last_insert_rowid(),
+            name: name.to_string(),
+            variant_a: variant_a.clone(),
+            variant_b: variant_b.clone(),
+            emails: emails.to_vec(),
+            comparison: comparison.clone(),
+            created_at: now,
+        })
+    }
+
+    /// The newest experiments first, without their per-email results.
+    pub async fn list_experiments(&self, limit: i64) -> Result<Vec<ExtractionExperiment>> {
+        let rows = sqlx::query(
+            "SELECT id, name, variant_a_json, variant_b_json, '[]' AS emails_json,
+                    comparison_json, created_at
+             FROM extraction_experiments ORDER BY created_at DESC, id DESC LIMIT ?",
+        )
+        .bind(limit)
+        .fetch_all(&self.read_pool)
+        .await
+        .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+        Ok(rows.iter().map(experiment_from_row).collect())
+    }
+
+    pub async fn get_experiment(&self, id: i64) -> Result<Option<ExtractionExperiment>> {
+        let row = sqlx::query(
+            "SELECT id, name, variant_a_json, variant_b_json, emails_json, comparison_json,
+                    created_at
+             FROM extraction_experiments WHERE id = ?",
+        )
+        .bind(id)
+        .fetch_optional(&self.read_pool)
+        .await
+        .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+        Ok(row.as_ref().map(experiment_from_row))
+    }
+
+    pub async fn delete_experiment(&self, id: i64) -> Result<bool> {
+        let result = sqlx::query("DELETE FROM extraction_experiments WHERE id = ?")
+            .bind(id)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Records a search the user ran, keeping the newest `keep` entries.
+    pub async fn record_search(&self, query: &str, result_count: i64, keep: i64) -> Result<()> {
+        sqlx::query(
+            "INSERT INTO search_history (query, result_count, searched_at) VALUES (?, ?, ?)",
+        )
+        .bind(query.trim())
+        .bind(result_count)
+        .bind(Utc::now())
+        .execute(&self.pool)
+        .await
+        .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+        sqlx::query(
+            "DELETE FROM search_history WHERE id NOT IN
+                (SELECT id FROM search_history ORDER BY id DESC LIMIT ?)",
+        )
+        .bind(keep)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+        Ok(())
+    }
+
+    /// Past searches, newest first.
+    pub async fn get_search_history(&self, limit: i64) -> Result<Vec<SearchHistoryEntry>> {
+        let rows = sqlx::query(
+            "SELECT id, query, result_count, searched_at FROM search_history
+             ORDER BY id DESC LIMIT ?",
+        )
+        .bind(limit)
+        .fetch_all(&self.read_pool)
+        .await
+        .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+        Ok(rows
+            .iter()
+            .map(|r| SearchHistoryEntry {
+                id: r.get("id"),
+                query: r.get("query"),
+                result_count: r.get("result_count"),
+                searched_at: r.get("searched_at"),
+            })
+            .collect())
+    }
+
+    pub async fn clear_search_history(&self) -> Result<u64> {
+        let result = sqlx::query("DELETE FROM search_history")
+            .execute(&self.pool)
+            .await
+            .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+        Ok(result.rows_affected())
+    }
+
+    /// Past queries starting with `prefix`, most frequent first and then
+    /// most recent. An empty prefix matches every query. Case is ignored for
+    /// ASCII letters only, as SQLite's `lower()` folds no others.
+    pub async fn get_search_suggestions(
+        &self,
+        prefix: &str,
+        limit: i64,
+    ) -> Result<Vec<SearchSuggestion>> {
+        let prefix = prefix.trim_start().to_ascii_lowercase();
+        let rows = sqlx::query(
+            "SELECT query, COUNT(*) AS count, MAX(searched_at) AS last_searched_at
+             FROM search_history
+             WHERE substr(lower(query), 1, length(?1)) = ?1
+             GROUP BY lower(query)
+             ORDER BY count DESC, last_searched_at DESC
+             LIMIT ?2",
+        )
+        .bind(&prefix)
+        .bind(limit)
+        .fetch_all(&self.read_pool)
+        .await
+        .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+        Ok(rows
+            .iter()
+            .map(|r| SearchSuggestion {
+                query: r.get("query"),
+                count: r.get("count"),
+                last_searched_at: r.get("last_searched_at"),
+            })
+            .collect())
+    }
+
+    /// Cached re-ranking for `cache_key`, most relevant email first.
+    pub async fn get_rerank(&self, cache_key: &str) -> Result<Option<Vec<i64>>> {
+        let row = sqlx::query("SELECT ranked_ids FROM rerank_cache WHERE cache_key = ?")
+            .bind(cache_key)
+            .fetch_optional(&self.read_pool)
+            .await
+            .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+        Ok(row.and_then(|r| serde_json::from_str(&r.get::<String, _>("ranked_ids")).ok()))
+    }
+
+    /// Caches a re-ranking and drops entries older than `max_age_days`.
+    pub async fn save_rerank(
+        &self,
+        cache_key: &str,
+        ranked_ids: &[i64],
+        max_age_days: i64,
+    ) -> Result<()> {
+        let now = Utc::now();
+        let ids = serde_json::to_string(ranked_ids)
+            .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+        sqlx::query(
+            "INSERT INTO rerank_cache (cache_key, ranked_ids, created_at) VALUES (?, ?, ?)
+             ON CONFLICT(cache_key) DO UPDATE SET
+                ranked_ids = excluded.ranked_ids,
+                created_at = excluded.created_at",
+        )
+        .bind(cache_key)
+        .bind(ids)
+        .bind(now)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+        sqlx::query("DELETE FROM rerank_cache WHERE created_at < ?")
+            .bind(now - chrono::Duration::days(max_age_days))
+            .execute(&self.pool)
+            .await
+            .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+        Ok(())
+    }
+
+    /// Secrets, see [`Config::is_secret`], are encrypted with DPAPI before
+    /// they are written, here and in [`Self::set_config_bulk`].
+    pub async fn set_config(&self, key: &str, value: &str) -> Result<()> {
+        let value = seal_if_secret(key, value)?;
+        sqlx::query("INSERT INTO app_config (key, value, updated_at) VALUES (?, ?, ?) ON CONFLICT(key) DO UPDATE SET value = excluded.value, updated_at = excluded.updated_at")
+            .bind(key)
+            .bind(value)
+            .bind(Utc::now())
+            .execute(&self.pool)
+            .await
+            .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+        Ok(())
+    }
+
+    pub async fn get_config(&self, key: &str) -> Result<Option<String>> {
+        let row = sqlx::query("SELECT value FROM app_config WHERE key = ?")
+            .bind(key)
+            .fetch_optional(&self.read_pool)
+            .await
+            .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+
+        row.map(|r| secrets::open(&r.get::<String, _>("value")))
+            .transpose()
+    }
+
+    /// All settings, with defaults filled in for unset or unreadable keys.
+    pub async fn get_all_config(&self) -> Result<Config> {
+        let rows = sqlx::query("SELECT key, value FROM app_config")
+            .fetch_all(&self.read_pool)
+            .await
+            .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+        let entries: Vec<(String, String)> = rows
+            .iter()
+            .filter_map(|r| {
+                let key: String = r.get("key");
+                match secrets::open(&r.get::<String, _>("value")) {
+                    Ok(value) => Some((key, value)),
+                    Err(e) => {
+                        warn!("Ignoring setting {}: {}", key, e);
+                        None
+                    }
+                }
+            })
+            .collect();
+        Ok(Config::from_entries(
+            entries.iter().map(|(k, v)| (k.as_str(), v.as_str())),
+        ))
+    }
+
+    /// Writes several keys in one transaction, so a partial save never lands.
+    pub async fn set_config_bulk(&self, entries: &[(String, String)]) -> Result<()> {
+        let mut tx = self
+            .pool
+            .begin()
+            .await
+            .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+        for (key, value) in entries {
+            sqlx::query(
+                "INSERT INTO app_config (key, value, updated_at) VALUES (?, ?, ?)
+                 ON CONFLICT(key) DO UPDATE SET value = excluded.value, updated_at = excluded.updated_at",
+            )
+            .bind(key)
+            .bind(seal_if_secret(key, value)?)
+            .bind(Utc::now())
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+        }
+        tx.commit()
+            .await
+            .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))
+    }
+
+    /// Encrypts secrets saved in plaintext by builds before encryption.
+    async fn seal_plaintext_secrets(&self) -> Result<()> {
+        if !secrets::AVAILABLE {
+            return Ok(());
+        }
+        let rows = sqlx::query("SELECT key, value FROM app_config WHERE value != ''")
             .fetch_all(&self.pool)
             .await
-            .unwrap_or_else(|_| vec![]);
+            .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+        let plaintext: Vec<(String, String)> = rows
+            .iter()
+            .map(|r| (r.get::<String, _>("key"), r.get::<String, _>("value")))
+            .filter(|(key, value)| Config::is_secret(key) && !secrets::is_sealed(value))
+            .collect();
+        if plaintext.is_empty() {
+            return Ok(());
+        }
+        self.set_config_bulk(&plaintext).await?;
+        info!("Encrypted {} stored secret(s)", plaintext.len());
+        Ok(())
+    }
+
+    /// The configured display timezone, falling back to the system timezone.
+    pub async fn get_user_timezone(&self) -> Result<UserTimezone> {
+        let value = self.get_config(TIMEZONE_CONFIG_KEY).await?;
+        Ok(UserTimezone::from_config(value.as_deref()))
+    }
+
+    /// The configured locale for generated text and displayed dates.
+    pub async fn get_user_locale(&self) -> Result<UserLocale> {
+        let value = self.get_config(LOCALE_CONFIG_KEY).await?;
+        Ok(UserLocale::from_config(value.as_deref()))
+    }
+
+    pub async fn list_vip_senders(&self) -> Result<Vec<String>> {
+        sqlx::query_scalar("SELECT sender FROM vip_senders ORDER BY sender")
+            .fetch_all(&self.read_pool)
+            .await
+            .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))
+    }
+
+    pub async fn is_vip_sender(&self, sender: &str) -> Result<bool> {
+        sqlx::query_scalar("SELECT EXISTS(SELECT 1 FROM vip_senders WHERE sender = ?)")
+            .bind(sender.trim().to_lowercase())
+            .fetch_one(&self.read_pool)
+            .await
+            .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))
+    }
+
+    pub async fn add_vip_sender(&self, sender: &str) -> Result<()> {
+        sqlx::query("INSERT OR IGNORE INTO vip_senders (sender, added_at) VALUES (?, ?)")
+            .bind(sender.trim().to_lowercase())
+            .bind(Utc::now())
+            .execute(&self.pool)
+            .await
+            .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+        Ok(())
+    }
+
+    pub async fn remove_vip_sender(&self, sender: &str) -> Result<bool> {
+        let result = sqlx::query("DELETE FROM vip_senders WHERE sender = ?")
+            .bind(sender.trim().to_lowercase())
+            .execute(&self.pool)
+            .await
+            .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Senders whose Inbox mail since `since` the user replied to at least
+    /// `min_replied` times, most replied first. An Inbox email counts as
+    /// replied when Sent Items has a later message in its conversation.
+    /// Existing VIPs are left out.
+    pub async fn suggest_vip_senders(
+        &self,
+        since: DateTime<Utc>,
+        min_replied: i64,
+        limit: i64,
+    ) -> Result<Vec<VipSuggestion>> {
+        let rows = sqlx::query(
+            "SELECT LOWER(i.sender) AS sender,
+                    COUNT(*) AS received,
+                    SUM(EXISTS(
+                        SELECT 1 FROM emails s
+                        WHERE s.folder = 'Sent Items'
+                          AND s.conversation_id = i.conversation_id
+                          AND julianday(s.sent_at) > julianday(i.received_at)
+                    )) AS replied
+             FROM emails i
+             WHERE i.folder = 'Inbox'
+               AND i.received_at >= ?
+               AND i.sender != ''
+               AND LOWER(i.sender) NOT IN (SELECT LOWER(sender) FROM vip_senders)
+             GROUP BY LOWER(i.sender)
+             HAVING replied >= ?
+             ORDER BY replied DESC, received DESC
+             LIMIT ?",
+        )
+        .bind(since)
+        .bind(min_replied)
+        .bind(limit)
+        .fetch_all(&self.read_pool)
+        .await
+        .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+
+        Ok(rows
+            .iter()
+            .map(|r| VipSuggestion {
+                sender: r.get("sender"),
+                replied: r.get("replied"),
+                received: r.get("received"),
+            })
+            .collect())
+    }
+
+    /// Stored settings for `project`, or the defaults when none were saved.
+    pub async fn get_project_settings(&self, project: &str) -> Result<ProjectSettings> {
+        let row = sqlx::query(
+            "SELECT name, mute_notifications, extraction_enabled, include_in_digest, retention_days
+             FROM projects WHERE name = ?",
+        )
+        .bind(project)
+        .fetch_optional(&self.read_pool)
+        .await
+        .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+
+        Ok(row
+            .map(|r| project_settings_from_row(&r))
+            .unwrap_or_else(|| ProjectSettings::new(project)))
+    }
+
+    /// Every project emails are filed under, the one with the latest mail
+    /// first.
+    pub async fn list_project_names(&self) -> Result<Vec<String>> {
+        sqlx::query_scalar(
+            "SELECT json_extract(f.client_or_project_json, '$.name') AS project
+             FROM emails e JOIN extracted_email_facts f ON f.email_id = e.id
+             WHERE e.deleted_at IS NULL
+               AND COALESCE(json_extract(f.client_or_project_json, '$.name'), '') NOT IN ('', 'Unknown')
+             GROUP BY project COLLATE NOCASE
+             ORDER BY MAX(e.received_at) DESC",
+        )
+        .fetch_all(&self.read_pool)
+        .await
+        .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))
+    }
+
+    pub async fn list_project_settings(&self) -> Result<Vec<ProjectSettings>> {
+        let rows = sqlx::query(
+            "SELECT name, mute_notifications, extraction_enabled, include_in_digest, retention_days
+             FROM projects ORDER BY name",
+        )
+        .fetch_all(&self.read_pool)
+        .await
+        .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+
+        Ok(rows.iter().map(project_settings_from_row).collect())
+    }
+
+    pub async fn set_project_settings(&self, settings: &ProjectSettings) -> Result<()> {
+        sqlx::query(
+            "INSERT INTO projects (name, mute_notifications, extraction_enabled, include_in_digest, retention_days, updated_at)
+             VALUES (?, ?, ?, ?, ?, ?)
+             ON CONFLICT(name) DO UPDATE SET
+                mute_notifications = excluded.mute_notifications,
+                extraction_enabled = excluded.extraction_enabled,
+                include_in_digest = excluded.include_in_digest,
+                retention_days = excluded.retention_days,
+                updated_at = excluded.updated_at",
+        )
+        .bind(&settings.name)
+        .bind(settings.mute_notifications)
+        .bind(settings.extraction_enabled)
+        .bind(settings.include_in_digest)
+        .bind(settings.retention_days)
+        .bind(Utc::now())
+        .execute(&self.pool)
+        .await
+        .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+        Ok(())
+    }
+
+    pub async fn list_settings_profiles(&self) -> Result<Vec<SettingsProfile>> {
+        let rows = sqlx::query(
+            "SELECT name, settings_json, updated_at FROM settings_profiles ORDER BY name",
+        )
+        .fetch_all(&self.read_pool)
+        .await
+        .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+        Ok(rows.iter().map(settings_profile_from_row).collect())
+    }
+
+    pub async fn get_settings_profile(&self, name: &str) -> Result<Option<SettingsProfile>> {
+        let row = sqlx::query(
+            "SELECT name, settings_json, updated_at FROM settings_profiles WHERE name = ?",
+        )
+        .bind(name)
+        .fetch_optional(&self.read_pool)
+        .await
+        .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+        Ok(row.as_ref().map(settings_profile_from_row))
+    }
+
+    /// Creates the profile, or replaces the settings of an existing one.
+    pub async fn save_settings_profile(&self, profile: &SettingsProfile) -> Result<()> {
+        let json = serde_json::to_string(&profile.settings)
+            .map_err(|e| noodle_core::error::NoodleError::Internal(e.to_string()))?;
+        sqlx::query(
+            "INSERT INTO settings_profiles (name, settings_json, created_at, updated_at)
+             VALUES (?, ?, ?, ?)
+             ON CONFLICT(name) DO UPDATE SET
+                settings_json = excluded.settings_json,
+                updated_at = excluded.updated_at",
+        )
+        .bind(&profile.name)
+        .bind(json)
+        .bind(profile.updated_at)
+        .bind(profile.updated_at)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+        Ok(())
+    }
+
+    pub async fn delete_settings_profile(&self, name: &str) -> Result<bool> {
+        let result = sqlx::query("DELETE FROM settings_profiles WHERE name = ?")
+            .bind(name)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    pub async fn get_scan_checkpoints(&self) -> Result<Vec<ScanCheckpoint>> {
+        let rows = sqlx::query(
+            "SELECT folder, last_received_at, processed, total FROM scan_checkpoints ORDER BY folder",
+        )
+        .fetch_all(&self.read_pool)
+        .await
+        .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+
+        Ok(rows
+            .iter()
+            .map(|r| ScanCheckpoint {
+                folder: r.get("folder"),
+                last_received_at: r.get("last_received_at"),
+                processed: r.get("processed"),
+                total: r.get("total"),
+            })
+            .collect())
+    }
+
+    pub async fn get_scan_checkpoint(&self, folder: &str) -> Result<Option<ScanCheckpoint>> {
+        let row = sqlx::query(
+            "SELECT folder, last_received_at, processed, total FROM scan_checkpoints WHERE folder = ?",
+        )
+        .bind(folder)
+        .fetch_optional(&self.read_pool)
+        .await
+        .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+
+        Ok(row.map(|r| ScanCheckpoint {
+            folder: r.get("folder"),
+            last_received_at: r.get("last_received_at"),
+            processed: r.get("processed"),
+            total: r.get("total"),
+        }))
+    }
+
+    pub async fn save_scan_checkpoint(&self, checkpoint: &ScanCheckpoint) -> Result<()> {
+        sqlx::query(
+            "INSERT INTO scan_checkpoints (folder, last_received_at, processed, total, updated_at)
+             VALUES (?, ?, ?, ?, ?)
+             ON CONFLICT(folder) DO UPDATE SET
+                last_received_at = excluded.last_received_at,
+                processed = excluded.processed,
+                total = excluded.total,
+                updated_at = excluded.updated_at",
+        )
+        .bind(&checkpoint.folder)
+        .bind(checkpoint.last_received_at)
+        .bind(checkpoint.processed)
+        .bind(checkpoint.total)
+        .bind(Utc::now())
+        .execute(&self.pool)
+        .await
+        .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+        Ok(())
+    }
+
+    pub async fn clear_scan_checkpoints(&self) -> Result<()> {
+        sqlx::query("DELETE FROM scan_checkpoints")
+            .execute(&self.pool)
+            .await
+            .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+        Ok(())
+    }
+
+    /// Extracted emails of `project` received before `until`, oldest first.
+    pub async fn get_project_email_facts(
+        &self,
+        project: &str,
+        until: Option<DateTime<Utc>>,
+    ) -> Result<Vec<ProjectEmailFacts>> {
+        let rows = sqlx::query(&format!(
+            "SELECT {}
+             FROM emails e JOIN extracted_email_facts f ON e.id = f.email_id
+             WHERE json_extract(f.client_or_project_json, '$.name') = ? COLLATE NOCASE
+               AND (? IS NULL OR e.received_at < ?)
+               AND e.deleted_at IS NULL
+             ORDER BY e.received_at, e.id",
+            PROJECT_EMAIL_FACTS_COLUMNS
+        ))
+        .bind(project)
+        .bind(until)
+        .bind(until)
+        .fetch_all(&self.read_pool)
+        .await
+        .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+
+        Ok(rows.iter().map(project_email_facts_from_row).collect())
+    }
+
+    /// The extracted emails of the conversation `email_id` belongs to,
+    /// oldest first; just the email itself when it has no conversation.
+    pub async fn get_thread_email_facts(&self, email_id: i64) -> Result<Vec<ProjectEmailFacts>> {
+        let rows = sqlx::query(&format!(
+            "SELECT {}
+             FROM emails e JOIN extracted_email_facts f ON e.id = f.email_id
+             WHERE e.id = ?
+                OR e.conversation_id = (SELECT conversation_id FROM emails WHERE id = ?)
+             ORDER BY e.received_at, e.id",
+            PROJECT_EMAIL_FACTS_COLUMNS
+        ))
+        .bind(email_id)
+        .bind(email_id)
+        .fetch_all(&self.read_pool)
+        .await
+        .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+
+        Ok(rows.iter().map(project_email_facts_from_row).collect())
+    }
+
+    /// Extracted emails whose risks, issues and blockers are not grouped
+    /// yet, oldest first.
+    pub async fn list_unclustered_issue_facts(&self, limit: i64) -> Result<Vec<IssueFacts>> {
+        let rows = sqlx::query(
+            "SELECT e.id, e.received_at, json_extract(f.client_or_project_json, '$.name') AS project,
+                    f.risks_json, f.issues_json, f.blockers_json
+             FROM emails e JOIN extracted_email_facts f ON e.id = f.email_id
+             WHERE f.issues_clustered = 0
+             ORDER BY e.received_at, e.id
+             LIMIT ?",
+        )
+        .bind(limit)
+        .fetch_all(&self.read_pool)
+        .await
+        .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+
+        Ok(rows.iter().map(issue_facts_from_row).collect())
+    }
+
+    /// An extracted email's risks, issues and blockers.
+    pub async fn get_issue_facts(&self, email_id: i64) -> Result<Option<IssueFacts>> {
+        let row = sqlx::query(
+            "SELECT e.id, e.received_at, json_extract(f.client_or_project_json, '$.name') AS project,
+                    f.risks_json, f.issues_json, f.blockers_json
+             FROM emails e JOIN extracted_email_facts f ON e.id = f.email_id
+             WHERE e.id = ?",
+        )
+        .bind(email_id)
+        .fetch_optional(&self.read_pool)
+        .await
+        .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+        Ok(row.as_ref().map(issue_facts_from_row))
+    }
+
+    /// The ticket filed in `tracker` for an email's risk, issue or blocker,
+    /// found by the hash of the item or, for tickets filed before items were
+    /// hashed, by its kind and position.
+    pub async fn get_issue_ticket(
+        &self,
+        email_id: i64,
+        kind: IssueKind,
+        position: usize,
+        item_hash: &str,
+        tracker: TicketTracker,
+    ) -> Result<Option<IssueTicket>> {
+        let row = sqlx::query(
+            "SELECT id, email_id, kind, position, tracker, ticket_key, url, created_at
+             FROM issue_tickets
+             WHERE email_id = ? AND tracker = ? AND item_hash IN (?, ?)
+               AND ticket_key IS NOT NULL",
+        )
+        .bind(email_id)
+        .bind(tracker.to_string())
+        .bind(item_hash)
+        .bind(format!("{}:{}", kind, position))
+        .fetch_optional(&self.read_pool)
+        .await
+        .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+        Ok(row.as_ref().and_then(issue_ticket_from_row))
+    }
+
+    /// Claims filing an item in `tracker` before the tracker is called, so
+    /// two requests can't both file it. Returns the reservation's id, or
+    /// `None` when the item is filed or being filed already. Reservations
+    /// older than `stale_after` were left by a run that died, and are taken
+    /// over.
+    pub async fn reserve_issue_ticket(
+        &self,
+        email_id: i64,
+        kind: IssueKind,
+        position: usize,
+        item_hash: &str,
+        tracker: TicketTracker,
+        stale_after: chrono::Duration,
+    ) -> Result<Option<i64>> {
+        let mut tx = self
+            .pool
+            .begin()
+            .await
+            .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+        let now = Utc::now();
+        sqlx::query(
+            "DELETE FROM issue_tickets
+             WHERE email_id = ? AND item_hash = ? AND tracker = ?
+               AND ticket_key IS NULL AND created_at < ?",
+        )
+        .bind(email_id)
+        .bind(item_hash)
+        .bind(tracker.to_string())
+        .bind(now - stale_after)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+        let inserted = sqlx::query(
+            "INSERT OR IGNORE INTO issue_tickets
+                (email_id, kind, position, item_hash, tracker, created_at)
+             VALUES (?, ?, ?, ?, ?, ?)",
+        )
+        .bind(email_id)
+        .bind(kind.to_string())
+        .bind(position as i64)
+        .bind(item_hash)
+        .bind(tracker.to_string())
+        .bind(now)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+        tx.commit()
+            .await
+            .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+        Ok((inserted.rows_affected() == 1).then(|| inserted.last_insert_rowid()))
+    }
+
+    /// Turns a reservation into the ticket the tracker created.
+    pub async fn complete_issue_ticket(
+        &self,
+        reservation: i64,
+        ticket_key: &str,
+        url: &str,
+    ) -> Result<IssueTicket> {
+        let row = sqlx::query(
+            "UPDATE issue_tickets SET ticket_key = ?, url = ?, created_at = ? WHERE id = ?
+             RETURNING id, email_id, kind, position, tracker, ticket_key, url, created_at",
+        )
+        .bind(ticket_key)
+        .bind(url)
+        .bind(Utc::now())
+        .bind(reservation)
+        .fetch_one(&self.pool)
+        .await
+        .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+        issue_ticket_from_row(&row).ok_or_else(|| {
+            noodle_core::error::NoodleError::Storage("Invalid issue ticket row".into())
+        })
+    }
+
+    /// Drops a reservation whose ticket could not be filed.
+    pub async fn release_issue_ticket(&self, reservation: i64) -> Result<()> {
+        sqlx::query("DELETE FROM issue_tickets WHERE id = ? AND ticket_key IS NULL")
+            .bind(reservation)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+        Ok(())
+    }
+
+    /// Every filed ticket, newest first.
+    pub async fn list_issue_tickets(&self) -> Result<Vec<IssueTicket>> {
+        let rows = sqlx::query(
+            "SELECT id, email_id, kind, position, tracker, ticket_key, url, created_at
+             FROM issue_tickets WHERE ticket_key IS NOT NULL
+             ORDER BY created_at DESC, id DESC",
+        )
+        .fetch_all(&self.read_pool)
+        .await
+        .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+        Ok(rows.iter().filter_map(issue_ticket_from_row).collect())
+    }
+
+    pub async fn list_issue_centroids(&self) -> Result<Vec<IssueCentroid>> {
+        let rows = sqlx::query("SELECT id, centroid_json, mention_count FROM issue_clusters")
+            .fetch_all(&self.read_pool)
+            .await
+            .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+
+        Ok(rows
+            .iter()
+            .map(|r| IssueCentroid {
+                id: r.get("id"),
+                centroid: serde_json::from_str(&r.get::<String, _>("centroid_json"))
+                    .unwrap_or_default(),
+                mentions: r.get("mention_count"),
+            })
+            .collect())
+    }
+
+    /// The clusters an email's mentions are in, with each mention's
+    /// embedding; `None` for mentions grouped before embeddings were kept.
+    pub async fn list_issue_mention_embeddings(
+        &self,
+        email_id: i64,
+    ) -> Result<Vec<(i64, Option<Vec<f32>>)>> {
+        let rows =
+            sqlx::query("SELECT cluster_id, embedding_json FROM issue_mentions WHERE email_id = ?")
+                .bind(email_id)
+                .fetch_all(&self.read_pool)
+                .await
+                .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+        Ok(rows
+            .iter()
+            .map(|r| {
+                let embedding = r
+                    .get::<Option<String>, _>("embedding_json")
+                    .and_then(|json| serde_json::from_str(&json).ok());
+                (r.get("cluster_id"), embedding)
+            })
+            .collect())
+    }
+
+    /// Forgets an email's mentions before it is grouped again, saving the
+    /// means and counts of the `clusters` they leave.
+    pub async fn clear_issue_mentions(
+        &self,
+        email_id: i64,
+        clusters: &[IssueCentroid],
+    ) -> Result<()> {
+        let mut tx = self
+            .pool
+            .begin()
+            .await
+            .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+        sqlx::query("DELETE FROM issue_mentions WHERE email_id = ?")
+            .bind(email_id)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+        for cluster in clusters {
+            sqlx::query(
+                "UPDATE issue_clusters SET centroid_json = ?, mention_count = ? WHERE id = ?",
+            )
+            .bind(serde_json::to_string(&cluster.centroid).unwrap_or_default())
+            .bind(cluster.mentions)
+            .bind(cluster.id)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+        }
+        tx.commit()
+            .await
+            .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))
+    }
+
+    /// Starts a cluster from its first mention and returns its id.
+    pub async fn create_issue_cluster(
+        &self,
+        mention: &IssueMention,
+        embedding: &[f32],
+    ) -> Result<i64> {
+        let mut tx = self
+            .pool
+            .begin()
+            .await
+            .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+        let id = sqlx::query(
+            "INSERT INTO issue_clusters
+                (kind, title, details, severity, centroid_json, mention_count, first_seen, last_seen)
+             VALUES (?, ?, ?, ?, ?, 0, ?, ?)",
+        )
+        .bind(mention.kind.to_string())
+        .bind(&mention.title)
+        .bind(&mention.details)
+        .bind(mention.severity.to_string())
+        .bind(serde_json::to_string(embedding).unwrap_or_default())
+        .bind(mention.received_at)
+        .bind(mention.received_at)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?
+        .last_insert_rowid();
+        insert_issue_mention(&mut tx, id, mention, embedding, embedding).await?;
+        tx.commit()
+            .await
+            .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+        Ok(id)
+    }
+
+    /// Adds a mention embedded as `embedding` to a cluster, which takes
+    /// `centroid` as its new mean.
+    pub async fn add_issue_mention(
+        &self,
+        cluster_id: i64,
+        mention: &IssueMention,
+        embedding: &[f32],
+        centroid: &[f32],
+    ) -> Result<()> {
+        let mut tx = self
+            .pool
+            .begin()
+            .await
+            .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+        insert_issue_mention(&mut tx, cluster_id, mention, embedding, centroid).await?;
+        tx.commit()
+            .await
+            .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))
+    }
+
+    pub async fn mark_issues_clustered(&self, email_id: i64) -> Result<()> {
+        sqlx::query("UPDATE extracted_email_facts SET issues_clustered = 1 WHERE email_id = ?")
+            .bind(email_id)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+        Ok(())
+    }
+
+    /// Drops clusters whose mentions were all re-extracted away or deleted.
+    pub async fn delete_empty_issue_clusters(&self) -> Result<u64> {
+        let result = sqlx::query(
+            "DELETE FROM issue_clusters
+             WHERE NOT EXISTS (SELECT 1 FROM issue_mentions m WHERE m.cluster_id = issue_clusters.id)",
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+        Ok(result.rows_affected())
+    }
+
+    /// Grouped emails that commit to or resolve something and have not been
+    /// matched against earlier issues yet, oldest first.
+    pub async fn list_untracked_issue_updates(&self, limit: i64) -> Result<Vec<IssueUpdateFacts>> {
+        let rows = sqlx::query(
+            "SELECT e.id, e.received_at, json_extract(f.client_or_project_json, '$.name') AS project,
+                    f.intent, f.summary, f.key_points_json
+             FROM emails e JOIN extracted_email_facts f ON e.id = f.email_id
+             WHERE f.issues_tracked = 0 AND f.issues_clustered = 1
+               AND f.intent IN ('commit', 'resolve')
+             ORDER BY e.received_at, e.id
+             LIMIT ?",
+        )
+        .bind(limit)
+        .fetch_all(&self.read_pool)
+        .await
+        .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+
+        Ok(rows
+            .iter()
+            .map(|r| IssueUpdateFacts {
+                email_id: r.get("id"),
+                received_at: r.get("received_at"),
+                project: r.get("project"),
+                intent: r.get("intent"),
+                summary: r.get("summary"),
+                key_points: serde_json::from_str(&r.get::<String, _>("key_points_json"))
+                    .unwrap_or_default(),
+            })
+            .collect())
+    }
+
+    /// Centroids of the clusters mentioned before `before`, in `project`
+    /// when one is given.
+    pub async fn list_issue_centroids_before(
+        &self,
+        before: DateTime<Utc>,
+        project: Option<&str>,
+    ) -> Result<Vec<IssueCentroid>> {
+        let rows = sqlx::query(
+            "SELECT c.id, c.centroid_json, c.mention_count FROM issue_clusters c
+             WHERE EXISTS (
+                SELECT 1 FROM issue_mentions m
+                WHERE m.cluster_id = c.id AND m.received_at < ?
+                  AND (? IS NULL OR m.project = ? COLLATE NOCASE))",
+        )
+        .bind(before)
+        .bind(project)
+        .bind(project)
+        .fetch_all(&self.read_pool)
+        .await
+        .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+
+        Ok(rows
+            .iter()
+            .map(|r| IssueCentroid {
+                id: r.get("id"),
+                centroid: serde_json::from_str(&r.get::<String, _>("centroid_json"))
+                    .unwrap_or_default(),
+                mentions: r.get("mention_count"),
+            })
+            .collect())
+    }
+
+    /// Records that an email reports the clusters `cluster_ids` as
+    /// `status`, replacing what it was matched to before.
+    pub async fn save_issue_updates(
+        &self,
+        email_id: i64,
+        received_at: DateTime<Utc>,
+        status: IssueStatus,
+        cluster_ids: &[i64],
+    ) -> Result<()> {
+        let mut tx = self
+            .pool
+            .begin()
+            .await
+            .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+        sqlx::query("DELETE FROM issue_updates WHERE email_id = ?")
+            .bind(email_id)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+        for cluster_id in cluster_ids {
+            sqlx::query(
+                "INSERT INTO issue_updates (email_id, cluster_id, status, received_at)
+                 VALUES (?, ?, ?, ?)",
+            )
+            .bind(email_id)
+            .bind(cluster_id)
+            .bind(status.to_string())
+            .bind(received_at)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+        }
+        sqlx::query("UPDATE extracted_email_facts SET issues_tracked = 1 WHERE email_id = ?")
+            .bind(email_id)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+        tx.commit()
+            .await
+            .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))
+    }
+
+    /// Works out every cluster's status from its mentions and updates: it
+    /// is resolved by a resolution at or after its last mention, mitigated
+    /// by a commitment then or by a last mention below its peak severity,
+    /// and open otherwise, so a new mention reopens it. Returns how many
+    /// clusters changed status.
+    pub async fn refresh_issue_statuses(&self) -> Result<u64> {
+        let mut tx = self
+            .pool
+            .begin()
+            .await
+            .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+        // Mentions of re-extracted emails may be gone.
+        sqlx::query(
+            "UPDATE issue_clusters SET
+                mention_count = (SELECT COUNT(*) FROM issue_mentions m WHERE m.cluster_id = issue_clusters.id),
+                first_seen = COALESCE((SELECT MIN(m.received_at) FROM issue_mentions m WHERE m.cluster_id = issue_clusters.id), first_seen),
+                last_seen = COALESCE((SELECT MAX(m.received_at) FROM issue_mentions m WHERE m.cluster_id = issue_clusters.id), last_seen)",
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+        sqlx::query(&format!(
+            "UPDATE issue_clusters SET
+                resolved_at = (
+                    SELECT MIN(u.received_at) FROM issue_updates u
+                    WHERE u.cluster_id = issue_clusters.id AND u.status = 'resolved'
+                      AND u.received_at >= issue_clusters.last_seen),
+                mitigated_at = COALESCE(
+                    (SELECT MIN(u.received_at) FROM issue_updates u
+                     WHERE u.cluster_id = issue_clusters.id AND u.status = 'mitigated'
+                       AND u.received_at >= issue_clusters.last_seen),
+                    CASE WHEN (
+                        SELECT MAX({}) FROM issue_mentions m
+                        WHERE m.cluster_id = issue_clusters.id AND m.received_at = issue_clusters.last_seen
+                    ) < {} THEN issue_clusters.last_seen END)",
+            severity_rank("m.severity"),
+            severity_rank("issue_clusters.severity"),
+        ))
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+        let status = "CASE WHEN resolved_at IS NOT NULL THEN 'resolved'
+                           WHEN mitigated_at IS NOT NULL THEN 'mitigated'
+                           ELSE 'open' END";
+        let changed = sqlx::query(&format!(
+            "UPDATE issue_clusters SET status = {} WHERE status != {}",
+            status, status
+        ))
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?
+        .rows_affected();
+        tx.commit()
+            .await
+            .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+        Ok(changed)
+    }
+
+    /// Issue counts and open durations per project, projects with the most
+    /// unresolved issues first.
+    pub async fn get_issue_metrics(&self) -> Result<Vec<ProjectIssueMetrics>> {
+        let rows = sqlx::query(
+            "SELECT DISTINCT m.project, c.id, c.status, c.first_seen, c.resolved_at
+             FROM issue_clusters c JOIN issue_mentions m ON m.cluster_id = c.id
+             WHERE m.project IS NOT NULL AND m.project != ''",
+        )
+        .fetch_all(&self.read_pool)
+        .await
+        .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+
+        let now = Utc::now();
+        let days = |from: DateTime<Utc>, to: DateTime<Utc>| {
+            (to - from).num_seconds().max(0) as f64 / 86_400.0
+        };
+        let mut by_project: HashMap<String, (ProjectIssueMetrics, Vec<f64>)> = HashMap::new();
+        let mut counted = HashSet::new();
+        for r in &rows {
+            let project: String = r.get("project");
+            if !counted.insert((project.to_lowercase(), r.get::<i64, _>("id"))) {
+                continue;
+            }
+            let (metrics, resolve_days) =
+                by_project.entry(project.to_lowercase()).or_insert_with(|| {
+                    (
+                        ProjectIssueMetrics {
+                            project: project.clone(),
+                            open: 0,
+                            mitigated: 0,
+                            resolved: 0,
+                            mean_days_to_resolve: None,
+                            oldest_open_days: None,
+                        },
+                        Vec::new(),
+                    )
+                });
+            let first_seen: DateTime<Utc> = r.get("first_seen");
+            let resolved_at: Option<DateTime<Utc>> = r.get("resolved_at");
+            match r.get::<String, _>("status").parse() {
+                Ok(IssueStatus::Resolved) => {
+                    metrics.resolved += 1;
+                    resolve_days.push(days(first_seen, resolved_at.unwrap_or(now)));
+                    continue;
+                }
+                Ok(IssueStatus::Mitigated) => metrics.mitigated += 1,
+                _ => metrics.open += 1,
+            }
+            let open_days = days(first_seen, now);
+            if metrics.oldest_open_days.is_none_or(|d| d < open_days) {
+                metrics.oldest_open_days = Some(open_days);
+            }
+        }
+
+        let mut metrics: Vec<ProjectIssueMetrics> = by_project
+            .into_values()
+            .map(|(mut metrics, resolve_days)| {
+                if !resolve_days.is_empty() {
+                    metrics.mean_days_to_resolve =
+                        Some(resolve_days.iter().sum::<f64>() / resolve_days.len() as f64);
+                }
+                metrics
+            })
+            .collect();
+        metrics.sort_by(|a, b| {
+            (b.open + b.mitigated)
+                .cmp(&(a.open + a.mitigated))
+                .then_with(|| a.project.cmp(&b.project))
+        });
+        Ok(metrics)
+    }
+
+    /// Issue clusters with at least `min_mentions` mentions, most recently
+    /// seen first.
+    pub async fn list_issue_clusters(&self, min_mentions: i64) -> Result<Vec<IssueCluster>> {
+        let clusters = sqlx::query(
+            "SELECT c.id, c.kind, c.title, c.details, c.severity, c.status, c.mitigated_at,
+                    c.resolved_at, MIN(m.received_at) AS first_seen, MAX(m.received_at) AS last_seen
+             FROM issue_clusters c JOIN issue_mentions m ON m.cluster_id = c.id
+             GROUP BY c.id
+             HAVING COUNT(*) >= ?
+             ORDER BY last_seen DESC",
+        )
+        .bind(min_mentions)
+        .fetch_all(&self.read_pool)
+        .await
+        .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+        let mentions = sqlx::query(
+            "SELECT cluster_id, email_id, kind, position, title, details, severity, project, received_at
+             FROM issue_mentions ORDER BY received_at, email_id",
+        )
+        .fetch_all(&self.read_pool)
+        .await
+        .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+
+        let mut by_cluster: HashMap<i64, Vec<IssueMention>> = HashMap::new();
+        for r in &mentions {
+            let (Ok(kind), Ok(severity)) = (
+                r.get::<String, _>("kind").parse(),
+                r.get::<String, _>("severity").parse(),
+            ) else {
+                continue;
+            };
+            by_cluster
+                .entry(r.get("cluster_id"))
+                .or_default()
+                .push(IssueMention {
+                    email_id: r.get("email_id"),
+                    kind,
+                    position: r.get::<i64, _>("position") as usize,
+                    title: r.get("title"),
+                    details: r.get("details"),
+                    severity,
+                    project: r.get("project"),
+                    received_at: r.get("received_at"),
+                });
+        }
+
+        Ok(clusters
+            .iter()
+            .filter_map(|r| {
+                let id: i64 = r.get("id");
+                let mentions = by_cluster.remove(&id).unwrap_or_default();
+                let mut projects: Vec<String> = Vec::new();
+                for project in mentions.iter().filter_map(|m| m.project.clone()) {
+                    if !projects.iter().any(|p| p.eq_ignore_ascii_case(&project)) {
+                        projects.push(project);
+                    }
+                }
+                Some(IssueCluster {
+                    id,
+                    kind: r.get::<String, _>("kind").parse().ok()?,
+                    title: r.get("title"),
+                    details: r.get("details"),
+                    severity: r.get::<String, _>("severity").parse().ok()?,
+                    status: r.get::<String, _>("status").parse().ok()?,
+                    projects,
+                    first_seen: r.get("first_seen"),
+                    last_seen: r.get("last_seen"),
+                    mitigated_at: r.get("mitigated_at"),
+                    resolved_at: r.get("resolved_at"),
+                    mentions,
+                })
+            })
+            .collect())
+    }
+
+    /// Extracted emails from `sender` with `sentiment`, received since `since`.
+    pub async fn count_sender_sentiment_since(
+        &self,
+        sender: &str,
+        sentiment: &str,
+        since: DateTime<Utc>,
+    ) -> Result<i64> {
+        sqlx::query_scalar(
+            "SELECT COUNT(*) FROM emails e JOIN extracted_email_facts f ON e.id = f.email_id
+             WHERE e.sender = ? AND f.sentiment = ? AND e.received_at >= ?",
+        )
+        .bind(sender)
+        .bind(sentiment)
+        .bind(since)
+        .fetch_one(&self.read_pool)
+        .await
+        .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))
+    }
+
+    /// Extracted emails of `project` with `urgency`, received since `since`.
+    pub async fn count_project_urgency_since(
+        &self,
+        project: &str,
+        urgency: &str,
+        since: DateTime<Utc>,
+    ) -> Result<i64> {
+        sqlx::query_scalar(
+            "SELECT COUNT(*) FROM emails e JOIN extracted_email_facts f ON e.id = f.email_id
+             WHERE json_extract(f.client_or_project_json, '$.name') = ? COLLATE NOCASE
+               AND f.urgency = ? AND e.received_at >= ?",
+        )
+        .bind(project)
+        .bind(urgency)
+        .bind(since)
+        .fetch_one(&self.read_pool)
+        .await
+        .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))
+    }
+
+    /// Confidence of the last `limit` extractions, newest first.
+    pub async fn list_recent_confidences(&self, limit: i64) -> Result<Vec<f64>> {
+        sqlx::query_scalar(
+            "SELECT confidence FROM extracted_email_facts ORDER BY created_at DESC LIMIT ?",
+        )
+        .bind(limit)
+        .fetch_all(&self.read_pool)
+        .await
+        .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))
+    }
+
+    pub async fn save_alert(
+        &self,
+        kind: AlertKind,
+        key: &str,
+        message: &str,
+        email_id: Option<i64>,
+    ) -> Result<Alert> {
+        let created_at = Utc::now();
+        let id = sqlx::query(
+            "INSERT INTO alerts (kind, alert_key, message, email_id, created_at)
+             VALUES (?, ?, ?, ?, ?)",
+        )
+        .bind(kind.to_string())
+        .bind(key)
+        .bind(message)
+        .bind(email_id)
+        .bind(created_at)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?
+        .last_insert_rowid();
+        Ok(Alert {
+            id,
+            kind,
+            key: key.to_string(),
+            message: message.to_string(),
+            email_id,
+            created_at,
+            acknowledged: false,
+        })
+    }
+
+    /// When an alert of `kind` about `key` was last raised.
+    pub async fn last_alert_at(&self, kind: AlertKind, key: &str) -> Result<Option<DateTime<Utc>>> {
+        sqlx::query_scalar("SELECT MAX(created_at) FROM alerts WHERE kind = ? AND alert_key = ?")
+            .bind(kind.to_string())
+            .bind(key)
+            .fetch_one(&self.read_pool)
+            .await
+            .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))
+    }
+
+    /// The newest alerts, unacknowledged ones first.
+    pub async fn list_alerts(&self, limit: i64) -> Result<Vec<Alert>> {
+        let rows = sqlx::query(
+            "SELECT id, kind, alert_key, message, email_id, created_at, acknowledged
+             FROM alerts ORDER BY acknowledged, created_at DESC LIMIT ?",
+        )
+        .bind(limit)
+        .fetch_all(&self.read_pool)
+        .await
+        .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+
+        Ok(rows
+            .iter()
+            .filter_map(|r| {
+                Some(Alert {
+                    id: r.get("id"),
+                    kind: r.get::<String, _>("kind").parse().ok()?,
+                    key: r.get("alert_key"),
+                    message: r.get("message"),
+                    email_id: r.get("email_id"),
+                    created_at: r.get("created_at"),
+                    acknowledged: r.get("acknowledged"),
+                })
+            })
+            .collect())
+    }
+
+    pub async fn acknowledge_alert(&self, id: i64) -> Result<bool> {
+        let result = sqlx::query("UPDATE alerts SET acknowledged = 1 WHERE id = ?")
+            .bind(id)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    pub async fn list_category_rules(&self) -> Result<Vec<CategoryRule>> {
+        let rows = sqlx::query(
+            "SELECT id, match_on, pattern, category, color, write_back, created_at
+             FROM category_rules ORDER BY id",
+        )
+        .fetch_all(&self.read_pool)
+        .await
+        .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+
+        Ok(rows
+            .iter()
+            .filter_map(|r| {
+                Some(CategoryRule {
+                    id: r.get("id"),
+                    match_on: r.get::<String, _>("match_on").parse().ok()?,
+                    pattern: r.get("pattern"),
+                    category: r.get("category"),
+                    color: r.get("color"),
+                    write_back: r.get("write_back"),
+                    created_at: r.get("created_at"),
+                })
+            })
+            .collect())
+    }
+
+    pub async fn add_category_rule(
+        &self,
+        match_on: CategoryMatch,
+        pattern: &str,
+        category: &str,
+        color: i32,
+        write_back: bool,
+    ) -> Result<CategoryRule> {
+        let mut rule = CategoryRule {
+            id: 0,
+            match_on,
+            pattern: pattern.trim().to_string(),
+            category: category.trim().to_string(),
+            color,
+            write_back,
+            created_at: Utc::now(),
+        };
+        validate_category_rule(&rule)?;
+
+        let mut tx = self
+            .pool
+            .begin()
+            .await
+            .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+        rule.id = sqlx::query(
+            "INSERT INTO category_rules (match_on, pattern, category, color, write_back, created_at)
+             VALUES (?, ?, ?, ?, ?, ?)",
+        )
+        .bind(rule.match_on.to_string())
+        .bind(&rule.pattern)
+        .bind(&rule.category)
+        .bind(rule.color)
+        .bind(rule.write_back)
+        .bind(rule.created_at)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?
+        .last_insert_rowid();
+        insert_category_audit(
+            &mut tx,
+            CategoryAction::RuleAdded,
+            Some(rule.id),
+            None,
+            &rule.category,
+            Some(&describe_category_rule(&rule)),
+        )
+        .await?;
+        tx.commit()
+            .await
+            .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+        Ok(rule)
+    }
+
+    /// Saves everything but `created_at`. Emails keep the categories they
+    /// were given until they are extracted again.
+    pub async fn update_category_rule(&self, rule: &CategoryRule) -> Result<bool> {
+        let rule = CategoryRule {
+            pattern: rule.pattern.trim().to_string(),
+            category: rule.category.trim().to_string(),
+            ..rule.clone()
+        };
+        validate_category_rule(&rule)?;
+
+        let mut tx = self
+            .pool
+            .begin()
+            .await
+            .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+        let result = sqlx::query(
+            "UPDATE category_rules
+             SET match_on = ?, pattern = ?, category = ?, color = ?, write_back = ?
+             WHERE id = ?",
+        )
+        .bind(rule.match_on.to_string())
+        .bind(&rule.pattern)
+        .bind(&rule.category)
+        .bind(rule.color)
+        .bind(rule.write_back)
+        .bind(rule.id)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+        if result.rows_affected() == 0 {
+            return Ok(false);
+        }
+        insert_category_audit(
+            &mut tx,
+            CategoryAction::RuleUpdated,
+            Some(rule.id),
+            None,
+            &rule.category,
+            Some(&describe_category_rule(&rule)),
+        )
+        .await?;
+        tx.commit()
+            .await
+            .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+        Ok(true)
+    }
+
+    /// Removes the rule. Categories it gave emails, in Noodle or Outlook,
+    /// stay.
+    pub async fn remove_category_rule(&self, id: i64) -> Result<bool> {
+        let mut tx = self
+            .pool
+            .begin()
+            .await
+            .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+        let row = sqlx::query(
+            "DELETE FROM category_rules WHERE id = ?
+             RETURNING id, match_on, pattern, category, color, write_back, created_at",
+        )
+        .bind(id)
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+        let Some(row) = row else {
+            return Ok(false);
+        };
+        let category: String = row.get("category");
+        let detail = format!(
+            "{} = {}",
+            row.get::<String, _>("match_on"),
+            row.get::<String, _>("pattern")
+        );
+        insert_category_audit(
+            &mut tx,
+            CategoryAction::RuleRemoved,
+            None,
+            None,
+            &category,
+            Some(&detail),
+        )
+        .await?;
+        tx.commit()
+            .await
+            .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+        Ok(true)
+    }
+
+    /// Replaces the categories rules gave an email with `labels`, pairs of
+    /// rule id and category, and audits the ones it didn't have yet. Returns
+    /// those new categories.
+    pub async fn replace_email_categories(
+        &self,
+        email_id: i64,
+        labels: &[(i64, String)],
+    ) -> Result<Vec<String>> {
+        let mut tx = self
+            .pool
+            .begin()
+            .await
+            .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+        let previous: Vec<String> =
+            sqlx::query_scalar("SELECT category FROM email_categories WHERE email_id = ?")
+                .bind(email_id)
+                .fetch_all(&mut *tx)
+                .await
+                .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+        sqlx::query("DELETE FROM email_categories WHERE email_id = ?")
+            .bind(email_id)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+
+        let now = Utc::now();
+        let mut added = Vec::new();
+        for (rule_id, category) in labels {
+            let inserted = sqlx::query(
+                "INSERT OR IGNORE INTO email_categories (email_id, category, rule_id, labelled_at)
+                 VALUES (?, ?, ?, ?)",
+            )
+            .bind(email_id)
+            .bind(category)
+            .bind(rule_id)
+            .bind(now)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?
+            .rows_affected()
+                > 0;
+            if inserted && !previous.iter().any(|p| p.eq_ignore_ascii_case(category)) {
+                insert_category_audit(
+                    &mut tx,
+                    CategoryAction::Labelled,
+                    Some(*rule_id),
+                    Some(email_id),
+                    category,
+                    None,
+                )
+                .await?;
+                added.push(category.clone());
+            }
+        }
+        tx.commit()
+            .await
+            .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+        Ok(added)
+    }
+
+    pub async fn record_category_audit(
+        &self,
+        action: CategoryAction,
+        rule_id: Option<i64>,
+        email_id: Option<i64>,
+        category: &str,
+        detail: Option<&str>,
+    ) -> Result<()> {
+        let mut tx = self
+            .pool
+            .begin()
+            .await
+            .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+        insert_category_audit(&mut tx, action, rule_id, email_id, category, detail).await?;
+        tx.commit()
+            .await
+            .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))
+    }
 
-        Ok(serde_json::json!({
-            "nodes": nodes_rows.into_iter().map(|n| serde_json::json!({ "id": n.get::<i64, _>("id").to_string(), "name": n.get::<String, _>("name"), "type": n.get::<String, _>("kind") })).collect::<Vec<_>>(),
-            "links": links_rows.into_iter().map(|l| serde_json::json!({ "source": l.get::<i64, _>("source").to_string(), "target": l.get::<i64, _>("target").to_string(), "type": l.get::<String, _>("kind") })).collect::<Vec<_>>()
-        }))
+    /// The newest entries of the category audit log.
+    pub async fn list_category_audit(&self, limit: i64) -> Result<Vec<CategoryAuditEntry>> {
+        let rows = sqlx::query(
+            "SELECT id, action, rule_id, email_id, category, detail, created_at
+             FROM category_audit ORDER BY created_at DESC, id DESC LIMIT ?",
+        )
+        .bind(limit)
+        .fetch_all(&self.read_pool)
+        .await
+        .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+
+        Ok(rows
+            .iter()
+            .filter_map(|r| {
+                Some(CategoryAuditEntry {
+                    id: r.get("id"),
+                    action: r.get::<String, _>("action").parse().ok()?,
+                    rule_id: r.get("rule_id"),
+                    email_id: r.get("email_id"),
+                    category: r.get("category"),
+                    detail: r.get("detail"),
+                    created_at: r.get("created_at"),
+                })
+            })
+            .collect())
     }
 
-    pub async fn save_log(
+    /// Stores a meeting read from the calendar, updating the occurrence if
+    /// it was stored before. Follow-ups already extracted are kept.
+    pub async fn upsert_meeting(&self, meeting: &Meeting) -> Result<i64> {
+        let attendees = serde_json::to_string(&meeting.attendees)
+            .map_err(|e| noodle_core::error::NoodleError::Internal(e.to_string()))?;
+        sqlx::query_scalar(
+            "INSERT INTO meetings (global_id, subject, organizer, attendees_json, start_at, end_at)
+             VALUES (?, ?, ?, ?, ?, ?)
+             ON CONFLICT(global_id, start_at) DO UPDATE SET
+                subject = excluded.subject,
+                organizer = excluded.organizer,
+                attendees_json = excluded.attendees_json,
+                end_at = excluded.end_at
+             RETURNING id",
+        )
+        .bind(&meeting.global_id)
+        .bind(&meeting.subject)
+        .bind(&meeting.organizer)
+        .bind(attendees)
+        .bind(meeting.start_at)
+        .bind(meeting.end_at)
+        .fetch_one(&self.pool)
+        .await
+        .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))
+    }
+
+    pub async fn get_meeting(&self, id: i64) -> Result<Option<Meeting>> {
+        let row = sqlx::query("SELECT * FROM meetings WHERE id = ?")
+            .bind(id)
+            .fetch_optional(&self.read_pool)
+            .await
+            .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+        Ok(row.as_ref().map(meeting_from_row))
+    }
+
+    /// Meetings that ended in the range and have no follow-ups yet, oldest
+    /// first.
+    pub async fn list_meetings_awaiting_follow_up(
         &self,
-        level: &str,
-        source: &str,
-        message: &str,
-        metadata: Option<serde_json::Value>,
+        ended_after: DateTime<Utc>,
+        ended_before: DateTime<Utc>,
+    ) -> Result<Vec<Meeting>> {
+        let rows = sqlx::query(
+            "SELECT * FROM meetings
+             WHERE followed_up_at IS NULL AND end_at >= ? AND end_at <= ?
+             ORDER BY end_at",
+        )
+        .bind(ended_after)
+        .bind(ended_before)
+        .fetch_all(&self.read_pool)
+        .await
+        .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+        Ok(rows.iter().map(meeting_from_row).collect())
+    }
+
+    /// Emails received in the range that could be about a meeting: neither
+    /// newsletters nor excluded from processing. The newest `limit` first.
+    pub async fn list_meeting_candidates(
+        &self,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+        limit: i64,
+    ) -> Result<Vec<MeetingCandidate>> {
+        sqlx::query_as(
+            "SELECT id, subject, sender, \"to\", cc, received_at, body_text FROM emails
+             WHERE received_at >= ? AND received_at <= ?
+                AND newsletter = 0 AND excluded_reason IS NULL
+             ORDER BY received_at DESC LIMIT ?",
+        )
+        .bind(start)
+        .bind(end)
+        .bind(limit)
+        .fetch_all(&self.read_pool)
+        .await
+        .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))
+    }
+
+    /// Replaces a meeting's related emails and follow-ups with those of a
+    /// new extraction, and marks it followed up. Tasks the user already
+    /// ticked off stay done when extracted again, even reworded slightly;
+    /// see [`meeting_task_key`].
+    pub async fn save_meeting_follow_up(
+        &self,
+        meeting_id: i64,
+        email_ids: &[i64],
+        tasks: &[MeetingTask],
     ) -> Result<()> {
-        let metadata_str = metadata.map(|m| serde_json::to_string(&m).unwrap());
-        sqlx::query("INSERT INTO logs (timestamp, level, source, message, metadata_json) VALUES (?, ?, ?, ?, ?)")
-            .bind(Utc::now())
-            .bind(level)
-            .bind(source)
-            .bind(message)
-            .bind(metadata_str)
+        let mut tx = self
+            .pool
+            .begin()
+            .await
+            .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+        let done: HashSet<String> = sqlx::query_as::<_, (String, String)>(
+            "SELECT kind, text FROM meeting_tasks WHERE meeting_id = ? AND done = 1",
+        )
+        .bind(meeting_id)
+        .fetch_all(&mut *tx)
+        .await
+        .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?
+        .iter()
+        .map(|(kind, text)| meeting_task_key(kind, text))
+        .collect();
+        for table in ["meeting_emails", "meeting_tasks"] {
+            sqlx::query(&format!("DELETE FROM {} WHERE meeting_id = ?", table))
+                .bind(meeting_id)
+                .execute(&mut *tx)
+                .await
+                .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+        }
+
+        for email_id in email_ids {
+            sqlx::query(
+                "INSERT OR IGNORE INTO meeting_emails (meeting_id, email_id) VALUES (?, ?)",
+            )
+            .bind(meeting_id)
+            .bind(email_id)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+        }
+        let now = Utc::now();
+        for task in tasks {
+            sqlx::query(
+                "INSERT INTO meeting_tasks
+                    (meeting_id, kind, text, owner, due_by, email_id, done, created_at)
+                 VALUES (?, ?, ?, ?, ?, ?, ?, ?)",
+            )
+            .bind(meeting_id)
+            .bind(task.kind.to_string())
+            .bind(&task.text)
+            .bind(&task.owner)
+            .bind(task.due_by)
+            .bind(task.email_id)
+            .bind(task.done || done.contains(&meeting_task_key(&task.kind.to_string(), &task.text)))
+            .bind(now)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+        }
+
+        sqlx::query("UPDATE meetings SET followed_up_at = ?, follow_up_error = NULL WHERE id = ?")
+            .bind(now)
+            .bind(meeting_id)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+        tx.commit()
+            .await
+            .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))
+    }
+
+    /// Notes why a meeting's follow-up extraction failed. The meeting stays
+    /// awaiting follow-up, so it is tried again.
+    pub async fn record_meeting_follow_up_error(&self, meeting_id: i64, error: &str) -> Result<()> {
+        sqlx::query("UPDATE meetings SET follow_up_error = ? WHERE id = ?")
+            .bind(error)
+            .bind(meeting_id)
             .execute(&self.pool)
             .await
             .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
         Ok(())
     }
 
-    pub async fn get_logs(&self, limit: i64) -> Result<Vec<serde_json::Value>> {
-        let rows = sqlx::query("SELECT * FROM logs ORDER BY timestamp DESC LIMIT ?")
-            .bind(limit)
-            .fetch_all(&self.pool)
+    /// Meetings that ended at or after `since`, the latest first, with their
+    /// related emails and follow-ups.
+    pub async fn list_meeting_follow_ups(
+        &self,
+        since: DateTime<Utc>,
+        limit: i64,
+    ) -> Result<Vec<MeetingFollowUp>> {
+        let rows =
+            sqlx::query("SELECT * FROM meetings WHERE end_at >= ? ORDER BY end_at DESC LIMIT ?")
+                .bind(since)
+                .bind(limit)
+                .fetch_all(&self.read_pool)
+                .await
+                .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+
+        let mut follow_ups = Vec::with_capacity(rows.len());
+        for row in &rows {
+            let meeting = meeting_from_row(row);
+            let email_ids = sqlx::query_scalar(
+                "SELECT m.email_id FROM meeting_emails m JOIN emails e ON e.id = m.email_id
+                 WHERE m.meeting_id = ? ORDER BY e.received_at",
+            )
+            .bind(meeting.id)
+            .fetch_all(&self.read_pool)
             .await
             .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+            let tasks = sqlx::query("SELECT * FROM meeting_tasks WHERE meeting_id = ? ORDER BY id")
+                .bind(meeting.id)
+                .fetch_all(&self.read_pool)
+                .await
+                .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?
+                .iter()
+                .filter_map(|r| {
+                    Some(MeetingTask {
+                        id: r.get("id"),
+                        meeting_id: r.get("meeting_id"),
+                        kind: r.get::<String, _>("kind").parse().ok()?,
+                        text: r.get("text"),
+                        owner: r.get("owner"),
+                        due_by: r.get("due_by"),
+                        email_id: r.get("email_id"),
+                        done: r.get("done"),
+                        created_at: r.get("created_at"),
+                    })
+                })
+                .collect();
+            follow_ups.push(MeetingFollowUp {
+                meeting,
+                email_ids,
+                tasks,
+            });
+        }
+        Ok(follow_ups)
+    }
 
-        Ok(rows.into_iter().map(|r| serde_json::json!({
-            "id": r.get::<i64, _>("id"),
-            "timestamp": r.get::<DateTime<Utc>, _>("timestamp"),
-            "level": r.get::<String, _>("level"),
-            "source": r.get::<String, _>("source"),
-            "message": r.get::<String, _>("message"),
-            "metadata": r.get::<Option<String>, _>("metadata_json").and_then(|s| serde_json::from_str::<serde_json::Value>(&s).ok())
-        })).collect())
+    /// Ticks a meeting follow-up off, or back on. Returns false when there
+    /// is no such task.
+    pub async fn set_meeting_task_done(&self, id: i64, done: bool) -> Result<bool> {
+        let result = sqlx::query("UPDATE meeting_tasks SET done = ? WHERE id = ?")
+            .bind(done)
+            .bind(id)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+        Ok(result.rows_affected() > 0)
     }
 
-    pub async fn set_config(&self, key: &str, value: &str) -> Result<()> {
-        sqlx::query("INSERT INTO app_config (key, value, updated_at) VALUES (?, ?, ?) ON CONFLICT(key) DO UPDATE SET value = excluded.value, updated_at = excluded.updated_at")
-            .bind(key)
-            .bind(value)
+    /// Replaces the attachments listed for an email with `attachments`.
+    pub async fn replace_attachments(
+        &self,
+        email_id: i64,
+        attachments: &[Attachment],
+    ) -> Result<()> {
+        let mut tx = self
+            .pool
+            .begin()
+            .await
+            .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+        sqlx::query("DELETE FROM attachments WHERE email_id = ?")
+            .bind(email_id)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+        for attachment in attachments {
+            sqlx::query(
+                "INSERT INTO attachments
+                    (email_id, filename, mime, size_bytes, extracted_text, hash, skipped_reason)
+                 VALUES (?, ?, ?, ?, ?, ?, ?)",
+            )
+            .bind(email_id)
+            .bind(&attachment.filename)
+            .bind(&attachment.mime)
+            .bind(attachment.size_bytes)
+            .bind(&attachment.extracted_text)
+            .bind(&attachment.hash)
+            .bind(&attachment.skipped_reason)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+        }
+        tx.commit()
+            .await
+            .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))
+    }
+
+    pub async fn list_attachments(&self, email_id: i64) -> Result<Vec<Attachment>> {
+        let rows = sqlx::query("SELECT * FROM attachments WHERE email_id = ? ORDER BY id")
+            .bind(email_id)
+            .fetch_all(&self.read_pool)
+            .await
+            .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+        Ok(rows
+            .iter()
+            .map(|r| Attachment {
+                id: r.get("id"),
+                email_id: r.get("email_id"),
+                filename: r.get("filename"),
+                mime: r.get("mime"),
+                size_bytes: r.get("size_bytes"),
+                extracted_text: r.get("extracted_text"),
+                hash: r.get("hash"),
+                skipped_reason: r.get("skipped_reason"),
+            })
+            .collect())
+    }
+
+    /// Hashes of every stored attachment's content: the files still in use.
+    pub async fn list_attachment_hashes(&self) -> Result<HashSet<String>> {
+        let hashes: Vec<String> =
+            sqlx::query_scalar("SELECT DISTINCT hash FROM attachments WHERE hash != ''")
+                .fetch_all(&self.read_pool)
+                .await
+                .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+        Ok(hashes.into_iter().collect())
+    }
+
+    /// Replaces the annotation of the email or project `input` is for.
+    /// Clearing every part of it removes it, and returns `None`.
+    pub async fn save_annotation(&self, input: &AnnotationInput) -> Result<Option<Annotation>> {
+        let (note, tags) = validate_annotation(input)?;
+        let project = input.project.as_deref().map(str::trim);
+        let mut tx = self
+            .pool
+            .begin()
+            .await
+            .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+        let existing: Option<i64> =
+            sqlx::query_scalar("SELECT id FROM user_annotations WHERE email_id = ? OR project = ?")
+                .bind(input.email_id)
+                .bind(project)
+                .fetch_optional(&mut *tx)
+                .await
+                .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+
+        if note.is_none() && tags.is_empty() && !input.pinned && !input.starred {
+            if let Some(id) = existing {
+                sqlx::query("DELETE FROM user_annotations WHERE id = ?")
+                    .bind(id)
+                    .execute(&mut *tx)
+                    .await
+                    .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+            }
+            tx.commit()
+                .await
+                .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+            return Ok(None);
+        }
+
+        let tags_json = serde_json::to_string(&tags).unwrap_or_else(|_| "[]".into());
+        let row = match existing {
+            Some(id) => sqlx::query(
+                "UPDATE user_annotations
+                 SET note = ?, tags_json = ?, pinned = ?, starred = ?, updated_at = ?
+                 WHERE id = ?
+                 RETURNING *",
+            )
+            .bind(&note)
+            .bind(&tags_json)
+            .bind(input.pinned)
+            .bind(input.starred)
             .bind(Utc::now())
-            .execute(&self.pool)
+            .bind(id),
+            None => sqlx::query(
+                "INSERT INTO user_annotations
+                    (email_id, project, note, tags_json, pinned, starred, updated_at)
+                 VALUES (?, ?, ?, ?, ?, ?, ?)
+                 RETURNING *",
+            )
+            .bind(input.email_id)
+            .bind(project)
+            .bind(&note)
+            .bind(&tags_json)
+            .bind(input.pinned)
+            .bind(input.starred)
+            .bind(Utc::now()),
+        }
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+        tx.commit()
             .await
             .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
-        Ok(())
+        Ok(Some(annotation_from_row(&row)))
     }
 
-    pub async fn get_config(&self, key: &str) -> Result<Option<String>> {
-        let row = sqlx::query("SELECT value FROM app_config WHERE key = ?")
-            .bind(key)
-            .fetch_optional(&self.pool)
+    /// Every annotation, pinned ones first, then the most recently edited.
+    pub async fn list_annotations(&self) -> Result<Vec<Annotation>> {
+        let rows = sqlx::query(
+            "SELECT * FROM user_annotations ORDER BY pinned DESC, updated_at DESC, id DESC",
+        )
+        .fetch_all(&self.read_pool)
+        .await
+        .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+        Ok(rows.iter().map(annotation_from_row).collect())
+    }
+
+    /// The annotation of `project`, matched ignoring case.
+    pub async fn get_project_annotation(&self, project: &str) -> Result<Option<Annotation>> {
+        let row = sqlx::query("SELECT * FROM user_annotations WHERE project = ?")
+            .bind(project.trim())
+            .fetch_optional(&self.read_pool)
+            .await
+            .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+        Ok(row.as_ref().map(annotation_from_row))
+    }
+
+    pub async fn delete_annotation(&self, id: i64) -> Result<bool> {
+        let result = sqlx::query("DELETE FROM user_annotations WHERE id = ?")
+            .bind(id)
+            .execute(&self.pool)
             .await
             .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Tags in use with how many emails and projects carry each, most used
+    /// first.
+    pub async fn list_annotation_tags(&self) -> Result<Vec<serde_json::Value>> {
+        let rows = sqlx::query(
+            "SELECT t.value AS tag, COUNT(*) AS count
+             FROM user_annotations a, json_each(a.tags_json) t
+             GROUP BY t.value ORDER BY count DESC, tag",
+        )
+        .fetch_all(&self.read_pool)
+        .await
+        .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+        Ok(rows
+            .iter()
+            .map(|r| {
+                serde_json::json!({
+                    "tag": r.get::<String, _>("tag"),
+                    "count": r.get::<i64, _>("count"),
+                })
+            })
+            .collect())
+    }
+
+    /// Project most recently assigned to an email in `conversation_id`, used to
+    /// apply project settings to a reply before it has been extracted.
+    pub async fn get_conversation_project(&self, conversation_id: &str) -> Result<Option<String>> {
+        let row = sqlx::query(
+            "SELECT json_extract(f.client_or_project_json, '$.name') AS project
+             FROM emails e JOIN extracted_email_facts f ON e.id = f.email_id
+             WHERE e.conversation_id = ?
+             ORDER BY e.received_at DESC LIMIT 1",
+        )
+        .bind(conversation_id)
+        .fetch_optional(&self.read_pool)
+        .await
+        .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+
+        Ok(row.and_then(|r| r.get::<Option<String>, _>("project")))
+    }
+}
+
+fn meeting_from_row(row: &SqliteRow) -> Meeting {
+    Meeting {
+        id: row.get("id"),
+        global_id: row.get("global_id"),
+        subject: row.get("subject"),
+        organizer: row.get("organizer"),
+        attendees: serde_json::from_str(&row.get::<String, _>("attendees_json"))
+            .unwrap_or_default(),
+        start_at: row.get("start_at"),
+        end_at: row.get("end_at"),
+        followed_up_at: row.get("followed_up_at"),
+        follow_up_error: row.get("follow_up_error"),
+    }
+}
+
+fn project_settings_from_row(row: &SqliteRow) -> ProjectSettings {
+    ProjectSettings {
+        name: row.get("name"),
+        mute_notifications: row.get("mute_notifications"),
+        extraction_enabled: row.get("extraction_enabled"),
+        include_in_digest: row.get("include_in_digest"),
+        retention_days: row.get("retention_days"),
+    }
+}
+
+fn settings_profile_from_row(row: &SqliteRow) -> SettingsProfile {
+    SettingsProfile {
+        name: row.get("name"),
+        settings: serde_json::from_str(&row.get::<String, _>("settings_json")).unwrap_or_default(),
+        updated_at: row.get("updated_at"),
+    }
+}
+
+fn annotation_from_row(row: &SqliteRow) -> Annotation {
+    Annotation {
+        id: row.get("id"),
+        email_id: row.get("email_id"),
+        project: row.get("project"),
+        note: row.get("note"),
+        tags: serde_json::from_str(&row.get::<String, _>("tags_json")).unwrap_or_default(),
+        pinned: row.get("pinned"),
+        starred: row.get("starred"),
+        updated_at: row.get("updated_at"),
+    }
+}
+
+/// Checks an annotation is for exactly one email or project and within
+/// limits. Returns its note, `None` when blank, and its tags normalized
+/// without repeats.
+fn validate_annotation(input: &AnnotationInput) -> Result<(Option<String>, Vec<String>)> {
+    let project = input.project.as_deref().map(str::trim);
+    if input.email_id.is_some() == project.is_some_and(|p| !p.is_empty()) {
+        return Err(noodle_core::error::NoodleError::Validation(
+            "An annotation is for either an email or a project".into(),
+        ));
+    }
+    let note = input
+        .note
+        .as_deref()
+        .map(str::trim)
+        .filter(|n| !n.is_empty())
+        .map(str::to_string);
+    if note
+        .as_ref()
+        .is_some_and(|n| n.chars().count() > MAX_ANNOTATION_NOTE_CHARS)
+    {
+        return Err(noodle_core::error::NoodleError::Validation(format!(
+            "Notes are limited to {} characters",
+            MAX_ANNOTATION_NOTE_CHARS
+        )));
+    }
+    let mut tags = Vec::new();
+    for tag in input
+        .tags
+        .iter()
+        .filter_map(|t| Annotation::normalize_tag(t))
+    {
+        if !tags.contains(&tag) {
+            tags.push(tag);
+        }
+    }
+    if tags.len() > MAX_ANNOTATION_TAGS {
+        return Err(noodle_core::error::NoodleError::Validation(format!(
+            "At most {} tags can be added",
+            MAX_ANNOTATION_TAGS
+        )));
+    }
+    Ok((note, tags))
+}
 
-        Ok(row.map(|r| r.get("value")))
+/// Outlook keeps an item's categories as one delimited string, so names
+/// can't contain the delimiters.
+fn validate_category_rule(rule: &CategoryRule) -> Result<()> {
+    if rule.pattern.is_empty() || rule.category.is_empty() {
+        return Err(noodle_core::error::NoodleError::Validation(
+            "A category rule needs a pattern and a category".into(),
+        ));
+    }
+    if rule.category.contains([',', ';']) {
+        return Err(noodle_core::error::NoodleError::Validation(
+            "Category names can't contain commas or semicolons".into(),
+        ));
+    }
+    Ok(())
+}
+
+fn describe_category_rule(rule: &CategoryRule) -> String {
+    format!(
+        "{} = {}{}",
+        rule.match_on,
+        rule.pattern,
+        if rule.write_back {
+            ", written to Outlook"
+        } else {
+            ""
+        }
+    )
+}
+
+async fn insert_category_audit(
+    tx: &mut sqlx::Transaction<'_, Sqlite>,
+    action: CategoryAction,
+    rule_id: Option<i64>,
+    email_id: Option<i64>,
+    category: &str,
+    detail: Option<&str>,
+) -> Result<()> {
+    sqlx::query(
+        "INSERT INTO category_audit (action, rule_id, email_id, category, detail, created_at)
+         VALUES (?, ?, ?, ?, ?, ?)",
+    )
+    .bind(action.to_string())
+    .bind(rule_id)
+    .bind(email_id)
+    .bind(category)
+    .bind(detail)
+    .bind(Utc::now())
+    .execute(&mut **tx)
+    .await
+    .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+    Ok(())
+}
+
+/// What identifies a meeting task across extractions: its kind and its
+/// words, ignoring case, punctuation and spacing.
+fn meeting_task_key(kind: &str, text: &str) -> String {
+    let words: Vec<String> = text
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .collect();
+    format!("{}:{}", kind, words.join(" "))
+}
+
+/// SQL ranking the severity in `column`, highest first, for comparisons.
+fn severity_rank(column: &str) -> String {
+    format!(
+        "CASE {} WHEN 'high' THEN 3 WHEN 'medium' THEN 2 ELSE 1 END",
+        column
+    )
+}
+
+/// Records a mention and folds it into its cluster's mean, time span and
+/// severity.
+async fn insert_issue_mention(
+    tx: &mut sqlx::Transaction<'_, Sqlite>,
+    cluster_id: i64,
+    mention: &IssueMention,
+    embedding: &[f32],
+    centroid: &[f32],
+) -> Result<()> {
+    sqlx::query(
+        "INSERT OR REPLACE INTO issue_mentions
+            (email_id, kind, position, cluster_id, title, details, severity, project, received_at,
+             embedding_json)
+         VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+    )
+    .bind(mention.email_id)
+    .bind(mention.kind.to_string())
+    .bind(mention.position as i64)
+    .bind(cluster_id)
+    .bind(&mention.title)
+    .bind(&mention.details)
+    .bind(mention.severity.to_string())
+    .bind(mention.project.as_deref())
+    .bind(mention.received_at)
+    .bind(serde_json::to_string(embedding).unwrap_or_default())
+    .execute(&mut **tx)
+    .await
+    .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+
+    sqlx::query(&format!(
+        "UPDATE issue_clusters SET
+            centroid_json = ?,
+            mention_count = mention_count + 1,
+            first_seen = MIN(first_seen, ?),
+            last_seen = MAX(last_seen, ?),
+            severity = CASE WHEN {} > {} THEN ? ELSE severity END
+         WHERE id = ?",
+        severity_rank("?"),
+        severity_rank("severity"),
+    ))
+    .bind(serde_json::to_string(centroid).unwrap_or_default())
+    .bind(mention.received_at)
+    .bind(mention.received_at)
+    .bind(mention.severity.to_string())
+    .bind(mention.severity.to_string())
+    .bind(cluster_id)
+    .execute(&mut **tx)
+    .await
+    .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+    Ok(())
+}
+
+fn project_email_facts_from_row(r: &SqliteRow) -> ProjectEmailFacts {
+    ProjectEmailFacts {
+        id: r.get("id"),
+        conversation_id: r.get("conversation_id"),
+        subject: r.get("subject"),
+        sender: r.get("sender"),
+        received_at: r.get("received_at"),
+        project: r.get("project"),
+        primary_type: r.get("primary_type"),
+        intent: r.get("intent"),
+        summary: r.get("summary"),
+        key_points: serde_json::from_str(&r.get::<String, _>("key_points_json"))
+            .unwrap_or_default(),
+        due_by: r.get("due_by"),
+        risks: serde_json::from_str(&r.get::<String, _>("risks_json")).unwrap_or_default(),
+        blockers: serde_json::from_str(&r.get::<String, _>("blockers_json")).unwrap_or_default(),
+    }
+}
+
+fn issue_facts_from_row(r: &SqliteRow) -> IssueFacts {
+    IssueFacts {
+        email_id: r.get("id"),
+        received_at: r.get("received_at"),
+        project: r.get("project"),
+        risks: serde_json::from_str(&r.get::<String, _>("risks_json")).unwrap_or_default(),
+        issues: serde_json::from_str(&r.get::<String, _>("issues_json")).unwrap_or_default(),
+        blockers: serde_json::from_str(&r.get::<String, _>("blockers_json")).unwrap_or_default(),
+    }
+}
+
+fn issue_ticket_from_row(r: &SqliteRow) -> Option<IssueTicket> {
+    Some(IssueTicket {
+        id: r.get("id"),
+        email_id: r.get("email_id"),
+        kind: r.get::<String, _>("kind").parse().ok()?,
+        position: r.get::<i64, _>("position") as usize,
+        tracker: r.get::<String, _>("tracker").parse().ok()?,
+        ticket_key: r.get::<Option<String>, _>("ticket_key")?,
+        url: r.get::<Option<String>, _>("url").unwrap_or_default(),
+        created_at: r.get("created_at"),
+    })
+}
+
+/// Id of the person entity for a mail participant, created on first sight.
+async fn upsert_person(
+    tx: &mut sqlx::Transaction<'_, Sqlite>,
+    name: &str,
+    now: DateTime<Utc>,
+) -> Result<i64> {
+    let row = sqlx::query(
+        "INSERT INTO entities (entity_type, canonical_name, normalized_key, created_at)
+         VALUES ('person', ?, ?, ?)
+         ON CONFLICT(normalized_key) DO UPDATE SET normalized_key = excluded.normalized_key
+         RETURNING id",
+    )
+    .bind(name)
+    .bind(format!("person:{}", name.to_lowercase()))
+    .bind(now)
+    .fetch_one(&mut **tx)
+    .await
+    .map_err(|e| noodle_core::error::NoodleError::Storage(e.to_string()))?;
+    Ok(row.get("id"))
+}
+
+/// The part of a sent message the user wrote: everything before the quoted
+/// history of the thread.
+fn own_text(body: &str) -> &str {
+    let mut offset = 0;
+    for line in body.split_inclusive('\n') {
+        let trimmed = line.trim();
+        let quoted = trimmed.starts_with('>')
+            || trimmed.starts_with("-----Original Message-----")
+            || trimmed.starts_with("From:")
+            || (trimmed.starts_with("On ") && trimmed.ends_with("wrote:"));
+        if quoted {
+            break;
+        }
+        offset += line.len();
+    }
+    &body[..offset]
+}
+
+/// Compares an email's stored subject and body with the incoming version.
+fn email_change(
+    email_id: i64,
+    previous_subject: &str,
+    previous_body: &str,
+    email: &noodle_core::types::Email,
+) -> EmailChange {
+    let (previous_subject, subject) = if previous_subject != email.subject {
+        (
+            Some(previous_subject.to_string()),
+            Some(email.subject.clone()),
+        )
+    } else {
+        (None, None)
+    };
+    let mut change = EmailChange {
+        id: 0,
+        email_id,
+        detected_at: Utc::now(),
+        previous_subject,
+        subject,
+        body_diff: None,
+        lines_added: 0,
+        lines_removed: 0,
+    };
+    if previous_body != email.body_text {
+        let diff = TextDiff::from_lines(previous_body, email.body_text.as_str());
+        for op in diff.iter_all_changes() {
+            match op.tag() {
+                ChangeTag::Insert => change.lines_added += 1,
+                ChangeTag::Delete => change.lines_removed += 1,
+                ChangeTag::Equal => {}
+            }
+        }
+        change.body_diff = Some(
+            diff.unified_diff()
+                .context_radius(2)
+                .header("before", "after")
+                .to_string(),
+        );
+    }
+    change
+}
+
+fn topic_summary_from_row(row: &SqliteRow) -> TopicSummary {
+    TopicSummary {
+        id: row.get("id"),
+        query: row.get("query"),
+        date_range: DateRange {
+            start: row.get("range_start"),
+            end: row.get("range_end"),
+        },
+        run_at: row.get("run_at"),
+        status: row.get("status"),
+        output_text: row.get("output_text"),
+        sources: row
+            .get::<Option<String>, _>("output_json")
+            .and_then(|s| serde_json::from_str(&s).ok())
+            .unwrap_or_default(),
+        error_text: row.get("error_text"),
+    }
+}
+
+fn seal_if_secret(key: &str, value: &str) -> Result<String> {
+    if Config::is_secret(key) {
+        secrets::seal(value)
+    } else {
+        Ok(value.to_string())
+    }
+}
+
+fn prompt_from_row(r: &SqliteRow) -> CustomPrompt {
+    CustomPrompt {
+        id: r.get("id"),
+        name: r.get("name"),
+        content: r.get("prompt_template"),
+        kind: r
+            .get::<String, _>("kind")
+            .parse()
+            .unwrap_or(PromptKind::Custom),
+        description: r.get("description"),
+        variables: serde_json::from_str(&r.get::<String, _>("variables_json")).unwrap_or_default(),
+        output_schema: r
+            .get::<Option<String>, _>("json_schema")
+            .and_then(|s| serde_json::from_str(&s).ok()),
+        schedule_cron: r.get("schedule_cron"),
+        pack: r.get("pack"),
+    }
+}
+
+fn experiment_from_row(r: &SqliteRow) -> ExtractionExperiment {
+    ExtractionExperiment {
+        id: r.get("id"),
+        name: r.get("name"),
+        variant_a: serde_json::from_str(&r.get::<String, _>("variant_a_json")).unwrap_or_default(),
+        variant_b: serde_json::from_str(&r.get::<String, _>("variant_b_json")).unwrap_or_default(),
+        emails: serde_json::from_str(&r.get::<String, _>("emails_json")).unwrap_or_default(),
+        comparison: serde_json::from_str(&r.get::<String, _>("comparison_json"))
+            .unwrap_or_default(),
+        created_at: r.get("created_at"),
+    }
+}
+
+fn email_with_facts_json(row: &SqliteRow) -> serde_json::Value {
+    let client_project: Option<serde_json::Value> = row
+        .get::<Option<String>, _>("client_or_project_json")
+        .and_then(|s| serde_json::from_str(&s).ok());
+
+    let risks: Option<serde_json::Value> = row
+        .get::<Option<String>, _>("risks_json")
+        .and_then(|s| serde_json::from_str(&s).ok());
+
+    serde_json::json!({
+        "id": row.get::<i64, _>("id"),
+        "subject": row.get::<String, _>("subject"),
+        "sender": row.get::<String, _>("sender"),
+        "received_at": row.get::<chrono::DateTime<chrono::Utc>, _>("received_at"),
+        "body_text": row.get::<String, _>("body_text"),
+        "conversation_id": row.get::<Option<String>, _>("conversation_id"),
+        "primary_type": row.get::<Option<String>, _>("primary_type"),
+        "intent": row.get::<Option<String>, _>("intent"),
+        "urgency": row.get::<Option<String>, _>("urgency"),
+        "sentiment": row.get::<Option<String>, _>("sentiment"),
+        "needs_response": row.get::<Option<bool>, _>("needs_response"),
+        "waiting_on": row.get::<Option<String>, _>("waiting_on"),
+        "due_by": row.get::<Option<chrono::DateTime<chrono::Utc>>, _>("due_by"),
+        "due_by_raw": row.get::<Option<String>, _>("due_by_raw"),
+        "summary": row.get::<Option<String>, _>("summary"),
+        "facts_stale": row.get::<Option<bool>, _>("stale").unwrap_or(false),
+        "review_reason": row.get::<Option<String>, _>("review_reason"),
+        "change_count": row.get::<i64, _>("change_count"),
+        "responded_at": row.get::<Option<chrono::DateTime<chrono::Utc>>, _>("responded_at"),
+        "response_latency_secs": row.get::<Option<i64>, _>("response_latency_secs"),
+        "labels": row.get::<Option<String>, _>("labels"),
+        "newsletter": row.get::<bool, _>("newsletter"),
+        "deleted_at": row.get::<Option<chrono::DateTime<chrono::Utc>>, _>("deleted_at"),
+        "vip": row.get::<bool, _>("vip"),
+        "priority": row.get::<i64, _>("priority"),
+        "annotation": row
+            .get::<Option<String>, _>("annotation_json")
+            .and_then(|s| serde_json::from_str::<serde_json::Value>(&s).ok()),
+        "client_or_project": client_project,
+        "risks": risks
+    })
+}
+
+/// Turns free text into an FTS5 MATCH expression by quoting every term, so user
+/// input can never be interpreted as FTS query syntax.
+fn fts_match_expression(query: &str) -> String {
+    query
+        .split_whitespace()
+        .map(|term| term.replace('"', ""))
+        .filter(|term| !term.is_empty())
+        .map(|term| format!("\"{}\"", term))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Appends `AND ...` clauses leaving out trashed emails and for every
+/// constraint set on `filter`. Expects the query to alias emails as `e` and
+/// facts as `f` and to already have a WHERE.
+fn push_search_filter(builder: &mut QueryBuilder<'_, Sqlite>, filter: &SearchFilter) {
+    builder.push(" AND e.deleted_at IS NULL");
+    for tag in &filter.tags {
+        builder.push(
+            " AND EXISTS (SELECT 1 FROM user_annotations a, json_each(a.tags_json) t
+                          WHERE a.email_id = e.id AND t.value = ",
+        );
+        builder.push_bind(tag.clone());
+        builder.push(")");
+    }
+    if let Some(sender) = &filter.sender {
+        builder.push(" AND e.sender LIKE ");
+        builder.push_bind(format!("%{}%", sender));
+    }
+    if let Some(project) = &filter.project {
+        builder.push(" AND json_extract(f.client_or_project_json, '$.name') LIKE ");
+        builder.push_bind(format!("%{}%", project));
+    }
+    if let Some(folder) = &filter.folder {
+        builder.push(" AND e.folder = ");
+        builder.push_bind(folder.clone());
+    }
+    if let Some(urgency) = &filter.urgency {
+        builder.push(" AND f.urgency = ");
+        builder.push_bind(urgency.to_string());
+    }
+    if let Some(sentiment) = &filter.sentiment {
+        builder.push(" AND f.sentiment = ");
+        builder.push_bind(sentiment.to_string());
+    }
+    if let Some(needs_response) = filter.needs_response {
+        builder.push(" AND f.needs_response = ");
+        builder.push_bind(needs_response);
+    }
+    if let Some(range) = &filter.date_range {
+        if let Some(start) = range.start {
+            builder.push(" AND e.received_at >= ");
+            builder.push_bind(start);
+        }
+        if let Some(end) = range.end {
+            builder.push(" AND e.received_at < ");
+            builder.push_bind(end);
+        }
+    }
+    if let Some(conversation_id) = &filter.conversation_id {
+        builder.push(" AND e.conversation_id = ");
+        builder.push_bind(conversation_id.clone());
     }
 }
@@ -0,0 +1,114 @@
+use noodle_core::error::{NoodleError, Result};
+use sqlx::sqlite::{SqliteConnectOptions, SqliteSynchronous};
+use sqlx::{ConnectOptions, Row};
+use std::path::Path;
+use std::str::FromStr;
+use std::time::Duration;
+use tracing::warn;
+
+/// `app_config` keys read when the pool is opened; changes apply on restart.
+pub const MAX_CONNECTIONS_KEY: &str = "sqlite_max_connections";
+pub const READ_CONNECTIONS_KEY: &str = "sqlite_read_connections";
+pub const BUSY_TIMEOUT_KEY: &str = "sqlite_busy_timeout_ms";
+/// `off`, `normal` (default), `full` or `extra`.
+pub const SYNCHRONOUS_KEY: &str = "sqlite_synchronous";
+
+const MAX_CONNECTIONS_LIMIT: u32 = 64;
+
+/// Connection pool tuning. The defaults suit WAL mode: enough connections for
+/// sync and UI reads to overlap, a busy timeout so writers queue instead of
+/// failing with "database is locked", and `synchronous = NORMAL`, which is
+/// durable across application crashes in WAL mode.
+#[derive(Debug, Clone, Copy)]
+pub struct PoolSettings {
+    pub max_connections: u32,
+    /// Size of the separate read-only pool used for queries.
+    pub read_connections: u32,
+    pub busy_timeout: Duration,
+    pub synchronous: SqliteSynchronous,
+}
+
+impl Default for PoolSettings {
+    fn default() -> Self {
+        Self {
+            max_connections: 8,
+            read_connections: 4,
+            busy_timeout: Duration::from_millis(5000),
+            synchronous: SqliteSynchronous::Normal,
+        }
+    }
+}
+
+impl PoolSettings {
+    /// Reads overrides from the database at `path` before the pool exists.
+    /// Missing, unreadable or invalid values fall back to the defaults.
+    pub async fn load(path: &Path) -> Self {
+        let mut settings = Self::default();
+        if !path.exists() {
+            return settings;
+        }
+
+        let rows = async {
+            let mut conn = SqliteConnectOptions::new()
+                .filename(path)
+                .read_only(true)
+                .connect()
+                .await?;
+            sqlx::query("SELECT key, value FROM app_config WHERE key IN (?, ?, ?, ?)")
+                .bind(MAX_CONNECTIONS_KEY)
+                .bind(READ_CONNECTIONS_KEY)
+                .bind(BUSY_TIMEOUT_KEY)
+                .bind(SYNCHRONOUS_KEY)
+                .fetch_all(&mut conn)
+                .await
+        }
+        .await;
+        // A fresh database has no app_config table until migrations run.
+        let Ok(rows) = rows else {
+            return settings;
+        };
+
+        for row in rows {
+            let key: String = row.get("key");
+            let value: String = row.get("value");
+            if let Err(e) = settings.apply(&key, &value) {
+                warn!("Ignoring {}: {}", key, e);
+            }
+        }
+        settings
+    }
+
+    fn apply(&mut self, key: &str, value: &str) -> Result<()> {
+        let value = value.trim();
+        match key {
+            MAX_CONNECTIONS_KEY => self.max_connections = parse_pool_size(value)?,
+            READ_CONNECTIONS_KEY => self.read_connections = parse_pool_size(value)?,
+            BUSY_TIMEOUT_KEY => {
+                let ms = value.parse::<u64>().map_err(|_| {
+                    NoodleError::Validation("busy timeout must be a number of milliseconds".into())
+                })?;
+                self.busy_timeout = Duration::from_millis(ms);
+            }
+            SYNCHRONOUS_KEY => {
+                self.synchronous = SqliteSynchronous::from_str(value).map_err(|_| {
+                    NoodleError::Validation("synchronous must be off, normal, full or extra".into())
+                })?;
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+}
+
+fn parse_pool_size(value: &str) -> Result<u32> {
+    value
+        .parse::<u32>()
+        .ok()
+        .filter(|n| (1..=MAX_CONNECTIONS_LIMIT).contains(n))
+        .ok_or_else(|| {
+            NoodleError::Validation(format!(
+                "pool size must be between 1 and {}",
+                MAX_CONNECTIONS_LIMIT
+            ))
+        })
+}
@@ -0,0 +1,100 @@
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine as _;
+use noodle_core::error::{NoodleError, Result};
+#[cfg(windows)]
+use windows::core::PCWSTR;
+#[cfg(windows)]
+use windows::Win32::Foundation::{LocalFree, HLOCAL};
+#[cfg(windows)]
+use windows::Win32::Security::Cryptography::{
+    CryptProtectData, CryptUnprotectData, CRYPTPROTECT_UI_FORBIDDEN, CRYPT_INTEGER_BLOB,
+};
+
+/// DPAPI only exists on Windows. Builds for other platforms, such as the
+/// ones tests run on, store secrets as given.
+pub const AVAILABLE: bool = cfg!(windows);
+
+/// Marks `app_config` values sealed with DPAPI. Values without it predate
+/// encryption and are read as plaintext until [`super::SqliteStorage`]
+/// seals them at startup.
+const PREFIX: &str = "dpapi:";
+
+pub fn is_sealed(stored: &str) -> bool {
+    stored.starts_with(PREFIX)
+}
+
+/// Encrypts `value` for the current Windows user. Empty values stay empty,
+/// so an unset secret reads as unset.
+pub fn seal(value: &str) -> Result<String> {
+    if value.is_empty() || !AVAILABLE {
+        return Ok(value.to_string());
+    }
+    let sealed = protect(value.as_bytes(), true)?;
+    Ok(format!("{}{}", PREFIX, BASE64.encode(sealed)))
+}
+
+/// Decrypts a value written by [`seal`]; plaintext is returned as is.
+pub fn open(stored: &str) -> Result<String> {
+    let Some(encoded) = stored.strip_prefix(PREFIX) else {
+        return Ok(stored.to_string());
+    };
+    let sealed = BASE64
+        .decode(encoded)
+        .map_err(|e| NoodleError::Storage(format!("Corrupt sealed setting: {}", e)))?;
+    let plain = protect(&sealed, false)?;
+    String::from_utf8(plain)
+        .map_err(|e| NoodleError::Storage(format!("Corrupt sealed setting: {}", e)))
+}
+
+/// `CryptProtectData` when `encrypt`, else `CryptUnprotectData`. Never shows
+/// UI, since it runs in the background.
+#[cfg(windows)]
+fn protect(data: &[u8], encrypt: bool) -> Result<Vec<u8>> {
+    let input = CRYPT_INTEGER_BLOB {
+        cbData: data.len() as u32,
+        pbData: data.as_ptr() as *mut u8,
+    };
+    let mut output = CRYPT_INTEGER_BLOB::default();
+    let result = unsafe {
+        if encrypt {
+            CryptProtectData(
+                &input,
+                PCWSTR::null(),
+                None,
+                None,
+                None,
+                CRYPTPROTECT_UI_FORBIDDEN,
+                &mut output,
+            )
+        } else {
+            CryptUnprotectData(
+                &input,
+                None,
+                None,
+                None,
+                None,
+                CRYPTPROTECT_UI_FORBIDDEN,
+                &mut output,
+            )
+        }
+    };
+    result.map_err(|e| NoodleError::Storage(format!("DPAPI failed: {}", e)))?;
+
+    let bytes = unsafe {
+        let bytes = std::slice::from_raw_parts(output.pbData, output.cbData as usize).to_vec();
+        // Plaintext out of CryptUnprotectData is wiped before it goes back to the heap.
+        for byte in std::slice::from_raw_parts_mut(output.pbData, output.cbData as usize) {
+            std::ptr::write_volatile(byte, 0);
+        }
+        LocalFree(HLOCAL(output.pbData.cast()));
+        bytes
+    };
+    Ok(bytes)
+}
+
+#[cfg(not(windows))]
+fn protect(_data: &[u8], _encrypt: bool) -> Result<Vec<u8>> {
+    Err(NoodleError::Storage(
+        "Sealed settings can only be read on Windows".into(),
+    ))
+}
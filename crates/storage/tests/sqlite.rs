@@ -0,0 +1,1281 @@
+use chrono::{Duration, Utc};
+use noodle_core::fixtures::{self, facts};
+use noodle_core::text::SanitizedText;
+use noodle_core::types::{
+    AnnotationInput, Attachment, Blocker, CategoryAction, CategoryMatch, ChatToolCall, Email,
+    ExperimentComparison, ExperimentEmail, ExperimentOutcome, ExperimentVariant, InferredRelation,
+    IssueKind, Meeting, MeetingAttendee, MeetingTask, MeetingTaskKind, OpenQuestion, PackPrompt,
+    ProjectSettings, PromptKind, PromptVariable, RelationKind, RelationStatus, ScanCheckpoint,
+    SearchFilter, Sentiment, Severity, TicketTracker, TopicSource, TriageState,
+    UnsupportedCitation, Urgency,
+};
+use std::collections::HashSet;
+use storage::sqlite::SqliteStorage;
+use tempfile::TempDir;
+
+async fn open() -> (TempDir, SqliteStorage) {
+    let dir = tempfile::tempdir().unwrap();
+    let storage = SqliteStorage::new(dir.path().join("noodle.db"))
+        .await
+        .unwrap();
+    (dir, storage)
+}
+
+fn email(entry_id: &str, subject: &str, body: &str) -> Email {
+    Email {
+        subject: subject.into(),
+        body_text: body.into(),
+        ..fixtures::email(entry_id)
+    }
+}
+
+#[tokio::test]
+async fn email_detail_and_body() {
+    let (_dir, storage) = open().await;
+    let id = storage
+        .save_email(&email("e1", "Quarterly numbers", "See attached."))
+        .await
+        .unwrap();
+
+    let detail = storage.get_email_detail(id).await.unwrap().unwrap();
+    assert_eq!(detail.id, id);
+    assert_eq!(detail.subject, "Quarterly numbers");
+    assert_eq!(detail.sender, "alice@example.com");
+    assert_eq!(
+        storage.get_email_body(id).await.unwrap().as_deref(),
+        Some("See attached.")
+    );
+    assert_eq!(storage.get_fact_summary(id).await.unwrap(), None);
+
+    assert!(storage.get_email_detail(id + 1).await.unwrap().is_none());
+    assert!(storage.get_email_body(id + 1).await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn prompt_crud() {
+    let (_dir, storage) = open().await;
+    assert!(storage.list_prompts().await.unwrap().is_empty());
+
+    let id = storage
+        .create_prompt("Weekly", "Summarize my week")
+        .await
+        .unwrap();
+    let prompts = storage.list_prompts().await.unwrap();
+    assert_eq!(prompts.len(), 1);
+    assert_eq!(prompts[0].id, id);
+    assert_eq!(prompts[0].name, "Weekly");
+    assert_eq!(prompts[0].content, "Summarize my week");
+
+    assert!(storage
+        .update_prompt(&id, "Weekly review", "Summarize my week by project")
+        .await
+        .unwrap());
+    let prompts = storage.list_prompts().await.unwrap();
+    assert_eq!(prompts[0].name, "Weekly review");
+    assert_eq!(prompts[0].content, "Summarize my week by project");
+    assert!(!storage.update_prompt("missing", "x", "y").await.unwrap());
+
+    assert!(storage.delete_prompt(&id).await.unwrap());
+    assert!(!storage.delete_prompt(&id).await.unwrap());
+    assert!(storage.list_prompts().await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn imported_prompts_are_updated_by_pack_and_name() {
+    let (_dir, storage) = open().await;
+    let own = storage
+        .create_prompt("Weekly", "Summarize my week")
+        .await
+        .unwrap();
+    let mut prompt = PackPrompt {
+        name: "Weekly".into(),
+        kind: PromptKind::Periodic,
+        description: Some("Monday review".into()),
+        template: "Summarize {{emails}} for {{team}}".into(),
+        variables: vec![PromptVariable {
+            name: "team".into(),
+            description: None,
+            default: Some("the team".into()),
+        }],
+        output_schema: Some(serde_json::json!({ "type": "object" })),
+        schedule_cron: Some("0 0 9 * * Mon *".into()),
+    };
+    let import = |pack: &'static str, prompt: &PackPrompt| {
+        let prompts = vec![prompt.clone()];
+        let storage = &storage;
+        async move { storage.import_prompts(pack, &prompts).await.unwrap()[0].clone() }
+    };
+    let (id, created) = import("Ops pack", &prompt).await;
+    assert!(created);
+    assert_ne!(id, own);
+
+    prompt.template = "Summarize {{emails}} by project for {{team}}".into();
+    assert_eq!(import("Ops pack", &prompt).await, (id.clone(), false));
+    // Another pack's prompt of the same name is its own.
+    assert!(import("Other", &prompt).await.1);
+
+    let imported = storage.get_prompt(&id).await.unwrap().unwrap();
+    assert_eq!(imported.kind, PromptKind::Periodic);
+    assert_eq!(imported.pack.as_deref(), Some("Ops pack"));
+    assert_eq!(
+        imported.content,
+        "Summarize {{emails}} by project for {{team}}"
+    );
+    assert_eq!(imported.variables[0].default.as_deref(), Some("the team"));
+    assert_eq!(imported.output_schema.unwrap()["type"], "object");
+    let own = storage.get_prompt(&own).await.unwrap().unwrap();
+    assert_eq!(own.kind, PromptKind::Custom);
+    assert!(own.pack.is_none());
+    assert_eq!(storage.list_prompts().await.unwrap().len(), 3);
+}
+
+#[tokio::test]
+async fn changed_email_is_logged() {
+    let (_dir, storage) = open().await;
+    let id = storage
+        .save_email(&email("e1", "Offer", "Price: 100\nValid until Friday"))
+        .await
+        .unwrap();
+    // Saving the same content again is not a change.
+    storage
+        .save_email(&email("e1", "Offer", "Price: 100\nValid until Friday"))
+        .await
+        .unwrap();
+    assert!(storage.list_email_changes(id).await.unwrap().is_empty());
+
+    let mut edited = email("e1", "Revised offer", "Price: 120\nValid until Friday");
+    edited.hash = "e1-v2".into();
+    assert_eq!(storage.save_email(&edited).await.unwrap(), id);
+
+    let changes = storage.list_email_changes(id).await.unwrap();
+    assert_eq!(changes.len(), 1);
+    assert_eq!(changes[0].previous_subject.as_deref(), Some("Offer"));
+    assert_eq!(changes[0].subject.as_deref(), Some("Revised offer"));
+    assert_eq!((changes[0].lines_added, changes[0].lines_removed), (1, 1));
+    let diff = changes[0].body_diff.as_deref().unwrap();
+    assert!(diff.contains("-Price: 100") && diff.contains("+Price: 120"));
+}
+
+#[tokio::test]
+async fn inferred_relations_keep_review_status() {
+    let (_dir, storage) = open().await;
+    let relation = |target: &str| InferredRelation {
+        id: 0,
+        source: "alice@example.com".into(),
+        target: target.into(),
+        kind: RelationKind::SameOrg,
+        confidence: 0.8,
+        evidence: "3 shared emails (3 direct, 0 copied)".into(),
+        status: RelationStatus::Suggested,
+    };
+    storage
+        .save_inferred_relations(&[relation("bob@example.com"), relation("carol@example.com")])
+        .await
+        .unwrap();
+    let saved = storage.list_inferred_relations().await.unwrap();
+    assert_eq!(saved.len(), 2);
+    let bob = saved
+        .iter()
+        .find(|r| r.target == "bob@example.com")
+        .unwrap();
+    assert!(storage
+        .set_relation_status(bob.id, RelationStatus::Dismissed)
+        .await
+        .unwrap());
+
+    // Recomputing keeps the dismissal and drops suggestions no longer inferred.
+    storage
+        .save_inferred_relations(&[relation("bob@example.com")])
+        .await
+        .unwrap();
+    let saved = storage.list_inferred_relations().await.unwrap();
+    assert_eq!(saved.len(), 1);
+    assert_eq!(saved[0].status, RelationStatus::Dismissed);
+}
+
+#[tokio::test]
+async fn search_suggestions_prefer_frequent_queries() {
+    let (_dir, storage) = open().await;
+    for query in ["budget review", "Budget Review", "budget q3", "roadmap"] {
+        storage.record_search(query, 3, 100).await.unwrap();
+    }
+
+    let suggestions = storage.get_search_suggestions("BUD", 10).await.unwrap();
+    let queries: Vec<&str> = suggestions.iter().map(|s| s.query.as_str()).collect();
+    assert_eq!(suggestions.len(), 2);
+    assert_eq!(queries[0].to_lowercase(), "budget review");
+    assert_eq!(suggestions[0].count, 2);
+    assert_eq!(queries[1], "budget q3");
+
+    // Letters beyond ASCII are matched as typed, the others in any case.
+    storage.record_search("ÉTÉ plan", 3, 100).await.unwrap();
+    let suggestions = storage.get_search_suggestions("Ét", 10).await.unwrap();
+    assert_eq!(suggestions.len(), 1);
+    assert_eq!(suggestions[0].query, "ÉTÉ plan");
+
+    storage.record_search("roadmap", 1, 2).await.unwrap();
+    assert_eq!(storage.get_search_history(10).await.unwrap().len(), 2);
+    assert_eq!(storage.clear_search_history().await.unwrap(), 2);
+    assert!(storage
+        .get_search_suggestions("", 10)
+        .await
+        .unwrap()
+        .is_empty());
+}
+
+#[tokio::test]
+async fn secret_settings_read_back_as_saved() {
+    let (_dir, storage) = open().await;
+    storage
+        .set_config_bulk(&[
+            ("jira_api_token".into(), "s3cret".into()),
+            ("jira_email".into(), "me@example.com".into()),
+        ])
+        .await
+        .unwrap();
+    storage.set_config("api_key", "sk-test").await.unwrap();
+
+    let config = storage.get_all_config().await.unwrap();
+    assert_eq!(config.jira_api_token.as_deref(), Some("s3cret"));
+    assert_eq!(config.api_key.as_deref(), Some("sk-test"));
+    assert_eq!(
+        storage.get_config("jira_email").await.unwrap().as_deref(),
+        Some("me@example.com")
+    );
+
+    let masked = config.masked();
+    assert_eq!(
+        masked.jira_api_token.as_deref(),
+        Some(noodle_core::config::SECRET_MASK)
+    );
+    assert_eq!(masked.jira_email.as_deref(), Some("me@example.com"));
+}
+
+#[tokio::test]
+async fn category_rules_label_emails_and_audit() {
+    let (_dir, storage) = open().await;
+    let id = storage
+        .save_email(&email("e1", "Kickoff", "Agenda attached."))
+        .await
+        .unwrap();
+    let rule = storage
+        .add_category_rule(CategoryMatch::Project, " Apollo ", "Apollo", 8, true)
+        .await
+        .unwrap();
+    assert_eq!(rule.pattern, "Apollo");
+    assert!(storage
+        .add_category_rule(CategoryMatch::Sender, "@example.com", "A, B", 0, false)
+        .await
+        .is_err());
+
+    let labels = vec![(rule.id, "Apollo".to_string())];
+    assert_eq!(
+        storage.replace_email_categories(id, &labels).await.unwrap(),
+        vec!["Apollo".to_string()]
+    );
+    // Extracting again with the same result labels nothing new.
+    assert!(storage
+        .replace_email_categories(id, &labels)
+        .await
+        .unwrap()
+        .is_empty());
+
+    assert!(storage.remove_category_rule(rule.id).await.unwrap());
+    assert!(storage.list_category_rules().await.unwrap().is_empty());
+
+    let actions: Vec<CategoryAction> = storage
+        .list_category_audit(10)
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|entry| entry.action)
+        .collect();
+    assert_eq!(
+        actions,
+        vec![
+            CategoryAction::RuleRemoved,
+            CategoryAction::Labelled,
+            CategoryAction::RuleAdded
+        ]
+    );
+}
+
+#[tokio::test]
+async fn newsletters_are_listed_by_sender() {
+    let (_dir, storage) = open().await;
+    let mut issue = email("n1", "Weekly news", "This week...");
+    issue.list_unsubscribe = Some("<https://example.com/unsubscribe>".into());
+    let id = storage.save_email(&issue).await.unwrap();
+    storage
+        .save_email(&email("e1", "Lunch?", "Free at noon?"))
+        .await
+        .unwrap();
+    storage.set_newsletter(id, true).await.unwrap();
+    assert!(storage.is_newsletter(id).await.unwrap());
+
+    let now = Utc::now();
+    let newsletters = storage
+        .list_newsletters_between(now - chrono::Duration::days(7), now, 10)
+        .await
+        .unwrap();
+    assert_eq!(newsletters.len(), 1);
+    assert_eq!(newsletters[0].id, id);
+
+    let senders = storage.list_newsletter_senders().await.unwrap();
+    assert_eq!(senders.len(), 1);
+    assert_eq!(senders[0].issues, 1);
+    assert_eq!(
+        senders[0].list_unsubscribe.as_deref(),
+        Some("<https://example.com/unsubscribe>")
+    );
+    assert_eq!(
+        storage.last_newsletter_roundup_end(false).await.unwrap(),
+        None
+    );
+}
+
+#[tokio::test]
+async fn meeting_follow_ups_keep_done_tasks() {
+    let (_dir, storage) = open().await;
+    let now = Utc::now();
+    let mut meeting = Meeting {
+        id: 0,
+        global_id: "global".into(),
+        subject: "Launch review".into(),
+        organizer: "Ann".into(),
+        attendees: vec![MeetingAttendee {
+            name: "Ann".into(),
+            address: "ann@example.com".into(),
+        }],
+        start_at: now - chrono::Duration::hours(4),
+        end_at: now - chrono::Duration::hours(3),
+        followed_up_at: None,
+        follow_up_error: None,
+    };
+    let id = storage.upsert_meeting(&meeting).await.unwrap();
+    meeting.subject = "Launch review (moved)".into();
+    assert_eq!(storage.upsert_meeting(&meeting).await.unwrap(), id);
+
+    let window = (now - chrono::Duration::days(1), now);
+    let due = storage
+        .list_meetings_awaiting_follow_up(window.0, window.1)
+        .await
+        .unwrap();
+    assert_eq!(due.len(), 1);
+    assert_eq!(due[0].subject, "Launch review (moved)");
+
+    let email_id = storage
+        .save_email(&email("m1", "Re: Launch review", "I'll send the deck."))
+        .await
+        .unwrap();
+    let task = MeetingTask {
+        id: 0,
+        meeting_id: id,
+        kind: MeetingTaskKind::ActionItem,
+        text: "Send the deck".into(),
+        owner: Some("Ann".into()),
+        due_by: None,
+        email_id: Some(email_id),
+        done: false,
+        created_at: now,
+    };
+    storage
+        .save_meeting_follow_up(id, &[email_id], std::slice::from_ref(&task))
+        .await
+        .unwrap();
+    assert!(storage
+        .list_meetings_awaiting_follow_up(window.0, window.1)
+        .await
+        .unwrap()
+        .is_empty());
+
+    let follow_ups = storage.list_meeting_follow_ups(window.0, 10).await.unwrap();
+    assert_eq!(follow_ups[0].email_ids, vec![email_id]);
+    let task_id = follow_ups[0].tasks[0].id;
+    assert!(storage.set_meeting_task_done(task_id, true).await.unwrap());
+
+    // Extracting again keeps what was ticked off, though the model worded
+    // it a little differently; a decision with the same words is another task.
+    let reworded = MeetingTask {
+        text: "send the deck.".into(),
+        ..task.clone()
+    };
+    let decision = MeetingTask {
+        kind: MeetingTaskKind::Decision,
+        ..task
+    };
+    storage
+        .save_meeting_follow_up(id, &[email_id], &[reworded, decision])
+        .await
+        .unwrap();
+    let follow_ups = storage.list_meeting_follow_ups(window.0, 10).await.unwrap();
+    let done: Vec<(MeetingTaskKind, bool)> = follow_ups[0]
+        .tasks
+        .iter()
+        .map(|t| (t.kind, t.done))
+        .collect();
+    assert!(done.contains(&(MeetingTaskKind::ActionItem, true)));
+    assert!(done.contains(&(MeetingTaskKind::Decision, false)));
+}
+
+#[tokio::test]
+async fn writing_samples_leave_out_quoted_history() {
+    let (_dir, storage) = open().await;
+    let mut reply = email(
+        "s1",
+        "Re: Budget",
+        "Sounds good, I'll confirm by Friday.\n\nOn Mon, Ann wrote:\n> Can you check the budget?",
+    );
+    reply.folder = "Sent Items".into();
+    storage.save_email(&reply).await.unwrap();
+    let mut forward = email("s2", "Fw: Budget", "-----Original Message-----\nFrom: Ann");
+    forward.folder = "Sent Items".into();
+    storage.save_email(&forward).await.unwrap();
+    storage
+        .save_email(&email("i1", "Budget", "Can you check the budget?"))
+        .await
+        .unwrap();
+
+    let samples = storage.get_writing_samples(5).await.unwrap();
+    assert_eq!(samples, vec!["Sounds good, I'll confirm by Friday."]);
+}
+
+#[tokio::test]
+async fn attachment_hashes_follow_listed_attachments() {
+    let (_dir, storage) = open().await;
+    let id = storage
+        .save_email(&email("a1", "Contract", "Attached."))
+        .await
+        .unwrap();
+    let attachment = |filename: &str, hash: &str, skipped_reason: Option<&str>| Attachment {
+        id: 0,
+        email_id: id,
+        filename: filename.into(),
+        mime: "application/pdf".into(),
+        size_bytes: 1024,
+        extracted_text: None,
+        hash: hash.into(),
+        skipped_reason: skipped_reason.map(Into::into),
+    };
+    storage
+        .replace_attachments(
+            id,
+            &[
+                attachment("contract.pdf", "abc", None),
+                attachment("scan.pdf", "", Some("too_large")),
+            ],
+        )
+        .await
+        .unwrap();
+    let listed = storage.list_attachments(id).await.unwrap();
+    assert_eq!(listed.len(), 2);
+    assert_eq!(listed[1].skipped_reason.as_deref(), Some("too_large"));
+    assert_eq!(
+        storage.list_attachment_hashes().await.unwrap(),
+        HashSet::from(["abc".to_string()])
+    );
+
+    storage
+        .replace_attachments(id, &[attachment("contract-v2.pdf", "def", None)])
+        .await
+        .unwrap();
+    assert_eq!(storage.list_attachments(id).await.unwrap().len(), 1);
+    assert_eq!(
+        storage.list_attachment_hashes().await.unwrap(),
+        HashSet::from(["def".to_string()])
+    );
+}
+
+#[tokio::test]
+async fn whats_new_lists_changes_since() {
+    let (_dir, storage) = open().await;
+    let since = Utc::now() - Duration::hours(1);
+
+    let mut question = email("q1", "Budget", "Can you confirm the budget?");
+    question.folder = "Sent Items".into();
+    question.conversation_id = Some("c1".into());
+    question.received_at = since - Duration::hours(1);
+    let question_id = storage.save_email(&question).await.unwrap();
+    let mut asked = facts(question_id);
+    asked.open_questions = vec![OpenQuestion {
+        question: SanitizedText::new("Can you confirm the budget?"),
+        asked_by: None,
+        owner: None,
+        due_by: None,
+        due_by_raw: None,
+        confidence: 0.9,
+    }];
+    storage.save_facts(&asked).await.unwrap();
+
+    let mut answer = email("a1", "Re: Budget", "Confirmed.");
+    answer.conversation_id = Some("c1".into());
+    let answer_id = storage.save_email(&answer).await.unwrap();
+
+    let urgent_id = storage
+        .save_email(&email("u1", "Outage", "The site is down."))
+        .await
+        .unwrap();
+    let mut urgent = facts(urgent_id);
+    urgent.urgency = Urgency::High;
+    urgent.needs_response = true;
+    urgent.due_by = Some(Utc::now() - Duration::minutes(5));
+    urgent.blockers = vec![Blocker {
+        title: SanitizedText::new("Site down"),
+        details: SanitizedText::new("Nobody can log in"),
+        owner: None,
+        severity: Severity::High,
+        confidence: 0.9,
+    }];
+    storage.save_facts(&urgent).await.unwrap();
+
+    let ids = |emails: Vec<serde_json::Value>| -> Vec<i64> {
+        emails.iter().map(|e| e["id"].as_i64().unwrap()).collect()
+    };
+    assert_eq!(
+        ids(storage.get_urgent_since(since).await.unwrap()),
+        vec![urgent_id]
+    );
+    assert_eq!(
+        ids(storage.get_blockers_since(since).await.unwrap()),
+        vec![urgent_id]
+    );
+    assert_eq!(
+        ids(storage
+            .get_overdue_between(since, Utc::now())
+            .await
+            .unwrap()),
+        vec![urgent_id]
+    );
+    assert_eq!(
+        ids(storage
+            .get_replies_to_open_questions_since(since)
+            .await
+            .unwrap()),
+        vec![answer_id]
+    );
+    assert!(storage
+        .get_urgent_since(Utc::now() + Duration::minutes(1))
+        .await
+        .unwrap()
+        .is_empty());
+}
+
+#[tokio::test]
+async fn sender_profile_sums_up_their_mail() {
+    let (_dir, storage) = open().await;
+    let mut first = email("p1", "Launch plan", "Can you review the plan?");
+    first.conversation_id = Some("c1".into());
+    let first_id = storage.save_email(&first).await.unwrap();
+    let mut asking = facts(first_id);
+    asking.sentiment = Sentiment::Positive;
+    asking.open_questions = vec![OpenQuestion {
+        question: SanitizedText::new("Can you review the plan?"),
+        asked_by: None,
+        owner: None,
+        due_by: None,
+        due_by_raw: None,
+        confidence: 0.9,
+    }];
+    storage.save_facts(&asking).await.unwrap();
+
+    let mut second = email("p2", "Launch risks", "I'm worried about the date.");
+    second.sender = "Alice@Example.com".into();
+    let second_id = storage.save_email(&second).await.unwrap();
+    let mut worried = facts(second_id);
+    worried.sentiment = Sentiment::Concerned;
+    storage.save_facts(&worried).await.unwrap();
+
+    let profile = storage
+        .get_sender_profile("alice@example.com", 0)
+        .await
+        .unwrap();
+    assert_eq!(profile["received"], 2);
+    assert_eq!(profile["volume"].as_array().unwrap().len(), 1);
+    assert_eq!(profile["volume"][0]["count"], 2);
+    assert_eq!(profile["average_sentiment"], 0.25);
+    assert_eq!(profile["open_questions"][0]["email_id"], first_id);
+    assert_eq!(profile["projects"][0]["name"], "Apollo");
+    assert_eq!(profile["projects"][0]["count"], 2);
+    assert_eq!(profile["replied"], 0);
+
+    let mut reply = email("p3", "Re: Launch plan", "Looks good.");
+    reply.folder = "Sent Items".into();
+    reply.conversation_id = Some("c1".into());
+    reply.sent_at = Utc::now() + Duration::minutes(1);
+    storage.save_email(&reply).await.unwrap();
+    let profile = storage
+        .get_sender_profile("alice@example.com", 0)
+        .await
+        .unwrap();
+    assert!(profile["open_questions"].as_array().unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn bulk_actions_update_and_purge_selected_emails() {
+    let (_dir, storage) = open().await;
+    let first_id = storage
+        .save_email(&email("b1", "Budget", "Numbers attached."))
+        .await
+        .unwrap();
+    storage.save_facts(&facts(first_id)).await.unwrap();
+    let second_id = storage
+        .save_email(&email("b2", "Budget again", "Revised numbers."))
+        .await
+        .unwrap();
+    storage.save_facts(&facts(second_id)).await.unwrap();
+    let bare_id = storage
+        .save_email(&email("b3", "No facts", "Not extracted yet."))
+        .await
+        .unwrap();
+    let ids = [first_id, second_id, bare_id];
+
+    assert_eq!(
+        storage
+            .set_triage_state(&ids, TriageState::Waiting)
+            .await
+            .unwrap(),
+        2
+    );
+    assert_eq!(storage.reassign_project(&ids, "Hermes").await.unwrap(), 2);
+    let updated = storage
+        .get_email_with_facts(first_id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(updated["needs_response"], false);
+    assert_eq!(updated["waiting_on"], "them");
+    assert_eq!(updated["client_or_project"]["name"], "Hermes");
+
+    let purged = storage.purge_emails(&[first_id, bare_id]).await.unwrap();
+    assert_eq!(purged.len(), 2);
+    assert!(storage.get_email(first_id).await.unwrap().is_none());
+    assert!(storage.get_email(second_id).await.unwrap().is_some());
+    assert!(storage.is_purged("store", "b1").await.unwrap());
+    assert!(!storage.is_purged("store", "b2").await.unwrap());
+}
+
+#[tokio::test]
+async fn trashed_emails_leave_search_until_restored() {
+    let (_dir, storage) = open().await;
+    let id = storage
+        .save_email(&email("t1", "Quarterly numbers", "Draft figures."))
+        .await
+        .unwrap();
+    storage.save_facts(&facts(id)).await.unwrap();
+    let kept_id = storage
+        .save_email(&email("t2", "Quarterly plan", "Next steps."))
+        .await
+        .unwrap();
+    let filter = SearchFilter::default();
+
+    assert_eq!(storage.trash_emails(&[id]).await.unwrap(), 1);
+    assert_eq!(storage.trash_emails(&[id]).await.unwrap(), 0);
+    let listed: Vec<i64> = storage
+        .list_emails(&filter, 10)
+        .await
+        .unwrap()
+        .iter()
+        .map(|e| e["id"].as_i64().unwrap())
+        .collect();
+    assert_eq!(listed, vec![kept_id]);
+    assert_eq!(
+        storage
+            .search_keyword("quarterly", &filter, 10)
+            .await
+            .unwrap()
+            .len(),
+        1
+    );
+    let trash = storage.list_trash().await.unwrap();
+    assert_eq!(trash.len(), 1);
+    assert_eq!(trash[0]["id"], id);
+    assert!(storage
+        .list_expired_trash(Utc::now())
+        .await
+        .unwrap()
+        .is_empty());
+    assert_eq!(
+        storage
+            .list_expired_trash(Utc::now() + Duration::days(31))
+            .await
+            .unwrap(),
+        vec![id]
+    );
+
+    assert!(storage.restore_email(id).await.unwrap());
+    assert!(!storage.restore_email(id).await.unwrap());
+    assert!(storage.list_trash().await.unwrap().is_empty());
+    assert_eq!(storage.list_emails(&filter, 10).await.unwrap().len(), 2);
+}
+
+#[tokio::test]
+async fn annotation_tags_filter_search_and_clearing_removes_them() {
+    let (_dir, storage) = open().await;
+    let id = storage
+        .save_email(&email("a1", "Renewal terms", "See attached."))
+        .await
+        .unwrap();
+    storage
+        .save_email(&email("a2", "Lunch", "Friday?"))
+        .await
+        .unwrap();
+
+    let saved = storage
+        .save_annotation(&AnnotationInput {
+            email_id: Some(id),
+            note: Some("  Ask legal first ".into()),
+            tags: vec!["#Legal".into(), "legal".into(), "Follow up".into()],
+            starred: true,
+            ..Default::default()
+        })
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(saved.note.as_deref(), Some("Ask legal first"));
+    assert_eq!(saved.tags, vec!["legal", "follow-up"]);
+
+    let filter = SearchFilter {
+        tags: vec!["legal".into()],
+        ..Default::default()
+    };
+    let listed = storage.list_emails(&filter, 10).await.unwrap();
+    assert_eq!(listed.len(), 1);
+    assert_eq!(listed[0]["id"], id);
+    assert_eq!(listed[0]["annotation"]["tags"][1], "follow-up");
+    assert_eq!(listed[0]["annotation"]["starred"], true);
+
+    assert!(storage
+        .save_annotation(&AnnotationInput {
+            email_id: Some(id),
+            project: Some("Acme".into()),
+            ..Default::default()
+        })
+        .await
+        .is_err());
+    storage
+        .save_annotation(&AnnotationInput {
+            project: Some("Acme".into()),
+            pinned: true,
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    assert!(
+        storage
+            .get_project_annotation("acme")
+            .await
+            .unwrap()
+            .unwrap()
+            .pinned
+    );
+    assert_eq!(
+        storage.list_annotations().await.unwrap()[0]
+            .project
+            .as_deref(),
+        Some("Acme")
+    );
+
+    let cleared = storage
+        .save_annotation(&AnnotationInput {
+            email_id: Some(id),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    assert!(cleared.is_none());
+    assert!(storage.list_emails(&filter, 10).await.unwrap().is_empty());
+    assert_eq!(storage.list_annotations().await.unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn payload_outbox_follows_projects_and_tags() {
+    let (_dir, storage) = open().await;
+    let id = storage
+        .save_email(&email("p1", "Kickoff", "Agenda attached."))
+        .await
+        .unwrap();
+    storage.save_facts(&facts(id)).await.unwrap();
+
+    // The vector upsert carries the payload, so the outbox waits for it.
+    assert!(storage
+        .get_pending_payload_syncs(10)
+        .await
+        .unwrap()
+        .is_empty());
+    storage.complete_vector_upsert(id).await.unwrap();
+    let pending = storage.get_pending_payload_syncs(10).await.unwrap();
+    assert_eq!(pending.len(), 1);
+    assert_eq!(pending[0].0, id);
+    storage
+        .complete_payload_sync(id, &pending[0].1)
+        .await
+        .unwrap();
+
+    storage
+        .save_annotation(&AnnotationInput {
+            email_id: Some(id),
+            tags: vec!["launch".into()],
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    storage.reassign_project(&[id], "Atlas").await.unwrap();
+    let pending = storage.get_pending_payload_syncs(10).await.unwrap();
+    assert_eq!(pending.len(), 1);
+    let fields = storage.get_vector_payload(id).await.unwrap().unwrap();
+    assert_eq!(fields.project.as_deref(), Some("Atlas"));
+    assert_eq!(fields.tags, vec!["launch"]);
+    assert_eq!(fields.sender, "alice@example.com");
+
+    // Tagged again while that payload was being pushed: the newer change
+    // has to stay queued.
+    tokio::time::sleep(std::time::Duration::from_millis(5)).await;
+    storage
+        .save_annotation(&AnnotationInput {
+            email_id: Some(id),
+            tags: vec!["launch".into(), "q3".into()],
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    storage
+        .complete_payload_sync(id, &pending[0].1)
+        .await
+        .unwrap();
+    let pending = storage.get_pending_payload_syncs(10).await.unwrap();
+    assert_eq!(pending.len(), 1);
+    storage
+        .complete_payload_sync(id, &pending[0].1)
+        .await
+        .unwrap();
+
+    // Starring leaves the payload alone; purging takes the annotation along.
+    storage
+        .save_annotation(&AnnotationInput {
+            email_id: Some(id),
+            tags: vec!["launch".into(), "q3".into()],
+            starred: true,
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    assert!(storage
+        .get_pending_payload_syncs(10)
+        .await
+        .unwrap()
+        .is_empty());
+    assert_eq!(storage.purge_emails(&[id]).await.unwrap().len(), 1);
+    assert!(storage.list_annotations().await.unwrap().is_empty());
+    assert!(storage
+        .get_pending_payload_syncs(10)
+        .await
+        .unwrap()
+        .is_empty());
+}
+
+#[tokio::test]
+async fn chat_sessions_keep_messages_and_sources() {
+    let (_dir, storage) = open().await;
+    let first = storage
+        .create_chat_session("  What did Acme decide?  ")
+        .await
+        .unwrap();
+    let second = storage.create_chat_session("Budget").await.unwrap();
+    storage
+        .add_chat_message(first, "user", "What did Acme decide?", &[], &[], &[])
+        .await
+        .unwrap();
+    let source = TopicSource {
+        email_id: 7,
+        subject: "Decision".into(),
+        sender: "ceo@acme.com".into(),
+        received_at: Utc::now(),
+        summary: "Going ahead.".into(),
+        cited: true,
+    };
+    let call = ChatToolCall {
+        step: 1,
+        tool: "search".into(),
+        arguments: serde_json::json!({ "query": "Acme decision" }),
+        result: Some("1 email".into()),
+        error: None,
+        duration_ms: 12,
+    };
+    storage
+        .add_chat_message(
+            first,
+            "assistant",
+            "They are going ahead [#7].",
+            &[source],
+            &[call],
+            &[UnsupportedCitation {
+                claim: "They are going ahead.".into(),
+                email_id: 7,
+                reason: "The email doesn't appear to say this".into(),
+            }],
+        )
+        .await
+        .unwrap();
+
+    let sessions = storage.list_chat_sessions(10).await.unwrap();
+    assert_eq!(
+        sessions.iter().map(|s| s.id).collect::<Vec<_>>(),
+        vec![first, second]
+    );
+    assert_eq!(sessions[0].title, "What did Acme decide?");
+    assert_eq!(sessions[0].message_count, 2);
+
+    let messages = storage.get_chat_messages(first).await.unwrap();
+    assert_eq!(messages[0].role, "user");
+    assert_eq!(messages[1].sources[0].email_id, 7);
+    assert!(messages[1].sources[0].cited);
+    assert!(messages[0].trace.is_empty());
+    assert_eq!(messages[1].trace[0].tool, "search");
+    assert_eq!(messages[1].trace[0].arguments["query"], "Acme decision");
+    assert_eq!(messages[1].unsupported[0].email_id, 7);
+    assert_eq!(messages[1].unsupported[0].claim, "They are going ahead.");
+
+    assert!(storage
+        .add_chat_message(999, "user", "Hello?", &[], &[], &[])
+        .await
+        .is_err());
+    assert!(storage.delete_chat_session(first).await.unwrap());
+    assert!(!storage.delete_chat_session(first).await.unwrap());
+    assert!(storage.get_chat_messages(first).await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn experiments_sample_extracted_emails_and_round_trip() {
+    let (_dir, storage) = open().await;
+    let extracted = storage
+        .save_email(&email("e1", "Launch", "Shipping Friday."))
+        .await
+        .unwrap();
+    storage.save_facts(&facts(extracted)).await.unwrap();
+    let trashed = storage
+        .save_email(&email("e2", "Old", "Ignore."))
+        .await
+        .unwrap();
+    storage.save_facts(&facts(trashed)).await.unwrap();
+    storage.trash_emails(&[trashed]).await.unwrap();
+    storage
+        .save_email(&email("e3", "Pending", "Not extracted yet."))
+        .await
+        .unwrap();
+    assert_eq!(
+        storage.sample_extracted_email_ids(10).await.unwrap(),
+        vec![extracted]
+    );
+
+    let variant = |label: &str, model: Option<&str>| ExperimentVariant {
+        label: label.into(),
+        prompt_id: None,
+        model: model.map(Into::into),
+        ..Default::default()
+    };
+    let outcome = |urgency: &str| ExperimentOutcome {
+        fields: [("urgency".to_string(), serde_json::json!(urgency))].into(),
+        confidence: Some(0.8),
+        prompt_tokens: 900,
+        completion_tokens: 150,
+        duration_ms: 1200,
+        error: None,
+    };
+    let comparison = ExperimentComparison {
+        compared: 1,
+        field_agreement: [("urgency".to_string(), 0.0)].into(),
+        agreement: 0.0,
+        ..Default::default()
+    };
+    let saved = storage
+        .save_experiment(
+            "Stricter urgency",
+            &variant("Current", None),
+            &variant("Candidate", Some("llama3.1:70b")),
+            &[ExperimentEmail {
+                email_id: extracted,
+                subject: "Launch".into(),
+                a: outcome("low"),
+                b: outcome("high"),
+                disagreements: vec!["urgency".into()],
+            }],
+            &comparison,
+        )
+        .await
+        .unwrap();
+
+    let listed = storage.list_experiments(10).await.unwrap();
+    assert_eq!(listed.len(), 1);
+    assert_eq!(listed[0].name, "Stricter urgency");
+    assert!(listed[0].emails.is_empty());
+    assert_eq!(listed[0].comparison.compared, 1);
+
+    let loaded = storage.get_experiment(saved.id).await.unwrap().unwrap();
+    assert_eq!(loaded.variant_b.model.as_deref(), Some("llama3.1:70b"));
+    assert_eq!(loaded.emails[0].b.fields["urgency"], "high");
+    assert_eq!(loaded.emails[0].disagreements, vec!["urgency"]);
+    assert_eq!(loaded.comparison.field_agreement["urgency"], 0.0);
+
+    assert!(storage.delete_experiment(saved.id).await.unwrap());
+    assert!(!storage.delete_experiment(saved.id).await.unwrap());
+    assert!(storage.get_experiment(saved.id).await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn project_retention_expires_only_that_projects_old_mail() {
+    let (_dir, storage) = open().await;
+    let now = Utc::now();
+    let mut ids = Vec::new();
+    for (entry_id, project, age_days) in [
+        ("old", "Apollo", 40),
+        ("recent", "Apollo", 10),
+        ("other", "Zeus", 400),
+    ] {
+        let mut old = email(entry_id, entry_id, "Body");
+        old.received_at = now - Duration::days(age_days);
+        let id = storage.save_email(&old).await.unwrap();
+        let mut saved = facts(id);
+        saved.client_or_project.name = project.into();
+        storage.save_facts(&saved).await.unwrap();
+        ids.push(id);
+    }
+    assert!(storage
+        .list_expired_project_emails(now)
+        .await
+        .unwrap()
+        .is_empty());
+
+    let mut settings = ProjectSettings::new("apollo");
+    settings.retention_days = Some(30);
+    storage.set_project_settings(&settings).await.unwrap();
+    assert_eq!(
+        storage.list_expired_project_emails(now).await.unwrap(),
+        vec![ids[0]]
+    );
+    assert_eq!(
+        storage.get_email_project(ids[2]).await.unwrap().as_deref(),
+        Some("Zeus")
+    );
+}
+
+#[tokio::test]
+async fn replied_senders_are_known() {
+    let (_dir, storage) = open().await;
+    let mut update = email("n1", "Weekly update", "News.");
+    update.sender = "news@vendor.example".into();
+    update.conversation_id = Some("c1".into());
+    update.received_at = Utc::now() - Duration::hours(2);
+    storage.save_email(&update).await.unwrap();
+    assert!(!storage.has_replied_to("news@vendor.example").await.unwrap());
+
+    let mut reply = email("s1", "Re: Weekly update", "Thanks, can we talk?");
+    reply.folder = "Sent Items".into();
+    reply.conversation_id = Some("c1".into());
+    reply.sent_at = Utc::now() - Duration::hours(1);
+    storage.save_email(&reply).await.unwrap();
+    assert!(storage.has_replied_to("News@Vendor.example").await.unwrap());
+    assert!(!storage.has_replied_to("alice@example.com").await.unwrap());
+}
+
+#[tokio::test]
+async fn issue_tickets_are_reserved_before_filing() {
+    let (_dir, storage) = open().await;
+    let id = storage
+        .save_email(&email("t1", "Vendor delay", "The vendor slipped."))
+        .await
+        .unwrap();
+    let stale_after = Duration::minutes(5);
+    let reserve = |hash: &'static str, position| {
+        let storage = &storage;
+        async move {
+            storage
+                .reserve_issue_ticket(
+                    id,
+                    IssueKind::Risk,
+                    position,
+                    hash,
+                    TicketTracker::Jira,
+                    stale_after,
+                )
+                .await
+                .unwrap()
+        }
+    };
+
+    // A second request while the first is talking to the tracker is refused,
+    // and a failed attempt frees the item again.
+    let first = reserve("abc", 0).await.unwrap();
+    assert_eq!(reserve("abc", 0).await, None);
+    storage.release_issue_ticket(first).await.unwrap();
+    let second = reserve("abc", 0).await.unwrap();
+    assert!(storage.list_issue_tickets().await.unwrap().is_empty());
+
+    let ticket = storage
+        .complete_issue_ticket(second, "OPS-7", "https://jira/OPS-7")
+        .await
+        .unwrap();
+    assert_eq!(ticket.ticket_key, "OPS-7");
+    assert_eq!(reserve("abc", 0).await, None);
+
+    // Found by its hash wherever re-extraction moved it, but another item
+    // now at its old position is not.
+    let found = storage
+        .get_issue_ticket(id, IssueKind::Risk, 3, "abc", TicketTracker::Jira)
+        .await
+        .unwrap();
+    assert_eq!(found.map(|t| t.ticket_key).as_deref(), Some("OPS-7"));
+    assert!(storage
+        .get_issue_ticket(id, IssueKind::Risk, 0, "def", TicketTracker::Jira)
+        .await
+        .unwrap()
+        .is_none());
+    assert!(reserve("def", 0).await.is_some());
+    assert_eq!(storage.list_issue_tickets().await.unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn scan_checkpoints_are_read_per_folder() {
+    let (_dir, storage) = open().await;
+    let now = Utc::now();
+    for (folder, processed) in [("Inbox", 3), ("Sent Items", 5)] {
+        storage
+            .save_scan_checkpoint(&ScanCheckpoint {
+                folder: folder.into(),
+                last_received_at: now,
+                processed,
+                total: 10,
+            })
+            .await
+            .unwrap();
+    }
+
+    let sent = storage
+        .get_scan_checkpoint("Sent Items")
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!((sent.folder.as_str(), sent.processed), ("Sent Items", 5));
+    assert!(storage
+        .get_scan_checkpoint("Archive")
+        .await
+        .unwrap()
+        .is_none());
+
+    storage.clear_scan_checkpoints().await.unwrap();
+    assert!(storage
+        .get_scan_checkpoint("Inbox")
+        .await
+        .unwrap()
+        .is_none());
+}
+
+fn in_thread(
+    entry_id: &str,
+    folder: &str,
+    conversation_id: &str,
+    sent_at: chrono::DateTime<Utc>,
+) -> Email {
+    let mut message = email(entry_id, "Budget", "Can you approve the budget?");
+    message.folder = folder.into();
+    message.conversation_id = Some(conversation_id.into());
+    message.sent_at = sent_at;
+    message.received_at = sent_at;
+    message
+}
+
+#[tokio::test]
+async fn replies_from_sent_items_answer_earlier_mail_of_the_thread() {
+    let (_dir, storage) = open().await;
+    let start = Utc::now() - Duration::hours(5);
+    let hours = |h| start + Duration::hours(h);
+    let asked = storage
+        .save_email(&in_thread("r1", "Inbox", "thread", hours(0)))
+        .await
+        .unwrap();
+    let other_thread = storage
+        .save_email(&in_thread("r2", "Inbox", "other", hours(0)))
+        .await
+        .unwrap();
+    for id in [asked, other_thread] {
+        let mut needs_reply = facts(id);
+        needs_reply.needs_response = true;
+        storage.save_facts(&needs_reply).await.unwrap();
+    }
+
+    // Only a reply sent after the email answers it.
+    storage
+        .save_email(&in_thread("r3", "Sent Items", "thread", hours(-1)))
+        .await
+        .unwrap();
+    assert_eq!(storage.link_responses("thread").await.unwrap(), 0);
+
+    // The first reply after it is the one that counts, once.
+    for (entry_id, at) in [("r4", 2), ("r5", 3)] {
+        storage
+            .save_email(&in_thread(entry_id, "Sent Items", "thread", hours(at)))
+            .await
+            .unwrap();
+    }
+    assert_eq!(storage.link_responses("thread").await.unwrap(), 1);
+    assert_eq!(storage.link_responses("thread").await.unwrap(), 0);
+
+    let answered = storage.get_email_with_facts(asked).await.unwrap().unwrap();
+    assert_eq!(answered["needs_response"], false);
+    assert_eq!(answered["response_latency_secs"], 2 * 3600);
+    let untouched = storage
+        .get_email_with_facts(other_thread)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(untouched["needs_response"], true);
+    assert!(untouched["responded_at"].is_null());
+
+    // Facts extracted again after the reply stay answered.
+    let mut reextracted = facts(asked);
+    reextracted.needs_response = true;
+    storage.save_facts(&reextracted).await.unwrap();
+    let answered = storage.get_email_with_facts(asked).await.unwrap().unwrap();
+    assert_eq!(answered["needs_response"], false);
+}
+
+#[tokio::test]
+async fn vip_senders_raise_the_priority_of_their_mail() {
+    let (_dir, storage) = open().await;
+    let since = Utc::now() - Duration::hours(1);
+    let mut urgent = email("p1", "Outage", "The site is down.");
+    urgent.sender = "ops@example.com".into();
+    let urgent = storage.save_email(&urgent).await.unwrap();
+    let mut question = email("p2", "Lunch?", "Free on Friday?");
+    question.sender = "Boss@Example.com".into();
+    question.received_at = Utc::now() - Duration::minutes(5);
+    let question = storage.save_email(&question).await.unwrap();
+    for (id, urgency) in [(urgent, Urgency::High), (question, Urgency::Medium)] {
+        let mut needs_reply = facts(id);
+        needs_reply.urgency = urgency;
+        needs_reply.needs_response = true;
+        storage.save_facts(&needs_reply).await.unwrap();
+    }
+    let order = |emails: Vec<serde_json::Value>| -> Vec<(i64, i64)> {
+        emails
+            .iter()
+            .map(|e| (e["id"].as_i64().unwrap(), e["priority"].as_i64().unwrap()))
+            .collect()
+    };
+    assert_eq!(
+        order(storage.get_missed_since(since).await.unwrap()),
+        vec![(urgent, 3), (question, 2)]
+    );
+
+    storage.add_vip_sender("boss@example.com").await.unwrap();
+    assert!(storage.is_vip_sender(" BOSS@example.com ").await.unwrap());
+    assert_eq!(
+        order(storage.get_missed_since(since).await.unwrap()),
+        vec![(question, 4), (urgent, 3)]
+    );
+    let boss = storage
+        .get_email_with_facts(question)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(boss["vip"], true);
+}
@@ -1,9 +1,33 @@
 import { useState, useEffect } from 'react'
 import { invoke } from '@tauri-apps/api/core'
 import { listen } from '@tauri-apps/api/event'
-import { Mail, Search, Settings, Share2, LayoutDashboard } from 'lucide-react'
+import { Mail, Search, Settings, Share2, LayoutDashboard, Download, Ticket, Puzzle, Lock, Square } from 'lucide-react'
 import { SentimentChart } from './components/SentimentChart'
+import { QueuePanel } from './components/QueuePanel'
+import { AlertsPanel } from './components/AlertsPanel'
+import { SelfInsightsPanel } from './components/SelfInsightsPanel'
+import { TopicSummaryPanel } from './components/TopicSummaryPanel'
+import { AskNoodlePanel } from './components/AskNoodlePanel'
 import { EntityGraph } from './components/EntityGraph'
+import { OrgSuggestions } from './components/OrgSuggestions'
+import { VipSenders } from './components/VipSenders'
+import { CategoryRules } from './components/CategoryRules'
+import { NewsletterPanel } from './components/NewsletterPanel'
+import { MeetingsPanel } from './components/MeetingsPanel'
+import { WelcomeBackPanel } from './components/WelcomeBackPanel'
+import { SenderProfileDialog } from './components/SenderProfileDialog'
+import { BulkActionBar } from './components/BulkActionBar'
+import { TrashPanel } from './components/TrashPanel'
+import { AnnotationEditor } from './components/AnnotationEditor'
+import { CommandPalette } from './components/CommandPalette'
+import { IssueClusters } from './components/IssueClusters'
+import { ShareMenu } from './components/ShareMenu'
+import { ProjectReportPanel } from './components/ProjectReportPanel'
+import { VectorSnapshots } from './components/VectorSnapshots'
+import { PromptLibrary } from './components/PromptLibrary'
+import { ExtractionExperiments } from './components/ExtractionExperiments'
+import { LOCALES, formatDate, formatDateTime, formatTime, setLocale } from './locale'
+import { EVENTS } from './events'
 import { clsx, type ClassValue } from 'clsx'
 import { twMerge } from 'tailwind-merge'
 
@@ -11,28 +35,193 @@ function cn(...inputs: ClassValue[]) {
     return twMerge(clsx(inputs))
 }
 
+// Reply latency, in the largest unit that keeps it readable.
+function formatLatency(secs: number) {
+    if (secs < 3600) return `${Math.max(1, Math.round(secs / 60))}m`
+    if (secs < 86400) return `${Math.round(secs / 3600)}h`
+    return `${Math.round(secs / 86400)}d`
+}
+
+// What settings reads return for a secret that is set; see SECRET_MASK in the core config.
+const SECRET_MASK = '••••••••'
+
 function App() {
     const [hasLoadedInitialEmails, setHasLoadedInitialEmails] = useState(false)
     const [emails, setEmails] = useState<any[]>([])
     const [searchQuery, setSearchQuery] = useState('')
+    // Narrows searches to one thread or sender until cleared.
+    const [searchScope, setSearchScope] = useState<{ kind: 'thread' | 'sender' | 'attachments', value: string, label: string } | null>(null)
+    const [searchSuggestions, setSearchSuggestions] = useState<any[]>([])
     const [activeTab, setActiveTab] = useState('dashboard')
     const [stats, setStats] = useState<any>({ total_emails: 0, sentiments: [] })
     const [graphData, setGraphData] = useState<any>({ nodes: [], links: [] })
+    const [graphExportType, setGraphExportType] = useState('')
+    const [exportPassword, setExportPassword] = useState('')
     const [isLoading, setIsLoading] = useState(false)
     const [logs, setLogs] = useState<any[]>([])
     const [config, setConfig] = useState<any>({
         ollama_url: 'http://localhost:11434',
         model_name: 'llama3',
+        context_window: '8192',
+        model_context_windows: '',
+        openai_url: 'https://api.openai.com/v1',
+        fallback_providers: '',
+        provider_timeout_secs: '120',
         sync_interval: '2',
         history_days: '90',
+        folder_modes: '',
         provider_type: 'ollama',
         api_key: '',
         confirm_exit: 'true',
+        start_minimized: 'false',
+        quick_search_shortcut: 'CommandOrControl+Shift+N',
         lemonade_url: 'http://localhost:8000/v1',
-        foundry_url: 'http://localhost:5000/v1'
+        foundry_url: 'http://localhost:5000/v1',
+        timezone: '',
+        locale: 'en-US',
+        digest_notifications: 'false',
+        digest_time: '08:00',
+        delegation_mode: 'false',
+        newsletter_roundup: 'true',
+        meeting_follow_ups: 'true',
+        quiet_hours_start: '',
+        quiet_hours_end: '',
+        quiet_hours_pause_sync: 'false',
+        meeting_pause_ai: 'false',
+        battery_sync_mode: 'throttle',
+        low_impact_mode: 'false',
+        low_impact_emails_per_minute: '6',
+        idle_extraction: 'false',
+        idle_threshold_mins: '10',
+        max_extraction_body_chars: '12000',
+        max_embedding_body_chars: '8000',
+        attachment_max_size_mb: '25',
+        attachment_mime_types: 'application/pdf, text/*, application/msword, application/vnd.ms-excel, application/vnd.ms-powerpoint, application/vnd.openxmlformats-officedocument.*',
+        maintenance_hour: '3',
+        maintenance_vacuum: 'false',
+        vector_snapshots: 'false',
+        vector_snapshot_retention: '3',
+        update_check: 'true',
+        update_channel: 'stable',
+        legal_hold: 'false',
+        self_insights: 'false',
+        rerank_results: 'false',
+        chat_rerun_retrieval: 'true',
+        chat_verify_citations: 'true',
+        jira_url: '',
+        jira_email: '',
+        jira_api_token: '',
+        jira_project_key: '',
+        jira_issue_type: 'Task',
+        azure_devops_url: '',
+        azure_devops_token: '',
+        azure_devops_project: '',
+        azure_devops_work_item_type: 'Task',
+        companion_api: 'false',
+        companion_api_port: '4870',
+        companion_api_token: '',
+        app_lock: 'false',
+        app_lock_idle_mins: '5'
     })
     const [availableModels, setAvailableModels] = useState<string[]>([])
     const [showExitConfirm, setShowExitConfirm] = useState(false)
+    const [profileAddress, setProfileAddress] = useState<string | null>(null)
+    const [selectedIds, setSelectedIds] = useState<number[]>([])
+    const [annotatingId, setAnnotatingId] = useState<number | null>(null)
+    const [showPalette, setShowPalette] = useState(false)
+    const [trashVersion, setTrashVersion] = useState(0)
+    const [scanProgress, setScanProgress] = useState<any>(null)
+    const [canResumeScan, setCanResumeScan] = useState(false)
+    const [profiles, setProfiles] = useState<any[]>([])
+    const [autostartEnabled, setAutostartEnabled] = useState(false)
+    const [profileName, setProfileName] = useState('')
+    const [updateInfo, setUpdateInfo] = useState<any>(null)
+    const [changeLogs, setChangeLogs] = useState<Record<number, any[]>>({})
+    const [locked, setLocked] = useState(false)
+    const [savedCredentials, setSavedCredentials] = useState<any[]>([])
+    const [fallbackKeys, setFallbackKeys] = useState<Record<string, string>>({})
+
+    const dismissReview = async (emailId: number) => {
+        try {
+            await invoke('dismiss_fact_review', { emailId })
+            setEmails((current) => current.map((e) => e.id === emailId ? { ...e, review_reason: null } : e))
+        } catch (e) {
+            addLog(`Failed to mark facts as reviewed: ${e}`, 'error')
+        }
+    }
+
+    // Mail loaded before the lock still has its bodies; drop it.
+    const onLocked = () => {
+        setLocked(true)
+        setEmails([])
+        setChangeLogs({})
+        setHasLoadedInitialEmails(false)
+    }
+
+    const unlockApp = async () => {
+        try {
+            if (await invoke<boolean>('unlock_app')) {
+                setLocked(false)
+                if (activeTab === 'emails') {
+                    handleSearch()
+                    setHasLoadedInitialEmails(true)
+                }
+            }
+        } catch (e) {
+            addLog(`Failed to unlock: ${e}`, 'error')
+        }
+    }
+
+    const fetchSavedCredentials = () => {
+        invoke<any[]>('list_saved_credentials')
+            .then(setSavedCredentials)
+            .catch((e) => addLog(`Failed to list saved credentials: ${e}`, 'error'))
+    }
+
+    const saveApiKey = async (provider: string) => {
+        try {
+            await invoke('save_api_key', { provider, key: fallbackKeys[provider] ?? '' })
+            setFallbackKeys({ ...fallbackKeys, [provider]: '' })
+            addLog(`Saved the ${provider} key`)
+        } catch (e) {
+            addLog(`Failed to save the ${provider} key: ${e}`, 'error')
+        }
+        fetchSavedCredentials()
+    }
+
+    const deleteApiKey = async (provider: string) => {
+        try {
+            await invoke('delete_api_key', { provider })
+            addLog(`Removed the saved ${provider} key`)
+        } catch (e) {
+            addLog(`Failed to remove the saved ${provider} key: ${e}`, 'error')
+        }
+        fetchSavedCredentials()
+    }
+
+    const lockApp = async () => {
+        try {
+            await invoke('lock_app')
+        } catch (e) {
+            addLog(`Failed to lock: ${e}`, 'error')
+        }
+    }
+
+    const verifyLegalHold = async () => {
+        try {
+            const result: any = await invoke('verify_legal_hold')
+            if (result.intact) {
+                addLog(`Legal hold archive intact: ${result.records} emails in ${result.segments} file(s)`)
+                if (result.torn > 0) {
+                    addLog(`Legal hold archive has ${result.torn} partly written record(s) from an interrupted write; no archived email is missing`, 'warn')
+                }
+            } else {
+                addLog(`Legal hold archive broken at record ${result.first_broken}: ${result.problem}`, 'error')
+            }
+        } catch (e) {
+            addLog(`Failed to verify the legal hold archive: ${e}`, 'error')
+        }
+    }
 
     const addLog = async (message: string, type: 'info' | 'error' | 'warn' = 'info') => {
         const timestamp = new Date().toISOString()
@@ -66,34 +255,101 @@ function App() {
 
     const fetchConfig = async () => {
         try {
-            const ollama = await invoke('get_config', { key: 'ollama_url' })
-            const model = await invoke('get_config', { key: 'model_name' })
-            const interval = await invoke('get_config', { key: 'sync_interval' })
-            const history = await invoke('get_config', { key: 'history_days' })
-            const provider = await invoke('get_config', { key: 'provider_type' })
-            const apiKey = await invoke('get_config', { key: 'api_key' })
-            const confirm = await invoke('get_config', { key: 'confirm_exit' })
-            const lemonade = await invoke('get_config', { key: 'lemonade_url' })
-            const foundry = await invoke('get_config', { key: 'foundry_url' })
-
-            if (ollama || model || interval || history || provider || apiKey || confirm || lemonade || foundry) {
-                setConfig({
-                    ollama_url: ollama || config.ollama_url,
-                    model_name: model || config.model_name,
-                    sync_interval: interval || config.sync_interval,
-                    history_days: history || config.history_days,
-                    provider_type: provider || 'ollama',
-                    api_key: apiKey || '',
-                    confirm_exit: confirm || 'true',
-                    lemonade_url: lemonade || 'http://localhost:8000/v1',
-                    foundry_url: foundry || 'http://localhost:5000/v1'
-                })
+            const stored: any = await invoke('get_all_config')
+            const values: any = {}
+            for (const [key, value] of Object.entries(stored)) {
+                values[key] = value === null ? '' : String(value)
             }
+            setConfig((prev: any) => ({ ...prev, ...values, model_name: values.model_name || prev.model_name }))
+            setLocale(values.locale)
         } catch (e) {
             addLog(`Failed to fetch config: ${e}`, 'error')
         }
     }
 
+    const fetchProfiles = async () => {
+        try {
+            setProfiles(await invoke('list_settings_profiles') as any[])
+        } catch (e) {
+            addLog(`Failed to fetch settings profiles: ${e}`, 'error')
+        }
+    }
+
+    const updateAutostart = async (enabled: boolean, startMinimized: boolean) => {
+        try {
+            await invoke('set_autostart', { enabled, startMinimized })
+            setAutostartEnabled(enabled)
+        } catch (e) {
+            addLog(`Failed to update startup settings: ${e}`, 'error')
+        }
+    }
+
+    const reportSkipped = (skipped: string[]) => {
+        if (skipped.length > 0) {
+            addLog(`Skipped settings not supported here: ${skipped.join(', ')}`, 'warn')
+        }
+    }
+
+    const download = (blob: Blob, fileName: string) => {
+        const link = document.createElement('a')
+        link.href = URL.createObjectURL(blob)
+        link.download = fileName
+        link.click()
+        URL.revokeObjectURL(link.href)
+    }
+
+    const exportSettings = async () => {
+        try {
+            const bundle = await invoke('export_settings')
+            download(new Blob([JSON.stringify(bundle, null, 2)], { type: 'application/json' }), 'noodle-settings.json')
+            addLog('Settings exported')
+        } catch (e) {
+            addLog(`Failed to export settings: ${e}`, 'error')
+        }
+    }
+
+    const exportGraph = async (format: string) => {
+        try {
+            const file: any = await invoke('export_graph', {
+                format,
+                entityType: graphExportType || null,
+                password: exportPassword || null,
+            })
+            const content = file.encrypted
+                ? Uint8Array.from(atob(file.content), (c) => c.charCodeAt(0))
+                : file.content
+            download(new Blob([content], { type: file.mime_type }), file.file_name)
+            addLog(`Exported graph (${file.nodes} nodes, ${file.links} links${file.encrypted ? ', encrypted' : ''})`)
+        } catch (e) {
+            addLog(`Failed to export graph: ${e}`, 'error')
+        }
+    }
+
+    const openEncryptedExport = async (file: File) => {
+        try {
+            const bytes = new Uint8Array(await file.arrayBuffer())
+            let binary = ''
+            bytes.forEach((b) => { binary += String.fromCharCode(b) })
+            const entries: any[] = await invoke('open_encrypted_export', { data: btoa(binary), password: exportPassword })
+            for (const entry of entries) {
+                download(new Blob([entry.content]), entry.name)
+            }
+            addLog(`Decrypted ${entries.length} file(s) from ${file.name}`)
+        } catch (e) {
+            addLog(`Failed to open ${file.name}: ${e}`, 'error')
+        }
+    }
+
+    const importSettings = async (file: File) => {
+        try {
+            const bundle = JSON.parse(await file.text())
+            reportSkipped(await invoke('import_settings', { bundle }) as string[])
+            addLog(`Imported settings from ${file.name}`)
+        } catch (e) {
+            addLog(`Failed to import settings: ${e}`, 'error')
+        }
+    }
+
     const startSync = async () => {
         addLog('Requesting Outlook sync...')
         setIsLoading(true)
@@ -109,11 +365,74 @@ function App() {
         }
     }
 
+    const stopSync = async () => {
+        addLog('Stopping sync...')
+        try {
+            await invoke('stop_sync')
+            setIsLoading(false)
+        } catch (error: any) {
+            addLog(`Failed to stop sync: ${error}`, 'error')
+        }
+    }
+
     useEffect(() => {
         fetchStats()
         fetchConfig()
+        fetchProfiles()
+        fetchSavedCredentials()
+        invoke('get_autostart')
+            .then((autostart: any) => setAutostartEnabled(autostart.enabled))
+            .catch(() => { })
+        invoke('get_scan_state')
+            .then((checkpoints: any) => setCanResumeScan(checkpoints.length > 0))
+            .catch(() => { })
+        invoke('get_app_lock_status')
+            .then((status: any) => status.locked && onLocked())
+            .catch(() => { })
+        const unlistenLock = listen(EVENTS.appLocked, () => onLocked())
+
+        const unlistenOpenSearch = listen(EVENTS.openSearch, (event: any) => {
+            setSearchQuery(event.payload.query)
+            setActiveTab('emails')
+            setHasLoadedInitialEmails(true)
+            invoke('search_emails', { query: event.payload.query })
+                .then((results) => setEmails(results as any[]))
+                .catch((e) => addLog(`Search failed: ${e}`, 'error'))
+        })
+
+        // noodle://email/<id> opens the email; noodle://project/<name> lists its mail.
+        const navigate = (link: any) => {
+            if (link.kind === 'email') {
+                invoke('open_email_window', { id: link.id })
+                    .catch((e) => addLog(`Failed to open email ${link.id}: ${e}`, 'error'))
+            } else if (link.kind === 'project') {
+                setSearchQuery(link.name)
+                setActiveTab('emails')
+                setHasLoadedInitialEmails(true)
+                invoke('search_emails', { query: link.name })
+                    .then((results) => setEmails(results as any[]))
+                    .catch((e) => addLog(`Search failed: ${e}`, 'error'))
+            }
+        }
+        invoke('take_deep_link')
+            .then((link) => link && navigate(link))
+            .catch(() => { })
+        const unlistenNavigate = listen(EVENTS.navigate, (event: any) => navigate(event.payload))
+
+        const unlistenUpdate = listen(EVENTS.updateAvailable, (event: any) => {
+            setUpdateInfo(event.payload)
+        })
+
+        const unlistenConfig = listen(EVENTS.configChanged, () => {
+            fetchConfig()
+        })
+
+        const unlistenScan = listen(EVENTS.scanProgress, (event: any) => {
+            setScanProgress(event.payload)
+            setCanResumeScan(event.payload.resumable)
+        })
 
-        const unlistenPromise = listen('noodle://log', (event: any) => {
+        const unlistenPromise = listen(EVENTS.log, (event: any) => {
             const { message, level } = event.payload
             const entry = {
                 timestamp: new Date().toISOString(),
@@ -124,7 +443,7 @@ function App() {
             setLogs(prev => [entry, ...prev].slice(0, 1000))
         })
 
-        const unlistenExit = listen('noodle://show-exit-confirm', () => {
+        const unlistenExit = listen(EVENTS.showExitConfirm, () => {
             setShowExitConfirm(true)
         })
 
@@ -133,6 +452,10 @@ function App() {
                 e.preventDefault()
                 invoke('request_exit').catch(err => console.error(err))
             }
+            if ((e.ctrlKey || e.metaKey) && e.key.toLowerCase() === 'k') {
+                e.preventDefault()
+                setShowPalette(open => !open)
+            }
         }
         window.addEventListener('keydown', handleKeyDown)
 
@@ -148,14 +471,73 @@ function App() {
         return () => {
             unlistenPromise.then(unlisten => unlisten())
             unlistenExit.then(unlisten => unlisten())
+            unlistenScan.then(unlisten => unlisten())
+            unlistenConfig.then(unlisten => unlisten())
+            unlistenOpenSearch.then(unlisten => unlisten())
+            unlistenNavigate.then(unlisten => unlisten())
+            unlistenUpdate.then(unlisten => unlisten())
+            unlistenLock.then(unlisten => unlisten())
             window.removeEventListener('keydown', handleKeyDown)
         }
     }, [])
 
-    const handleSearch = async () => {
-        addLog(`Searching for: ${searchQuery}`)
+    const rerunEmail = async (command: 'reprocess_email' | 'reembed_email', id: number) => {
+        try {
+            const refreshed: any = await invoke(command, { id })
+            setEmails(prev => prev.map(e => e.id === id ? { ...e, ...refreshed } : e))
+            addLog(command === 'reprocess_email' ? `Reprocessed email ${id}` : `Re-embedded email ${id}`)
+        } catch (e) {
+            addLog(`Failed to rerun email ${id}: ${e}`, 'error')
+        }
+    }
+
+    const toggleSelected = (id: number) => {
+        setSelectedIds(prev => prev.includes(id) ? prev.filter(i => i !== id) : [...prev, id])
+    }
+
+    const onBulkDone = (result: any, change: { triage?: string, project?: string }) => {
+        const ids = new Set(selectedIds)
+        if (result.operation === 'purge' || result.operation === 'trash') {
+            setEmails(prev => prev.filter(e => !ids.has(e.id)))
+            setTrashVersion(v => v + 1)
+        } else if (change.triage) {
+            const waitingOn = ({ needs_response: 'me', waiting: 'them', done: 'none' } as Record<string, string>)[change.triage]
+            setEmails(prev => prev.map(e => ids.has(e.id) && e.waiting_on ? { ...e, needs_response: change.triage === 'needs_response', waiting_on: waitingOn } : e))
+        } else if (change.project) {
+            setEmails(prev => prev.map(e => ids.has(e.id) && e.client_or_project ? { ...e, client_or_project: { name: change.project, confidence: 1 } } : e))
+        }
+        const failed = result.failed.length > 0 ? `, ${result.failed.length} failed` : ''
+        addLog(`${result.operation.replace('_', ' ')}: ${result.updated} of ${result.requested} emails updated${failed}`)
+        setSelectedIds(result.failed)
+    }
+
+    const toggleChanges = async (id: number) => {
+        if (changeLogs[id]) {
+            setChangeLogs(prev => {
+                const next = { ...prev }
+                delete next[id]
+                return next
+            })
+            return
+        }
+        try {
+            const detail: any = await invoke('get_email', { id })
+            setChangeLogs(prev => ({ ...prev, [id]: detail.changes }))
+        } catch (e) {
+            addLog(`Failed to load changes for email ${id}: ${e}`, 'error')
+        }
+    }
+
+    const handleSearch = async (scope = searchScope) => {
+        addLog(scope ? `Searching ${scope.label} for: ${searchQuery}` : `Searching for: ${searchQuery}`)
         try {
-            const results = await invoke('search_emails', { query: searchQuery })
+            const results = scope?.kind === 'thread'
+                ? await invoke('search_in_thread', { conversationId: scope.value, query: searchQuery })
+                : scope?.kind === 'sender'
+                    ? await invoke('search_from_sender', { sender: scope.value, query: searchQuery })
+                    : scope?.kind === 'attachments'
+                        ? await invoke('search_attachments', { query: searchQuery })
+                        : await invoke('search_emails', { query: searchQuery })
             setEmails(results as any[])
             addLog(`Search returned ${(results as any[]).length} results`)
         } catch (error: any) {
@@ -163,6 +545,26 @@ function App() {
         }
     }
 
+    const searchTag = (tag: string) => {
+        setSearchQuery(`#${tag}`)
+        setSearchScope(null)
+        invoke('search_emails', { query: `#${tag}` })
+            .then((results) => setEmails(results as any[]))
+            .catch((e) => addLog(`Search failed: ${e}`, 'error'))
+    }
+
+    const scopeSearch = (scope: { kind: 'thread' | 'sender' | 'attachments', value: string, label: string } | null) => {
+        setSearchScope(scope)
+        handleSearch(scope)
+    }
+
+    const updateSearchQuery = (query: string) => {
+        setSearchQuery(query)
+        invoke<any[]>('get_search_suggestions', { prefix: query })
+            .then(setSearchSuggestions)
+            .catch(() => setSearchSuggestions([]))
+    }
+
     const handleTabChange = (tab: string) => {
         addLog(`Switching tab to: ${tab}`)
         setActiveTab(tab)
@@ -268,16 +670,52 @@ function App() {
                             <Search className="absolute left-3 top-1/2 -translate-y-1/2 w-4 h-4 text-zinc-500 group-focus-within:text-blue-400 transition-colors" />
                             <input
                                 type="text"
-                                placeholder="Search emails semantically..."
+                                placeholder={searchScope ? `Search ${searchScope.label}...` : 'Search emails semantically...'}
                                 className="w-full bg-zinc-900/50 border border-zinc-800 rounded-lg py-2 pl-10 pr-4 focus:outline-none focus:bg-zinc-900 focus:border-blue-500/50 text-sm transition-all placeholder:text-zinc-600"
                                 value={searchQuery}
-                                onChange={(e) => setSearchQuery(e.target.value)}
+                                list="search-suggestions"
+                                onFocus={() => updateSearchQuery(searchQuery)}
+                                onChange={(e) => updateSearchQuery(e.target.value)}
                                 onKeyDown={(e) => e.key === 'Enter' && handleSearch()}
                             />
+                            {!searchScope && searchQuery.trim() && (
+                                <button
+                                    onClick={() => scopeSearch({ kind: 'attachments', value: '', label: 'attachments' })}
+                                    title="Search only the text of attachments"
+                                    className="absolute right-2 top-1/2 -translate-y-1/2 text-[10px] text-zinc-400 bg-zinc-800/50 px-1.5 py-0.5 rounded border border-zinc-700 hover:text-indigo-300"
+                                >
+                                    Attachments only
+                                </button>
+                            )}
+                            {searchScope && (
+                                <button
+                                    onClick={() => scopeSearch(null)}
+                                    title="Search all emails"
+                                    className="absolute right-2 top-1/2 -translate-y-1/2 text-[10px] text-blue-300 bg-blue-500/10 px-1.5 py-0.5 rounded border border-blue-500/20 hover:bg-blue-500/20"
+                                >
+                                    {searchScope.label} ×
+                                </button>
+                            )}
+                            <datalist id="search-suggestions">
+                                {searchSuggestions.map((suggestion) => (
+                                    <option key={suggestion.query} value={suggestion.query}>
+                                        {suggestion.count > 1 ? `${suggestion.count} searches` : ''}
+                                    </option>
+                                ))}
+                            </datalist>
                         </div>
                     </div>
 
-                    <div className="flex items-center justify-end min-w-[200px]">
+                    <div className="flex items-center justify-end gap-2 min-w-[200px]">
+                        {isLoading && (
+                            <button
+                                onClick={stopSync}
+                                title="Stop sync"
+                                className="p-2 text-zinc-400 hover:text-white hover:bg-zinc-800 rounded-lg transition-colors"
+                            >
+                                <Square className="w-4 h-4" />
+                            </button>
+                        )}
                         <button
                             onClick={startSync}
                             disabled={isLoading}
@@ -289,12 +727,16 @@ function App() {
                             {isLoading ? (
                                 <>
                                     <div className="w-4 h-4 border-2 border-current border-t-transparent rounded-full animate-spin" />
-                                    <span>Syncing...</span>
+                                    <span>
+                                        {scanProgress && scanProgress.resumable
+                                            ? `Syncing ${scanProgress.folder} ${scanProgress.processed}/${scanProgress.total}`
+                                            : 'Syncing...'}
+                                    </span>
                                 </>
                             ) : (
                                 <>
                                     <div className={`w-2 h-2 rounded-full ${isLoading ? 'bg-zinc-500' : 'bg-green-500 animate-pulse'}`} />
-                                    <span>Sync Outlook</span>
+                                    <span>{canResumeScan ? 'Resume Sync' : 'Sync Outlook'}</span>
                                 </>
                             )}
                         </button>
@@ -304,6 +746,8 @@ function App() {
                 <main className="flex-1 overflow-auto p-8 scrollbar-thin scrollbar-thumb-zinc-800 scrollbar-track-transparent">
                     {activeTab === 'dashboard' && (
                         <div className="space-y-6 max-w-7xl mx-auto animate-in fade-in slide-in-from-bottom-4 duration-500">
+                            <WelcomeBackPanel onLog={addLog} />
+
                             <div className="grid grid-cols-1 md:grid-cols-2 lg:grid-cols-3 gap-6">
                                 <div className="lg:col-span-2 bg-zinc-900/40 border border-zinc-800/50 rounded-2xl p-1 overflow-hidden backdrop-blur-sm">
                                     <div className="p-4 border-b border-zinc-800/50 bg-zinc-900/20">
@@ -347,23 +791,87 @@ function App() {
                                 </div>
                             </div>
 
+                            <div className="flex justify-end -mb-4">
+                                <ShareMenu sources={[['Digest', { kind: 'digest' }]]} placeholder="Copy today's digest for chat…" onLog={addLog} />
+                            </div>
+
+                            <AlertsPanel />
+
+                            <QueuePanel />
+
+                            <NewsletterPanel onLog={addLog} />
+
+                            {config.meeting_follow_ups === 'true' && <MeetingsPanel onLog={addLog} />}
+
+                            {config.self_insights === 'true' && <SelfInsightsPanel />}
+
                             <div className="rounded-2xl border border-zinc-800/50 bg-zinc-900/40 backdrop-blur-sm overflow-hidden flex flex-col h-[500px]">
                                 <div className="p-4 border-b border-zinc-800/50 bg-zinc-900/20 flex justify-between items-center">
                                     <h3 className="font-medium text-zinc-300 flex items-center gap-2">
                                         <Share2 className="w-4 h-4 text-blue-400" />
                                         Entity Relationship Graph
                                     </h3>
-                                    <span className="text-xs text-zinc-500">Interactive Visualization</span>
+                                    <div className="flex items-center gap-2 text-xs">
+                                        <select
+                                            value={graphExportType}
+                                            onChange={(e) => setGraphExportType(e.target.value)}
+                                            className="bg-zinc-950 border border-zinc-800 rounded px-2 py-1 text-zinc-400"
+                                        >
+                                            <option value="">All types</option>
+                                            {[...new Set<string>(graphData.nodes.map((n: any) => n.type))].map((type) => (
+                                                <option key={type} value={type}>{type}</option>
+                                            ))}
+                                        </select>
+                                        <input
+                                            type="password"
+                                            value={exportPassword}
+                                            onChange={(e) => setExportPassword(e.target.value)}
+                                            placeholder="Password (optional)"
+                                            className="w-36 bg-zinc-950 border border-zinc-800 rounded px-2 py-1 text-zinc-400"
+                                        />
+                                        {[['graphml', 'GraphML'], ['dot', 'DOT'], ['json', 'JSON']].map(([format, label]) => (
+                                            <button
+                                                key={format}
+                                                onClick={() => exportGraph(format)}
+                                                className="px-2 py-1 rounded border border-zinc-800 text-zinc-400 hover:text-blue-400 hover:border-blue-500/50 transition-colors"
+                                            >
+                                                {label}
+                                            </button>
+                                        ))}
+                                    </div>
                                 </div>
                                 <div className="flex-1 relative">
                                     <EntityGraph nodes={graphData.nodes} links={graphData.links} />
                                 </div>
                             </div>
+
+                            <OrgSuggestions onChange={fetchStats} />
+
+                            <VipSenders />
+
+                            <CategoryRules onLog={addLog} />
+
+                            <IssueClusters trackers={[
+                                ...(config.jira_url ? ['jira'] : []),
+                                ...(config.azure_devops_url ? ['azure_devops'] : []),
+                            ]} />
+
+                            <ProjectReportPanel password={exportPassword} onLog={addLog} />
+                        </div>
+                    )}
+
+                    {activeTab === 'search' && (
+                        <div className="space-y-6">
+                            <AskNoodlePanel />
+                            <TopicSummaryPanel />
                         </div>
                     )}
 
                     {activeTab === 'emails' && (
                         <div className="grid grid-cols-1 gap-4 max-w-4xl mx-auto animate-in fade-in slide-in-from-bottom-8 duration-500">
+                            {selectedIds.length > 0 && (
+                                <BulkActionBar ids={selectedIds} onDone={onBulkDone} onClear={() => setSelectedIds([])} />
+                            )}
                             {emails.length === 0 ? (
                                 <div className="flex flex-col items-center justify-center py-32 text-zinc-500 border-2 border-dashed border-zinc-800 rounded-3xl bg-zinc-900/20">
                                     <Search className="w-12 h-12 mb-4 text-zinc-700" />
@@ -372,10 +880,33 @@ function App() {
                                 </div>
                             ) : (
                                 emails.map((email) => (
-                                    <div key={email.id} className="p-5 rounded-xl border border-zinc-800 bg-zinc-900/40 hover:bg-zinc-900/80 hover:border-zinc-700/80 transition-all cursor-pointer group shadow-sm hover:shadow-xl hover:shadow-black/50 hover:-translate-y-0.5 space-y-3">
+                                    <div key={email.result_type === 'attachment' ? `attachment-${email.attachment.id}` : email.id} className="p-5 rounded-xl border border-zinc-800 bg-zinc-900/40 hover:bg-zinc-900/80 hover:border-zinc-700/80 transition-all cursor-pointer group shadow-sm hover:shadow-xl hover:shadow-black/50 hover:-translate-y-0.5 space-y-3">
                                         <div className="flex justify-between items-start">
                                             <div className="flex-1 mr-4">
                                                 <div className="flex items-center gap-2 mb-1">
+                                                    {email.result_type !== 'attachment' && (
+                                                        <input
+                                                            type="checkbox"
+                                                            checked={selectedIds.includes(email.id)}
+                                                            onClick={(e) => e.stopPropagation()}
+                                                            onChange={() => toggleSelected(email.id)}
+                                                            title="Select for bulk actions"
+                                                            className="accent-indigo-500"
+                                                        />
+                                                    )}
+                                                    {email.result_type === 'attachment' && (
+                                                        <span
+                                                            title={email.explanation?.vector?.chunk}
+                                                            className="text-[10px] font-bold uppercase tracking-wider text-indigo-400 bg-indigo-500/10 px-1.5 py-0.5 rounded border border-indigo-500/20"
+                                                        >
+                                                            Attachment · {email.attachment.filename}
+                                                        </span>
+                                                    )}
+                                                    {email.result_type === 'email' && (
+                                                        <span className="text-[10px] font-bold uppercase tracking-wider text-sky-400 bg-sky-500/10 px-1.5 py-0.5 rounded border border-sky-500/20">
+                                                            Email
+                                                        </span>
+                                                    )}
                                                     {email.client_or_project?.name && (
                                                         <span className="text-[10px] font-bold uppercase tracking-wider text-zinc-500 bg-zinc-950 px-1.5 py-0.5 rounded border border-zinc-800">
                                                             {email.client_or_project.name}
@@ -386,18 +917,75 @@ function App() {
                                                             Action Required
                                                         </span>
                                                     )}
+                                                    {email.responded_at && (
+                                                        <span
+                                                            title={`Replied ${new Date(email.responded_at).toLocaleString()}`}
+                                                            className="text-[10px] font-bold uppercase tracking-wider text-green-500 bg-green-500/10 px-1.5 py-0.5 rounded border border-green-500/20"
+                                                        >
+                                                            Replied in {formatLatency(email.response_latency_secs)}
+                                                        </span>
+                                                    )}
                                                     {email.urgency?.toLowerCase() === 'high' && (
                                                         <span className="text-[10px] font-bold uppercase tracking-wider text-red-500 bg-red-500/10 px-1.5 py-0.5 rounded border border-red-500/20">
                                                             High Urgency
                                                         </span>
                                                     )}
+                                                    {email.change_count > 0 && (
+                                                        <button
+                                                            onClick={(e) => { e.stopPropagation(); toggleChanges(email.id) }}
+                                                            title={email.facts_stale ? 'Changed since its facts were extracted' : undefined}
+                                                            className="text-[10px] font-bold uppercase tracking-wider text-purple-400 bg-purple-500/10 px-1.5 py-0.5 rounded border border-purple-500/20"
+                                                        >
+                                                            Edited{email.facts_stale ? ' · facts stale' : ''}
+                                                        </button>
+                                                    )}
+                                                    {email.newsletter && (
+                                                        <span className="text-[10px] font-bold uppercase tracking-wider text-zinc-400 bg-zinc-500/10 px-1.5 py-0.5 rounded border border-zinc-500/20">
+                                                            Newsletter
+                                                        </span>
+                                                    )}
+                                                    {email.labels?.split(', ').map((label: string) => (
+                                                        <span key={label} className="text-[10px] font-bold uppercase tracking-wider text-teal-400 bg-teal-500/10 px-1.5 py-0.5 rounded border border-teal-500/20">
+                                                            {label}
+                                                        </span>
+                                                    ))}
+                                                    {email.annotation?.pinned && (
+                                                        <span title="Pinned" className="text-[10px] font-bold uppercase tracking-wider text-indigo-300 bg-indigo-500/10 px-1.5 py-0.5 rounded border border-indigo-500/20">
+                                                            Pinned
+                                                        </span>
+                                                    )}
+                                                    {email.annotation?.starred && (
+                                                        <span title="Starred" className="text-xs text-yellow-400">★</span>
+                                                    )}
+                                                    {email.annotation?.tags?.map((tag: string) => (
+                                                        <button
+                                                            key={tag}
+                                                            onClick={(e) => { e.stopPropagation(); searchTag(tag) }}
+                                                            title={`Search #${tag}`}
+                                                            className="text-[10px] font-bold tracking-wider text-indigo-400 bg-indigo-500/10 px-1.5 py-0.5 rounded border border-indigo-500/20"
+                                                        >
+                                                            #{tag}
+                                                        </button>
+                                                    ))}
+                                                    {email.review_reason && (
+                                                        <button
+                                                            onClick={(e) => { e.stopPropagation(); dismissReview(email.id) }}
+                                                            title={`${email.review_reason}. Click once you have checked the facts.`}
+                                                            className="text-[10px] font-bold uppercase tracking-wider text-orange-400 bg-orange-500/10 px-1.5 py-0.5 rounded border border-orange-500/20"
+                                                        >
+                                                            Review facts
+                                                        </button>
+                                                    )}
                                                 </div>
                                                 <h3 className="font-semibold text-lg text-zinc-200 group-hover:text-blue-400 transition-colors leading-tight">
                                                     {email.subject}
                                                 </h3>
+                                                {email.result_type === 'attachment' && email.explanation?.vector?.chunk && (
+                                                    <p className="mt-1 text-xs text-zinc-500 line-clamp-2">{email.explanation.vector.chunk}</p>
+                                                )}
                                             </div>
                                             <span className="text-xs font-mono text-zinc-500 bg-zinc-950 px-2 py-1 rounded border border-zinc-800 whitespace-nowrap">
-                                                {new Date(email.received_at).toLocaleDateString()}
+                                                {formatDate(email.received_at)}
                                             </span>
                                         </div>
 
@@ -421,15 +1009,119 @@ function App() {
                                                         {email.risks.length} Risk{email.risks.length > 1 ? 's' : ''}
                                                     </span>
                                                 )}
+                                                <button
+                                                    onClick={(e) => {
+                                                        e.stopPropagation()
+                                                        invoke('open_email_window', { id: email.id }).catch((err) => addLog(`Failed to open email: ${err}`, 'error'))
+                                                    }}
+                                                    className="text-[10px] text-zinc-600 hover:text-zinc-300 opacity-0 group-hover:opacity-100 transition-opacity"
+                                                >
+                                                    Open in window
+                                                </button>
+                                                <button
+                                                    onClick={(e) => {
+                                                        e.stopPropagation()
+                                                        invoke('open_in_outlook', { emailId: email.id }).catch((err) => addLog(`Failed to open in Outlook: ${err}`, 'error'))
+                                                    }}
+                                                    className="text-[10px] text-zinc-600 hover:text-zinc-300 opacity-0 group-hover:opacity-100 transition-opacity"
+                                                >
+                                                    Open in Outlook
+                                                </button>
+                                                <button
+                                                    onClick={(e) => { e.stopPropagation(); rerunEmail('reprocess_email', email.id) }}
+                                                    className="text-[10px] text-zinc-600 hover:text-zinc-300 opacity-0 group-hover:opacity-100 transition-opacity"
+                                                >
+                                                    Reprocess
+                                                </button>
+                                                {email.conversation_id && (
+                                                    <button
+                                                        onClick={(e) => { e.stopPropagation(); scopeSearch({ kind: 'thread', value: email.conversation_id, label: 'this thread' }) }}
+                                                        className="text-[10px] text-zinc-600 hover:text-zinc-300 opacity-0 group-hover:opacity-100 transition-opacity"
+                                                    >
+                                                        Search thread
+                                                    </button>
+                                                )}
+                                                <button
+                                                    onClick={(e) => { e.stopPropagation(); scopeSearch({ kind: 'sender', value: email.sender, label: email.sender }) }}
+                                                    className="text-[10px] text-zinc-600 hover:text-zinc-300 opacity-0 group-hover:opacity-100 transition-opacity"
+                                                >
+                                                    Search sender
+                                                </button>
+                                                <button
+                                                    onClick={(e) => { e.stopPropagation(); setProfileAddress(email.sender) }}
+                                                    className="text-[10px] text-zinc-600 hover:text-zinc-300 opacity-0 group-hover:opacity-100 transition-opacity"
+                                                >
+                                                    Sender profile
+                                                </button>
+                                                <ShareMenu
+                                                    sources={[['Email', { kind: 'email', id: email.id }], ['Thread', { kind: 'thread', email_id: email.id }]]}
+                                                    placeholder="Copy for chat…"
+                                                    onLog={addLog}
+                                                />
+                                                <button
+                                                    onClick={(e) => { e.stopPropagation(); rerunEmail('reembed_email', email.id) }}
+                                                    className="text-[10px] text-zinc-600 hover:text-zinc-300 opacity-0 group-hover:opacity-100 transition-opacity"
+                                                >
+                                                    Re-embed
+                                                </button>
+                                                {email.result_type !== 'attachment' && (
+                                                    <button
+                                                        onClick={(e) => { e.stopPropagation(); setAnnotatingId(annotatingId === email.id ? null : email.id) }}
+                                                        className="text-[10px] text-zinc-600 hover:text-zinc-300 opacity-0 group-hover:opacity-100 transition-opacity"
+                                                    >
+                                                        {email.annotation ? 'Edit note' : 'Add note'}
+                                                    </button>
+                                                )}
                                             </div>
                                         </div>
 
                                         <p className="text-sm text-zinc-400 line-clamp-2 leading-relaxed opacity-80 group-hover:opacity-100 transition-opacity">
                                             {email.summary || email.body_text}
                                         </p>
+
+                                        {email.annotation?.note && annotatingId !== email.id && (
+                                            <p className="text-xs text-zinc-300 bg-indigo-500/5 border-l-2 border-indigo-500/40 pl-2 whitespace-pre-wrap">
+                                                {email.annotation.note}
+                                            </p>
+                                        )}
+
+                                        {annotatingId === email.id && (
+                                            <AnnotationEditor
+                                                emailId={email.id}
+                                                annotation={email.annotation ?? null}
+                                                onSaved={(annotation) => setEmails(prev => prev.map(e => e.id === email.id && e.result_type !== 'attachment' ? { ...e, annotation } : e))}
+                                                onClose={() => setAnnotatingId(null)}
+                                                onLog={addLog}
+                                            />
+                                        )}
+
+                                        {changeLogs[email.id] && (
+                                            <div className="space-y-3 border-t border-zinc-800 pt-3" onClick={(e) => e.stopPropagation()}>
+                                                {changeLogs[email.id].map((change: any) => (
+                                                    <div key={change.id} className="space-y-1 text-xs">
+                                                        <div className="text-zinc-500">
+                                                            Changed {formatDateTime(change.detected_at)} · +{change.lines_added} −{change.lines_removed} lines
+                                                        </div>
+                                                        {change.previous_subject && (
+                                                            <div className="text-zinc-400">
+                                                                Subject: <span className="line-through text-zinc-600">{change.previous_subject}</span> → {change.subject}
+                                                            </div>
+                                                        )}
+                                                        {change.body_diff && (
+                                                            <pre className="bg-zinc-950 border border-zinc-800 rounded p-2 overflow-x-auto text-zinc-400 whitespace-pre-wrap">{change.body_diff}</pre>
+                                                        )}
+                                                    </div>
+                                                ))}
+                                            </div>
+                                        )}
                                     </div>
                                 ))
                             )}
+                            <TrashPanel
+                                refreshKey={trashVersion}
+                                onRestored={(email) => setEmails(prev => [email, ...prev])}
+                                onLog={addLog}
+                            />
                         </div>
                     )}
 
@@ -453,7 +1145,7 @@ function App() {
                                         {logs.map((log, i) => (
                                             <tr key={i} className="hover:bg-zinc-900/40 transition-colors group">
                                                 <td className="p-4 text-zinc-500">
-                                                    {new Date(log.timestamp).toLocaleTimeString()}
+                                                    {formatTime(log.timestamp)}
                                                 </td>
                                                 <td className="p-4">
                                                     <span className={cn(
@@ -516,13 +1208,13 @@ function App() {
                                                     config.provider_type === 'ollama' ? config.ollama_url :
                                                         config.provider_type === 'lemonade' ? config.lemonade_url :
                                                             config.provider_type === 'foundry' ? config.foundry_url :
-                                                                config.ollama_url // fallback for generic openai
+                                                                config.openai_url
                                                 }
                                                 onChange={(e) => {
                                                     if (config.provider_type === 'ollama') setConfig({ ...config, ollama_url: e.target.value })
                                                     else if (config.provider_type === 'lemonade') setConfig({ ...config, lemonade_url: e.target.value })
                                                     else if (config.provider_type === 'foundry') setConfig({ ...config, foundry_url: e.target.value })
-                                                    else setConfig({ ...config, ollama_url: e.target.value })
+                                                    else setConfig({ ...config, openai_url: e.target.value })
                                                 }}
                                                 placeholder={
                                                     config.provider_type === 'ollama' ? "http://localhost:11434" :
@@ -546,6 +1238,28 @@ function App() {
                                             </div>
                                         )}
 
+                                        {savedCredentials.length > 0 && (
+                                            <div className="space-y-2">
+                                                <label className="text-sm text-zinc-400">Keys saved in Windows Credential Manager</label>
+                                                {savedCredentials.map((cred) => (
+                                                    <div key={cred.provider} className="flex items-center justify-between text-sm text-zinc-300">
+                                                        <span>
+                                                            {cred.provider}
+                                                            {cred.last_written && (
+                                                                <span className="text-zinc-500"> · saved {formatDateTime(cred.last_written)}</span>
+                                                            )}
+                                                        </span>
+                                                        <button
+                                                            onClick={() => deleteApiKey(cred.provider)}
+                                                            className="text-sm text-red-400 hover:text-red-300"
+                                                        >
+                                                            Remove
+                                                        </button>
+                                                    </div>
+                                                ))}
+                                            </div>
+                                        )}
+
                                         <div className="space-y-2">
                                             <label className="text-sm text-zinc-400">Model Name</label>
                                             <div className="flex gap-2">
@@ -564,16 +1278,22 @@ function App() {
                                                         const currentUrl =
                                                             config.provider_type === 'lemonade' ? config.lemonade_url :
                                                                 config.provider_type === 'foundry' ? config.foundry_url :
-                                                                    config.ollama_url;
+                                                                    config.provider_type === 'openai' ? config.openai_url :
+                                                                        config.ollama_url;
 
                                                         addLog(`Fetching models from ${currentUrl}...`)
                                                         try {
                                                             // Temporarily save config to ensure backend uses correct credentials for fetch
-                                                            await invoke('save_config', { key: 'ollama_url', value: config.ollama_url })
-                                                            await invoke('save_config', { key: 'lemonade_url', value: config.lemonade_url })
-                                                            await invoke('save_config', { key: 'foundry_url', value: config.foundry_url })
-                                                            await invoke('save_config', { key: 'provider_type', value: config.provider_type })
-                                                            await invoke('save_config', { key: 'api_key', value: config.api_key })
+                                                            await invoke('set_config_bulk', {
+                                                                values: {
+                                                                    ollama_url: config.ollama_url,
+                                                                    lemonade_url: config.lemonade_url,
+                                                                    foundry_url: config.foundry_url,
+                                                                    openai_url: config.openai_url,
+                                                                    provider_type: config.provider_type,
+                                                                    api_key: config.api_key
+                                                                }
+                                                            })
 
                                                             const models = await invoke('get_models') as string[]
                                                             setAvailableModels(models)
@@ -588,6 +1308,78 @@ function App() {
                                                 </button>
                                             </div>
                                         </div>
+
+                                        <div className="space-y-2">
+                                            <label className="text-sm text-zinc-400">Context window (tokens)</label>
+                                            <input
+                                                type="number"
+                                                min="512"
+                                                className="w-full bg-zinc-950 border border-zinc-800 rounded-lg px-4 py-2 focus:border-blue-500 outline-none transition-all"
+                                                value={config.context_window}
+                                                onChange={(e) => setConfig({ ...config, context_window: e.target.value })}
+                                            />
+                                        </div>
+
+                                        <div className="space-y-2">
+                                            <label className="text-sm text-zinc-400">Per-model context windows</label>
+                                            <input
+                                                type="text"
+                                                className="w-full bg-zinc-950 border border-zinc-800 rounded-lg px-4 py-2 focus:border-blue-500 outline-none transition-all"
+                                                value={config.model_context_windows}
+                                                onChange={(e) => setConfig({ ...config, model_context_windows: e.target.value })}
+                                                placeholder="llama3=8192, qwen2.5=32768"
+                                            />
+                                        </div>
+
+                                        <div className="space-y-2">
+                                            <label className="text-sm text-zinc-400">Fallback providers</label>
+                                            <input
+                                                type="text"
+                                                className="w-full bg-zinc-950 border border-zinc-800 rounded-lg px-4 py-2 focus:border-blue-500 outline-none transition-all"
+                                                value={config.fallback_providers}
+                                                onChange={(e) => setConfig({ ...config, fallback_providers: e.target.value })}
+                                                placeholder="openai=gpt-4o-mini, lemonade"
+                                            />
+                                            <p className="text-xs text-zinc-500">
+                                                Tried in order when the provider above fails or times out. Endpoints come from the settings of each provider; search embeddings always use the provider above.
+                                            </p>
+                                        </div>
+
+                                        {[...new Set(config.fallback_providers.split(',').map((e: string) => e.split('=')[0].trim()))]
+                                            .filter((provider) => provider && provider !== 'ollama' && provider !== config.provider_type)
+                                            .map((provider) => (
+                                                <div key={provider} className="space-y-2">
+                                                    <label className="text-sm text-zinc-400">API key for the {provider} fallback</label>
+                                                    <div className="flex gap-2">
+                                                        <input
+                                                            type="password"
+                                                            className="flex-1 bg-zinc-950 border border-zinc-800 rounded-lg px-4 py-2 focus:border-blue-500 outline-none transition-all"
+                                                            value={fallbackKeys[provider] ?? ''}
+                                                            onChange={(e) => setFallbackKeys({ ...fallbackKeys, [provider]: e.target.value })}
+                                                            placeholder={savedCredentials.some((c) => c.provider === provider) ? 'Saved in Credential Manager' : 'sk-...'}
+                                                        />
+                                                        <button
+                                                            onClick={() => saveApiKey(provider)}
+                                                            disabled={!fallbackKeys[provider]}
+                                                            className="px-4 py-2 bg-zinc-800 hover:bg-zinc-700 disabled:opacity-50 rounded-lg text-sm transition-all"
+                                                        >
+                                                            Save
+                                                        </button>
+                                                    </div>
+                                                </div>
+                                            ))}
+
+                                        <div className="space-y-2">
+                                            <label className="text-sm text-zinc-400">Timeout before trying a fallback (seconds)</label>
+                                            <input
+                                                type="number"
+                                                min="5"
+                                                max="600"
+                                                className="w-full bg-zinc-950 border border-zinc-800 rounded-lg px-4 py-2 focus:border-blue-500 outline-none transition-all"
+                                                value={config.provider_timeout_secs}
+                                                onChange={(e) => setConfig({ ...config, provider_timeout_secs: e.target.value })}
+                                            />
+                                        </div>
                                     </div>
                                 </section>
 
@@ -615,6 +1407,41 @@ function App() {
                                                 onChange={(e) => setConfig({ ...config, history_days: e.target.value })}
                                             />
                                         </div>
+                                        <div className="space-y-2 col-span-2">
+                                            <label className="text-sm text-zinc-400">Folder modes (full, index_only or exclude)</label>
+                                            <input
+                                                type="text"
+                                                className="w-full bg-zinc-950 border border-zinc-800 rounded-lg px-4 py-2 focus:border-blue-500 outline-none transition-all"
+                                                value={config.folder_modes}
+                                                onChange={(e) => setConfig({ ...config, folder_modes: e.target.value })}
+                                                placeholder="Newsletters=index_only, Junk=exclude"
+                                            />
+                                            <p className="text-xs text-zinc-500">
+                                                Inbox and Sent Items are always scanned unless excluded. Other folders are scanned when listed here, by name or by path such as Inbox/Newsletters.
+                                            </p>
+                                        </div>
+                                        <div className="space-y-2">
+                                            <label className="text-sm text-zinc-400">Timezone (IANA, blank = system)</label>
+                                            <input
+                                                type="text"
+                                                className="w-full bg-zinc-950 border border-zinc-800 rounded-lg px-4 py-2 focus:border-blue-500 outline-none transition-all"
+                                                value={config.timezone}
+                                                onChange={(e) => setConfig({ ...config, timezone: e.target.value })}
+                                                placeholder="Europe/London"
+                                            />
+                                        </div>
+                                        <div className="space-y-2">
+                                            <label className="text-sm text-zinc-400">Language and date format</label>
+                                            <select
+                                                className="w-full bg-zinc-950 border border-zinc-800 rounded-lg px-4 py-2 focus:border-blue-500 outline-none transition-all"
+                                                value={config.locale}
+                                                onChange={(e) => setConfig({ ...config, locale: e.target.value })}
+                                            >
+                                                {LOCALES.map(([tag, name]) => (
+                                                    <option key={tag} value={tag}>{name}</option>
+                                                ))}
+                                            </select>
+                                        </div>
                                     </div>
 
                                     <div className="pt-4 border-t border-zinc-800/50">
@@ -632,21 +1459,772 @@ function App() {
                                             <span className="text-sm text-zinc-300 group-hover:text-white transition-colors">Confirm before exiting app</span>
                                         </label>
                                     </div>
+
+                                    <div className="flex items-center gap-6">
+                                        <label className="flex items-center gap-2 text-sm text-zinc-300 cursor-pointer">
+                                            <input
+                                                type="checkbox"
+                                                checked={autostartEnabled}
+                                                onChange={(e) => updateAutostart(e.target.checked, config.start_minimized === 'true')}
+                                            />
+                                            Start with Windows
+                                        </label>
+                                        <label className="flex items-center gap-2 text-sm text-zinc-300 cursor-pointer">
+                                            <input
+                                                type="checkbox"
+                                                checked={config.start_minimized === 'true'}
+                                                onChange={(e) => updateAutostart(autostartEnabled, e.target.checked)}
+                                            />
+                                            Start minimized to tray and sync in the background
+                                        </label>
+                                    </div>
+
+                                    <label className="flex items-center gap-2 text-sm text-zinc-300 cursor-pointer">
+                                        <input
+                                            type="checkbox"
+                                            checked={config.self_insights === 'true'}
+                                            onChange={(e) => setConfig({ ...config, self_insights: e.target.checked ? 'true' : 'false' })}
+                                        />
+                                        Analyze my sent mail (tone, commitments, unanswered questions)
+                                    </label>
+
+                                    <label className="flex items-center gap-2 text-sm text-zinc-300 cursor-pointer">
+                                        <input
+                                            type="checkbox"
+                                            checked={config.rerank_results === 'true'}
+                                            onChange={(e) => setConfig({ ...config, rerank_results: e.target.checked ? 'true' : 'false' })}
+                                        />
+                                        Re-rank the top search results with the AI model (slower, more relevant)
+                                    </label>
+
+                                    <label className="flex items-center gap-2 text-sm text-zinc-300 cursor-pointer">
+                                        <input
+                                            type="checkbox"
+                                            checked={config.chat_rerun_retrieval === 'true'}
+                                            onChange={(e) => setConfig({ ...config, chat_rerun_retrieval: e.target.checked ? 'true' : 'false' })}
+                                        />
+                                        Search again for each follow-up question in Ask Noodle
+                                    </label>
+
+                                    <label className="flex items-center gap-2 text-sm text-zinc-300 cursor-pointer">
+                                        <input
+                                            type="checkbox"
+                                            checked={config.chat_verify_citations === 'true'}
+                                            onChange={(e) => setConfig({ ...config, chat_verify_citations: e.target.checked ? 'true' : 'false' })}
+                                        />
+                                        Flag Ask Noodle citations the cited email doesn't back up
+                                    </label>
+
+                                    <div className="flex items-center justify-between gap-4">
+                                        <span className="text-sm text-zinc-300">Search history is used for suggestions</span>
+                                        <button
+                                            onClick={async () => {
+                                                try {
+                                                    const removed = await invoke('clear_search_history')
+                                                    setSearchSuggestions([])
+                                                    addLog(`Cleared ${removed} searches from history`)
+                                                } catch (e) {
+                                                    addLog(`Failed to clear search history: ${e}`, 'error')
+                                                }
+                                            }}
+                                            className="text-sm text-zinc-400 hover:text-zinc-200 transition-colors"
+                                        >
+                                            Clear history
+                                        </button>
+                                    </div>
+
+                                    <div className="space-y-2">
+                                        <label className="text-sm text-zinc-400">Quick search shortcut (blank = off)</label>
+                                        <input
+                                            type="text"
+                                            className="w-full bg-zinc-950 border border-zinc-800 rounded-lg px-4 py-2 focus:border-blue-500 outline-none transition-all"
+                                            value={config.quick_search_shortcut}
+                                            onChange={(e) => setConfig({ ...config, quick_search_shortcut: e.target.value })}
+                                            placeholder="CommandOrControl+Shift+N"
+                                        />
+                                    </div>
+
+                                    <div className="pt-4 border-t border-zinc-800/50 flex items-center justify-between gap-4">
+                                        <label className="flex items-center gap-3 cursor-pointer group">
+                                            <div className="relative">
+                                                <input
+                                                    type="checkbox"
+                                                    className="peer sr-only"
+                                                    checked={config.digest_notifications === 'true'}
+                                                    onChange={(e) => setConfig({ ...config, digest_notifications: e.target.checked ? 'true' : 'false' })}
+                                                />
+                                                <div className="w-10 h-6 bg-zinc-800 rounded-full peer-checked:bg-blue-600 transition-colors" />
+                                                <div className="absolute left-1 top-1 w-4 h-4 bg-white rounded-full transition-transform peer-checked:translate-x-4" />
+                                            </div>
+                                            <span className="text-sm text-zinc-300 group-hover:text-white transition-colors">Morning digest notification</span>
+                                        </label>
+                                        <input
+                                            type="time"
+                                            className="bg-zinc-950 border border-zinc-800 rounded-lg px-3 py-1.5 text-sm focus:border-blue-500 outline-none transition-all"
+                                            value={config.digest_time}
+                                            onChange={(e) => setConfig({ ...config, digest_time: e.target.value })}
+                                        />
+                                    </div>
+
+                                    <label className="flex items-center gap-2 text-sm text-zinc-300 cursor-pointer">
+                                        <input
+                                            type="checkbox"
+                                            checked={config.delegation_mode === 'true'}
+                                            onChange={(e) => setConfig({ ...config, delegation_mode: e.target.checked ? 'true' : 'false' })}
+                                        />
+                                        While out of office, send a "what you missed" digest and hold reply reminders until I'm back
+                                    </label>
+
+                                    <label className="flex items-center gap-2 text-sm text-zinc-300 cursor-pointer">
+                                        <input
+                                            type="checkbox"
+                                            checked={config.newsletter_roundup === 'true'}
+                                            onChange={(e) => setConfig({ ...config, newsletter_roundup: e.target.checked ? 'true' : 'false' })}
+                                        />
+                                        Summarize newsletters in a weekly roundup
+                                    </label>
+
+                                    <label className="flex items-center gap-2 text-sm text-zinc-300 cursor-pointer">
+                                        <input
+                                            type="checkbox"
+                                            checked={config.meeting_follow_ups === 'true'}
+                                            onChange={(e) => setConfig({ ...config, meeting_follow_ups: e.target.checked ? 'true' : 'false' })}
+                                        />
+                                        Collect decisions and action items from the mail around my meetings
+                                    </label>
+
+                                    <div className="pt-4 border-t border-zinc-800/50 flex items-center justify-between gap-4">
+                                        <div className="flex items-center gap-2 text-sm text-zinc-300">
+                                            <span>Quiet hours</span>
+                                            <input
+                                                type="time"
+                                                className="bg-zinc-950 border border-zinc-800 rounded-lg px-3 py-1.5 text-sm focus:border-blue-500 outline-none transition-all"
+                                                value={config.quiet_hours_start}
+                                                onChange={(e) => setConfig({ ...config, quiet_hours_start: e.target.value })}
+                                            />
+                                            <span>to</span>
+                                            <input
+                                                type="time"
+                                                className="bg-zinc-950 border border-zinc-800 rounded-lg px-3 py-1.5 text-sm focus:border-blue-500 outline-none transition-all"
+                                                value={config.quiet_hours_end}
+                                                onChange={(e) => setConfig({ ...config, quiet_hours_end: e.target.value })}
+                                            />
+                                        </div>
+                                        <label className="flex items-center gap-2 text-sm text-zinc-300 cursor-pointer">
+                                            <input
+                                                type="checkbox"
+                                                checked={config.quiet_hours_pause_sync === 'true'}
+                                                onChange={(e) => setConfig({ ...config, quiet_hours_pause_sync: e.target.checked ? 'true' : 'false' })}
+                                            />
+                                            Also pause sync
+                                        </label>
+                                        <label className="flex items-center gap-2 text-sm text-zinc-300 cursor-pointer">
+                                            <input
+                                                type="checkbox"
+                                                checked={config.meeting_pause_ai === 'true'}
+                                                onChange={(e) => setConfig({ ...config, meeting_pause_ai: e.target.checked ? 'true' : 'false' })}
+                                            />
+                                            No AI work during meetings in my calendar
+                                        </label>
+                                    </div>
+
+                                    <div className="pt-4 border-t border-zinc-800/50 flex items-center justify-between gap-4">
+                                        <label className="text-sm text-zinc-300">On battery</label>
+                                        <select
+                                            className="bg-zinc-950 border border-zinc-800 rounded-lg px-3 py-1.5 text-sm focus:border-blue-500 outline-none transition-all"
+                                            value={config.battery_sync_mode}
+                                            onChange={(e) => setConfig({ ...config, battery_sync_mode: e.target.value })}
+                                        >
+                                            <option value="throttle">Sync less often</option>
+                                            <option value="pause">Pause sync</option>
+                                            <option value="ignore">Sync normally</option>
+                                        </select>
+                                    </div>
+
+                                    <div className="pt-4 border-t border-zinc-800/50 flex items-center justify-between gap-4">
+                                        <label className="flex items-center gap-2 text-sm text-zinc-300 cursor-pointer">
+                                            <input
+                                                type="checkbox"
+                                                checked={config.low_impact_mode === 'true'}
+                                                onChange={(e) => setConfig({ ...config, low_impact_mode: e.target.checked ? 'true' : 'false' })}
+                                            />
+                                            Low impact mode (emails per minute)
+                                        </label>
+                                        <input
+                                            type="number"
+                                            min="1"
+                                            className="w-20 bg-zinc-950 border border-zinc-800 rounded-lg px-3 py-1.5 text-sm focus:border-blue-500 outline-none transition-all"
+                                            value={config.low_impact_emails_per_minute}
+                                            onChange={(e) => setConfig({ ...config, low_impact_emails_per_minute: e.target.value })}
+                                        />
+                                    </div>
+
+                                    <div className="pt-4 border-t border-zinc-800/50 flex items-center justify-between gap-4">
+                                        <label className="flex items-center gap-2 text-sm text-zinc-300 cursor-pointer">
+                                            <input
+                                                type="checkbox"
+                                                checked={config.idle_extraction === 'true'}
+                                                onChange={(e) => setConfig({ ...config, idle_extraction: e.target.checked ? 'true' : 'false' })}
+                                            />
+                                            Extract facts only while idle (minutes without input)
+                                        </label>
+                                        <input
+                                            type="number"
+                                            min="1"
+                                            className="w-20 bg-zinc-950 border border-zinc-800 rounded-lg px-3 py-1.5 text-sm focus:border-blue-500 outline-none transition-all"
+                                            value={config.idle_threshold_mins}
+                                            onChange={(e) => setConfig({ ...config, idle_threshold_mins: e.target.value })}
+                                        />
+                                    </div>
+
+                                    <div className="pt-4 border-t border-zinc-800/50 flex items-center justify-between gap-4">
+                                        <label className="text-sm text-zinc-300">Longest body sent for extraction (characters)</label>
+                                        <input
+                                            type="number"
+                                            min="1000"
+                                            className="w-28 bg-zinc-950 border border-zinc-800 rounded-lg px-3 py-1.5 text-sm focus:border-blue-500 outline-none transition-all"
+                                            value={config.max_extraction_body_chars}
+                                            onChange={(e) => setConfig({ ...config, max_extraction_body_chars: e.target.value })}
+                                        />
+                                    </div>
+
+                                    <div className="pt-4 border-t border-zinc-800/50 flex items-center justify-between gap-4">
+                                        <label className="text-sm text-zinc-300">Longest body embedded for search (characters)</label>
+                                        <input
+                                            type="number"
+                                            min="500"
+                                            className="w-28 bg-zinc-950 border border-zinc-800 rounded-lg px-3 py-1.5 text-sm focus:border-blue-500 outline-none transition-all"
+                                            value={config.max_embedding_body_chars}
+                                            onChange={(e) => setConfig({ ...config, max_embedding_body_chars: e.target.value })}
+                                        />
+                                    </div>
+
+                                    <div className="pt-4 border-t border-zinc-800/50 flex items-center justify-between gap-4">
+                                        <label className="text-sm text-zinc-300">Largest attachment stored (MB, 0 for none)</label>
+                                        <input
+                                            type="number"
+                                            min="0"
+                                            max="500"
+                                            className="w-28 bg-zinc-950 border border-zinc-800 rounded-lg px-3 py-1.5 text-sm focus:border-blue-500 outline-none transition-all"
+                                            value={config.attachment_max_size_mb}
+                                            onChange={(e) => setConfig({ ...config, attachment_max_size_mb: e.target.value })}
+                                        />
+                                    </div>
+
+                                    <div className="pt-4 border-t border-zinc-800/50 space-y-2">
+                                        <label className="text-sm text-zinc-300">Attachment types stored</label>
+                                        <input
+                                            type="text"
+                                            className="w-full bg-zinc-950 border border-zinc-800 rounded-lg px-3 py-1.5 text-sm focus:border-blue-500 outline-none transition-all"
+                                            value={config.attachment_mime_types}
+                                            onChange={(e) => setConfig({ ...config, attachment_mime_types: e.target.value })}
+                                            placeholder="application/pdf, text/*"
+                                        />
+                                        <p className="text-xs text-zinc-500">
+                                            Other attachments are listed with the email but not kept. Stored files live in the app data folder and are removed once no email lists them.
+                                        </p>
+                                    </div>
+
+                                    <div className="pt-4 border-t border-zinc-800/50 flex items-center justify-between gap-4">
+                                        <label className="text-sm text-zinc-300">Database maintenance hour</label>
+                                        <input
+                                            type="number"
+                                            min="0"
+                                            max="23"
+                                            className="w-20 bg-zinc-950 border border-zinc-800 rounded-lg px-3 py-1.5 text-sm focus:border-blue-500 outline-none transition-all"
+                                            value={config.maintenance_hour}
+                                            onChange={(e) => setConfig({ ...config, maintenance_hour: e.target.value })}
+                                        />
+                                    </div>
+
+                                    <div className="flex items-center justify-between gap-4">
+                                        <label className="flex items-center gap-2 text-sm text-zinc-300 cursor-pointer">
+                                            <input
+                                                type="checkbox"
+                                                checked={config.maintenance_vacuum === 'true'}
+                                                onChange={(e) => setConfig({ ...config, maintenance_vacuum: e.target.checked ? 'true' : 'false' })}
+                                            />
+                                            Compact the database weekly
+                                        </label>
+                                        <div className="flex items-center gap-4">
+                                            <button
+                                                onClick={async () => {
+                                                    try {
+                                                        const report: any = await invoke('run_maintenance', { vacuum: false })
+                                                        addLog(`Database maintenance finished in ${report.duration_ms}ms`)
+                                                    } catch (e) {
+                                                        addLog(`Database maintenance failed: ${e}`, 'error')
+                                                    }
+                                                }}
+                                                className="text-sm text-zinc-400 hover:text-zinc-200 transition-colors"
+                                            >
+                                                Run now
+                                            </button>
+                                            <button
+                                                onClick={async () => {
+                                                    try {
+                                                        const report: any = await invoke('repair_database')
+                                                        if (report.problems_after.length === 0) {
+                                                            addLog(`Database repaired (${report.problems_before.length} problem(s) found)`)
+                                                        } else {
+                                                            addLog(`Database still reports ${report.problems_after.length} problem(s) after repair`, 'error')
+                                                        }
+                                                    } catch (e) {
+                                                        addLog(`Database repair failed: ${e}`, 'error')
+                                                    }
+                                                }}
+                                                className="text-sm text-zinc-400 hover:text-zinc-200 transition-colors"
+                                            >
+                                                Repair
+                                            </button>
+                                            <button
+                                                onClick={async () => {
+                                                    try {
+                                                        const stats: any = await invoke('get_vector_stats')
+                                                        if (!stats.available) {
+                                                            addLog('Vector store is not available', 'warn')
+                                                            return
+                                                        }
+                                                        addLog(`Vector store: ${stats.point_count} points for ${stats.email_count} emails, ${stats.orphaned_points} orphaned, ${stats.missing_vectors} missing`)
+                                                        if (stats.collection_dim && stats.embedding_dim && stats.collection_dim !== stats.embedding_dim) {
+                                                            addLog(`Embedding model produces ${stats.embedding_dim}-dimensional vectors but the collection expects ${stats.collection_dim}`, 'error')
+                                                        } else if (stats.orphaned_points > 0 || stats.missing_vectors > 0) {
+                                                            const report: any = await invoke('repair_vectors')
+                                                            addLog(`Vectors repaired: ${report.orphans_deleted} orphans deleted, ${report.reembedded} re-embedded, ${report.failed} failed`)
+                                                        }
+                                                    } catch (e) {
+                                                        addLog(`Vector check failed: ${e}`, 'error')
+                                                    }
+                                                }}
+                                                className="text-sm text-zinc-400 hover:text-zinc-200 transition-colors"
+                                            >
+                                                Check vectors
+                                            </button>
+                                            <button
+                                                onClick={async () => {
+                                                    try {
+                                                        const path = await invoke('create_diagnostics_bundle')
+                                                        addLog(`Diagnostics bundle saved to ${path}`)
+                                                    } catch (e) {
+                                                        addLog(`Failed to create diagnostics bundle: ${e}`, 'error')
+                                                    }
+                                                }}
+                                                className="text-sm text-zinc-400 hover:text-zinc-200 transition-colors"
+                                            >
+                                                Diagnostics
+                                            </button>
+                                        </div>
+                                    </div>
+
+                                    <div className="pt-4 border-t border-zinc-800/50 flex items-center justify-between gap-4">
+                                        <label className="flex items-center gap-2 text-sm text-zinc-300 cursor-pointer">
+                                            <input
+                                                type="checkbox"
+                                                checked={config.vector_snapshots === 'true'}
+                                                onChange={(e) => setConfig({ ...config, vector_snapshots: e.target.checked ? 'true' : 'false' })}
+                                            />
+                                            Snapshot the vector store daily
+                                        </label>
+                                        <label className="flex items-center gap-2 text-sm text-zinc-400">
+                                            Keep
+                                            <input
+                                                type="number"
+                                                min="1"
+                                                max="30"
+                                                className="w-16 bg-zinc-950 border border-zinc-800 rounded-lg px-3 py-1.5 text-sm focus:border-blue-500 outline-none transition-all"
+                                                value={config.vector_snapshot_retention}
+                                                onChange={(e) => setConfig({ ...config, vector_snapshot_retention: e.target.value })}
+                                            />
+                                        </label>
+                                    </div>
+                                    <VectorSnapshots onLog={addLog} />
+                                    <div className="pt-4 border-t border-zinc-800/50 flex items-center justify-between gap-4">
+                                        <label className="flex items-center gap-2 text-sm text-zinc-300 cursor-pointer">
+                                            <input
+                                                type="checkbox"
+                                                checked={config.legal_hold === 'true'}
+                                                onChange={(e) => setConfig({ ...config, legal_hold: e.target.checked ? 'true' : 'false' })}
+                                            />
+                                            Keep a tamper-evident archive of every ingested email (legal hold)
+                                        </label>
+                                        <button onClick={verifyLegalHold} className="text-sm text-blue-400 hover:text-blue-300">Verify archive</button>
+                                    </div>
                                 </section>
 
+                                <section className="bg-zinc-900/40 border border-zinc-800/50 rounded-2xl p-6 space-y-6">
+                                    <h3 className="text-lg font-medium flex items-center gap-2">
+                                        <Ticket className="w-5 h-5 text-blue-400" />
+                                        Ticket Trackers
+                                    </h3>
+                                    <div className="grid grid-cols-2 gap-4">
+                                        <div className="space-y-2">
+                                            <label className="text-sm text-zinc-400">Jira site</label>
+                                            <input
+                                                type="text"
+                                                className="w-full bg-zinc-950 border border-zinc-800 rounded-lg px-4 py-2 focus:border-blue-500 outline-none transition-all"
+                                                value={config.jira_url}
+                                                onChange={(e) => setConfig({ ...config, jira_url: e.target.value })}
+                                                placeholder="https://example.atlassian.net"
+                                            />
+                                        </div>
+                                        <div className="space-y-2">
+                                            <label className="text-sm text-zinc-400">Jira account email</label>
+                                            <input
+                                                type="text"
+                                                className="w-full bg-zinc-950 border border-zinc-800 rounded-lg px-4 py-2 focus:border-blue-500 outline-none transition-all"
+                                                value={config.jira_email}
+                                                onChange={(e) => setConfig({ ...config, jira_email: e.target.value })}
+                                                placeholder="you@example.com"
+                                            />
+                                        </div>
+                                        <div className="space-y-2">
+                                            <label className="text-sm text-zinc-400">Jira API token</label>
+                                            <input
+                                                type="password"
+                                                className="w-full bg-zinc-950 border border-zinc-800 rounded-lg px-4 py-2 focus:border-blue-500 outline-none transition-all"
+                                                value={config.jira_api_token}
+                                                onChange={(e) => setConfig({ ...config, jira_api_token: e.target.value })}
+                                                placeholder=""
+                                            />
+                                        </div>
+                                        <div className="space-y-2">
+                                            <label className="text-sm text-zinc-400">Default project key</label>
+                                            <input
+                                                type="text"
+                                                className="w-full bg-zinc-950 border border-zinc-800 rounded-lg px-4 py-2 focus:border-blue-500 outline-none transition-all"
+                                                value={config.jira_project_key}
+                                                onChange={(e) => setConfig({ ...config, jira_project_key: e.target.value })}
+                                                placeholder="OPS"
+                                            />
+                                        </div>
+                                        <div className="space-y-2">
+                                            <label className="text-sm text-zinc-400">Issue type</label>
+                                            <input
+                                                type="text"
+                                                className="w-full bg-zinc-950 border border-zinc-800 rounded-lg px-4 py-2 focus:border-blue-500 outline-none transition-all"
+                                                value={config.jira_issue_type}
+                                                onChange={(e) => setConfig({ ...config, jira_issue_type: e.target.value })}
+                                                placeholder="Task"
+                                            />
+                                        </div>
+                                    </div>
+                                    <div className="pt-4 border-t border-zinc-800/50 grid grid-cols-2 gap-4">
+                                        <div className="space-y-2">
+                                            <label className="text-sm text-zinc-400">Azure DevOps organization</label>
+                                            <input
+                                                type="text"
+                                                className="w-full bg-zinc-950 border border-zinc-800 rounded-lg px-4 py-2 focus:border-blue-500 outline-none transition-all"
+                                                value={config.azure_devops_url}
+                                                onChange={(e) => setConfig({ ...config, azure_devops_url: e.target.value })}
+                                                placeholder="https://dev.azure.com/example"
+                                            />
+                                        </div>
+                                        <div className="space-y-2">
+                                            <label className="text-sm text-zinc-400">Personal access token</label>
+                                            <input
+                                                type="password"
+                                                className="w-full bg-zinc-950 border border-zinc-800 rounded-lg px-4 py-2 focus:border-blue-500 outline-none transition-all"
+                                                value={config.azure_devops_token}
+                                                onChange={(e) => setConfig({ ...config, azure_devops_token: e.target.value })}
+                                                placeholder=""
+                                            />
+                                        </div>
+                                        <div className="space-y-2">
+                                            <label className="text-sm text-zinc-400">Default project</label>
+                                            <input
+                                                type="text"
+                                                className="w-full bg-zinc-950 border border-zinc-800 rounded-lg px-4 py-2 focus:border-blue-500 outline-none transition-all"
+                                                value={config.azure_devops_project}
+                                                onChange={(e) => setConfig({ ...config, azure_devops_project: e.target.value })}
+                                                placeholder=""
+                                            />
+                                        </div>
+                                        <div className="space-y-2">
+                                            <label className="text-sm text-zinc-400">Work item type</label>
+                                            <input
+                                                type="text"
+                                                className="w-full bg-zinc-950 border border-zinc-800 rounded-lg px-4 py-2 focus:border-blue-500 outline-none transition-all"
+                                                value={config.azure_devops_work_item_type}
+                                                onChange={(e) => setConfig({ ...config, azure_devops_work_item_type: e.target.value })}
+                                                placeholder="Task"
+                                            />
+                                        </div>
+                                    </div>
+                                </section>
+
+                                <section className="bg-zinc-900/40 border border-zinc-800/50 rounded-2xl p-6 space-y-6">
+                                    <h3 className="text-lg font-medium flex items-center gap-2">
+                                        <Puzzle className="w-5 h-5 text-blue-400" />
+                                        Browser Extension
+                                    </h3>
+                                    <label className="flex items-center gap-2 text-sm text-zinc-300 cursor-pointer">
+                                        <input
+                                            type="checkbox"
+                                            checked={config.companion_api === 'true'}
+                                            onChange={(e) => setConfig({ ...config, companion_api: e.target.checked ? 'true' : 'false' })}
+                                        />
+                                        Let the browser extension look up emails and draft replies (takes effect after a restart)
+                                    </label>
+                                    <div className="grid grid-cols-2 gap-4">
+                                        <div className="space-y-2">
+                                            <label className="text-sm text-zinc-400">Port</label>
+                                            <input
+                                                type="number"
+                                                min="1024"
+                                                max="65535"
+                                                className="w-full bg-zinc-950 border border-zinc-800 rounded-lg px-4 py-2 focus:border-blue-500 outline-none transition-all"
+                                                value={config.companion_api_port}
+                                                onChange={(e) => setConfig({ ...config, companion_api_port: e.target.value })}
+                                            />
+                                        </div>
+                                        <div className="space-y-2">
+                                            <label className="text-sm text-zinc-400">Access token</label>
+                                            <div className="flex gap-2">
+                                                <input
+                                                    type="password"
+                                                    className="w-full bg-zinc-950 border border-zinc-800 rounded-lg px-4 py-2 focus:border-blue-500 outline-none transition-all"
+                                                    value={config.companion_api_token}
+                                                    onChange={(e) => setConfig({ ...config, companion_api_token: e.target.value })}
+                                                    placeholder="Paste into the extension's options"
+                                                />
+                                                <button
+                                                    onClick={() => setConfig({ ...config, companion_api_token: crypto.randomUUID() })}
+                                                    className="text-sm text-blue-400 hover:text-blue-300 shrink-0"
+                                                >
+                                                    Generate
+                                                </button>
+                                                <button
+                                                    onClick={() => navigator.clipboard.writeText(config.companion_api_token)}
+                                                    disabled={!config.companion_api_token || config.companion_api_token === SECRET_MASK}
+                                                    className="text-sm text-blue-400 hover:text-blue-300 disabled:text-zinc-600 shrink-0"
+                                                >
+                                                    Copy
+                                                </button>
+                                            </div>
+                                        </div>
+                                    </div>
+                                </section>
+
+                                <section className="bg-zinc-900/40 border border-zinc-800/50 rounded-2xl p-6 space-y-6">
+                                    <h3 className="text-lg font-medium flex items-center gap-2">
+                                        <Lock className="w-5 h-5 text-blue-400" />
+                                        App Lock
+                                    </h3>
+                                    <div className="flex items-center justify-between gap-4">
+                                        <label className="flex items-center gap-2 text-sm text-zinc-300 cursor-pointer">
+                                            <input
+                                                type="checkbox"
+                                                checked={config.app_lock === 'true'}
+                                                onChange={(e) => setConfig({ ...config, app_lock: e.target.checked ? 'true' : 'false' })}
+                                            />
+                                            Ask for Windows Hello or your Windows password before showing email
+                                        </label>
+                                        <button
+                                            onClick={lockApp}
+                                            disabled={config.app_lock !== 'true'}
+                                            className="text-sm text-blue-400 hover:text-blue-300 disabled:text-zinc-600 shrink-0"
+                                        >
+                                            Lock now
+                                        </button>
+                                    </div>
+                                    <div className="space-y-2">
+                                        <label className="text-sm text-zinc-400">Lock after this many idle minutes</label>
+                                        <input
+                                            type="number"
+                                            min="1"
+                                            max="240"
+                                            className="w-full bg-zinc-950 border border-zinc-800 rounded-lg px-4 py-2 focus:border-blue-500 outline-none transition-all"
+                                            value={config.app_lock_idle_mins}
+                                            onChange={(e) => setConfig({ ...config, app_lock_idle_mins: e.target.value })}
+                                        />
+                                    </div>
+                                </section>
+
+                                <section className="bg-zinc-900/40 border border-zinc-800/50 rounded-2xl p-6 space-y-6">
+                                    <h3 className="text-lg font-medium flex items-center gap-2">
+                                        <Download className="w-5 h-5 text-blue-400" />
+                                        Updates
+                                    </h3>
+                                    <div className="flex items-center justify-between gap-4">
+                                        <label className="flex items-center gap-2 text-sm text-zinc-300 cursor-pointer">
+                                            <input
+                                                type="checkbox"
+                                                checked={config.update_check === 'true'}
+                                                onChange={(e) => setConfig({ ...config, update_check: e.target.checked ? 'true' : 'false' })}
+                                            />
+                                            Check for updates automatically
+                                        </label>
+                                        <select
+                                            className="bg-zinc-950 border border-zinc-800 rounded-lg px-3 py-1.5 text-sm focus:border-blue-500 outline-none transition-all"
+                                            value={config.update_channel}
+                                            onChange={(e) => setConfig({ ...config, update_channel: e.target.value })}
+                                        >
+                                            <option value="stable">Stable</option>
+                                            <option value="beta">Beta</option>
+                                        </select>
+                                    </div>
+                                    <div className="flex items-center justify-between gap-4 text-sm">
+                                        <span className="text-zinc-400">
+                                            {updateInfo === null
+                                                ? 'Not checked yet'
+                                                : updateInfo.releases.length === 0
+                                                    ? `Noodle ${updateInfo.current_version} is up to date`
+                                                    : `Noodle ${updateInfo.releases[0].version} is available (you have ${updateInfo.current_version})`}
+                                        </span>
+                                        <div className="flex items-center gap-4">
+                                            <button
+                                                onClick={async () => {
+                                                    try {
+                                                        setUpdateInfo(await invoke('check_for_updates'))
+                                                    } catch (e) {
+                                                        addLog(`Update check failed: ${e}`, 'error')
+                                                    }
+                                                }}
+                                                className="text-zinc-400 hover:text-zinc-200 transition-colors"
+                                            >
+                                                Check now
+                                            </button>
+                                            {updateInfo?.releases[0]?.installable && (
+                                                <button
+                                                    onClick={async () => {
+                                                        try {
+                                                            addLog(`Installing Noodle ${updateInfo.releases[0].version}...`)
+                                                            await invoke('install_update')
+                                                        } catch (e) {
+                                                            addLog(`Update failed: ${e}`, 'error')
+                                                        }
+                                                    }}
+                                                    className="text-blue-400 hover:text-blue-300 transition-colors"
+                                                >
+                                                    Install and restart
+                                                </button>
+                                            )}
+                                        </div>
+                                    </div>
+                                    {updateInfo?.releases.map((release: any) => (
+                                        <div key={release.version} className="space-y-1 text-sm">
+                                            <a href={release.url} target="_blank" className="font-medium text-zinc-200 hover:text-blue-400">
+                                                {release.name}{release.prerelease ? ' (beta)' : ''}
+                                            </a>
+                                            <p className="text-zinc-500 whitespace-pre-wrap">{release.notes}</p>
+                                        </div>
+                                    ))}
+                                </section>
+
+                                <section className="bg-zinc-900/40 border border-zinc-800/50 rounded-2xl p-6 space-y-6">
+                                    <h3 className="text-lg font-medium flex items-center gap-2">
+                                        <Share2 className="w-5 h-5 text-green-400" />
+                                        Profiles &amp; Backup
+                                    </h3>
+                                    <div className="flex items-center gap-2">
+                                        <input
+                                            type="text"
+                                            className="flex-1 bg-zinc-950 border border-zinc-800 rounded-lg px-4 py-2 focus:border-blue-500 outline-none transition-all"
+                                            value={profileName}
+                                            onChange={(e) => setProfileName(e.target.value)}
+                                            placeholder="Profile name, e.g. work laptop"
+                                        />
+                                        <button
+                                            onClick={async () => {
+                                                try {
+                                                    await invoke('save_settings_profile', { name: profileName })
+                                                    addLog(`Saved settings profile ${profileName}`)
+                                                    setProfileName('')
+                                                    fetchProfiles()
+                                                } catch (e) {
+                                                    addLog(`Failed to save profile: ${e}`, 'error')
+                                                }
+                                            }}
+                                            className="bg-zinc-800 hover:bg-zinc-700 text-white px-4 py-2 rounded-lg text-sm font-medium transition-colors"
+                                        >
+                                            Save current
+                                        </button>
+                                    </div>
+                                    {profiles.map((profile) => (
+                                        <div key={profile.name} className="flex items-center justify-between gap-4 text-sm">
+                                            <span className="text-zinc-300">{profile.name}</span>
+                                            <div className="flex items-center gap-4">
+                                                <button
+                                                    onClick={async () => {
+                                                        try {
+                                                            reportSkipped(await invoke('apply_settings_profile', { name: profile.name }) as string[])
+                                                            addLog(`Switched to settings profile ${profile.name}`)
+                                                        } catch (e) {
+                                                            addLog(`Failed to apply profile: ${e}`, 'error')
+                                                        }
+                                                    }}
+                                                    className="text-zinc-400 hover:text-zinc-200 transition-colors"
+                                                >
+                                                    Apply
+                                                </button>
+                                                <button
+                                                    onClick={async () => {
+                                                        try {
+                                                            await invoke('delete_settings_profile', { name: profile.name })
+                                                            fetchProfiles()
+                                                        } catch (e) {
+                                                            addLog(`Failed to delete profile: ${e}`, 'error')
+                                                        }
+                                                    }}
+                                                    className="text-zinc-500 hover:text-red-400 transition-colors"
+                                                >
+                                                    Delete
+                                                </button>
+                                            </div>
+                                        </div>
+                                    ))}
+                                    <div className="pt-4 border-t border-zinc-800/50 flex items-center gap-4">
+                                        <button
+                                            onClick={exportSettings}
+                                            className="text-sm text-zinc-400 hover:text-zinc-200 transition-colors"
+                                        >
+                                            Export settings
+                                        </button>
+                                        <label className="text-sm text-zinc-400 hover:text-zinc-200 transition-colors cursor-pointer">
+                                            Import settings
+                                            <input
+                                                type="file"
+                                                accept="application/json"
+                                                className="hidden"
+                                                onChange={(e) => {
+                                                    const file = e.target.files?.[0]
+                                                    if (file) importSettings(file)
+                                                    e.target.value = ''
+                                                }}
+                                            />
+                                        </label>
+                                        <label className="text-sm text-zinc-400 hover:text-zinc-200 transition-colors cursor-pointer">
+                                            Open encrypted export
+                                            <input
+                                                type="file"
+                                                accept=".noodlex"
+                                                className="hidden"
+                                                onChange={(e) => {
+                                                    const file = e.target.files?.[0]
+                                                    if (file) openEncryptedExport(file)
+                                                    e.target.value = ''
+                                                }}
+                                            />
+                                        </label>
+                                        <input
+                                            type="password"
+                                            value={exportPassword}
+                                            onChange={(e) => setExportPassword(e.target.value)}
+                                            placeholder="Export password"
+                                            className="w-40 bg-zinc-950 border border-zinc-800 rounded-lg px-3 py-1.5 text-sm focus:border-blue-500 outline-none transition-all"
+                                        />
+                                    </div>
+                                </section>
+
+                                <PromptLibrary onLog={addLog} download={download} />
+
+                                <ExtractionExperiments onLog={addLog} />
+
                                 <div className="flex justify-end gap-3">
                                     <button
                                         onClick={async () => {
                                             try {
-                                                await invoke('save_config', { key: 'ollama_url', value: config.ollama_url })
-                                                await invoke('save_config', { key: 'lemonade_url', value: config.lemonade_url })
-                                                await invoke('save_config', { key: 'foundry_url', value: config.foundry_url })
-                                                await invoke('save_config', { key: 'model_name', value: config.model_name })
-                                                await invoke('save_config', { key: 'sync_interval', value: config.sync_interval })
-                                                await invoke('save_config', { key: 'history_days', value: config.history_days })
-                                                await invoke('save_config', { key: 'provider_type', value: config.provider_type })
-                                                await invoke('save_config', { key: 'api_key', value: config.api_key })
-                                                await invoke('save_config', { key: 'confirm_exit', value: config.confirm_exit })
+                                                await invoke('set_config_bulk', { values: config })
                                                 addLog('Settings saved successfully')
                                             } catch (e) {
                                                 addLog(`Failed to save settings: ${e}`, 'error')
@@ -663,6 +2241,23 @@ function App() {
                 </main>
             </div >
 
+            {locked && (
+                <div className="fixed inset-0 bg-zinc-950 z-40 flex flex-col items-center justify-center gap-4">
+                    <Lock className="w-10 h-10 text-zinc-500" />
+                    <p className="text-zinc-400 text-sm">Noodle is locked</p>
+                    <button
+                        onClick={unlockApp}
+                        className="px-4 py-2 rounded-lg text-sm font-medium bg-blue-500/10 text-blue-400 hover:bg-blue-500/20 border border-blue-500/20 transition-colors"
+                    >
+                        Unlock
+                    </button>
+                </div>
+            )}
+
+            {showPalette && <CommandPalette onClose={() => setShowPalette(false)} onLog={addLog} />}
+
+            {profileAddress && <SenderProfileDialog address={profileAddress} onClose={() => setProfileAddress(null)} />}
+
             {showExitConfirm && (
                 <div className="fixed inset-0 bg-black/50 backdrop-blur-sm z-50 flex items-center justify-center p-4 animate-in fade-in duration-200">
                     <div className="bg-zinc-900 border border-zinc-800 rounded-2xl p-6 max-w-sm w-full shadow-2xl scale-100 animate-in zoom-in-95 duration-200">
@@ -0,0 +1,72 @@
+import { useEffect, useState } from 'react'
+import { invoke } from '@tauri-apps/api/core'
+import { listen } from '@tauri-apps/api/event'
+import { EVENTS } from '../events'
+
+const LIMIT = 20
+
+const KIND_LABELS: Record<string, string> = {
+    vip_email: 'VIP email',
+    hostile_sender: 'Hostile sender',
+    urgent_flood: 'Urgent flood',
+    confidence_drop: 'Confidence drop',
+}
+
+export function AlertsPanel() {
+    const [alerts, setAlerts] = useState<any[]>([])
+
+    const refresh = () => {
+        invoke<any[]>('list_alerts', { limit: LIMIT })
+            .then(setAlerts)
+            .catch((e) => console.error('Failed to fetch alerts', e))
+    }
+
+    useEffect(() => {
+        refresh()
+        const unlisten = listen(EVENTS.alert, () => refresh())
+        return () => {
+            unlisten.then(u => u())
+        }
+    }, [])
+
+    const acknowledge = async (id: number) => {
+        await invoke('acknowledge_alert', { id }).catch(() => { })
+        refresh()
+    }
+
+    const open = (emailId: number) => {
+        invoke('open_email_window', { id: emailId }).catch(() => { })
+    }
+
+    if (alerts.length === 0) return null
+    const unacknowledged = alerts.filter((a) => !a.acknowledged).length
+
+    return (
+        <div className="rounded-2xl border border-zinc-800/50 bg-zinc-900/40 backdrop-blur-sm overflow-hidden">
+            <div className="p-4 border-b border-zinc-800/50 bg-zinc-900/20 flex justify-between items-center">
+                <h3 className="font-medium text-zinc-300">Alerts</h3>
+                <span className="text-xs text-zinc-500">{unacknowledged} new</span>
+            </div>
+            <div className="p-4 space-y-3 text-sm max-h-80 overflow-y-auto">
+                {alerts.map((alert) => (
+                    <div key={alert.id} className="flex items-center justify-between gap-4">
+                        <div className="min-w-0">
+                            <span className={alert.acknowledged ? 'text-zinc-500' : 'text-amber-400'}>
+                                {KIND_LABELS[alert.kind] ?? alert.kind}
+                            </span>
+                            <p className={`truncate ${alert.acknowledged ? 'text-zinc-500' : 'text-zinc-200'}`}>{alert.message}</p>
+                        </div>
+                        <div className="flex items-center gap-4 shrink-0">
+                            {alert.email_id != null && (
+                                <button onClick={() => open(alert.email_id)} className="text-zinc-500 hover:text-blue-400 transition-colors">Open</button>
+                            )}
+                            {!alert.acknowledged && (
+                                <button onClick={() => acknowledge(alert.id)} className="text-zinc-500 hover:text-emerald-400 transition-colors">Acknowledge</button>
+                            )}
+                        </div>
+                    </div>
+                ))}
+            </div>
+        </div>
+    )
+}
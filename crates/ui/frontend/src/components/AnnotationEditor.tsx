@@ -0,0 +1,79 @@
+import { useState } from 'react'
+import { invoke } from '@tauri-apps/api/core'
+
+// Edits the user's note, tags, pin and star on an email or a project.
+// Clearing everything removes the annotation.
+export function AnnotationEditor({ emailId, project, annotation, onSaved, onClose, onLog }: {
+    emailId?: number,
+    project?: string,
+    annotation: any | null,
+    onSaved: (annotation: any | null) => void,
+    onClose: () => void,
+    onLog: (message: string, level?: 'info' | 'error' | 'warn') => void,
+}) {
+    const [note, setNote] = useState<string>(annotation?.note ?? '')
+    const [tags, setTags] = useState<string>((annotation?.tags ?? []).map((t: string) => `#${t}`).join(' '))
+    const [pinned, setPinned] = useState<boolean>(annotation?.pinned ?? false)
+    const [starred, setStarred] = useState<boolean>(annotation?.starred ?? false)
+    const [busy, setBusy] = useState(false)
+
+    const save = async () => {
+        setBusy(true)
+        try {
+            const saved = await invoke<any | null>('save_annotation', {
+                annotation: {
+                    email_id: emailId ?? null,
+                    project: project ?? null,
+                    note,
+                    tags: tags.split(/[\s,]+/).filter(Boolean),
+                    pinned,
+                    starred,
+                },
+            })
+            onSaved(saved)
+            onClose()
+        } catch (e) {
+            onLog(`Failed to save the note: ${e}`, 'error')
+        } finally {
+            setBusy(false)
+        }
+    }
+
+    return (
+        <div onClick={(e) => e.stopPropagation()} className="p-3 rounded-lg border border-zinc-800 bg-zinc-950 space-y-2 text-xs">
+            <textarea
+                value={note}
+                onChange={(e) => setNote(e.target.value)}
+                placeholder="Note"
+                rows={3}
+                className="w-full bg-zinc-900 border border-zinc-800 rounded-lg px-2 py-1 text-zinc-200"
+            />
+            <input
+                value={tags}
+                onChange={(e) => setTags(e.target.value)}
+                placeholder="#tags, searchable as #tag"
+                className="w-full bg-zinc-900 border border-zinc-800 rounded-lg px-2 py-1 text-zinc-200"
+            />
+            <div className="flex items-center gap-4 text-zinc-400">
+                <label className="flex items-center gap-1">
+                    <input type="checkbox" checked={pinned} onChange={(e) => setPinned(e.target.checked)} className="accent-indigo-500" />
+                    Pinned
+                </label>
+                <label className="flex items-center gap-1">
+                    <input type="checkbox" checked={starred} onChange={(e) => setStarred(e.target.checked)} className="accent-indigo-500" />
+                    Starred
+                </label>
+                <button onClick={onClose} disabled={busy} className="ml-auto text-zinc-500 hover:text-zinc-300 disabled:opacity-50">
+                    Cancel
+                </button>
+                <button
+                    onClick={save}
+                    disabled={busy}
+                    className="px-2 py-1 rounded-lg border border-indigo-500/30 text-indigo-300 hover:bg-indigo-500/10 disabled:opacity-50"
+                >
+                    Save
+                </button>
+            </div>
+        </div>
+    )
+}
@@ -0,0 +1,170 @@
+import { useEffect, useState } from 'react'
+import { invoke } from '@tauri-apps/api/core'
+import { listen } from '@tauri-apps/api/event'
+import { EVENTS } from '../events'
+import { formatDate, formatDateTime } from '../locale'
+
+// One tool a research answer called, while it runs or from the stored trace.
+function ToolCall({ call }: { call: any }) {
+    const args = Object.entries(call.arguments ?? {}).map(([k, v]) => `${k}: ${v}`).join(', ')
+    return (
+        <div className="flex gap-2 items-baseline text-xs pl-4 text-zinc-500">
+            <span className="font-mono text-zinc-400">{call.tool}</span>
+            <span className="truncate">{args}</span>
+            <span className={`shrink-0 ${call.error ? 'text-red-400' : ''}`}>
+                {call.error ?? (call.result ? `${call.result} · ${call.duration_ms} ms` : 'running…')}
+            </span>
+        </div>
+    )
+}
+
+// Questions about the mail, answered with cited emails. Follow-ups go to the
+// open session so they are read with what was asked before. Research answers
+// look things up with tools first, shown step by step. Citations the cited
+// email doesn't back up are flagged under the answer.
+export function AskNoodlePanel() {
+    const [sessions, setSessions] = useState<any[]>([])
+    const [sessionId, setSessionId] = useState<number | null>(null)
+    const [messages, setMessages] = useState<any[]>([])
+    const [question, setQuestion] = useState('')
+    const [asking, setAsking] = useState(false)
+    const [error, setError] = useState<string | null>(null)
+    const [research, setResearch] = useState(false)
+    const [calls, setCalls] = useState<any[]>([])
+
+    const refresh = () => {
+        invoke<any[]>('list_chat_sessions')
+            .then(setSessions)
+            .catch((e) => console.error('Failed to load chat sessions', e))
+    }
+
+    useEffect(refresh, [])
+
+    useEffect(() => {
+        // A call is announced when it starts and again when it is done.
+        const unlisten = listen(EVENTS.chatToolCall, (event: any) => {
+            const { call } = event.payload
+            setCalls((current) => [...current.filter((c) => c.step !== call.step), call])
+        })
+        return () => { unlisten.then((u) => u()) }
+    }, [])
+
+    const open = async (id: number | null) => {
+        setSessionId(id)
+        setError(null)
+        setMessages(id === null ? [] : await invoke<any[]>('get_chat_session', { id }).catch(() => []))
+    }
+
+    const ask = async () => {
+        if (!question.trim()) return
+        setAsking(true)
+        setError(null)
+        setCalls([])
+        try {
+            const turn: any = await invoke('ask_noodle', { sessionId, question, research })
+            setSessionId(turn.session_id)
+            setMessages((current) => [...current, turn.question, turn.answer])
+            setQuestion('')
+            refresh()
+        } catch (e) {
+            setError(String(e))
+            // The question is kept in the session even when answering failed.
+            if (sessionId !== null) open(sessionId)
+            refresh()
+        } finally {
+            setAsking(false)
+        }
+    }
+
+    const remove = async (id: number) => {
+        await invoke('delete_chat_session', { id }).catch(() => { })
+        if (sessionId === id) open(null)
+        refresh()
+    }
+
+    return (
+        <div className="max-w-4xl mx-auto space-y-6 animate-in fade-in slide-in-from-bottom-8 duration-500">
+            <div className="rounded-2xl border border-zinc-800/50 bg-zinc-900/40 p-4 space-y-3">
+                <div className="flex justify-between items-baseline">
+                    <h3 className="font-medium text-zinc-300">Ask Noodle</h3>
+                    {sessionId !== null && (
+                        <button onClick={() => open(null)} className="text-xs text-zinc-500 hover:text-zinc-300">New conversation</button>
+                    )}
+                </div>
+                {messages.map((message) => (
+                    <div key={message.id} className={message.role === 'user' ? 'text-sm text-zinc-200 font-medium' : 'space-y-2'}>
+                        {message.role === 'user' ? message.content : (
+                            <>
+                                {message.trace?.length > 0 && (
+                                    <details className="text-xs text-zinc-500">
+                                        <summary className="cursor-pointer hover:text-zinc-300">
+                                            Researched with {message.trace.length} tool {message.trace.length === 1 ? 'call' : 'calls'}
+                                        </summary>
+                                        {message.trace.map((call: any) => <ToolCall key={call.step} call={call} />)}
+                                    </details>
+                                )}
+                                <p className="text-sm text-zinc-300 whitespace-pre-wrap leading-relaxed">{message.content}</p>
+                                {message.sources.filter((s: any) => s.cited).map((source: any) => (
+                                    <div key={source.email_id} className="flex gap-2 items-baseline text-xs pl-4">
+                                        <span className={`font-mono ${message.unsupported?.some((u: any) => u.email_id === source.email_id) ? 'text-amber-400' : 'text-blue-400'}`}>#{source.email_id}</span>
+                                        <span className="text-zinc-300 truncate">{source.subject}</span>
+                                        <span className="text-zinc-500 shrink-0">{source.sender} · {formatDate(source.received_at)}</span>
+                                    </div>
+                                ))}
+                                {message.unsupported?.map((u: any, i: number) => (
+                                    <p key={i} className="text-xs text-amber-400/90 pl-4" title={u.reason}>
+                                        ⚠ #{u.email_id} may not support: “{u.claim}”
+                                    </p>
+                                ))}
+                            </>
+                        )}
+                    </div>
+                ))}
+                {asking && calls.map((call) => <ToolCall key={call.step} call={call} />)}
+                <div className="flex gap-2">
+                    <input
+                        type="text"
+                        placeholder={sessionId === null ? 'e.g. What did we agree with Acme about pricing?' : 'Ask a follow-up'}
+                        className="flex-1 bg-zinc-950 border border-zinc-800 rounded-lg px-3 py-2 text-sm focus:outline-none focus:border-blue-500/50"
+                        value={question}
+                        onChange={(e) => setQuestion(e.target.value)}
+                        onKeyDown={(e) => e.key === 'Enter' && !asking && ask()}
+                    />
+                    <label className="flex items-center gap-1 text-xs text-zinc-400" title="Look the answer up with several searches, threads, project health and tasks">
+                        <input type="checkbox" checked={research} onChange={(e) => setResearch(e.target.checked)} className="accent-blue-500" />
+                        Research
+                    </label>
+                    <button
+                        onClick={ask}
+                        disabled={asking || !question.trim()}
+                        className="px-4 py-2 bg-blue-600 hover:bg-blue-500 disabled:bg-zinc-800 disabled:text-zinc-500 rounded-lg text-sm font-medium"
+                    >
+                        {asking ? 'Thinking…' : 'Ask'}
+                    </button>
+                </div>
+                {error && <p className="text-sm text-red-400">{error}</p>}
+            </div>
+
+            {sessions.length > 0 && (
+                <div className="rounded-2xl border border-zinc-800/50 bg-zinc-900/40 p-4 space-y-2">
+                    <h3 className="font-medium text-zinc-300">Conversations</h3>
+                    {sessions.map((session) => (
+                        <div key={session.id} className="flex items-center justify-between gap-4 text-sm">
+                            <button
+                                onClick={() => open(session.id)}
+                                className={`text-left truncate hover:text-blue-400 ${session.id === sessionId ? 'text-blue-400' : 'text-zinc-300'}`}
+                            >
+                                {session.title}
+                                <span className="text-xs text-zinc-500 ml-2">{session.message_count} messages</span>
+                            </button>
+                            <div className="flex items-center gap-4 shrink-0">
+                                <span className="text-xs text-zinc-500">{formatDateTime(session.updated_at)}</span>
+                                <button onClick={() => remove(session.id)} className="text-zinc-500 hover:text-red-400 transition-colors">Delete</button>
+                            </div>
+                        </div>
+                    ))}
+                </div>
+            )}
+        </div>
+    )
+}
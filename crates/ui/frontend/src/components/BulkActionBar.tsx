@@ -0,0 +1,107 @@
+import { useEffect, useState } from 'react'
+import { invoke } from '@tauri-apps/api/core'
+import { listen } from '@tauri-apps/api/event'
+import { EVENTS } from '../events'
+
+const TRIAGE_STATES = [
+    { value: 'needs_response', label: 'Needs response' },
+    { value: 'waiting', label: 'Waiting' },
+    { value: 'done', label: 'Done' },
+]
+
+// Actions on the emails ticked in the list, each run as one backend call.
+export function BulkActionBar({ ids, onDone, onClear }: {
+    ids: number[],
+    onDone: (result: any, change: { triage?: string, project?: string }) => void,
+    onClear: () => void,
+}) {
+    const [progress, setProgress] = useState<any>(null)
+    const [busy, setBusy] = useState(false)
+    const [project, setProject] = useState('')
+    const [error, setError] = useState<string | null>(null)
+
+    useEffect(() => {
+        const unlisten = listen(EVENTS.bulkProgress, (event: any) => setProgress(event.payload))
+        return () => { unlisten.then(u => u()) }
+    }, [])
+
+    const run = async (command: string, args: Record<string, unknown>, change: { triage?: string, project?: string } = {}) => {
+        setBusy(true)
+        setError(null)
+        setProgress(null)
+        try {
+            const result = await invoke(command, { ids, ...args })
+            onDone(result, change)
+        } catch (e) {
+            setError(String(e))
+        } finally {
+            setBusy(false)
+        }
+    }
+
+    const trash = () => run('bulk_trash', {})
+
+    const purge = () => {
+        if (!confirm(`Delete ${ids.length} emails from Noodle for good? They stay in Outlook but won't be indexed again.`)) return
+        run('bulk_purge', {})
+    }
+
+    return (
+        <div className="sticky top-0 z-10 p-3 rounded-xl border border-indigo-500/30 bg-zinc-950/95 backdrop-blur flex flex-wrap items-center gap-2 text-xs">
+            <span className="font-bold text-indigo-300">{ids.length} selected</span>
+            {TRIAGE_STATES.map((state) => (
+                <button
+                    key={state.value}
+                    disabled={busy}
+                    onClick={() => run('bulk_set_triage', { triage: state.value }, { triage: state.value })}
+                    className="px-2 py-1 rounded-lg border border-zinc-800 text-zinc-300 hover:bg-zinc-800 disabled:opacity-50"
+                >
+                    {state.label}
+                </button>
+            ))}
+            <input
+                value={project}
+                onChange={(e) => setProject(e.target.value)}
+                placeholder="Project"
+                className="w-32 bg-zinc-900 border border-zinc-800 rounded-lg px-2 py-1 text-zinc-200"
+            />
+            <button
+                disabled={busy || !project.trim()}
+                onClick={() => run('bulk_reassign_project', { project }, { project: project.trim() })}
+                className="px-2 py-1 rounded-lg border border-zinc-800 text-zinc-300 hover:bg-zinc-800 disabled:opacity-50"
+            >
+                Move to project
+            </button>
+            <button
+                disabled={busy}
+                onClick={() => run('bulk_reprocess', {})}
+                className="px-2 py-1 rounded-lg border border-zinc-800 text-zinc-300 hover:bg-zinc-800 disabled:opacity-50"
+            >
+                Reprocess
+            </button>
+            <button
+                disabled={busy}
+                onClick={trash}
+                className="px-2 py-1 rounded-lg border border-zinc-800 text-zinc-300 hover:bg-zinc-800 disabled:opacity-50"
+            >
+                Delete
+            </button>
+            <button
+                disabled={busy}
+                onClick={purge}
+                className="px-2 py-1 rounded-lg border border-red-500/30 text-red-400 hover:bg-red-500/10 disabled:opacity-50"
+            >
+                Purge
+            </button>
+            <button onClick={onClear} disabled={busy} className="ml-auto text-zinc-500 hover:text-zinc-300 disabled:opacity-50">
+                Clear
+            </button>
+            {busy && progress && (
+                <span className="w-full text-zinc-500">
+                    {progress.done} of {progress.total} done{progress.failed > 0 ? `, ${progress.failed} failed` : ''}
+                </span>
+            )}
+            {error && <span className="w-full text-red-400">{error}</span>}
+        </div>
+    )
+}
@@ -0,0 +1,175 @@
+import { useEffect, useState } from 'react'
+import { invoke } from '@tauri-apps/api/core'
+
+const AUDIT_LIMIT = 50
+
+const MATCH_LABELS: Record<string, string> = {
+    project: 'Project',
+    sender: 'Sender',
+    primary_type: 'Email type',
+}
+
+// Outlook's OlCategoryColor values.
+const COLORS: [number, string, string][] = [
+    [0, 'None', 'bg-zinc-600'],
+    [1, 'Red', 'bg-red-500'],
+    [2, 'Orange', 'bg-orange-500'],
+    [4, 'Yellow', 'bg-yellow-400'],
+    [5, 'Green', 'bg-green-500'],
+    [6, 'Teal', 'bg-teal-500'],
+    [8, 'Blue', 'bg-blue-500'],
+    [9, 'Purple', 'bg-purple-500'],
+]
+
+const ACTION_LABELS: Record<string, string> = {
+    rule_added: 'Rule added',
+    rule_updated: 'Rule changed',
+    rule_removed: 'Rule removed',
+    labelled: 'Labelled',
+    written: 'Written to Outlook',
+    already_set: 'Already in Outlook',
+    write_failed: 'Outlook write failed',
+}
+
+const colorClass = (color: number) => COLORS.find(([value]) => value === color)?.[2] ?? 'bg-zinc-600'
+
+export function CategoryRules({ onLog }: { onLog: (message: string, level?: 'info' | 'error' | 'warn') => void }) {
+    const [rules, setRules] = useState<any[]>([])
+    const [audit, setAudit] = useState<any[]>([])
+    const [showAudit, setShowAudit] = useState(false)
+    const [matchOn, setMatchOn] = useState('project')
+    const [pattern, setPattern] = useState('')
+    const [category, setCategory] = useState('')
+    const [color, setColor] = useState(8)
+    const [writeBack, setWriteBack] = useState(false)
+
+    const refresh = () => {
+        invoke<any[]>('list_category_rules')
+            .then(setRules)
+            .catch((e) => console.error('Failed to load category rules', e))
+        invoke<any[]>('list_category_audit', { limit: AUDIT_LIMIT })
+            .then(setAudit)
+            .catch((e) => console.error('Failed to load category audit', e))
+    }
+
+    useEffect(refresh, [])
+
+    const add = async () => {
+        try {
+            await invoke('add_category_rule', { matchOn, pattern, category, color, writeBack })
+            setPattern('')
+            setCategory('')
+            refresh()
+        } catch (e) {
+            onLog(`Failed to add category rule: ${e}`, 'error')
+        }
+    }
+
+    const toggleWriteBack = async (rule: any) => {
+        await invoke('update_category_rule', { rule: { ...rule, write_back: !rule.write_back } })
+            .catch((e) => onLog(`Failed to update category rule: ${e}`, 'error'))
+        refresh()
+    }
+
+    const remove = async (id: number) => {
+        await invoke('remove_category_rule', { id })
+            .catch((e) => onLog(`Failed to remove category rule: ${e}`, 'error'))
+        refresh()
+    }
+
+    return (
+        <div className="rounded-2xl border border-zinc-800/50 bg-zinc-900/40 backdrop-blur-sm overflow-hidden">
+            <div className="p-4 border-b border-zinc-800/50 bg-zinc-900/20 flex items-start justify-between gap-4">
+                <div>
+                    <h3 className="font-medium text-zinc-300">Category Rules</h3>
+                    <p className="text-xs text-zinc-500 mt-1">
+                        Label extracted emails with an Outlook category. Rules set to write to Outlook also add it to the message in your mailbox.
+                    </p>
+                </div>
+                <button onClick={() => setShowAudit(!showAudit)} className="text-xs text-zinc-500 hover:text-zinc-300 shrink-0">
+                    {showAudit ? 'Hide audit log' : 'Audit log'}
+                </button>
+            </div>
+            <div className="p-4 space-y-4 text-sm">
+                <form
+                    onSubmit={(e) => {
+                        e.preventDefault()
+                        add()
+                    }}
+                    className="flex flex-wrap gap-2 items-center"
+                >
+                    <select
+                        value={matchOn}
+                        onChange={(e) => setMatchOn(e.target.value)}
+                        className="bg-zinc-950 border border-zinc-800 rounded-lg px-2 py-2 text-zinc-200 focus:outline-none focus:border-blue-500/50"
+                    >
+                        {Object.entries(MATCH_LABELS).map(([value, label]) => (
+                            <option key={value} value={value}>{label}</option>
+                        ))}
+                    </select>
+                    <input
+                        value={pattern}
+                        onChange={(e) => setPattern(e.target.value)}
+                        placeholder={matchOn === 'sender' ? 'name@example.com or @example.com' : matchOn === 'primary_type' ? 'request' : 'Project name'}
+                        className="flex-1 min-w-[140px] bg-zinc-950 border border-zinc-800 rounded-lg px-3 py-2 text-zinc-200 focus:outline-none focus:border-blue-500/50"
+                    />
+                    <span className="text-zinc-600">→</span>
+                    <input
+                        value={category}
+                        onChange={(e) => setCategory(e.target.value)}
+                        placeholder="Category"
+                        className="w-32 bg-zinc-950 border border-zinc-800 rounded-lg px-3 py-2 text-zinc-200 focus:outline-none focus:border-blue-500/50"
+                    />
+                    <select
+                        value={color}
+                        onChange={(e) => setColor(Number(e.target.value))}
+                        className="bg-zinc-950 border border-zinc-800 rounded-lg px-2 py-2 text-zinc-200 focus:outline-none focus:border-blue-500/50"
+                    >
+                        {COLORS.map(([value, label]) => (
+                            <option key={value} value={value}>{label}</option>
+                        ))}
+                    </select>
+                    <label className="flex items-center gap-1 text-xs text-zinc-400">
+                        <input type="checkbox" checked={writeBack} onChange={(e) => setWriteBack(e.target.checked)} />
+                        Write to Outlook
+                    </label>
+                    <button type="submit" className="text-blue-400 hover:text-blue-300">Add</button>
+                </form>
+                {rules.length === 0 && <p className="text-zinc-500">No category rules yet.</p>}
+                {rules.map((rule) => (
+                    <div key={rule.id} className="flex items-center justify-between gap-4">
+                        <div className="flex items-center gap-2 min-w-0">
+                            <span className={`w-2.5 h-2.5 rounded-full shrink-0 ${colorClass(rule.color)}`} />
+                            <span className="truncate text-zinc-200">
+                                {MATCH_LABELS[rule.match_on]} is {rule.pattern} → {rule.category}
+                            </span>
+                        </div>
+                        <div className="flex items-center gap-3 shrink-0">
+                            <label className="flex items-center gap-1 text-xs text-zinc-400" title="Also set the category on the message in Outlook">
+                                <input type="checkbox" checked={rule.write_back} onChange={() => toggleWriteBack(rule)} />
+                                Write to Outlook
+                            </label>
+                            <button onClick={() => remove(rule.id)} className="text-zinc-500 hover:text-red-400 transition-colors">Remove</button>
+                        </div>
+                    </div>
+                ))}
+                {showAudit && (
+                    <div className="space-y-1 pt-2 border-t border-zinc-800/50 max-h-64 overflow-y-auto">
+                        {audit.length === 0 && <p className="text-xs text-zinc-500">Nothing recorded yet.</p>}
+                        {audit.map((entry) => (
+                            <div key={entry.id} className="flex items-baseline gap-3 text-xs">
+                                <span className="font-mono text-zinc-600 shrink-0">{new Date(entry.created_at).toLocaleString()}</span>
+                                <span className={entry.action === 'write_failed' ? 'text-red-400' : 'text-zinc-300'}>
+                                    {ACTION_LABELS[entry.action] ?? entry.action}
+                                </span>
+                                <span className="text-zinc-400">{entry.category}</span>
+                                {entry.email_id && <span className="text-zinc-600">email {entry.email_id}</span>}
+                                {entry.detail && <span className="text-zinc-500 truncate">{entry.detail}</span>}
+                            </div>
+                        ))}
+                    </div>
+                )}
+            </div>
+        </div>
+    )
+}
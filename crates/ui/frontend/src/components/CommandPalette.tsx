@@ -0,0 +1,122 @@
+import { useEffect, useRef, useState, type KeyboardEvent } from 'react'
+import { invoke } from '@tauri-apps/api/core'
+
+// Runs any action from get_commands by keyboard: filter, pick with the
+// arrow keys and Enter, then answer its arguments one by one.
+export function CommandPalette({ onClose, onLog }: {
+    onClose: () => void,
+    onLog: (message: string, level?: 'info' | 'error' | 'warn') => void,
+}) {
+    const [commands, setCommands] = useState<any[]>([])
+    const [input, setInput] = useState('')
+    const [highlighted, setHighlighted] = useState(0)
+    const [command, setCommand] = useState<any | null>(null)
+    const [args, setArgs] = useState<Record<string, string>>({})
+    const [error, setError] = useState<string | null>(null)
+    const inputRef = useRef<HTMLInputElement>(null)
+
+    useEffect(() => {
+        invoke<any[]>('get_commands')
+            .then(setCommands)
+            .catch((e) => setError(String(e)))
+    }, [])
+
+    useEffect(() => { inputRef.current?.focus() }, [command, args])
+
+    const arg = command?.args[Object.keys(args).length]
+    const needle = input.trim().toLowerCase()
+    const options: string[] = arg
+        ? (arg.options ?? []).filter((o: string) => o.toLowerCase().includes(needle))
+        : commands.filter((c) => c.title.toLowerCase().includes(needle)).map((c) => c.title)
+
+    const run = async (command: any, args: Record<string, string>) => {
+        try {
+            onLog(await invoke<string>('execute_command', { id: command.id, args }))
+            onClose()
+        } catch (e) {
+            setError(String(e))
+        }
+    }
+
+    const choose = (value: string) => {
+        setError(null)
+        if (!command) {
+            const picked = commands.find((c) => c.title === value)
+            if (!picked) return
+            setInput('')
+            setHighlighted(0)
+            if (picked.args.length === 0) {
+                run(picked, {})
+            } else {
+                setCommand(picked)
+            }
+            return
+        }
+        if (arg.required && !value.trim()) return
+        const next = { ...args, [arg.name]: value.trim() }
+        setInput('')
+        setHighlighted(0)
+        if (Object.keys(next).length === command.args.length) {
+            run(command, next)
+        } else {
+            setArgs(next)
+        }
+    }
+
+    const onKeyDown = (e: KeyboardEvent) => {
+        if (e.key === 'Escape') {
+            onClose()
+        } else if (e.key === 'ArrowDown') {
+            e.preventDefault()
+            setHighlighted((h) => Math.min(h + 1, options.length - 1))
+        } else if (e.key === 'ArrowUp') {
+            e.preventDefault()
+            setHighlighted((h) => Math.max(h - 1, 0))
+        } else if (e.key === 'Enter') {
+            e.preventDefault()
+            // Free text and numbers are taken as typed; lists need a match.
+            choose(options[highlighted] ?? (arg && arg.type !== 'choice' ? input : ''))
+        }
+    }
+
+    return (
+        <div onClick={onClose} className="fixed inset-0 bg-black/50 backdrop-blur-sm z-50 flex items-start justify-center p-4 pt-[15vh] animate-in fade-in duration-200">
+            <div onClick={(e) => e.stopPropagation()} className="bg-zinc-900 border border-zinc-800 rounded-2xl max-w-lg w-full shadow-2xl overflow-hidden text-sm">
+                {command && (
+                    <div className="px-4 pt-3 text-xs text-zinc-500">
+                        {command.title}
+                        {command.args.slice(0, Object.keys(args).length).map((a: any) => ` · ${args[a.name]}`)}
+                    </div>
+                )}
+                <input
+                    ref={inputRef}
+                    value={input}
+                    onChange={(e) => { setInput(e.target.value); setHighlighted(0) }}
+                    onKeyDown={onKeyDown}
+                    type={arg?.type === 'integer' ? 'number' : 'text'}
+                    placeholder={arg ? arg.label : 'Type a command…'}
+                    className="w-full bg-transparent px-4 py-3 text-zinc-200 outline-none"
+                />
+                {options.length > 0 && (
+                    <ul className="max-h-72 overflow-y-auto border-t border-zinc-800">
+                        {options.map((option, i) => {
+                            const description = command ? null : commands.find((c) => c.title === option)?.description
+                            return (
+                                <li
+                                    key={option}
+                                    onMouseEnter={() => setHighlighted(i)}
+                                    onClick={() => choose(option)}
+                                    className={`px-4 py-2 cursor-pointer ${i === highlighted ? 'bg-zinc-800 text-white' : 'text-zinc-300'}`}
+                                >
+                                    {option}
+                                    {description && <span className="ml-2 text-xs text-zinc-500">{description}</span>}
+                                </li>
+                            )
+                        })}
+                    </ul>
+                )}
+                {error && <p className="px-4 py-2 text-xs text-red-400 border-t border-zinc-800">{error}</p>}
+            </div>
+        </div>
+    )
+}
@@ -0,0 +1,96 @@
+import { useEffect, useRef, useState } from 'react'
+import { invoke } from '@tauri-apps/api/core'
+
+// The webview's speech recognition, which uses the OS speech service.
+const Recognition = (window as any).SpeechRecognition ?? (window as any).webkitSpeechRecognition
+
+// Dictate a reply to an email and have it written up in the user's style.
+export function DictationReply({ emailId }: { emailId: number }) {
+    const [transcript, setTranscript] = useState('')
+    const [listening, setListening] = useState(false)
+    const [drafting, setDrafting] = useState(false)
+    const [draft, setDraft] = useState<string | null>(null)
+    const [error, setError] = useState<string | null>(null)
+    const recognition = useRef<any>(null)
+
+    useEffect(() => () => recognition.current?.stop(), [])
+
+    const startListening = () => {
+        const rec = new Recognition()
+        rec.continuous = true
+        rec.interimResults = false
+        rec.lang = navigator.language
+        rec.onresult = (event: any) => {
+            const spoken = Array.from(event.results)
+                .slice(event.resultIndex)
+                .map((result: any) => result[0].transcript.trim())
+                .join(' ')
+            setTranscript((current) => (current ? `${current} ${spoken}` : spoken))
+        }
+        rec.onerror = (event: any) => setError(`Speech recognition failed: ${event.error}`)
+        rec.onend = () => setListening(false)
+        recognition.current = rec
+        setError(null)
+        rec.start()
+        setListening(true)
+    }
+
+    const stopListening = () => recognition.current?.stop()
+
+    const write = async () => {
+        stopListening()
+        setDrafting(true)
+        setError(null)
+        try {
+            setDraft(await invoke<string>('draft_from_dictation', { emailId, transcript }))
+        } catch (e) {
+            setError(String(e))
+        } finally {
+            setDrafting(false)
+        }
+    }
+
+    return (
+        <div className="px-6 py-4 border-t border-zinc-800 space-y-2 text-sm">
+            <div className="flex items-center justify-between gap-4">
+                <span className="text-zinc-300">Dictate a reply</span>
+                {Recognition ? (
+                    <button
+                        onClick={listening ? stopListening : startListening}
+                        className={listening ? 'text-xs text-red-400 hover:text-red-300' : 'text-xs text-blue-400 hover:text-blue-300'}
+                    >
+                        {listening ? '● Stop listening' : 'Start dictating'}
+                    </button>
+                ) : (
+                    <span className="text-xs text-zinc-500">Speech recognition isn't available; type your notes instead.</span>
+                )}
+            </div>
+            <textarea
+                value={transcript}
+                onChange={(e) => setTranscript(e.target.value)}
+                placeholder="What should the reply say?"
+                rows={3}
+                className="w-full bg-zinc-900 border border-zinc-800 rounded-lg px-3 py-2 text-zinc-200 focus:outline-none focus:border-blue-500/50"
+            />
+            <div className="flex justify-end">
+                <button onClick={write} disabled={drafting || !transcript.trim()} className="text-xs text-blue-400 hover:text-blue-300 disabled:text-zinc-600">
+                    {drafting ? 'Writing…' : 'Write reply'}
+                </button>
+            </div>
+            {error && <p className="text-xs text-red-400">{error}</p>}
+            {draft !== null && (
+                <div className="space-y-1">
+                    <textarea
+                        value={draft}
+                        onChange={(e) => setDraft(e.target.value)}
+                        rows={8}
+                        className="w-full bg-zinc-900 border border-zinc-800 rounded-lg px-3 py-2 text-zinc-200 focus:outline-none focus:border-blue-500/50"
+                    />
+                    <button onClick={() => navigator.clipboard.writeText(draft)} className="text-xs text-zinc-500 hover:text-zinc-300">
+                        Copy reply
+                    </button>
+                </div>
+            )}
+        </div>
+    )
+}
@@ -0,0 +1,93 @@
+import { useEffect, useState } from 'react'
+import { invoke } from '@tauri-apps/api/core'
+import { listen } from '@tauri-apps/api/event'
+import { formatDateTime, setLocale } from '../locale'
+import { EVENTS } from '../events'
+import { DictationReply } from './DictationReply'
+
+function formatSize(bytes: number) {
+    if (bytes < 1024) return `${bytes} B`
+    if (bytes < 1024 * 1024) return `${Math.round(bytes / 1024)} KB`
+    return `${(bytes / (1024 * 1024)).toFixed(1)} MB`
+}
+
+// A single email in its own window, opened with `open_email_window`.
+export function EmailReader({ id }: { id: number }) {
+    const [email, setEmail] = useState<any>(null)
+    const [error, setError] = useState<string | null>(null)
+    const [outlookError, setOutlookError] = useState<string | null>(null)
+    const [attachments, setAttachments] = useState<any[]>([])
+
+    useEffect(() => {
+        // A separate window, so it reads the locale itself.
+        invoke<string | null>('get_config', { key: 'locale' })
+            .then((locale) => setLocale(locale ?? ''))
+            .catch(() => { })
+            .then(() => invoke('get_email', { id }))
+            .then(setEmail)
+            .catch((e) => setError(String(e)))
+        invoke<any[]>('list_attachments', { emailId: id })
+            .then(setAttachments)
+            .catch(() => setAttachments([]))
+        // The body was shown before the lock; hide it until the main window unlocks.
+        const unlistenLock = listen(EVENTS.appLocked, () => {
+            setEmail((current: any) => current && { ...current, body_text: null, body_locked: true })
+        })
+        return () => {
+            unlistenLock.then(unlisten => unlisten())
+        }
+    }, [id])
+
+    if (error) {
+        return <div className="h-screen bg-zinc-950 text-red-400 p-6 text-sm">{error}</div>
+    }
+    if (!email) {
+        return <div className="h-screen bg-zinc-950 text-zinc-500 p-6 text-sm">Loading…</div>
+    }
+
+    return (
+        <div className="h-screen flex flex-col bg-zinc-950 text-zinc-100">
+            <div className="px-6 py-4 border-b border-zinc-800 space-y-1">
+                <h1 className="text-lg font-semibold text-zinc-100">{email.subject}</h1>
+                <div className="flex justify-between text-xs text-zinc-500">
+                    <span>{email.sender}</span>
+                    <span>{formatDateTime(email.received_at)}</span>
+                </div>
+                <button
+                    onClick={() => invoke('open_in_outlook', { emailId: id }).catch((e) => setOutlookError(String(e)))}
+                    className="text-xs text-blue-400 hover:text-blue-300"
+                >
+                    Open in Outlook
+                </button>
+                {outlookError && <p className="text-xs text-red-400">{outlookError}</p>}
+            </div>
+            <div className="flex-1 overflow-y-auto px-6 py-4">
+                {email.body_locked ? (
+                    <p className="text-sm text-zinc-500">Unlock Noodle in the main window, then reopen this email to read it.</p>
+                ) : (
+                    <p className="text-sm text-zinc-300 whitespace-pre-wrap leading-relaxed">{email.body_text}</p>
+                )}
+                {attachments.length > 0 && (
+                    <ul className="mt-6 space-y-1 text-xs">
+                        {attachments.map((a) => (
+                            <li key={a.id} className="flex justify-between gap-4 text-zinc-400">
+                                <span className="truncate">{a.filename}</span>
+                                <span className={a.skipped_reason ? 'text-zinc-600' : 'text-zinc-500'}>
+                                    {formatSize(a.size_bytes)}
+                                    {a.skipped_reason === 'too_large' && ' · too large to keep'}
+                                    {a.skipped_reason === 'type_not_allowed' && ' · type not kept'}
+                                </span>
+                            </li>
+                        ))}
+                    </ul>
+                )}
+                {email.changes?.length > 0 && (
+                    <p className="mt-6 text-xs text-purple-400">
+                        Edited {email.changes.length} time{email.changes.length > 1 ? 's' : ''} since it was first indexed
+                    </p>
+                )}
+            </div>
+            {!email.body_locked && <DictationReply emailId={id} />}
+        </div>
+    )
+}
@@ -3,7 +3,8 @@ import ForceGraph2D from 'react-force-graph-2d'
 
 interface GraphProps {
     nodes: { id: string; name: string; type: string }[]
-    links: { source: string; target: string; type: string }[]
+    // Inferred relations also carry a confidence and the user's review status.
+    links: { source: string; target: string; type: string; confidence?: number; status?: string }[]
 }
 
 export function EntityGraph({ nodes, links }: GraphProps) {
@@ -18,7 +19,9 @@ export function EntityGraph({ nodes, links }: GraphProps) {
                 graphData={graphData}
                 nodeLabel="name"
                 nodeAutoColorBy="type"
-                linkColor={() => '#3f3f46'}
+                linkColor={(l: any) => l.status === 'confirmed' ? '#3b82f6' : '#3f3f46'}
+                linkLineDash={(l: any) => l.status === 'suggested' ? [2, 2] : null}
+                linkLabel={(l: any) => l.confidence === undefined ? l.type : `${l.type} (${Math.round(l.confidence * 100)}%)`}
                 linkDirectionalArrowLength={3.5}
                 linkDirectionalArrowRelPos={1}
                 enableNodeDrag={true}
@@ -0,0 +1,248 @@
+import { useEffect, useState } from 'react'
+import { invoke } from '@tauri-apps/api/core'
+import { listen } from '@tauri-apps/api/event'
+import { FlaskConical, Trash2 } from 'lucide-react'
+import { EVENTS } from '../events'
+
+type Variant = {
+    label: string,
+    prompt_id: string | null,
+    model: string | null,
+    prompt_price: number | null,
+    completion_price: number | null,
+}
+
+const percent = (share: number) => `${Math.round(share * 100)}%`
+
+// Runs two extraction set-ups (prompt and model) over the same emails and
+// shows how their results compare, so prompt changes are judged on data.
+export function ExtractionExperiments({ onLog }: {
+    onLog: (message: string, level?: 'info' | 'warn' | 'error') => void,
+}) {
+    const [prompts, setPrompts] = useState<any[]>([])
+    const [experiments, setExperiments] = useState<any[]>([])
+    const [name, setName] = useState('')
+    const [variants, setVariants] = useState<Variant[]>([
+        { label: 'Current', prompt_id: null, model: null, prompt_price: null, completion_price: null },
+        { label: 'Candidate', prompt_id: null, model: null, prompt_price: null, completion_price: null },
+    ])
+    const [sampleSize, setSampleSize] = useState('20')
+    const [emailIds, setEmailIds] = useState('')
+    const [opened, setOpened] = useState<any | null>(null)
+    const [busy, setBusy] = useState(false)
+    const [progress, setProgress] = useState<{ done: number, total: number } | null>(null)
+
+    const refresh = () => {
+        invoke<any[]>('list_extraction_experiments')
+            .then(setExperiments)
+            .catch((e) => console.error('Failed to load experiments', e))
+    }
+
+    useEffect(() => {
+        refresh()
+        invoke<any[]>('list_prompts')
+            .then((all) => setPrompts(all.filter((p) => /\{\{\s*email\s*\}\}/.test(p.content))))
+            .catch((e) => console.error('Failed to load prompts', e))
+        const unlisten = listen(EVENTS.experimentProgress, (event: any) => setProgress(event.payload))
+        return () => { unlisten.then(u => u()) }
+    }, [])
+
+    const setVariant = (index: number, change: Partial<Variant>) =>
+        setVariants((current) => current.map((v, i) => i === index ? { ...v, ...change } : v))
+
+    const run = async () => {
+        setBusy(true)
+        setProgress(null)
+        try {
+            const ids = emailIds.split(/[\s,]+/).filter(Boolean).map(Number).filter((id) => !Number.isNaN(id))
+            const experiment: any = await invoke('run_extraction_experiment', {
+                name,
+                variantA: variants[0],
+                variantB: variants[1],
+                emailIds: ids.length > 0 ? ids : null,
+                sampleSize: Number(sampleSize) || null,
+            })
+            setOpened(experiment)
+            onLog(`Experiment ${experiment.name}: ${percent(experiment.comparison.agreement)} agreement over ${experiment.comparison.compared} emails`)
+            refresh()
+        } catch (e) {
+            onLog(`Experiment failed: ${e}`, 'error')
+        } finally {
+            setBusy(false)
+        }
+    }
+
+    const open = async (id: number) => {
+        try {
+            setOpened(await invoke('get_extraction_experiment', { id }))
+        } catch (e) {
+            onLog(`Failed to load experiment: ${e}`, 'error')
+        }
+    }
+
+    const remove = async (id: number) => {
+        try {
+            await invoke('delete_extraction_experiment', { id })
+            if (opened?.id === id) setOpened(null)
+            refresh()
+        } catch (e) {
+            onLog(`Failed to delete experiment: ${e}`, 'error')
+        }
+    }
+
+    return (
+        <section className="bg-zinc-900/40 border border-zinc-800/50 rounded-2xl p-6 space-y-4">
+            <h3 className="text-lg font-medium flex items-center gap-2">
+                <FlaskConical className="w-5 h-5 text-purple-400" />
+                Extraction Experiments
+            </h3>
+            <div className="grid grid-cols-2 gap-4">
+                {variants.map((variant, index) => (
+                    <div key={index} className="p-3 rounded-lg border border-zinc-800 bg-zinc-950 space-y-2 text-xs">
+                        <input
+                            value={variant.label}
+                            onChange={(e) => setVariant(index, { label: e.target.value })}
+                            placeholder={index === 0 ? 'A' : 'B'}
+                            className="w-full bg-zinc-900 border border-zinc-800 rounded-lg px-2 py-1 text-zinc-200"
+                        />
+                        <select
+                            value={variant.prompt_id ?? ''}
+                            onChange={(e) => setVariant(index, { prompt_id: e.target.value || null })}
+                            className="w-full bg-zinc-900 border border-zinc-800 rounded-lg px-2 py-1 text-zinc-200"
+                        >
+                            <option value="">Built-in extraction prompt</option>
+                            {prompts.map((prompt) => <option key={prompt.id} value={prompt.id}>{prompt.name}</option>)}
+                        </select>
+                        <input
+                            value={variant.model ?? ''}
+                            onChange={(e) => setVariant(index, { model: e.target.value || null })}
+                            placeholder="Model (configured one if empty)"
+                            className="w-full bg-zinc-900 border border-zinc-800 rounded-lg px-2 py-1 text-zinc-200"
+                        />
+                        <div className="flex gap-2">
+                            {(['prompt_price', 'completion_price'] as const).map((key) => (
+                                <input
+                                    key={key}
+                                    type="number"
+                                    min={0}
+                                    step="any"
+                                    value={variant[key] ?? ''}
+                                    onChange={(e) => setVariant(index, { [key]: e.target.value === '' ? null : Number(e.target.value) })}
+                                    placeholder={key === 'prompt_price' ? '$ / M prompt tokens' : '$ / M completion tokens'}
+                                    className="w-full bg-zinc-900 border border-zinc-800 rounded-lg px-2 py-1 text-zinc-200"
+                                />
+                            ))}
+                        </div>
+                    </div>
+                ))}
+            </div>
+            <div className="flex items-center gap-2 text-xs">
+                <input
+                    value={name}
+                    onChange={(e) => setName(e.target.value)}
+                    placeholder="Experiment name"
+                    className="w-40 bg-zinc-950 border border-zinc-800 rounded-lg px-2 py-1 text-zinc-200"
+                />
+                <input
+                    type="number"
+                    min={1}
+                    value={sampleSize}
+                    onChange={(e) => setSampleSize(e.target.value)}
+                    title="Emails picked at random"
+                    className="w-16 bg-zinc-950 border border-zinc-800 rounded-lg px-2 py-1 text-zinc-200"
+                />
+                <input
+                    value={emailIds}
+                    onChange={(e) => setEmailIds(e.target.value)}
+                    placeholder="Or email ids, comma separated"
+                    className="flex-1 bg-zinc-950 border border-zinc-800 rounded-lg px-2 py-1 text-zinc-200"
+                />
+                <button
+                    onClick={run}
+                    disabled={busy}
+                    className="px-3 py-1 rounded-lg border border-purple-500/30 text-purple-300 hover:bg-purple-500/10 disabled:opacity-50"
+                >
+                    {busy ? (progress ? `Running… ${progress.done} / ${progress.total}` : 'Running…') : 'Run experiment'}
+                </button>
+            </div>
+
+            {opened && <ExperimentResult experiment={opened} onClose={() => setOpened(null)} />}
+
+            {experiments.length > 0 && (
+                <div className="pt-4 border-t border-zinc-800/50 space-y-1 text-sm">
+                    {experiments.map((experiment) => (
+                        <div key={experiment.id} className="flex items-center justify-between gap-4">
+                            <button onClick={() => open(experiment.id)} className="text-zinc-300 hover:text-zinc-100 truncate text-left">
+                                {experiment.name}
+                                <span className="ml-2 text-xs text-zinc-500">
+                                    {percent(experiment.comparison.agreement)} agreement · {experiment.comparison.compared} emails · {new Date(experiment.created_at).toLocaleDateString()}
+                                </span>
+                            </button>
+                            <button onClick={() => remove(experiment.id)} className="text-zinc-500 hover:text-red-400 shrink-0" title="Delete experiment">
+                                <Trash2 className="w-4 h-4" />
+                            </button>
+                        </div>
+                    ))}
+                </div>
+            )}
+        </section>
+    )
+}
+
+function ExperimentResult({ experiment, onClose }: { experiment: any, onClose: () => void }) {
+    const { comparison, variant_a: a, variant_b: b } = experiment
+    const rows: [string, (s: any) => string][] = [
+        ['Extracted', (s) => `${s.succeeded} / ${s.succeeded + s.failed}`],
+        ['Mean confidence', (s) => s.mean_confidence.toFixed(2)],
+        ['Prompt tokens', (s) => s.prompt_tokens.toLocaleString()],
+        ['Completion tokens', (s) => s.completion_tokens.toLocaleString()],
+        ['Cost', (s) => s.cost == null ? '—' : `$${s.cost.toFixed(4)}`],
+        ['Mean time', (s) => `${(s.mean_duration_ms / 1000).toFixed(1)}s`],
+    ]
+    const disagreeing = experiment.emails.filter((e: any) => e.disagreements.length > 0 || e.a.error || e.b.error)
+
+    return (
+        <div className="p-4 rounded-lg border border-zinc-800 bg-zinc-950 space-y-3 text-xs">
+            <div className="flex justify-between">
+                <span className="text-zinc-300 font-medium text-sm">{experiment.name}</span>
+                <button onClick={onClose} className="text-zinc-500 hover:text-zinc-300">Close</button>
+            </div>
+            <table className="w-full text-left">
+                <thead className="text-zinc-500">
+                    <tr><th></th><th>{a.label}</th><th>{b.label}</th></tr>
+                </thead>
+                <tbody className="text-zinc-300">
+                    {rows.map(([label, value]) => (
+                        <tr key={label}><td className="text-zinc-500">{label}</td><td>{value(comparison.a)}</td><td>{value(comparison.b)}</td></tr>
+                    ))}
+                </tbody>
+            </table>
+            <div className="flex flex-wrap gap-2">
+                {Object.entries(comparison.field_agreement).map(([field, share]: [string, any]) => (
+                    <span key={field} className={`px-2 py-0.5 rounded-full border ${share < 0.8 ? 'border-amber-500/30 text-amber-300' : 'border-zinc-700 text-zinc-400'}`}>
+                        {field} {percent(share)}
+                    </span>
+                ))}
+            </div>
+            {disagreeing.length > 0 && (
+                <details>
+                    <summary className="cursor-pointer text-zinc-500 hover:text-zinc-300">{disagreeing.length} email(s) with differences</summary>
+                    <div className="mt-2 space-y-2">
+                        {disagreeing.map((email: any) => (
+                            <div key={email.email_id} className="space-y-0.5">
+                                <p className="text-zinc-300">#{email.email_id} {email.subject}</p>
+                                {email.a.error && <p className="text-red-400">{a.label}: {email.a.error}</p>}
+                                {email.b.error && <p className="text-red-400">{b.label}: {email.b.error}</p>}
+                                {email.disagreements.map((field: string) => (
+                                    <p key={field} className="text-zinc-500">
+                                        {field}: {JSON.stringify(email.a.fields[field])} vs {JSON.stringify(email.b.fields[field])}
+                                    </p>
+                                ))}
+                            </div>
+                        ))}
+                    </div>
+                </details>
+            )}
+        </div>
+    )
+}
@@ -0,0 +1,157 @@
+import { useEffect, useState } from 'react'
+import { invoke } from '@tauri-apps/api/core'
+
+const SEVERITY_COLORS: Record<string, string> = {
+    high: 'text-red-400',
+    medium: 'text-yellow-400',
+    low: 'text-zinc-400',
+}
+
+const STATUS_COLORS: Record<string, string> = {
+    open: 'bg-red-500/10 text-red-400',
+    mitigated: 'bg-yellow-500/10 text-yellow-400',
+    resolved: 'bg-green-500/10 text-green-400',
+}
+
+const TRACKER_LABELS: Record<string, string> = {
+    jira: 'Jira',
+    azure_devops: 'Azure DevOps',
+}
+
+const mentionKey = (m: any) => `${m.email_id}:${m.kind}:${m.position}`
+
+const formatDays = (days: number | null) => {
+    if (days === null || days === undefined) return '–'
+    return days < 1 ? '<1d' : `${Math.round(days)}d`
+}
+
+export function IssueClusters({ trackers }: { trackers: string[] }) {
+    const [clusters, setClusters] = useState<any[]>([])
+    const [metrics, setMetrics] = useState<any[]>([])
+    const [tickets, setTickets] = useState<any[]>([])
+    const [running, setRunning] = useState(false)
+    const [filing, setFiling] = useState<number | null>(null)
+    const [error, setError] = useState<string | null>(null)
+
+    const refresh = () => {
+        invoke<any[]>('list_issue_clusters', { duplicatesOnly: true })
+            .then(setClusters)
+            .catch((e) => console.error('Failed to load issue clusters', e))
+        invoke<any[]>('get_issue_metrics')
+            .then(setMetrics)
+            .catch((e) => console.error('Failed to load issue metrics', e))
+        invoke<any[]>('list_issue_tickets')
+            .then(setTickets)
+            .catch((e) => console.error('Failed to load tickets', e))
+    }
+
+    useEffect(refresh, [])
+
+    const group = async () => {
+        setRunning(true)
+        await invoke('cluster_issues').catch((e) => console.error('Grouping failed', e))
+        setRunning(false)
+        refresh()
+    }
+
+    // Filed from the newest mention, which describes the issue as it stands.
+    const fileTicket = async (cluster: any, tracker: string) => {
+        const mention = cluster.mentions[cluster.mentions.length - 1]
+        setFiling(cluster.id)
+        setError(null)
+        try {
+            await invoke('create_ticket_from_issue', {
+                emailId: mention.email_id,
+                kind: mention.kind,
+                position: mention.position,
+                tracker,
+                project: null,
+            })
+        } catch (e) {
+            setError(String(e))
+        }
+        setFiling(null)
+        refresh()
+    }
+
+    const ticketsOf = (cluster: any) => {
+        const keys = new Set(cluster.mentions.map(mentionKey))
+        return tickets.filter((t) => keys.has(mentionKey(t)))
+    }
+
+    return (
+        <div className="rounded-2xl border border-zinc-800/50 bg-zinc-900/40 backdrop-blur-sm overflow-hidden">
+            <div className="p-4 border-b border-zinc-800/50 bg-zinc-900/20 flex justify-between items-center">
+                <h3 className="font-medium text-zinc-300">Issues</h3>
+                <button onClick={group} disabled={running} className="text-xs text-blue-400 hover:text-blue-300 disabled:text-zinc-600">
+                    {running ? 'Updating…' : 'Update now'}
+                </button>
+            </div>
+            {metrics.length > 0 && (
+                <div className="p-4 border-b border-zinc-800/50 text-sm max-h-60 overflow-y-auto">
+                    <table className="w-full">
+                        <thead>
+                            <tr className="text-xs text-zinc-500 text-left">
+                                <th className="font-normal pb-2">Project</th>
+                                <th className="font-normal pb-2 text-right">Open</th>
+                                <th className="font-normal pb-2 text-right">Mitigated</th>
+                                <th className="font-normal pb-2 text-right">Resolved</th>
+                                <th className="font-normal pb-2 text-right">Avg. to resolve</th>
+                                <th className="font-normal pb-2 text-right">Oldest open</th>
+                            </tr>
+                        </thead>
+                        <tbody>
+                            {metrics.map((m) => (
+                                <tr key={m.project} className="text-zinc-300">
+                                    <td className="truncate max-w-[10rem]">{m.project}</td>
+                                    <td className="text-right">{m.open}</td>
+                                    <td className="text-right">{m.mitigated}</td>
+                                    <td className="text-right">{m.resolved}</td>
+                                    <td className="text-right">{formatDays(m.mean_days_to_resolve)}</td>
+                                    <td className="text-right">{formatDays(m.oldest_open_days)}</td>
+                                </tr>
+                            ))}
+                        </tbody>
+                    </table>
+                </div>
+            )}
+            <div className="p-4 space-y-3 text-sm max-h-80 overflow-y-auto">
+                {error && <p className="text-xs text-red-400">{error}</p>}
+                {clusters.length === 0 && <p className="text-zinc-500">No issue has been raised more than once.</p>}
+                {clusters.map((c) => (
+                    <div key={c.id} className="min-w-0">
+                        <div className="flex items-center justify-between gap-4">
+                            <span className="truncate text-zinc-200">{c.title}</span>
+                            <div className="flex items-center gap-2 shrink-0 text-xs">
+                                <span className={`px-1.5 py-0.5 rounded ${STATUS_COLORS[c.status] ?? ''}`}>{c.status}</span>
+                                <span className={SEVERITY_COLORS[c.severity] ?? 'text-zinc-400'}>
+                                    {c.severity} · {c.mentions.length}×
+                                </span>
+                            </div>
+                        </div>
+                        <div className="flex items-center justify-between gap-4 text-xs">
+                            <span className="text-zinc-500 truncate">
+                                {c.projects.length > 0 ? c.projects.join(', ') : 'No project'} · {new Date(c.first_seen).toLocaleDateString()} – {new Date(c.resolved_at ?? c.last_seen).toLocaleDateString()}
+                            </span>
+                            <div className="flex items-center gap-3 shrink-0">
+                                {ticketsOf(c).map((t) => (
+                                    <a key={t.id} href={t.url} target="_blank" className="text-blue-400 hover:text-blue-300">{t.ticket_key}</a>
+                                ))}
+                                {ticketsOf(c).length === 0 && trackers.map((tracker) => (
+                                    <button
+                                        key={tracker}
+                                        onClick={() => fileTicket(c, tracker)}
+                                        disabled={filing === c.id}
+                                        className="text-zinc-500 hover:text-blue-400 disabled:text-zinc-700 transition-colors"
+                                    >
+                                        File in {TRACKER_LABELS[tracker] ?? tracker}
+                                    </button>
+                                ))}
+                            </div>
+                        </div>
+                    </div>
+                ))}
+            </div>
+        </div>
+    )
+}
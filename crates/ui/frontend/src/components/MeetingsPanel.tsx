@@ -0,0 +1,96 @@
+import { useEffect, useState } from 'react'
+import { invoke } from '@tauri-apps/api/core'
+import { formatDate } from '../locale'
+
+export function MeetingsPanel({ onLog }: { onLog: (message: string, level?: 'info' | 'error' | 'warn') => void }) {
+    const [followUps, setFollowUps] = useState<any[]>([])
+    const [running, setRunning] = useState<number | null>(null)
+
+    const refresh = () => {
+        invoke<any[]>('list_meeting_follow_ups')
+            .then(setFollowUps)
+            .catch((e) => console.error('Failed to load meeting follow-ups', e))
+    }
+
+    useEffect(refresh, [])
+
+    const extractAgain = async (meeting: any) => {
+        setRunning(meeting.id)
+        try {
+            const found = await invoke<boolean>('follow_up_meeting', { id: meeting.id })
+            if (!found) onLog(`No mail about "${meeting.subject}" found yet`)
+            refresh()
+        } catch (e) {
+            onLog(`Failed to extract follow-ups for "${meeting.subject}": ${e}`, 'error')
+        } finally {
+            setRunning(null)
+        }
+    }
+
+    const toggleDone = async (task: any) => {
+        await invoke('set_meeting_task_done', { id: task.id, done: !task.done })
+            .catch((e) => onLog(`Failed to update task: ${e}`, 'error'))
+        refresh()
+    }
+
+    const openEmail = (id: number) =>
+        invoke('open_email_window', { id }).catch((e) => onLog(`Failed to open email: ${e}`, 'error'))
+
+    return (
+        <div className="rounded-2xl border border-zinc-800/50 bg-zinc-900/40 backdrop-blur-sm overflow-hidden">
+            <div className="p-4 border-b border-zinc-800/50 bg-zinc-900/20">
+                <h3 className="font-medium text-zinc-300">Meeting Follow-ups</h3>
+                <p className="text-xs text-zinc-500 mt-1">
+                    Decisions and action items from the mail around meetings that ended in the last two weeks.
+                </p>
+            </div>
+            <div className="p-4 space-y-4 text-sm max-h-[500px] overflow-y-auto">
+                {followUps.length === 0 && <p className="text-zinc-500">No meetings yet; they are read from your calendar while syncing.</p>}
+                {followUps.map(({ meeting, email_ids, tasks }) => (
+                    <div key={meeting.id} className="space-y-2">
+                        <div className="flex justify-between items-baseline gap-4">
+                            <div className="min-w-0">
+                                <div className="truncate text-zinc-200">{meeting.subject || '(no subject)'}</div>
+                                <div className="text-xs text-zinc-500">
+                                    {formatDate(meeting.end_at)} · {meeting.organizer} · {email_ids.length} related emails
+                                </div>
+                            </div>
+                            <button
+                                onClick={() => extractAgain(meeting)}
+                                disabled={running === meeting.id}
+                                className="text-xs text-blue-400 hover:text-blue-300 disabled:text-zinc-600 shrink-0"
+                            >
+                                {running === meeting.id ? 'Extracting…' : meeting.followed_up_at ? 'Extract again' : 'Extract now'}
+                            </button>
+                        </div>
+                        {meeting.follow_up_error && <p className="text-xs text-red-400">{meeting.follow_up_error}</p>}
+                        {meeting.followed_up_at && tasks.length === 0 && (
+                            <p className="text-xs text-zinc-500">The related mail settled nothing.</p>
+                        )}
+                        {tasks.map((task: any) => (
+                            <div key={task.id} className="flex items-start gap-2 pl-2">
+                                {task.kind === 'action_item' ? (
+                                    <input type="checkbox" checked={task.done} onChange={() => toggleDone(task)} className="mt-1" />
+                                ) : (
+                                    <span className="text-xs text-emerald-400 mt-0.5 shrink-0">Decided</span>
+                                )}
+                                <div className={task.done ? 'text-zinc-500 line-through' : 'text-zinc-300'}>
+                                    {task.text}
+                                    <span className="text-xs text-zinc-500">
+                                        {task.owner && ` · ${task.owner}`}
+                                        {task.due_by && ` · due ${formatDate(task.due_by)}`}
+                                    </span>
+                                    {task.email_id && (
+                                        <button onClick={() => openEmail(task.email_id)} className="ml-2 text-xs text-zinc-600 hover:text-zinc-300 font-mono">
+                                            #{task.email_id}
+                                        </button>
+                                    )}
+                                </div>
+                            </div>
+                        ))}
+                    </div>
+                ))}
+            </div>
+        </div>
+    )
+}
@@ -0,0 +1,135 @@
+import { useEffect, useState } from 'react'
+import { invoke } from '@tauri-apps/api/core'
+import { formatDate } from '../locale'
+
+function formatRange(range: any) {
+    const fmt = (d: string | null) => (d ? formatDate(d) : '…')
+    return `${fmt(range.start)} – ${fmt(range.end)}`
+}
+
+// List-Unsubscribe holds <uri> entries; a web link is easier to act on than mailto.
+function unsubscribeLink(header: string | null) {
+    const uris = (header ?? '').match(/<[^>]+>/g)?.map((uri) => uri.slice(1, -1)) ?? []
+    return uris.find((uri) => uri.startsWith('http')) ?? uris[0] ?? null
+}
+
+export function NewsletterPanel({ onLog }: { onLog: (message: string, level?: 'info' | 'error' | 'warn') => void }) {
+    const [roundups, setRoundups] = useState<any[]>([])
+    const [selected, setSelected] = useState<any>(null)
+    const [senders, setSenders] = useState<any[]>([])
+    const [showSenders, setShowSenders] = useState(false)
+    const [running, setRunning] = useState(false)
+
+    const refresh = () => {
+        invoke<any[]>('list_newsletter_roundups')
+            .then((list) => {
+                setRoundups(list)
+                setSelected((current: any) => current ?? list[0] ?? null)
+            })
+            .catch((e) => console.error('Failed to load newsletter roundups', e))
+        invoke<any[]>('list_newsletter_senders')
+            .then(setSenders)
+            .catch((e) => console.error('Failed to load newsletter senders', e))
+    }
+
+    useEffect(refresh, [])
+
+    const runNow = async () => {
+        setRunning(true)
+        try {
+            const roundup = await invoke<any>('run_newsletter_roundup')
+            if (roundup) {
+                setSelected(roundup)
+                refresh()
+            } else {
+                onLog('No newsletters arrived since the last roundup')
+            }
+        } catch (e) {
+            onLog(`Failed to build newsletter roundup: ${e}`, 'error')
+        } finally {
+            setRunning(false)
+        }
+    }
+
+    const copyUnsubscribe = async (sender: any) => {
+        const link = unsubscribeLink(sender.list_unsubscribe)
+        if (!link) return
+        await navigator.clipboard.writeText(link)
+        onLog(`Copied the unsubscribe link for ${sender.sender}`)
+    }
+
+    return (
+        <div className="rounded-2xl border border-zinc-800/50 bg-zinc-900/40 backdrop-blur-sm overflow-hidden">
+            <div className="p-4 border-b border-zinc-800/50 bg-zinc-900/20 flex items-start justify-between gap-4">
+                <div>
+                    <h3 className="font-medium text-zinc-300">Newsletter Roundup</h3>
+                    <p className="text-xs text-zinc-500 mt-1">
+                        Newsletters stay out of the digest and project dashboards; they are summarized here once a week.
+                    </p>
+                </div>
+                <div className="flex items-center gap-4 shrink-0 text-xs">
+                    <button onClick={() => setShowSenders(!showSenders)} className="text-zinc-500 hover:text-zinc-300">
+                        {showSenders ? 'Hide senders' : `Senders (${senders.length})`}
+                    </button>
+                    <button onClick={runNow} disabled={running} className="text-blue-400 hover:text-blue-300 disabled:text-zinc-600">
+                        {running ? 'Summarizing…' : 'Build now'}
+                    </button>
+                </div>
+            </div>
+            <div className="p-4 space-y-4 text-sm">
+                {showSenders && (
+                    <div className="space-y-2 pb-4 border-b border-zinc-800/50">
+                        {senders.length === 0 && <p className="text-zinc-500">No newsletters found yet.</p>}
+                        {senders.map((sender) => (
+                            <div key={sender.sender} className="flex items-center justify-between gap-4">
+                                <div className="min-w-0">
+                                    <div className="truncate text-zinc-300">{sender.sender}</div>
+                                    <div className="text-xs text-zinc-500">{sender.issues} issues · last {formatDate(sender.last_received_at)}</div>
+                                </div>
+                                {unsubscribeLink(sender.list_unsubscribe) && (
+                                    <button onClick={() => copyUnsubscribe(sender)} className="text-xs text-zinc-500 hover:text-red-400 transition-colors shrink-0">
+                                        Copy unsubscribe link
+                                    </button>
+                                )}
+                            </div>
+                        ))}
+                    </div>
+                )}
+                {!selected && <p className="text-zinc-500">No roundups yet.</p>}
+                {selected && (
+                    <div className="space-y-3">
+                        <div className="flex justify-between items-baseline gap-4">
+                            <select
+                                value={selected.id}
+                                onChange={(e) => setSelected(roundups.find((r) => r.id === Number(e.target.value)) ?? selected)}
+                                className="bg-zinc-950 border border-zinc-800 rounded-lg px-2 py-1 text-xs text-zinc-300"
+                            >
+                                {roundups.map((roundup) => (
+                                    <option key={roundup.id} value={roundup.id}>{formatRange(roundup.date_range)}</option>
+                                ))}
+                            </select>
+                            <span className="text-xs text-zinc-500">{selected.sources.length} newsletters</span>
+                        </div>
+                        {selected.status === 'failed' ? (
+                            <p className="text-red-400">{selected.error_text}</p>
+                        ) : (
+                            <p className="text-zinc-300 whitespace-pre-wrap leading-relaxed">{selected.output_text}</p>
+                        )}
+                        <div className="space-y-2 border-t border-zinc-800/50 pt-3">
+                            {selected.sources.map((source: any) => (
+                                <div key={source.email_id}>
+                                    <div className="flex gap-2 items-baseline">
+                                        <span className={source.cited ? 'text-blue-400 font-mono' : 'text-zinc-600 font-mono'}>#{source.email_id}</span>
+                                        <span className="text-zinc-200 truncate">{source.subject}</span>
+                                        <span className="text-xs text-zinc-500 shrink-0">{source.sender} · {formatDate(source.received_at)}</span>
+                                    </div>
+                                    <p className="text-zinc-400 pl-10">{source.summary}</p>
+                                </div>
+                            ))}
+                        </div>
+                    </div>
+                )}
+            </div>
+        </div>
+    )
+}
@@ -0,0 +1,64 @@
+import { useEffect, useState } from 'react'
+import { invoke } from '@tauri-apps/api/core'
+
+const KIND_LABELS: Record<string, string> = {
+    frequent_collaborator: 'works with',
+    same_org: 'same organization as',
+}
+
+export function OrgSuggestions({ onChange }: { onChange: () => void }) {
+    const [relations, setRelations] = useState<any[]>([])
+    const [running, setRunning] = useState(false)
+
+    const refresh = () => {
+        invoke<any[]>('list_inferred_relations')
+            .then(setRelations)
+            .catch((e) => console.error('Failed to load inferred relations', e))
+    }
+
+    useEffect(refresh, [])
+
+    const infer = async () => {
+        setRunning(true)
+        await invoke('infer_org_chart').catch((e) => console.error('Inference failed', e))
+        setRunning(false)
+        refresh()
+        onChange()
+    }
+
+    const review = async (id: number, status: 'confirmed' | 'dismissed') => {
+        await invoke('set_relation_status', { id, status }).catch(() => { })
+        refresh()
+        onChange()
+    }
+
+    const suggested = relations.filter((r) => r.status === 'suggested')
+
+    return (
+        <div className="rounded-2xl border border-zinc-800/50 bg-zinc-900/40 backdrop-blur-sm overflow-hidden">
+            <div className="p-4 border-b border-zinc-800/50 bg-zinc-900/20 flex justify-between items-center">
+                <h3 className="font-medium text-zinc-300">Inferred Relationships</h3>
+                <button onClick={infer} disabled={running} className="text-xs text-blue-400 hover:text-blue-300 disabled:text-zinc-600">
+                    {running ? 'Inferring…' : 'Infer now'}
+                </button>
+            </div>
+            <div className="p-4 space-y-2 text-sm max-h-80 overflow-y-auto">
+                {suggested.length === 0 && <p className="text-zinc-500">No suggestions to review.</p>}
+                {suggested.map((r) => (
+                    <div key={r.id} className="flex items-center justify-between gap-4">
+                        <div className="min-w-0">
+                            <div className="truncate text-zinc-200">
+                                {r.source} <span className="text-zinc-500">{KIND_LABELS[r.kind] ?? r.kind}</span> {r.target}
+                            </div>
+                            <div className="text-xs text-zinc-500">{Math.round(r.confidence * 100)}% · {r.evidence}</div>
+                        </div>
+                        <div className="flex items-center gap-3 shrink-0">
+                            <button onClick={() => review(r.id, 'confirmed')} className="text-zinc-500 hover:text-green-400 transition-colors">Confirm</button>
+                            <button onClick={() => review(r.id, 'dismissed')} className="text-zinc-500 hover:text-red-400 transition-colors">Dismiss</button>
+                        </div>
+                    </div>
+                ))}
+            </div>
+        </div>
+    )
+}
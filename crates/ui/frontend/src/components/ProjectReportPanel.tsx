@@ -0,0 +1,98 @@
+import { useState } from 'react'
+import { invoke } from '@tauri-apps/api/core'
+import { AnnotationEditor } from './AnnotationEditor'
+
+const PROFILES: [string, string][] = [
+    ['internal', 'Internal (with email bodies)'],
+    ['manager', 'Manager (summaries and facts)'],
+    ['client', 'Client (no names or addresses)'],
+]
+
+export function ProjectReportPanel({ password, onLog }: { password: string, onLog: (message: string, level?: 'info' | 'error' | 'warn') => void }) {
+    const [project, setProject] = useState('')
+    const [profile, setProfile] = useState('manager')
+    const [redact, setRedact] = useState('')
+    const [running, setRunning] = useState(false)
+    // The project's annotation while it is being edited; its notes and tags go in the report.
+    const [annotation, setAnnotation] = useState<any | undefined>(undefined)
+
+    const editNotes = async () => {
+        try {
+            const annotations = await invoke<any[]>('list_annotations')
+            const name = project.trim().toLowerCase()
+            setAnnotation(annotations.find((a) => a.project?.toLowerCase() === name) ?? null)
+        } catch (e) {
+            onLog(`Failed to load notes for ${project}: ${e}`, 'error')
+        }
+    }
+
+    const exportReport = async () => {
+        setRunning(true)
+        try {
+            const report: any = await invoke('export_project_report', {
+                project,
+                profile,
+                redact: redact.split(',').map((t) => t.trim()).filter(Boolean),
+                password: password || null,
+            })
+            const content = report.encrypted
+                ? Uint8Array.from(atob(report.content), (c) => c.charCodeAt(0))
+                : report.content
+            const link = document.createElement('a')
+            link.href = URL.createObjectURL(new Blob([content], { type: report.mime_type }))
+            link.download = report.file_name
+            link.click()
+            URL.revokeObjectURL(link.href)
+            onLog(`Exported ${profile} report for ${project} (${report.emails} emails${report.encrypted ? ', encrypted' : ''})`)
+        } catch (e) {
+            onLog(`Failed to export report: ${e}`, 'error')
+        }
+        setRunning(false)
+    }
+
+    const inputClass = 'bg-zinc-950 border border-zinc-800 rounded-lg px-3 py-1.5 text-sm focus:border-blue-500 outline-none transition-all'
+
+    return (
+        <div className="rounded-2xl border border-zinc-800/50 bg-zinc-900/40 backdrop-blur-sm overflow-hidden">
+            <div className="p-4 border-b border-zinc-800/50 bg-zinc-900/20 flex justify-between items-center">
+                <h3 className="font-medium text-zinc-300">Project Report</h3>
+                <div className="flex gap-4">
+                    <button
+                        onClick={editNotes}
+                        disabled={!project.trim()}
+                        className="text-xs text-zinc-400 hover:text-zinc-200 disabled:text-zinc-600"
+                    >
+                        Notes
+                    </button>
+                    <button
+                        onClick={exportReport}
+                        disabled={running || !project.trim()}
+                        className="text-xs text-blue-400 hover:text-blue-300 disabled:text-zinc-600"
+                    >
+                        {running ? 'Exporting…' : 'Export'}
+                    </button>
+                </div>
+            </div>
+            <div className="p-4 grid grid-cols-3 gap-3">
+                <input className={inputClass} value={project} onChange={(e) => setProject(e.target.value)} placeholder="Project" />
+                <select className={inputClass} value={profile} onChange={(e) => setProfile(e.target.value)}>
+                    {PROFILES.map(([value, label]) => (
+                        <option key={value} value={value}>{label}</option>
+                    ))}
+                </select>
+                <input className={inputClass} value={redact} onChange={(e) => setRedact(e.target.value)} placeholder="Redact (comma separated)" />
+            </div>
+            {annotation !== undefined && (
+                <div className="px-4 pb-4">
+                    <AnnotationEditor
+                        project={project.trim()}
+                        annotation={annotation}
+                        onSaved={() => onLog(`Saved notes for ${project.trim()}`)}
+                        onClose={() => setAnnotation(undefined)}
+                        onLog={onLog}
+                    />
+                </div>
+            )}
+        </div>
+    )
+}
@@ -0,0 +1,185 @@
+import { useEffect, useState } from 'react'
+import { invoke } from '@tauri-apps/api/core'
+import { FileText } from 'lucide-react'
+
+// The user's prompts, shared as prompt packs: export some or all of them,
+// import a pack file, and preview a prompt with its variables filled in.
+export function PromptLibrary({ onLog, download }: {
+    onLog: (message: string, level?: 'info' | 'warn' | 'error') => void,
+    download: (blob: Blob, fileName: string) => void,
+}) {
+    const [prompts, setPrompts] = useState<any[]>([])
+    const [selected, setSelected] = useState<string[]>([])
+    const [packName, setPackName] = useState('')
+    const [previewing, setPreviewing] = useState<any | null>(null)
+    const [values, setValues] = useState<Record<string, string>>({})
+    const [emailId, setEmailId] = useState('')
+    const [sample, setSample] = useState('')
+    const [preview, setPreview] = useState<any | null>(null)
+    const [busy, setBusy] = useState(false)
+
+    const refresh = () => {
+        invoke<any[]>('list_prompts')
+            .then(setPrompts)
+            .catch((e) => console.error('Failed to load prompts', e))
+    }
+
+    useEffect(refresh, [])
+
+    const exportPack = async () => {
+        try {
+            const pack: any = await invoke('export_prompt_pack', { ids: selected, name: packName || 'My prompts', description: null })
+            const fileName = `${pack.name.replace(/[^\w-]+/g, '-').toLowerCase()}.prompts.json`
+            download(new Blob([JSON.stringify(pack, null, 2)], { type: 'application/json' }), fileName)
+            onLog(`Exported ${pack.prompts.length} prompt(s)`)
+        } catch (e) {
+            onLog(`Failed to export prompts: ${e}`, 'error')
+        }
+    }
+
+    const importPack = async (file: File) => {
+        try {
+            const pack = JSON.parse(await file.text())
+            const result: any = await invoke('import_prompt_pack', { pack })
+            onLog(`Imported prompt pack ${result.pack}: ${result.created} new, ${result.updated} updated`)
+            refresh()
+        } catch (e) {
+            onLog(`Failed to import ${file.name}: ${e}`, 'error')
+        }
+    }
+
+    const openPreview = async (prompt: any) => {
+        setPreviewing(prompt)
+        setPreview(null)
+        // Variables without a default come back as an error; start them empty.
+        const defaults = Object.fromEntries(prompt.variables.map((v: any) => [v.name, '']))
+        const resolved = await invoke<Record<string, string>>('resolve_prompt_variables', { id: prompt.id, values: {} }).catch(() => ({}))
+        setValues({ ...defaults, ...resolved })
+    }
+
+    const runPreview = async () => {
+        setBusy(true)
+        try {
+            setPreview(await invoke('preview_prompt', {
+                id: previewing.id,
+                values,
+                emailId: emailId ? Number(emailId) : null,
+                sample: sample || null,
+            }))
+        } catch (e) {
+            onLog(`Prompt preview failed: ${e}`, 'error')
+        } finally {
+            setBusy(false)
+        }
+    }
+
+    const toggle = (id: string) =>
+        setSelected((current) => current.includes(id) ? current.filter((s) => s !== id) : [...current, id])
+
+    return (
+        <section className="bg-zinc-900/40 border border-zinc-800/50 rounded-2xl p-6 space-y-4">
+            <h3 className="text-lg font-medium flex items-center gap-2">
+                <FileText className="w-5 h-5 text-amber-400" />
+                Prompt Library
+            </h3>
+            {prompts.length === 0 && <p className="text-sm text-zinc-500">No prompts yet. Import a prompt pack to add some.</p>}
+            {prompts.map((prompt) => (
+                <div key={prompt.id} className="flex items-center justify-between gap-4 text-sm">
+                    <label className="flex items-center gap-2 text-zinc-300 truncate">
+                        <input type="checkbox" checked={selected.includes(prompt.id)} onChange={() => toggle(prompt.id)} />
+                        {prompt.name}
+                        <span className="text-xs text-zinc-500">{prompt.kind}{prompt.pack ? ` · ${prompt.pack}` : ''}</span>
+                    </label>
+                    <button onClick={() => openPreview(prompt)} className="text-zinc-400 hover:text-zinc-200 transition-colors shrink-0">
+                        Preview
+                    </button>
+                </div>
+            ))}
+
+            {previewing && (
+                <div className="p-4 rounded-lg border border-zinc-800 bg-zinc-950 space-y-3 text-sm">
+                    <div className="flex justify-between">
+                        <span className="text-zinc-300 font-medium">{previewing.name}</span>
+                        <button onClick={() => setPreviewing(null)} className="text-xs text-zinc-500 hover:text-zinc-300">Close</button>
+                    </div>
+                    {previewing.description && <p className="text-xs text-zinc-500">{previewing.description}</p>}
+                    {Object.keys(values).map((name) => (
+                        <label key={name} className="flex items-center gap-2 text-xs text-zinc-400">
+                            <span className="w-28 font-mono">{name}</span>
+                            <input
+                                value={values[name]}
+                                onChange={(e) => setValues({ ...values, [name]: e.target.value })}
+                                placeholder={previewing.variables.find((v: any) => v.name === name)?.description ?? ''}
+                                className="flex-1 bg-zinc-900 border border-zinc-800 rounded-lg px-2 py-1 text-zinc-200"
+                            />
+                        </label>
+                    ))}
+                    <div className="flex gap-2">
+                        <input
+                            type="number"
+                            value={emailId}
+                            onChange={(e) => setEmailId(e.target.value)}
+                            placeholder="Email id (newest if empty)"
+                            className="w-48 bg-zinc-900 border border-zinc-800 rounded-lg px-2 py-1 text-xs text-zinc-200"
+                        />
+                        <button
+                            onClick={runPreview}
+                            disabled={busy}
+                            className="ml-auto px-3 py-1 rounded-lg border border-blue-500/30 text-blue-300 hover:bg-blue-500/10 disabled:opacity-50 text-xs"
+                        >
+                            {busy ? 'Running…' : 'Run preview'}
+                        </button>
+                    </div>
+                    <textarea
+                        value={sample}
+                        onChange={(e) => setSample(e.target.value)}
+                        placeholder="Or paste sample email text"
+                        rows={3}
+                        className="w-full bg-zinc-900 border border-zinc-800 rounded-lg px-2 py-1 text-xs text-zinc-200"
+                    />
+                    {preview && (
+                        <div className="space-y-2 text-xs">
+                            <details>
+                                <summary className="cursor-pointer text-zinc-500 hover:text-zinc-300">Prompt as sent</summary>
+                                <pre className="whitespace-pre-wrap text-zinc-400 mt-1">{preview.rendered}</pre>
+                            </details>
+                            <pre className="whitespace-pre-wrap text-zinc-200 bg-zinc-900 rounded-lg p-2">{preview.output}</pre>
+                            {previewing.output_schema && (preview.schema_errors.length === 0
+                                ? <p className="text-green-400">Matches the expected schema</p>
+                                : preview.schema_errors.map((error: string) => <p key={error} className="text-amber-400">{error}</p>))}
+                        </div>
+                    )}
+                </div>
+            )}
+
+            <div className="pt-4 border-t border-zinc-800/50 flex items-center gap-4">
+                <input
+                    value={packName}
+                    onChange={(e) => setPackName(e.target.value)}
+                    placeholder="Pack name"
+                    className="w-40 bg-zinc-950 border border-zinc-800 rounded-lg px-3 py-1.5 text-sm focus:border-blue-500 outline-none transition-all"
+                />
+                <button
+                    onClick={exportPack}
+                    disabled={prompts.length === 0}
+                    className="text-sm text-zinc-400 hover:text-zinc-200 disabled:opacity-50 transition-colors"
+                >
+                    {selected.length > 0 ? `Export ${selected.length} prompt(s)` : 'Export all prompts'}
+                </button>
+                <label className="text-sm text-zinc-400 hover:text-zinc-200 transition-colors cursor-pointer">
+                    Import prompt pack
+                    <input
+                        type="file"
+                        accept="application/json"
+                        className="hidden"
+                        onChange={(e) => {
+                            const file = e.target.files?.[0]
+                            if (file) importPack(file)
+                            e.target.value = ''
+                        }}
+                    />
+                </label>
+            </div>
+        </section>
+    )
+}
@@ -0,0 +1,139 @@
+import { useEffect, useState } from 'react'
+import { invoke } from '@tauri-apps/api/core'
+import { listen } from '@tauri-apps/api/event'
+import { EVENTS } from '../events'
+
+const POLL_MS = 3000
+
+const STAGE_LABELS: Record<string, string> = {
+    waiting: 'Waiting for a slot',
+    extracting: 'Extracting',
+    embedding: 'Embedding',
+    extracting_and_embedding: 'Extracting and embedding',
+}
+
+const LATENCY_LABELS: Record<string, string> = {
+    outlook_fetch: 'Outlook fetch',
+    sqlite_save: 'Database save',
+    extraction: 'LLM extraction',
+    embedding: 'Embedding',
+    qdrant_upsert: 'Vector index',
+}
+
+function formatMs(ms: number) {
+    return ms < 1000 ? `${Math.round(ms)} ms` : `${(ms / 1000).toFixed(1)} s`
+}
+
+function formatElapsed(ms: number) {
+    const seconds = Math.floor(ms / 1000)
+    return seconds < 60 ? `${seconds}s` : `${Math.floor(seconds / 60)}m ${seconds % 60}s`
+}
+
+export function QueuePanel() {
+    const [status, setStatus] = useState<any>(null)
+
+    const refresh = () => {
+        invoke('get_queue_status')
+            .then(setStatus)
+            .catch((e) => console.error('Failed to fetch queue status', e))
+    }
+
+    useEffect(() => {
+        refresh()
+        const timer = setInterval(refresh, POLL_MS)
+        // Finished emails leave the list without waiting for the next poll.
+        const unlisten = listen(EVENTS.emailProgress, (event: any) => {
+            if (event.payload.stage === 'indexed') refresh()
+        })
+        return () => {
+            clearInterval(timer)
+            unlisten.then(u => u())
+        }
+    }, [])
+
+    const cancel = async (entryId: string) => {
+        await invoke('cancel_queue_item', { entryId }).catch(() => { })
+        refresh()
+    }
+
+    const discardRetry = async (emailId: number) => {
+        await invoke('discard_vector_retry', { emailId }).catch(() => { })
+        refresh()
+    }
+
+    if (!status) return null
+    const idle = status.pending_total === 0 && status.in_progress.length === 0 && status.retries.length === 0
+
+    return (
+        <div className="rounded-2xl border border-zinc-800/50 bg-zinc-900/40 backdrop-blur-sm overflow-hidden">
+            <div className="p-4 border-b border-zinc-800/50 bg-zinc-900/20 flex justify-between items-center">
+                <h3 className="font-medium text-zinc-300">Processing Queue</h3>
+                <span className="text-xs text-zinc-500">
+                    {status.active_workers}/{status.max_workers} workers busy · {status.pending_total} pending
+                </span>
+            </div>
+            <div className="p-4 space-y-4 text-sm max-h-80 overflow-y-auto">
+                {idle && <p className="text-zinc-500">Nothing queued.</p>}
+                {status.in_progress.map((item: any) => (
+                    <div key={item.entry_id} className="flex items-center justify-between gap-4">
+                        <span className="truncate text-zinc-200">{item.subject}</span>
+                        <div className="flex items-center gap-4 shrink-0">
+                            <span className="text-blue-400">{STAGE_LABELS[item.stage] ?? item.stage} · {formatElapsed(item.elapsed_ms)}</span>
+                            <button onClick={() => cancel(item.entry_id)} className="text-zinc-500 hover:text-red-400 transition-colors">Cancel</button>
+                        </div>
+                    </div>
+                ))}
+                {status.pending.map((item: any) => (
+                    <div key={item.entry_id} className="flex items-center justify-between gap-4">
+                        <span className="truncate text-zinc-400">{item.subject}</span>
+                        <div className="flex items-center gap-4 shrink-0">
+                            {item.priority === 'delta' && <span className="text-emerald-400">New</span>}
+                            {item.priority === 'vip' && <span className="text-amber-400">VIP</span>}
+                            <span className="text-zinc-600">{item.folder}</span>
+                            <button onClick={() => cancel(item.entry_id)} className="text-zinc-500 hover:text-red-400 transition-colors">Skip</button>
+                        </div>
+                    </div>
+                ))}
+                {status.pending_total > status.pending.length && (
+                    <p className="text-zinc-600">and {status.pending_total - status.pending.length} more</p>
+                )}
+                {status.retries.map((item: any) => (
+                    <div key={item.email_id} className="flex items-center justify-between gap-4">
+                        <span className="truncate text-zinc-400" title={item.last_error ?? ''}>{item.subject}</span>
+                        <div className="flex items-center gap-4 shrink-0">
+                            <span className="text-amber-400">Retry pending ({item.attempts} failed)</span>
+                            <button onClick={() => discardRetry(item.email_id)} className="text-zinc-500 hover:text-red-400 transition-colors">Discard</button>
+                        </div>
+                    </div>
+                ))}
+            </div>
+            {status.latencies.length > 0 && (
+                <div className="p-4 border-t border-zinc-800/50 text-sm">
+                    <h4 className="text-xs font-medium text-zinc-500 uppercase tracking-wider mb-2">Time per stage</h4>
+                    <table className="w-full text-left">
+                        <thead className="text-xs text-zinc-600">
+                            <tr>
+                                <th className="font-normal">Stage</th>
+                                <th className="font-normal text-right">Runs</th>
+                                <th className="font-normal text-right">Median</th>
+                                <th className="font-normal text-right">95th</th>
+                                <th className="font-normal text-right">Slowest</th>
+                            </tr>
+                        </thead>
+                        <tbody className="text-zinc-300">
+                            {status.latencies.map((latency: any) => (
+                                <tr key={latency.stage}>
+                                    <td>{LATENCY_LABELS[latency.stage] ?? latency.stage}</td>
+                                    <td className="text-right text-zinc-500">{latency.count}</td>
+                                    <td className="text-right">{formatMs(latency.p50_ms)}</td>
+                                    <td className="text-right">{formatMs(latency.p95_ms)}</td>
+                                    <td className="text-right text-zinc-500">{formatMs(latency.max_ms)}</td>
+                                </tr>
+                            ))}
+                        </tbody>
+                    </table>
+                </div>
+            )}
+        </div>
+    )
+}
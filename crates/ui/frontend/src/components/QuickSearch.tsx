@@ -0,0 +1,68 @@
+import { useEffect, useRef, useState } from 'react'
+import { invoke } from '@tauri-apps/api/core'
+import { Search } from 'lucide-react'
+
+export function QuickSearch() {
+    const [query, setQuery] = useState('')
+    const [results, setResults] = useState<any[]>([])
+    const [error, setError] = useState<string | null>(null)
+    const inputRef = useRef<HTMLInputElement>(null)
+
+    useEffect(() => {
+        // The window is reused between invocations; refocus whenever it is shown.
+        const onFocus = () => inputRef.current?.select()
+        window.addEventListener('focus', onFocus)
+        return () => window.removeEventListener('focus', onFocus)
+    }, [])
+
+    const search = async () => {
+        if (!query.trim()) return
+        try {
+            setResults(await invoke('search_emails', { query }) as any[])
+            setError(null)
+        } catch (e) {
+            setError(`${e}`)
+        }
+    }
+
+    const openInMain = () => {
+        invoke('open_search_in_main', { query }).catch(() => { })
+    }
+
+    return (
+        <div className="h-screen flex flex-col bg-zinc-950 text-zinc-100 border border-zinc-800 rounded-xl overflow-hidden">
+            <div className="flex items-center gap-3 px-4 py-3 border-b border-zinc-800">
+                <Search className="w-5 h-5 text-zinc-500" />
+                <input
+                    ref={inputRef}
+                    autoFocus
+                    className="flex-1 bg-transparent outline-none text-lg placeholder:text-zinc-600"
+                    placeholder="Search emails..."
+                    value={query}
+                    onChange={(e) => setQuery(e.target.value)}
+                    onKeyDown={(e) => {
+                        if (e.key === 'Enter' && e.ctrlKey) openInMain()
+                        else if (e.key === 'Enter') search()
+                        else if (e.key === 'Escape') invoke('hide_quick_search').catch(() => { })
+                    }}
+                />
+            </div>
+            <div className="flex-1 overflow-y-auto">
+                {error && <p className="px-4 py-3 text-sm text-red-400">{error}</p>}
+                {results.map((email) => (
+                    <button
+                        key={email.id}
+                        onClick={openInMain}
+                        className="w-full text-left px-4 py-2 hover:bg-zinc-900 transition-colors"
+                    >
+                        <p className="text-sm font-medium text-zinc-200 truncate">{email.subject}</p>
+                        <p className="text-xs text-zinc-500 truncate">{email.sender}</p>
+                    </button>
+                ))}
+            </div>
+            <p className="px-4 py-2 text-xs text-zinc-600 border-t border-zinc-800">
+                Enter to search · Ctrl+Enter to open in Noodle · Esc to close
+            </p>
+        </div>
+    )
+}
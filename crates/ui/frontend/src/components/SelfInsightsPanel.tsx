@@ -0,0 +1,70 @@
+import { useEffect, useState } from 'react'
+import { invoke } from '@tauri-apps/api/core'
+import { formatDate } from '../locale'
+
+const DAYS = 90
+
+function formatWords(words: number | null) {
+    return words === null ? '–' : `${Math.round(words)} words`
+}
+
+export function SelfInsightsPanel() {
+    const [insights, setInsights] = useState<any>(null)
+
+    useEffect(() => {
+        invoke('get_self_insights', { days: DAYS })
+            .then(setInsights)
+            .catch((e) => console.error('Failed to fetch sent mail insights', e))
+    }, [])
+
+    if (!insights) return null
+    const total = insights.tone.reduce((sum: number, t: any) => sum + t.count, 0)
+
+    return (
+        <div className="rounded-2xl border border-zinc-800/50 bg-zinc-900/40 backdrop-blur-sm overflow-hidden">
+            <div className="p-4 border-b border-zinc-800/50 bg-zinc-900/20 flex justify-between items-center">
+                <h3 className="font-medium text-zinc-300">Your Sent Mail</h3>
+                <span className="text-xs text-zinc-500">Last {DAYS} days · {insights.sent_count} sent</span>
+            </div>
+            <div className="p-4 grid grid-cols-1 md:grid-cols-2 gap-6 text-sm">
+                <div className="space-y-3">
+                    <h4 className="text-xs uppercase tracking-wider text-zinc-500">Tone</h4>
+                    {insights.tone.map((t: any) => (
+                        <div key={t.sentiment} className="flex justify-between">
+                            <span className="capitalize text-zinc-300">{t.sentiment}</span>
+                            <span className="text-zinc-500">{Math.round((t.count / total) * 100)}%</span>
+                        </div>
+                    ))}
+                    <div className="flex justify-between pt-2 border-t border-zinc-800/50">
+                        <span className="text-zinc-500">Average message</span>
+                        <span className="text-zinc-300">{formatWords(insights.avg_words)}</span>
+                    </div>
+                    <div className="flex justify-between">
+                        <span className="text-zinc-500">Average reply</span>
+                        <span className="text-zinc-300">{formatWords(insights.avg_reply_words)}</span>
+                    </div>
+                </div>
+                <div className="space-y-3 max-h-72 overflow-y-auto">
+                    <h4 className="text-xs uppercase tracking-wider text-zinc-500">Commitments ({insights.commitments.length})</h4>
+                    {insights.commitments.slice(0, 10).map((c: any) => (
+                        <div key={c.email_id}>
+                            <div className="text-zinc-200 truncate">{c.subject}</div>
+                            <div className="text-xs text-zinc-500">
+                                {c.summary}{c.due_by ? ` · due ${formatDate(c.due_by)}` : ''}
+                            </div>
+                        </div>
+                    ))}
+                    <h4 className="text-xs uppercase tracking-wider text-zinc-500 pt-2">Unanswered questions ({insights.unanswered_questions.length})</h4>
+                    {insights.unanswered_questions.slice(0, 10).map((q: any, i: number) => (
+                        <div key={`${q.email_id}-${i}`}>
+                            <div className="text-zinc-200">{q.question}</div>
+                            <div className="text-xs text-zinc-500">
+                                {q.subject} · {formatDate(q.sent_at)}{q.owner ? ` · ${q.owner}` : ''}
+                            </div>
+                        </div>
+                    ))}
+                </div>
+            </div>
+        </div>
+    )
+}
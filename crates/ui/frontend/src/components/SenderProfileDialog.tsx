@@ -0,0 +1,109 @@
+import { useEffect, useState } from 'react'
+import { invoke } from '@tauri-apps/api/core'
+import { formatDate } from '../locale'
+
+function describeSentiment(score: number | null) {
+    if (score === null) return '–'
+    if (score >= 0.3) return 'Positive'
+    if (score <= -0.3) return 'Tense'
+    return 'Neutral'
+}
+
+function formatResponseTime(secs: number | null) {
+    if (secs === null) return '–'
+    if (secs < 3600) return `${Math.max(1, Math.round(secs / 60))} min`
+    if (secs < 86400) return `${Math.round(secs / 3600)} h`
+    return `${(secs / 86400).toFixed(1)} days`
+}
+
+// Everything known about one sender, opened from an email in the list.
+export function SenderProfileDialog({ address, onClose }: { address: string, onClose: () => void }) {
+    const [profile, setProfile] = useState<any>(null)
+    const [error, setError] = useState<string | null>(null)
+
+    useEffect(() => {
+        invoke('get_sender_profile', { address })
+            .then(setProfile)
+            .catch((e) => setError(String(e)))
+    }, [address])
+
+    const peak = profile ? Math.max(1, ...profile.volume.map((v: any) => v.count)) : 1
+
+    return (
+        <div onClick={onClose} className="fixed inset-0 bg-black/50 backdrop-blur-sm z-50 flex items-center justify-center p-4 animate-in fade-in duration-200">
+            <div onClick={(e) => e.stopPropagation()} className="bg-zinc-900 border border-zinc-800 rounded-2xl p-6 max-w-2xl w-full max-h-[85vh] overflow-y-auto shadow-2xl space-y-5 text-sm">
+                <div className="flex justify-between items-start gap-4">
+                    <div>
+                        <h3 className="text-lg font-bold text-white break-all">{address}</h3>
+                        {profile?.first_received_at && (
+                            <p className="text-xs text-zinc-500">
+                                {profile.received} emails since {formatDate(profile.first_received_at)}, last on {formatDate(profile.last_received_at)}
+                            </p>
+                        )}
+                    </div>
+                    <button onClick={onClose} className="text-zinc-500 hover:text-zinc-300">×</button>
+                </div>
+
+                {error && <p className="text-red-400">{error}</p>}
+                {!profile && !error && <p className="text-zinc-500">Loading…</p>}
+                {profile && profile.received === 0 && <p className="text-zinc-500">No mail from this sender yet.</p>}
+                {profile && profile.received > 0 && (
+                    <>
+                        <div className="grid grid-cols-3 gap-4">
+                            <div>
+                                <div className="text-xs uppercase tracking-wider text-zinc-500">Tone</div>
+                                <div className="text-zinc-200">{describeSentiment(profile.average_sentiment)}</div>
+                            </div>
+                            <div>
+                                <div className="text-xs uppercase tracking-wider text-zinc-500">You reply in</div>
+                                <div className="text-zinc-200">{formatResponseTime(profile.average_response_secs)}</div>
+                            </div>
+                            <div>
+                                <div className="text-xs uppercase tracking-wider text-zinc-500">Replied to</div>
+                                <div className="text-zinc-200">{profile.replied} of {profile.received}</div>
+                            </div>
+                        </div>
+
+                        <div className="space-y-1">
+                            <h4 className="text-xs uppercase tracking-wider text-zinc-500">Emails per month</h4>
+                            <div className="flex items-end gap-1 h-20">
+                                {profile.volume.map((v: any) => (
+                                    <div
+                                        key={v.month}
+                                        title={`${v.month}: ${v.count}`}
+                                        className="flex-1 bg-blue-500/40 rounded-t"
+                                        style={{ height: `${(v.count / peak) * 100}%` }}
+                                    />
+                                ))}
+                            </div>
+                        </div>
+
+                        {profile.projects.length > 0 && (
+                            <div className="space-y-1">
+                                <h4 className="text-xs uppercase tracking-wider text-zinc-500">Shared projects</h4>
+                                <div className="flex flex-wrap gap-2">
+                                    {profile.projects.map((p: any) => (
+                                        <span key={p.name} className="text-xs text-zinc-300 bg-zinc-800/60 px-2 py-0.5 rounded border border-zinc-700">
+                                            {p.name} · {p.count}
+                                        </span>
+                                    ))}
+                                </div>
+                            </div>
+                        )}
+
+                        <div className="space-y-2">
+                            <h4 className="text-xs uppercase tracking-wider text-zinc-500">Their open questions ({profile.open_questions.length})</h4>
+                            {profile.open_questions.length === 0 && <p className="text-zinc-500">None waiting on you.</p>}
+                            {profile.open_questions.map((q: any, i: number) => (
+                                <div key={`${q.email_id}-${i}`}>
+                                    <div className="text-zinc-200">{q.question}</div>
+                                    <div className="text-xs text-zinc-500">{q.subject} · {formatDate(q.received_at)}</div>
+                                </div>
+                            ))}
+                        </div>
+                    </>
+                )}
+            </div>
+        </div>
+    )
+}
@@ -0,0 +1,42 @@
+import { invoke } from '@tauri-apps/api/core'
+
+const FORMATS: [string, string][] = [
+    ['slack', 'Slack'],
+    ['teams', 'Teams'],
+    ['markdown', 'Markdown'],
+]
+
+// Copies a summary to the clipboard, formatted for a chat tool.
+export function ShareMenu({ sources, placeholder, onLog }: {
+    sources: [string, any][],
+    placeholder: string,
+    onLog: (message: string, level?: 'info' | 'error' | 'warn') => void,
+}) {
+    const share = async (value: string) => {
+        const [index, format] = value.split(':')
+        const [label, source] = sources[Number(index)]
+        try {
+            const text: string = await invoke('format_for_chat', { source, format })
+            await navigator.clipboard.writeText(text)
+            onLog(`Copied ${label.toLowerCase()} summary for ${format}`)
+        } catch (e) {
+            onLog(`Failed to format summary: ${e}`, 'error')
+        }
+    }
+
+    return (
+        <select
+            value=""
+            onClick={(e) => e.stopPropagation()}
+            onChange={(e) => share(e.target.value)}
+            className="bg-transparent text-[10px] text-zinc-600 hover:text-zinc-300 outline-none cursor-pointer"
+        >
+            <option value="" disabled>{placeholder}</option>
+            {sources.map(([label], i) => FORMATS.map(([format, name]) => (
+                <option key={`${i}:${format}`} value={`${i}:${format}`}>
+                    {sources.length > 1 ? `${label} for ${name}` : name}
+                </option>
+            )))}
+        </select>
+    )
+}
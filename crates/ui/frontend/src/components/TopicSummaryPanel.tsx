@@ -0,0 +1,135 @@
+import { useEffect, useState } from 'react'
+import { invoke } from '@tauri-apps/api/core'
+import { formatDate, formatDateTime } from '../locale'
+
+function toIso(date: string, endOfDay = false) {
+    if (!date) return null
+    return new Date(`${date}T${endOfDay ? '23:59:59' : '00:00:00'}`).toISOString()
+}
+
+function formatRange(range: any) {
+    const fmt = (d: string | null) => (d ? formatDate(d) : '…')
+    return `${fmt(range.start)} – ${fmt(range.end)}`
+}
+
+export function TopicSummaryPanel() {
+    const [query, setQuery] = useState('')
+    const [start, setStart] = useState('')
+    const [end, setEnd] = useState('')
+    const [running, setRunning] = useState(false)
+    const [error, setError] = useState<string | null>(null)
+    const [summaries, setSummaries] = useState<any[]>([])
+    const [selected, setSelected] = useState<any>(null)
+
+    const refresh = () => {
+        invoke<any[]>('list_topic_summaries')
+            .then((list) => {
+                setSummaries(list)
+                setSelected((current: any) => current ?? list[0] ?? null)
+            })
+            .catch((e) => console.error('Failed to load topic summaries', e))
+    }
+
+    useEffect(refresh, [])
+
+    const run = async () => {
+        if (!query.trim()) return
+        setRunning(true)
+        setError(null)
+        try {
+            const summary = await invoke('summarize_topic', {
+                query,
+                start: toIso(start),
+                end: toIso(end, true),
+            })
+            setSelected(summary)
+            refresh()
+        } catch (e) {
+            setError(String(e))
+        } finally {
+            setRunning(false)
+        }
+    }
+
+    const remove = async (id: number) => {
+        await invoke('delete_topic_summary', { id }).catch(() => { })
+        if (selected?.id === id) setSelected(null)
+        refresh()
+    }
+
+    return (
+        <div className="max-w-4xl mx-auto space-y-6 animate-in fade-in slide-in-from-bottom-8 duration-500">
+            <div className="rounded-2xl border border-zinc-800/50 bg-zinc-900/40 p-4 space-y-3">
+                <h3 className="font-medium text-zinc-300">Summarize a topic</h3>
+                <div className="flex flex-wrap gap-2">
+                    <input
+                        type="text"
+                        placeholder="e.g. Q3 budget review"
+                        className="flex-1 min-w-[200px] bg-zinc-950 border border-zinc-800 rounded-lg px-3 py-2 text-sm focus:outline-none focus:border-blue-500/50"
+                        value={query}
+                        onChange={(e) => setQuery(e.target.value)}
+                        onKeyDown={(e) => e.key === 'Enter' && run()}
+                    />
+                    <input type="date" value={start} onChange={(e) => setStart(e.target.value)} className="bg-zinc-950 border border-zinc-800 rounded-lg px-3 py-2 text-sm text-zinc-300" />
+                    <input type="date" value={end} onChange={(e) => setEnd(e.target.value)} className="bg-zinc-950 border border-zinc-800 rounded-lg px-3 py-2 text-sm text-zinc-300" />
+                    <button
+                        onClick={run}
+                        disabled={running || !query.trim()}
+                        className="px-4 py-2 bg-blue-600 hover:bg-blue-500 disabled:bg-zinc-800 disabled:text-zinc-500 rounded-lg text-sm font-medium"
+                    >
+                        {running ? 'Summarizing…' : 'Summarize'}
+                    </button>
+                </div>
+                <p className="text-xs text-zinc-500">Leave the dates empty to cover the last 90 days.</p>
+                {error && <p className="text-sm text-red-400">{error}</p>}
+            </div>
+
+            {selected && (
+                <div className="rounded-2xl border border-zinc-800/50 bg-zinc-900/40 p-4 space-y-4">
+                    <div className="flex justify-between items-baseline">
+                        <h3 className="font-medium text-zinc-200">{selected.query}</h3>
+                        <span className="text-xs text-zinc-500">{formatRange(selected.date_range)}</span>
+                    </div>
+                    {selected.status === 'failed' ? (
+                        <p className="text-sm text-red-400">{selected.error_text}</p>
+                    ) : (
+                        <p className="text-sm text-zinc-300 whitespace-pre-wrap leading-relaxed">{selected.output_text}</p>
+                    )}
+                    {selected.sources.length > 0 && (
+                        <div className="space-y-2 border-t border-zinc-800/50 pt-3">
+                            <h4 className="text-xs uppercase tracking-wider text-zinc-500">Sources</h4>
+                            {selected.sources.map((source: any) => (
+                                <div key={source.email_id} className="text-sm">
+                                    <div className="flex gap-2 items-baseline">
+                                        <span className={source.cited ? 'text-blue-400 font-mono' : 'text-zinc-600 font-mono'}>#{source.email_id}</span>
+                                        <span className="text-zinc-200 truncate">{source.subject}</span>
+                                        <span className="text-xs text-zinc-500 shrink-0">{source.sender} · {formatDate(source.received_at)}</span>
+                                    </div>
+                                    <p className="text-zinc-400 pl-10">{source.summary}</p>
+                                </div>
+                            ))}
+                        </div>
+                    )}
+                </div>
+            )}
+
+            {summaries.length > 0 && (
+                <div className="rounded-2xl border border-zinc-800/50 bg-zinc-900/40 p-4 space-y-2">
+                    <h3 className="font-medium text-zinc-300">Previous summaries</h3>
+                    {summaries.map((summary) => (
+                        <div key={summary.id} className="flex items-center justify-between gap-4 text-sm">
+                            <button onClick={() => setSelected(summary)} className="text-left truncate text-zinc-300 hover:text-blue-400">
+                                {summary.query}
+                                <span className="text-xs text-zinc-500 ml-2">{formatRange(summary.date_range)}</span>
+                            </button>
+                            <div className="flex items-center gap-4 shrink-0">
+                                <span className="text-xs text-zinc-500">{formatDateTime(summary.run_at)}</span>
+                                <button onClick={() => remove(summary.id)} className="text-zinc-500 hover:text-red-400 transition-colors">Delete</button>
+                            </div>
+                        </div>
+                    ))}
+                </div>
+            )}
+        </div>
+    )
+}
@@ -0,0 +1,60 @@
+import { useEffect, useState } from 'react'
+import { invoke } from '@tauri-apps/api/core'
+import { formatDate } from '../locale'
+
+// Emails deleted in Noodle, restorable until the nightly maintenance purges them.
+export function TrashPanel({ refreshKey, onRestored, onLog }: {
+    refreshKey: number,
+    onRestored: (email: any) => void,
+    onLog: (message: string, level?: 'info' | 'error' | 'warn') => void,
+}) {
+    const [trash, setTrash] = useState<any[]>([])
+    const [open, setOpen] = useState(false)
+
+    useEffect(() => {
+        invoke<any[]>('list_trash')
+            .then(setTrash)
+            .catch((e) => console.error('Failed to load the trash', e))
+    }, [refreshKey])
+
+    const restore = async (id: number) => {
+        try {
+            const email = await invoke<any>('restore_email', { id })
+            setTrash((current) => current.filter((e) => e.id !== id))
+            onRestored(email)
+        } catch (e) {
+            onLog(`Failed to restore email ${id}: ${e}`, 'error')
+        }
+    }
+
+    if (trash.length === 0) return null
+
+    return (
+        <div className="rounded-xl border border-zinc-800 bg-zinc-900/20 text-sm">
+            <button onClick={() => setOpen(!open)} className="w-full p-4 flex justify-between text-zinc-400 hover:text-zinc-200">
+                <span className="font-semibold">Trash ({trash.length})</span>
+                <span className="text-xs text-zinc-600">Deleted emails are purged after 30 days</span>
+            </button>
+            {open && (
+                <ul className="divide-y divide-zinc-800 border-t border-zinc-800">
+                    {trash.map((email) => (
+                        <li key={email.id} className="p-4 flex justify-between items-center gap-4">
+                            <div className="min-w-0">
+                                <p className="text-zinc-300 truncate">{email.subject}</p>
+                                <p className="text-xs text-zinc-600 truncate">
+                                    {email.sender} · deleted {formatDate(email.deleted_at)} · purged {formatDate(email.purge_at)}
+                                </p>
+                            </div>
+                            <button
+                                onClick={() => restore(email.id)}
+                                className="shrink-0 text-xs px-2 py-1 rounded-lg border border-zinc-800 text-zinc-300 hover:bg-zinc-800"
+                            >
+                                Restore
+                            </button>
+                        </li>
+                    ))}
+                </ul>
+            )}
+        </div>
+    )
+}
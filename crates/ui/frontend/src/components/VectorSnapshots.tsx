@@ -0,0 +1,81 @@
+import { useEffect, useState } from 'react'
+import { invoke } from '@tauri-apps/api/core'
+import { formatDateTime } from '../locale'
+
+function formatSize(bytes: number) {
+    if (bytes < 1024 * 1024) return `${Math.max(1, Math.round(bytes / 1024))} KB`
+    return `${(bytes / (1024 * 1024)).toFixed(1)} MB`
+}
+
+export function VectorSnapshots({ onLog }: { onLog: (message: string, level?: 'info' | 'warn' | 'error') => void }) {
+    const [snapshots, setSnapshots] = useState<any[]>([])
+    const [busy, setBusy] = useState(false)
+
+    const refresh = () => {
+        invoke<any[]>('list_vector_snapshots')
+            .then(setSnapshots)
+            .catch((e) => console.error('Failed to load vector snapshots', e))
+    }
+
+    useEffect(refresh, [])
+
+    const create = async () => {
+        setBusy(true)
+        try {
+            const snapshot: any = await invoke('create_vector_snapshot')
+            onLog(`Vector snapshot saved (${formatSize(snapshot.size_bytes)})`)
+        } catch (e) {
+            onLog(`Vector snapshot failed: ${e}`, 'error')
+        } finally {
+            setBusy(false)
+            refresh()
+        }
+    }
+
+    const restore = async (name: string) => {
+        if (!confirm('Replace the vector store with this snapshot? Emails indexed since then will need their vectors repaired.')) return
+        setBusy(true)
+        try {
+            await invoke('restore_vector_snapshot', { name })
+            onLog(`Restored vector snapshot ${name}`)
+        } catch (e) {
+            onLog(`Vector snapshot restore failed: ${e}`, 'error')
+        } finally {
+            setBusy(false)
+        }
+    }
+
+    return (
+        <div className="space-y-2">
+            <div className="flex items-center justify-between">
+                <span className="text-sm text-zinc-300">Saved snapshots</span>
+                <button
+                    onClick={create}
+                    disabled={busy}
+                    className="text-sm text-zinc-400 hover:text-zinc-200 disabled:text-zinc-600 transition-colors"
+                >
+                    Snapshot now
+                </button>
+            </div>
+            {snapshots.length === 0 ? (
+                <p className="text-xs text-zinc-500">No snapshots yet.</p>
+            ) : (
+                snapshots.map((snapshot) => (
+                    <div key={snapshot.name} className="flex items-center justify-between gap-4 text-xs">
+                        <span className="text-zinc-400">
+                            {formatDateTime(snapshot.created_at)}
+                            <span className="text-zinc-600 ml-2">{formatSize(snapshot.size_bytes)}</span>
+                        </span>
+                        <button
+                            onClick={() => restore(snapshot.name)}
+                            disabled={busy}
+                            className="text-zinc-500 hover:text-blue-400 disabled:text-zinc-700 transition-colors"
+                        >
+                            Restore
+                        </button>
+                    </div>
+                ))
+            )}
+        </div>
+    )
+}
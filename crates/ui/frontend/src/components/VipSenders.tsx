@@ -0,0 +1,80 @@
+import { useEffect, useState } from 'react'
+import { invoke } from '@tauri-apps/api/core'
+
+export function VipSenders() {
+    const [vips, setVips] = useState<string[]>([])
+    const [suggestions, setSuggestions] = useState<any[]>([])
+    const [sender, setSender] = useState('')
+
+    const refresh = () => {
+        invoke<string[]>('list_vip_senders')
+            .then(setVips)
+            .catch((e) => console.error('Failed to load VIP senders', e))
+        invoke<any[]>('suggest_vip_senders')
+            .then(setSuggestions)
+            .catch((e) => console.error('Failed to load VIP suggestions', e))
+    }
+
+    useEffect(refresh, [])
+
+    const add = async (address: string) => {
+        if (!address.trim()) return
+        await invoke('add_vip_sender', { sender: address }).catch(() => { })
+        setSender('')
+        refresh()
+    }
+
+    const remove = async (address: string) => {
+        await invoke('remove_vip_sender', { sender: address }).catch(() => { })
+        refresh()
+    }
+
+    return (
+        <div className="rounded-2xl border border-zinc-800/50 bg-zinc-900/40 backdrop-blur-sm overflow-hidden">
+            <div className="p-4 border-b border-zinc-800/50 bg-zinc-900/20">
+                <h3 className="font-medium text-zinc-300">VIP Senders</h3>
+                <p className="text-xs text-zinc-500 mt-1">
+                    Listed first in the digest, notified about during quiet hours and extracted ahead of the initial scan.
+                </p>
+            </div>
+            <div className="p-4 space-y-4 text-sm">
+                <form
+                    onSubmit={(e) => {
+                        e.preventDefault()
+                        add(sender)
+                    }}
+                    className="flex gap-2"
+                >
+                    <input
+                        value={sender}
+                        onChange={(e) => setSender(e.target.value)}
+                        placeholder="name@example.com"
+                        className="flex-1 bg-zinc-950 border border-zinc-800 rounded-lg px-3 py-2 text-zinc-200 focus:outline-none focus:border-blue-500/50"
+                    />
+                    <button type="submit" className="text-blue-400 hover:text-blue-300">Add</button>
+                </form>
+                {vips.length === 0 && <p className="text-zinc-500">No VIP senders yet.</p>}
+                {vips.map((vip) => (
+                    <div key={vip} className="flex items-center justify-between gap-4">
+                        <span className="truncate text-zinc-200">{vip}</span>
+                        <button onClick={() => remove(vip)} className="text-zinc-500 hover:text-red-400 transition-colors">Remove</button>
+                    </div>
+                ))}
+                {suggestions.length > 0 && (
+                    <div className="space-y-2 pt-2 border-t border-zinc-800/50">
+                        <p className="text-xs text-zinc-500">Senders you often reply to</p>
+                        {suggestions.map((s) => (
+                            <div key={s.sender} className="flex items-center justify-between gap-4">
+                                <div className="min-w-0">
+                                    <div className="truncate text-zinc-300">{s.sender}</div>
+                                    <div className="text-xs text-zinc-500">Replied to {s.replied} of {s.received} emails</div>
+                                </div>
+                                <button onClick={() => add(s.sender)} className="text-zinc-500 hover:text-green-400 transition-colors shrink-0">Make VIP</button>
+                            </div>
+                        ))}
+                    </div>
+                )}
+            </div>
+        </div>
+    )
+}
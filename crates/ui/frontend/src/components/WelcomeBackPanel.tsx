@@ -0,0 +1,68 @@
+import { useEffect, useState } from 'react'
+import { invoke } from '@tauri-apps/api/core'
+import { listen } from '@tauri-apps/api/event'
+import { formatDateTime } from '../locale'
+import { EVENTS } from '../events'
+
+const SECTIONS: [key: string, title: string, tone: string][] = [
+    ['urgent', 'Urgent', 'text-red-400'],
+    ['blockers', 'New blockers', 'text-orange-400'],
+    ['overdue', 'Now overdue', 'text-amber-400'],
+    ['replies', 'Replies to your questions', 'text-green-400'],
+]
+
+// What changed while the user was away: loaded on start, then sent by the
+// backend whenever they come back to the window after a while.
+export function WelcomeBackPanel({ onLog }: { onLog: (message: string, level?: 'info' | 'error' | 'warn') => void }) {
+    const [whatsNew, setWhatsNew] = useState<any>(null)
+
+    useEffect(() => {
+        invoke('get_whats_new', { since: null })
+            .then(setWhatsNew)
+            .catch((e) => console.error('Failed to load what changed', e))
+        const unlisten = listen(EVENTS.whatsNew, (event: any) => setWhatsNew(event.payload))
+        return () => {
+            unlisten.then(f => f())
+        }
+    }, [])
+
+    if (!whatsNew || SECTIONS.every(([key]) => whatsNew[key].length === 0)) {
+        return null
+    }
+
+    const openEmail = (id: number) =>
+        invoke('open_email_window', { id }).catch((e) => onLog(`Failed to open email: ${e}`, 'error'))
+
+    return (
+        <div className="rounded-2xl border border-blue-500/20 bg-blue-500/5 backdrop-blur-sm overflow-hidden">
+            <div className="p-4 border-b border-zinc-800/50 flex justify-between items-start gap-4">
+                <div>
+                    <h3 className="font-medium text-zinc-200">Welcome back</h3>
+                    <p className="text-xs text-zinc-500 mt-1">What changed since {formatDateTime(whatsNew.since)}</p>
+                </div>
+                <button onClick={() => setWhatsNew(null)} className="text-xs text-zinc-500 hover:text-zinc-300">
+                    Dismiss
+                </button>
+            </div>
+            <div className="p-4 grid grid-cols-1 md:grid-cols-2 gap-4 text-sm">
+                {SECTIONS.filter(([key]) => whatsNew[key].length > 0).map(([key, title, tone]) => (
+                    <div key={key} className="space-y-1">
+                        <div className={`text-xs font-bold uppercase tracking-wider ${tone}`}>
+                            {title} · {whatsNew[key].length}
+                        </div>
+                        {whatsNew[key].slice(0, 5).map((email: any) => (
+                            <button
+                                key={email.id}
+                                onClick={() => openEmail(email.id)}
+                                className="block w-full text-left truncate text-zinc-300 hover:text-blue-400"
+                            >
+                                {email.subject || '(no subject)'}
+                                <span className="text-xs text-zinc-500"> · {email.sender}</span>
+                            </button>
+                        ))}
+                    </div>
+                ))}
+            </div>
+        </div>
+    )
+}
@@ -0,0 +1,18 @@
+// Event names the backend emits, kept in step with `UiEvent` in events.rs.
+export const EVENTS = {
+    log: 'noodle://log',
+    syncState: 'noodle://sync-state',
+    scanProgress: 'noodle://scan-progress',
+    emailProgress: 'noodle://email-progress',
+    bulkProgress: 'noodle://bulk-progress',
+    chatToolCall: 'noodle://chat-tool-call',
+    experimentProgress: 'noodle://experiment-progress',
+    configChanged: 'noodle://config-changed',
+    showExitConfirm: 'noodle://show-exit-confirm',
+    openSearch: 'noodle://open-search',
+    navigate: 'noodle://navigate',
+    alert: 'noodle://alert',
+    updateAvailable: 'noodle://update-available',
+    appLocked: 'noodle://app-locked',
+    whatsNew: 'noodle://whats-new',
+} as const
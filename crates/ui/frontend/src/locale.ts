@@ -0,0 +1,30 @@
+// Dates are shown in the locale chosen in settings rather than the webview's.
+let current: string | undefined
+
+export const LOCALES: [string, string][] = [
+    ['en-US', 'English (United States)'],
+    ['en-GB', 'English (United Kingdom)'],
+    ['de-DE', 'Deutsch'],
+    ['fr-FR', 'Français'],
+    ['es-ES', 'Español'],
+    ['it-IT', 'Italiano'],
+    ['nl-NL', 'Nederlands'],
+    ['pt-BR', 'Português (Brasil)'],
+    ['ja-JP', '日本語'],
+]
+
+export function setLocale(locale: string) {
+    current = locale || undefined
+}
+
+export function formatDate(value: string | number | Date) {
+    return new Date(value).toLocaleDateString(current)
+}
+
+export function formatDateTime(value: string | number | Date) {
+    return new Date(value).toLocaleString(current)
+}
+
+export function formatTime(value: string | number | Date) {
+    return new Date(value).toLocaleTimeString(current)
+}
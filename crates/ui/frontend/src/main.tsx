@@ -1,10 +1,18 @@
 import React from 'react'
 import ReactDOM from 'react-dom/client'
 import App from './App'
+import { QuickSearch } from './components/QuickSearch'
+import { EmailReader } from './components/EmailReader'
 import './index.css'
 
 ReactDOM.createRoot(document.getElementById('root') as HTMLElement).render(
     <React.StrictMode>
-        <App />
+        {window.location.hash === '#quick-search' ? (
+            <QuickSearch />
+        ) : window.location.hash.startsWith('#email/') ? (
+            <EmailReader id={Number(window.location.hash.slice('#email/'.length))} />
+        ) : (
+            <App />
+        )}
     </React.StrictMode>,
 )
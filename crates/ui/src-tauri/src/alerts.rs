@@ -0,0 +1,73 @@
+use crate::events::UiEvent;
+use crate::AppState;
+use chrono::Utc;
+use noodle_core::types::{Alert, AlertKind};
+use tauri::{AppHandle, Manager};
+use tauri_plugin_notification::NotificationExt;
+use tokio::sync::broadcast::error::RecvError;
+use tracing::{error, warn};
+
+/// Forwards alerts raised by the pipeline to the frontend, and shows them as
+/// a toast unless quiet hours or focus mode are on, or the project is muted.
+/// Mail from VIP senders is shown during quiet hours too. Held-back alerts
+/// still appear in the app.
+pub async fn run(app: AppHandle) {
+    let state = app.state::<AppState>();
+    let mut alerts = state.pipeline.subscribe_alerts();
+    let shutdown = state.shutdown.clone();
+
+    loop {
+        let alert = tokio::select! {
+            alert = alerts.recv() => alert,
+            _ = shutdown.cancelled() => return,
+        };
+        match alert {
+            Ok(alert) => {
+                if let Err(e) = deliver(&app, &alert).await {
+                    error!("Failed to deliver alert {}: {}", alert.id, e);
+                }
+            }
+            Err(RecvError::Lagged(skipped)) => {
+                warn!("Skipped {} alerts; they are listed in the app", skipped)
+            }
+            Err(RecvError::Closed) => return,
+        }
+    }
+}
+
+async fn deliver(app: &AppHandle, alert: &Alert) -> Result<(), String> {
+    let state = app.state::<AppState>();
+    state.events.publish(UiEvent::Alert(alert.clone()));
+    let allowed = if alert.kind == AlertKind::VipEmail {
+        state.policy.vip_notifications_allowed(Utc::now()).await
+    } else {
+        state.policy.notifications_allowed(Utc::now()).await
+    }
+    .map_err(|e| e.to_string())?;
+    let muted = alert.kind == AlertKind::UrgentFlood
+        && state
+            .sqlite
+            .get_project_settings(&alert.key)
+            .await
+            .map_err(|e| e.to_string())?
+            .mute_notifications;
+    if !allowed || muted {
+        return Ok(());
+    }
+
+    app.notification()
+        .builder()
+        .title(title(alert.kind))
+        .body(&alert.message)
+        .show()
+        .map_err(|e| e.to_string())
+}
+
+fn title(kind: AlertKind) -> &'static str {
+    match kind {
+        AlertKind::VipEmail => "Noodle: VIP email",
+        AlertKind::HostileSender => "Noodle: hostile sender",
+        AlertKind::UrgentFlood => "Noodle: urgent emails piling up",
+        AlertKind::ConfidenceDrop => "Noodle: extraction quality dropped",
+    }
+}
@@ -0,0 +1,403 @@
+use crate::events::{EventBus, UiEvent};
+use crate::AppState;
+use noodle_core::types::{Annotation, Attachment};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+use tauri::ipc::Invoke;
+use tauri::{AppHandle, Manager, Runtime};
+use tracing::{error, info};
+use windows::core::{factory, HSTRING, PCWSTR, PWSTR};
+use windows::Foundation::IAsyncOperation;
+use windows::Security::Credentials::UI::{
+    UserConsentVerificationResult, UserConsentVerifier, UserConsentVerifierAvailability,
+};
+use windows::Win32::Foundation::{CloseHandle, ERROR_CANCELLED, HANDLE};
+use windows::Win32::Security::Credentials::{
+    CredUIPromptForWindowsCredentialsW, CredUnPackAuthenticationBufferW,
+    CREDUIWIN_ENUMERATE_CURRENT_USER, CREDUI_INFOW, CRED_PACK_PROTECTED_CREDENTIALS,
+};
+use windows::Win32::Security::{LogonUserW, LOGON32_LOGON_INTERACTIVE, LOGON32_PROVIDER_DEFAULT};
+use windows::Win32::System::Com::CoTaskMemFree;
+use windows::Win32::System::WinRT::IUserConsentVerifierInterop;
+use windows::Win32::UI::WindowsAndMessaging::GetForegroundWindow;
+
+/// Fields of email and email change payloads left out while locked.
+const BODY_FIELDS: &[&str] = &["body_text", "body_html", "body_diff"];
+/// Body excerpts in a search result's `explanation`, by match kind.
+const EXCERPT_FIELDS: &[(&str, &str)] = &[("vector", "chunk"), ("keyword", "snippet")];
+/// How often the idle timeout is checked.
+const CHECK_INTERVAL: Duration = Duration::from_secs(15);
+const PROMPT_MESSAGE: &str = "Unlock Noodle to read your email";
+/// Commands whose result is made from email content: answers, drafts,
+/// summaries and prompt previews. [`guard`] refuses them while locked.
+const CONTENT_COMMANDS: &[&str] = &[
+    "ask_noodle",
+    "get_chat_session",
+    "summarize_topic",
+    "list_topic_summaries",
+    "run_newsletter_roundup",
+    "list_newsletter_roundups",
+    "format_for_chat",
+    "preview_prompt",
+    "draft_reply",
+    "draft_from_dictation",
+];
+/// Why a content command was refused.
+pub const CONTENT_LOCKED: &str = "Unlock Noodle to see what it made from your email";
+
+/// Whether email bodies may be shown. Commands that return emails pass them
+/// through [`AppLock::redact`], and those returning content made from them
+/// are refused by [`guard`], so a locked app never sends a body to any
+/// window, whatever the frontend does.
+pub struct AppLock {
+    locked: AtomicBool,
+}
+
+impl AppLock {
+    pub fn new(locked: bool) -> Self {
+        Self {
+            locked: AtomicBool::new(locked),
+        }
+    }
+
+    pub fn is_locked(&self) -> bool {
+        self.locked.load(Ordering::SeqCst)
+    }
+
+    /// Publishes [`UiEvent::AppLocked`] when this locks the app, so open
+    /// windows cover what they show.
+    pub fn lock(&self, events: &EventBus) {
+        if !self.locked.swap(true, Ordering::SeqCst) {
+            info!("App locked");
+            events.publish(UiEvent::AppLocked);
+        }
+    }
+
+    fn unlock(&self) {
+        self.locked.store(false, Ordering::SeqCst);
+    }
+
+    /// Nulls the body fields of an email or email change payload while
+    /// locked, with the body excerpts search results explain a match by and
+    /// the note of its annotation, and marks it with `body_locked` so views
+    /// can say why the body is missing.
+    pub fn redact(&self, email: &mut serde_json::Value) {
+        if !self.is_locked() {
+            return;
+        }
+        if let Some(email) = email.as_object_mut() {
+            for field in BODY_FIELDS {
+                if let Some(value) = email.get_mut(*field) {
+                    *value = serde_json::Value::Null;
+                }
+            }
+            if let Some(explanation) = email.get_mut("explanation") {
+                for (kind, field) in EXCERPT_FIELDS {
+                    if let Some(value) = explanation
+                        .get_mut(*kind)
+                        .and_then(|match_| match_.get_mut(*field))
+                    {
+                        *value = serde_json::Value::Null;
+                    }
+                }
+            }
+            if let Some(note) = email
+                .get_mut("annotation")
+                .and_then(|annotation| annotation.get_mut("note"))
+            {
+                *note = serde_json::Value::Null;
+            }
+            email.insert("body_locked".into(), true.into());
+        }
+    }
+
+    /// Drops the user's note from an annotation while locked; tags, pins
+    /// and stars stay.
+    pub fn redact_annotation(&self, annotation: &mut Annotation) {
+        if self.is_locked() {
+            annotation.note = None;
+        }
+    }
+
+    /// Drops the text read out of an attachment while locked.
+    pub fn redact_attachment(&self, attachment: &mut Attachment) {
+        if self.is_locked() {
+            attachment.extracted_text = None;
+        }
+    }
+
+    /// Asks the user to prove who they are and unlocks on success. Returns
+    /// false when they cancel or fail.
+    pub async fn unlock_with_prompt(&self) -> Result<bool, String> {
+        if !self.is_locked() {
+            return Ok(true);
+        }
+        let verified = tokio::task::spawn_blocking(verify_user)
+            .await
+            .map_err(|e| e.to_string())??;
+        if verified {
+            self.unlock();
+            info!("App unlocked");
+        }
+        Ok(verified)
+    }
+}
+
+/// Wraps the command handler so [`CONTENT_COMMANDS`] are refused while the
+/// app is locked, in one place rather than in each command.
+pub fn guard<R: Runtime>(
+    handler: impl Fn(Invoke<R>) -> bool + Send + Sync + 'static,
+) -> impl Fn(Invoke<R>) -> bool + Send + Sync + 'static {
+    move |invoke| {
+        let locked = invoke
+            .message
+            .webview()
+            .try_state::<AppState>()
+            .is_some_and(|state| state.lock.is_locked());
+        if locked && CONTENT_COMMANDS.contains(&invoke.message.command()) {
+            invoke.resolver.reject(CONTENT_LOCKED);
+            return true;
+        }
+        handler(invoke)
+    }
+}
+
+/// Locks the app once the user has been away from the keyboard and mouse
+/// for `app_lock_idle_mins`, while the app lock is on.
+pub async fn run(app: AppHandle) {
+    let state = app.state::<AppState>();
+    let shutdown = state.shutdown.clone();
+    loop {
+        tokio::select! {
+            _ = tokio::time::sleep(CHECK_INTERVAL) => {}
+            _ = shutdown.cancelled() => return,
+        }
+        if state.lock.is_locked() {
+            continue;
+        }
+        let config = match state.sqlite.get_all_config().await {
+            Ok(config) => config,
+            Err(e) => {
+                error!("Failed to read app lock settings: {}", e);
+                continue;
+            }
+        };
+        if !config.app_lock {
+            continue;
+        }
+        let timeout = Duration::from_secs(u64::from(config.app_lock_idle_mins) * 60);
+        if agent::engine::idle::user_idle_time().is_some_and(|idle| idle >= timeout) {
+            state.lock.lock(&state.events);
+        }
+    }
+}
+
+/// Windows Hello when it is set up, otherwise the Windows password of the
+/// signed-in user.
+fn verify_user() -> Result<bool, String> {
+    if hello_available() {
+        windows_hello()
+    } else {
+        windows_password()
+    }
+}
+
+fn hello_available() -> bool {
+    UserConsentVerifier::CheckAvailabilityAsync()
+        .and_then(|op| op.get())
+        .is_ok_and(|availability| availability == UserConsentVerifierAvailability::Available)
+}
+
+fn windows_hello() -> Result<bool, String> {
+    // Owned by the app's window, which has focus when the user asks to
+    // unlock; otherwise the prompt can open behind it.
+    let interop = factory::<UserConsentVerifier, IUserConsentVerifierInterop>()
+        .map_err(|e| format!("Windows Hello is unavailable: {}", e))?;
+    let result = unsafe {
+        interop
+            .RequestVerificationForWindowAsync::<_, IAsyncOperation<UserConsentVerificationResult>>(
+                GetForegroundWindow(),
+                &HSTRING::from(PROMPT_MESSAGE),
+            )
+    }
+    .and_then(|op| op.get())
+    .map_err(|e| format!("Windows Hello failed: {}", e))?;
+    Ok(result == UserConsentVerificationResult::Verified)
+}
+
+/// Buffer returned by the credential prompt, wiped and freed on drop.
+struct AuthBuffer {
+    data: *mut core::ffi::c_void,
+    size: u32,
+}
+
+impl Drop for AuthBuffer {
+    fn drop(&mut self) {
+        if self.data.is_null() {
+            return;
+        }
+        unsafe {
+            wipe(std::slice::from_raw_parts_mut(
+                self.data.cast::<u8>(),
+                self.size as usize,
+            ));
+            CoTaskMemFree(Some(self.data));
+        }
+    }
+}
+
+fn windows_password() -> Result<bool, String> {
+    let caption = HSTRING::from("Unlock Noodle");
+    let message = HSTRING::from(PROMPT_MESSAGE);
+    let info = CREDUI_INFOW {
+        cbSize: std::mem::size_of::<CREDUI_INFOW>() as u32,
+        hwndParent: unsafe { GetForegroundWindow() },
+        pszMessageText: PCWSTR(message.as_ptr()),
+        pszCaptionText: PCWSTR(caption.as_ptr()),
+        ..Default::default()
+    };
+    let mut package = 0;
+    let mut buffer = AuthBuffer {
+        data: std::ptr::null_mut(),
+        size: 0,
+    };
+    let status = unsafe {
+        CredUIPromptForWindowsCredentialsW(
+            Some(&info),
+            0,
+            &mut package,
+            None,
+            0,
+            &mut buffer.data,
+            &mut buffer.size,
+            None,
+            CREDUIWIN_ENUMERATE_CURRENT_USER,
+        )
+    };
+    if status == ERROR_CANCELLED.0 {
+        return Ok(false);
+    }
+    if status != 0 {
+        return Err(format!("Credential prompt failed with error {}", status));
+    }
+
+    // Sizes in UTF-16 units, as in CREDUI_MAX_USERNAME_LENGTH and friends.
+    let mut user = [0u16; 514];
+    let mut domain = [0u16; 338];
+    let mut password = [0u16; 257];
+    let (mut user_len, mut domain_len, mut password_len) = (514, 338, 257);
+    let unpacked = unsafe {
+        CredUnPackAuthenticationBufferW(
+            CRED_PACK_PROTECTED_CREDENTIALS,
+            buffer.data,
+            buffer.size,
+            PWSTR(user.as_mut_ptr()),
+            &mut user_len,
+            PWSTR(domain.as_mut_ptr()),
+            Some(&mut domain_len),
+            PWSTR(password.as_mut_ptr()),
+            &mut password_len,
+        )
+    };
+    drop(buffer);
+    let verified = unpacked.is_ok() && logon(&user, &domain, &password);
+    wipe(&mut password);
+    unpacked.map_err(|e| format!("Failed to read the entered credential: {}", e))?;
+    Ok(verified)
+}
+
+/// Checks the password with `LogonUserW`. A user given as `DOMAIN\name`
+/// carries its own domain.
+fn logon(user: &[u16], domain: &[u16], password: &[u16]) -> bool {
+    let user = String::from_utf16_lossy(until_nul(user));
+    let domain = String::from_utf16_lossy(until_nul(domain));
+    let (domain, user) = match user.split_once('\\') {
+        Some((domain, user)) => (domain.to_string(), user.to_string()),
+        None => (domain, user),
+    };
+    let domain = (!domain.is_empty()).then(|| HSTRING::from(domain));
+    let mut token = HANDLE::default();
+    let result = unsafe {
+        LogonUserW(
+            &HSTRING::from(user),
+            domain
+                .as_ref()
+                .map_or(PCWSTR::null(), |d| PCWSTR(d.as_ptr())),
+            PCWSTR(password.as_ptr()),
+            LOGON32_LOGON_INTERACTIVE,
+            LOGON32_PROVIDER_DEFAULT,
+            &mut token,
+        )
+    };
+    if result.is_ok() {
+        let _ = unsafe { CloseHandle(token) };
+    }
+    result.is_ok()
+}
+
+fn until_nul(text: &[u16]) -> &[u16] {
+    &text[..text.iter().position(|c| *c == 0).unwrap_or(text.len())]
+}
+
+/// Zeroes `buffer` in a way the compiler can't skip as a dead store.
+fn wipe<T: Default>(buffer: &mut [T]) {
+    for item in buffer {
+        unsafe { std::ptr::write_volatile(item, T::default()) };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn email() -> serde_json::Value {
+        json!({
+            "id": 7,
+            "subject": "Budget",
+            "body_text": "The number is 42.",
+            "body_html": "<p>The number is 42.</p>",
+            "explanation": {
+                "vector": { "score": 0.8, "chunk": "number is 42" },
+                "keyword": { "rank": -1.5, "snippet": "**number** is 42" }
+            },
+            "annotation": { "id": 3, "note": "Ask Dana first", "tags": ["budget"] }
+        })
+    }
+
+    #[test]
+    fn redact_leaves_unlocked_emails_alone() {
+        let mut unlocked = email();
+        AppLock::new(false).redact(&mut unlocked);
+        assert_eq!(unlocked, email());
+    }
+
+    #[test]
+    fn redact_removes_bodies_and_excerpts_while_locked() {
+        let mut locked = email();
+        AppLock::new(true).redact(&mut locked);
+        assert_eq!(
+            locked,
+            json!({
+                "id": 7,
+                "subject": "Budget",
+                "body_text": null,
+                "body_html": null,
+                "explanation": {
+                    "vector": { "score": 0.8, "chunk": null },
+                    "keyword": { "rank": -1.5, "snippet": null }
+                },
+                "annotation": { "id": 3, "note": null, "tags": ["budget"] },
+                "body_locked": true
+            })
+        );
+
+        // Fields an email doesn't have are not added.
+        let mut change = json!({ "id": 7, "body_diff": "- 41\n+ 42" });
+        AppLock::new(true).redact(&mut change);
+        assert_eq!(
+            change,
+            json!({ "id": 7, "body_diff": null, "body_locked": true })
+        );
+    }
+}
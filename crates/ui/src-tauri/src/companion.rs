@@ -0,0 +1,184 @@
+use crate::{AppState, EMAIL_TIME_FIELDS};
+use axum::extract::{Path, Query, Request, State};
+use axum::http::{header, StatusCode};
+use axum::middleware::{self, Next};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use noodle_core::time::localize_fields;
+use serde::{Deserialize, Serialize};
+use std::net::{Ipv4Addr, SocketAddr};
+use subtle::ConstantTimeEq;
+use tauri::{AppHandle, Manager};
+use tracing::{error, info};
+
+/// Local HTTP API for the browser extension, so mail read in Outlook on the
+/// web gets the same facts and drafts as the app. It only listens on the
+/// loopback interface and every request needs the configured bearer token.
+/// Enabling it, or changing the port, takes effect at the next start.
+pub async fn run(app: AppHandle) {
+    let state = app.state::<AppState>();
+    let config = match state.sqlite.get_all_config().await {
+        Ok(config) => config,
+        Err(e) => {
+            error!("Failed to read companion API settings: {}", e);
+            return;
+        }
+    };
+    if !config.companion_api {
+        return;
+    }
+    if config.companion_api_token.is_none() {
+        error!("Companion API is enabled but has no token; not starting it");
+        return;
+    }
+
+    let addr = SocketAddr::from((Ipv4Addr::LOCALHOST, config.companion_api_port as u16));
+    let listener = match tokio::net::TcpListener::bind(addr).await {
+        Ok(listener) => listener,
+        Err(e) => {
+            error!("Failed to start companion API on {}: {}", addr, e);
+            return;
+        }
+    };
+    let router = Router::new()
+        .route("/v1/messages", get(lookup))
+        .route("/v1/messages/{id}/facts", get(facts))
+        .route("/v1/messages/{id}/draft", post(draft))
+        .layer(middleware::from_fn_with_state(app.clone(), authorize))
+        .with_state(app.clone());
+
+    info!("Companion API listening on {}", addr);
+    let shutdown = state.shutdown.clone();
+    if let Err(e) = axum::serve(listener, router)
+        .with_graceful_shutdown(async move { shutdown.cancelled().await })
+        .await
+    {
+        error!("Companion API stopped: {}", e);
+    }
+}
+
+struct ApiError(StatusCode, String);
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        (self.0, Json(serde_json::json!({ "error": self.1 }))).into_response()
+    }
+}
+
+fn internal(e: impl ToString) -> ApiError {
+    ApiError(StatusCode::INTERNAL_SERVER_ERROR, e.to_string())
+}
+
+/// Checks the bearer token against the current setting, so a new token
+/// applies without a restart. Compared in constant time, so response times
+/// don't tell how much of a guess was right.
+async fn authorize(State(app): State<AppHandle>, request: Request, next: Next) -> Response {
+    let expected = app
+        .state::<AppState>()
+        .sqlite
+        .get_all_config()
+        .await
+        .ok()
+        .and_then(|config| config.companion_api_token);
+    let given = request
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "));
+    match (expected, given) {
+        (Some(expected), Some(given))
+            if bool::from(expected.as_bytes().ct_eq(given.as_bytes())) =>
+        {
+            next.run(request).await
+        }
+        _ => ApiError(StatusCode::UNAUTHORIZED, "Invalid token".into()).into_response(),
+    }
+}
+
+#[derive(Deserialize)]
+struct LookupQuery {
+    internet_message_id: String,
+}
+
+#[derive(Serialize)]
+struct LookupResult {
+    indexed: bool,
+    email_id: Option<i64>,
+}
+
+/// Whether the message with this `Message-ID` header has been synced.
+async fn lookup(
+    State(app): State<AppHandle>,
+    Query(query): Query<LookupQuery>,
+) -> Result<Json<LookupResult>, ApiError> {
+    // Outlook stores the header with its angle brackets; web clients may not.
+    let id = query
+        .internet_message_id
+        .trim()
+        .trim_start_matches('<')
+        .trim_end_matches('>');
+    if id.is_empty() {
+        return Err(ApiError(
+            StatusCode::BAD_REQUEST,
+            "internet_message_id is empty".into(),
+        ));
+    }
+    let email_id = app
+        .state::<AppState>()
+        .sqlite
+        .find_email_by_internet_message_id(&format!("<{}>", id))
+        .await
+        .map_err(internal)?;
+    Ok(Json(LookupResult {
+        indexed: email_id.is_some(),
+        email_id,
+    }))
+}
+
+/// The email and its extracted facts, as the app's email list shows them.
+async fn facts(
+    State(app): State<AppHandle>,
+    Path(id): Path<i64>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let state = app.state::<AppState>();
+    let mut email = state
+        .sqlite
+        .get_email_with_facts(id)
+        .await
+        .map_err(internal)?
+        .ok_or_else(|| ApiError(StatusCode::NOT_FOUND, "Email not found".into()))?;
+    let tz = state.sqlite.get_user_timezone().await.map_err(internal)?;
+    localize_fields(&mut email, EMAIL_TIME_FIELDS, tz);
+    state.lock.redact(&mut email);
+    Ok(Json(email))
+}
+
+#[derive(Serialize)]
+struct DraftResult {
+    draft: String,
+}
+
+async fn draft(
+    State(app): State<AppHandle>,
+    Path(id): Path<i64>,
+) -> Result<Json<DraftResult>, ApiError> {
+    let state = app.state::<AppState>();
+    if state.lock.is_locked() {
+        return Err(ApiError(
+            StatusCode::LOCKED,
+            crate::app_lock::CONTENT_LOCKED.into(),
+        ));
+    }
+    if state
+        .sqlite
+        .get_email_detail(id)
+        .await
+        .map_err(internal)?
+        .is_none()
+    {
+        return Err(ApiError(StatusCode::NOT_FOUND, "Email not found".into()));
+    }
+    let draft = crate::generate_draft(&state, id).await.map_err(internal)?;
+    Ok(Json(DraftResult { draft }))
+}
@@ -0,0 +1,131 @@
+use crate::events::{EventBus, UiEvent};
+use serde::Serialize;
+use std::sync::Mutex;
+use tauri::AppHandle;
+use tracing::{info, warn};
+use windows::core::{HSTRING, PCWSTR};
+use windows::Win32::System::Registry::{RegSetKeyValueW, HKEY_CURRENT_USER, REG_SZ};
+
+/// Links look like `noodle://email/123` or `noodle://project/Acme`.
+pub const SCHEME: &str = "noodle";
+
+/// Where a link asks the app to go.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum DeepLink {
+    Email { id: i64 },
+    Project { name: String },
+}
+
+/// A link the app was launched with, kept until the frontend has loaded
+/// and asks for it; events emitted before then would be lost.
+static PENDING: Mutex<Option<DeepLink>> = Mutex::new(None);
+
+/// Registers `noodle://` for the current user, pointing at this executable.
+/// Rewritten on every start so the handler follows the app when it moves.
+pub fn register_scheme() -> Result<(), String> {
+    let exe = std::env::current_exe().map_err(|e| e.to_string())?;
+    let key = format!("Software\\Classes\\{}", SCHEME);
+    let command = format!("\"{}\" \"%1\"", exe.display());
+    set_string(&key, None, "URL:Noodle")?;
+    set_string(&key, Some("URL Protocol"), "")?;
+    set_string(&format!("{}\\shell\\open\\command", key), None, &command)
+}
+
+/// Writes a string value, creating the key when missing. `None` sets the
+/// key's default value.
+fn set_string(key: &str, name: Option<&str>, value: &str) -> Result<(), String> {
+    let data: Vec<u16> = value.encode_utf16().chain(std::iter::once(0)).collect();
+    let name = name.map(HSTRING::from);
+    let name_ptr = name.as_ref().map_or(PCWSTR::null(), |n| PCWSTR(n.as_ptr()));
+    unsafe {
+        RegSetKeyValueW(
+            HKEY_CURRENT_USER,
+            &HSTRING::from(key),
+            name_ptr,
+            REG_SZ.0,
+            Some(data.as_ptr().cast()),
+            (data.len() * std::mem::size_of::<u16>()) as u32,
+        )
+    }
+    .ok()
+    .map_err(|e| format!("Failed to write {}: {}", key, e))
+}
+
+impl DeepLink {
+    /// Parses `noodle://email/<id>` and `noodle://project/<name>`; anything
+    /// else is `None`.
+    pub fn parse(url: &str) -> Option<Self> {
+        let rest = url
+            .strip_prefix(SCHEME)?
+            .strip_prefix("://")?
+            .trim_end_matches('/');
+        let (kind, value) = rest.split_once('/')?;
+        let value = percent_decode(value);
+        if value.is_empty() {
+            return None;
+        }
+        match kind.to_ascii_lowercase().as_str() {
+            "email" => value.parse().ok().map(|id| Self::Email { id }),
+            "project" => Some(Self::Project { name: value }),
+            _ => None,
+        }
+    }
+}
+
+/// Decodes `%XX` escapes, leaving malformed ones as they are.
+fn percent_decode(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let escaped = (bytes[i] == b'%')
+            .then(|| value.get(i + 1..i + 3))
+            .flatten()
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match escaped {
+            Some(byte) => {
+                out.push(byte);
+                i += 3;
+            }
+            None => {
+                out.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+/// The first link among a launch's arguments.
+fn find(args: &[String]) -> Option<DeepLink> {
+    args.iter().find_map(|arg| {
+        let link = DeepLink::parse(arg);
+        if link.is_none() && arg.starts_with(&format!("{}:", SCHEME)) {
+            warn!("Ignoring unrecognized link {}", arg);
+        }
+        link
+    })
+}
+
+/// Remembers a link this instance was launched with, for [`take_pending`].
+pub fn remember_launch_link(args: &[String]) {
+    if let Some(link) = find(args) {
+        info!("Launched with link {:?}", link);
+        *PENDING.lock().unwrap_or_else(|e| e.into_inner()) = Some(link);
+    }
+}
+
+/// Tells the frontend where to go for a link forwarded by a second launch.
+pub fn forward(app: &AppHandle, args: &[String]) {
+    let Some(link) = find(args) else {
+        return;
+    };
+    info!("Opening link {:?}", link);
+    EventBus::new(app).publish(UiEvent::Navigate(link));
+}
+
+/// The link this instance was launched with, once.
+pub fn take_pending() -> Option<DeepLink> {
+    PENDING.lock().unwrap_or_else(|e| e.into_inner()).take()
+}
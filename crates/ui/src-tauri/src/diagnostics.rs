@@ -0,0 +1,303 @@
+use crate::AppState;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::any::Any;
+use std::collections::{BTreeMap, VecDeque};
+use std::fmt::Write as _;
+use std::fs::File;
+use std::io::Write as _;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+use tracing::field::{Field, Visit};
+use tracing::{error, Event, Level, Subscriber};
+use tracing_subscriber::layer::{Context, SubscriberExt};
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::{EnvFilter, Layer};
+use zip::write::SimpleFileOptions;
+use zip::ZipWriter;
+
+/// Log lines kept in memory for crash reports and bundles.
+const RECENT_LINES: usize = 500;
+/// Rows of the persisted `logs` table included in a bundle.
+const BUNDLE_LOG_ROWS: i64 = 2000;
+/// Newest crash reports included in a bundle.
+const BUNDLE_CRASHES: usize = 10;
+const CRASH_PREFIX: &str = "crash-";
+
+static RECENT: Mutex<VecDeque<String>> = Mutex::new(VecDeque::new());
+static COMPONENTS: Mutex<BTreeMap<String, ComponentHealth>> = Mutex::new(BTreeMap::new());
+/// `<app data>/diagnostics`, known once setup has resolved the app data dir.
+static DIR: OnceLock<PathBuf> = OnceLock::new();
+
+/// Warnings and errors logged by one crate since startup.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ComponentHealth {
+    pub warnings: u64,
+    pub errors: u64,
+    pub last_error: Option<String>,
+    pub last_error_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct SystemInfo {
+    app_version: &'static str,
+    tauri_version: &'static str,
+    os: &'static str,
+    arch: &'static str,
+}
+
+impl SystemInfo {
+    fn current() -> Self {
+        Self {
+            app_version: env!("CARGO_PKG_VERSION"),
+            tauri_version: tauri::VERSION,
+            os: std::env::consts::OS,
+            arch: std::env::consts::ARCH,
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct CrashReport {
+    occurred_at: DateTime<Utc>,
+    message: String,
+    location: Option<String>,
+    thread: Option<String>,
+    backtrace: String,
+    system: SystemInfo,
+    components: BTreeMap<String, ComponentHealth>,
+    recent_logs: Vec<String>,
+}
+
+/// Installs the tracing subscriber and the panic hook. Call first thing in
+/// `main`; crash reports are written once [`set_dir`] has run.
+pub fn init() {
+    let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info"));
+    let _ = tracing_subscriber::registry()
+        .with(filter)
+        .with(tracing_subscriber::fmt::layer())
+        .with(RecentLogs)
+        .try_init();
+
+    let previous = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        let location = info.location().map(|l| l.to_string());
+        write_crash_report(panic_message(info.payload()), location);
+        previous(info);
+    }));
+}
+
+pub fn set_dir(app_dir: &Path) {
+    let dir = app_dir.join("diagnostics");
+    if let Err(e) = std::fs::create_dir_all(&dir) {
+        error!("Failed to create diagnostics dir: {}", e);
+        return;
+    }
+    let _ = DIR.set(dir);
+}
+
+pub fn components() -> BTreeMap<String, ComponentHealth> {
+    COMPONENTS.lock().unwrap_or_else(|e| e.into_inner()).clone()
+}
+
+fn recent_logs() -> Vec<String> {
+    RECENT
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .iter()
+        .cloned()
+        .collect()
+}
+
+fn panic_message(payload: &(dyn Any + Send)) -> String {
+    payload
+        .downcast_ref::<&str>()
+        .map(|s| s.to_string())
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "unknown panic payload".to_string())
+}
+
+fn write_crash_report(message: String, location: Option<String>) {
+    let Some(dir) = DIR.get() else {
+        return;
+    };
+    // The panic may have happened while a lock was held on this thread, so
+    // never block here.
+    let recent_logs = RECENT
+        .try_lock()
+        .map(|lines| lines.iter().cloned().collect())
+        .unwrap_or_default();
+    let components = COMPONENTS.try_lock().map(|c| c.clone()).unwrap_or_default();
+    let report = CrashReport {
+        occurred_at: Utc::now(),
+        message,
+        location,
+        thread: std::thread::current().name().map(str::to_string),
+        backtrace: std::backtrace::Backtrace::force_capture().to_string(),
+        system: SystemInfo::current(),
+        components,
+        recent_logs,
+    };
+    let path = dir.join(format!(
+        "{}{}.json",
+        CRASH_PREFIX,
+        report.occurred_at.format("%Y%m%d-%H%M%S%.3f")
+    ));
+    if let Ok(json) = serde_json::to_vec_pretty(&report) {
+        let _ = std::fs::write(path, json);
+    }
+}
+
+/// Zips recent logs, crash reports, system and schema info and the settings
+/// (secrets left out) into `<app data>/diagnostics`. Returns the file path.
+pub async fn create_bundle(state: &AppState) -> Result<PathBuf, String> {
+    let dir = DIR.get().ok_or("Diagnostics directory is not available")?;
+    let config = state
+        .sqlite
+        .get_all_config()
+        .await
+        .map_err(|e| e.to_string())?;
+    let logs = state
+        .sqlite
+        .get_logs(BUNDLE_LOG_ROWS)
+        .await
+        .map_err(|e| e.to_string())?;
+    let schema = state
+        .sqlite
+        .get_schema_info()
+        .await
+        .map_err(|e| e.to_string())?;
+    let sync = state.sync.lock().await.as_ref().map(|sync| sync.status());
+    let created_at = Utc::now();
+    let info = serde_json::json!({
+        "created_at": created_at,
+        "system": SystemInfo::current(),
+        "schema": schema,
+        "sync": sync,
+        "components": components(),
+    });
+
+    let mut recent = String::new();
+    for line in recent_logs() {
+        let _ = writeln!(recent, "{}", line);
+    }
+
+    let mut files: Vec<(String, Vec<u8>)> = vec![
+        ("info.json".into(), to_json(&info)?),
+        (
+            "config.json".into(),
+            to_json(&config.to_portable_entries())?,
+        ),
+        ("logs.json".into(), to_json(&logs)?),
+        ("recent.log".into(), recent.into_bytes()),
+    ];
+    for path in newest_crash_reports(dir) {
+        if let (Some(name), Ok(contents)) = (
+            path.file_name().and_then(|n| n.to_str()),
+            std::fs::read(&path),
+        ) {
+            files.push((format!("crashes/{}", name), contents));
+        }
+    }
+
+    let path = dir.join(format!(
+        "noodle-diagnostics-{}.zip",
+        created_at.format("%Y%m%d-%H%M%S")
+    ));
+    write_zip(&path, &files).map_err(|e| format!("Failed to write diagnostics bundle: {}", e))?;
+    Ok(path)
+}
+
+fn to_json(value: &impl Serialize) -> Result<Vec<u8>, String> {
+    serde_json::to_vec_pretty(value).map_err(|e| e.to_string())
+}
+
+fn newest_crash_reports(dir: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut reports: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| {
+            path.file_name()
+                .and_then(|n| n.to_str())
+                .is_some_and(|n| n.starts_with(CRASH_PREFIX) && n.ends_with(".json"))
+        })
+        .collect();
+    // Names embed the timestamp, so they sort chronologically.
+    reports.sort();
+    reports.into_iter().rev().take(BUNDLE_CRASHES).collect()
+}
+
+fn write_zip(path: &Path, files: &[(String, Vec<u8>)]) -> zip::result::ZipResult<()> {
+    let mut zip = ZipWriter::new(File::create(path)?);
+    let options = SimpleFileOptions::default();
+    for (name, contents) in files {
+        zip.start_file(name.as_str(), options)?;
+        zip.write_all(contents)?;
+    }
+    zip.finish()?;
+    Ok(())
+}
+
+/// Keeps the last [`RECENT_LINES`] log lines and counts warnings and errors per
+/// crate.
+struct RecentLogs;
+
+impl<S: Subscriber> Layer<S> for RecentLogs {
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        let mut fields = FieldText::default();
+        event.record(&mut fields);
+        let metadata = event.metadata();
+        let now = Utc::now();
+        let line = format!(
+            "{} {:>5} {}: {}{}",
+            now.to_rfc3339(),
+            metadata.level(),
+            metadata.target(),
+            fields.message,
+            fields.extra
+        );
+
+        let level = *metadata.level();
+        if level <= Level::WARN {
+            let component = metadata
+                .target()
+                .split("::")
+                .next()
+                .unwrap_or_default()
+                .to_string();
+            let mut components = COMPONENTS.lock().unwrap_or_else(|e| e.into_inner());
+            let health = components.entry(component).or_default();
+            if level == Level::ERROR {
+                health.errors += 1;
+                health.last_error = Some(fields.message.clone());
+                health.last_error_at = Some(now);
+            } else {
+                health.warnings += 1;
+            }
+        }
+
+        let mut recent = RECENT.lock().unwrap_or_else(|e| e.into_inner());
+        if recent.len() == RECENT_LINES {
+            recent.pop_front();
+        }
+        recent.push_back(line);
+    }
+}
+
+#[derive(Default)]
+struct FieldText {
+    message: String,
+    extra: String,
+}
+
+impl Visit for FieldText {
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        if field.name() == "message" {
+            self.message = format!("{:?}", value);
+        } else {
+            let _ = write!(self.extra, " {}={:?}", field.name(), value);
+        }
+    }
+}
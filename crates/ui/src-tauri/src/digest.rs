@@ -0,0 +1,310 @@
+use crate::AppState;
+use agent::digest::roundup::RoundupService;
+use agent::digest::Digest;
+use chrono::{NaiveTime, Utc};
+use tauri::image::Image;
+use tauri::{AppHandle, Manager};
+use tauri_plugin_notification::NotificationExt;
+use tracing::{error, info};
+
+const CHECK_INTERVAL_SECS: u64 = 60;
+const ROUNDUP_CHECK_INTERVAL_SECS: u64 = 3600;
+const DEFAULT_DIGEST_TIME: &str = "08:00";
+const TOAST_ITEMS: usize = 3;
+
+/// Keeps the tray badge in sync with the unhandled-urgent count and fires the
+/// morning digest toast once per local day, after `digest_time`, when
+/// `digest_notifications` is enabled.
+pub async fn run(app: AppHandle) {
+    let mut last_badge: Option<usize> = None;
+    let mut interval = tokio::time::interval(std::time::Duration::from_secs(CHECK_INTERVAL_SECS));
+
+    let shutdown = app.state::<AppState>().shutdown.clone();
+
+    loop {
+        tokio::select! {
+            _ = interval.tick() => {}
+            _ = shutdown.cancelled() => return,
+        }
+        if let Err(e) = tick(&app, &mut last_badge).await {
+            error!("Digest check failed: {}", e);
+        }
+    }
+}
+
+async fn tick(app: &AppHandle, last_badge: &mut Option<usize>) -> Result<(), String> {
+    let state = app.state::<AppState>();
+    let now = Utc::now();
+    let digest = state.digest.build(now).await.map_err(|e| e.to_string())?;
+
+    if *last_badge != Some(digest.urgent_unhandled) {
+        update_tray_badge(app, digest.urgent_unhandled);
+        *last_badge = Some(digest.urgent_unhandled);
+    }
+
+    let enabled = state
+        .sqlite
+        .get_config("digest_notifications")
+        .await
+        .unwrap_or(None)
+        .is_some_and(|v| v == "true");
+    if !enabled {
+        return Ok(());
+    }
+
+    let tz = state
+        .sqlite
+        .get_user_timezone()
+        .await
+        .map_err(|e| e.to_string())?;
+    let local = tz.to_local(now);
+    let digest_time = state
+        .sqlite
+        .get_config("digest_time")
+        .await
+        .unwrap_or(None)
+        .and_then(|s| NaiveTime::parse_from_str(&s, "%H:%M").ok())
+        .unwrap_or_else(|| {
+            NaiveTime::parse_from_str(DEFAULT_DIGEST_TIME, "%H:%M").unwrap_or_default()
+        });
+    let today = local.date_naive().to_string();
+    let last_sent = state
+        .sqlite
+        .get_config("digest_last_sent")
+        .await
+        .unwrap_or(None);
+    if local.time() < digest_time || last_sent.as_deref() == Some(today.as_str()) {
+        return Ok(());
+    }
+    // Held back rather than dropped: delivered on the first check after quiet
+    // hours or focus mode end.
+    if !state
+        .policy
+        .notifications_allowed(now)
+        .await
+        .map_err(|e| e.to_string())?
+    {
+        return Ok(());
+    }
+
+    let announced = digest.announced().count();
+    if announced > 0 {
+        show_toast(app, &digest)?;
+        info!("Delivered morning digest with {} items", announced);
+    }
+
+    state
+        .sqlite
+        .set_config("digest_last_sent", &today)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Builds the weekly newsletter roundup once it is due, while
+/// `newsletter_roundup` is enabled, and announces it when notifications are
+/// allowed.
+pub async fn run_roundups(app: AppHandle) {
+    let mut interval =
+        tokio::time::interval(std::time::Duration::from_secs(ROUNDUP_CHECK_INTERVAL_SECS));
+    let shutdown = app.state::<AppState>().shutdown.clone();
+
+    loop {
+        tokio::select! {
+            _ = interval.tick() => {}
+            _ = shutdown.cancelled() => return,
+        }
+        if let Err(e) = roundup_tick(&app).await {
+            error!("Newsletter roundup failed: {}", e);
+        }
+    }
+}
+
+async fn roundup_tick(app: &AppHandle) -> Result<(), String> {
+    let state = app.state::<AppState>();
+    let enabled = state
+        .sqlite
+        .get_config("newsletter_roundup")
+        .await
+        .unwrap_or(None)
+        .is_none_or(|v| v == "true");
+    if !enabled {
+        return Ok(());
+    }
+
+    let now = Utc::now();
+    let roundups = RoundupService::new(
+        state.sqlite.clone(),
+        state.ai.clone(),
+        state.pipeline.pacing(),
+    );
+    if roundups
+        .next_due()
+        .await
+        .map_err(|e| e.to_string())?
+        .is_some_and(|due| due > now)
+    {
+        return Ok(());
+    }
+    let Some(roundup) = roundups.run(now).await.map_err(|e| e.to_string())? else {
+        return Ok(());
+    };
+    info!(
+        "Built newsletter roundup of {} newsletters",
+        roundup.sources.len()
+    );
+
+    if roundup.status == "completed"
+        && state
+            .policy
+            .notifications_allowed(now)
+            .await
+            .map_err(|e| e.to_string())?
+    {
+        app.notification()
+            .builder()
+            .title("Newsletter roundup")
+            .body(format!(
+                "This week's {} newsletters, summarized.",
+                roundup.sources.len()
+            ))
+            .show()
+            .map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+/// Builds the digest and shows it right away, regardless of the schedule,
+/// quiet hours or focus mode, since the user asked for it.
+pub async fn notify_now(app: &AppHandle) -> Result<(), String> {
+    let digest = app
+        .state::<AppState>()
+        .digest
+        .build(Utc::now())
+        .await
+        .map_err(|e| e.to_string())?;
+    if digest.announced().next().is_none() {
+        return app
+            .notification()
+            .builder()
+            .title("Noodle digest")
+            .body("Nothing needs your attention right now.")
+            .show()
+            .map_err(|e| e.to_string());
+    }
+    show_toast(app, &digest)
+}
+
+/// Leaves out items of projects with muted notifications.
+fn show_toast(app: &AppHandle, digest: &Digest) -> Result<(), String> {
+    let announced = digest.announced().count();
+    let body = digest
+        .top(TOAST_ITEMS)
+        .iter()
+        .map(|item| {
+            let marker = if item.vip { "★" } else { "•" };
+            format!("{} {}", marker, item.subject)
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+    let title = match digest.away_since {
+        Some(_) => format!(
+            "While you were away: {} email(s), {} to catch up on",
+            digest.received_while_away, announced
+        ),
+        None => format!(
+            "Noodle digest: {} item(s), {} urgent",
+            announced, digest.urgent_unhandled
+        ),
+    };
+    app.notification()
+        .builder()
+        .title(title)
+        .body(body)
+        .show()
+        .map_err(|e| e.to_string())
+}
+
+fn update_tray_badge(app: &AppHandle, count: usize) {
+    let (Some(tray), Some(icon)) = (app.tray_by_id(crate::tray::ID), app.default_window_icon())
+    else {
+        return;
+    };
+    let icon = if count == 0 {
+        icon.clone()
+    } else {
+        render_badge(icon, count)
+    };
+    if let Err(e) = tray.set_icon(Some(icon)) {
+        error!("Failed to update tray badge: {}", e);
+    }
+}
+
+/// 3x5 bitmap glyphs for the badge text, one row per entry, MSB on the left.
+const GLYPHS: [(char, [u8; 5]); 11] = [
+    ('0', [0b111, 0b101, 0b101, 0b101, 0b111]),
+    ('1', [0b010, 0b110, 0b010, 0b010, 0b111]),
+    ('2', [0b111, 0b001, 0b111, 0b100, 0b111]),
+    ('3', [0b111, 0b001, 0b111, 0b001, 0b111]),
+    ('4', [0b101, 0b101, 0b111, 0b001, 0b001]),
+    ('5', [0b111, 0b100, 0b111, 0b001, 0b111]),
+    ('6', [0b111, 0b100, 0b111, 0b101, 0b111]),
+    ('7', [0b111, 0b001, 0b010, 0b010, 0b010]),
+    ('8', [0b111, 0b101, 0b111, 0b101, 0b111]),
+    ('9', [0b111, 0b101, 0b111, 0b001, 0b111]),
+    ('+', [0b000, 0b010, 0b111, 0b010, 0b000]),
+];
+
+/// Draws a red count badge over the bottom-right of `base`.
+fn render_badge(base: &Image<'_>, count: usize) -> Image<'static> {
+    let (width, height) = (base.width() as i64, base.height() as i64);
+    let mut rgba = base.rgba().to_vec();
+    let mut put = |x: i64, y: i64, color: [u8; 4]| {
+        if (0..width).contains(&x) && (0..height).contains(&y) {
+            let i = ((y * width + x) * 4) as usize;
+            rgba[i..i + 4].copy_from_slice(&color);
+        }
+    };
+
+    let radius = width.min(height) * 3 / 10;
+    let (cx, cy) = (width - radius - 1, height - radius - 1);
+    for y in cy - radius..=cy + radius {
+        for x in cx - radius..=cx + radius {
+            if (x - cx).pow(2) + (y - cy).pow(2) <= radius.pow(2) {
+                put(x, y, [220, 38, 38, 255]);
+            }
+        }
+    }
+
+    let text = if count > 9 {
+        "9+".to_string()
+    } else {
+        count.to_string()
+    };
+    let glyphs: Vec<[u8; 5]> = text
+        .chars()
+        .filter_map(|c| GLYPHS.iter().find(|(g, _)| *g == c).map(|(_, rows)| *rows))
+        .collect();
+    let columns = glyphs.len() as i64 * 4 - 1;
+    let scale = ((radius * 2 * 7 / 10) / columns.max(5)).max(1);
+    let left = cx - columns * scale / 2;
+    let top = cy - 5 * scale / 2;
+
+    for (n, rows) in glyphs.iter().enumerate() {
+        for (row, bits) in rows.iter().enumerate() {
+            for col in 0..3 {
+                if bits & (0b100 >> col) == 0 {
+                    continue;
+                }
+                let x0 = left + (n as i64 * 4 + col) * scale;
+                let y0 = top + row as i64 * scale;
+                for dy in 0..scale {
+                    for dx in 0..scale {
+                        put(x0 + dx, y0 + dy, [255, 255, 255, 255]);
+                    }
+                }
+            }
+        }
+    }
+
+    Image::new_owned(rgba, width as u32, height as u32)
+}
@@ -0,0 +1,224 @@
+use crate::deep_link::DeepLink;
+use crate::updates::UpdateInfo;
+use agent::digest::whats_new::WhatsNew;
+use noodle_core::events::{AppEvent, Notifier};
+use noodle_core::types::Alert;
+use serde::Serialize;
+use tauri::{AppHandle, Emitter};
+use tracing::warn;
+
+/// Every event sent to the frontend. Each has one name, listed in
+/// `frontend/src/events.ts` as well, and one payload shape: the variant's
+/// content, serialized as is.
+#[derive(Debug, Clone, Serialize)]
+#[serde(untagged)]
+pub enum UiEvent {
+    /// Raised by the sync loop and pipeline; see [`AppEvent`].
+    App(AppEvent),
+    ConfigChanged {
+        keys: Vec<String>,
+    },
+    /// Asks the main window to confirm quitting.
+    ShowExitConfirm,
+    /// Shows a query from the quick search palette in the main window.
+    OpenSearch {
+        query: String,
+    },
+    Navigate(DeepLink),
+    Alert(Alert),
+    UpdateAvailable(UpdateInfo),
+    /// Views cover what they show; see [`crate::app_lock`].
+    AppLocked,
+    /// What changed while the user was away; see [`crate::welcome`].
+    WhatsNew(WhatsNew),
+}
+
+impl UiEvent {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::App(event) => event.name(),
+            Self::ConfigChanged { .. } => "noodle://config-changed",
+            Self::ShowExitConfirm => "noodle://show-exit-confirm",
+            Self::OpenSearch { .. } => "noodle://open-search",
+            Self::Navigate(_) => "noodle://navigate",
+            Self::Alert(_) => "noodle://alert",
+            Self::UpdateAvailable(_) => "noodle://update-available",
+            Self::AppLocked => "noodle://app-locked",
+            Self::WhatsNew(_) => "noodle://whats-new",
+        }
+    }
+
+    /// The window the event is for; `None` sends it to every window.
+    fn target(&self) -> Option<&'static str> {
+        match self {
+            Self::OpenSearch { .. } | Self::Navigate(_) | Self::Alert(_) | Self::WhatsNew(_) => {
+                Some("main")
+            }
+            _ => None,
+        }
+    }
+}
+
+impl From<AppEvent> for UiEvent {
+    fn from(event: AppEvent) -> Self {
+        Self::App(event)
+    }
+}
+
+/// Sends [`UiEvent`]s to the frontend. Backend crates get it as their
+/// [`Notifier`].
+#[derive(Clone)]
+pub struct EventBus {
+    app: AppHandle,
+}
+
+impl EventBus {
+    pub fn new(app: &AppHandle) -> Self {
+        Self { app: app.clone() }
+    }
+
+    pub fn publish(&self, event: impl Into<UiEvent>) {
+        let event = event.into();
+        let name = event.name();
+        let sent = match event.target() {
+            Some(window) => self.app.emit_to(window, name, &event),
+            None => self.app.emit(name, &event),
+        };
+        if let Err(e) = sent {
+            warn!("Failed to emit {}: {}", name, e);
+        }
+    }
+}
+
+impl Notifier for EventBus {
+    fn notify(&self, event: AppEvent) {
+        self.publish(event);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+    use noodle_core::events::{EmailProgress, ExperimentProgress, LogEntry, LogLevel};
+    use noodle_core::types::{AlertKind, ProcessStage};
+    use serde_json::json;
+
+    fn payload(event: impl Into<UiEvent>) -> (&'static str, serde_json::Value) {
+        let event = event.into();
+        (event.name(), serde_json::to_value(&event).unwrap())
+    }
+
+    #[test]
+    fn events_keep_their_names_and_payloads() {
+        assert_eq!(
+            payload(AppEvent::Log(LogEntry {
+                message: "Synced".into(),
+                level: LogLevel::Warn,
+            })),
+            (
+                "noodle://log",
+                json!({ "message": "Synced", "level": "warn" })
+            )
+        );
+        assert_eq!(
+            payload(AppEvent::EmailProgress(EmailProgress {
+                email_id: 7,
+                stage: ProcessStage::Embedded,
+            })),
+            (
+                "noodle://email-progress",
+                json!({ "email_id": 7, "stage": "embedded" })
+            )
+        );
+        assert_eq!(
+            payload(AppEvent::ExperimentProgress(ExperimentProgress {
+                name: "A vs B".into(),
+                done: 3,
+                total: 20,
+            })),
+            (
+                "noodle://experiment-progress",
+                json!({ "name": "A vs B", "done": 3, "total": 20 })
+            )
+        );
+        assert_eq!(
+            payload(UiEvent::ConfigChanged {
+                keys: vec!["theme".into()],
+            }),
+            ("noodle://config-changed", json!({ "keys": ["theme"] }))
+        );
+        assert_eq!(
+            payload(UiEvent::ShowExitConfirm),
+            ("noodle://show-exit-confirm", json!(null))
+        );
+        assert_eq!(
+            payload(UiEvent::OpenSearch {
+                query: "budget".into(),
+            }),
+            ("noodle://open-search", json!({ "query": "budget" }))
+        );
+        assert_eq!(
+            payload(UiEvent::Navigate(DeepLink::Email { id: 7 })),
+            ("noodle://navigate", json!({ "kind": "email", "id": 7 }))
+        );
+        assert_eq!(
+            payload(UiEvent::AppLocked),
+            ("noodle://app-locked", json!(null))
+        );
+    }
+
+    #[test]
+    fn the_frontend_listens_for_every_event_name() {
+        let frontend = include_str!("../../frontend/src/events.ts");
+        let names = [
+            noodle_core::events::LOG,
+            noodle_core::events::SYNC_STATE,
+            noodle_core::events::SCAN_PROGRESS,
+            noodle_core::events::EMAIL_PROGRESS,
+            noodle_core::events::BULK_PROGRESS,
+            noodle_core::events::CHAT_TOOL_CALL,
+            noodle_core::events::EXPERIMENT_PROGRESS,
+            UiEvent::ConfigChanged { keys: Vec::new() }.name(),
+            UiEvent::ShowExitConfirm.name(),
+            UiEvent::OpenSearch {
+                query: String::new(),
+            }
+            .name(),
+            UiEvent::Navigate(DeepLink::Email { id: 0 }).name(),
+            UiEvent::Alert(Alert {
+                id: 0,
+                kind: AlertKind::VipEmail,
+                key: String::new(),
+                message: String::new(),
+                email_id: None,
+                created_at: Utc::now(),
+                acknowledged: false,
+            })
+            .name(),
+            UiEvent::UpdateAvailable(UpdateInfo {
+                current_version: String::new(),
+                channel: String::new(),
+                releases: Vec::new(),
+                checked_at: Utc::now(),
+            })
+            .name(),
+            UiEvent::AppLocked.name(),
+            UiEvent::WhatsNew(WhatsNew {
+                since: Utc::now(),
+                urgent: Vec::new(),
+                blockers: Vec::new(),
+                overdue: Vec::new(),
+                replies: Vec::new(),
+            })
+            .name(),
+        ];
+        for name in names {
+            assert!(
+                frontend.contains(&format!("'{}'", name)),
+                "{} is missing from events.ts",
+                name
+            );
+        }
+    }
+}
@@ -1,323 +1,2187 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
+mod alerts;
+mod app_lock;
+mod companion;
+mod deep_link;
+mod diagnostics;
+mod digest;
+mod events;
+mod meetings;
+mod palette;
+mod quick_search;
+mod reader;
+mod tray;
+mod updates;
+mod welcome;
+
+use agent::archive::{self, ArchiveEntry};
+use agent::digest::roundup::RoundupService;
+use agent::digest::whats_new::WhatsNew;
+use agent::digest::{Digest, DigestService};
+use agent::engine::legal_hold::LegalHold;
+use agent::engine::maintenance::MaintenanceScheduler;
+use agent::engine::payload_sync::PayloadSyncWorker;
+use agent::engine::policy::ActivityPolicy;
+use agent::engine::shutdown::ShutdownCoordinator;
+use agent::engine::snapshots::VectorSnapshots;
 use agent::engine::SyncManager;
+use agent::graph::export::{self, GraphExport, GraphFormat};
+use agent::graph::OrgInference;
+use agent::integrations::TicketService;
+use agent::issues::IssueClustering;
+use agent::meetings::MeetingFollowUps;
+use agent::pipeline::attachments::AttachmentStore;
+use agent::pipeline::dictation::DictationDrafter;
+use agent::pipeline::experiments::ExperimentRunner;
 use agent::pipeline::ExtractionPipeline;
+use agent::prompts::PromptLibrary;
+use agent::report::{ProjectReport, ProjectReporter};
+use agent::search::chat::{ChatAssistant, ChatTurn};
+use agent::search::summarize::TopicSummarizer;
+use agent::search::SearchService;
+use agent::share::ChatFormatter;
+use agent::timeline::TimelineService;
+use ai::budget::ContextWindows;
+use ai::provider::creds::{CredentialStore, SavedCredential};
+use ai::provider::failover::FailoverProvider;
 use ai::provider::{AiProvider, OllamaProvider, OpenAICompatibleProvider};
+use events::{EventBus, UiEvent};
+use noodle_core::config::{
+    Config, SettingsBundle, SettingsProfile, SECRET_KEYS, SECRET_MASK, SETTINGS_BUNDLE_VERSION,
+};
+use noodle_core::events::{AppEvent, LogEntry, LogLevel};
+use noodle_core::metrics::StageMetrics;
+use noodle_core::time::localize_fields;
+use noodle_core::types::{
+    Alert, Annotation, AnnotationInput, Attachment, BulkResult, CategoryAuditEntry, CategoryMatch,
+    CategoryRule, ChatFormat, ChatMessage, ChatSession, ChatSource, CustomPrompt, DateRange,
+    ExperimentVariant, ExportProfile, ExtractionExperiment, GraphFilter, HoldVerification,
+    InferredRelation, IssueCluster, IssueKind, IssueTicket, MaintenanceReport, MeetingFollowUp,
+    NewsletterRoundup, NewsletterSender, ProjectIssueMetrics, ProjectSettings, PromptPack,
+    PromptPackImport, PromptPreview, QueueStatus, RelationStatus, RepairReport, ScanCheckpoint,
+    SchemaInfo, SearchHistoryEntry, SearchSuggestion, TicketTracker, TopicSummary, TriageState,
+    VectorRepairReport, VectorSnapshot, VectorStats, VipSuggestion,
+};
 use outlook::client::OutlookClient;
+use std::collections::{BTreeMap, HashMap};
 use std::sync::Arc;
 use storage::qdrant::QdrantStorage;
 use storage::sqlite::SqliteStorage;
-use tauri::{command, Emitter, Manager, State};
-use tokio::sync::RwLock;
+use tauri::{command, Manager, State};
+use tauri_plugin_autostart::{MacosLauncher, ManagerExt};
+use tokio::sync::{Mutex, RwLock};
 use tracing::{error, info};
 
 struct AppState {
     sqlite: Arc<SqliteStorage>,
-    qdrant: Arc<QdrantStorage>,
+    digest: Arc<DigestService>,
+    policy: Arc<ActivityPolicy>,
+    shutdown: Arc<ShutdownCoordinator>,
     ai: Arc<RwLock<Arc<dyn AiProvider>>>, // Wrap in RwLock for runtime updates
     pipeline: Arc<ExtractionPipeline>,
+    search: Arc<SearchService>,
+    issues: Arc<IssueClustering>,
+    snapshots: Arc<VectorSnapshots>,
+    legal_hold: Arc<LegalHold>,
     outlook: Arc<OutlookClient>,
+    lock: Arc<app_lock::AppLock>,
+    events: Arc<EventBus>,
+    /// The background sync loop, once started; it is never started twice.
+    sync: Mutex<Option<Arc<SyncManager>>>,
     app_handle: tauri::AppHandle,
 }
 
+/// Passed by the login item so startup can tell a login launch from a manual one.
+const AUTOSTART_ARG: &str = "--autostart";
+
+#[derive(serde::Serialize)]
+struct AutostartSettings {
+    enabled: bool,
+    start_minimized: bool,
+}
+
+/// Timestamp fields in email payloads that are shown to the user in their timezone.
+const EMAIL_TIME_FIELDS: &[&str] = &[
+    "received_at",
+    "due_by",
+    "responded_at",
+    "deleted_at",
+    "purge_at",
+];
+const TIMELINE_TIME_FIELDS: &[&str] = &["at", "due_by", "previous_due_by"];
+/// Searches kept in the history; older ones are dropped.
+const SEARCH_HISTORY_SIZE: i64 = 1000;
+/// Results returned by searches scoped to a thread or sender.
+const SCOPED_SEARCH_LIMIT: u64 = 50;
+
+async fn localize_emails(
+    state: &AppState,
+    mut emails: Vec<serde_json::Value>,
+) -> Result<Vec<serde_json::Value>, String> {
+    let tz = state
+        .sqlite
+        .get_user_timezone()
+        .await
+        .map_err(|e| e.to_string())?;
+    for email in &mut emails {
+        localize_fields(email, EMAIL_TIME_FIELDS, tz);
+        state.lock.redact(email);
+    }
+    Ok(emails)
+}
+
+#[command]
+async fn search_emails(
+    state: State<'_, AppState>,
+    query: String,
+) -> Result<Vec<serde_json::Value>, String> {
+    // If query is empty, return recent 50 emails
+    let emails = if query.trim().is_empty() {
+        state.sqlite.get_recent_emails(50).await
+    } else {
+        state.search.search(&query, 20).await
+    }
+    .map_err(|e| e.to_string())?;
+    if !query.trim().is_empty() {
+        if let Err(e) = state
+            .sqlite
+            .record_search(&query, emails.len() as i64, SEARCH_HISTORY_SIZE)
+            .await
+        {
+            error!("Failed to record search: {}", e);
+        }
+    }
+
+    localize_emails(&state, emails).await
+}
+
+/// Searches one conversation, e.g. from an email's context menu. Scoped
+/// searches are left out of the search history.
+#[command]
+async fn search_in_thread(
+    state: State<'_, AppState>,
+    conversation_id: String,
+    query: String,
+) -> Result<Vec<serde_json::Value>, String> {
+    let emails = state
+        .search
+        .search_in_thread(&conversation_id, &query, SCOPED_SEARCH_LIMIT)
+        .await
+        .map_err(|e| e.to_string())?;
+    localize_emails(&state, emails).await
+}
+
+/// Searches mail from one sender, e.g. from an email's context menu.
+#[command]
+async fn search_from_sender(
+    state: State<'_, AppState>,
+    sender: String,
+    query: String,
+) -> Result<Vec<serde_json::Value>, String> {
+    let emails = state
+        .search
+        .search_from_sender(&sender, &query, SCOPED_SEARCH_LIMIT)
+        .await
+        .map_err(|e| e.to_string())?;
+    localize_emails(&state, emails).await
+}
+
+/// Searches the text of stored attachments only.
+#[command]
+async fn search_attachments(
+    state: State<'_, AppState>,
+    query: String,
+) -> Result<Vec<serde_json::Value>, String> {
+    let emails = state
+        .search
+        .search_attachments(&query, 20)
+        .await
+        .map_err(|e| e.to_string())?;
+    localize_emails(&state, emails).await
+}
+
+#[command]
+async fn get_search_history(
+    state: State<'_, AppState>,
+    limit: Option<i64>,
+) -> Result<Vec<SearchHistoryEntry>, String> {
+    state
+        .sqlite
+        .get_search_history(limit.unwrap_or(50))
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Deletes the search history; returns how many searches were removed.
+#[command]
+async fn clear_search_history(state: State<'_, AppState>) -> Result<u64, String> {
+    state
+        .sqlite
+        .clear_search_history()
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Recent and frequent past queries starting with `prefix`.
+#[command]
+async fn get_search_suggestions(
+    state: State<'_, AppState>,
+    prefix: String,
+) -> Result<Vec<SearchSuggestion>, String> {
+    state
+        .sqlite
+        .get_search_suggestions(&prefix, 8)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Emails with a deadline on the user's current local calendar day.
+#[command]
+async fn get_due_today(state: State<'_, AppState>) -> Result<Vec<serde_json::Value>, String> {
+    let tz = state
+        .sqlite
+        .get_user_timezone()
+        .await
+        .map_err(|e| e.to_string())?;
+    let (start, end) = tz.day_bounds(chrono::Utc::now());
+    let emails = state
+        .sqlite
+        .get_emails_due_between(start, end)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    localize_emails(&state, emails).await
+}
+
+/// New urgent mail, blockers, deadlines passed and replies to the user's
+/// questions since `since`; by default since the user last left the main
+/// window.
+#[command]
+async fn get_whats_new(
+    state: State<'_, AppState>,
+    since: Option<chrono::DateTime<chrono::Utc>>,
+) -> Result<WhatsNew, String> {
+    let now = chrono::Utc::now();
+    let since = match since {
+        Some(since) => since,
+        None => welcome::last_seen(&state).await?.unwrap_or(now),
+    };
+    welcome::whats_new(&state, since, now).await
+}
+
+#[command]
+async fn get_digest(state: State<'_, AppState>) -> Result<Digest, String> {
+    state
+        .digest
+        .build(chrono::Utc::now())
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// An email summary, thread or today's digest as Slack blocks, a Teams
+/// Adaptive Card or markdown, to paste or post into a chat tool.
+#[command]
+async fn format_for_chat(
+    state: State<'_, AppState>,
+    source: ChatSource,
+    format: ChatFormat,
+) -> Result<String, String> {
+    let formatter = ChatFormatter::new(state.sqlite.clone());
+    let summary = match source {
+        ChatSource::Email { id } => formatter.email(id).await,
+        ChatSource::Thread { email_id } => formatter.thread(email_id).await,
+        ChatSource::Digest => {
+            let digest = state
+                .digest
+                .build(chrono::Utc::now())
+                .await
+                .map_err(|e| e.to_string())?;
+            formatter.digest(&digest).await
+        }
+    }
+    .map_err(|e| e.to_string())?;
+    Ok(summary.render(format))
+}
+
+#[command]
+async fn get_waiting_board(state: State<'_, AppState>) -> Result<serde_json::Value, String> {
+    let mut board = state
+        .sqlite
+        .get_waiting_board()
+        .await
+        .map_err(|e| e.to_string())?;
+    let tz = state
+        .sqlite
+        .get_user_timezone()
+        .await
+        .map_err(|e| e.to_string())?;
+    if let Some(lanes) = board.as_object_mut() {
+        for email in lanes
+            .values_mut()
+            .filter_map(|l| l.as_array_mut())
+            .flatten()
+        {
+            localize_fields(email, EMAIL_TIME_FIELDS, tz);
+            state.lock.redact(email);
+        }
+    }
+    Ok(board)
+}
+
+#[command]
+async fn get_graph(state: State<'_, AppState>) -> Result<serde_json::Value, String> {
+    state.sqlite.get_entities().await.map_err(|e| e.to_string())
+}
+
+/// The entity graph as GraphML, DOT or D3-style JSON, optionally limited to
+/// one project or one entity type. With a password the file comes back as
+/// an encrypted archive.
+#[command]
+async fn export_graph(
+    state: State<'_, AppState>,
+    format: GraphFormat,
+    project: Option<String>,
+    entity_type: Option<String>,
+    password: Option<String>,
+) -> Result<GraphExport, String> {
+    let filter = GraphFilter {
+        project: project.filter(|p| !p.is_empty()),
+        entity_type: entity_type.filter(|t| !t.is_empty()),
+    };
+    let graph = state
+        .sqlite
+        .get_graph(&filter)
+        .await
+        .map_err(|e| e.to_string())?;
+    let file = export::render(&graph, format);
+    match password.filter(|p| !p.is_empty()) {
+        Some(password) => file.encrypt(&password).await.map_err(|e| e.to_string()),
+        None => Ok(file),
+    }
+}
+
+/// A project health report for sharing, with what it leaves out set by
+/// `profile` and `redact` terms masked. Notes are left out while locked.
+/// With a password the file comes back as an encrypted archive.
+#[command]
+async fn export_project_report(
+    state: State<'_, AppState>,
+    project: String,
+    profile: ExportProfile,
+    redact: Vec<String>,
+    password: Option<String>,
+) -> Result<ProjectReport, String> {
+    let project = project.trim();
+    if project.is_empty() {
+        return Err("Project name is required".into());
+    }
+    // The internal report carries the email bodies.
+    if profile == ExportProfile::Internal && state.lock.is_locked() {
+        return Err("Unlock Noodle to export a report with email bodies".into());
+    }
+    let report = ProjectReporter::new(state.sqlite.clone())
+        .build(project, profile, &redact, !state.lock.is_locked())
+        .await
+        .map_err(|e| e.to_string())?;
+    match password.filter(|p| !p.is_empty()) {
+        Some(password) => report.encrypt(&password).await.map_err(|e| e.to_string()),
+        None => Ok(report),
+    }
+}
+
+/// The files inside an encrypted export, given as base64.
+#[command]
+async fn open_encrypted_export(
+    data: String,
+    password: String,
+) -> Result<Vec<ArchiveEntry>, String> {
+    archive::open_base64(&data, &password)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[command]
+async fn list_vip_senders(state: State<'_, AppState>) -> Result<Vec<String>, String> {
+    state
+        .sqlite
+        .list_vip_senders()
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[command]
+async fn add_vip_sender(state: State<'_, AppState>, sender: String) -> Result<(), String> {
+    let sender = sender.trim();
+    if sender.is_empty() {
+        return Err("Sender address is required".into());
+    }
+    state
+        .sqlite
+        .add_vip_sender(sender)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[command]
+async fn remove_vip_sender(state: State<'_, AppState>, sender: String) -> Result<bool, String> {
+    state
+        .sqlite
+        .remove_vip_sender(&sender)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Senders the user replied to at least 3 times over the last 90 days,
+/// offered as VIPs.
+#[command]
+async fn suggest_vip_senders(state: State<'_, AppState>) -> Result<Vec<VipSuggestion>, String> {
+    let since = chrono::Utc::now() - chrono::Duration::days(90);
+    state
+        .sqlite
+        .suggest_vip_senders(since, 3, 10)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Alerts raised from extracted facts, unacknowledged first.
+#[command]
+async fn list_alerts(state: State<'_, AppState>, limit: i64) -> Result<Vec<Alert>, String> {
+    state
+        .sqlite
+        .list_alerts(limit)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[command]
+async fn acknowledge_alert(state: State<'_, AppState>, id: i64) -> Result<bool, String> {
+    state
+        .sqlite
+        .acknowledge_alert(id)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[command]
+async fn list_category_rules(state: State<'_, AppState>) -> Result<Vec<CategoryRule>, String> {
+    state
+        .sqlite
+        .list_category_rules()
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Applies to emails extracted from now on.
+#[command]
+async fn add_category_rule(
+    state: State<'_, AppState>,
+    match_on: CategoryMatch,
+    pattern: String,
+    category: String,
+    color: i32,
+    write_back: bool,
+) -> Result<CategoryRule, String> {
+    state
+        .sqlite
+        .add_category_rule(match_on, &pattern, &category, color, write_back)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[command]
+async fn update_category_rule(
+    state: State<'_, AppState>,
+    rule: CategoryRule,
+) -> Result<bool, String> {
+    state
+        .sqlite
+        .update_category_rule(&rule)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[command]
+async fn remove_category_rule(state: State<'_, AppState>, id: i64) -> Result<bool, String> {
+    state
+        .sqlite
+        .remove_category_rule(id)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[command]
+async fn list_category_audit(
+    state: State<'_, AppState>,
+    limit: i64,
+) -> Result<Vec<CategoryAuditEntry>, String> {
+    state
+        .sqlite
+        .list_category_audit(limit)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Recomputes the relations inferred from who mails whom; returns how many.
+#[command]
+async fn infer_org_chart(state: State<'_, AppState>) -> Result<usize, String> {
+    OrgInference::new(state.sqlite.clone())
+        .run()
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[command]
+async fn list_inferred_relations(
+    state: State<'_, AppState>,
+) -> Result<Vec<InferredRelation>, String> {
+    state
+        .sqlite
+        .list_inferred_relations()
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Groups newly extracted risks, issues and blockers into canonical issues
+/// and updates their statuses; returns how many mentions were grouped.
+#[command]
+async fn cluster_issues(state: State<'_, AppState>) -> Result<usize, String> {
+    state.issues.run().await.map_err(|e| e.to_string())
+}
+
+/// Canonical issues, most recently raised first; `duplicates_only` leaves out
+/// issues raised in a single email.
+#[command]
+async fn list_issue_clusters(
+    state: State<'_, AppState>,
+    duplicates_only: bool,
+) -> Result<Vec<IssueCluster>, String> {
+    state
+        .sqlite
+        .list_issue_clusters(if duplicates_only { 2 } else { 1 })
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Open, mitigated and resolved issues per project, with how long they
+/// stay open.
+#[command]
+async fn get_issue_metrics(state: State<'_, AppState>) -> Result<Vec<ProjectIssueMetrics>, String> {
+    state
+        .sqlite
+        .get_issue_metrics()
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Files an email's risk, issue or blocker as a Jira issue or Azure DevOps
+/// work item; `project` overrides the configured default project.
+#[command]
+async fn create_ticket_from_issue(
+    state: State<'_, AppState>,
+    email_id: i64,
+    kind: IssueKind,
+    position: usize,
+    tracker: TicketTracker,
+    project: Option<String>,
+) -> Result<IssueTicket, String> {
+    TicketService::new(state.sqlite.clone())
+        .create_from_issue(email_id, kind, position, tracker, project.as_deref())
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[command]
+async fn list_issue_tickets(state: State<'_, AppState>) -> Result<Vec<IssueTicket>, String> {
+    state
+        .sqlite
+        .list_issue_tickets()
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Confirms or dismisses an inferred relation.
+#[command]
+async fn set_relation_status(
+    state: State<'_, AppState>,
+    id: i64,
+    status: RelationStatus,
+) -> Result<(), String> {
+    let updated = state
+        .sqlite
+        .set_relation_status(id, status)
+        .await
+        .map_err(|e| e.to_string())?;
+    if !updated {
+        return Err("Relation not found".into());
+    }
+    Ok(())
+}
+
+#[command]
+async fn get_stats(state: State<'_, AppState>) -> Result<serde_json::Value, String> {
+    state
+        .sqlite
+        .get_dashboard_stats()
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Reply latency per sender and project over the last `days` (default 90).
+#[command]
+async fn get_response_times(
+    state: State<'_, AppState>,
+    days: Option<i64>,
+) -> Result<serde_json::Value, String> {
+    let since = chrono::Utc::now() - chrono::Duration::days(days.unwrap_or(90));
+    state
+        .sqlite
+        .get_response_times(since)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Tone, message length, commitments and unanswered questions in the user's
+/// Sent Items over the last `days` (default 90). Off unless `self_insights` is on.
+#[command]
+async fn get_self_insights(
+    state: State<'_, AppState>,
+    days: Option<i64>,
+) -> Result<serde_json::Value, String> {
+    let config = state
+        .sqlite
+        .get_all_config()
+        .await
+        .map_err(|e| e.to_string())?;
+    if !config.self_insights {
+        return Err("Sent mail analysis is turned off in Settings".into());
+    }
+    let since = chrono::Utc::now() - chrono::Duration::days(days.unwrap_or(90));
+    state
+        .sqlite
+        .get_self_insights(since)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Mail volume per month, tone, open questions, the user's reply times and
+/// shared projects for one sender.
+#[command]
+async fn get_sender_profile(
+    state: State<'_, AppState>,
+    address: String,
+) -> Result<serde_json::Value, String> {
+    let tz = state
+        .sqlite
+        .get_user_timezone()
+        .await
+        .map_err(|e| e.to_string())?;
+    // As for the heatmap, one offset for every month.
+    let offset = tz.to_local(chrono::Utc::now()).offset().local_minus_utc();
+    state
+        .sqlite
+        .get_sender_profile(&address, offset)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Email volume by weekday × hour (in the user's timezone) and by sender domain.
+/// Defaults to the last 90 days.
+#[command]
+async fn get_volume_heatmap(
+    state: State<'_, AppState>,
+    start: Option<chrono::DateTime<chrono::Utc>>,
+    end: Option<chrono::DateTime<chrono::Utc>>,
+) -> Result<serde_json::Value, String> {
+    let end = end.unwrap_or_else(chrono::Utc::now);
+    let start = start.unwrap_or(end - chrono::Duration::days(90));
+    let tz = state
+        .sqlite
+        .get_user_timezone()
+        .await
+        .map_err(|e| e.to_string())?;
+    // A single offset for the whole range; buckets near a DST change may shift by an hour.
+    let offset = tz.to_local(end).offset().local_minus_utc();
+
+    state
+        .sqlite
+        .get_volume_heatmap(start, end, offset)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Checkpoints of an interrupted initial scan; empty when there is nothing to resume.
+#[command]
+async fn get_scan_state(state: State<'_, AppState>) -> Result<Vec<ScanCheckpoint>, String> {
+    state
+        .sqlite
+        .get_scan_checkpoints()
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// What the pipeline is busy with: pending and in-progress emails, failed
+/// embeddings awaiting retry and LLM worker usage.
+#[command]
+async fn get_queue_status(state: State<'_, AppState>) -> Result<QueueStatus, String> {
+    state
+        .pipeline
+        .queue_status()
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Skips a pending email or stops one in progress, by Outlook entry id.
+#[command]
+async fn cancel_queue_item(state: State<'_, AppState>, entry_id: String) -> Result<(), String> {
+    if !state.pipeline.queue().cancel(&entry_id) {
+        return Err("Email is no longer queued".into());
+    }
+    info!("Cancelled queued email {}", entry_id);
+    Ok(())
+}
+
+/// Gives up on embedding an email; it stays searchable by keyword only.
+#[command]
+async fn discard_vector_retry(state: State<'_, AppState>, email_id: i64) -> Result<(), String> {
+    state
+        .sqlite
+        .complete_vector_upsert(email_id)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[command]
+async fn run_maintenance(
+    state: State<'_, AppState>,
+    vacuum: Option<bool>,
+) -> Result<MaintenanceReport, String> {
+    let _work = state
+        .shutdown
+        .begin_work()
+        .ok_or_else(|| "Noodle is shutting down".to_string())?;
+    state
+        .sqlite
+        .run_maintenance(vacuum.unwrap_or(false))
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[command]
+async fn get_maintenance_status(
+    state: State<'_, AppState>,
+) -> Result<Option<MaintenanceReport>, String> {
+    state
+        .sqlite
+        .get_last_maintenance()
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[command]
+async fn get_schema_info(state: State<'_, AppState>) -> Result<SchemaInfo, String> {
+    state
+        .sqlite
+        .get_schema_info()
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[command]
+async fn repair_database(state: State<'_, AppState>) -> Result<RepairReport, String> {
+    let _work = state
+        .shutdown
+        .begin_work()
+        .ok_or_else(|| "Noodle is shutting down".to_string())?;
+    state
+        .sqlite
+        .repair_database()
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Compares the vector store with SQLite: counts, orphaned points, emails
+/// without vectors and embedding dimensions.
+#[command]
+async fn get_vector_stats(state: State<'_, AppState>) -> Result<VectorStats, String> {
+    state
+        .pipeline
+        .vector_stats()
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Deletes orphaned points and re-embeds emails missing a vector.
+#[command]
+async fn repair_vectors(state: State<'_, AppState>) -> Result<VectorRepairReport, String> {
+    state
+        .pipeline
+        .repair_vectors()
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Re-reads the legal hold archive and checks its hash chain.
+#[command]
+async fn verify_legal_hold(state: State<'_, AppState>) -> Result<HoldVerification, String> {
+    state.legal_hold.verify().await.map_err(|e| e.to_string())
+}
+
+/// Saved vector store snapshots, newest first.
+#[command]
+async fn list_vector_snapshots(state: State<'_, AppState>) -> Result<Vec<VectorSnapshot>, String> {
+    state.snapshots.list().map_err(|e| e.to_string())
+}
+
+#[command]
+async fn create_vector_snapshot(state: State<'_, AppState>) -> Result<VectorSnapshot, String> {
+    let _work = state
+        .shutdown
+        .begin_work()
+        .ok_or_else(|| "Noodle is shutting down".to_string())?;
+    state.snapshots.create().await.map_err(|e| e.to_string())
+}
+
+/// Replaces the vector store with a saved snapshot.
+#[command]
+async fn restore_vector_snapshot(state: State<'_, AppState>, name: String) -> Result<(), String> {
+    let _work = state
+        .shutdown
+        .begin_work()
+        .ok_or_else(|| "Noodle is shutting down".to_string())?;
+    state
+        .snapshots
+        .restore(&name)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[command]
+async fn start_sync(state: State<'_, AppState>) -> Result<(), String> {
+    info!("Manual sync requested");
+    state.events.publish(AppEvent::Log(LogEntry {
+        message: "Manual sync started".into(),
+        level: LogLevel::Info,
+    }));
+    sync_now(&state).await;
+    Ok(())
+}
+
+/// Stops the background sync loop after the email in progress, abandoning
+/// any Outlook fetch. Returns whether it was running.
+#[command]
+async fn stop_sync(state: State<'_, AppState>) -> Result<bool, String> {
+    let Some(sync) = state.sync.lock().await.take() else {
+        return Ok(false);
+    };
+    info!("Sync stop requested");
+    sync.stop();
+    // Emails it left in the shared queue are cleared as it returns, so a
+    // sync started after this doesn't lose its own.
+    sync.finished().await;
+    state.events.publish(AppEvent::Log(LogEntry {
+        message: "Sync stopped".into(),
+        level: LogLevel::Info,
+    }));
+    Ok(true)
+}
+
+/// Starts the background sync loop, or runs a delta scan right away when it
+/// is already running.
+async fn sync_now(state: &AppState) {
+    if !spawn_background_sync(state).await {
+        if let Some(sync) = state.sync.lock().await.as_ref() {
+            sync.sync_now();
+        }
+    }
+}
+
+/// Starts the background sync loop unless it is already running. Returns
+/// whether it was started by this call.
+async fn spawn_background_sync(state: &AppState) -> bool {
+    let mut sync = state.sync.lock().await;
+    if sync.is_some() {
+        info!("Background sync is already running");
+        return false;
+    }
+
+    let history_days = state
+        .sqlite
+        .get_config("history_days")
+        .await
+        .unwrap_or(None)
+        .and_then(|s| s.parse::<i64>().ok())
+        .unwrap_or(90);
+
+    let sync_interval = state
+        .sqlite
+        .get_config("sync_interval")
+        .await
+        .unwrap_or(None)
+        .and_then(|s| s.parse::<i64>().ok())
+        .unwrap_or(2);
+
+    let sync_manager = Arc::new(SyncManager::new(
+        state.pipeline.clone(),
+        state.outlook.clone(),
+        state.sqlite.clone(),
+        state.shutdown.clone(),
+        state.events.clone(),
+        history_days,
+        sync_interval,
+    ));
+    *sync = Some(sync_manager.clone());
+
+    tokio::spawn(async move {
+        sync_manager.start_background_sync().await;
+    });
+    true
+}
+
+#[command]
+async fn get_autostart(
+    app_handle: tauri::AppHandle,
+    state: State<'_, AppState>,
+) -> Result<AutostartSettings, String> {
+    let config = state
+        .sqlite
+        .get_all_config()
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(AutostartSettings {
+        enabled: app_handle
+            .autolaunch()
+            .is_enabled()
+            .map_err(|e| e.to_string())?,
+        start_minimized: config.start_minimized,
+    })
+}
+
+/// Registers or removes the login item. With `start_minimized`, a login launch
+/// stays in the tray and begins background sync without showing the window.
+#[command]
+async fn set_autostart(
+    app_handle: tauri::AppHandle,
+    state: State<'_, AppState>,
+    enabled: bool,
+    start_minimized: bool,
+) -> Result<(), String> {
+    let autolaunch = app_handle.autolaunch();
+    if enabled {
+        autolaunch.enable()
+    } else {
+        autolaunch.disable()
+    }
+    .map_err(|e| e.to_string())?;
+
+    update_config(
+        &state,
+        HashMap::from([("start_minimized".to_string(), start_minimized.to_string())]),
+    )
+    .await
+}
+
+/// Pauses background sync for `minutes`; it resumes on its own afterwards.
+/// `0` ends focus mode immediately. Returns when focus mode ends, if active.
+#[command]
+async fn set_focus_mode(
+    state: State<'_, AppState>,
+    minutes: u32,
+) -> Result<Option<chrono::DateTime<chrono::Utc>>, String> {
+    let until =
+        (minutes > 0).then(|| chrono::Utc::now() + chrono::Duration::minutes(minutes as i64));
+    state
+        .policy
+        .set_focus_until(until)
+        .await
+        .map_err(|e| e.to_string())?;
+    info!(
+        "Focus mode {}",
+        until.map_or("ended".to_string(), |u| format!("until {}", u))
+    );
+    Ok(until)
+}
+
+#[command]
+async fn get_logs(
+    state: State<'_, AppState>,
+    limit: i64,
+) -> Result<Vec<serde_json::Value>, String> {
+    state
+        .sqlite
+        .get_logs(limit)
+        .await
+        .map_err(|e: noodle_core::error::NoodleError| e.to_string())
+}
+
+/// One stored value. Secrets come back as [`SECRET_MASK`] when set.
+#[command]
+async fn get_config(state: State<'_, AppState>, key: String) -> Result<Option<String>, String> {
+    let value = state
+        .sqlite
+        .get_config(&key)
+        .await
+        .map_err(|e: noodle_core::error::NoodleError| e.to_string())?;
+    if Config::is_secret(&key) {
+        return Ok(value
+            .filter(|v| !v.is_empty())
+            .map(|_| SECRET_MASK.to_string()));
+    }
+    Ok(value)
+}
+
+#[command]
+async fn save_config(state: State<'_, AppState>, key: String, value: String) -> Result<(), String> {
+    update_config(&state, HashMap::from([(key, value)])).await
+}
+
+/// All settings, with secrets masked; see [`Config::masked`].
+#[command]
+async fn get_all_config(state: State<'_, AppState>) -> Result<Config, String> {
+    state
+        .sqlite
+        .get_all_config()
+        .await
+        .map(|config| config.masked())
+        .map_err(|e| e.to_string())
+}
+
+/// API keys kept in Windows Credential Manager, without the keys.
+#[command]
+fn list_saved_credentials() -> Result<Vec<SavedCredential>, String> {
+    CredentialStore::list_saved_credentials().map_err(|e| e.to_string())
+}
+
+/// Saves `provider`'s key in Credential Manager, where fallback providers
+/// read theirs from, and rebuilds the AI provider.
+#[command]
+async fn save_api_key(
+    state: State<'_, AppState>,
+    provider: String,
+    key: String,
+) -> Result<(), String> {
+    CredentialStore::save_api_key(&provider, key.trim()).map_err(|e| e.to_string())?;
+    let config = state
+        .sqlite
+        .get_all_config()
+        .await
+        .map_err(|e| e.to_string())?;
+    *state.ai.write().await = build_provider(&config);
+    Ok(())
+}
+
+/// Removes `provider`'s key from Credential Manager; false when none was saved.
+#[command]
+fn delete_api_key(provider: String) -> Result<bool, String> {
+    CredentialStore::delete_api_key(&provider).map_err(|e| e.to_string())
+}
+
+/// Validates and saves several settings at once; nothing is written if any
+/// value is rejected.
+#[command]
+async fn set_config_bulk(
+    state: State<'_, AppState>,
+    values: HashMap<String, String>,
+) -> Result<(), String> {
+    update_config(&state, values).await
+}
+
+/// Current settings as a portable bundle; secrets such as the API key are left out.
+#[command]
+async fn export_settings(state: State<'_, AppState>) -> Result<SettingsBundle, String> {
+    let config = state
+        .sqlite
+        .get_all_config()
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(SettingsBundle {
+        version: SETTINGS_BUNDLE_VERSION,
+        exported_at: chrono::Utc::now(),
+        settings: config.to_portable_entries(),
+    })
+}
+
+/// Applies an exported bundle. Returns the keys that were skipped because this
+/// version doesn't know them or they are secrets.
+#[command]
+async fn import_settings(
+    state: State<'_, AppState>,
+    bundle: SettingsBundle,
+) -> Result<Vec<String>, String> {
+    if bundle.version > SETTINGS_BUNDLE_VERSION {
+        return Err(format!(
+            "Settings file is from a newer version of Noodle (format {})",
+            bundle.version
+        ));
+    }
+    apply_portable_settings(&state, bundle.settings).await
+}
+
+#[command]
+async fn list_settings_profiles(
+    state: State<'_, AppState>,
+) -> Result<Vec<SettingsProfile>, String> {
+    state
+        .sqlite
+        .list_settings_profiles()
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Saves the current settings under `name`, replacing a profile of that name.
+#[command]
+async fn save_settings_profile(state: State<'_, AppState>, name: String) -> Result<(), String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err("Profile name is required".into());
+    }
+    let config = state
+        .sqlite
+        .get_all_config()
+        .await
+        .map_err(|e| e.to_string())?;
+    state
+        .sqlite
+        .save_settings_profile(&SettingsProfile {
+            name: name.to_string(),
+            settings: config.to_portable_entries(),
+            updated_at: chrono::Utc::now(),
+        })
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[command]
+async fn apply_settings_profile(
+    state: State<'_, AppState>,
+    name: String,
+) -> Result<Vec<String>, String> {
+    let profile = state
+        .sqlite
+        .get_settings_profile(&name)
+        .await
+        .map_err(|e| e.to_string())?
+        .ok_or("Profile not found")?;
+    let skipped = apply_portable_settings(&state, profile.settings).await?;
+    info!("Applied settings profile '{}'", profile.name);
+    Ok(skipped)
+}
+
+#[command]
+async fn delete_settings_profile(state: State<'_, AppState>, name: String) -> Result<(), String> {
+    let deleted = state
+        .sqlite
+        .delete_settings_profile(&name)
+        .await
+        .map_err(|e| e.to_string())?;
+    if !deleted {
+        return Err("Profile not found".into());
+    }
+    Ok(())
+}
+
+async fn apply_portable_settings(
+    state: &AppState,
+    settings: BTreeMap<String, String>,
+) -> Result<Vec<String>, String> {
+    let (apply, skipped): (HashMap<_, _>, HashMap<_, _>) = settings
+        .into_iter()
+        .partition(|(key, _)| Config::is_setting(key) && !SECRET_KEYS.contains(&key.as_str()));
+    update_config(state, apply).await?;
+    let mut skipped: Vec<String> = skipped.into_keys().collect();
+    skipped.sort();
+    Ok(skipped)
+}
+
+/// Keys whose change requires rebuilding the AI provider.
+const AI_CONFIG_KEYS: &[&str] = &[
+    "provider_type",
+    "ollama_url",
+    "lemonade_url",
+    "foundry_url",
+    "openai_url",
+    "model_name",
+    "api_key",
+    "context_window",
+    "model_context_windows",
+    "fallback_providers",
+    "provider_timeout_secs",
+];
+
+/// The configured AI provider, followed by its fallbacks.
+fn build_provider(config: &Config) -> Arc<dyn AiProvider> {
+    let windows = context_windows(config);
+    let mut chain = FailoverProvider::new(
+        config.provider_type.clone(),
+        single_provider(
+            config,
+            &config.provider_type,
+            config.model_name.clone(),
+            config.api_key.clone(),
+            &windows,
+        ),
+        std::time::Duration::from_secs(config.provider_timeout_secs as u64),
+    );
+    for (provider_type, model) in config.fallback_provider_list() {
+        if provider_type == config.provider_type {
+            continue;
+        }
+        let model = model.or_else(|| config.model_name.clone());
+        // Fallbacks are usually another vendor, so `api_key` is not theirs.
+        let api_key = CredentialStore::get_api_key(&provider_type).unwrap_or_else(|e| {
+            error!("Failed to read the {} API key: {}", provider_type, e);
+            None
+        });
+        let provider = single_provider(config, &provider_type, model, api_key, &windows);
+        chain = chain.with_fallback(provider_type, provider);
+    }
+    Arc::new(chain)
+}
+
+fn single_provider(
+    config: &Config,
+    provider_type: &str,
+    model: Option<String>,
+    api_key: Option<String>,
+    windows: &ContextWindows,
+) -> Arc<dyn AiProvider> {
+    let url = match provider_type {
+        "ollama" => {
+            return Arc::new(
+                OllamaProvider::new(config.ollama_url.clone(), model)
+                    .with_context_windows(windows.clone()),
+            )
+        }
+        "lemonade" => config.lemonade_url.clone(),
+        "foundry" => config.foundry_url.clone(),
+        _ => config.openai_url.clone(),
+    };
+    // Lemonade, Foundry, and OpenAI all use OpenAI-compatible API
+    Arc::new(
+        OpenAICompatibleProvider::new(url, api_key, model).with_context_windows(windows.clone()),
+    )
+}
+
+/// The context windows configured for the AI provider.
+fn context_windows(config: &Config) -> ContextWindows {
+    ContextWindows::parse(
+        config.context_window as usize,
+        &config.model_context_windows,
+    )
+    .unwrap_or_else(|e| {
+        error!("Ignoring invalid model context windows: {}", e);
+        ContextWindows::new(config.context_window as usize)
+    })
+}
+
+/// Applies `values` on top of the stored config, validates the result, writes
+/// the keys that changed and emits `noodle://config-changed` with their names.
+async fn update_config(
+    state: &AppState,
+    mut values: HashMap<String, String>,
+) -> Result<(), String> {
+    // The UI sends back the mask it was shown for secrets left untouched.
+    values.retain(|key, value| !(Config::is_secret(key) && value == SECRET_MASK));
+    // Otherwise turning the lock off would reveal what it hides.
+    if state.lock.is_locked() && values.keys().any(|key| key.starts_with("app_lock")) {
+        return Err("Unlock Noodle to change the app lock".into());
+    }
+    let current = state
+        .sqlite
+        .get_all_config()
+        .await
+        .map_err(|e| e.to_string())?;
+    let mut config = current.clone();
+    for (key, value) in &values {
+        config.apply_entry(key, value).map_err(|e| e.to_string())?;
+    }
+    config.check().map_err(|e| e.to_string())?;
+    if !config.quick_search_shortcut.trim().is_empty() {
+        quick_search::parse_shortcut(config.quick_search_shortcut.trim())?;
+    }
+
+    let before: HashMap<String, String> = current.to_entries().into_iter().collect();
+    let changed: Vec<(String, String)> = config
+        .to_entries()
+        .into_iter()
+        .filter(|(key, value)| values.contains_key(key) && before.get(key) != Some(value))
+        .collect();
+    if changed.is_empty() {
+        return Ok(());
+    }
+    state
+        .sqlite
+        .set_config_bulk(&changed)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let keys: Vec<&str> = changed.iter().map(|(key, _)| key.as_str()).collect();
+    state.events.publish(UiEvent::ConfigChanged {
+        keys: keys.iter().map(|key| key.to_string()).collect(),
+    });
+
+    if keys.contains(&"quick_search_shortcut") {
+        quick_search::register_shortcut(&state.app_handle, &config.quick_search_shortcut);
+    }
+
+    if keys.iter().any(|key| AI_CONFIG_KEYS.contains(key)) {
+        let new_provider = build_provider(&config);
+        let mut ai_lock = state.ai.write().await;
+        *ai_lock = new_provider;
+        info!("Re-initialized AI provider: {}", config.provider_type);
+    }
+    Ok(())
+}
+
+/// A project's history between `start` and `end` (both optional), oldest first.
+#[command]
+async fn get_project_timeline(
+    state: State<'_, AppState>,
+    project: String,
+    start: Option<chrono::DateTime<chrono::Utc>>,
+    end: Option<chrono::DateTime<chrono::Utc>>,
+) -> Result<Vec<serde_json::Value>, String> {
+    let events = TimelineService::new(state.sqlite.clone())
+        .build(&project, &DateRange { start, end })
+        .await
+        .map_err(|e| e.to_string())?;
+    let tz = state
+        .sqlite
+        .get_user_timezone()
+        .await
+        .map_err(|e| e.to_string())?;
+    events
+        .iter()
+        .map(|event| {
+            let mut value = serde_json::to_value(event).map_err(|e| e.to_string())?;
+            localize_fields(&mut value, TIMELINE_TIME_FIELDS, tz);
+            Ok(value)
+        })
+        .collect()
+}
+
+#[command]
+async fn get_project_settings(
+    state: State<'_, AppState>,
+    project: String,
+) -> Result<ProjectSettings, String> {
+    state
+        .sqlite
+        .get_project_settings(&project)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[command]
+async fn set_project_settings(
+    state: State<'_, AppState>,
+    settings: ProjectSettings,
+) -> Result<(), String> {
+    if settings.name.trim().is_empty() {
+        return Err("Project name is required".into());
+    }
+    if settings.retention_days.is_some_and(|d| d <= 0) {
+        return Err("Retention override must be a positive number of days".into());
+    }
+    state
+        .sqlite
+        .set_project_settings(&settings)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[command]
+async fn get_models(state: State<'_, AppState>) -> Result<Vec<String>, String> {
+    let ai = state.ai.read().await;
+    ai.list_models().await.map_err(|e| e.to_string())
+}
+
+#[command]
+async fn save_log_cmd(
+    state: State<'_, AppState>,
+    level: String,
+    source: String,
+    message: String,
+) -> Result<(), String> {
+    state
+        .sqlite
+        .save_log(&level, &source, &message, None)
+        .await
+        .map_err(|e: noodle_core::error::NoodleError| e.to_string())
+}
+
+/// Opens an email in its own window, next to the main one.
+#[command]
+async fn open_email_window(state: State<'_, AppState>, id: i64) -> Result<(), String> {
+    let email = state
+        .sqlite
+        .get_email_detail(id)
+        .await
+        .map_err(|e| e.to_string())?
+        .ok_or("Email not found")?;
+    reader::open(&state.app_handle, id, &email.subject)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Shows the original message in Outlook.
+#[command]
+async fn open_in_outlook(state: State<'_, AppState>, email_id: i64) -> Result<(), String> {
+    let email = state
+        .sqlite
+        .get_email(email_id)
+        .await
+        .map_err(|e| e.to_string())?
+        .ok_or("Email not found")?;
+    state
+        .outlook
+        .display(&email.entry_id, &email.store_id)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// An email's attachments, including those the attachment policy left out.
+#[command]
+async fn list_attachments(
+    state: State<'_, AppState>,
+    email_id: i64,
+) -> Result<Vec<Attachment>, String> {
+    let mut attachments = state
+        .sqlite
+        .list_attachments(email_id)
+        .await
+        .map_err(|e| e.to_string())?;
+    for attachment in &mut attachments {
+        state.lock.redact_attachment(attachment);
+    }
+    Ok(attachments)
+}
+
+#[command]
+async fn get_email(state: State<'_, AppState>, id: i64) -> Result<serde_json::Value, String> {
+    let email = state
+        .sqlite
+        .get_email_detail(id)
+        .await
+        .map_err(|e| e.to_string())?
+        .ok_or("Email not found")?;
+
+    let changes = state
+        .sqlite
+        .list_email_changes(id)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let mut email = serde_json::to_value(email).map_err(|e| e.to_string())?;
+    let tz = state
+        .sqlite
+        .get_user_timezone()
+        .await
+        .map_err(|e| e.to_string())?;
+    localize_fields(&mut email, EMAIL_TIME_FIELDS, tz);
+    state.lock.redact(&mut email);
+    let mut changes = serde_json::to_value(changes).map_err(|e| e.to_string())?;
+    if let Some(changes) = changes.as_array_mut() {
+        for change in changes {
+            localize_fields(change, &["detected_at"], tz);
+            state.lock.redact(change);
+        }
+    }
+    email["changes"] = changes;
+    Ok(email)
+}
+
+/// Reruns extraction and embedding for one email and returns it refreshed.
+#[command]
+async fn reprocess_email(state: State<'_, AppState>, id: i64) -> Result<serde_json::Value, String> {
+    state
+        .pipeline
+        .reprocess_email(id)
+        .await
+        .map_err(|e| e.to_string())?;
+    refreshed_email(&state, id).await
+}
+
+/// Regenerates one email's vector and returns it refreshed.
+#[command]
+async fn reembed_email(state: State<'_, AppState>, id: i64) -> Result<serde_json::Value, String> {
+    state
+        .pipeline
+        .reembed_email(id)
+        .await
+        .map_err(|e| e.to_string())?;
+    refreshed_email(&state, id).await
+}
+
+/// Sets the triage state of the selected emails at once.
+#[command]
+async fn bulk_set_triage(
+    state: State<'_, AppState>,
+    ids: Vec<i64>,
+    triage: TriageState,
+) -> Result<BulkResult, String> {
+    state
+        .pipeline
+        .bulk_set_triage(&ids, triage, &*state.events)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Files the selected emails under another project at once.
+#[command]
+async fn bulk_reassign_project(
+    state: State<'_, AppState>,
+    ids: Vec<i64>,
+    project: String,
+) -> Result<BulkResult, String> {
+    state
+        .pipeline
+        .bulk_reassign_project(&ids, &project, &*state.events)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Reruns extraction and embedding for the selected emails, reporting
+/// progress as `noodle://bulk-progress` events.
+#[command]
+async fn bulk_reprocess(state: State<'_, AppState>, ids: Vec<i64>) -> Result<BulkResult, String> {
+    state
+        .pipeline
+        .bulk_reprocess(&ids, &*state.events)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Moves the selected emails to the trash, from where `restore_email` can
+/// bring them back until they are purged. Outlook keeps them.
+#[command]
+async fn bulk_trash(state: State<'_, AppState>, ids: Vec<i64>) -> Result<BulkResult, String> {
+    state
+        .pipeline
+        .bulk_trash(&ids, &*state.events)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Emails in the trash, most recently deleted first.
+#[command]
+async fn list_trash(state: State<'_, AppState>) -> Result<Vec<serde_json::Value>, String> {
+    let emails = state.sqlite.list_trash().await.map_err(|e| e.to_string())?;
+    localize_emails(&state, emails).await
+}
+
+/// Takes an email out of the trash and returns it refreshed.
+#[command]
+async fn restore_email(state: State<'_, AppState>, id: i64) -> Result<serde_json::Value, String> {
+    let restored = state
+        .sqlite
+        .restore_email(id)
+        .await
+        .map_err(|e| e.to_string())?;
+    if !restored {
+        return Err("Email is not in the trash".into());
+    }
+    refreshed_email(&state, id).await
+}
+
+/// Deletes the selected emails from Noodle for good. Outlook keeps them.
+#[command]
+async fn bulk_purge(state: State<'_, AppState>, ids: Vec<i64>) -> Result<BulkResult, String> {
+    state
+        .pipeline
+        .bulk_purge(&ids, &*state.events)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Notes, tags, pins and stars on emails and projects.
+#[command]
+async fn list_annotations(state: State<'_, AppState>) -> Result<Vec<Annotation>, String> {
+    let mut annotations = state
+        .sqlite
+        .list_annotations()
+        .await
+        .map_err(|e| e.to_string())?;
+    for annotation in &mut annotations {
+        state.lock.redact_annotation(annotation);
+    }
+    Ok(annotations)
+}
+
+/// Replaces the annotation of an email or project. Returns `None` when
+/// everything was cleared and the annotation removed. Refused while locked,
+/// when the note being replaced can't be seen.
+#[command]
+async fn save_annotation(
+    state: State<'_, AppState>,
+    annotation: AnnotationInput,
+) -> Result<Option<Annotation>, String> {
+    if state.lock.is_locked() {
+        return Err("Unlock Noodle to edit notes".into());
+    }
+    let saved = state
+        .sqlite
+        .save_annotation(&annotation)
+        .await
+        .map_err(|e| e.to_string())?;
+    state.pipeline.request_payload_sync();
+    Ok(saved)
+}
+
+#[command]
+async fn delete_annotation(state: State<'_, AppState>, id: i64) -> Result<bool, String> {
+    let deleted = state
+        .sqlite
+        .delete_annotation(id)
+        .await
+        .map_err(|e| e.to_string())?;
+    state.pipeline.request_payload_sync();
+    Ok(deleted)
+}
+
+/// Tags in use with their counts, for filtering and autocomplete.
+#[command]
+async fn list_annotation_tags(
+    state: State<'_, AppState>,
+) -> Result<Vec<serde_json::Value>, String> {
+    state
+        .sqlite
+        .list_annotation_tags()
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// `id` in the shape `search_emails` returns, so the UI can swap it in place.
+async fn refreshed_email(state: &AppState, id: i64) -> Result<serde_json::Value, String> {
+    let email = state
+        .sqlite
+        .get_email_with_facts(id)
+        .await
+        .map_err(|e| e.to_string())?
+        .ok_or("Email not found")?;
+    let mut emails = localize_emails(state, vec![email]).await?;
+    Ok(emails.remove(0))
+}
+
+/// Summarizes what the mail between `start` and `end` says about `query`,
+/// citing the emails it draws on. Defaults to the last 90 days.
+#[command]
+async fn summarize_topic(
+    state: State<'_, AppState>,
+    query: String,
+    start: Option<chrono::DateTime<chrono::Utc>>,
+    end: Option<chrono::DateTime<chrono::Utc>>,
+) -> Result<TopicSummary, String> {
+    let end = end.unwrap_or_else(chrono::Utc::now);
+    let start = start.unwrap_or(end - chrono::Duration::days(90));
+    TopicSummarizer::new(
+        state.sqlite.clone(),
+        state.search.clone(),
+        state.ai.clone(),
+        state.pipeline.pacing(),
+    )
+    .summarize(
+        &query,
+        DateRange {
+            start: Some(start),
+            end: Some(end),
+        },
+    )
+    .await
+    .map_err(|e| e.to_string())
+}
+
+/// Builds the newsletter roundup now instead of waiting for the week to
+/// end. `None` when no newsletters arrived since the last one.
+#[command]
+async fn run_newsletter_roundup(
+    state: State<'_, AppState>,
+) -> Result<Option<NewsletterRoundup>, String> {
+    RoundupService::new(
+        state.sqlite.clone(),
+        state.ai.clone(),
+        state.pipeline.pacing(),
+    )
+    .run(chrono::Utc::now())
+    .await
+    .map_err(|e| e.to_string())
+}
+
+#[command]
+async fn list_newsletter_roundups(
+    state: State<'_, AppState>,
+) -> Result<Vec<NewsletterRoundup>, String> {
+    state
+        .sqlite
+        .list_newsletter_roundups(20)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[command]
+async fn list_newsletter_senders(
+    state: State<'_, AppState>,
+) -> Result<Vec<NewsletterSender>, String> {
+    state
+        .sqlite
+        .list_newsletter_senders()
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[command]
+async fn list_meeting_follow_ups(
+    state: State<'_, AppState>,
+    days: Option<i64>,
+) -> Result<Vec<MeetingFollowUp>, String> {
+    let since = chrono::Utc::now() - chrono::Duration::days(days.unwrap_or(14));
+    state
+        .sqlite
+        .list_meeting_follow_ups(since, 50)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Extracts a meeting's follow-ups again from the mail around it, e.g. after
+/// more arrived. Returns false when none is related to it.
+#[command]
+async fn follow_up_meeting(state: State<'_, AppState>, id: i64) -> Result<bool, String> {
+    let meeting = state
+        .sqlite
+        .get_meeting(id)
+        .await
+        .map_err(|e| e.to_string())?
+        .ok_or("Meeting not found")?;
+    MeetingFollowUps::new(
+        state.sqlite.clone(),
+        state.outlook.clone(),
+        state.ai.clone(),
+        state.pipeline.pacing(),
+    )
+    .follow_up(&meeting, chrono::Utc::now())
+    .await
+    .map_err(|e| e.to_string())
+}
+
+#[command]
+async fn set_meeting_task_done(
+    state: State<'_, AppState>,
+    id: i64,
+    done: bool,
+) -> Result<(), String> {
+    let updated = state
+        .sqlite
+        .set_meeting_task_done(id, done)
+        .await
+        .map_err(|e| e.to_string())?;
+    if !updated {
+        return Err("Task not found".into());
+    }
+    Ok(())
+}
+
+/// Answers a question about the user's mail, citing emails. Pass the
+/// `session_id` of an earlier answer to ask a follow-up; without one a new
+/// session is started. With `research` the answer is looked up with tools,
+/// each call announced as a `noodle://chat-tool-call` event.
+#[command]
+async fn ask_noodle(
+    state: State<'_, AppState>,
+    session_id: Option<i64>,
+    question: String,
+    research: Option<bool>,
+) -> Result<ChatTurn, String> {
+    ChatAssistant::new(state.sqlite.clone(), state.search.clone(), state.ai.clone())
+        .ask(
+            session_id,
+            &question,
+            research.unwrap_or(false),
+            &*state.events,
+        )
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[command]
+async fn list_chat_sessions(state: State<'_, AppState>) -> Result<Vec<ChatSession>, String> {
+    state
+        .sqlite
+        .list_chat_sessions(50)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// A session's questions and answers, oldest first.
 #[command]
-async fn search_emails(
-    state: State<'_, AppState>,
-    query: String,
-) -> Result<Vec<serde_json::Value>, String> {
-    // If query is empty, return recent 50 emails
-    if query.trim().is_empty() {
-        return state
-            .sqlite
-            .get_recent_emails(50)
-            .await
-            .map_err(|e| e.to_string());
-    }
-
-    // 1. Generate embedding for query
-    // 1. Generate embedding for query
-    let ai = state.ai.read().await;
-    let embedding = ai
-        .generate_embedding(&query)
+async fn get_chat_session(state: State<'_, AppState>, id: i64) -> Result<Vec<ChatMessage>, String> {
+    state
+        .sqlite
+        .get_chat_messages(id)
         .await
-        .map_err(|e| e.to_string())?;
+        .map_err(|e| e.to_string())
+}
 
-    // 2. Vector Search in Qdrant
-    let results = state
-        .qdrant
-        .search_emails(embedding, None, 20)
+#[command]
+async fn delete_chat_session(state: State<'_, AppState>, id: i64) -> Result<(), String> {
+    let deleted = state
+        .sqlite
+        .delete_chat_session(id)
         .await
         .map_err(|e| e.to_string())?;
+    if !deleted {
+        return Err("Chat session not found".into());
+    }
+    Ok(())
+}
 
-    // 3. Fetch full email data from SQLite using internal IDs
-    let ids: Vec<i64> = results
-        .into_iter()
-        .filter_map(|r| {
-            r.id.and_then(|id| id.point_id_options)
-                .and_then(|id| match id {
-                    qdrant_client::qdrant::point_id::PointIdOptions::Num(num) => Some(num as i64),
-                    _ => None,
-                })
-        })
-        .collect();
-
+#[command]
+async fn list_topic_summaries(state: State<'_, AppState>) -> Result<Vec<TopicSummary>, String> {
     state
         .sqlite
-        .get_emails_by_ids(ids)
+        .list_topic_summaries(50)
         .await
         .map_err(|e| e.to_string())
 }
 
 #[command]
-async fn get_graph(state: State<'_, AppState>) -> Result<serde_json::Value, String> {
-    state.sqlite.get_entities().await.map_err(|e| e.to_string())
+async fn delete_topic_summary(state: State<'_, AppState>, id: i64) -> Result<(), String> {
+    let deleted = state
+        .sqlite
+        .delete_topic_summary(id)
+        .await
+        .map_err(|e| e.to_string())?;
+    if !deleted {
+        return Err("Summary not found".into());
+    }
+    Ok(())
 }
 
 #[command]
-async fn get_stats(state: State<'_, AppState>) -> Result<serde_json::Value, String> {
-    state
+async fn list_prompts(state: State<'_, AppState>) -> Result<Vec<CustomPrompt>, String> {
+    state.sqlite.list_prompts().await.map_err(|e| e.to_string())
+}
+
+/// Creates a prompt, or updates it in place when `prompt.id` is set.
+#[command]
+async fn save_prompt(
+    state: State<'_, AppState>,
+    prompt: serde_json::Value,
+) -> Result<String, String> {
+    let name = prompt["name"].as_str().unwrap_or("Untitled");
+    let content = prompt["content"].as_str().unwrap_or("");
+    match prompt["id"].as_str() {
+        Some(id) => {
+            let updated = state
+                .sqlite
+                .update_prompt(id, name, content)
+                .await
+                .map_err(|e| e.to_string())?;
+            if !updated {
+                return Err("Prompt not found".into());
+            }
+            Ok(id.to_string())
+        }
+        None => state
+            .sqlite
+            .create_prompt(name, content)
+            .await
+            .map_err(|e| e.to_string()),
+    }
+}
+
+#[command]
+async fn delete_prompt(state: State<'_, AppState>, id: String) -> Result<(), String> {
+    let deleted = state
         .sqlite
-        .get_dashboard_stats()
+        .delete_prompt(&id)
+        .await
+        .map_err(|e| e.to_string())?;
+    if !deleted {
+        return Err("Prompt not found".into());
+    }
+    Ok(())
+}
+
+/// The prompts `ids`, or every prompt when empty, as a pack to share.
+#[command]
+async fn export_prompt_pack(
+    state: State<'_, AppState>,
+    ids: Vec<String>,
+    name: String,
+    description: Option<String>,
+) -> Result<PromptPack, String> {
+    PromptLibrary::new(state.sqlite.clone(), state.ai.clone())
+        .export_pack(&ids, &name, description)
         .await
         .map_err(|e| e.to_string())
 }
 
+/// Adds a pack's prompts, updating those imported from it before. Nothing
+/// is imported when any prompt is invalid.
 #[command]
-async fn start_sync(state: State<'_, AppState>) -> Result<(), String> {
-    info!("Manual sync requested");
-    let app_handle = state.app_handle.clone();
-    let _ = app_handle.emit(
-        "noodle://log",
-        serde_json::json!({
-            "message": "Manual sync started",
-            "level": "info"
-        }),
+async fn import_prompt_pack(
+    state: State<'_, AppState>,
+    pack: serde_json::Value,
+) -> Result<PromptPackImport, String> {
+    let result = PromptLibrary::new(state.sqlite.clone(), state.ai.clone())
+        .import_pack(pack)
+        .await
+        .map_err(|e| e.to_string())?;
+    info!(
+        "Imported prompt pack '{}': {} new, {} updated",
+        result.pack, result.created, result.updated
     );
+    Ok(result)
+}
 
-    let history_days = state
-        .sqlite
-        .get_config("history_days")
+/// The values a prompt's variables take, given `values` for some of them.
+#[command]
+async fn resolve_prompt_variables(
+    state: State<'_, AppState>,
+    id: String,
+    values: Option<HashMap<String, String>>,
+) -> Result<BTreeMap<String, String>, String> {
+    PromptLibrary::new(state.sqlite.clone(), state.ai.clone())
+        .resolve_variables(&id, &values.unwrap_or_default())
         .await
-        .unwrap_or(None)
-        .and_then(|s| s.parse::<i64>().ok())
-        .unwrap_or(90);
+        .map_err(|e| e.to_string())
+}
 
-    let sync_interval = state
-        .sqlite
-        .get_config("sync_interval")
+/// Runs a prompt on `email_id`, the newest mail, or pasted `sample` text,
+/// and checks the output against the prompt's schema.
+#[command]
+async fn preview_prompt(
+    state: State<'_, AppState>,
+    id: String,
+    values: Option<HashMap<String, String>>,
+    email_id: Option<i64>,
+    sample: Option<String>,
+) -> Result<PromptPreview, String> {
+    PromptLibrary::new(state.sqlite.clone(), state.ai.clone())
+        .preview(&id, &values.unwrap_or_default(), email_id, sample)
         .await
-        .unwrap_or(None)
-        .and_then(|s| s.parse::<i64>().ok())
-        .unwrap_or(2);
+        .map_err(|e| e.to_string())
+}
 
-    let sync_manager = Arc::new(SyncManager::new(
-        state.pipeline.clone(),
-        state.outlook.clone(),
+/// Runs two extraction variants over `email_ids`, or a random sample of
+/// extracted emails, and stores the comparison. Reports progress as
+/// `noodle://experiment-progress` events.
+#[command]
+async fn run_extraction_experiment(
+    state: State<'_, AppState>,
+    name: String,
+    variant_a: ExperimentVariant,
+    variant_b: ExperimentVariant,
+    email_ids: Option<Vec<i64>>,
+    sample_size: Option<usize>,
+) -> Result<ExtractionExperiment, String> {
+    let _work = state
+        .shutdown
+        .begin_work()
+        .ok_or_else(|| "Noodle is shutting down".to_string())?;
+    ExperimentRunner::new(
         state.sqlite.clone(),
-        state.app_handle.clone(),
-        history_days,
-        sync_interval,
-    ));
-
-    tokio::spawn(async move {
-        sync_manager.start_background_sync().await;
-    });
-
-    Ok(())
+        state.ai.clone(),
+        state.pipeline.clone(),
+    )
+    .run(
+        &name,
+        variant_a,
+        variant_b,
+        email_ids,
+        sample_size,
+        &*state.events,
+    )
+    .await
+    .map_err(|e| e.to_string())
 }
 
+/// Past experiments, newest first, without their per-email results.
 #[command]
-async fn get_logs(
+async fn list_extraction_experiments(
     state: State<'_, AppState>,
-    limit: i64,
-) -> Result<Vec<serde_json::Value>, String> {
+) -> Result<Vec<ExtractionExperiment>, String> {
     state
         .sqlite
-        .get_logs(limit)
+        .list_experiments(50)
         .await
-        .map_err(|e: noodle_core::error::NoodleError| e.to_string())
+        .map_err(|e| e.to_string())
 }
 
 #[command]
-async fn get_config(state: State<'_, AppState>, key: String) -> Result<Option<String>, String> {
+async fn get_extraction_experiment(
+    state: State<'_, AppState>,
+    id: i64,
+) -> Result<ExtractionExperiment, String> {
     state
         .sqlite
-        .get_config(&key)
+        .get_experiment(id)
         .await
-        .map_err(|e: noodle_core::error::NoodleError| e.to_string())
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| "Experiment not found".into())
 }
 
 #[command]
-async fn save_config(state: State<'_, AppState>, key: String, value: String) -> Result<(), String> {
-    state
+async fn delete_extraction_experiment(state: State<'_, AppState>, id: i64) -> Result<(), String> {
+    let deleted = state
         .sqlite
-        .set_config(&key, &value)
+        .delete_experiment(id)
         .await
-        .map_err(|e: noodle_core::error::NoodleError| e.to_string())?;
-
-    // If AI settings changed, re-initialize provider
-    if key == "ollama_url" || key == "model_name" || key == "provider_type" || key == "api_key" {
-        let provider_type = state
-            .sqlite
-            .get_config("provider_type")
-            .await
-            .unwrap_or(Some("ollama".to_string()))
-            .unwrap_or("ollama".to_string());
-
-        let url = match provider_type.as_str() {
-            "lemonade" => state
-                .sqlite
-                .get_config("lemonade_url")
-                .await
-                .unwrap_or(Some("http://localhost:8000/v1".to_string()))
-                .unwrap_or("http://localhost:8000/v1".to_string()),
-            "foundry" => state
-                .sqlite
-                .get_config("foundry_url")
-                .await
-                .unwrap_or(Some("http://localhost:5000/v1".to_string()))
-                .unwrap_or("http://localhost:5000/v1".to_string()),
-            "openai" | _ => state
-                .sqlite
-                .get_config("ollama_url")
-                .await
-                .unwrap_or(Some("http://localhost:11434".to_string()))
-                .unwrap_or("http://localhost:11434".to_string()),
-        };
-
-        let model = state.sqlite.get_config("model_name").await.unwrap_or(None);
-        let api_key = state.sqlite.get_config("api_key").await.unwrap_or(None);
-
-        let new_provider: Arc<dyn AiProvider> = if provider_type == "ollama" {
-            Arc::new(OllamaProvider::new(url, model))
-        } else {
-            // Lemonade, Foundry, and OpenAI all use OpenAI-compatible API
-            Arc::new(OpenAICompatibleProvider::new(url, api_key, model))
-        };
+        .map_err(|e| e.to_string())?;
+    if !deleted {
+        return Err("Experiment not found".into());
+    }
+    Ok(())
+}
 
-        let mut ai_lock = state.ai.write().await;
-        *ai_lock = new_provider;
-        info!("Re-initialized AI provider: {}", provider_type);
+/// Marks an email's facts as checked after they were flagged for review.
+#[command]
+async fn dismiss_fact_review(state: State<'_, AppState>, email_id: i64) -> Result<(), String> {
+    let cleared = state
+        .sqlite
+        .clear_fact_review(email_id)
+        .await
+        .map_err(|e| e.to_string())?;
+    if !cleared {
+        return Err("Email has no facts waiting for review".into());
     }
     Ok(())
 }
 
 #[command]
-async fn get_models(state: State<'_, AppState>) -> Result<Vec<String>, String> {
-    let ai = state.ai.read().await;
-    ai.list_models().await.map_err(|e| e.to_string())
+async fn draft_reply(state: State<'_, AppState>, email_id: i64) -> Result<String, String> {
+    generate_draft(&state, email_id).await
 }
 
+/// Writes the reply the user dictated to an email, from the transcript the
+/// frontend's speech recognition produced, in the style of their sent mail.
 #[command]
-async fn save_log_cmd(
+async fn draft_from_dictation(
     state: State<'_, AppState>,
-    level: String,
-    source: String,
-    message: String,
-) -> Result<(), String> {
-    state
-        .sqlite
-        .save_log(&level, &source, &message, None)
+    email_id: i64,
+    transcript: String,
+) -> Result<String, String> {
+    DictationDrafter::new(state.sqlite.clone(), state.ai.clone())
+        .draft(email_id, &transcript)
         .await
-        .map_err(|e: noodle_core::error::NoodleError| e.to_string())
+        .map_err(|e| e.to_string())
 }
 
-#[command]
-async fn get_email(state: State<'_, AppState>, id: i64) -> Result<serde_json::Value, String> {
-    use sqlx::Row;
-    let email = sqlx::query("SELECT * FROM emails WHERE id = ?")
-        .bind(id)
-        .fetch_optional(state.sqlite.pool())
+/// Drafts a reply to an email in the user's language.
+async fn generate_draft(state: &AppState, email_id: i64) -> Result<String, String> {
+    let body = state
+        .sqlite
+        .get_email_body(email_id)
+        .await
+        .map_err(|e| e.to_string())?
+        .ok_or("Email not found")?;
+
+    let locale = state
+        .sqlite
+        .get_user_locale()
         .await
         .map_err(|e| e.to_string())?;
+    let prompt = format!(
+        "Draft a professional reply to this email:\n{}\n\n{}\n{}",
+        ai::injection::data_block(&body),
+        ai::injection::DATA_INSTRUCTION,
+        locale.prompt_instruction()
+    );
+    let request = ai::provider::ChatRequest {
+        messages: vec![ai::provider::Message {
+            role: "user".into(),
+            content: prompt,
+        }],
+        temperature: 0.7,
+        response_format: None,
+        model: None,
+    };
+    let ai = state.ai.read().await;
+    let response = ai
+        .chat_completion(request)
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(response.content)
+}
+
+/// How long exit waits for in-flight emails to finish before giving up.
+const SHUTDOWN_GRACE: std::time::Duration = std::time::Duration::from_secs(20);
 
-    match email {
-        Some(row) => Ok(serde_json::json!({
-            "id": row.get::<i64, _>("id"),
-            "subject": row.get::<String, _>("subject"),
-            "sender": row.get::<String, _>("sender"),
-            "received_at": row.get::<chrono::DateTime<chrono::Utc>, _>("received_at"),
-            "body_text": row.get::<String, _>("body_text")
-        })),
-        None => Err("Email not found".into()),
+/// Stops background sync, lets in-flight pipeline work finish (bounded by
+/// `SHUTDOWN_GRACE`), closes the database and exits.
+async fn shutdown_and_exit(app_handle: &tauri::AppHandle) {
+    if let Some(state) = app_handle.try_state::<AppState>() {
+        info!("Shutting down: draining in-flight work");
+        if !state.shutdown.shutdown(SHUTDOWN_GRACE).await {
+            error!("Shutdown grace period elapsed with work still in flight");
+        }
+        state.sqlite.close().await;
     }
+    app_handle.exit(0);
 }
 
 #[command]
-async fn list_prompts(state: State<'_, AppState>) -> Result<Vec<serde_json::Value>, String> {
-    use sqlx::Row;
-    // Return empty list if table doesn't exist yet, but use real query
-    let results = sqlx::query("SELECT id, name, prompt_template FROM prompts")
-        .fetch_all(state.sqlite.pool())
-        .await
-        .unwrap_or_else(|_| vec![]);
+fn hide_quick_search(app_handle: tauri::AppHandle) {
+    quick_search::hide(&app_handle);
+}
 
-    Ok(results
-        .into_iter()
-        .map(|r: sqlx::sqlite::SqliteRow| {
-            serde_json::json!({
-                "id": r.get::<String, _>("id"),
-                "name": r.get::<String, _>("name"),
-                "content": r.get::<String, _>("prompt_template")
-            })
-        })
-        .collect::<Vec<_>>())
+/// Closes the palette and shows `query`'s full results in the main window.
+#[command]
+fn open_search_in_main(app_handle: tauri::AppHandle, query: String) {
+    quick_search::hide(&app_handle);
+    show_main_window(&app_handle);
+    EventBus::new(&app_handle).publish(UiEvent::OpenSearch { query });
 }
 
+fn show_main_window(app: &tauri::AppHandle) {
+    if let Some(window) = app.get_webview_window("main") {
+        let _ = window.show();
+        let _ = window.unminimize();
+        let _ = window.set_focus();
+    }
+}
+
+/// Actions the command palette offers, with the arguments each takes.
 #[command]
-async fn save_prompt(state: State<'_, AppState>, prompt: serde_json::Value) -> Result<(), String> {
-    use uuid::Uuid;
-    let now = chrono::Utc::now();
-    sqlx::query("INSERT INTO prompts (id, name, kind, scope_json, model_pref_json, prompt_template, created_at, updated_at) VALUES (?, ?, ?, ?, ?, ?, ?, ?)")
-        .bind(Uuid::new_v4().to_string())
-        .bind(prompt["name"].as_str().unwrap_or("Untitled"))
-        .bind("custom")
-        .bind("{}")
-        .bind("{}")
-        .bind(prompt["content"].as_str().unwrap_or(""))
-        .bind(now)
-        .bind(now)
-        .execute(state.sqlite.pool())
-        .await
-        .map_err(|e| e.to_string())?;
-    Ok(())
+async fn get_commands(state: State<'_, AppState>) -> Result<Vec<palette::PaletteCommand>, String> {
+    palette::commands(&state).await
 }
 
+/// Runs a palette action from [`get_commands`] by id. Returns a line for
+/// the activity log.
 #[command]
-async fn draft_reply(state: State<'_, AppState>, email_id: i64) -> Result<String, String> {
-    use sqlx::Row;
-    let email = sqlx::query("SELECT body_text FROM emails WHERE id = ?")
-        .bind(email_id)
-        .fetch_optional(state.sqlite.pool())
-        .await
-        .map_err(|e| e.to_string())?;
-
-    if let Some(row) = email {
-        let body: String = row.get("body_text");
-        let prompt = format!("Draft a professional reply to this email: {}", body);
-        let request = ai::provider::ChatRequest {
-            messages: vec![ai::provider::Message {
-                role: "user".into(),
-                content: prompt,
-            }],
-            temperature: 0.7,
-            response_format: None,
-            model: None,
-        };
-        let ai = state.ai.read().await;
-        let response = ai
-            .chat_completion(request)
-            .await
-            .map_err(|e| e.to_string())?;
-        Ok(response.content)
-    } else {
-        Err("Email not found".into())
-    }
+async fn execute_command(
+    app_handle: tauri::AppHandle,
+    id: String,
+    args: Option<serde_json::Map<String, serde_json::Value>>,
+) -> Result<String, String> {
+    palette::execute(&app_handle, &id, &args.unwrap_or_default()).await
+}
+
+/// Writes a zip of logs, crash reports and settings (secrets left out) for
+/// attaching to a bug report, and returns its path.
+#[command]
+async fn create_diagnostics_bundle(state: State<'_, AppState>) -> Result<String, String> {
+    let path = diagnostics::create_bundle(&state).await?;
+    info!("Wrote diagnostics bundle to {}", path.display());
+    Ok(path.display().to_string())
+}
+
+#[command]
+async fn check_for_updates(app_handle: tauri::AppHandle) -> Result<updates::UpdateInfo, String> {
+    updates::check(&app_handle).await
+}
+
+/// Installs the newest release in place and restarts; fails when this build or
+/// the release doesn't support in-place updates.
+#[command]
+async fn install_update(app_handle: tauri::AppHandle) -> Result<(), String> {
+    updates::install(&app_handle).await
 }
 
 #[command]
 async fn force_exit(app_handle: tauri::AppHandle) {
-    app_handle.exit(0);
+    shutdown_and_exit(&app_handle).await;
 }
 
 #[command]
@@ -337,99 +2201,117 @@ async fn request_exit(state: State<'_, AppState>) -> Result<(), String> {
             .ok_or("No main window")?;
         window.show().map_err(|e| e.to_string())?;
         window.set_focus().map_err(|e| e.to_string())?;
-        state
-            .app_handle
-            .emit("noodle://show-exit-confirm", ())
-            .map_err(|e| e.to_string())?;
+        state.events.publish(UiEvent::ShowExitConfirm);
     } else {
-        state.app_handle.exit(0);
+        shutdown_and_exit(&state.app_handle).await;
+    }
+    Ok(())
+}
+
+#[derive(serde::Serialize)]
+struct AppLockStatus {
+    enabled: bool,
+    locked: bool,
+}
+
+#[command]
+async fn get_app_lock_status(state: State<'_, AppState>) -> Result<AppLockStatus, String> {
+    let config = state
+        .sqlite
+        .get_all_config()
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(AppLockStatus {
+        enabled: config.app_lock,
+        locked: state.lock.is_locked(),
+    })
+}
+
+/// Prompts for Windows Hello or the Windows password; true once unlocked.
+#[command]
+async fn unlock_app(state: State<'_, AppState>) -> Result<bool, String> {
+    state.lock.unlock_with_prompt().await
+}
+
+#[command]
+async fn lock_app(state: State<'_, AppState>) -> Result<(), String> {
+    let enabled = state
+        .sqlite
+        .get_all_config()
+        .await
+        .map_err(|e| e.to_string())?
+        .app_lock;
+    if !enabled {
+        return Err("The app lock is off".into());
     }
+    state.lock.lock(&state.events);
     Ok(())
 }
 
+/// The `noodle://` link this instance was launched with, if any. The
+/// frontend asks once it has loaded; later links arrive as events.
+#[command]
+fn take_deep_link() -> Option<deep_link::DeepLink> {
+    deep_link::take_pending()
+}
+
 fn main() {
+    diagnostics::init();
+    deep_link::remember_launch_link(&std::env::args().collect::<Vec<_>>());
+
     tauri::Builder::default()
+        // Must be registered first: a second launch hands over to the running
+        // instance before it opens the database or starts its own sync loop.
+        .plugin(tauri_plugin_single_instance::init(|app, args, _cwd| {
+            info!("Another launch was redirected to this instance");
+            show_main_window(app);
+            // Clicking a noodle:// link while running lands here.
+            deep_link::forward(app, &args);
+        }))
+        .plugin(tauri_plugin_notification::init())
+        .plugin(
+            tauri_plugin_global_shortcut::Builder::new()
+                .with_handler(quick_search::on_shortcut)
+                .build(),
+        )
+        .plugin(tauri_plugin_updater::Builder::new().build())
+        .plugin(tauri_plugin_autostart::init(
+            MacosLauncher::LaunchAgent,
+            Some(vec![AUTOSTART_ARG]),
+        ))
         .setup(|app| {
             let app_handle = app.handle().clone();
 
-            // Initialize Tray
-            use tauri::menu::{Menu, MenuItem};
-            use tauri::tray::{MouseButton, TrayIconBuilder, TrayIconEvent};
-
-            let quit_i = MenuItem::with_id(app, "quit", "Quit", true, None::<&str>)?;
-            let show_i = MenuItem::with_id(app, "show", "Show", true, None::<&str>)?;
-            let menu = Menu::with_items(app, &[&show_i, &quit_i])?;
-
-            let _tray = TrayIconBuilder::with_id("tray")
-                .icon(app.default_window_icon().unwrap().clone())
-                .menu(&menu)
-                .show_menu_on_left_click(false)
-                .on_menu_event(move |app, event| match event.id().as_ref() {
-                    "quit" => {
-                        let app_clone = app.clone();
-                        tauri::async_runtime::spawn(async move {
-                            let state = app_clone.state::<AppState>();
-                            let confirm = state
-                                .sqlite
-                                .get_config("confirm_exit")
-                                .await
-                                .unwrap_or(Some("true".to_string()))
-                                .unwrap_or("true".to_string())
-                                != "false";
-
-                            if confirm {
-                                if let Some(window) = app_clone.get_webview_window("main") {
-                                    let _ = window.show();
-                                    let _ = window.set_focus();
-                                    let _ = app_clone.emit("noodle://show-exit-confirm", ());
-                                }
-                            } else {
-                                app_clone.exit(0);
-                            }
-                        });
-                    }
-                    "show" => {
-                        if let Some(window) = app.get_webview_window("main") {
-                            let _ = window.show();
-                            let _ = window.set_focus();
-                        }
-                    }
-                    _ => {}
-                })
-                .on_tray_icon_event(|tray, event| {
-                    if let TrayIconEvent::Click {
-                        button: MouseButton::Left,
-                        ..
-                    } = event
-                    {
-                        let app = tray.app_handle();
-                        if let Some(window) = app.get_webview_window("main") {
-                            let _ = window.show();
-                            let _ = window.set_focus();
-                        }
-                    }
-                })
-                .build(app)?;
+            tray::create(app.handle())?;
+            if let Err(e) = deep_link::register_scheme() {
+                error!("Failed to register the noodle:// link handler: {}", e);
+            }
+            quick_search::create_window(app.handle())?;
+
+            let launched_at_login = std::env::args().any(|arg| arg == AUTOSTART_ARG);
+            let window_handle = app_handle.clone();
 
-            tauri::async_runtime::block_on(async move {
+            // Resolves to true when this launch should stay in the tray.
+            let start_in_background = tauri::async_runtime::block_on(async move {
                 let app_dir = match app_handle.path().app_data_dir() {
                     Ok(path) => path,
                     Err(e) => {
                         error!("Failed to get app data dir: {}", e);
-                        return;
+                        return false;
                     }
                 };
 
                 if let Err(e) = std::fs::create_dir_all(&app_dir) {
                     error!("Failed to create app data dir: {}", e);
                 }
+                diagnostics::set_dir(&app_dir);
 
                 let db_path = app_dir.join("noodle.db");
                 let sqlite = match SqliteStorage::new(db_path).await {
                     Ok(s) => Arc::new(s),
                     Err(e) => {
                         error!("Failed to initialize SQLite: {}", e);
-                        return;
+                        return false;
                     }
                 };
 
@@ -437,94 +2319,292 @@ fn main() {
                     Ok(q) => Arc::new(q),
                     Err(e) => {
                         error!("Failed to initialize Qdrant: {}", e);
-                        return;
+                        return false;
                     }
                 };
+                if qdrant.layout_upgraded() {
+                    // The outbox replay embeds them again, subject and body.
+                    match sqlite.enqueue_all_vector_upserts().await {
+                        Ok(queued) => info!("Queued {} emails for re-embedding", queued),
+                        Err(e) => error!("Failed to queue emails for re-embedding: {}", e),
+                    }
+                }
 
-                let provider_type = sqlite
-                    .get_config("provider_type")
-                    .await
-                    .unwrap_or(Some("ollama".into()))
-                    .unwrap_or("ollama".into());
-
-                let url = match provider_type.as_str() {
-                    "lemonade" => sqlite
-                        .get_config("lemonade_url")
-                        .await
-                        .unwrap_or(Some("http://localhost:8000/v1".to_string()))
-                        .unwrap_or("http://localhost:8000/v1".to_string()),
-                    "foundry" => sqlite
-                        .get_config("foundry_url")
-                        .await
-                        .unwrap_or(Some("http://localhost:5000/v1".to_string()))
-                        .unwrap_or("http://localhost:5000/v1".to_string()),
-                    "openai" | _ => sqlite
-                        .get_config("ollama_url")
-                        .await
-                        .unwrap_or(Some("http://localhost:11434".to_string()))
-                        .unwrap_or("http://localhost:11434".to_string()),
-                };
+                let startup_config = sqlite.get_all_config().await.unwrap_or_default();
+                let ai = Arc::new(RwLock::new(build_provider(&startup_config)));
+                let lock = Arc::new(app_lock::AppLock::new(startup_config.app_lock));
 
-                let model = sqlite.get_config("model_name").await.unwrap_or(None);
-                let api_key = sqlite.get_config("api_key").await.unwrap_or(None);
+                let shutdown = Arc::new(ShutdownCoordinator::new());
+                let metrics = Arc::new(StageMetrics::new());
 
-                let ai_provider: Arc<dyn AiProvider> = if provider_type == "ollama" {
-                    Arc::new(OllamaProvider::new(url, model))
-                } else {
-                    Arc::new(OpenAICompatibleProvider::new(url, api_key, model))
-                };
+                let legal_hold =
+                    Arc::new(LegalHold::new(sqlite.clone(), app_dir.join("legal-hold")));
 
-                let ai = Arc::new(RwLock::new(ai_provider));
+                let outlook = match OutlookClient::new(metrics.clone()) {
+                    Ok(o) => Arc::new(o),
+                    Err(e) => {
+                        error!("Failed to initialize Outlook client: {}", e);
+                        return false;
+                    }
+                };
 
+                let attachments = Arc::new(AttachmentStore::new(
+                    sqlite.clone(),
+                    outlook.clone(),
+                    app_dir.join("attachments"),
+                ));
                 let pipeline = Arc::new(ExtractionPipeline::new(
                     sqlite.clone(),
                     qdrant.clone(),
                     ai.clone(),
+                    shutdown.clone(),
+                    legal_hold.clone(),
+                    attachments.clone(),
+                    outlook.clone(),
+                    metrics,
                 ));
 
-                let outlook = match OutlookClient::new() {
-                    Ok(o) => Arc::new(o),
-                    Err(e) => {
-                        error!("Failed to initialize Outlook client: {}", e);
-                        return;
-                    }
-                };
+                let search = Arc::new(SearchService::new(
+                    sqlite.clone(),
+                    qdrant.clone(),
+                    ai.clone(),
+                ));
+
+                let issues = Arc::new(IssueClustering::new(
+                    sqlite.clone(),
+                    ai.clone(),
+                    pipeline.pacing(),
+                ));
+
+                let pipeline_for_replay = pipeline.clone();
+                let snapshots = Arc::new(VectorSnapshots::new(
+                    qdrant.clone(),
+                    sqlite.clone(),
+                    app_dir.join("qdrant-snapshots"),
+                ));
+                let maintenance = MaintenanceScheduler::new(
+                    sqlite.clone(),
+                    snapshots.clone(),
+                    pipeline.clone(),
+                    attachments,
+                    shutdown.clone(),
+                );
+                let payload_sync = PayloadSyncWorker::new(pipeline.clone(), shutdown.clone());
+                let digest = Arc::new(DigestService::new(sqlite.clone()));
+                let policy = Arc::new(ActivityPolicy::new(sqlite.clone()));
 
                 app_handle.manage(AppState {
                     sqlite,
-                    qdrant,
+                    digest,
+                    policy,
+                    shutdown,
                     ai,
                     pipeline,
+                    search,
+                    issues,
+                    snapshots,
+                    legal_hold,
                     outlook,
+                    lock,
+                    sync: Mutex::new(None),
+                    events: Arc::new(EventBus::new(&app_handle)),
                     app_handle: app_handle.clone(),
                 });
+
+                tauri::async_runtime::spawn(digest::run(app_handle.clone()));
+                tauri::async_runtime::spawn(digest::run_roundups(app_handle.clone()));
+                tauri::async_runtime::spawn(meetings::run(app_handle.clone()));
+                tauri::async_runtime::spawn(alerts::run(app_handle.clone()));
+                tauri::async_runtime::spawn(updates::run(app_handle.clone()));
+                tauri::async_runtime::spawn(companion::run(app_handle.clone()));
+                tauri::async_runtime::spawn(app_lock::run(app_handle.clone()));
+                tauri::async_runtime::spawn(maintenance.run());
+                tauri::async_runtime::spawn(payload_sync.run());
+
+                // Finish vector upserts a previous run left in the outbox.
+                let replay = pipeline_for_replay;
+                tauri::async_runtime::spawn(async move {
+                    if let Err(e) = replay.replay_vector_outbox().await {
+                        error!("Vector outbox replay failed: {}", e);
+                    }
+                });
+
+                let state = app_handle.state::<AppState>();
+                let config = state.sqlite.get_all_config().await.unwrap_or_default();
+                quick_search::register_shortcut(&app_handle, &config.quick_search_shortcut);
+                tray::refresh(&app_handle);
+                if launched_at_login && config.start_minimized {
+                    info!("Started at login; syncing in the background");
+                    spawn_background_sync(&state).await;
+                    return true;
+                }
+                false
             });
 
+            // The window starts hidden so a login launch never flashes it.
+            if !start_in_background {
+                if let Some(window) = window_handle.get_webview_window("main") {
+                    let _ = window.show();
+                }
+            }
+
             Ok(())
         })
-        .on_window_event(|window, event| {
-            if let tauri::WindowEvent::CloseRequested { api, .. } = event {
+        .on_window_event(|window, event| match event {
+            // Reader windows really close; the others only hide.
+            tauri::WindowEvent::CloseRequested { .. } if reader::is_reader(window.label()) => {
+                reader::save_geometry(window);
+            }
+            tauri::WindowEvent::CloseRequested { api, .. } => {
                 let _ = window.hide();
                 api.prevent_close();
             }
+            // The palette dismisses itself when the user clicks elsewhere.
+            tauri::WindowEvent::Focused(false) if window.label() == quick_search::LABEL => {
+                let _ = window.hide();
+            }
+            tauri::WindowEvent::Focused(focused) if window.label() == "main" => {
+                welcome::focus_changed(window.app_handle(), *focused);
+            }
+            _ => {}
         })
-        .invoke_handler(tauri::generate_handler![
+        .invoke_handler(app_lock::guard(tauri::generate_handler![
+            take_deep_link,
+            get_app_lock_status,
+            list_saved_credentials,
+            save_api_key,
+            delete_api_key,
+            unlock_app,
+            lock_app,
             search_emails,
+            search_in_thread,
+            search_from_sender,
+            search_attachments,
+            get_search_history,
+            clear_search_history,
+            get_search_suggestions,
+            get_due_today,
+            get_waiting_board,
+            get_digest,
+            get_whats_new,
             get_stats,
+            get_response_times,
+            get_self_insights,
+            get_sender_profile,
+            get_volume_heatmap,
             get_graph,
+            export_graph,
+            open_encrypted_export,
+            list_alerts,
+            list_vip_senders,
+            add_vip_sender,
+            remove_vip_sender,
+            suggest_vip_senders,
+            acknowledge_alert,
+            list_category_rules,
+            add_category_rule,
+            update_category_rule,
+            remove_category_rule,
+            list_category_audit,
+            export_project_report,
+            infer_org_chart,
+            list_inferred_relations,
+            set_relation_status,
+            cluster_issues,
+            list_issue_clusters,
+            get_issue_metrics,
+            format_for_chat,
+            create_ticket_from_issue,
+            list_issue_tickets,
             start_sync,
+            stop_sync,
+            get_scan_state,
+            get_queue_status,
+            cancel_queue_item,
+            discard_vector_retry,
+            get_autostart,
+            hide_quick_search,
+            open_search_in_main,
+            set_autostart,
+            set_focus_mode,
+            run_maintenance,
+            get_maintenance_status,
+            get_schema_info,
+            repair_database,
+            get_vector_stats,
+            repair_vectors,
+            list_vector_snapshots,
+            verify_legal_hold,
+            create_vector_snapshot,
+            restore_vector_snapshot,
             get_email,
+            list_attachments,
+            open_email_window,
+            open_in_outlook,
+            reprocess_email,
+            reembed_email,
+            bulk_set_triage,
+            bulk_reassign_project,
+            bulk_reprocess,
+            bulk_trash,
+            bulk_purge,
+            list_trash,
+            restore_email,
+            list_annotations,
+            save_annotation,
+            delete_annotation,
+            list_annotation_tags,
+            get_commands,
+            execute_command,
+            summarize_topic,
+            list_topic_summaries,
+            ask_noodle,
+            list_chat_sessions,
+            get_chat_session,
+            delete_chat_session,
+            run_newsletter_roundup,
+            list_newsletter_roundups,
+            list_newsletter_senders,
+            list_meeting_follow_ups,
+            follow_up_meeting,
+            set_meeting_task_done,
+            delete_topic_summary,
             list_prompts,
+            export_prompt_pack,
+            import_prompt_pack,
+            resolve_prompt_variables,
+            preview_prompt,
+            run_extraction_experiment,
+            list_extraction_experiments,
+            get_extraction_experiment,
+            delete_extraction_experiment,
             save_prompt,
+            delete_prompt,
             draft_reply,
+            draft_from_dictation,
+            dismiss_fact_review,
             get_logs,
             get_config,
             save_config,
+            get_all_config,
+            set_config_bulk,
+            export_settings,
+            import_settings,
+            list_settings_profiles,
+            save_settings_profile,
+            apply_settings_profile,
+            delete_settings_profile,
+            get_project_timeline,
+            get_project_settings,
+            set_project_settings,
             save_log_cmd,
             get_models,
+            create_diagnostics_bundle,
+            check_for_updates,
+            install_update,
             force_exit,
             request_exit
-        ])
+        ]))
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
 }
@@ -0,0 +1,79 @@
+use crate::AppState;
+use agent::meetings::MeetingFollowUps;
+use chrono::Utc;
+use tauri::{AppHandle, Manager};
+use tauri_plugin_notification::NotificationExt;
+use tracing::{error, info};
+
+const CHECK_INTERVAL_SECS: u64 = 900;
+
+/// Reads ended meetings from the calendar while mail is syncing and
+/// `meeting_follow_ups` is enabled, extracts their follow-ups from the
+/// related mail, and announces them when notifications are allowed.
+pub async fn run(app: AppHandle) {
+    let mut interval = tokio::time::interval(std::time::Duration::from_secs(CHECK_INTERVAL_SECS));
+    let shutdown = app.state::<AppState>().shutdown.clone();
+
+    loop {
+        tokio::select! {
+            _ = interval.tick() => {}
+            _ = shutdown.cancelled() => return,
+        }
+        if let Err(e) = tick(&app).await {
+            error!("Meeting follow-ups failed: {}", e);
+        }
+    }
+}
+
+async fn tick(app: &AppHandle) -> Result<(), String> {
+    let state = app.state::<AppState>();
+    let enabled = state
+        .sqlite
+        .get_config("meeting_follow_ups")
+        .await
+        .unwrap_or(None)
+        .is_none_or(|v| v == "true");
+    let now = Utc::now();
+    if !enabled
+        || state.sync.lock().await.is_none()
+        || !state
+            .policy
+            .sync_allowed(now)
+            .await
+            .map_err(|e| e.to_string())?
+    {
+        return Ok(());
+    }
+
+    let followed_up = MeetingFollowUps::new(
+        state.sqlite.clone(),
+        state.outlook.clone(),
+        state.ai.clone(),
+        state.pipeline.pacing(),
+    )
+    .run(now)
+    .await
+    .map_err(|e| e.to_string())?;
+    if followed_up == 0 {
+        return Ok(());
+    }
+    info!("Extracted follow-ups for {} meetings", followed_up);
+
+    if state
+        .policy
+        .notifications_allowed(now)
+        .await
+        .map_err(|e| e.to_string())?
+    {
+        app.notification()
+            .builder()
+            .title("Meeting follow-ups")
+            .body(format!(
+                "Decisions and action items from {} meeting(s) are ready.",
+                followed_up
+            ))
+            .show()
+            .map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
@@ -0,0 +1,189 @@
+use crate::deep_link::DeepLink;
+use crate::events::UiEvent;
+use crate::{show_main_window, sync_now, AppState};
+use serde::Serialize;
+use tauri::{AppHandle, Manager};
+
+/// An action the command palette offers. The frontend asks for `args` in
+/// order, then runs it with [`execute`] by `id`.
+#[derive(Debug, Clone, Serialize)]
+pub struct PaletteCommand {
+    pub id: &'static str,
+    pub title: &'static str,
+    pub description: &'static str,
+    pub args: Vec<PaletteArg>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PaletteArg {
+    pub name: &'static str,
+    pub label: &'static str,
+    pub required: bool,
+    #[serde(flatten)]
+    pub kind: ArgKind,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ArgKind {
+    Text,
+    Integer,
+    /// One of `options`, e.g. a project name.
+    Choice {
+        options: Vec<String>,
+    },
+}
+
+/// The actions available right now. Choices such as project names are
+/// filled in from what is stored.
+pub async fn commands(state: &AppState) -> Result<Vec<PaletteCommand>, String> {
+    let projects = state
+        .sqlite
+        .list_project_names()
+        .await
+        .map_err(|e| e.to_string())?;
+    let paused = state.policy.sync_paused().await.unwrap_or(false);
+    Ok(vec![
+        PaletteCommand {
+            id: "sync_now",
+            title: "Sync now",
+            description: "Scan Outlook for new mail right away",
+            args: vec![],
+        },
+        PaletteCommand {
+            id: "toggle_sync_pause",
+            title: if paused { "Resume sync" } else { "Pause sync" },
+            description: "Stop or restart background scanning",
+            args: vec![],
+        },
+        PaletteCommand {
+            id: "search",
+            title: "Search emails",
+            description: "Search mail in the main window; #tags filter by tag",
+            args: vec![PaletteArg {
+                name: "query",
+                label: "Query",
+                required: true,
+                kind: ArgKind::Text,
+            }],
+        },
+        PaletteCommand {
+            id: "generate_digest",
+            title: "Generate digest",
+            description: "Show what needs your attention now",
+            args: vec![],
+        },
+        PaletteCommand {
+            id: "open_project",
+            title: "Open project",
+            description: "List a project's mail",
+            args: vec![PaletteArg {
+                name: "project",
+                label: "Project",
+                required: true,
+                kind: ArgKind::Choice { options: projects },
+            }],
+        },
+        PaletteCommand {
+            id: "open_email",
+            title: "Open email",
+            description: "Open an email by its id in its own window",
+            args: vec![PaletteArg {
+                name: "id",
+                label: "Email id",
+                required: true,
+                kind: ArgKind::Integer,
+            }],
+        },
+    ])
+}
+
+/// Runs the palette command `id` with `args`, keyed by argument name.
+/// Returns a line for the activity log; views change through the usual
+/// events.
+pub async fn execute(
+    app: &AppHandle,
+    id: &str,
+    args: &serde_json::Map<String, serde_json::Value>,
+) -> Result<String, String> {
+    let state = app.state::<AppState>();
+    match id {
+        "sync_now" => {
+            sync_now(&state).await;
+            Ok("Sync started".into())
+        }
+        "toggle_sync_pause" => Ok(if crate::tray::toggle_pause(app).await? {
+            "Sync paused".into()
+        } else {
+            "Sync resumed".into()
+        }),
+        "search" => {
+            let query = text_arg(args, "query")?;
+            show_main_window(app);
+            state.events.publish(UiEvent::OpenSearch {
+                query: query.clone(),
+            });
+            Ok(format!("Searching for: {}", query))
+        }
+        "generate_digest" => {
+            crate::digest::notify_now(app).await?;
+            Ok("Digest generated".into())
+        }
+        "open_project" => {
+            let project = text_arg(args, "project")?;
+            // Palette input is typed, so match the stored name's case.
+            let name = state
+                .sqlite
+                .list_project_names()
+                .await
+                .map_err(|e| e.to_string())?
+                .into_iter()
+                .find(|name| name.eq_ignore_ascii_case(&project))
+                .ok_or_else(|| format!("Unknown project: {}", project))?;
+            show_main_window(app);
+            state
+                .events
+                .publish(UiEvent::Navigate(DeepLink::Project { name: name.clone() }));
+            Ok(format!("Opened project {}", name))
+        }
+        "open_email" => {
+            let id = integer_arg(args, "id")?;
+            state
+                .sqlite
+                .get_email(id)
+                .await
+                .map_err(|e| e.to_string())?
+                .ok_or_else(|| format!("Email {} not found", id))?;
+            state
+                .events
+                .publish(UiEvent::Navigate(DeepLink::Email { id }));
+            Ok(format!("Opened email {}", id))
+        }
+        _ => Err(format!("Unknown command: {}", id)),
+    }
+}
+
+fn text_arg(
+    args: &serde_json::Map<String, serde_json::Value>,
+    name: &str,
+) -> Result<String, String> {
+    args.get(name)
+        .and_then(|v| v.as_str())
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(str::to_string)
+        .ok_or_else(|| format!("{} is required", name))
+}
+
+/// Accepts a number or, as palettes send what was typed, a numeric string.
+fn integer_arg(
+    args: &serde_json::Map<String, serde_json::Value>,
+    name: &str,
+) -> Result<i64, String> {
+    match args.get(name) {
+        Some(serde_json::Value::Number(n)) => n.as_i64(),
+        Some(serde_json::Value::String(s)) => s.trim().parse().ok(),
+        _ => None,
+    }
+    .ok_or_else(|| format!("{} must be a whole number", name))
+}
@@ -0,0 +1,85 @@
+use tauri::{AppHandle, Manager, WebviewUrl, WebviewWindowBuilder};
+use tauri_plugin_global_shortcut::{GlobalShortcutExt, Shortcut, ShortcutEvent, ShortcutState};
+use tracing::{error, info};
+
+/// Label of the palette window; the frontend renders the palette instead of
+/// the full app when loaded with this as its URL hash.
+pub const LABEL: &str = "quick-search";
+
+/// Creates the palette window hidden; the shortcut only toggles visibility so
+/// it opens instantly.
+pub fn create_window(app: &AppHandle) -> tauri::Result<()> {
+    WebviewWindowBuilder::new(
+        app,
+        LABEL,
+        WebviewUrl::App("index.html#quick-search".into()),
+    )
+    .title("Noodle Quick Search")
+    .inner_size(640.0, 420.0)
+    .resizable(false)
+    .decorations(false)
+    .always_on_top(true)
+    .skip_taskbar(true)
+    .center()
+    .visible(false)
+    .build()?;
+    Ok(())
+}
+
+pub fn show(app: &AppHandle) {
+    if let Some(window) = app.get_webview_window(LABEL) {
+        let _ = window.center();
+        let _ = window.show();
+        let _ = window.set_focus();
+    }
+}
+
+pub fn hide(app: &AppHandle) {
+    if let Some(window) = app.get_webview_window(LABEL) {
+        let _ = window.hide();
+    }
+}
+
+pub fn toggle(app: &AppHandle) {
+    let visible = app
+        .get_webview_window(LABEL)
+        .and_then(|window| window.is_visible().ok())
+        .unwrap_or(false);
+    if visible {
+        hide(app);
+    } else {
+        show(app);
+    }
+}
+
+/// Global shortcut handler registered with the plugin.
+pub fn on_shortcut(app: &AppHandle, _shortcut: &Shortcut, event: ShortcutEvent) {
+    if event.state == ShortcutState::Pressed {
+        toggle(app);
+    }
+}
+
+pub fn parse_shortcut(accelerator: &str) -> Result<Shortcut, String> {
+    accelerator
+        .parse::<Shortcut>()
+        .map_err(|e| format!("Invalid shortcut '{}': {}", accelerator, e))
+}
+
+/// Replaces any registered shortcut with `accelerator`; empty disables it.
+pub fn register_shortcut(app: &AppHandle, accelerator: &str) {
+    let shortcuts = app.global_shortcut();
+    if let Err(e) = shortcuts.unregister_all() {
+        error!("Failed to clear global shortcuts: {}", e);
+    }
+    let accelerator = accelerator.trim();
+    if accelerator.is_empty() {
+        return;
+    }
+    let result = parse_shortcut(accelerator)
+        .and_then(|shortcut| shortcuts.register(shortcut).map_err(|e| e.to_string()));
+    match result {
+        Ok(()) => info!("Registered quick search shortcut {}", accelerator),
+        // Usually another app already owns the combination.
+        Err(e) => error!("Failed to register quick search shortcut: {}", e),
+    }
+}
@@ -0,0 +1,88 @@
+use crate::AppState;
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager, WebviewUrl, WebviewWindowBuilder, Window};
+use tracing::error;
+
+/// Reader windows are labelled with this prefix and the email id; the
+/// frontend renders a single email when loaded with `#email/<id>`.
+pub const LABEL_PREFIX: &str = "email-";
+
+const DEFAULT_WIDTH: f64 = 720.0;
+const DEFAULT_HEIGHT: f64 = 800.0;
+
+/// Where a window was and how big, in logical pixels.
+#[derive(Serialize, Deserialize)]
+struct WindowGeometry {
+    x: f64,
+    y: f64,
+    width: f64,
+    height: f64,
+}
+
+fn geometry_key(label: &str) -> String {
+    format!("window_state:{}", label)
+}
+
+pub fn is_reader(label: &str) -> bool {
+    label.starts_with(LABEL_PREFIX)
+}
+
+/// Opens a reader window for email `id`, or focuses it if already open.
+/// The window comes back where it was last closed.
+pub async fn open(app: &AppHandle, id: i64, subject: &str) -> tauri::Result<()> {
+    let label = format!("{}{}", LABEL_PREFIX, id);
+    if let Some(window) = app.get_webview_window(&label) {
+        window.show()?;
+        return window.set_focus();
+    }
+
+    let mut builder = WebviewWindowBuilder::new(
+        app,
+        &label,
+        WebviewUrl::App(format!("index.html#email/{}", id).into()),
+    )
+    .title(subject);
+    builder = match saved_geometry(app, &label).await {
+        Some(g) => builder.position(g.x, g.y).inner_size(g.width, g.height),
+        None => builder.inner_size(DEFAULT_WIDTH, DEFAULT_HEIGHT).center(),
+    };
+    builder.build()?;
+    Ok(())
+}
+
+async fn saved_geometry(app: &AppHandle, label: &str) -> Option<WindowGeometry> {
+    let state = app.try_state::<AppState>()?;
+    let saved = state.sqlite.get_config(&geometry_key(label)).await.ok()??;
+    serde_json::from_str(&saved).ok()
+}
+
+/// Remembers a reader window's position and size as it closes.
+pub fn save_geometry(window: &Window) {
+    let Ok(scale) = window.scale_factor() else {
+        return;
+    };
+    let (Ok(position), Ok(size)) = (window.outer_position(), window.inner_size()) else {
+        return;
+    };
+    let position = position.to_logical::<f64>(scale);
+    let size = size.to_logical::<f64>(scale);
+    let geometry = WindowGeometry {
+        x: position.x,
+        y: position.y,
+        width: size.width,
+        height: size.height,
+    };
+    let Ok(value) = serde_json::to_string(&geometry) else {
+        return;
+    };
+    let key = geometry_key(window.label());
+    let app = window.app_handle().clone();
+    tauri::async_runtime::spawn(async move {
+        let Some(state) = app.try_state::<AppState>() else {
+            return;
+        };
+        if let Err(e) = state.sqlite.set_config(&key, &value).await {
+            error!("Failed to save window state: {}", e);
+        }
+    });
+}
@@ -0,0 +1,190 @@
+use crate::events::{EventBus, UiEvent};
+use crate::{show_main_window, shutdown_and_exit, sync_now, AppState};
+use agent::engine::LAST_SYNC_KEY;
+use chrono::{DateTime, Utc};
+use noodle_core::types::SyncState;
+use tauri::menu::{CheckMenuItem, Menu, MenuEvent, MenuItem, PredefinedMenuItem};
+use tauri::tray::{MouseButton, TrayIconBuilder, TrayIconEvent};
+use tauri::{AppHandle, Listener, Manager, Wry};
+use tracing::error;
+
+pub const ID: &str = "tray";
+
+/// Creates the tray icon. Its menu is rebuilt by [`refresh`] whenever the
+/// sync loop reports a state change, so the status line and the pause toggle
+/// always match what sync is doing.
+pub fn create(app: &AppHandle) -> tauri::Result<()> {
+    let menu = build_menu(app, "Starting…", false)?;
+    TrayIconBuilder::with_id(ID)
+        .icon(app.default_window_icon().unwrap().clone())
+        .menu(&menu)
+        .show_menu_on_left_click(false)
+        .on_menu_event(on_menu_event)
+        .on_tray_icon_event(|tray, event| {
+            if let TrayIconEvent::Click {
+                button: MouseButton::Left,
+                ..
+            } = event
+            {
+                show_main_window(tray.app_handle());
+            }
+        })
+        .build(app)?;
+
+    let handle = app.clone();
+    app.listen(noodle_core::events::SYNC_STATE, move |_| refresh(&handle));
+    Ok(())
+}
+
+/// Rebuilds the menu from the current sync status. A no-op until the app
+/// state is initialized.
+pub fn refresh(app: &AppHandle) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let Some(state) = app.try_state::<AppState>() else {
+            return;
+        };
+        let paused = state.policy.sync_paused().await.unwrap_or(false);
+        let status = status_line(&state, paused).await;
+        let menu = match build_menu(&app, &status, paused) {
+            Ok(menu) => menu,
+            Err(e) => {
+                error!("Failed to build tray menu: {}", e);
+                return;
+            }
+        };
+        if let Some(tray) = app.tray_by_id(ID) {
+            if let Err(e) = tray.set_menu(Some(menu)) {
+                error!("Failed to update tray menu: {}", e);
+            }
+        }
+    });
+}
+
+fn build_menu(app: &AppHandle, status: &str, paused: bool) -> tauri::Result<Menu<Wry>> {
+    let status_i = MenuItem::with_id(app, "status", status, false, None::<&str>)?;
+    let sync_i = MenuItem::with_id(app, "sync_now", "Sync now", !paused, None::<&str>)?;
+    let pause_i = CheckMenuItem::with_id(app, "pause", "Pause sync", true, paused, None::<&str>)?;
+    let digest_i = MenuItem::with_id(app, "digest", "Generate digest", true, None::<&str>)?;
+    let show_i = MenuItem::with_id(app, "show", "Show", true, None::<&str>)?;
+    let quit_i = MenuItem::with_id(app, "quit", "Quit", true, None::<&str>)?;
+    Menu::with_items(
+        app,
+        &[
+            &status_i,
+            &PredefinedMenuItem::separator(app)?,
+            &sync_i,
+            &pause_i,
+            &digest_i,
+            &PredefinedMenuItem::separator(app)?,
+            &show_i,
+            &quit_i,
+        ],
+    )
+}
+
+async fn status_line(state: &AppState, paused: bool) -> String {
+    if paused {
+        return "Sync paused".into();
+    }
+    let status = state.sync.lock().await.as_ref().map(|sync| sync.status());
+    let last_sync_at = match &status {
+        Some(status) => match status.state {
+            SyncState::Syncing => return "Syncing…".into(),
+            SyncState::Paused => {
+                return format!(
+                    "Sync paused: {}",
+                    status.pause_reason.as_deref().unwrap_or("waiting")
+                )
+            }
+            SyncState::Idle => status.last_sync_at,
+        },
+        // Sync hasn't started this session; show the last run from before.
+        None => state
+            .sqlite
+            .get_config(LAST_SYNC_KEY)
+            .await
+            .unwrap_or(None)
+            .and_then(|s| DateTime::parse_from_rfc3339(&s).ok())
+            .map(|dt| dt.with_timezone(&Utc)),
+    };
+    let Some(last) = last_sync_at else {
+        return "Not synced yet".into();
+    };
+    let tz = state.sqlite.get_user_timezone().await.unwrap_or_default();
+    let local = tz.to_local(last);
+    if tz.local_date(last) == tz.local_date(Utc::now()) {
+        return format!("Last sync: {}", local.format("%H:%M"));
+    }
+    let locale = state.sqlite.get_user_locale().await.unwrap_or_default();
+    format!("Last sync: {}", locale.format_datetime(&local))
+}
+
+/// Pauses sync, or resumes it when paused. Returns whether it is paused now.
+pub async fn toggle_pause(app: &AppHandle) -> Result<bool, String> {
+    let state = app.state::<AppState>();
+    let paused = !state.policy.sync_paused().await.unwrap_or(false);
+    state
+        .policy
+        .set_sync_paused(paused)
+        .await
+        .map_err(|e| e.to_string())?;
+    // Wake the loop so it notices the change now, not at the next poll.
+    if let Some(sync) = state.sync.lock().await.as_ref() {
+        sync.sync_now();
+    }
+    refresh(app);
+    Ok(paused)
+}
+
+fn on_menu_event(app: &AppHandle, event: MenuEvent) {
+    match event.id().as_ref() {
+        "sync_now" => {
+            let app = app.clone();
+            tauri::async_runtime::spawn(async move {
+                sync_now(&app.state::<AppState>()).await;
+            });
+        }
+        "pause" => {
+            let app = app.clone();
+            tauri::async_runtime::spawn(async move {
+                if let Err(e) = toggle_pause(&app).await {
+                    error!("Failed to toggle sync pause: {}", e);
+                }
+            });
+        }
+        "digest" => {
+            let app = app.clone();
+            tauri::async_runtime::spawn(async move {
+                if let Err(e) = crate::digest::notify_now(&app).await {
+                    error!("Failed to generate digest: {}", e);
+                }
+            });
+        }
+        "quit" => {
+            let app = app.clone();
+            tauri::async_runtime::spawn(async move {
+                let state = app.state::<AppState>();
+                let confirm = state
+                    .sqlite
+                    .get_config("confirm_exit")
+                    .await
+                    .unwrap_or(Some("true".to_string()))
+                    .unwrap_or("true".to_string())
+                    != "false";
+
+                if confirm {
+                    if let Some(window) = app.get_webview_window("main") {
+                        let _ = window.show();
+                        let _ = window.set_focus();
+                        EventBus::new(&app).publish(UiEvent::ShowExitConfirm);
+                    }
+                } else {
+                    shutdown_and_exit(&app).await;
+                }
+            });
+        }
+        "show" => show_main_window(app),
+        _ => {}
+    }
+}
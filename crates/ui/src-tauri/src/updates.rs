@@ -0,0 +1,253 @@
+use crate::events::UiEvent;
+use crate::AppState;
+use chrono::{DateTime, Utc};
+use semver::Version;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+use tauri_plugin_notification::NotificationExt;
+use tauri_plugin_updater::UpdaterExt;
+use tracing::{error, info};
+
+const RELEASES_URL: &str = "https://api.github.com/repos/sheetalkjain/noodle/releases?per_page=20";
+/// Release asset holding the signed Tauri updater manifest.
+const MANIFEST_ASSET: &str = "latest.json";
+/// Minisign public key for in-place updates, baked in by release builds. When
+/// it is absent the check still works but updates are downloaded by hand.
+const UPDATER_PUBKEY: Option<&str> = option_env!("NOODLE_UPDATER_PUBKEY");
+
+const FIRST_CHECK_DELAY: Duration = Duration::from_secs(60);
+const CHECK_INTERVAL: Duration = Duration::from_secs(6 * 60 * 60);
+/// Bookkeeping keys in `app_config`.
+const LAST_CHECK_KEY: &str = "update_last_check";
+const NOTIFIED_VERSION_KEY: &str = "update_notified_version";
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ReleaseInfo {
+    pub version: String,
+    pub name: String,
+    /// Release notes, in Markdown.
+    pub notes: String,
+    pub url: String,
+    pub published_at: Option<DateTime<Utc>>,
+    pub prerelease: bool,
+    /// The release ships a signed manifest this build can install from.
+    pub installable: bool,
+    #[serde(skip)]
+    manifest_url: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct UpdateInfo {
+    pub current_version: String,
+    pub channel: String,
+    /// Releases newer than this build on the selected channel, newest first.
+    pub releases: Vec<ReleaseInfo>,
+    pub checked_at: DateTime<Utc>,
+}
+
+impl UpdateInfo {
+    pub fn latest(&self) -> Option<&ReleaseInfo> {
+        self.releases.first()
+    }
+}
+
+#[derive(Deserialize)]
+struct FeedRelease {
+    tag_name: String,
+    #[serde(default)]
+    name: Option<String>,
+    #[serde(default)]
+    body: Option<String>,
+    html_url: String,
+    published_at: Option<DateTime<Utc>>,
+    prerelease: bool,
+    draft: bool,
+    #[serde(default)]
+    assets: Vec<FeedAsset>,
+}
+
+#[derive(Deserialize)]
+struct FeedAsset {
+    name: String,
+    browser_download_url: String,
+}
+
+/// Queries the release feed for versions newer than this build. The `stable`
+/// channel skips pre-releases; `beta` includes them.
+pub async fn check(app: &AppHandle) -> Result<UpdateInfo, String> {
+    let state = app.state::<AppState>();
+    let config = state
+        .sqlite
+        .get_all_config()
+        .await
+        .map_err(|e| e.to_string())?;
+    let current = app.package_info().version.clone();
+    let releases = fetch_releases().await?;
+    let mut newer: Vec<(Version, ReleaseInfo)> = releases
+        .into_iter()
+        .filter(|r| !r.draft && (config.update_channel == "beta" || !r.prerelease))
+        .filter_map(|r| {
+            let version = Version::parse(r.tag_name.trim_start_matches('v')).ok()?;
+            (version > current).then(|| (version, release_info(r)))
+        })
+        .collect();
+    newer.sort_by(|a, b| b.0.cmp(&a.0));
+
+    let checked_at = Utc::now();
+    if let Err(e) = state
+        .sqlite
+        .set_config(LAST_CHECK_KEY, &checked_at.to_rfc3339())
+        .await
+    {
+        error!("Failed to record update check: {}", e);
+    }
+    Ok(UpdateInfo {
+        current_version: current.to_string(),
+        channel: config.update_channel,
+        releases: newer.into_iter().map(|(_, info)| info).collect(),
+        checked_at,
+    })
+}
+
+/// Downloads and installs the newest release on the selected channel through
+/// the Tauri updater, then restarts. Only works in builds with a pubkey and
+/// for releases that publish a signed manifest.
+pub async fn install(app: &AppHandle) -> Result<(), String> {
+    let pubkey = UPDATER_PUBKEY
+        .filter(|key| !key.is_empty())
+        .ok_or("In-place updates are not enabled in this build; download the release instead")?;
+    let info = check(app).await?;
+    let endpoint = info
+        .latest()
+        .ok_or("Noodle is up to date")?
+        .manifest_url
+        .as_deref()
+        .ok_or("This release can't be installed in place; download it instead")?
+        .parse()
+        .map_err(|e| format!("Invalid update manifest URL: {}", e))?;
+    let update = app
+        .updater_builder()
+        .pubkey(pubkey)
+        .endpoints(vec![endpoint])
+        .map_err(|e| e.to_string())?
+        .build()
+        .map_err(|e| e.to_string())?
+        .check()
+        .await
+        .map_err(|e| e.to_string())?
+        .ok_or("Noodle is up to date")?;
+
+    info!("Installing update {}", update.version);
+    update
+        .download_and_install(|_, _| {}, || {})
+        .await
+        .map_err(|e| e.to_string())?;
+    app.restart();
+}
+
+/// Checks the feed periodically while `update_check` is enabled and notifies
+/// once per new version.
+pub async fn run(app: AppHandle) {
+    let shutdown = app.state::<AppState>().shutdown.clone();
+    let mut wait = FIRST_CHECK_DELAY;
+    loop {
+        tokio::select! {
+            _ = tokio::time::sleep(wait) => {}
+            _ = shutdown.cancelled() => return,
+        }
+        wait = CHECK_INTERVAL;
+        if let Err(e) = tick(&app).await {
+            error!("Update check failed: {}", e);
+        }
+    }
+}
+
+async fn tick(app: &AppHandle) -> Result<(), String> {
+    let state = app.state::<AppState>();
+    let config = state
+        .sqlite
+        .get_all_config()
+        .await
+        .map_err(|e| e.to_string())?;
+    if !config.update_check {
+        return Ok(());
+    }
+
+    let info = check(app).await?;
+    let Some(latest) = info.latest() else {
+        return Ok(());
+    };
+    let notified = state
+        .sqlite
+        .get_config(NOTIFIED_VERSION_KEY)
+        .await
+        .unwrap_or(None);
+    if notified.as_deref() == Some(latest.version.as_str()) {
+        return Ok(());
+    }
+    // Held back until quiet hours or focus mode end; the next check retries.
+    if !state
+        .policy
+        .notifications_allowed(Utc::now())
+        .await
+        .map_err(|e| e.to_string())?
+    {
+        return Ok(());
+    }
+
+    state.events.publish(UiEvent::UpdateAvailable(info.clone()));
+    app.notification()
+        .builder()
+        .title(format!("Noodle {} is available", latest.version))
+        .body(format!(
+            "You have {}. Open Settings to see what's new.",
+            info.current_version
+        ))
+        .show()
+        .map_err(|e| e.to_string())?;
+    info!("Notified about update {}", latest.version);
+    state
+        .sqlite
+        .set_config(NOTIFIED_VERSION_KEY, &latest.version)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+async fn fetch_releases() -> Result<Vec<FeedRelease>, String> {
+    reqwest::Client::new()
+        .get(RELEASES_URL)
+        // The GitHub API rejects requests without a user agent.
+        .header(reqwest::header::USER_AGENT, "noodle-update-check")
+        .timeout(Duration::from_secs(30))
+        .send()
+        .await
+        .and_then(|r| r.error_for_status())
+        .map_err(|e| format!("Failed to reach the release feed: {}", e))?
+        .json()
+        .await
+        .map_err(|e| format!("Unexpected release feed response: {}", e))
+}
+
+fn release_info(release: FeedRelease) -> ReleaseInfo {
+    let manifest_url = release
+        .assets
+        .into_iter()
+        .find(|a| a.name == MANIFEST_ASSET)
+        .map(|a| a.browser_download_url);
+    let installable = UPDATER_PUBKEY.is_some_and(|key| !key.is_empty()) && manifest_url.is_some();
+    let version = release.tag_name.trim_start_matches('v').to_string();
+    ReleaseInfo {
+        name: release
+            .name
+            .filter(|n| !n.trim().is_empty())
+            .unwrap_or_else(|| version.clone()),
+        version,
+        notes: release.body.unwrap_or_default(),
+        url: release.html_url,
+        published_at: release.published_at,
+        prerelease: release.prerelease,
+        installable,
+        manifest_url,
+    }
+}
@@ -0,0 +1,86 @@
+use crate::events::UiEvent;
+use crate::AppState;
+use agent::digest::whats_new::WhatsNew;
+use chrono::{DateTime, Duration, Utc};
+use noodle_core::time::localize_fields;
+use tauri::{AppHandle, Manager};
+use tracing::warn;
+
+/// `app_config` key holding when the user last left the main window.
+pub const LAST_SEEN_KEY: &str = "last_seen_at";
+/// Time away after which coming back shows what changed.
+const MIN_ABSENCE_MINS: i64 = 30;
+
+/// Notes when the user leaves the main window, and when they come back to
+/// it after a while, sends what changed meanwhile as [`UiEvent::WhatsNew`].
+pub fn focus_changed(app: &AppHandle, focused: bool) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        // Focus changes come before setup has finished.
+        let Some(state) = app.try_state::<AppState>() else {
+            return;
+        };
+        let now = Utc::now();
+        let result = if focused {
+            welcome_back(&state, now).await
+        } else {
+            state
+                .sqlite
+                .set_config(LAST_SEEN_KEY, &now.to_rfc3339())
+                .await
+                .map_err(|e| e.to_string())
+        };
+        if let Err(e) = result {
+            warn!("Failed to track time away from the app: {}", e);
+        }
+    });
+}
+
+async fn welcome_back(state: &AppState, now: DateTime<Utc>) -> Result<(), String> {
+    let Some(since) = last_seen(state).await? else {
+        return Ok(());
+    };
+    if now - since < Duration::minutes(MIN_ABSENCE_MINS) {
+        return Ok(());
+    }
+    let whats_new = whats_new(state, since, now).await?;
+    if !whats_new.is_empty() {
+        state.events.publish(UiEvent::WhatsNew(whats_new));
+    }
+    Ok(())
+}
+
+/// When the user last left the main window, if ever.
+pub async fn last_seen(state: &AppState) -> Result<Option<DateTime<Utc>>, String> {
+    Ok(state
+        .sqlite
+        .get_config(LAST_SEEN_KEY)
+        .await
+        .map_err(|e| e.to_string())?
+        .and_then(|v| DateTime::parse_from_rfc3339(&v).ok())
+        .map(|t| t.with_timezone(&Utc)))
+}
+
+/// What changed between `since` and `now`, localized and, while the app is
+/// locked, without bodies.
+pub async fn whats_new(
+    state: &AppState,
+    since: DateTime<Utc>,
+    now: DateTime<Utc>,
+) -> Result<WhatsNew, String> {
+    let mut whats_new = state
+        .digest
+        .whats_new(since, now)
+        .await
+        .map_err(|e| e.to_string())?;
+    let tz = state
+        .sqlite
+        .get_user_timezone()
+        .await
+        .map_err(|e| e.to_string())?;
+    for email in whats_new.emails_mut() {
+        localize_fields(email, crate::EMAIL_TIME_FIELDS, tz);
+        state.lock.redact(email);
+    }
+    Ok(whats_new)
+}